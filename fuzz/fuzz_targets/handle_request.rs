@@ -0,0 +1,34 @@
+#![no_main]
+
+//! Feed arbitrary JSON-RPC messages to the server. Tools only see a scratch directory:
+//! relative paths resolve into it and nothing outside it may be read or written.
+
+use json_mcp_server::json_tools::outputs::TempOutputs;
+use json_mcp_server::json_tools::{JsonToolsHandler, PathPolicy};
+use json_mcp_server::mcp::server::MCPServer;
+use libfuzzer_sys::fuzz_target;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+thread_local! {
+    static SERVER: (Runtime, MCPServer) = {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let scratch = std::env::temp_dir().join("json-mcp-server-fuzz");
+        std::fs::create_dir_all(&scratch).unwrap();
+        let handler = JsonToolsHandler::new()
+            .with_path_policy(PathPolicy::new(Some(scratch.clone()), vec![scratch.clone()]).unwrap())
+            .with_temp_outputs(TempOutputs::new(scratch.join("outputs"), Duration::from_secs(60)).unwrap());
+        let mut server = MCPServer::new(handler);
+        runtime.block_on(server.register_tools()).unwrap();
+        (runtime, server)
+    };
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    SERVER.with(|(runtime, server)| {
+        let _ = runtime.block_on(server.handle_request(input));
+    });
+});
@@ -0,0 +1,41 @@
+#![no_main]
+
+//! Arbitrary JSONPath queries through everything json-query does with one: the syntax
+//! check and its report, key resolution, the streaming parser and both dialects.
+
+use json_mcp_server::json_tools::jsonpath::normalized_path_to_pointer;
+use json_mcp_server::json_tools::resolve::{diagnose_empty, resolve_keys, KeyMatch};
+use json_mcp_server::json_tools::streaming::parse_streamable_path;
+use json_mcp_server::json_tools::syntax::{check_syntax, syntax_report};
+use json_mcp_server::json_tools::JsonPathDialect;
+use libfuzzer_sys::fuzz_target;
+use serde_json::{json, Value};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(query) = std::str::from_utf8(data) else {
+        return;
+    };
+    let document = json!({
+        "users": [{"name": "ann", "age": 31, "tags": ["a"]}, {"name": "bob", "age": null}],
+        "meta": {"count": 2, "it's": true, "": [1.5, -2, "x"]}
+    });
+
+    let _ = parse_streamable_path(query);
+    let _ = resolve_keys(&document, query, KeyMatch::Fuzzy);
+    let _ = diagnose_empty(&document, query);
+    let mut dialects = vec![JsonPathDialect::Rfc9535];
+    // json-query only hands queries that pass the syntax check to jsonpath-rust
+    match check_syntax(query) {
+        Ok(()) => dialects.push(JsonPathDialect::JsonpathRust),
+        Err(error) => {
+            let _: Value = syntax_report(query, &error);
+        }
+    }
+    for dialect in dialects {
+        if let Ok(found) = dialect.query_with_paths(&document, query) {
+            for (path, _) in found {
+                let _ = normalized_path_to_pointer(&path);
+            }
+        }
+    }
+});
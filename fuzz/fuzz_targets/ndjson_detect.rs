@@ -0,0 +1,25 @@
+#![no_main]
+
+//! Arbitrary bytes through the NDJSON detection of `pipe` and json-query's planner, and
+//! the NDJSON record stream the planner hands them to.
+
+use json_mcp_server::json_tools::cli::is_ndjson;
+use json_mcp_server::json_tools::planner::{Execution, QueryPlan};
+use json_mcp_server::json_tools::resolve::KeyMatch;
+use json_mcp_server::json_tools::storage;
+use json_mcp_server::json_tools::streaming::{stream_ndjson_query, stream_query, StreamStep};
+use libfuzzer_sys::fuzz_target;
+
+const PATH: &str = "mem://fuzz.json";
+
+fuzz_target!(|data: &[u8]| {
+    let _ = is_ndjson(data);
+    storage::write(PATH, data).unwrap();
+    let Ok(plan) = QueryPlan::new(Some(PATH), "$[*]", KeyMatch::Exact, Execution::Streaming) else {
+        return;
+    };
+    let steps = [StreamStep::Wildcard];
+    let _ = stream_ndjson_query(PATH, &steps, |_, _| {});
+    let _ = stream_query(PATH, &steps, |_, _| {});
+    let _ = plan.describe();
+});
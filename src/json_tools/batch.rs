@@ -0,0 +1,144 @@
+use crate::json_tools::handler::JsonToolsHandler;
+use crate::json_tools::patch::{parse_pointer, pointer_get};
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+pub fn create_batch_tool() -> Tool {
+    Tool {
+        name: "json-batch".to_string(),
+        description: "Run a sequence of JSON tool calls in one request and return each step's result. Later steps can use earlier outputs via {\"$from\": \"step_id\", \"path\": \"/json/pointer\"} in their arguments.".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "steps": {
+                    "type": "array",
+                    "description": "Tool invocations executed in order",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "id": {
+                                "type": "string",
+                                "description": "Name used to reference this step's output (default: its index)"
+                            },
+                            "tool": {
+                                "type": "string",
+                                "description": "Tool to call, e.g. 'json-query'"
+                            },
+                            "arguments": {
+                                "type": "object",
+                                "description": "Tool arguments. Any value of the form {\"$from\": \"<step id>\", \"path\": \"/pointer\"} is replaced by that step's output (parsed as JSON when possible)"
+                            }
+                        },
+                        "required": ["tool"]
+                    }
+                },
+                "stop_on_error": {
+                    "type": "boolean",
+                    "description": "Stop at the first failing step (default: true)"
+                }
+            },
+            "required": ["steps"]
+        })
+    }
+}
+
+/// Execute the steps of a json-batch call through `handler`.
+pub async fn run_batch(handler: &JsonToolsHandler, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+    let steps = args.get("steps")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| anyhow::anyhow!("steps is required. Usage example:\n{{\n  \"steps\": [\n    {{\"id\": \"user\", \"tool\": \"json-query\", \"arguments\": {{\"file_path\": \"./users.json\", \"query\": \"$.users[0].id\"}}}},\n    {{\"tool\": \"json-write\", \"arguments\": {{\"file_path\": \"./selected.json\", \"data\": {{\"$from\": \"user\", \"path\": \"/results\"}}}}}}\n  ]\n}}"))?;
+    let stop_on_error = args.get("stop_on_error").and_then(|v| v.as_bool()).unwrap_or(true);
+
+    let mut outputs: HashMap<String, Value> = HashMap::new();
+    let mut results = Vec::with_capacity(steps.len());
+    let mut failed = 0;
+
+    for (index, step) in steps.iter().enumerate() {
+        let id = step.get("id").and_then(|v| v.as_str()).map_or_else(|| index.to_string(), str::to_string);
+        let outcome = match run_step(handler, step, &outputs).await {
+            Ok(result) => result,
+            Err(e) => ToolResult::error(e.to_string()),
+        };
+        let ok = outcome.is_error != Some(true);
+        let text: String = outcome.content.iter().map(|c| c.text.as_str()).collect::<Vec<_>>().join("\n");
+
+        results.push(json!({
+            "step": index,
+            "id": id,
+            "tool": step.get("tool").cloned().unwrap_or(Value::Null),
+            "ok": ok,
+            "output": text,
+        }));
+        if ok {
+            outputs.insert(id, output_value(&text));
+        } else {
+            failed += 1;
+            if stop_on_error {
+                break;
+            }
+        }
+    }
+
+    let summary = format!(
+        "Batch ran {} of {} step(s), {} failed:\n\n{}",
+        results.len(), steps.len(), failed, serde_json::to_string_pretty(&results)?
+    );
+    Ok(if failed > 0 { ToolResult::error(summary) } else { ToolResult::success(summary) })
+}
+
+async fn run_step(handler: &JsonToolsHandler, step: &Value, outputs: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+    let tool = step.get("tool").and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Each step needs a 'tool' name"))?;
+    if tool == "json-batch" {
+        anyhow::bail!("json-batch steps cannot call json-batch");
+    }
+    let arguments = match step.get("arguments") {
+        None => HashMap::new(),
+        Some(Value::Object(arguments)) => arguments.iter()
+            .map(|(k, v)| Ok((k.clone(), resolve_references(v, outputs)?)))
+            .collect::<anyhow::Result<HashMap<_, _>>>()?,
+        Some(other) => anyhow::bail!("Step arguments must be an object, got: {}", other),
+    };
+
+    handler.call_tool(ToolCall {
+        name: tool.to_string(),
+        arguments,
+    }).await
+}
+
+/// Tool outputs are text, usually a heading followed by a JSON body. Use the JSON body
+/// when there is one so later steps can address into it.
+fn output_value(text: &str) -> Value {
+    if let Ok(value) = serde_json::from_str(text) {
+        return value;
+    }
+    text.split_once("\n\n")
+        .and_then(|(_, body)| serde_json::from_str(body).ok())
+        .unwrap_or_else(|| Value::String(text.to_string()))
+}
+
+fn resolve_references(value: &Value, outputs: &HashMap<String, Value>) -> anyhow::Result<Value> {
+    match value {
+        Value::Object(obj) => {
+            if let Some(source) = obj.get("$from").and_then(|v| v.as_str()) {
+                let output = outputs.get(source)
+                    .ok_or_else(|| anyhow::anyhow!("'$from' refers to step '{}', which has not run successfully", source))?;
+                let path = obj.get("path").and_then(|v| v.as_str()).unwrap_or("");
+                return pointer_get(output, &parse_pointer(path)?)
+                    .cloned()
+                    .map_err(|e| anyhow::anyhow!("Cannot resolve '{}' in the output of step '{}': {}", path, source, e));
+            }
+            obj.iter()
+                .map(|(k, v)| Ok((k.clone(), resolve_references(v, outputs)?)))
+                .collect::<anyhow::Result<Map<_, _>>>()
+                .map(Value::Object)
+        }
+        Value::Array(items) => items.iter()
+            .map(|item| resolve_references(item, outputs))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map(Value::Array),
+        _ => Ok(value.clone()),
+    }
+}
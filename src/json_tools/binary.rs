@@ -0,0 +1,326 @@
+use crate::json_tools::formats::{serialize_for_path, DocumentFormat};
+use crate::json_tools::integrity::sha256_hex;
+use crate::json_tools::jsonpath::{normalized_path_to_pointer, JsonPathDialect};
+use crate::json_tools::patch::{parse_pointer, pointer_get_mut};
+use crate::json_tools::storage;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use base64::alphabet;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig, STANDARD};
+use base64::engine::DecodePaddingMode;
+use base64::Engine;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+
+/// Decoders that accept input with or without `=` padding.
+const LENIENT: GeneralPurposeConfig = GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+const STANDARD_LENIENT: GeneralPurpose = GeneralPurpose::new(&alphabet::STANDARD, LENIENT);
+const URL_SAFE_LENIENT: GeneralPurpose = GeneralPurpose::new(&alphabet::URL_SAFE, LENIENT);
+
+/// Decode base64 text, standard or URL-safe, padded or not. A `data:` URL prefix is
+/// stripped and its media type returned alongside the bytes.
+pub fn decode_base64(text: &str) -> Result<(Vec<u8>, Option<String>), String> {
+    let (declared, payload) = match text.strip_prefix("data:").and_then(|rest| rest.split_once(',')) {
+        Some((header, payload)) => {
+            let Some(media_type) = header.strip_suffix(";base64") else {
+                return Err("data URL is not base64-encoded".to_string());
+            };
+            (Some(media_type.to_string()).filter(|t| !t.is_empty()), payload)
+        }
+        None => (None, text),
+    };
+    // Encoders commonly wrap lines (MIME uses 76 columns)
+    let compact: String = payload.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+    let bytes = STANDARD_LENIENT.decode(&compact)
+        .or_else(|e| URL_SAFE_LENIENT.decode(&compact).map_err(|_| e))
+        .map_err(|e| format!("invalid base64: {}", e))?;
+    Ok((bytes, declared))
+}
+
+/// Guess a media type from the leading bytes of decoded content.
+pub fn sniff_media_type(bytes: &[u8]) -> &'static str {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"\x1f\x8b", "application/gzip"),
+        (b"BM", "image/bmp"),
+        (b"\x00\x00\x01\x00", "image/x-icon"),
+        (b"OggS", "audio/ogg"),
+        (b"ID3", "audio/mpeg"),
+        (b"\x7fELF", "application/x-elf"),
+    ];
+    if let Some((_, media_type)) = SIGNATURES.iter().find(|(magic, _)| bytes.starts_with(magic)) {
+        return media_type;
+    }
+    if bytes.len() >= 12 && bytes.starts_with(b"RIFF") {
+        match &bytes[8..12] {
+            b"WEBP" => return "image/webp",
+            b"WAVE" => return "audio/wav",
+            _ => {}
+        }
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) if text.trim_start().starts_with(['{', '[']) && serde_json::from_str::<Value>(text).is_ok() => "application/json",
+        Ok(text) if text.trim_start().starts_with("<svg") => "image/svg+xml",
+        Ok(text) if text.trim_start().starts_with('<') => "text/xml",
+        Ok(_) => "text/plain",
+        Err(_) => "application/octet-stream",
+    }
+}
+
+/// What a base64 string decodes to, returned in place of the string itself.
+fn summarize(text: &str) -> Value {
+    match decode_base64(text) {
+        Ok((bytes, declared)) => {
+            let mut summary = json!({
+                "bytes": bytes.len(),
+                "media_type": sniff_media_type(&bytes),
+                "sha256": sha256_hex(&bytes),
+            });
+            if let Some(declared) = declared {
+                summary["declared_type"] = json!(declared);
+            }
+            json!({"base64": summary})
+        }
+        Err(e) => json!({"base64": {"error": e, "length": text.len()}}),
+    }
+}
+
+/// The `decode_base64_path` argument of json-read and json-query: a JSONPath, evaluated
+/// against each result, selecting base64 strings to describe instead of returning.
+pub struct Base64Summary {
+    path: String,
+}
+
+impl Base64Summary {
+    pub fn from_args(args: &HashMap<String, Value>) -> Option<Self> {
+        args.get("decode_base64_path")
+            .and_then(|v| v.as_str())
+            .map(|path| Self { path: path.to_string() })
+    }
+
+    /// Replace the strings the path selects in `result` with their decoded length, media
+    /// type and hash. Returns the number of strings replaced.
+    pub fn apply(&self, result: &mut Value) -> Result<usize, String> {
+        let pointers: Vec<String> = JsonPathDialect::default().query_with_paths(result, &self.path)?
+            .into_iter()
+            .filter(|(_, value)| value.is_string())
+            .filter_map(|(path, _)| normalized_path_to_pointer(&path))
+            .collect();
+        for pointer in &pointers {
+            if let Some(target) = result.pointer_mut(pointer) {
+                *target = summarize(target.as_str().unwrap_or_default());
+            }
+        }
+        Ok(pointers.len())
+    }
+
+    /// Apply to every element of a result array.
+    pub fn apply_all(&self, results: &mut Value) -> Result<usize, String> {
+        match results {
+            Value::Array(items) => items.iter_mut().map(|item| self.apply(item)).sum(),
+            other => self.apply(other),
+        }
+    }
+}
+
+/// JSON Schema for the `decode_base64_path` argument.
+pub fn decode_base64_schema() -> Value {
+    json!({
+        "type": "string",
+        "description": "JSONPath, evaluated against each result, selecting base64 strings (or data: URLs) to replace with {\"base64\": {bytes, media_type, sha256}} instead of returning the encoded data, e.g. '$.attachments[*].content'"
+    })
+}
+
+pub struct JsonBinary;
+
+impl Default for JsonBinary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonBinary {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn create_extract_tool() -> Tool {
+        Tool {
+            name: "json-base64-extract".to_string(),
+            description: "Decode a base64 string field (e.g. an embedded image or attachment) and write the raw bytes to a file, so binary payloads never pass through the model's context".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "JSON file containing the base64 field"
+                    },
+                    "pointer": {
+                        "type": "string",
+                        "description": "JSON Pointer (RFC 6901) to the base64 string, e.g. '/attachments/0/content'. Standard and URL-safe alphabets, missing padding and data: URLs are accepted"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "Binary file to write; must lie within the server's allowed roots"
+                    },
+                    "overwrite": {
+                        "type": "boolean",
+                        "description": "Replace output_path if it already exists (default: false)",
+                        "default": false
+                    }
+                },
+                "required": ["file_path", "pointer", "output_path"]
+            }),
+        }
+    }
+
+    fn create_embed_tool() -> Tool {
+        Tool {
+            name: "json-base64-embed".to_string(),
+            description: "Read a binary file and store it base64-encoded at a JSON Pointer in a JSON file (the reverse of json-base64-extract)".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "JSON file to write the field into"
+                    },
+                    "pointer": {
+                        "type": "string",
+                        "description": "JSON Pointer (RFC 6901) to the field to set; its parent must exist, e.g. '/attachments/0/content'"
+                    },
+                    "binary_path": {
+                        "type": "string",
+                        "description": "Binary file to encode; must lie within the server's allowed roots"
+                    },
+                    "data_url": {
+                        "type": "boolean",
+                        "description": "Store a data: URL with the detected media type instead of bare base64 (default: false)"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "Where to write the updated document (default: rewrite file_path in place)"
+                    }
+                },
+                "required": ["file_path", "pointer", "binary_path"]
+            }),
+        }
+    }
+
+    async fn handle_extract(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let (Some(file_path), Some(pointer), Some(output_path)) = (
+            args.get("file_path").and_then(|v| v.as_str()),
+            args.get("pointer").and_then(|v| v.as_str()),
+            args.get("output_path").and_then(|v| v.as_str()),
+        ) else {
+            anyhow::bail!(
+                "file_path, pointer and output_path are required. Usage example:\n{{\n  \"file_path\": \"./message.json\",\n  \"pointer\": \"/attachments/0/content\",\n  \"output_path\": \"./attachment.png\"\n}}"
+            );
+        };
+        let overwrite = args.get("overwrite").and_then(|v| v.as_bool()).unwrap_or(false);
+
+        let content = fs::read_to_string(file_path)
+            .map_err(|e| anyhow::anyhow!("Failed to read file '{}': {}", file_path, e))?;
+        let document: Value = match serde_json::from_str(&content) {
+            Ok(document) => document,
+            Err(e) => return Ok(ToolResult::error(format!("Invalid JSON in '{}': {}", file_path, e))),
+        };
+        let text = match document.pointer(pointer) {
+            Some(Value::String(text)) => text,
+            Some(other) => return Ok(ToolResult::error(format!(
+                "The value at '{}' in '{}' is not a string: {}", pointer, file_path, other
+            ))),
+            None => return Ok(ToolResult::error(format!("Cannot resolve pointer '{}' in '{}'", pointer, file_path))),
+        };
+        let (bytes, declared) = match decode_base64(text) {
+            Ok(decoded) => decoded,
+            Err(e) => return Ok(ToolResult::error(format!("At '{}' in '{}': {}", pointer, file_path, e))),
+        };
+        if overwrite {
+            storage::write(output_path, &bytes)?;
+        } else if let Err(e) = storage::write_new(output_path, &bytes) {
+            return Ok(ToolResult::error(e.to_string()));
+        }
+
+        let media_type = declared.unwrap_or_else(|| sniff_media_type(&bytes).to_string());
+        Ok(ToolResult::success(format!(
+            "Decoded {} bytes ({}) from '{}' in '{}' and wrote '{}' (sha256: {})",
+            bytes.len(), media_type, pointer, file_path, output_path, sha256_hex(&bytes)
+        )))
+    }
+
+    async fn handle_embed(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let (Some(file_path), Some(pointer), Some(binary_path)) = (
+            args.get("file_path").and_then(|v| v.as_str()),
+            args.get("pointer").and_then(|v| v.as_str()),
+            args.get("binary_path").and_then(|v| v.as_str()),
+        ) else {
+            anyhow::bail!(
+                "file_path, pointer and binary_path are required. Usage example:\n{{\n  \"file_path\": \"./message.json\",\n  \"pointer\": \"/attachments/0/content\",\n  \"binary_path\": \"./attachment.png\"\n}}"
+            );
+        };
+        let output_path = args.get("output_path").and_then(|v| v.as_str()).unwrap_or(file_path);
+        let data_url = args.get("data_url").and_then(|v| v.as_bool()).unwrap_or(false);
+
+        let bytes = fs::read(binary_path)
+            .map_err(|e| anyhow::anyhow!("Failed to read file '{}': {}", binary_path, e))?;
+        let content = fs::read_to_string(file_path)
+            .map_err(|e| anyhow::anyhow!("Failed to read file '{}': {}", file_path, e))?;
+        let mut document: Value = match serde_json::from_str(&content) {
+            Ok(document) => document,
+            Err(e) => return Ok(ToolResult::error(format!("Invalid JSON in '{}': {}", file_path, e))),
+        };
+
+        let media_type = sniff_media_type(&bytes);
+        let mut encoded = STANDARD.encode(&bytes);
+        if data_url {
+            encoded = format!("data:{};base64,{}", media_type, encoded);
+        }
+        let tokens = parse_pointer(pointer)?;
+        let Some((last, parent_tokens)) = tokens.split_last() else {
+            return Ok(ToolResult::error("pointer must name a field, not the whole document".to_string()));
+        };
+        let parent = match pointer_get_mut(&mut document, parent_tokens) {
+            Ok(parent) => parent,
+            Err(e) => return Ok(ToolResult::error(format!("Cannot resolve pointer '{}' in '{}': {}", pointer, file_path, e))),
+        };
+        match parent {
+            Value::Object(map) => {
+                map.insert(last.clone(), Value::String(encoded));
+            }
+            Value::Array(items) => match last.parse::<usize>().ok().and_then(|index| items.get_mut(index)) {
+                Some(item) => *item = Value::String(encoded),
+                None => return Ok(ToolResult::error(format!("Array index '{}' is out of bounds in '{}'", last, pointer))),
+            },
+            _ => return Ok(ToolResult::error(format!("The parent of '{}' is not an object or array", pointer))),
+        }
+        storage::write(output_path, &serialize_for_path(output_path, &document, DocumentFormat::Json, true)?)?;
+
+        Ok(ToolResult::success(format!(
+            "Encoded {} bytes ({}) from '{}' at '{}' in '{}' (sha256: {})",
+            bytes.len(), media_type, binary_path, pointer, output_path, sha256_hex(&bytes)
+        )))
+    }
+}
+
+#[async_trait]
+impl ToolHandler for JsonBinary {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_extract_tool(), Self::create_embed_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-base64-extract" => self.handle_extract(&tool_call.arguments).await,
+            "json-base64-embed" => self.handle_embed(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
@@ -0,0 +1,230 @@
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use jsonpath_rust::JsonPath;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+
+pub struct JsonCheck;
+
+impl Default for JsonCheck {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonCheck {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn create_check_tool() -> Tool {
+        Tool {
+            name: "json-check".to_string(),
+            description: "Evaluate simple assertions against a JSON file (path exists, value equals, contains, array length) and return a boolean with an explanation. Useful as a guard before writes.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "Path to the JSON file to check"
+                    },
+                    "checks": {
+                        "type": "array",
+                        "description": "Assertions to evaluate. All must pass for the overall result to be true",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "path": {
+                                    "type": "string",
+                                    "description": "JSONPath selecting the value(s) to check (e.g., '$.users')"
+                                },
+                                "op": {
+                                    "type": "string",
+                                    "enum": ["exists", "not_exists", "equals", "not_equals", "contains", "length_gte", "length_lte", "length_eq", "type"],
+                                    "description": "Assertion to apply to every selected value"
+                                },
+                                "value": {
+                                    "description": "Expected value for equals/contains, a number for length_*, or a type name ('object', 'array', 'string', 'number', 'boolean', 'null') for type"
+                                }
+                            },
+                            "required": ["path", "op"]
+                        }
+                    }
+                },
+                "required": ["file_path", "checks"]
+            })
+        }
+    }
+
+    async fn handle_check(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let file_path = args.get("file_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "file_path is required. Usage example:\n{{\n  \"file_path\": \"./data.json\",\n  \"checks\": [{{\"path\": \"$.users\", \"op\": \"exists\"}}]\n}}"
+            ))?;
+
+        let checks = args.get("checks")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| anyhow::anyhow!(
+                "checks is required. Usage example:\n{{\n  \"file_path\": \"./data.json\",\n  \"checks\": [{{\"path\": \"$.users\", \"op\": \"length_gte\", \"value\": 1}}]\n}}\nOperators: exists, not_exists, equals, not_equals, contains, length_gte, length_lte, length_eq, type"
+            ))?;
+
+        let content = fs::read_to_string(file_path)
+            .map_err(|e| anyhow::anyhow!("Failed to read file '{}': {}", file_path, e))?;
+        let json_value: Value = serde_json::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("Failed to parse JSON: {}", e))?;
+
+        let mut reports = Vec::new();
+        for check in checks {
+            let path = check.get("path").and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow::anyhow!("Each check requires a 'path' string, got: {}", check))?;
+            let op = check.get("op").and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow::anyhow!("Each check requires an 'op' string, got: {}", check))?;
+            let expected = check.get("value");
+
+            let matches: Vec<Value> = match json_value.query(path) {
+                Ok(values) => values.into_iter().cloned().collect(),
+                Err(e) => return Ok(ToolResult::error(format!("JSONPath query error in '{}': {}", path, e))),
+            };
+
+            let (passed, explanation) = match evaluate_check(op, &matches, expected) {
+                Ok(outcome) => outcome,
+                Err(e) => return Ok(ToolResult::error(format!("Invalid check on '{}': {}", path, e))),
+            };
+            reports.push(json!({
+                "path": path,
+                "op": op,
+                "passed": passed,
+                "explanation": explanation,
+            }));
+        }
+
+        let passed = reports.iter().all(|r| r["passed"] == json!(true));
+        let output = json!({
+            "passed": passed,
+            "checks": reports,
+        });
+
+        Ok(ToolResult::success(format!(
+            "Checks against '{}' {}:\n\n{}",
+            file_path,
+            if passed { "passed" } else { "failed" },
+            serde_json::to_string_pretty(&output)?
+        )))
+    }
+}
+
+const CHECK_OPERATORS: &[&str] = &[
+    "exists", "not_exists", "equals", "not_equals", "contains", "length_gte", "length_lte", "length_eq", "type",
+];
+
+/// Evaluate a single assertion against the values selected by its path. Every selected
+/// value must satisfy the assertion, and value assertions fail when nothing was selected.
+fn evaluate_check(op: &str, matches: &[Value], expected: Option<&Value>) -> anyhow::Result<(bool, String)> {
+    if !CHECK_OPERATORS.contains(&op) {
+        anyhow::bail!("unknown operator '{}'. Use one of: {}", op, CHECK_OPERATORS.join(", "));
+    }
+    let require_expected = || expected.ok_or_else(|| anyhow::anyhow!("operator '{}' requires a 'value'", op));
+
+    match op {
+        "exists" => Ok((
+            !matches.is_empty(),
+            format!("path selected {} value(s)", matches.len()),
+        )),
+        "not_exists" => Ok((
+            matches.is_empty(),
+            format!("path selected {} value(s)", matches.len()),
+        )),
+        _ if matches.is_empty() => Ok((false, "path did not select any value".to_string())),
+        "equals" | "not_equals" => {
+            let expected = require_expected()?;
+            let passed = if op == "equals" {
+                matches.iter().all(|v| v == expected)
+            } else {
+                matches.iter().all(|v| v != expected)
+            };
+            Ok((passed, describe_values(matches)))
+        }
+        "contains" => {
+            let expected = require_expected()?;
+            let passed = matches.iter().all(|v| match (v, expected) {
+                (Value::String(s), Value::String(needle)) => s.contains(needle.as_str()),
+                (Value::Array(items), _) => items.contains(expected),
+                (Value::Object(obj), Value::String(key)) => obj.contains_key(key),
+                _ => false,
+            });
+            Ok((passed, describe_values(matches)))
+        }
+        "length_gte" | "length_lte" | "length_eq" => {
+            let bound = require_expected()?.as_u64()
+                .ok_or_else(|| anyhow::anyhow!("operator '{}' requires a non-negative integer 'value'", op))? as usize;
+            let lengths: Vec<Option<usize>> = matches.iter().map(value_length).collect();
+            let passed = lengths.iter().all(|len| match (len, op) {
+                (Some(len), "length_gte") => *len >= bound,
+                (Some(len), "length_lte") => *len <= bound,
+                (Some(len), _) => *len == bound,
+                (None, _) => false,
+            });
+            let described: Vec<String> = lengths.iter()
+                .map(|len| len.map(|l| l.to_string()).unwrap_or_else(|| "n/a".to_string()))
+                .collect();
+            Ok((passed, format!("length(s): {}", described.join(", "))))
+        }
+        "type" => {
+            let expected = require_expected()?.as_str()
+                .ok_or_else(|| anyhow::anyhow!("operator 'type' requires a type name as 'value'"))?;
+            let types: Vec<&str> = matches.iter().map(type_name).collect();
+            Ok((types.iter().all(|t| *t == expected), format!("type(s): {}", types.join(", "))))
+        }
+        _ => unreachable!("operator validated above"),
+    }
+}
+
+fn value_length(value: &Value) -> Option<usize> {
+    match value {
+        Value::Array(arr) => Some(arr.len()),
+        Value::Object(obj) => Some(obj.len()),
+        Value::String(s) => Some(s.chars().count()),
+        _ => None,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::String(_) => "string",
+        Value::Number(_) => "number",
+        Value::Bool(_) => "boolean",
+        Value::Null => "null",
+    }
+}
+
+fn describe_values(matches: &[Value]) -> String {
+    let shown: Vec<String> = matches.iter().take(3).map(|v| {
+        let text = v.to_string();
+        if text.chars().count() > 80 {
+            format!("{}...", text.chars().take(80).collect::<String>())
+        } else {
+            text
+        }
+    }).collect();
+    let more = if matches.len() > 3 { format!(" (and {} more)", matches.len() - 3) } else { String::new() };
+    format!("actual: {}{}", shown.join(", "), more)
+}
+
+#[async_trait]
+impl ToolHandler for JsonCheck {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_check_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-check" => self.handle_check(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
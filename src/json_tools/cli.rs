@@ -0,0 +1,158 @@
+use crate::json_tools::handler::JsonToolsHandler;
+use crate::json_tools::storage;
+use crate::mcp::protocol::{ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Short option names accepted for common path arguments.
+const ALIASES: &[(&str, &str)] = &[("file", "file_path"), ("files", "file_paths"), ("output", "output_path")];
+
+/// Turn `--name value` pairs into tool arguments using the tool's input schema. Names may
+/// use dashes (`--output-path`); string properties take the value verbatim, other types
+/// parse it as JSON; a repeated option or a non-JSON value appends to an array property; an
+/// option without a value is `true`. `--json '{...}'` supplies several arguments at once.
+pub fn parse_tool_arguments(schema: &Value, args: &[String]) -> Result<HashMap<String, Value>, String> {
+    let empty = Map::new();
+    let properties = schema.get("properties").and_then(|p| p.as_object()).unwrap_or(&empty);
+    let mut arguments: HashMap<String, Value> = HashMap::new();
+    let mut i = 0;
+    while i < args.len() {
+        let Some(option) = args[i].strip_prefix("--") else {
+            return Err(format!("Expected an option such as --file_path, found '{}'", args[i]));
+        };
+        let (option, inline) = match option.split_once('=') {
+            Some((option, value)) => (option, Some(value.to_string())),
+            None => (option, None),
+        };
+        let value = match inline {
+            Some(value) => Some(value),
+            None if args.get(i + 1).is_some_and(|next| !next.starts_with("--")) => {
+                i += 1;
+                Some(args[i].clone())
+            }
+            None => None,
+        };
+        i += 1;
+
+        if option == "json" {
+            let object: Map<String, Value> = serde_json::from_str(value.as_deref().unwrap_or_default())
+                .map_err(|e| format!("--json must be a JSON object: {}", e))?;
+            arguments.extend(object);
+            continue;
+        }
+        let mut name = option.replace('-', "_");
+        if !properties.contains_key(&name) {
+            if let Some((_, target)) = ALIASES.iter().find(|(alias, target)| *alias == name && properties.contains_key(*target)) {
+                name = target.to_string();
+            } else {
+                let mut known: Vec<&str> = properties.keys().map(String::as_str).collect();
+                known.sort_unstable();
+                return Err(format!("Unknown option --{}. Options: --{}", option, known.join(", --")));
+            }
+        }
+
+        let kind = properties[&name].get("type").and_then(|t| t.as_str());
+        let parsed = match (value, kind) {
+            (None, _) => Value::Bool(true),
+            (Some(value), Some("string")) => Value::String(value),
+            (Some(value), _) => serde_json::from_str(&value).unwrap_or(Value::String(value)),
+        };
+        match (arguments.get_mut(&name), kind) {
+            (Some(Value::Array(items)), Some("array")) => match parsed {
+                Value::Array(more) => items.extend(more),
+                one => items.push(one),
+            },
+            (_, Some("array")) if !parsed.is_array() => {
+                arguments.insert(name, Value::Array(vec![parsed]));
+            }
+            _ => {
+                arguments.insert(name, parsed);
+            }
+        }
+    }
+    Ok(arguments)
+}
+
+/// The definition and arguments of `tool` called with command-line `args`.
+async fn resolve(handler: &JsonToolsHandler, tool: &str, args: &[String]) -> anyhow::Result<(Value, HashMap<String, Value>)> {
+    let tools = handler.get_tools().await?;
+    let Some(definition) = tools.iter().find(|t| t.name == tool) else {
+        let mut names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
+        names.sort_unstable();
+        anyhow::bail!("Unknown tool '{}'. Available tools: {}", tool, names.join(", "));
+    };
+    let arguments = parse_tool_arguments(&definition.input_schema, args).map_err(anyhow::Error::msg)?;
+    Ok((definition.input_schema.clone(), arguments))
+}
+
+/// Run one tool from the command line, as `json-mcp-server run <tool> --option value ...`.
+pub async fn run_tool(handler: &JsonToolsHandler, tool: &str, args: &[String]) -> anyhow::Result<ToolResult> {
+    let (_, arguments) = resolve(handler, tool, args).await?;
+    handler.call_tool(ToolCall { name: tool.to_string(), arguments }).await
+}
+
+/// What `pipe` writes: the document for stdout, and a note for stderr.
+#[derive(Debug)]
+pub struct Piped {
+    pub output: String,
+    pub note: Option<String>,
+}
+
+/// Run one tool over a document read from stdin, as `json-mcp-server pipe <tool> ...`.
+/// The input becomes the tool's `file_path` (`mem://stdin.json`, or `mem://stdin.ndjson`
+/// when it is one JSON value per line) unless `--file` names another. The output is the
+/// input as the tool rewrote it, else the JSON the tool returned (the `results` of a
+/// result envelope), else the input unchanged, with the tool's message as the note.
+pub async fn pipe_tool(handler: &JsonToolsHandler, tool: &str, args: &[String], input: &[u8]) -> anyhow::Result<Piped> {
+    let (schema, mut arguments) = resolve(handler, tool, args).await?;
+    let reads_file = schema.pointer("/properties/file_path").is_some();
+    let stdin_path = match (reads_file, arguments.contains_key("file_path")) {
+        (true, false) => {
+            let path = if is_ndjson(input) { "mem://stdin.ndjson" } else { "mem://stdin.json" };
+            storage::write(path, input)?;
+            arguments.insert("file_path".to_string(), Value::String(path.to_string()));
+            Some(path)
+        }
+        (false, _) if !input.iter().all(u8::is_ascii_whitespace) => {
+            anyhow::bail!("{} does not read a file_path, so it cannot take a document on stdin", tool)
+        }
+        _ => None,
+    };
+
+    let result = handler.call_tool(ToolCall { name: tool.to_string(), arguments }).await?;
+    let text: Vec<&str> = result.content.iter().map(|c| c.text.as_str()).collect();
+    let text = text.join("\n");
+    if result.is_error == Some(true) {
+        anyhow::bail!(text);
+    }
+    if let Some(path) = stdin_path {
+        let rewritten = storage::read(path)?;
+        if rewritten != input {
+            return Ok(Piped { output: String::from_utf8_lossy(&rewritten).into_owned(), note: None });
+        }
+    }
+
+    let Some((summary, payload)) = text.split_once("\n\n") else {
+        let output = String::from_utf8_lossy(input).into_owned();
+        return Ok(Piped { output, note: Some(text) });
+    };
+    match serde_json::from_str::<Value>(payload) {
+        Ok(Value::Object(mut envelope)) if envelope.contains_key("metadata") && envelope.contains_key("results") => {
+            let truncated = envelope["metadata"].get("truncated").and_then(|t| t.as_bool()).unwrap_or(false);
+            let note = truncated.then(|| format!("{} (truncated; pass --limit for more)", summary));
+            let results = envelope.remove("results").unwrap_or_default();
+            Ok(Piped { output: serde_json::to_string_pretty(&results)?, note })
+        }
+        _ => Ok(Piped { output: payload.to_string(), note: None }),
+    }
+}
+
+/// Whether `input` is NDJSON: not a single JSON value, but one per non-blank line.
+pub fn is_ndjson(input: &[u8]) -> bool {
+    if serde_json::from_slice::<Value>(input).is_ok() {
+        return false;
+    }
+    let mut lines = input.split(|&b| b == b'\n').filter(|line| !line.iter().all(u8::is_ascii_whitespace)).peekable();
+    lines.peek().is_some() && lines.all(|line| serde_json::from_slice::<Value>(line).is_ok())
+}
@@ -0,0 +1,749 @@
+use crate::json_tools::formats::{inline_document, read_document, DocumentFormat};
+use crate::json_tools::patch::{parse_pointer, pointer_get};
+use crate::json_tools::storage;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Type names a Rust declaration must not take, since generated code uses them unqualified.
+const RESERVED_TYPE_NAMES: &[&str] = &["String", "Vec", "Option", "Box", "Result", "Self"];
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false", "fn",
+    "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "static",
+    "struct", "trait", "true", "type", "unsafe", "use", "where", "while", "abstract", "become", "box", "do",
+    "final", "macro", "override", "priv", "try", "typeof", "unsized", "virtual", "yield",
+];
+
+/// Infer a JSON Schema that every sample satisfies: object keys present in all samples are
+/// required, integers mixed with other numbers become `number`, and mixed kinds `anyOf`.
+pub fn infer_schema(samples: &[&Value]) -> Value {
+    let mut scalars = BTreeSet::new();
+    let mut nullable = false;
+    let mut objects = Vec::new();
+    let mut items = Vec::new();
+    let mut arrays = 0;
+    for sample in samples {
+        match sample {
+            Value::Null => nullable = true,
+            Value::Bool(_) => {
+                scalars.insert("boolean");
+            }
+            Value::Number(n) if n.is_i64() || n.is_u64() => {
+                scalars.insert("integer");
+            }
+            Value::Number(_) => {
+                scalars.insert("number");
+            }
+            Value::String(_) => {
+                scalars.insert("string");
+            }
+            Value::Array(elements) => {
+                arrays += 1;
+                items.extend(elements);
+            }
+            Value::Object(object) => objects.push(object),
+        }
+    }
+    if scalars.contains("number") {
+        scalars.remove("integer");
+    }
+
+    let mut variants: Vec<Value> = scalars.into_iter().map(|kind| json!({"type": kind})).collect();
+    if !objects.is_empty() {
+        let mut keys: Vec<&String> = Vec::new();
+        for object in &objects {
+            keys.extend(object.keys().filter(|key| !keys.contains(key)).collect::<Vec<_>>());
+        }
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        for key in keys {
+            let values: Vec<&Value> = objects.iter().filter_map(|object| object.get(key)).collect();
+            if values.len() == objects.len() {
+                required.push(json!(key));
+            }
+            properties.insert(key.clone(), infer_schema(&values));
+        }
+        variants.push(json!({"type": "object", "properties": properties, "required": required}));
+    }
+    if arrays > 0 {
+        let items = if items.is_empty() { json!({}) } else { infer_schema(&items) };
+        variants.push(json!({"type": "array", "items": items}));
+    }
+
+    match (variants.len(), nullable) {
+        (0, true) => json!({"type": "null"}),
+        (0, false) => json!({}),
+        (1, false) => variants.remove(0),
+        (1, true) => {
+            let mut schema = variants.remove(0);
+            schema["type"] = json!([schema["type"], "null"]);
+            schema
+        }
+        (_, _) => {
+            if nullable {
+                variants.push(json!({"type": "null"}));
+            }
+            json!({"anyOf": variants})
+        }
+    }
+}
+
+/// A type in the generated code.
+#[derive(Clone, Debug, PartialEq)]
+enum Ty {
+    Any,
+    Null,
+    Bool,
+    Integer,
+    Number,
+    String,
+    Literal(String),
+    Array(Box<Ty>),
+    Map(Box<Ty>),
+    Nullable(Box<Ty>),
+    Union(Vec<Ty>),
+    Named(String),
+}
+
+struct Field {
+    json_name: String,
+    ty: Ty,
+    required: bool,
+    description: Option<String>,
+}
+
+enum Decl {
+    Struct { name: String, description: Option<String>, fields: Vec<Field>, extra: Option<Ty> },
+    Enum { name: String, description: Option<String>, values: Vec<String> },
+    Alias { name: String, description: Option<String>, ty: Ty },
+}
+
+impl Decl {
+    fn name(&self) -> &str {
+        match self {
+            Decl::Struct { name, .. } | Decl::Enum { name, .. } | Decl::Alias { name, .. } => name,
+        }
+    }
+}
+
+/// `user_address`, `user-address` and `userAddress` as `UserAddress`.
+fn pascal_case(text: &str) -> String {
+    let mut result = String::new();
+    for word in words(text) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            result.extend(first.to_uppercase());
+            result.push_str(&chars.as_str().to_lowercase());
+        }
+    }
+    result
+}
+
+fn snake_case(text: &str) -> String {
+    words(text).iter().map(|word| word.to_lowercase()).collect::<Vec<_>>().join("_")
+}
+
+/// Split on anything that is not a letter or digit, and where lowercase turns to uppercase.
+fn words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+    for c in text.chars() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            previous_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && previous_lower && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        previous_lower = c.is_lowercase() || c.is_ascii_digit();
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// A name for the items of an array named `name`: `users` gives `User`.
+fn singular(name: &str) -> String {
+    if let Some(stem) = name.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else if name.ends_with('s') && !name.ends_with("ss") && name.len() > 1 {
+        name[..name.len() - 1].to_string()
+    } else {
+        format!("{}Item", name)
+    }
+}
+
+fn description(schema: &Value) -> Option<String> {
+    schema.get("description").and_then(|d| d.as_str()).map(str::to_string)
+}
+
+fn wrap_nullable(ty: Ty, nullable: bool) -> Ty {
+    match ty {
+        Ty::Any | Ty::Null | Ty::Nullable(_) => ty,
+        ty if nullable => Ty::Nullable(Box::new(ty)),
+        ty => ty,
+    }
+}
+
+/// Whether `ty` is the declaration named `name` itself, possibly nullable.
+fn declares(ty: &Ty, name: &str) -> bool {
+    match ty {
+        Ty::Named(declared) => declared == name,
+        Ty::Nullable(inner) => declares(inner, name),
+        _ => false,
+    }
+}
+
+/// Builds declarations from a schema, naming nested object types after their properties.
+struct Generator<'a> {
+    root: &'a Value,
+    decls: Vec<Decl>,
+    taken: HashSet<String>,
+    /// Declared names of `$ref` targets, by pointer.
+    refs: HashMap<String, String>,
+}
+
+impl<'a> Generator<'a> {
+    fn new(root: &'a Value) -> Self {
+        Self {
+            root,
+            decls: Vec::new(),
+            taken: RESERVED_TYPE_NAMES.iter().map(|name| name.to_string()).collect(),
+            refs: HashMap::new(),
+        }
+    }
+
+    fn unique_name(&mut self, hint: &str) -> String {
+        let mut base = pascal_case(hint);
+        if base.is_empty() || base.starts_with(|c: char| c.is_ascii_digit()) {
+            base = format!("Type{}", base);
+        }
+        let mut name = base.clone();
+        let mut n = 2;
+        while self.taken.contains(&name) {
+            name = format!("{}{}", base, n);
+            n += 1;
+        }
+        self.taken.insert(name.clone());
+        name
+    }
+
+    fn resolve(&self, reference: &str) -> Result<&'a Value, String> {
+        let tokens = reference.strip_prefix('#')
+            .and_then(|pointer| parse_pointer(pointer).ok())
+            .ok_or_else(|| format!("cannot resolve '{}'; only references within the schema are supported", reference))?;
+        pointer_get(self.root, &tokens).map_err(|_| format!("'{}' does not exist in the schema", reference))
+    }
+
+    /// Declare the root schema under `name`.
+    fn declare_root(&mut self, schema: &Value, name: &str) -> Result<(), String> {
+        let name = self.unique_name(name);
+        self.refs.insert("#".to_string(), name.clone());
+        match self.ty(schema, &name, true)? {
+            ty if declares(&ty, &name) => {}
+            ty => {
+                let description = description(schema);
+                self.decls.insert(0, Decl::Alias { name, description, ty });
+            }
+        }
+        Ok(())
+    }
+
+    /// The type of `schema`, declaring a struct or enum named after `hint` if it needs one.
+    /// `named` means `hint` is already reserved for this schema.
+    fn ty(&mut self, schema: &Value, hint: &str, named: bool) -> Result<Ty, String> {
+        let Some(object) = schema.as_object() else {
+            return Ok(Ty::Any);
+        };
+        if let Some(reference) = object.get("$ref").and_then(|r| r.as_str()) {
+            if let Some(name) = self.refs.get(reference) {
+                return Ok(Ty::Named(name.clone()));
+            }
+            let target = self.resolve(reference)?;
+            let hint = target.get("title").and_then(|t| t.as_str())
+                .unwrap_or_else(|| reference.rsplit('/').next().unwrap_or(reference));
+            let name = self.unique_name(hint);
+            self.refs.insert(reference.to_string(), name.clone());
+            return match self.ty(target, &name, true)? {
+                ty if declares(&ty, &name) => Ok(ty),
+                ty => {
+                    self.decls.push(Decl::Alias { name: name.clone(), description: description(target), ty });
+                    Ok(Ty::Named(name))
+                }
+            };
+        }
+
+        if let Some(Value::String(constant)) = object.get("const") {
+            return Ok(Ty::Literal(constant.clone()));
+        }
+        if let Some(Value::Array(values)) = object.get("enum") {
+            let strings: Vec<String> = values.iter().filter_map(|v| v.as_str().map(str::to_string)).collect();
+            let nullable = values.iter().any(Value::is_null);
+            if !strings.is_empty() && strings.len() + usize::from(nullable) == values.len() {
+                let name = self.name_for(object, hint, named);
+                self.decls.push(Decl::Enum { name: name.clone(), description: description(schema), values: strings });
+                return Ok(wrap_nullable(Ty::Named(name), nullable));
+            }
+            return Ok(Ty::Any);
+        }
+        for keyword in ["anyOf", "oneOf"] {
+            if let Some(Value::Array(variants)) = object.get(keyword) {
+                let mut types = Vec::new();
+                let mut nullable = false;
+                for (i, variant) in variants.iter().enumerate() {
+                    match self.ty(variant, &format!("{}{}", hint, i + 1), false)? {
+                        Ty::Null => nullable = true,
+                        ty => types.push(ty),
+                    }
+                }
+                let ty = if types.len() == 1 { types.remove(0) } else { Ty::Union(types) };
+                return Ok(wrap_nullable(ty, nullable));
+            }
+        }
+        if let Some(Value::Array(parts)) = object.get("allOf") {
+            return self.all_of(schema, parts, hint, named);
+        }
+
+        let mut kinds: Vec<&str> = match object.get("type") {
+            Some(Value::String(kind)) => vec![kind.as_str()],
+            Some(Value::Array(kinds)) => kinds.iter().filter_map(|k| k.as_str()).collect(),
+            _ if object.contains_key("properties") => vec!["object"],
+            _ if object.contains_key("items") => vec!["array"],
+            _ => Vec::new(),
+        };
+        let nullable = kinds.contains(&"null") || object.get("nullable") == Some(&Value::Bool(true));
+        kinds.retain(|kind| *kind != "null");
+        if kinds.contains(&"number") {
+            kinds.retain(|kind| *kind != "integer");
+        }
+        let mut types = Vec::new();
+        for kind in &kinds {
+            types.push(match *kind {
+                "string" => Ty::String,
+                "integer" => Ty::Integer,
+                "number" => Ty::Number,
+                "boolean" => Ty::Bool,
+                "array" => {
+                    let item_hint = singular(hint);
+                    match object.get("items") {
+                        Some(items) => Ty::Array(Box::new(self.ty(items, &item_hint, false)?)),
+                        None => Ty::Array(Box::new(Ty::Any)),
+                    }
+                }
+                "object" => self.object(schema, hint, named)?,
+                _ => Ty::Any,
+            });
+        }
+        let ty = match types.len() {
+            0 if nullable && kinds.is_empty() && object.get("type").is_some() => return Ok(Ty::Null),
+            0 => Ty::Any,
+            1 => types.remove(0),
+            _ => Ty::Union(types),
+        };
+        Ok(wrap_nullable(ty, nullable))
+    }
+
+    fn name_for(&mut self, schema: &Map<String, Value>, hint: &str, named: bool) -> String {
+        if named {
+            return hint.to_string();
+        }
+        let hint = schema.get("title").and_then(|t| t.as_str()).unwrap_or(hint);
+        self.unique_name(hint)
+    }
+
+    /// Merge the parts of an `allOf` into one schema: their properties, required lists and
+    /// the first type, title and description found.
+    fn all_of(&mut self, schema: &Value, parts: &[Value], hint: &str, named: bool) -> Result<Ty, String> {
+        let mut merged = schema.as_object().cloned().unwrap_or_default();
+        merged.remove("allOf");
+        for part in parts {
+            let part = match part.get("$ref").and_then(|r| r.as_str()) {
+                Some(reference) => self.resolve(reference)?,
+                None => part,
+            };
+            for (key, value) in part.as_object().into_iter().flatten() {
+                match (key.as_str(), value) {
+                    ("properties", Value::Object(properties)) => {
+                        let target = merged.entry("properties").or_insert_with(|| json!({}));
+                        if let Some(target) = target.as_object_mut() {
+                            target.extend(properties.iter().map(|(k, v)| (k.clone(), v.clone())));
+                        }
+                    }
+                    ("required", Value::Array(required)) => {
+                        let target = merged.entry("required").or_insert_with(|| json!([]));
+                        if let Some(target) = target.as_array_mut() {
+                            target.extend(required.iter().cloned());
+                        }
+                    }
+                    ("type" | "title" | "description" | "additionalProperties", _) => {
+                        merged.entry(key.clone()).or_insert_with(|| value.clone());
+                    }
+                    _ => {}
+                }
+            }
+        }
+        self.ty(&Value::Object(merged), hint, named)
+    }
+
+    /// An object schema: a struct when it lists properties, else a map.
+    fn object(&mut self, schema: &Value, hint: &str, named: bool) -> Result<Ty, String> {
+        let empty = Map::new();
+        let properties = schema.get("properties").and_then(|p| p.as_object()).unwrap_or(&empty);
+        let extra = match schema.get("additionalProperties") {
+            Some(extra @ Value::Object(map)) if !map.is_empty() => Some(extra),
+            _ => None,
+        };
+        if properties.is_empty() {
+            return Ok(Ty::Map(Box::new(match extra {
+                Some(extra) => self.ty(extra, &singular(hint), false)?,
+                None => Ty::Any,
+            })));
+        }
+        let object = schema.as_object().expect("object schemas are objects");
+        let name = self.name_for(object, hint, named);
+        // Reserve the declaration's place so it precedes the types of its fields
+        let index = self.decls.len();
+        self.decls.push(Decl::Alias { name: name.clone(), description: None, ty: Ty::Any });
+        let required: HashSet<&str> = schema.get("required").and_then(|r| r.as_array()).into_iter().flatten()
+            .filter_map(|r| r.as_str())
+            .collect();
+        let mut fields = Vec::new();
+        for (json_name, property) in properties {
+            let ty = self.ty(property, json_name, false)?;
+            fields.push(Field { json_name: json_name.clone(), ty, required: required.contains(json_name.as_str()), description: description(property) });
+        }
+        let extra = match extra {
+            Some(extra) => Some(self.ty(extra, &format!("{}Value", name), false)?),
+            None => None,
+        };
+        self.decls[index] = Decl::Struct { name: name.clone(), description: description(schema), fields, extra };
+        Ok(Ty::Named(name))
+    }
+}
+
+fn direct(ty: &Ty) -> Option<&str> {
+    match ty {
+        Ty::Named(name) => Some(name),
+        Ty::Nullable(inner) => direct(inner),
+        _ => None,
+    }
+}
+
+/// The named types a declaration holds directly, not behind a `Vec` or map, which Rust
+/// must box if they lead back to it.
+fn direct_names(decl: &Decl) -> Vec<&str> {
+    match decl {
+        Decl::Struct { fields, .. } => fields.iter().filter_map(|field| direct(&field.ty)).collect(),
+        Decl::Alias { ty, .. } => direct(ty).into_iter().collect(),
+        Decl::Enum { .. } => Vec::new(),
+    }
+}
+
+fn reaches(decls: &[Decl], from: &str, target: &str, seen: &mut HashSet<String>) -> bool {
+    if from == target {
+        return true;
+    }
+    if !seen.insert(from.to_string()) {
+        return false;
+    }
+    decls.iter()
+        .filter(|decl| decl.name() == from)
+        .flat_map(direct_names)
+        .any(|next| reaches(decls, next, target, seen))
+}
+
+fn doc_lines(description: &Option<String>, indent: &str, out: &mut String) {
+    for line in description.iter().flat_map(|d| d.lines()).map(str::trim_end) {
+        match line {
+            "" => out.push_str(&format!("{}///\n", indent)),
+            line => out.push_str(&format!("{}/// {}\n", indent, line)),
+        }
+    }
+}
+
+fn rust_type(ty: &Ty, boxed: bool) -> String {
+    match ty {
+        Ty::Any | Ty::Union(_) => "serde_json::Value".to_string(),
+        Ty::Null => "()".to_string(),
+        Ty::Bool => "bool".to_string(),
+        Ty::Integer => "i64".to_string(),
+        Ty::Number => "f64".to_string(),
+        Ty::String | Ty::Literal(_) => "String".to_string(),
+        Ty::Array(item) => format!("Vec<{}>", rust_type(item, false)),
+        Ty::Map(value) => format!("HashMap<String, {}>", rust_type(value, false)),
+        Ty::Nullable(inner) => format!("Option<{}>", rust_type(inner, boxed)),
+        Ty::Named(name) if boxed => format!("Box<{}>", name),
+        Ty::Named(name) => name.clone(),
+    }
+}
+
+/// A Rust identifier for `text`, made unique among `used`.
+fn rust_identifier(text: &str, pascal: bool, used: &mut HashSet<String>) -> String {
+    let mut name = if pascal { pascal_case(text) } else { snake_case(text) };
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name = if pascal { format!("V{}", name) } else { format!("field_{}", name) };
+    }
+    let base = name.clone();
+    let mut n = 2;
+    while used.contains(&name) {
+        name = format!("{}{}", base, n);
+        n += 1;
+    }
+    used.insert(name.clone());
+    name
+}
+
+fn render_rust(decls: &[Decl], source: &str) -> String {
+    let mut body = String::new();
+    for decl in decls {
+        body.push('\n');
+        match decl {
+            Decl::Struct { name, description, fields, extra } => {
+                doc_lines(description, "", &mut body);
+                body.push_str(&format!("#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\npub struct {} {{\n", name));
+                let mut used = HashSet::new();
+                for field in fields {
+                    let mut identifier = rust_identifier(&field.json_name, false, &mut used);
+                    let mut attributes = Vec::new();
+                    if identifier != field.json_name {
+                        attributes.push(format!("rename = {}", json!(field.json_name)));
+                    }
+                    if RUST_KEYWORDS.contains(&identifier.as_str()) {
+                        identifier = format!("r#{}", identifier);
+                    }
+                    let boxed = direct(&field.ty).is_some_and(|target| reaches(decls, target, name, &mut HashSet::new()));
+                    let mut ty = rust_type(&field.ty, boxed);
+                    if !field.required {
+                        if !matches!(field.ty, Ty::Nullable(_) | Ty::Any) {
+                            ty = format!("Option<{}>", ty);
+                        }
+                        if ty.starts_with("Option<") {
+                            attributes.push("default, skip_serializing_if = \"Option::is_none\"".to_string());
+                        } else {
+                            attributes.push("default, skip_serializing_if = \"serde_json::Value::is_null\"".to_string());
+                        }
+                    }
+                    doc_lines(&field.description, "    ", &mut body);
+                    if !attributes.is_empty() {
+                        body.push_str(&format!("    #[serde({})]\n", attributes.join(", ")));
+                    }
+                    body.push_str(&format!("    pub {}: {},\n", identifier, ty));
+                }
+                if let Some(extra) = extra {
+                    let identifier = rust_identifier("extra", false, &mut used);
+                    body.push_str(&format!("    #[serde(flatten)]\n    pub {}: HashMap<String, {}>,\n", identifier, rust_type(extra, false)));
+                }
+                body.push_str("}\n");
+            }
+            Decl::Enum { name, description, values } => {
+                doc_lines(description, "", &mut body);
+                body.push_str(&format!("#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]\npub enum {} {{\n", name));
+                let mut used = HashSet::new();
+                for value in values {
+                    let variant = rust_identifier(value, true, &mut used);
+                    if &variant != value {
+                        body.push_str(&format!("    #[serde(rename = {})]\n", json!(value)));
+                    }
+                    body.push_str(&format!("    {},\n", variant));
+                }
+                body.push_str("}\n");
+            }
+            Decl::Alias { name, description, ty } => {
+                doc_lines(description, "", &mut body);
+                body.push_str(&format!("pub type {} = {};\n", name, rust_type(ty, false)));
+            }
+        }
+    }
+    let mut header = format!("// Generated by json-codegen from {}.\n\nuse serde::{{Deserialize, Serialize}};\n", source);
+    if body.contains("HashMap<") {
+        header.push_str("use std::collections::HashMap;\n");
+    }
+    header + body.as_str()
+}
+
+fn ts_doc(description: &Option<String>, indent: &str, out: &mut String) {
+    let Some(description) = description else {
+        return;
+    };
+    let lines: Vec<&str> = description.lines().map(str::trim_end).collect();
+    match lines.as_slice() {
+        [line] => out.push_str(&format!("{}/** {} */\n", indent, line)),
+        lines => {
+            out.push_str(&format!("{}/**\n", indent));
+            for line in lines {
+                out.push_str(&format!("{} *{}{}\n", indent, if line.is_empty() { "" } else { " " }, line));
+            }
+            out.push_str(&format!("{} */\n", indent));
+        }
+    }
+}
+
+fn ts_type(ty: &Ty) -> String {
+    match ty {
+        Ty::Any => "unknown".to_string(),
+        Ty::Null => "null".to_string(),
+        Ty::Bool => "boolean".to_string(),
+        Ty::Integer | Ty::Number => "number".to_string(),
+        Ty::String => "string".to_string(),
+        Ty::Literal(value) => json!(value).to_string(),
+        Ty::Array(item) => match item.as_ref() {
+            Ty::Nullable(_) | Ty::Union(_) => format!("({})[]", ts_type(item)),
+            item => format!("{}[]", ts_type(item)),
+        },
+        Ty::Map(value) => format!("Record<string, {}>", ts_type(value)),
+        Ty::Nullable(inner) => format!("{} | null", ts_type(inner)),
+        Ty::Union(types) => types.iter().map(ts_type).collect::<Vec<_>>().join(" | "),
+        Ty::Named(name) => name.clone(),
+    }
+}
+
+fn render_typescript(decls: &[Decl], source: &str) -> String {
+    let mut out = format!("// Generated by json-codegen from {}.\n", source);
+    for decl in decls {
+        out.push('\n');
+        match decl {
+            Decl::Struct { name, description, fields, extra } => {
+                ts_doc(description, "", &mut out);
+                out.push_str(&format!("export interface {} {{\n", name));
+                for field in fields {
+                    let identifier = field.json_name.chars().enumerate()
+                        .all(|(i, c)| c == '_' || c == '$' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit()));
+                    let key = if identifier && !field.json_name.is_empty() { field.json_name.clone() } else { json!(field.json_name).to_string() };
+                    ts_doc(&field.description, "  ", &mut out);
+                    out.push_str(&format!("  {}{}: {};\n", key, if field.required { "" } else { "?" }, ts_type(&field.ty)));
+                }
+                if extra.is_some() {
+                    out.push_str("  [key: string]: unknown;\n");
+                }
+                out.push_str("}\n");
+            }
+            Decl::Enum { name, description, values } => {
+                ts_doc(description, "", &mut out);
+                let literals: Vec<String> = values.iter().map(|value| json!(value).to_string()).collect();
+                out.push_str(&format!("export type {} = {};\n", name, literals.join(" | ")));
+            }
+            Decl::Alias { name, description, ty } => {
+                ts_doc(description, "", &mut out);
+                out.push_str(&format!("export type {} = {};\n", name, ts_type(ty)));
+            }
+        }
+    }
+    out
+}
+
+pub struct JsonCodegen;
+
+impl Default for JsonCodegen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonCodegen {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn create_codegen_tool() -> Tool {
+        Tool {
+            name: "json-codegen".to_string(),
+            description: "Generate TypeScript interfaces or Rust serde structs from a JSON Schema, or from a sample JSON file whose schema is inferred. Nested objects become named types; local $refs are followed.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "target": {
+                        "type": "string",
+                        "enum": ["typescript", "rust"],
+                        "description": "Language to generate"
+                    },
+                    "schema_path": {
+                        "type": "string",
+                        "description": "JSON Schema file to generate from"
+                    },
+                    "schema": {
+                        "description": "JSON Schema given inline (instead of schema_path)"
+                    },
+                    "file_path": {
+                        "type": "string",
+                        "description": "Sample data to infer the types from (instead of a schema); keys missing from some records become optional"
+                    },
+                    "root_name": {
+                        "type": "string",
+                        "description": "Name of the top-level type (default: the schema's title, else 'Root')"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "Also write the generated code to this file, e.g. ./src/types.ts"
+                    }
+                },
+                "required": ["target"]
+            })
+        }
+    }
+
+    async fn handle_codegen(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let usage = "Usage example:\n{\n  \"target\": \"typescript\",\n  \"file_path\": \"./response.json\",\n  \"root_name\": \"User\",\n  \"output_path\": \"./src/user.ts\"\n}";
+        let target = args.get("target")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("target is required. {}", usage))?;
+        if target != "typescript" && target != "rust" {
+            return Ok(ToolResult::error(format!("Invalid target '{}'. Use 'typescript' or 'rust'", target)));
+        }
+        let sources = (args.get("schema_path").and_then(|v| v.as_str()), args.get("schema"), args.get("file_path").and_then(|v| v.as_str()));
+        let (source, schema) = match sources {
+            (Some(schema_path), None, None) => (format!("'{}'", schema_path), read_document(schema_path, DocumentFormat::from_path(schema_path))?),
+            (None, Some(schema), None) => ("an inline schema".to_string(), inline_document(schema)?),
+            (None, None, Some(file_path)) => {
+                let sample = read_document(file_path, DocumentFormat::from_path(file_path))?;
+                (format!("the data in '{}'", file_path), infer_schema(&[&sample]))
+            }
+            (None, None, None) => anyhow::bail!("schema_path, schema or file_path is required. {}", usage),
+            _ => return Ok(ToolResult::error("Provide one of schema_path, schema or file_path".to_string())),
+        };
+        let root_name = args.get("root_name").and_then(|v| v.as_str())
+            .or_else(|| schema.get("title").and_then(|t| t.as_str()))
+            .unwrap_or("Root");
+
+        let mut generator = Generator::new(&schema);
+        if let Err(message) = generator.declare_root(&schema, root_name) {
+            return Ok(ToolResult::error(format!("Cannot generate types from {}: {}", source, message)));
+        }
+        let code = match target {
+            "rust" => render_rust(&generator.decls, &source),
+            _ => render_typescript(&generator.decls, &source),
+        };
+
+        let mut note = String::new();
+        if let Some(output_path) = args.get("output_path").and_then(|v| v.as_str()) {
+            storage::write(output_path, code.as_bytes())?;
+            note = format!(" (written to '{}')", output_path);
+        }
+        let language = if target == "rust" { "Rust" } else { "TypeScript" };
+        Ok(ToolResult::success(format!(
+            "Generated {} {} type(s) from {}{}:\n\n{}", generator.decls.len(), language, source, note, code
+        )))
+    }
+}
+
+#[async_trait]
+impl ToolHandler for JsonCodegen {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_codegen_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-codegen" => self.handle_codegen(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
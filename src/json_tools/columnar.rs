@@ -0,0 +1,47 @@
+use serde_json::Value;
+use std::fs::File;
+use std::sync::Arc;
+
+use arrow_json::reader::{infer_json_schema_from_iterator, ReaderBuilder};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+
+const BATCH_SIZE: usize = 8192;
+
+/// Write an array of flat objects to a Parquet file, inferring the column types from the
+/// records. Returns the size of the written file in bytes.
+pub fn write_parquet(output_path: &str, records: &[Value]) -> anyhow::Result<u64> {
+    for (index, record) in records.iter().enumerate() {
+        let Some(obj) = record.as_object() else {
+            anyhow::bail!("Parquet output requires every result to be an object, result {} is {}", index, record);
+        };
+        if let Some((key, _)) = obj.iter().find(|(_, v)| v.is_object() || v.is_array()) {
+            anyhow::bail!("Parquet output requires flat objects, result {} has a nested value at '{}'", index, key);
+        }
+    }
+    if records.is_empty() {
+        anyhow::bail!("Parquet output requires at least one result to infer a schema from");
+    }
+
+    let schema = Arc::new(infer_json_schema_from_iterator(records.iter().map(|r| Ok(r.clone())))?);
+    let mut decoder = ReaderBuilder::new(schema.clone())
+        .with_batch_size(BATCH_SIZE)
+        .build_decoder()?;
+
+    let file = File::create(output_path)
+        .map_err(|e| anyhow::anyhow!("Failed to create file '{}': {}", output_path, e))?;
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(file, schema, Some(properties))?;
+    for chunk in records.chunks(BATCH_SIZE) {
+        decoder.serialize(chunk)?;
+        if let Some(batch) = decoder.flush()? {
+            writer.write(&batch)?;
+        }
+    }
+    writer.close()?;
+
+    Ok(std::fs::metadata(output_path)?.len())
+}
@@ -0,0 +1,722 @@
+use crate::json_tools::formats::{inline_document, read_document, serialize_for_path, DocumentFormat};
+use crate::json_tools::patch::apply_json_patch;
+use crate::json_tools::storage;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// How loosely two documents are compared.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Equality {
+    /// Numbers differing by at most this much are equal.
+    pub tolerance: f64,
+    /// Arrays are equal when they hold the same elements in any order.
+    pub ignore_array_order: bool,
+}
+
+/// Where and how two documents first differ.
+#[derive(Debug, Clone)]
+pub struct Difference {
+    pub tokens: Vec<String>,
+    pub reason: String,
+    pub left: Option<Value>,
+    pub right: Option<Value>,
+}
+
+impl Difference {
+    fn new(tokens: &[String], reason: impl Into<String>, left: Option<&Value>, right: Option<&Value>) -> Self {
+        Self { tokens: tokens.to_vec(), reason: reason.into(), left: left.cloned(), right: right.cloned() }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "path": json_path(&self.tokens),
+            "pointer": pointer(&self.tokens),
+            "reason": self.reason,
+            "left": self.left,
+            "right": self.right,
+        })
+    }
+}
+
+impl Equality {
+    /// The first difference between `left` and `right`, in left document order, or `None`
+    /// when they are equal. Key order never matters.
+    pub fn first_difference(&self, left: &Value, right: &Value) -> Option<Difference> {
+        self.compare(left, right, &mut Vec::new())
+    }
+
+    pub fn equal(&self, left: &Value, right: &Value) -> bool {
+        self.first_difference(left, right).is_none()
+    }
+
+    fn compare(&self, left: &Value, right: &Value, tokens: &mut Vec<String>) -> Option<Difference> {
+        match (left, right) {
+            (Value::Object(l), Value::Object(r)) => {
+                for (key, value) in l {
+                    tokens.push(key.clone());
+                    let difference = match r.get(key) {
+                        Some(other) => self.compare(value, other, tokens),
+                        None => Some(Difference::new(tokens, "missing on the right", Some(value), None)),
+                    };
+                    tokens.pop();
+                    if difference.is_some() {
+                        return difference;
+                    }
+                }
+                r.iter().find(|(key, _)| !l.contains_key(*key)).map(|(key, value)| {
+                    tokens.push(key.clone());
+                    let difference = Difference::new(tokens, "missing on the left", None, Some(value));
+                    tokens.pop();
+                    difference
+                })
+            }
+            (Value::Array(l), Value::Array(r)) if self.ignore_array_order => {
+                if l.len() != r.len() {
+                    return Some(Difference::new(tokens, format!("array lengths differ ({} vs {})", l.len(), r.len()), Some(left), Some(right)));
+                }
+                // Pair each left element with an equal, still unused right element
+                let mut used = vec![false; r.len()];
+                for (index, item) in l.iter().enumerate() {
+                    match (0..r.len()).find(|&j| !used[j] && self.equal(item, &r[j])) {
+                        Some(j) => used[j] = true,
+                        None => {
+                            tokens.push(index.to_string());
+                            let difference = Difference::new(tokens, "no equal element on the right", Some(item), None);
+                            tokens.pop();
+                            return Some(difference);
+                        }
+                    }
+                }
+                None
+            }
+            (Value::Array(l), Value::Array(r)) => {
+                for (index, (item, other)) in l.iter().zip(r).enumerate() {
+                    tokens.push(index.to_string());
+                    let difference = self.compare(item, other, tokens);
+                    tokens.pop();
+                    if difference.is_some() {
+                        return difference;
+                    }
+                }
+                (l.len() != r.len()).then(|| {
+                    let index = l.len().min(r.len());
+                    tokens.push(index.to_string());
+                    let difference = Difference::new(
+                        tokens,
+                        format!("array lengths differ ({} vs {})", l.len(), r.len()),
+                        l.get(index), r.get(index),
+                    );
+                    tokens.pop();
+                    difference
+                })
+            }
+            (Value::Number(l), Value::Number(r)) => {
+                let equal = match (l.as_i64(), r.as_i64(), l.as_u64(), r.as_u64()) {
+                    (Some(a), Some(b), _, _) if self.tolerance == 0.0 => a == b,
+                    (_, _, Some(a), Some(b)) if self.tolerance == 0.0 => a == b,
+                    _ => match (l.as_f64(), r.as_f64()) {
+                        (Some(a), Some(b)) => (a - b).abs() <= self.tolerance,
+                        _ => false,
+                    },
+                };
+                (!equal).then(|| Difference::new(tokens, "values differ", Some(left), Some(right)))
+            }
+            _ if std::mem::discriminant(left) != std::mem::discriminant(right) => {
+                Some(Difference::new(tokens, format!("types differ ({} vs {})", type_name(left), type_name(right)), Some(left), Some(right)))
+            }
+            _ => (left != right).then(|| Difference::new(tokens, "values differ", Some(left), Some(right))),
+        }
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+pub fn pointer(tokens: &[String]) -> String {
+    tokens.iter().map(|t| format!("/{}", t.replace('~', "~0").replace('/', "~1"))).collect()
+}
+
+/// A normalized JSONPath, with array indexes bare and keys quoted.
+pub fn json_path(tokens: &[String]) -> String {
+    let mut path = String::from("$");
+    for token in tokens {
+        if !token.is_empty() && token.chars().all(|c| c.is_ascii_digit()) {
+            path.push_str(&format!("[{}]", token));
+        } else {
+            path.push_str(&format!("['{}']", token.replace('\\', "\\\\").replace('\'', "\\'")));
+        }
+    }
+    path
+}
+
+const COMPARE_USAGE: &str = "{\n  \"left_file\": \"./expected.json\",\n  \"right_file\": \"./actual.json\"\n}";
+
+/// The document a side of a comparison names: `<side>_file`, or inline `<side>_content`.
+pub fn side_document(args: &HashMap<String, Value>, side: &str, usage: &str) -> anyhow::Result<Result<(String, Value), String>> {
+    let file = args.get(&format!("{}_file", side)).and_then(|v| v.as_str());
+    Ok(match (file, args.get(&format!("{}_content", side))) {
+        (Some(path), None) => Ok((format!("'{}'", path), read_document(path, DocumentFormat::from_path(path))?)),
+        (None, Some(content)) => Ok((format!("inline {} content", side), inline_document(content)?)),
+        (Some(_), Some(_)) => Err(format!("Provide either {0}_file or {0}_content, not both", side)),
+        (None, None) => return Err(anyhow::anyhow!(
+            "{}_file (or {}_content) is required. Usage example:\n{}", side, side, usage
+        )),
+    })
+}
+
+/// Where ours and theirs changed the base in different ways; `None` means removed or never added.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub tokens: Vec<String>,
+    pub base: Option<Value>,
+    pub ours: Option<Value>,
+    pub theirs: Option<Value>,
+}
+
+impl Conflict {
+    fn reason(&self) -> &'static str {
+        match (&self.base, &self.ours, &self.theirs) {
+            (None, _, _) => "both added different values",
+            (Some(_), None, _) => "ours removed it, theirs changed it",
+            (Some(_), _, None) => "theirs removed it, ours changed it",
+            _ => "both changed it differently",
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        let mut entry = Map::new();
+        entry.insert("path".to_string(), json!(json_path(&self.tokens)));
+        entry.insert("pointer".to_string(), json!(pointer(&self.tokens)));
+        entry.insert("reason".to_string(), json!(self.reason()));
+        for (side, value) in [("base", &self.base), ("ours", &self.ours), ("theirs", &self.theirs)] {
+            if let Some(value) = value {
+                entry.insert(side.to_string(), value.clone());
+            }
+        }
+        Value::Object(entry)
+    }
+}
+
+/// What a conflicting location holds in the merged document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Ours,
+    Theirs,
+    /// An object holding all three versions under git-style marker keys.
+    Markers,
+}
+
+/// Three-way merge of `ours` and `theirs`, both edited from `base`. Objects merge key by key,
+/// arrays element by element when no side changed their length, and appends to the same array
+/// are combined; any other location both sides changed differently is a conflict.
+pub fn merge3(base: &Value, ours: &Value, theirs: &Value, resolution: Resolution) -> (Option<Value>, Vec<Conflict>) {
+    let mut conflicts = Vec::new();
+    let merged = merge_value(Some(base), Some(ours), Some(theirs), resolution, &mut Vec::new(), &mut conflicts);
+    (merged, conflicts)
+}
+
+fn same(left: Option<&Value>, right: Option<&Value>) -> bool {
+    match (left, right) {
+        (Some(left), Some(right)) => Equality::default().equal(left, right),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+fn merge_value(
+    base: Option<&Value>,
+    ours: Option<&Value>,
+    theirs: Option<&Value>,
+    resolution: Resolution,
+    tokens: &mut Vec<String>,
+    conflicts: &mut Vec<Conflict>,
+) -> Option<Value> {
+    if same(ours, theirs) || same(base, theirs) {
+        return ours.cloned();
+    }
+    if same(base, ours) {
+        return theirs.cloned();
+    }
+    match (base, ours, theirs) {
+        (None | Some(Value::Object(_)), Some(Value::Object(o)), Some(Value::Object(t))) => {
+            let empty = Map::new();
+            let b = base.and_then(|v| v.as_object()).unwrap_or(&empty);
+            let mut merged = Map::new();
+            for key in o.keys().chain(t.keys().filter(|key| !o.contains_key(*key))) {
+                tokens.push(key.clone());
+                if let Some(value) = merge_value(b.get(key), o.get(key), t.get(key), resolution, tokens, conflicts) {
+                    merged.insert(key.clone(), value);
+                }
+                tokens.pop();
+            }
+            Some(Value::Object(merged))
+        }
+        (Some(Value::Array(b)), Some(Value::Array(o)), Some(Value::Array(t))) if o.len() == b.len() && t.len() == b.len() => {
+            let mut merged = Vec::with_capacity(b.len());
+            for index in 0..b.len() {
+                tokens.push(index.to_string());
+                // An element cannot be removed without changing the length, so it is always there
+                merged.extend(merge_value(Some(&b[index]), Some(&o[index]), Some(&t[index]), resolution, tokens, conflicts));
+                tokens.pop();
+            }
+            Some(Value::Array(merged))
+        }
+        (Some(Value::Array(b)), Some(Value::Array(o)), Some(Value::Array(t))) if o.starts_with(b) && t.starts_with(b) => {
+            let mut merged = o.clone();
+            for item in &t[b.len()..] {
+                if !o[b.len()..].iter().any(|added| Equality::default().equal(added, item)) {
+                    merged.push(item.clone());
+                }
+            }
+            Some(Value::Array(merged))
+        }
+        _ => {
+            conflicts.push(Conflict { tokens: tokens.clone(), base: base.cloned(), ours: ours.cloned(), theirs: theirs.cloned() });
+            match resolution {
+                Resolution::Ours => ours.cloned(),
+                Resolution::Theirs => theirs.cloned(),
+                Resolution::Markers => Some(json!({
+                    "<<<<<<< ours": ours,
+                    "||||||| base": base,
+                    ">>>>>>> theirs": theirs,
+                })),
+            }
+        }
+    }
+}
+
+/// Arrays whose differing middles need more LCS cells than this are compared by position.
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+/// An RFC 6902 patch that turns `left` into `right`. Changed elements are patched in place
+/// rather than replaced; with `detect_moves`, a value removed from one key or array position
+/// and added unchanged at another key or position of the same container becomes a `move`.
+pub fn diff(left: &Value, right: &Value, detect_moves: bool) -> Vec<Value> {
+    let mut operations = Vec::new();
+    diff_value(left, right, &mut Vec::new(), detect_moves, &mut operations);
+    operations
+}
+
+fn diff_value(left: &Value, right: &Value, tokens: &mut Vec<String>, detect_moves: bool, operations: &mut Vec<Value>) {
+    match (left, right) {
+        _ if Equality::default().equal(left, right) => {}
+        (Value::Object(l), Value::Object(r)) => diff_object(l, r, tokens, detect_moves, operations),
+        (Value::Array(l), Value::Array(r)) => diff_array(l, r, tokens, detect_moves, operations),
+        _ => operations.push(json!({"op": "replace", "path": pointer(tokens), "value": right})),
+    }
+}
+
+fn diff_object(left: &Map<String, Value>, right: &Map<String, Value>, tokens: &mut Vec<String>, detect_moves: bool, operations: &mut Vec<Value>) {
+    let child = |tokens: &[String], key: &str| {
+        let mut child = tokens.to_vec();
+        child.push(key.to_string());
+        pointer(&child)
+    };
+    let mut added: Vec<(&String, bool)> = right.keys().filter(|key| !left.contains_key(*key)).map(|key| (key, false)).collect();
+    for (key, value) in left.iter().filter(|(key, _)| !right.contains_key(*key)) {
+        let renamed = added.iter_mut()
+            .find(|(new, used)| detect_moves && !*used && Equality::default().equal(value, &right[*new]));
+        match renamed {
+            Some((new, used)) => {
+                *used = true;
+                operations.push(json!({"op": "move", "from": child(tokens, key), "path": child(tokens, new)}));
+            }
+            None => operations.push(json!({"op": "remove", "path": child(tokens, key)})),
+        }
+    }
+    for (key, value) in left {
+        if let Some(other) = right.get(key) {
+            tokens.push(key.clone());
+            diff_value(value, other, tokens, detect_moves, operations);
+            tokens.pop();
+        }
+    }
+    for (key, _) in added.into_iter().filter(|(_, used)| !used) {
+        operations.push(json!({"op": "add", "path": child(tokens, key), "value": right[key]}));
+    }
+}
+
+/// How an array's elements line up: `(Some(left), Some(right))` for equal elements, and
+/// `(Some(left), None)` / `(None, Some(right))` for removed and added ones.
+fn align(left: &[Value], right: &[Value]) -> Vec<(Option<usize>, Option<usize>)> {
+    let equal = |a: &Value, b: &Value| Equality::default().equal(a, b);
+    let prefix = left.iter().zip(right).take_while(|(a, b)| equal(a, b)).count();
+    let suffix = left[prefix..].iter().rev().zip(right[prefix..].iter().rev()).take_while(|(a, b)| equal(a, b)).count();
+    let (l, r) = (&left[prefix..left.len() - suffix], &right[prefix..right.len() - suffix]);
+
+    let mut alignment: Vec<_> = (0..prefix).map(|i| (Some(i), Some(i))).collect();
+    if l.len().saturating_mul(r.len()) > MAX_LCS_CELLS {
+        alignment.extend((0..l.len()).map(|i| (Some(prefix + i), None)));
+        alignment.extend((0..r.len()).map(|j| (None, Some(prefix + j))));
+    } else {
+        // Longest common subsequence: lengths[i][j] covers l[i..] and r[j..]
+        let width = r.len() + 1;
+        let mut lengths = vec![0u32; (l.len() + 1) * width];
+        for i in (0..l.len()).rev() {
+            for j in (0..r.len()).rev() {
+                lengths[i * width + j] = if equal(&l[i], &r[j]) {
+                    lengths[(i + 1) * width + j + 1] + 1
+                } else {
+                    lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < l.len() || j < r.len() {
+            if i < l.len() && j < r.len() && equal(&l[i], &r[j]) {
+                alignment.push((Some(prefix + i), Some(prefix + j)));
+                i += 1;
+                j += 1;
+            } else if j == r.len() || (i < l.len() && lengths[(i + 1) * width + j] >= lengths[i * width + j + 1]) {
+                alignment.push((Some(prefix + i), None));
+                i += 1;
+            } else {
+                alignment.push((None, Some(prefix + j)));
+                j += 1;
+            }
+        }
+    }
+    alignment.extend((0..suffix).map(|k| (Some(left.len() - suffix + k), Some(right.len() - suffix + k))));
+    alignment
+}
+
+fn diff_array(left: &[Value], right: &[Value], tokens: &mut Vec<String>, detect_moves: bool, operations: &mut Vec<Value>) {
+    // Which left element ends up at each right position; None for added elements
+    let mut source: Vec<Option<usize>> = vec![None; right.len()];
+    let mut used = vec![false; left.len()];
+    let mut gaps: Vec<(Vec<usize>, Vec<usize>)> = vec![(Vec::new(), Vec::new())];
+    for pair in align(left, right) {
+        match pair {
+            (Some(i), Some(j)) => {
+                source[j] = Some(i);
+                used[i] = true;
+                gaps.push((Vec::new(), Vec::new()));
+            }
+            (Some(i), None) => gaps.last_mut().expect("there is always a gap").0.push(i),
+            (None, Some(j)) => gaps.last_mut().expect("there is always a gap").1.push(j),
+            (None, None) => {}
+        }
+    }
+    if detect_moves {
+        for j in gaps.iter().flat_map(|(_, added)| added) {
+            let moved = gaps.iter().flat_map(|(removed, _)| removed)
+                .find(|&&i| !used[i] && Equality::default().equal(&left[i], &right[*j]));
+            if let Some(&i) = moved {
+                source[*j] = Some(i);
+                used[i] = true;
+            }
+        }
+    }
+    // Elements removed and added at the same place were changed; patch them in place
+    for (removed, added) in &gaps {
+        let removed = removed.iter().filter(|&&i| !used[i]);
+        let added = added.iter().filter(|&&j| source[j].is_none());
+        for (&i, &j) in removed.zip(added).collect::<Vec<_>>() {
+            source[j] = Some(i);
+            used[i] = true;
+        }
+    }
+
+    let position = |tokens: &mut Vec<String>, index: usize| {
+        tokens.push(index.to_string());
+        let path = pointer(tokens);
+        tokens.pop();
+        path
+    };
+    let mut current: Vec<Option<usize>> = (0..left.len()).map(Some).collect();
+    for i in (0..left.len()).rev().filter(|&i| !used[i]) {
+        operations.push(json!({"op": "remove", "path": position(tokens, i)}));
+        current.remove(i);
+    }
+    for (j, item) in right.iter().enumerate() {
+        match source[j] {
+            None => {
+                operations.push(json!({"op": "add", "path": position(tokens, j), "value": item}));
+                current.insert(j, None);
+            }
+            Some(i) => {
+                // Everything before j is already in place, so the element is at j or after it
+                let at = current.iter().position(|&c| c == Some(i)).expect("every kept element is present");
+                if at != j {
+                    operations.push(json!({"op": "move", "from": position(tokens, at), "path": position(tokens, j)}));
+                    let moved = current.remove(at);
+                    current.insert(j, moved);
+                }
+                tokens.push(j.to_string());
+                diff_value(&left[i], item, tokens, detect_moves, operations);
+                tokens.pop();
+            }
+        }
+    }
+}
+
+fn write_atomically(path: &str, document: &Value) -> anyhow::Result<Result<(), String>> {
+    let format = DocumentFormat::from_path(path);
+    let content = match serialize_for_path(path, document, format, true) {
+        Ok(content) => content,
+        Err(e) => return Ok(Err(format!("Cannot write '{}' as {}: {}", path, format.name(), e))),
+    };
+    storage::write(path, &content)?;
+    Ok(Ok(()))
+}
+
+pub struct JsonCompare;
+
+impl Default for JsonCompare {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonCompare {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn create_equal_tool() -> Tool {
+        Tool {
+            name: "json-equal".to_string(),
+            description: "Decide whether two documents are semantically equal, ignoring key order and formatting (optionally float differences and array order). Returns equal: true/false and the first difference with its path and both values.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "left_file": {
+                        "type": "string",
+                        "description": "First document (JSON, TOML, MessagePack or CBOR), or use left_content"
+                    },
+                    "left_content": {
+                        "description": "First document inline, as JSON text or a JSON value"
+                    },
+                    "right_file": {
+                        "type": "string",
+                        "description": "Second document, or use right_content"
+                    },
+                    "right_content": {
+                        "description": "Second document inline, as JSON text or a JSON value"
+                    },
+                    "tolerance": {
+                        "type": "number",
+                        "minimum": 0,
+                        "description": "Numbers differing by at most this much are equal, e.g. 1e-9 (default: 0, exact; 1 and 1.0 are always equal)"
+                    },
+                    "ignore_array_order": {
+                        "type": "boolean",
+                        "description": "Treat arrays as equal when they hold the same elements in any order (default: false)",
+                        "default": false
+                    }
+                }
+            })
+        }
+    }
+
+    fn create_merge3_tool() -> Tool {
+        Tool {
+            name: "json-merge3".to_string(),
+            description: "Three-way merge of two edited copies of a document (ours, theirs) against their common base, e.g. when a file you edited was also changed by someone else. Non-overlapping changes merge cleanly; conflicts are listed with their paths and all three values, and can be written with conflict markers to a side file.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "base_file": {
+                        "type": "string",
+                        "description": "The common ancestor both copies were edited from"
+                    },
+                    "ours_file": {
+                        "type": "string",
+                        "description": "Our edited copy"
+                    },
+                    "theirs_file": {
+                        "type": "string",
+                        "description": "Their edited copy"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "Write the merged document here (may be ours_file); without it the merged document is returned"
+                    },
+                    "favor": {
+                        "type": "string",
+                        "enum": ["ours", "theirs"],
+                        "description": "Which side's value a conflicting location keeps in the merged document (default: ours)",
+                        "default": "ours"
+                    },
+                    "conflicts_path": {
+                        "type": "string",
+                        "description": "When there are conflicts, also write the merged document with each conflict replaced by {\"<<<<<<< ours\", \"||||||| base\", \">>>>>>> theirs\"} here, for review"
+                    }
+                },
+                "required": ["base_file", "ours_file", "theirs_file"]
+            })
+        }
+    }
+
+    async fn handle_merge3(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let usage = "Usage example:\n{\n  \"base_file\": \"./config.base.json\",\n  \"ours_file\": \"./config.json\",\n  \"theirs_file\": \"./config.remote.json\",\n  \"output_path\": \"./config.json\"\n}";
+        let mut documents = Vec::new();
+        for name in ["base_file", "ours_file", "theirs_file"] {
+            let path = args.get(name)
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow::anyhow!("{} is required. {}", name, usage))?;
+            documents.push(read_document(path, DocumentFormat::from_path(path))?);
+        }
+        let resolution = match args.get("favor").and_then(|v| v.as_str()).unwrap_or("ours") {
+            "ours" => Resolution::Ours,
+            "theirs" => Resolution::Theirs,
+            other => return Ok(ToolResult::error(format!("Invalid favor '{}'. Use 'ours' or 'theirs'", other))),
+        };
+
+        let (merged, conflicts) = merge3(&documents[0], &documents[1], &documents[2], resolution);
+        let merged = merged.unwrap_or(Value::Null);
+        let mut result = json!({
+            "clean": conflicts.is_empty(),
+            "conflicts": conflicts.iter().map(Conflict::to_json).collect::<Vec<_>>(),
+        });
+        let mut notes = Vec::new();
+        if let Some(output_path) = args.get("output_path").and_then(|v| v.as_str()) {
+            if let Err(message) = write_atomically(output_path, &merged)? {
+                return Ok(ToolResult::error(message));
+            }
+            notes.push(format!("wrote '{}'", output_path));
+        } else {
+            result["merged"] = merged;
+        }
+        if let (Some(conflicts_path), false) = (args.get("conflicts_path").and_then(|v| v.as_str()), conflicts.is_empty()) {
+            let (marked, _) = merge3(&documents[0], &documents[1], &documents[2], Resolution::Markers);
+            if let Err(message) = write_atomically(conflicts_path, &marked.unwrap_or(Value::Null))? {
+                return Ok(ToolResult::error(message));
+            }
+            notes.push(format!("wrote conflict markers to '{}'", conflicts_path));
+        }
+
+        let summary = if conflicts.is_empty() {
+            "Merged cleanly".to_string()
+        } else {
+            let favor = if resolution == Resolution::Theirs { "theirs" } else { "ours" };
+            format!("Merged with {} conflict(s), resolved with {} in the merged document", conflicts.len(), favor)
+        };
+        let notes = if notes.is_empty() { String::new() } else { format!("; {}", notes.join(", ")) };
+        Ok(ToolResult::success(format!(
+            "{}{}:\n\n{}", summary, notes, serde_json::to_string_pretty(&result)?
+        )))
+    }
+
+    fn create_diff_tool() -> Tool {
+        Tool {
+            name: "json-diff".to_string(),
+            description: "Compute a small RFC 6902 JSON Patch that turns one document into another, with array and key move detection, so changes can be reviewed and then applied (json-transaction action 'patch') instead of overwriting the file.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "left_file": {
+                        "type": "string",
+                        "description": "The document to patch (JSON, TOML, MessagePack or CBOR), or use left_content"
+                    },
+                    "left_content": {
+                        "description": "The document to patch, inline"
+                    },
+                    "right_file": {
+                        "type": "string",
+                        "description": "The document the patch should produce, or use right_content"
+                    },
+                    "right_content": {
+                        "description": "The document the patch should produce, inline"
+                    },
+                    "detect_moves": {
+                        "type": "boolean",
+                        "description": "Emit 'move' for array elements that changed position and values whose key was renamed, instead of remove + add (default: true)",
+                        "default": true
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "Also write the patch to this file"
+                    }
+                }
+            })
+        }
+    }
+
+    async fn handle_diff(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let (left_name, left) = match side_document(args, "left", COMPARE_USAGE)? {
+            Ok(side) => side,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+        let (right_name, right) = match side_document(args, "right", COMPARE_USAGE)? {
+            Ok(side) => side,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+        let detect_moves = args.get("detect_moves").and_then(|v| v.as_bool()).unwrap_or(true);
+
+        let patch = diff(&left, &right, detect_moves);
+        // Check the patch before handing it out
+        let mut patched = left;
+        apply_json_patch(&mut patched, &patch)?;
+        if let Some(difference) = Equality::default().first_difference(&patched, &right) {
+            anyhow::bail!("Generated patch does not reproduce the right document at {}", pointer(&difference.tokens));
+        }
+
+        let patch = Value::Array(patch);
+        let mut note = String::new();
+        if let Some(output_path) = args.get("output_path").and_then(|v| v.as_str()) {
+            if let Err(message) = write_atomically(output_path, &patch)? {
+                return Ok(ToolResult::error(message));
+            }
+            note = format!(" (written to '{}')", output_path);
+        }
+        let count = patch.as_array().map_or(0, Vec::len);
+        Ok(ToolResult::success(format!(
+            "{} operation(s) turn {} into {}{}:\n\n{}", count, left_name, right_name, note, serde_json::to_string_pretty(&patch)?
+        )))
+    }
+
+    async fn handle_equal(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let (left_name, left) = match side_document(args, "left", COMPARE_USAGE)? {
+            Ok(side) => side,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+        let (right_name, right) = match side_document(args, "right", COMPARE_USAGE)? {
+            Ok(side) => side,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+        let tolerance = args.get("tolerance").and_then(|v| v.as_f64()).unwrap_or(0.0);
+        if !(tolerance >= 0.0 && tolerance.is_finite()) {
+            return Ok(ToolResult::error(format!("tolerance must be a non-negative number, got {}", tolerance)));
+        }
+        let equality = Equality {
+            tolerance,
+            ignore_array_order: args.get("ignore_array_order").and_then(|v| v.as_bool()).unwrap_or(false),
+        };
+
+        let (summary, result) = match equality.first_difference(&left, &right) {
+            None => ("are semantically equal", json!({"equal": true})),
+            Some(difference) => ("differ", json!({"equal": false, "first_difference": difference.to_json()})),
+        };
+        Ok(ToolResult::success(format!(
+            "{} and {} {}:\n\n{}", left_name, right_name, summary, serde_json::to_string_pretty(&result)?
+        )))
+    }
+}
+
+#[async_trait]
+impl ToolHandler for JsonCompare {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_equal_tool(), Self::create_merge3_tool(), Self::create_diff_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-equal" => self.handle_equal(&tool_call.arguments).await,
+            "json-merge3" => self.handle_merge3(&tool_call.arguments).await,
+            "json-diff" => self.handle_diff(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
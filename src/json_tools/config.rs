@@ -0,0 +1,102 @@
+use crate::json_tools::encryption::EncryptionConfig;
+use crate::json_tools::formats::{read_document, DocumentFormat};
+use crate::json_tools::secrets::EnvironmentConfig;
+use crate::json_tools::storage::StorageConfig;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+/// Settings read from the `--config` file, JSON or TOML by extension.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub tools: ToolConfig,
+    pub encryption: EncryptionConfig,
+    pub storage: StorageConfig,
+    pub environment: EnvironmentConfig,
+}
+
+impl ServerConfig {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let file_path = path.to_string_lossy();
+        let document = read_document(&file_path, DocumentFormat::resolve(&file_path, None)?)?;
+        serde_json::from_value(document)
+            .map_err(|e| anyhow::anyhow!("Invalid config file '{}': {}", file_path, e))
+    }
+}
+
+/// Which tools the server offers, and under which names.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ToolConfig {
+    /// Tool names left out of tools/list and refused when called, including from json-batch
+    /// steps and background jobs.
+    pub disabled: Vec<String>,
+    /// Additional names for tools, alias to tool name. An alias keeps working when its
+    /// tool's own name is disabled, which renames the tool.
+    pub aliases: BTreeMap<String, String>,
+    /// Also offer every hyphenated tool under its underscored name (`json_read` for
+    /// `json-read`), for clients that reject or mangle hyphens in tool names.
+    pub underscore_aliases: bool,
+}
+
+impl ToolConfig {
+    /// Apply the configuration to the complete tool list: drop disabled tools and add a copy
+    /// of each aliased tool under its alias. Names that match no tool are errors, so a typo
+    /// cannot silently leave a tool enabled.
+    pub fn apply(&self, tools: Vec<Tool>) -> anyhow::Result<Vec<Tool>> {
+        let names: HashSet<&str> = tools.iter().map(|tool| tool.name.as_str()).collect();
+        for name in &self.disabled {
+            if !names.contains(name.as_str()) {
+                anyhow::bail!("Config disables unknown tool '{}'", name);
+            }
+        }
+        for (alias, target) in &self.aliases {
+            if !names.contains(target.as_str()) {
+                anyhow::bail!("Config alias '{}' names unknown tool '{}'", alias, target);
+            }
+            if names.contains(alias.as_str()) {
+                anyhow::bail!("Config alias '{}' is already the name of a tool", alias);
+            }
+        }
+
+        let mut aliased: Vec<Tool> = self.aliases.iter()
+            .filter_map(|(alias, target)| tools.iter().find(|tool| &tool.name == target).map(|tool| alias_of(tool, alias)))
+            .collect();
+        let mut tools: Vec<Tool> = tools.into_iter().filter(|tool| !self.disabled.contains(&tool.name)).collect();
+        if self.underscore_aliases {
+            // Configured aliases take precedence over generated ones
+            aliased.extend(tools.iter()
+                .filter(|tool| tool.name.contains('-'))
+                .map(|tool| alias_of(tool, &tool.name.replace('-', "_")))
+                .filter(|alias| !self.aliases.contains_key(&alias.name)));
+        }
+        tools.extend(aliased);
+        Ok(tools)
+    }
+
+    /// The call addressed to the tool its name stands for, or an error result if that name
+    /// is disabled.
+    pub fn route(&self, mut tool_call: ToolCall) -> Result<ToolCall, ToolResult> {
+        if let Some(target) = self.aliases.get(&tool_call.name) {
+            tool_call.name = target.clone();
+            return Ok(tool_call);
+        }
+        if self.underscore_aliases && tool_call.name.starts_with("json_") {
+            tool_call.name = tool_call.name.replace('_', "-");
+        }
+        if self.disabled.contains(&tool_call.name) {
+            return Err(ToolResult::error(format!("Tool '{}' is disabled on this server", tool_call.name)));
+        }
+        Ok(tool_call)
+    }
+}
+
+fn alias_of(tool: &Tool, alias: &str) -> Tool {
+    Tool {
+        name: alias.to_string(),
+        description: format!("{} (alias of {})", tool.description, tool.name),
+        input_schema: tool.input_schema.clone(),
+    }
+}
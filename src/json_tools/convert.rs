@@ -0,0 +1,569 @@
+use crate::json_tools::files::{RecordFormat, RecordWriter};
+use crate::json_tools::storage;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+pub struct JsonConvert;
+
+impl Default for JsonConvert {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonConvert {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn create_csv_to_json_tool() -> Tool {
+        Tool {
+            name: "csv-to-json".to_string(),
+            description: "Convert a CSV or TSV file into a JSON array or NDJSON file, with header detection, delimiter options and type inference for numbers, booleans and empty cells.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "Path to the CSV/TSV file to convert"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "Path of the JSON or NDJSON file to write"
+                    },
+                    "output_format": {
+                        "type": "string",
+                        "enum": ["json", "ndjson"],
+                        "description": "Output format (default: from the output extension, '.ndjson'/'.jsonl' means NDJSON)"
+                    },
+                    "delimiter": {
+                        "type": "string",
+                        "description": "Single-character field delimiter. Use '\\t' for tabs (default: tab for .tsv files, comma otherwise)"
+                    },
+                    "has_headers": {
+                        "description": "true/false, or 'auto' to detect whether the first row is a header (default: 'auto')",
+                        "default": "auto"
+                    },
+                    "infer_types": {
+                        "type": "boolean",
+                        "description": "Convert numeric and boolean cells to JSON numbers/booleans and empty cells to null (default: true)",
+                        "default": true
+                    }
+                },
+                "required": ["file_path", "output_path"]
+            })
+        }
+    }
+
+    fn create_xml_to_json_tool() -> Tool {
+        Tool {
+            name: "xml-to-json".to_string(),
+            description: "Convert an XML file into JSON so it can be queried with the other JSON tools. Attributes, text content and namespaces are mapped according to the options.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "Path to the XML file to convert"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "Optional path of the JSON file to write. When omitted the JSON is returned"
+                    },
+                    "attribute_prefix": {
+                        "type": "string",
+                        "description": "Prefix for keys holding XML attributes (default: '@')",
+                        "default": "@"
+                    },
+                    "text_key": {
+                        "type": "string",
+                        "description": "Key holding text content of elements that also have attributes or children (default: '#text')",
+                        "default": "#text"
+                    },
+                    "strip_namespaces": {
+                        "type": "boolean",
+                        "description": "Drop namespace prefixes (ns:name -> name) and xmlns declarations (default: false)",
+                        "default": false
+                    }
+                },
+                "required": ["file_path"]
+            })
+        }
+    }
+
+    fn create_json_to_xml_tool() -> Tool {
+        Tool {
+            name: "json-to-xml".to_string(),
+            description: "Convert a JSON file into XML, the reverse of xml-to-json. Keys with the attribute prefix become attributes and arrays become repeated elements.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "Path to the JSON file to convert"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "Optional path of the XML file to write. When omitted the XML is returned"
+                    },
+                    "root_name": {
+                        "type": "string",
+                        "description": "Root element name used when the JSON is not a single-key object (default: 'root')",
+                        "default": "root"
+                    },
+                    "attribute_prefix": {
+                        "type": "string",
+                        "description": "Prefix marking keys that become XML attributes (default: '@')",
+                        "default": "@"
+                    },
+                    "text_key": {
+                        "type": "string",
+                        "description": "Key whose value becomes element text content (default: '#text')",
+                        "default": "#text"
+                    },
+                    "pretty": {
+                        "type": "boolean",
+                        "description": "Indent the XML output (default: true)",
+                        "default": true
+                    }
+                },
+                "required": ["file_path"]
+            })
+        }
+    }
+
+    async fn handle_xml_to_json(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let file_path = args.get("file_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "file_path is required. Usage example:\n{{\n  \"file_path\": \"./feed.xml\",\n  \"output_path\": \"./feed.json\"\n}}"
+            ))?;
+
+        let mapping = XmlMapping::from_args(args);
+        let content = fs::read_to_string(file_path)
+            .map_err(|e| anyhow::anyhow!("Failed to read file '{}': {}", file_path, e))?;
+        let value = match xml_to_value(&content, &mapping) {
+            Ok(value) => value,
+            Err(e) => return Ok(ToolResult::error(format!("Failed to parse XML in '{}': {}", file_path, e))),
+        };
+
+        let output = serde_json::to_string_pretty(&value)?;
+        write_or_return(args, output, &format!("Converted XML '{}' to JSON", file_path))
+    }
+
+    async fn handle_json_to_xml(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let file_path = args.get("file_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "file_path is required. Usage example:\n{{\n  \"file_path\": \"./data.json\",\n  \"output_path\": \"./data.xml\"\n}}"
+            ))?;
+
+        let mapping = XmlMapping::from_args(args);
+        let root_name = args.get("root_name").and_then(|v| v.as_str()).unwrap_or("root");
+        let pretty = args.get("pretty").and_then(|v| v.as_bool()).unwrap_or(true);
+
+        let content = fs::read_to_string(file_path)
+            .map_err(|e| anyhow::anyhow!("Failed to read file '{}': {}", file_path, e))?;
+        let value: Value = serde_json::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("Failed to parse JSON: {}", e))?;
+
+        let output = value_to_xml(&value, &mapping, root_name, pretty);
+        write_or_return(args, output, &format!("Converted JSON '{}' to XML", file_path))
+    }
+
+    async fn handle_csv_to_json(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let usage = "Usage example:\n{\n  \"file_path\": \"./data.csv\",\n  \"output_path\": \"./data.json\"\n}";
+        let file_path = args.get("file_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("file_path is required. {}", usage))?;
+
+        let output_path = args.get("output_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("output_path is required. {}", usage))?;
+
+        let output_format = match args.get("output_format").and_then(|v| v.as_str()) {
+            Some(name) => RecordFormat::parse(name)?,
+            None => RecordFormat::from_extension(output_path),
+        };
+        let delimiter = match args.get("delimiter").and_then(|v| v.as_str()) {
+            Some("\\t") | Some("\t") | Some("tab") => b'\t',
+            Some(d) if d.len() == 1 => d.as_bytes()[0],
+            Some(d) => return Ok(ToolResult::error(format!(
+                "delimiter must be a single character, got '{}'", d
+            ))),
+            None => match Path::new(file_path).extension().and_then(|e| e.to_str()) {
+                Some(ext) if ext.eq_ignore_ascii_case("tsv") => b'\t',
+                _ => b',',
+            },
+        };
+
+        let infer_types = args.get("infer_types")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(false)
+            .flexible(true)
+            .from_path(file_path)
+            .map_err(|e| anyhow::anyhow!("Failed to open file '{}': {}", file_path, e))?;
+        let mut rows = reader.records();
+
+        let first = match rows.next() {
+            Some(row) => row.map_err(|e| anyhow::anyhow!("Failed to parse CSV: {}", e))?,
+            None => return Ok(ToolResult::error(format!("CSV file '{}' is empty", file_path))),
+        };
+
+        let has_headers = match args.get("has_headers") {
+            Some(Value::Bool(b)) => *b,
+            _ => looks_like_header(&first),
+        };
+
+        let headers: Vec<String> = if has_headers {
+            first.iter().enumerate()
+                .map(|(i, h)| if h.trim().is_empty() { format!("column_{}", i + 1) } else { h.trim().to_string() })
+                .collect()
+        } else {
+            (1..=first.len()).map(|i| format!("column_{}", i)).collect()
+        };
+
+        let mut writer = RecordWriter::create(Path::new(output_path), output_format)?;
+        let to_record = |row: &csv::StringRecord| -> Value {
+            let mut record = Map::new();
+            for (i, cell) in row.iter().enumerate() {
+                let key = headers.get(i).cloned().unwrap_or_else(|| format!("column_{}", i + 1));
+                let value = if infer_types { infer_cell(cell) } else { Value::String(cell.to_string()) };
+                record.insert(key, value);
+            }
+            Value::Object(record)
+        };
+
+        if !has_headers {
+            writer.write(&to_record(&first))?;
+        }
+        for row in rows {
+            let row = row.map_err(|e| anyhow::anyhow!("Failed to parse CSV: {}", e))?;
+            writer.write(&to_record(&row))?;
+        }
+        let count = writer.finish()?;
+
+        Ok(ToolResult::success(format!(
+            "Converted {} rows from '{}' to '{}' ({} columns{}):\n{}",
+            count,
+            file_path,
+            output_path,
+            headers.len(),
+            if has_headers { ", header detected" } else { ", no header" },
+            headers.join(", ")
+        )))
+    }
+}
+
+/// A first row is treated as a header when every cell is non-empty text that would not
+/// be inferred as a number or boolean.
+fn looks_like_header(row: &csv::StringRecord) -> bool {
+    !row.is_empty() && row.iter().all(|cell| matches!(infer_cell(cell), Value::String(ref s) if !s.is_empty()))
+}
+
+/// Infer a JSON value for a CSV cell. Numbers with leading zeros (e.g. zip codes and
+/// ids like `007`) are kept as strings.
+fn infer_cell(cell: &str) -> Value {
+    let trimmed = cell.trim();
+    if trimmed.is_empty() {
+        return Value::Null;
+    }
+    if trimmed.eq_ignore_ascii_case("true") {
+        return Value::Bool(true);
+    }
+    if trimmed.eq_ignore_ascii_case("false") {
+        return Value::Bool(false);
+    }
+
+    let digits = trimmed.trim_start_matches('-');
+    let leading_zero = digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.");
+    if !leading_zero {
+        if let Ok(i) = trimmed.parse::<i64>() {
+            return json!(i);
+        }
+        if let Ok(f) = trimmed.parse::<f64>() {
+            if f.is_finite() && trimmed.chars().any(|c| c.is_ascii_digit()) {
+                return json!(f);
+            }
+        }
+    }
+    Value::String(cell.to_string())
+}
+
+/// Write `output` to the optional `output_path` argument, or return it inline.
+fn write_or_return(args: &HashMap<String, Value>, output: String, summary: &str) -> anyhow::Result<ToolResult> {
+    match args.get("output_path").and_then(|v| v.as_str()) {
+        Some(output_path) => {
+            storage::create_parent_dirs(output_path)?;
+            storage::write(output_path, output.as_bytes())?;
+            Ok(ToolResult::success(format!("{} and wrote {} bytes to '{}'", summary, output.len(), output_path)))
+        }
+        None => Ok(ToolResult::success(format!("{}:\n\n{}", summary, output))),
+    }
+}
+
+/// How XML attributes, text and namespaces map onto JSON keys.
+struct XmlMapping {
+    attribute_prefix: String,
+    text_key: String,
+    strip_namespaces: bool,
+}
+
+impl XmlMapping {
+    fn from_args(args: &HashMap<String, Value>) -> Self {
+        Self {
+            attribute_prefix: args.get("attribute_prefix").and_then(|v| v.as_str()).unwrap_or("@").to_string(),
+            text_key: args.get("text_key").and_then(|v| v.as_str()).unwrap_or("#text").to_string(),
+            strip_namespaces: args.get("strip_namespaces").and_then(|v| v.as_bool()).unwrap_or(false),
+        }
+    }
+}
+
+/// An element being assembled while reading XML events.
+struct XmlFrame {
+    name: String,
+    fields: Map<String, Value>,
+    text: String,
+}
+
+impl XmlFrame {
+    fn open(element: &BytesStart, mapping: &XmlMapping) -> anyhow::Result<Self> {
+        let name = if mapping.strip_namespaces {
+            String::from_utf8_lossy(element.local_name().as_ref()).into_owned()
+        } else {
+            String::from_utf8_lossy(element.name().as_ref()).into_owned()
+        };
+
+        let mut fields = Map::new();
+        for attribute in element.attributes() {
+            let attribute = attribute?;
+            let key = attribute.key;
+            if mapping.strip_namespaces && (key.as_ref() == b"xmlns" || key.as_ref().starts_with(b"xmlns:")) {
+                continue;
+            }
+            let key_name = if mapping.strip_namespaces {
+                String::from_utf8_lossy(key.local_name().as_ref()).into_owned()
+            } else {
+                String::from_utf8_lossy(key.as_ref()).into_owned()
+            };
+            fields.insert(
+                format!("{}{}", mapping.attribute_prefix, key_name),
+                Value::String(attribute.unescape_value()?.into_owned()),
+            );
+        }
+        Ok(Self { name, fields, text: String::new() })
+    }
+
+    fn close(self, mapping: &XmlMapping) -> (String, Value) {
+        let text = self.text.trim();
+        let value = if self.fields.is_empty() {
+            if text.is_empty() { Value::Null } else { Value::String(text.to_string()) }
+        } else {
+            let mut fields = self.fields;
+            if !text.is_empty() {
+                fields.insert(mapping.text_key.clone(), Value::String(text.to_string()));
+            }
+            Value::Object(fields)
+        };
+        (self.name, value)
+    }
+}
+
+/// Insert a child element, turning repeated names into arrays.
+fn insert_child(fields: &mut Map<String, Value>, name: String, value: Value) {
+    match fields.get_mut(&name) {
+        Some(Value::Array(items)) => items.push(value),
+        Some(existing) => {
+            let first = existing.take();
+            *existing = Value::Array(vec![first, value]);
+        }
+        None => {
+            fields.insert(name, value);
+        }
+    }
+}
+
+fn xml_to_value(xml: &str, mapping: &XmlMapping) -> anyhow::Result<Value> {
+    let mut reader = Reader::from_str(xml);
+    let mut stack: Vec<XmlFrame> = Vec::new();
+    let mut root = Map::new();
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(element) => stack.push(XmlFrame::open(&element, mapping)?),
+            Event::Empty(element) => {
+                let (name, value) = XmlFrame::open(&element, mapping)?.close(mapping);
+                match stack.last_mut() {
+                    Some(parent) => insert_child(&mut parent.fields, name, value),
+                    None => insert_child(&mut root, name, value),
+                }
+            }
+            Event::End(_) => {
+                let frame = stack.pop().ok_or_else(|| anyhow::anyhow!("unexpected closing tag"))?;
+                let (name, value) = frame.close(mapping);
+                match stack.last_mut() {
+                    Some(parent) => insert_child(&mut parent.fields, name, value),
+                    None => insert_child(&mut root, name, value),
+                }
+            }
+            Event::Text(text) => {
+                if let Some(frame) = stack.last_mut() {
+                    frame.text.push_str(&text.xml_content()?);
+                }
+            }
+            Event::CData(data) => {
+                if let Some(frame) = stack.last_mut() {
+                    frame.text.push_str(&data.decode()?);
+                }
+            }
+            Event::GeneralRef(reference) => {
+                if let Some(frame) = stack.last_mut() {
+                    let name = reference.decode()?;
+                    match reference.resolve_char_ref()? {
+                        Some(ch) => frame.text.push(ch),
+                        None => match quick_xml::escape::resolve_predefined_entity(&name) {
+                            Some(resolved) => frame.text.push_str(resolved),
+                            None => frame.text.push_str(&format!("&{};", name)),
+                        },
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if !stack.is_empty() {
+        anyhow::bail!("unclosed element <{}>", stack.last().map(|f| f.name.as_str()).unwrap_or_default());
+    }
+    Ok(Value::Object(root))
+}
+
+fn value_to_xml(value: &Value, mapping: &XmlMapping, root_name: &str, pretty: bool) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
+    if pretty {
+        out.push('\n');
+    }
+    match value {
+        Value::Object(obj) if obj.len() == 1 && !obj.values().next().map(Value::is_array).unwrap_or(false) => {
+            let (name, inner) = obj.iter().next().expect("single-key object");
+            write_xml_element(&mut out, name, inner, mapping, 0, pretty);
+        }
+        _ => write_xml_element(&mut out, root_name, value, mapping, 0, pretty),
+    }
+    out
+}
+
+/// Make an arbitrary JSON key usable as an XML element or attribute name.
+fn xml_name(key: &str) -> String {
+    let mut name: String = key.chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':') { c } else { '_' })
+        .collect();
+    if !name.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        name.insert(0, '_');
+    }
+    name
+}
+
+fn write_xml_element(out: &mut String, key: &str, value: &Value, mapping: &XmlMapping, depth: usize, pretty: bool) {
+    if let Value::Array(items) = value {
+        for item in items {
+            write_xml_element(out, key, item, mapping, depth, pretty);
+        }
+        return;
+    }
+
+    let name = xml_name(key);
+    let indent = if pretty { "  ".repeat(depth) } else { String::new() };
+    let newline = if pretty { "\n" } else { "" };
+    out.push_str(&indent);
+    out.push('<');
+    out.push_str(&name);
+
+    match value {
+        Value::Object(obj) => {
+            let mut text = None;
+            let mut children = Vec::new();
+            for (child_key, child) in obj {
+                if let Some(attribute) = child_key.strip_prefix(mapping.attribute_prefix.as_str()).filter(|_| !mapping.attribute_prefix.is_empty()) {
+                    out.push_str(&format!(" {}=\"{}\"", xml_name(attribute), escape(scalar_text(child))));
+                } else if *child_key == mapping.text_key {
+                    text = Some(scalar_text(child));
+                } else {
+                    children.push((child_key, child));
+                }
+            }
+
+            if children.is_empty() && text.is_none() {
+                out.push_str("/>");
+                out.push_str(newline);
+                return;
+            }
+            out.push('>');
+            if let Some(text) = text {
+                out.push_str(&escape(&text));
+            }
+            if !children.is_empty() {
+                out.push_str(newline);
+                for (child_key, child) in children {
+                    write_xml_element(out, child_key, child, mapping, depth + 1, pretty);
+                }
+                out.push_str(&indent);
+            }
+        }
+        Value::Null => {
+            out.push_str("/>");
+            out.push_str(newline);
+            return;
+        }
+        scalar => {
+            out.push('>');
+            out.push_str(&escape(scalar_text(scalar)));
+        }
+    }
+
+    out.push_str("</");
+    out.push_str(&name);
+    out.push('>');
+    out.push_str(newline);
+}
+
+fn scalar_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+#[async_trait]
+impl ToolHandler for JsonConvert {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![
+            Self::create_csv_to_json_tool(),
+            Self::create_xml_to_json_tool(),
+            Self::create_json_to_xml_tool(),
+        ])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "csv-to-json" => self.handle_csv_to_json(&tool_call.arguments).await,
+            "xml-to-json" => self.handle_xml_to_json(&tool_call.arguments).await,
+            "json-to-xml" => self.handle_json_to_xml(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
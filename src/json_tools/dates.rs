@@ -0,0 +1,332 @@
+use crate::json_tools::formats::{read_document, serialize_for_path, DocumentFormat};
+use crate::json_tools::storage;
+use crate::json_tools::timewindow::parse_timestamp;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone, Utc};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+
+/// Words in a key name that mark its numbers as epoch timestamps.
+const TIME_WORDS: &[&str] = &[
+    "at", "time", "timestamp", "date", "datetime", "ts", "epoch",
+    "created", "updated", "modified", "deleted", "expires", "expiry", "started", "finished",
+];
+
+/// Formats with a date and a time of day but no offset, month-first for slashed dates.
+const DATETIME_FORMATS: &[(&str, &str)] = &[
+    ("%Y-%m-%dT%H:%M:%S%.f", "iso8601 without offset"),
+    ("%Y-%m-%d %H:%M:%S%.f", "iso8601 without offset"),
+    ("%Y-%m-%d %H:%M", "iso8601 without offset"),
+    ("%Y/%m/%d %H:%M:%S", "yyyy/mm/dd"),
+    ("%m/%d/%Y %I:%M:%S %p", "mm/dd/yyyy"),
+    ("%m/%d/%Y %I:%M %p", "mm/dd/yyyy"),
+    ("%m/%d/%Y %H:%M:%S", "mm/dd/yyyy"),
+    ("%m/%d/%Y %H:%M", "mm/dd/yyyy"),
+    ("%d.%m.%Y %H:%M:%S", "dd.mm.yyyy"),
+    ("%d.%m.%Y %H:%M", "dd.mm.yyyy"),
+    ("%B %d, %Y %I:%M %p", "month name"),
+    ("%B %d, %Y %H:%M:%S", "month name"),
+    ("%b %d, %Y %I:%M %p", "month name"),
+    ("%b %d, %Y %H:%M:%S", "month name"),
+    ("%d %B %Y %H:%M:%S", "month name"),
+    ("%d %b %Y %H:%M:%S", "month name"),
+    ("%a %b %e %H:%M:%S %Y", "ctime"),
+];
+
+/// Formats with only a date, read as midnight. Plain ISO dates (2025-01-31) are valid
+/// RFC 3339 full-dates and are left alone.
+const DATE_FORMATS: &[(&str, &str)] = &[
+    ("%Y/%m/%d", "yyyy/mm/dd"),
+    ("%m/%d/%Y", "mm/dd/yyyy"),
+    ("%d.%m.%Y", "dd.mm.yyyy"),
+    ("%B %d, %Y", "month name"),
+    ("%b %d, %Y", "month name"),
+    ("%d %B %Y", "month name"),
+    ("%d %b %Y", "month name"),
+];
+
+/// Formats that carry their own offset.
+const OFFSET_FORMATS: &[(&str, &str)] = &[
+    ("%Y-%m-%d %H:%M:%S%.f%#z", "iso8601 with offset"),
+    ("%Y-%m-%d %H:%M:%S%.f %#z", "iso8601 with offset"),
+    ("%d/%b/%Y:%H:%M:%S %z", "common log"),
+];
+
+/// How to read values that could be timestamps.
+struct Detection<'a> {
+    fields: Option<Vec<&'a str>>,
+    day_first: bool,
+    assume_offset: FixedOffset,
+}
+
+impl Detection<'_> {
+    /// Whether values under `key` are considered: every key unless `fields` names some.
+    fn considers(&self, key: Option<&str>) -> bool {
+        match &self.fields {
+            Some(fields) => key.is_some_and(|key| fields.contains(&key)),
+            None => true,
+        }
+    }
+
+    /// Whether numbers under `key` are epoch timestamps: named in `fields`, or a time-like key.
+    fn is_time_key(&self, key: Option<&str>) -> bool {
+        let Some(key) = key else {
+            return false;
+        };
+        self.fields.is_some() || key_words(key).iter().any(|word| TIME_WORDS.contains(&word.as_str()))
+    }
+
+    /// The timestamp a value holds and the name of its format, unless it is already RFC 3339.
+    fn parse(&self, value: &Value, key: Option<&str>) -> Option<(DateTime<FixedOffset>, &'static str)> {
+        match value {
+            Value::Number(_) if self.is_time_key(key) => epoch(value),
+            Value::String(text) => {
+                let text = text.trim();
+                if DateTime::parse_from_rfc3339(text).is_ok() {
+                    return None;
+                }
+                if text.parse::<f64>().is_ok() {
+                    return self.is_time_key(key).then(|| epoch(value)).flatten();
+                }
+                self.parse_text(text)
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_text(&self, text: &str) -> Option<(DateTime<FixedOffset>, &'static str)> {
+        if let Ok(timestamp) = DateTime::parse_from_rfc2822(text) {
+            return Some((timestamp, "rfc2822"));
+        }
+        if let Some(found) = OFFSET_FORMATS.iter()
+            .find_map(|(format, name)| DateTime::parse_from_str(text, format).ok().map(|ts| (ts, *name)))
+        {
+            return Some(found);
+        }
+        let day_first = |format: &str| if self.day_first { format.replace("%m/%d", "%d/%m") } else { format.to_string() };
+        let naive = DATETIME_FORMATS.iter()
+            .find_map(|(format, name)| NaiveDateTime::parse_from_str(text, &day_first(format)).ok().map(|ts| (ts, *name)))
+            .or_else(|| DATE_FORMATS.iter().find_map(|(format, name)| {
+                NaiveDate::parse_from_str(text, &day_first(format)).ok()
+                    .and_then(|date| date.and_hms_opt(0, 0, 0))
+                    .map(|ts| (ts, *name))
+            }));
+        let (naive, name) = naive?;
+        let name = match name {
+            "mm/dd/yyyy" if self.day_first => "dd/mm/yyyy",
+            name => name,
+        };
+        self.assume_offset.from_local_datetime(&naive).single().map(|ts| (ts, name))
+    }
+}
+
+/// Epoch seconds or milliseconds (by magnitude) that fall in a plausible range of years, so
+/// counters and durations under time-like keys are not mistaken for timestamps.
+fn epoch(value: &Value) -> Option<(DateTime<FixedOffset>, &'static str)> {
+    let number = match value {
+        Value::String(text) => text.trim().parse::<f64>().ok()?,
+        other => other.as_f64()?,
+    };
+    let timestamp = parse_timestamp(value)?;
+    if !(1971..=2200).contains(&timestamp.year()) {
+        return None;
+    }
+    let name = if number.abs() < 1e11 { "epoch seconds" } else { "epoch milliseconds" };
+    Some((timestamp.fixed_offset(), name))
+}
+
+/// The lowercase words of a snake_case, kebab-case or camelCase key.
+pub(crate) fn key_words(key: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for c in key.chars() {
+        if (matches!(c, '_' | '-' | '.' | ' ') || c.is_uppercase()) && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        if c.is_alphanumeric() {
+            word.extend(c.to_lowercase());
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// Conversions at one path, with `[*]` for array elements.
+#[derive(Default)]
+struct PathReport {
+    count: usize,
+    formats: BTreeMap<&'static str, usize>,
+    example: Option<(Value, String)>,
+}
+
+fn child_path(path: &str, key: &str) -> String {
+    if !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        format!("{}.{}", path, key)
+    } else {
+        format!("{}['{}']", path, key.replace('\'', "\\'"))
+    }
+}
+
+/// Rewrite every detected timestamp under `value` to RFC 3339 in UTC.
+fn normalize(value: &mut Value, key: Option<&str>, path: &str, detection: &Detection, report: &mut BTreeMap<String, PathReport>) {
+    match value {
+        Value::Object(map) => {
+            for (child, item) in map.iter_mut() {
+                normalize(item, Some(child), &child_path(path, child), detection, report);
+            }
+        }
+        Value::Array(items) => {
+            let path = format!("{}[*]", path);
+            for item in items {
+                normalize(item, key, &path, detection, report);
+            }
+        }
+        _ if detection.considers(key) => {
+            if let Some((timestamp, format)) = detection.parse(value, key) {
+                let normalized = timestamp.with_timezone(&Utc).to_rfc3339_opts(SecondsFormat::AutoSi, true);
+                let entry = report.entry(path.to_string()).or_default();
+                entry.count += 1;
+                *entry.formats.entry(format).or_default() += 1;
+                entry.example.get_or_insert_with(|| (value.clone(), normalized.clone()));
+                *value = Value::String(normalized);
+            }
+        }
+        _ => {}
+    }
+}
+
+pub struct JsonDates;
+
+impl Default for JsonDates {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonDates {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn create_normalize_dates_tool() -> Tool {
+        Tool {
+            name: "json-normalize-dates".to_string(),
+            description: "Find timestamps written in other formats (epoch seconds/milliseconds, RFC 2822, 03/14/2025 2:30 PM, 14.03.2025, March 14, 2025, ...) and rewrite them as RFC 3339 in UTC, reporting the conversions per path.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "JSON (or TOML/MessagePack/CBOR) file to normalize"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "Write the result here instead of updating file_path in place"
+                    },
+                    "fields": {
+                        "type": "array",
+                        "items": {"type": "string"},
+                        "description": "Only convert values under these keys, and treat their numbers as epoch timestamps. By default every string in a known format is converted, and numbers only under time-like keys (created_at, updatedAt, timestamp, ...)"
+                    },
+                    "day_first": {
+                        "type": "boolean",
+                        "description": "Read slashed dates as dd/mm/yyyy instead of mm/dd/yyyy (default: false)",
+                        "default": false
+                    },
+                    "assume_offset": {
+                        "type": "string",
+                        "description": "UTC offset of times written without one, e.g. '+02:00' (default: 'Z', UTC)",
+                        "default": "Z"
+                    },
+                    "dry_run": {
+                        "type": "boolean",
+                        "description": "Report the conversions without writing anything (default: false)",
+                        "default": false
+                    }
+                },
+                "required": ["file_path"]
+            })
+        }
+    }
+
+    async fn handle_normalize_dates(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let file_path = args.get("file_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "file_path is required. Usage example:\n{{\n  \"file_path\": \"./events.json\",\n  \"dry_run\": true\n}}"
+            ))?;
+        let assume_offset = match args.get("assume_offset").and_then(|v| v.as_str()).unwrap_or("Z") {
+            "Z" | "z" => FixedOffset::east_opt(0).expect("zero is a valid offset"),
+            offset => match DateTime::parse_from_rfc3339(&format!("2000-01-01T00:00:00{}", offset)) {
+                Ok(parsed) => *parsed.offset(),
+                Err(_) => return Ok(ToolResult::error(format!(
+                    "Invalid assume_offset '{}'. Use 'Z' or an offset like '+02:00'", offset
+                ))),
+            },
+        };
+        let detection = Detection {
+            fields: args.get("fields").and_then(|v| v.as_array())
+                .map(|fields| fields.iter().filter_map(|f| f.as_str()).collect()),
+            day_first: args.get("day_first").and_then(|v| v.as_bool()).unwrap_or(false),
+            assume_offset,
+        };
+        let dry_run = args.get("dry_run").and_then(|v| v.as_bool()).unwrap_or(false);
+
+        let format = DocumentFormat::from_path(file_path);
+        let mut document = read_document(file_path, format)?;
+        let mut report = BTreeMap::new();
+        normalize(&mut document, None, "$", &detection, &mut report);
+
+        let total: usize = report.values().map(|entry| entry.count).sum();
+        let paths: Vec<Value> = report.into_iter()
+            .map(|(path, entry)| {
+                let mut item = Map::new();
+                item.insert("path".to_string(), json!(path));
+                item.insert("converted".to_string(), json!(entry.count));
+                item.insert("formats".to_string(), json!(entry.formats));
+                if let Some((from, to)) = entry.example {
+                    item.insert("example".to_string(), json!({"from": from, "to": to}));
+                }
+                Value::Object(item)
+            })
+            .collect();
+        let summary = serde_json::to_string_pretty(&json!({"converted": total, "paths": paths}))?;
+
+        if dry_run || total == 0 {
+            let note = if dry_run { "nothing was written" } else { "the file was not changed" };
+            return Ok(ToolResult::success(format!(
+                "Found {} timestamp(s) to normalize in '{}'; {}:\n\n{}", total, file_path, note, summary
+            )));
+        }
+
+        let output_path = args.get("output_path").and_then(|v| v.as_str()).unwrap_or(file_path);
+        let output_format = DocumentFormat::from_path(output_path);
+        let content = match serialize_for_path(output_path, &document, output_format, true) {
+            Ok(content) => content,
+            Err(e) => return Ok(ToolResult::error(format!("Cannot write '{}' as {}: {}", output_path, output_format.name(), e))),
+        };
+        storage::write(output_path, &content)?;
+
+        Ok(ToolResult::success(format!(
+            "Normalized {} timestamp(s) from '{}' to RFC 3339 and wrote '{}':\n\n{}", total, file_path, output_path, summary
+        )))
+    }
+}
+
+#[async_trait]
+impl ToolHandler for JsonDates {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_normalize_dates_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-normalize-dates" => self.handle_normalize_dates(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
@@ -0,0 +1,474 @@
+use crate::json_tools::files::{for_each_record, RecordFormat};
+use crate::json_tools::formats::{read_document, DocumentFormat};
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+const DEFAULT_MAX_ENUM_VALUES: usize = 10;
+const DEFAULT_MAX_FIELDS: usize = 200;
+/// Strings longer than this are never treated as enum values.
+const MAX_ENUM_VALUE_LENGTH: usize = 64;
+const DEFAULT_TOP_K: usize = 5;
+/// Distinct values counted exactly per field; beyond this the count is a lower bound.
+const MAX_DISTINCT: usize = 1_000_000;
+/// Values whose occurrences are counted for top_values; later new values are not tracked.
+const MAX_COUNTED_VALUES: usize = 10_000;
+/// Numbers kept per field for percentiles and the histogram, thinned evenly beyond this.
+const MAX_NUMBER_SAMPLES: usize = 100_000;
+const HISTOGRAM_BINS: usize = 10;
+/// Upper bounds (in characters) of the string length buckets.
+const LENGTH_BUCKETS: &[(usize, &str)] = &[(0, "0"), (8, "1-8"), (32, "9-32"), (128, "33-128"), (512, "129-512"), (usize::MAX, "513+")];
+
+pub struct JsonDescribe;
+
+impl Default for JsonDescribe {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What was seen at one generalized path such as `$.users[*].role`.
+#[derive(Default)]
+struct FieldStats {
+    /// Path of the enclosing object, for fields that are object members.
+    parent: Option<String>,
+    /// Dotted name relative to a record, as used by `where` predicates.
+    field: String,
+    present: usize,
+    types: Vec<(&'static str, usize)>,
+    /// Distinct string values, dropped once there are too many to be enum-like.
+    strings: Option<HashMap<String, usize>>,
+    min: Option<f64>,
+    max: Option<f64>,
+    integers: bool,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    /// Distribution statistics, collected when profiling.
+    profile: Option<Profile>,
+}
+
+/// Distribution of one field's values for `profile`.
+#[derive(Default)]
+struct Profile {
+    nulls: usize,
+    distinct: HashSet<u64>,
+    counts: HashMap<String, usize>,
+    counts_complete: bool,
+    sum: f64,
+    sum_of_squares: f64,
+    numbers: usize,
+    samples: Vec<f64>,
+    sample_stride: usize,
+    strings: usize,
+    length_sum: usize,
+    min_string_length: Option<usize>,
+    max_string_length: Option<usize>,
+    length_buckets: [usize; 6],
+}
+
+impl Profile {
+    fn new() -> Self {
+        Self { counts_complete: true, sample_stride: 1, ..Self::default() }
+    }
+
+    fn add(&mut self, value: &Value) {
+        match value {
+            Value::Null => self.nulls += 1,
+            Value::Array(_) | Value::Object(_) => return,
+            Value::Number(number) => {
+                let n = number.as_f64().unwrap_or(0.0);
+                self.sum += n;
+                self.sum_of_squares += n * n;
+                // Keep every stride-th number; when the sample is full, drop every other one
+                if self.numbers % self.sample_stride == 0 {
+                    self.samples.push(n);
+                    if self.samples.len() > MAX_NUMBER_SAMPLES {
+                        let mut index = 0;
+                        self.samples.retain(|_| { index += 1; index % 2 == 1 });
+                        self.sample_stride *= 2;
+                    }
+                }
+                self.numbers += 1;
+            }
+            Value::String(text) => {
+                let length = text.chars().count();
+                self.strings += 1;
+                self.length_sum += length;
+                self.min_string_length = Some(self.min_string_length.map_or(length, |min| min.min(length)));
+                self.max_string_length = Some(self.max_string_length.map_or(length, |max| max.max(length)));
+                let bucket = LENGTH_BUCKETS.iter().position(|(bound, _)| length <= *bound).unwrap_or(LENGTH_BUCKETS.len() - 1);
+                self.length_buckets[bucket] += 1;
+            }
+            Value::Bool(_) => {}
+        }
+        let key = value.to_string();
+        if self.distinct.len() < MAX_DISTINCT {
+            let mut hasher = DefaultHasher::new();
+            key.hash(&mut hasher);
+            self.distinct.insert(hasher.finish());
+        }
+        if let Some(count) = self.counts.get_mut(&key) {
+            *count += 1;
+        } else if self.counts.len() < MAX_COUNTED_VALUES {
+            self.counts.insert(key, 1);
+        } else {
+            self.counts_complete = false;
+        }
+    }
+
+    fn report(&self, present: usize, top_k: usize, integers: bool, entry: &mut Map<String, Value>) {
+        let rate = |count: usize| if present == 0 { 0.0 } else { (count as f64 / present as f64 * 10_000.0).round() / 10_000.0 };
+        entry.insert("null_rate".to_string(), json!(rate(self.nulls)));
+        entry.insert("distinct".to_string(), json!(self.distinct.len()));
+        if self.distinct.len() >= MAX_DISTINCT {
+            entry.insert("distinct_capped".to_string(), json!(true));
+        }
+        if !self.counts.is_empty() {
+            let mut counts: Vec<(&String, &usize)> = self.counts.iter().collect();
+            counts.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+            let top: Vec<Value> = counts.into_iter().take(top_k)
+                .map(|(value, count)| json!({
+                    "value": serde_json::from_str::<Value>(value).unwrap_or(Value::Null),
+                    "count": count,
+                    "share": rate(*count),
+                }))
+                .collect();
+            entry.insert("top_values".to_string(), json!(top));
+            if !self.counts_complete {
+                entry.insert("top_values_approximate".to_string(), json!(true));
+            }
+        }
+        if self.numbers > 0 {
+            let mean = self.sum / self.numbers as f64;
+            let variance = (self.sum_of_squares / self.numbers as f64 - mean * mean).max(0.0);
+            entry.insert("mean".to_string(), json!(round(mean)));
+            entry.insert("stddev".to_string(), json!(round(variance.sqrt())));
+            let mut sorted = self.samples.clone();
+            sorted.sort_by(f64::total_cmp);
+            let percentile = |p: f64| {
+                let n = sorted[((sorted.len() - 1) as f64 * p).round() as usize];
+                if integers { json!(n as i64) } else { json!(round(n)) }
+            };
+            entry.insert("percentiles".to_string(), json!({
+                "p25": percentile(0.25), "p50": percentile(0.5), "p75": percentile(0.75), "p90": percentile(0.9), "p99": percentile(0.99),
+            }));
+            entry.insert("histogram".to_string(), histogram(&sorted, self.sample_stride));
+            if self.sample_stride > 1 {
+                // Percentiles and the histogram come from every n-th number
+                entry.insert("sampled_every".to_string(), json!(self.sample_stride));
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min_string_length, self.max_string_length) {
+            let buckets: Map<String, Value> = LENGTH_BUCKETS.iter().zip(self.length_buckets)
+                .filter(|(_, count)| *count > 0)
+                .map(|((_, name), count)| (name.to_string(), json!(count)))
+                .collect();
+            entry.insert("string_length".to_string(), json!({
+                "min": min,
+                "max": max,
+                "mean": round(self.length_sum as f64 / self.strings as f64),
+                "buckets": buckets,
+            }));
+        }
+    }
+}
+
+/// Equal-width bins over sorted numbers, counts scaled back up by the sampling stride.
+fn histogram(sorted: &[f64], stride: usize) -> Value {
+    let (min, max) = (sorted[0], sorted[sorted.len() - 1]);
+    if min == max {
+        return json!([{"from": min, "to": max, "count": sorted.len() * stride}]);
+    }
+    let width = (max - min) / HISTOGRAM_BINS as f64;
+    let mut counts = [0usize; HISTOGRAM_BINS];
+    for n in sorted {
+        counts[(((n - min) / width) as usize).min(HISTOGRAM_BINS - 1)] += stride;
+    }
+    json!(counts.iter().enumerate()
+        .map(|(i, count)| json!({"from": round(min + width * i as f64), "to": round(min + width * (i + 1) as f64), "count": count}))
+        .collect::<Vec<_>>())
+}
+
+/// Round to 6 decimals for readable statistics.
+fn round(n: f64) -> f64 {
+    (n * 1e6).round() / 1e6
+}
+
+struct Collector {
+    fields: Vec<(String, FieldStats)>,
+    index: HashMap<String, usize>,
+    max_enum_values: usize,
+    max_fields: usize,
+    dropped_fields: usize,
+    /// Number of top values per field when profiling.
+    profile_top_k: Option<usize>,
+}
+
+impl Collector {
+    fn new(max_enum_values: usize, max_fields: usize, profile_top_k: Option<usize>) -> Self {
+        Self { fields: Vec::new(), index: HashMap::new(), max_enum_values, max_fields, dropped_fields: 0, profile_top_k }
+    }
+
+    fn stats(&mut self, path: &str, parent: Option<&str>, field: &str) -> Option<&mut FieldStats> {
+        let position = match self.index.get(path) {
+            Some(&position) => position,
+            None if self.fields.len() >= self.max_fields => {
+                self.dropped_fields += 1;
+                return None;
+            }
+            None => {
+                self.index.insert(path.to_string(), self.fields.len());
+                self.fields.push((path.to_string(), FieldStats {
+                    parent: parent.map(str::to_string),
+                    field: field.to_string(),
+                    strings: Some(HashMap::new()),
+                    integers: true,
+                    profile: self.profile_top_k.map(|_| Profile::new()),
+                    ..FieldStats::default()
+                }));
+                self.fields.len() - 1
+            }
+        };
+        Some(&mut self.fields[position].1)
+    }
+
+    fn visit(&mut self, value: &Value, path: &str, parent: Option<&str>, field: &str) {
+        let max_enum_values = self.max_enum_values;
+        if let Some(stats) = self.stats(path, parent, field) {
+            stats.present += 1;
+            if let Some(profile) = &mut stats.profile {
+                profile.add(value);
+            }
+            let kind = type_name(value);
+            match stats.types.iter_mut().find(|(name, _)| *name == kind) {
+                Some((_, count)) => *count += 1,
+                None => stats.types.push((kind, 1)),
+            }
+            match value {
+                Value::String(text) => {
+                    let too_many = match &mut stats.strings {
+                        Some(values) if text.chars().count() <= MAX_ENUM_VALUE_LENGTH => {
+                            *values.entry(text.clone()).or_default() += 1;
+                            values.len() > max_enum_values
+                        }
+                        Some(_) => true,
+                        None => false,
+                    };
+                    if too_many {
+                        stats.strings = None;
+                    }
+                }
+                Value::Number(number) => {
+                    if let Some(n) = number.as_f64() {
+                        stats.min = Some(stats.min.map_or(n, |min| min.min(n)));
+                        stats.max = Some(stats.max.map_or(n, |max| max.max(n)));
+                    }
+                    stats.integers &= number.is_i64() || number.is_u64();
+                }
+                Value::Array(items) => {
+                    stats.min_length = Some(stats.min_length.map_or(items.len(), |min| min.min(items.len())));
+                    stats.max_length = Some(stats.max_length.map_or(items.len(), |max| max.max(items.len())));
+                }
+                _ => {}
+            }
+        }
+        match value {
+            Value::Object(map) => {
+                for (key, child) in map {
+                    let child_path = member_path(path, key);
+                    let child_field = if field.is_empty() { key.clone() } else { format!("{}.{}", field, key) };
+                    self.visit(child, &child_path, Some(path), &child_field);
+                }
+            }
+            Value::Array(items) => {
+                let child_path = format!("{}[*]", path);
+                // Field names restart inside array elements, which are the records queries filter on
+                for item in items {
+                    self.visit(item, &child_path, None, "");
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Fields in first-seen order, as JSON.
+    fn report(&self) -> Vec<Value> {
+        let objects_at = |path: &str| self.index.get(path)
+            .and_then(|&i| self.fields[i].1.types.iter().find(|(name, _)| *name == "object"))
+            .map_or(0, |(_, count)| *count);
+        self.fields.iter().map(|(path, stats)| {
+            let mut entry = Map::new();
+            entry.insert("path".to_string(), json!(path));
+            if !stats.field.is_empty() {
+                entry.insert("field".to_string(), json!(stats.field));
+            }
+            let mut types = stats.types.clone();
+            types.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+            entry.insert("types".to_string(), Value::Object(types.into_iter().map(|(name, count)| (name.to_string(), json!(count))).collect()));
+            entry.insert("present".to_string(), json!(stats.present));
+            if let Some(parent) = &stats.parent {
+                entry.insert("optional".to_string(), json!(stats.present < objects_at(parent)));
+            }
+            // Enum-like: few distinct strings, at least one of them repeated
+            if let Some(values) = &stats.strings {
+                let total: usize = values.values().sum();
+                if !values.is_empty() && values.len() < total {
+                    let mut values: Vec<(&String, &usize)> = values.iter().collect();
+                    values.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+                    entry.insert("values".to_string(), Value::Object(values.into_iter().map(|(v, n)| (v.clone(), json!(n))).collect()));
+                }
+            }
+            if let (Some(min), Some(max)) = (stats.min, stats.max) {
+                let number = |n: f64| if stats.integers { json!(n as i64) } else { json!(n) };
+                entry.insert("min".to_string(), number(min));
+                entry.insert("max".to_string(), number(max));
+            }
+            if let (Some(min), Some(max)) = (stats.min_length, stats.max_length) {
+                entry.insert("length".to_string(), json!({"min": min, "max": max}));
+            }
+            if let (Some(profile), Some(top_k)) = (&stats.profile, self.profile_top_k) {
+                profile.report(stats.present, top_k, stats.integers, &mut entry);
+            }
+            Value::Object(entry)
+        }).collect()
+    }
+}
+
+fn member_path(parent: &str, key: &str) -> String {
+    let simple = key.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if simple {
+        format!("{}.{}", parent, key)
+    } else {
+        format!("{}['{}']", parent, key.replace('\\', "\\\\").replace('\'', "\\'"))
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+impl JsonDescribe {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn create_describe_tool() -> Tool {
+        Tool {
+            name: "json-describe".to_string(),
+            description: "Describe a file's fields before querying or writing it: every path (as JSONPath and as a dotted field name), its types, whether it is optional, enum-like value sets for low-cardinality strings, numeric ranges and array lengths. With profile, also null rates, distinct counts, top values, numeric percentiles and histograms, and string length distributions.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "JSON, NDJSON or TOML file to describe"
+                    },
+                    "array_path": {
+                        "type": "string",
+                        "description": "Simple JSONPath to an array of records to stream instead of loading the whole document (e.g. '$.events'). NDJSON files are always streamed"
+                    },
+                    "max_enum_values": {
+                        "type": "integer",
+                        "description": "Report string fields with at most this many distinct values as value sets (default: 10)",
+                        "minimum": 1
+                    },
+                    "max_fields": {
+                        "type": "integer",
+                        "description": "Maximum number of paths to report (default: 200)",
+                        "minimum": 1
+                    },
+                    "profile": {
+                        "type": "boolean",
+                        "description": "Add distribution statistics per field: null_rate, distinct count, top_values, mean/stddev/percentiles/histogram for numbers and string_length buckets (default: false)",
+                        "default": false
+                    },
+                    "top_k": {
+                        "type": "integer",
+                        "description": "Number of most frequent values reported per field when profiling (default: 5)",
+                        "minimum": 1
+                    }
+                },
+                "required": ["file_path"]
+            }),
+        }
+    }
+
+    async fn handle_describe(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let file_path = args.get("file_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "file_path is required. Usage example:\n{{\n  \"file_path\": \"./data.json\"\n}}"
+            ))?;
+        let array_path = args.get("array_path").and_then(|v| v.as_str());
+        let max_enum_values = args.get("max_enum_values")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_MAX_ENUM_VALUES, |v| v.max(1) as usize);
+        let max_fields = args.get("max_fields")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_MAX_FIELDS, |v| v.max(1) as usize);
+
+        let profile_top_k = args.get("profile").and_then(|v| v.as_bool()).unwrap_or(false).then(|| {
+            args.get("top_k").and_then(|v| v.as_u64()).map_or(DEFAULT_TOP_K, |v| v.max(1) as usize)
+        });
+
+        let mut collector = Collector::new(max_enum_values, max_fields, profile_top_k);
+        let ndjson = RecordFormat::from_extension(file_path) == RecordFormat::Ndjson;
+        let records = if array_path.is_some() || ndjson {
+            let array_path = array_path.unwrap_or("$");
+            let base = if ndjson { "$".to_string() } else { format!("{}[*]", array_path) };
+            for_each_record(file_path, RecordFormat::detect(file_path, array_path)?, array_path, |record| {
+                collector.visit(&record, &base, None, "");
+                Ok(())
+            }).map(Some)?
+        } else {
+            let document = read_document(file_path, DocumentFormat::from_path(file_path))?;
+            collector.visit(&document, "$", None, "");
+            None
+        };
+
+        let mut description = json!({"fields": collector.report()});
+        if let Some(records) = records {
+            description["records"] = json!(records);
+        }
+        if collector.dropped_fields > 0 {
+            description["truncated"] = json!(format!(
+                "only the first {} paths are described; raise max_fields or describe a narrower array_path", max_fields
+            ));
+        }
+        let source = match records {
+            Some(records) => format!(" from {} record(s)", records),
+            None => String::new(),
+        };
+        Ok(ToolResult::success(format!(
+            "Described {} path(s) in '{}'{}:\n\n{}",
+            collector.fields.len(), file_path, source, serde_json::to_string_pretty(&description)?
+        )))
+    }
+}
+
+#[async_trait]
+impl ToolHandler for JsonDescribe {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_describe_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-describe" => self.handle_describe(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
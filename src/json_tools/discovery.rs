@@ -0,0 +1,445 @@
+use crate::json_tools::formats::{parse_document, read_document, DocumentFormat};
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use serde::de::{Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+const DEFAULT_EXTENSIONS: &[&str] = &["json", "ndjson", "jsonl", "yaml", "yml", "toml"];
+const DEFAULT_MAX_FILES: usize = 1000;
+/// Files up to this size are parsed completely for the validity flag.
+const FULL_PARSE_LIMIT: u64 = 10 * 1024 * 1024;
+/// NDJSON files are spot-checked on their first lines.
+const NDJSON_SAMPLE_LINES: usize = 100;
+/// Records inspected for the key set of a fingerprint.
+const FINGERPRINT_SAMPLE: usize = 100;
+/// Keys reported per file in a fingerprint.
+const FINGERPRINT_MAX_KEYS: usize = 50;
+
+pub struct JsonDiscovery {
+    /// Directories indexed when no `directory` is given
+    roots: Vec<PathBuf>,
+}
+
+impl Default for JsonDiscovery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonDiscovery {
+    pub fn new() -> Self {
+        Self { roots: Vec::new() }
+    }
+
+    /// Index `roots` when json-list-files is called without a directory.
+    pub fn with_roots(roots: Vec<PathBuf>) -> Self {
+        Self { roots }
+    }
+
+    fn create_list_files_tool() -> Tool {
+        Tool {
+            name: "json-list-files".to_string(),
+            description: "List JSON, NDJSON, YAML and TOML files in a directory with size, modification time and a quick validity check, so you can discover which files exist before guessing paths.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "directory": {
+                        "type": "string",
+                        "description": "Directory to list. When omitted, the server's allowed roots (or its base directory) are indexed recursively"
+                    },
+                    "recursive": {
+                        "type": "boolean",
+                        "description": "Include subdirectories; hidden directories such as .git are skipped (default: false)"
+                    },
+                    "extensions": {
+                        "type": "array",
+                        "items": {"type": "string"},
+                        "description": "File extensions to include (default: json, ndjson, jsonl, yaml, yml, toml)"
+                    },
+                    "fingerprint": {
+                        "type": "boolean",
+                        "description": "Also report each file's top-level type, record count and key set, streaming large files (default: false)"
+                    },
+                    "max_files": {
+                        "type": "integer",
+                        "description": "Maximum number of files to return (default: 1000)",
+                        "minimum": 1
+                    }
+                }
+            })
+        }
+    }
+
+    async fn handle_list_files(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let fingerprint = args.get("fingerprint").and_then(|v| v.as_bool()).unwrap_or(false);
+        let max_files = args.get("max_files").and_then(|v| v.as_u64()).unwrap_or(DEFAULT_MAX_FILES as u64) as usize;
+        let extensions = parse_extensions(args.get("extensions"), DEFAULT_EXTENSIONS)?;
+
+        // Without a directory, map the whole data landscape the server may access
+        let (roots, recursive) = match args.get("directory").and_then(|v| v.as_str()) {
+            Some(directory) => (
+                vec![PathBuf::from(directory)],
+                args.get("recursive").and_then(|v| v.as_bool()).unwrap_or(false),
+            ),
+            None if self.roots.is_empty() => (vec![std::env::current_dir()?], true),
+            None => (self.roots.clone(), true),
+        };
+
+        let mut listings = Vec::with_capacity(roots.len());
+        let (mut total, mut returned, mut invalid) = (0, 0, 0);
+        for root in &roots {
+            if !root.is_dir() {
+                return Ok(ToolResult::error(format!("'{}' is not a directory", root.display())));
+            }
+            let mut files = collect_files(root, recursive, &extensions, &is_hidden)?;
+            files.sort();
+            let found = files.len();
+            files.truncate(max_files.saturating_sub(returned));
+            total += found;
+            returned += files.len();
+
+            let entries = files.iter()
+                .map(|path| describe_file(root, path, fingerprint))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            invalid += entries.iter().filter(|e| e["valid"] == false).count();
+            listings.push(json!({
+                "directory": root.display().to_string(),
+                "files": entries,
+                "total": found,
+                "truncated": entries.len() < found,
+            }));
+        }
+
+        let location = match args.get("directory").and_then(|v| v.as_str()) {
+            Some(directory) => format!("'{}'{}", directory, if recursive { " and its subdirectories" } else { "" }),
+            None => format!("{} root(s)", roots.len()),
+        };
+        let output = if listings.len() == 1 { listings.remove(0) } else { json!(listings) };
+        Ok(ToolResult::success(format!(
+            "Found {} file(s) in {} ({} invalid):\n\n{}",
+            total, location, invalid, serde_json::to_string_pretty(&output)?
+        )))
+    }
+}
+
+fn describe_file(root: &Path, path: &Path, fingerprint: bool) -> anyhow::Result<Value> {
+    let metadata = fs::metadata(path)
+        .map_err(|e| anyhow::anyhow!("Failed to stat '{}': {}", path.display(), e))?;
+    let modified = metadata.modified().ok()
+        .map(|time| chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339());
+    let format = file_format(path);
+    let (valid, error) = match check_validity(path, format, metadata.len()) {
+        Some(Ok(())) => (json!(true), None),
+        Some(Err(e)) => (json!(false), Some(e)),
+        None => (Value::Null, None),
+    };
+
+    let mut entry = json!({
+        "path": relative_name(root, path),
+        "format": format,
+        "bytes": metadata.len(),
+        "modified": modified,
+        "valid": valid,
+    });
+    if let Some(error) = error {
+        entry["error"] = json!(error);
+    } else if fingerprint {
+        match fingerprint_file(path, format) {
+            Ok(Some(summary)) => entry["fingerprint"] = summary,
+            Ok(None) => {}
+            Err(e) => {
+                entry["valid"] = json!(false);
+                entry["error"] = json!(e.to_string());
+            }
+        }
+    }
+    Ok(entry)
+}
+
+/// Top-level type, record count and key set of a file. JSON is streamed so only the
+/// sampled records are held in memory.
+fn fingerprint_file(path: &Path, format: &str) -> anyhow::Result<Option<Value>> {
+    let summary = match format {
+        "json" => {
+            let file = fs::File::open(path)?;
+            let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(file));
+            let summary = deserializer.deserialize_any(SummaryVisitor)?;
+            deserializer.end()?;
+            summary
+        }
+        "ndjson" => {
+            let mut summary = Summary::new("ndjson");
+            let mut records = 0;
+            for line in BufReader::new(fs::File::open(path)?).lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                if records < FINGERPRINT_SAMPLE {
+                    summary.add_keys_of(&serde_json::from_str(&line)?);
+                }
+                records += 1;
+            }
+            summary.records = Some(records);
+            summary
+        }
+        "toml" => Summary::of_value(&read_document(&path.to_string_lossy(), DocumentFormat::Toml)?),
+        _ => return Ok(None),
+    };
+    Ok(Some(summary.into_json()))
+}
+
+struct Summary {
+    top_level: &'static str,
+    records: Option<usize>,
+    keys: Vec<String>,
+    seen: HashSet<String>,
+    key_count: usize,
+}
+
+impl Summary {
+    fn new(top_level: &'static str) -> Self {
+        Self { top_level, records: None, keys: Vec::new(), seen: HashSet::new(), key_count: 0 }
+    }
+
+    fn add_key(&mut self, key: &str) {
+        if self.seen.insert(key.to_string()) {
+            self.key_count += 1;
+            if self.keys.len() < FINGERPRINT_MAX_KEYS {
+                self.keys.push(key.to_string());
+            }
+        }
+    }
+
+    fn add_keys_of(&mut self, record: &Value) {
+        if let Value::Object(obj) = record {
+            for key in obj.keys() {
+                self.add_key(key);
+            }
+        }
+    }
+
+    fn of_value(value: &Value) -> Self {
+        let mut summary = Self::new(value_type(value));
+        match value {
+            Value::Array(items) => {
+                items.iter().take(FINGERPRINT_SAMPLE).for_each(|item| summary.add_keys_of(item));
+                summary.records = Some(items.len());
+            }
+            Value::Object(_) => summary.add_keys_of(value),
+            _ => {}
+        }
+        summary
+    }
+
+    fn into_json(self) -> Value {
+        let mut summary = json!({"top_level": self.top_level});
+        if let Some(records) = self.records {
+            summary["records"] = json!(records);
+        }
+        if self.key_count > 0 {
+            summary["keys"] = json!(self.keys);
+            summary["key_count"] = json!(self.key_count);
+        }
+        summary
+    }
+}
+
+fn value_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Summarizes a document in one pass: object keys are collected without their values, and
+/// array elements beyond the sample are skipped after being counted.
+struct SummaryVisitor;
+
+impl<'de> Visitor<'de> for SummaryVisitor {
+    type Value = Summary;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a JSON document")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Summary, A::Error> {
+        let mut summary = Summary::new("object");
+        while let Some(key) = map.next_key::<String>()? {
+            map.next_value::<IgnoredAny>()?;
+            summary.add_key(&key);
+        }
+        Ok(summary)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Summary, A::Error> {
+        let mut summary = Summary::new("array");
+        let mut records = 0;
+        while records < FINGERPRINT_SAMPLE {
+            match seq.next_element::<Value>()? {
+                Some(record) => summary.add_keys_of(&record),
+                None => break,
+            }
+            records += 1;
+        }
+        while seq.next_element::<IgnoredAny>()?.is_some() {
+            records += 1;
+        }
+        summary.records = Some(records);
+        Ok(summary)
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<Summary, E> {
+        Ok(Summary::new("boolean"))
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<Summary, E> {
+        Ok(Summary::new("number"))
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<Summary, E> {
+        Ok(Summary::new("number"))
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<Summary, E> {
+        Ok(Summary::new("number"))
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<Summary, E> {
+        Ok(Summary::new("string"))
+    }
+
+    fn visit_unit<E>(self) -> Result<Summary, E> {
+        Ok(Summary::new("null"))
+    }
+}
+
+fn file_format(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).as_deref() {
+        Some("ndjson" | "jsonl") => "ndjson",
+        Some("yaml" | "yml") => "yaml",
+        Some("toml") => "toml",
+        Some("json") => "json",
+        _ => "other",
+    }
+}
+
+/// Quick validity check: small files are parsed completely, large JSON files only have
+/// their first character checked and NDJSON files their first lines. `None` means the
+/// format is not checked.
+fn check_validity(path: &Path, format: &str, size: u64) -> Option<Result<(), String>> {
+    let result = match format {
+        "json" | "toml" if size <= FULL_PARSE_LIMIT => fs::read(path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                let format = if format == "toml" { DocumentFormat::Toml } else { DocumentFormat::Json };
+                parse_document(&content, format).map(|_| ()).map_err(|e| e.to_string())
+            }),
+        "json" => fs::File::open(path)
+            .map_err(|e| e.to_string())
+            .and_then(|file| {
+                let mut reader = BufReader::new(file);
+                let buffer = reader.fill_buf().map_err(|e| e.to_string())?;
+                match buffer.iter().find(|b| !b.is_ascii_whitespace()) {
+                    Some(b'{' | b'[') => Ok(()),
+                    _ => Err("does not start with '{' or '['".to_string()),
+                }
+            }),
+        "ndjson" => fs::File::open(path)
+            .map_err(|e| e.to_string())
+            .and_then(|file| {
+                for (i, line) in BufReader::new(file).lines().take(NDJSON_SAMPLE_LINES).enumerate() {
+                    let line = line.map_err(|e| e.to_string())?;
+                    if !line.trim().is_empty() {
+                        serde_json::from_str::<Value>(&line).map_err(|e| format!("line {}: {}", i + 1, e))?;
+                    }
+                }
+                Ok(())
+            }),
+        _ => return None,
+    };
+    Some(result)
+}
+
+/// Parse an `extensions` argument, falling back to `defaults`.
+pub(crate) fn parse_extensions(value: Option<&Value>, defaults: &[&str]) -> anyhow::Result<Vec<String>> {
+    match value {
+        None => Ok(defaults.iter().map(|e| e.to_string()).collect()),
+        Some(value) => value.as_array()
+            .and_then(|exts| exts.iter()
+                .map(|e| e.as_str().map(|e| e.trim_start_matches('.').to_ascii_lowercase()))
+                .collect::<Option<Vec<_>>>())
+            .ok_or_else(|| anyhow::anyhow!("extensions must be an array of strings, e.g. [\"json\", \"toml\"]")),
+    }
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+/// Collect files under `root` with one of `extensions`, descending into subdirectories when
+/// `recursive` is set. Directories for which `skip_dir` returns true are not entered.
+pub(crate) fn collect_files(
+    root: &Path,
+    recursive: bool,
+    extensions: &[String],
+    skip_dir: &dyn Fn(&Path) -> bool,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir)
+            .map_err(|e| anyhow::anyhow!("Failed to read directory '{}': {}", dir.display(), e))?
+        {
+            let entry = entry?;
+            let path = entry.path();
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                if recursive && !skip_dir(&path) {
+                    pending.push(path);
+                }
+            } else if file_type.is_file() {
+                let matches = path.extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|e| extensions.iter().any(|x| x.eq_ignore_ascii_case(e)));
+                if matches {
+                    files.push(path);
+                }
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Path of `path` relative to `root` with '/' separators.
+pub(crate) fn relative_name(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    relative.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[async_trait]
+impl ToolHandler for JsonDiscovery {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_list_files_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-list-files" => self.handle_list_files(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
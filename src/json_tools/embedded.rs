@@ -0,0 +1,228 @@
+use crate::json_tools::formats::{serialize_for_path, DocumentFormat};
+use crate::json_tools::patch::{parse_pointer, pointer_get_mut};
+use crate::json_tools::storage;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+
+/// Decoding stops after this many nested string encodings.
+const MAX_DECODE_DEPTH: usize = 8;
+
+pub struct JsonEmbedded;
+
+impl Default for JsonEmbedded {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonEmbedded {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn create_unescape_tool() -> Tool {
+        Tool {
+            name: "json-unescape".to_string(),
+            description: "Decode JSON that was encoded as a string (e.g. \"{\\\"id\\\": 1}\" in logs and API payloads) into structured JSON, either inline or at a JSON Pointer inside a file".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "content": {
+                        "type": "string",
+                        "description": "String holding encoded JSON to decode (use this or file_path)"
+                    },
+                    "file_path": {
+                        "type": "string",
+                        "description": "JSON file containing the encoded string"
+                    },
+                    "pointer": {
+                        "type": "string",
+                        "description": "JSON Pointer (RFC 6901) to the string inside file_path, e.g. '/events/0/payload' (default: the whole document)"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "Where to write the updated document (default: rewrite file_path in place)"
+                    },
+                    "recursive": {
+                        "type": "boolean",
+                        "description": "Keep decoding while the result is itself an encoded JSON object, array or string (default: true)"
+                    }
+                }
+            }),
+        }
+    }
+
+    fn create_escape_tool() -> Tool {
+        Tool {
+            name: "json-escape".to_string(),
+            description: "Encode a JSON value as a JSON string (the reverse of json-unescape), either inline or at a JSON Pointer inside a file".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "content": {
+                        "description": "JSON value to encode (use this or file_path)"
+                    },
+                    "file_path": {
+                        "type": "string",
+                        "description": "JSON file containing the value to encode"
+                    },
+                    "pointer": {
+                        "type": "string",
+                        "description": "JSON Pointer (RFC 6901) to the value inside file_path (default: the whole document)"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "Where to write the updated document (default: rewrite file_path in place)"
+                    }
+                }
+            }),
+        }
+    }
+
+    async fn handle_unescape(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let recursive = args.get("recursive").and_then(|v| v.as_bool()).unwrap_or(true);
+        self.transform(args, "json-unescape", |value| decode(value, recursive)).await
+    }
+
+    async fn handle_escape(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        self.transform(args, "json-escape", |value| Ok((Value::String(serde_json::to_string(value)?), 1))).await
+    }
+
+    /// Apply `convert` to inline `content`, or to the value at `pointer` in `file_path`.
+    /// `convert` returns the new value and the number of encoding levels it added or removed.
+    async fn transform<F>(&self, args: &HashMap<String, Value>, tool: &str, convert: F) -> anyhow::Result<ToolResult>
+    where
+        F: Fn(&Value) -> anyhow::Result<(Value, usize)>,
+    {
+        let verb = if tool == "json-unescape" { "Decoded" } else { "Encoded" };
+        match (args.get("content"), args.get("file_path").and_then(|v| v.as_str())) {
+            (Some(content), None) => match convert(content) {
+                Ok((value, levels)) => Ok(ToolResult::success(format!(
+                    "{} {} level(s) of string encoding:\n\n{}", verb, levels, serde_json::to_string_pretty(&value)?
+                ))),
+                Err(e) => Ok(ToolResult::error(e.to_string())),
+            },
+            (None, Some(file_path)) => {
+                let pointer = args.get("pointer").and_then(|v| v.as_str()).unwrap_or("");
+                let output_path = args.get("output_path").and_then(|v| v.as_str()).unwrap_or(file_path);
+                let content = fs::read_to_string(file_path)
+                    .map_err(|e| anyhow::anyhow!("Failed to read file '{}': {}", file_path, e))?;
+                let mut document: Value = match serde_json::from_str(&content) {
+                    Ok(document) => document,
+                    Err(e) => return Ok(ToolResult::error(format!("Invalid JSON in '{}': {}", file_path, e))),
+                };
+                let target = match pointer_get_mut(&mut document, &parse_pointer(pointer)?) {
+                    Ok(target) => target,
+                    Err(e) => return Ok(ToolResult::error(format!("Cannot resolve pointer '{}' in '{}': {}", pointer, file_path, e))),
+                };
+                let (value, levels) = match convert(target) {
+                    Ok(converted) => converted,
+                    Err(e) => return Ok(ToolResult::error(format!("At '{}' in '{}': {}", pointer, file_path, e))),
+                };
+                *target = value;
+                storage::write(output_path, &serialize_for_path(output_path, &document, DocumentFormat::Json, true)?)?;
+                Ok(ToolResult::success(format!(
+                    "{} {} level(s) of string encoding at '{}' in '{}' and wrote '{}'",
+                    verb, levels, if pointer.is_empty() { "/" } else { pointer }, file_path, output_path
+                )))
+            }
+            (Some(_), Some(_)) => Ok(ToolResult::error("Provide either content or file_path, not both".to_string())),
+            (None, None) => anyhow::bail!(
+                "content or file_path is required. Usage example:\n{{\n  \"file_path\": \"./events.json\",\n  \"pointer\": \"/events/0/payload\"\n}}\nor pass the value itself as \"content\""
+            ),
+        }
+    }
+}
+
+/// Parse a string holding JSON text, repeatedly when `recursive` and the result is again
+/// an encoded object, array or string. Numbers and booleans in strings are left alone.
+fn decode(value: &Value, recursive: bool) -> anyhow::Result<(Value, usize)> {
+    let Value::String(text) = value else {
+        anyhow::bail!("Expected a string containing encoded JSON, found {}", type_name(value));
+    };
+    let mut current: Value = serde_json::from_str(text)
+        .map_err(|e| anyhow::anyhow!("The string is not valid encoded JSON: {}", e))?;
+    let mut levels = 1;
+    while recursive && levels < MAX_DECODE_DEPTH {
+        let Value::String(inner) = &current else { break };
+        if !inner.trim_start().starts_with(['{', '[', '"']) {
+            break;
+        }
+        match serde_json::from_str(inner) {
+            Ok(next) => current = next,
+            Err(_) => break,
+        }
+        levels += 1;
+    }
+    Ok((current, levels))
+}
+
+/// JSON Schema for the `expand_embedded_json` argument of json-read and json-query.
+pub fn expand_embedded_schema() -> Value {
+    json!({
+        "type": "boolean",
+        "description": "Parse string values that hold serialized JSON objects or arrays (e.g. double-encoded log payloads) and return them inline as {\"embedded_json\": <parsed value>} (default: false). The number expanded is reported as 'embedded_json_expanded' in the metadata"
+    })
+}
+
+/// Apply the `expand_embedded_json` argument to `results`. Returns how many strings were
+/// expanded, or `None` when the argument is not set.
+pub fn expand_embedded_output(args: &HashMap<String, Value>, results: &mut Value) -> Option<usize> {
+    args.get("expand_embedded_json").and_then(|v| v.as_bool()).unwrap_or(false)
+        .then(|| expand_embedded(results))
+}
+
+/// Replace each string in `value` that holds an encoded JSON object or array, possibly
+/// encoded more than once, by `{"embedded_json": <decoded>}`, expanding the decoded value
+/// in turn. Other strings, including encoded numbers and booleans, are left alone.
+fn expand_embedded(value: &mut Value) -> usize {
+    match value {
+        Value::String(text) => {
+            if !text.trim_start().starts_with(['{', '[', '"']) {
+                return 0;
+            }
+            let Ok((mut decoded @ (Value::Object(_) | Value::Array(_)), _)) = decode(value, true) else {
+                return 0;
+            };
+            let nested = expand_embedded(&mut decoded);
+            *value = json!({"embedded_json": decoded});
+            1 + nested
+        }
+        Value::Array(items) => items.iter_mut().map(expand_embedded).sum(),
+        Value::Object(fields) => fields.values_mut().map(expand_embedded).sum(),
+        _ => 0,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+#[async_trait]
+impl ToolHandler for JsonEmbedded {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![
+            Self::create_unescape_tool(),
+            Self::create_escape_tool(),
+        ])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-unescape" => self.handle_unescape(&tool_call.arguments).await,
+            "json-escape" => self.handle_escape(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use crate::json_tools::sops;
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// PBKDF2-HMAC-SHA256 rounds for new `.enc` files unless the config sets others.
+pub const DEFAULT_PBKDF2_ITERATIONS: u32 = 600_000;
+
+const AES_CIPHER: &str = "aes-256-gcm";
+const AES_KDF: &str = "pbkdf2-sha256";
+
+/// The `encryption` section of the `--config` file. Files ending in `.enc` are encrypted
+/// with AES-256-GCM under a passphrase, files ending in `.age` with age.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EncryptionConfig {
+    /// Environment variable holding the passphrase for `.enc` files.
+    pub passphrase_env: Option<String>,
+    /// PBKDF2 rounds for the `.enc` files this server writes; each file records its own.
+    pub pbkdf2_iterations: Option<u32>,
+    /// Recipients (`age1...`) that `.age` files are encrypted to.
+    pub age_recipients: Vec<String>,
+    /// File of identities (`AGE-SECRET-KEY-1...`) that decrypt `.age` files.
+    pub age_identity_file: Option<PathBuf>,
+    /// The sops executable that decrypts and re-encrypts SOPS documents, with the key
+    /// providers they name.
+    pub sops_command: Option<PathBuf>,
+}
+
+/// How a file is encrypted, by its extension.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Scheme {
+    Aes,
+    Age,
+}
+
+fn scheme(file_path: &str) -> Option<Scheme> {
+    match Path::new(file_path).extension()?.to_str()?.to_ascii_lowercase().as_str() {
+        "enc" => Some(Scheme::Aes),
+        "age" => Some(Scheme::Age),
+        _ => None,
+    }
+}
+
+/// Whether `file_path` is stored encrypted: it ends in `.enc` or `.age`, or looks like a SOPS
+/// document while sops is configured.
+pub fn is_encrypted(file_path: &str) -> bool {
+    scheme(file_path).is_some() || (sops_command().is_some() && sops::may_be_sops(file_path))
+}
+
+/// `file_path` without its `.enc` or `.age` extension, whose own extension names the format
+/// of the document inside (`secrets.toml.enc` holds TOML).
+pub fn plain_path(file_path: &str) -> &str {
+    match scheme(file_path) {
+        Some(_) => &file_path[..file_path.len() - 4],
+        None => file_path,
+    }
+}
+
+/// Derived keys by salt and PBKDF2 rounds.
+type KeyCache = HashMap<(Vec<u8>, u32), [u8; 32]>;
+
+/// Keys for encrypted files. They are installed once for the whole process, since every
+/// tool reads documents through `formats::read_document`.
+pub struct Keyring {
+    passphrase: Option<String>,
+    iterations: u32,
+    /// Salt of the `.enc` files this process writes, so their key is derived once.
+    salt: [u8; 16],
+    /// Keys derived so far, since derivation is deliberately slow.
+    keys: Mutex<KeyCache>,
+    #[cfg(feature = "age")]
+    recipients: Vec<age::x25519::Recipient>,
+    #[cfg(feature = "age")]
+    identities: Vec<age::x25519::Identity>,
+    sops: Option<PathBuf>,
+}
+
+static KEYRING: RwLock<Option<Arc<Keyring>>> = RwLock::new(None);
+
+#[cfg(feature = "age")]
+fn read_identities(path: &Path) -> anyhow::Result<Vec<age::x25519::Identity>> {
+    let path = crate::json_tools::paths::expand_path(&path.to_string_lossy())?;
+    let text = std::fs::read_to_string(&path)
+        .map_err(|e| anyhow::anyhow!("Failed to read age identity file '{}': {}", path.display(), e))?;
+    let identities = text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.parse::<age::x25519::Identity>()
+            .map_err(|e| anyhow::anyhow!("Invalid identity in '{}': {}", path.display(), e)))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    if identities.is_empty() {
+        anyhow::bail!("The age identity file '{}' holds no identities", path.display());
+    }
+    Ok(identities)
+}
+
+impl Keyring {
+    /// The keys `config` names, or `None` when it configures no encryption. The passphrase
+    /// is read from the environment now.
+    pub fn from_config(config: &EncryptionConfig) -> anyhow::Result<Option<Self>> {
+        let passphrase = match &config.passphrase_env {
+            Some(name) => match std::env::var(name) {
+                Ok(passphrase) if !passphrase.is_empty() => Some(passphrase),
+                _ => anyhow::bail!("encryption.passphrase_env names '{}', which is not set or empty", name),
+            },
+            None => None,
+        };
+        let iterations = config.pbkdf2_iterations.unwrap_or(DEFAULT_PBKDF2_ITERATIONS);
+        if iterations == 0 {
+            anyhow::bail!("encryption.pbkdf2_iterations must be at least 1");
+        }
+
+        #[cfg(feature = "age")]
+        let (recipients, identities) = {
+            let recipients = config.age_recipients.iter()
+                .map(|recipient| recipient.parse::<age::x25519::Recipient>()
+                    .map_err(|e| anyhow::anyhow!("Invalid age recipient '{}': {}", recipient, e)))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let identities = match &config.age_identity_file {
+                Some(path) => read_identities(path)?,
+                None => Vec::new(),
+            };
+            (recipients, identities)
+        };
+        #[cfg(feature = "age")]
+        let configured = passphrase.is_some() || !recipients.is_empty() || !identities.is_empty();
+        #[cfg(not(feature = "age"))]
+        let configured = {
+            if !config.age_recipients.is_empty() || config.age_identity_file.is_some() {
+                anyhow::bail!("age keys are configured, but this server was built without the 'age' feature");
+            }
+            passphrase.is_some()
+        };
+        let configured = configured || config.sops_command.is_some();
+        if !configured {
+            return Ok(None);
+        }
+
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        Ok(Some(Self {
+            passphrase,
+            iterations,
+            salt,
+            keys: Mutex::new(HashMap::new()),
+            #[cfg(feature = "age")]
+            recipients,
+            #[cfg(feature = "age")]
+            identities,
+            sops: config.sops_command.clone(),
+        }))
+    }
+
+    /// Make these the keys every tool uses, replacing any installed before.
+    pub fn install(self) {
+        *KEYRING.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(self));
+    }
+
+    fn installed(file_path: &str) -> anyhow::Result<Arc<Keyring>> {
+        KEYRING.read().unwrap_or_else(|e| e.into_inner()).clone().ok_or_else(|| anyhow::anyhow!(
+            "'{}' is encrypted, but no keys are configured. Set encryption.passphrase_env (for .enc files) or encryption.age_identity_file and age_recipients (for .age files) in the --config file",
+            file_path
+        ))
+    }
+
+    fn key(&self, file_path: &str, salt: &[u8], iterations: u32) -> anyhow::Result<[u8; 32]> {
+        let passphrase = self.passphrase.as_deref().ok_or_else(|| anyhow::anyhow!(
+            "'{}' is encrypted with a passphrase, but encryption.passphrase_env is not configured", file_path
+        ))?;
+        let mut keys = self.keys.lock().unwrap_or_else(|e| e.into_inner());
+        let key = keys.entry((salt.to_vec(), iterations)).or_insert_with(|| {
+            let mut key = [0u8; 32];
+            pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, iterations, &mut key);
+            key
+        });
+        Ok(*key)
+    }
+
+    /// A JSON envelope with the KDF parameters, nonce and ciphertext, all base64.
+    fn seal_aes(&self, file_path: &str, plaintext: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let key = self.key(file_path, &self.salt, self.iterations)?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| anyhow::anyhow!("Failed to encrypt '{}'", file_path))?;
+        let envelope = json!({
+            "encrypted": AES_CIPHER,
+            "kdf": AES_KDF,
+            "iterations": self.iterations,
+            "salt": STANDARD.encode(self.salt),
+            "nonce": STANDARD.encode(nonce),
+            "ciphertext": STANDARD.encode(ciphertext),
+        });
+        let mut content = serde_json::to_vec_pretty(&envelope)?;
+        content.push(b'\n');
+        Ok(content)
+    }
+
+    fn open_aes(&self, file_path: &str, content: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let invalid = || anyhow::anyhow!("'{}' is not an encrypted document; .enc files hold the envelope this server writes", file_path);
+        let envelope: Value = serde_json::from_slice(content).map_err(|_| invalid())?;
+        if envelope["encrypted"] != AES_CIPHER || envelope["kdf"] != AES_KDF {
+            return Err(invalid());
+        }
+        let field = |name: &str| envelope[name].as_str().and_then(|text| STANDARD.decode(text).ok()).ok_or_else(invalid);
+        let (salt, nonce, ciphertext) = (field("salt")?, field("nonce")?, field("ciphertext")?);
+        let iterations = envelope["iterations"].as_u64()
+            .and_then(|n| u32::try_from(n).ok())
+            .filter(|n| *n > 0)
+            .ok_or_else(invalid)?;
+        if nonce.len() != 12 {
+            return Err(invalid());
+        }
+        let key = self.key(file_path, &salt, iterations)?;
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| anyhow::anyhow!("Cannot decrypt '{}': wrong passphrase, or the file was modified", file_path))
+    }
+
+    #[cfg(feature = "age")]
+    fn seal_age(&self, file_path: &str, plaintext: &[u8]) -> anyhow::Result<Vec<u8>> {
+        use std::io::Write;
+        if self.recipients.is_empty() {
+            anyhow::bail!("'{}' is an age file, but encryption.age_recipients is not configured", file_path);
+        }
+        let encryptor = age::Encryptor::with_recipients(self.recipients.iter().map(|r| r as &dyn age::Recipient))
+            .map_err(|e| anyhow::anyhow!("Failed to encrypt '{}': {}", file_path, e))?;
+        let mut content = Vec::new();
+        let mut writer = encryptor.wrap_output(&mut content)?;
+        writer.write_all(plaintext)?;
+        writer.finish()?;
+        Ok(content)
+    }
+
+    #[cfg(feature = "age")]
+    fn open_age(&self, file_path: &str, content: &[u8]) -> anyhow::Result<Vec<u8>> {
+        use std::io::Read;
+        if self.identities.is_empty() {
+            anyhow::bail!("'{}' is an age file, but encryption.age_identity_file is not configured", file_path);
+        }
+        let decryptor = age::Decryptor::new_buffered(content)
+            .map_err(|e| anyhow::anyhow!("'{}' is not an age file: {}", file_path, e))?;
+        let mut reader = decryptor.decrypt(self.identities.iter().map(|i| i as &dyn age::Identity))
+            .map_err(|e| anyhow::anyhow!("Cannot decrypt '{}': {}", file_path, e))?;
+        let mut plaintext = Vec::new();
+        reader.read_to_end(&mut plaintext)?;
+        Ok(plaintext)
+    }
+}
+
+fn sops_command() -> Option<PathBuf> {
+    KEYRING.read().unwrap_or_else(|e| e.into_inner()).as_ref()?.sops.clone()
+}
+
+#[cfg(not(feature = "age"))]
+fn age_unavailable(file_path: &str) -> anyhow::Error {
+    anyhow::anyhow!("'{}' is an age file, but this server was built without the 'age' feature", file_path)
+}
+
+/// The plaintext of a file read from `file_path`; content of unencrypted files is returned
+/// as it is.
+pub fn open(file_path: &str, content: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+    match scheme(file_path) {
+        None => match sops_command() {
+            Some(command) => sops::open(&command, file_path, content),
+            None => Ok(content),
+        },
+        Some(Scheme::Aes) => Keyring::installed(file_path)?.open_aes(file_path, &content),
+        #[cfg(feature = "age")]
+        Some(Scheme::Age) => Keyring::installed(file_path)?.open_age(file_path, &content),
+        #[cfg(not(feature = "age"))]
+        Some(Scheme::Age) => Err(age_unavailable(file_path)),
+    }
+}
+
+/// What to write to `file_path` for `content`: encrypted for `.enc` and `.age` files and SOPS
+/// documents, so their plaintext never reaches the disk.
+pub fn seal(file_path: &str, content: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+    match scheme(file_path) {
+        None => match sops_command() {
+            Some(command) => sops::seal(&command, file_path, content),
+            None => Ok(content),
+        },
+        Some(Scheme::Aes) => Keyring::installed(file_path)?.seal_aes(file_path, &content),
+        #[cfg(feature = "age")]
+        Some(Scheme::Age) => Keyring::installed(file_path)?.seal_age(file_path, &content),
+        #[cfg(not(feature = "age"))]
+        Some(Scheme::Age) => Err(age_unavailable(file_path)),
+    }
+}
@@ -0,0 +1,46 @@
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::time::Instant;
+
+/// Pagination and timing metadata attached to read and query results so agents can
+/// page through data without parsing prose.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResultMetadata {
+    pub total_matched: usize,
+    pub returned: usize,
+    pub truncated: bool,
+    pub next_offset: Option<usize>,
+    pub elapsed_ms: f64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl ResultMetadata {
+    /// `next_offset` is the offset to pass on the next call, or `None` when every
+    /// matching result has been returned.
+    pub fn new(total_matched: usize, returned: usize, next_offset: Option<usize>, started: Instant) -> Self {
+        let elapsed_ms = (started.elapsed().as_secs_f64() * 1_000_000.0).round() / 1000.0;
+        Self {
+            total_matched,
+            returned,
+            truncated: next_offset.is_some(),
+            next_offset,
+            elapsed_ms,
+            extra: Map::new(),
+        }
+    }
+
+    /// Attach a tool-specific field (e.g. `page`) to the metadata.
+    pub fn with(mut self, key: &str, value: Value) -> Self {
+        self.extra.insert(key.to_string(), value);
+        self
+    }
+
+    /// Wrap `results` into the `{"metadata": ..., "results": ...}` envelope.
+    pub fn wrap(self, results: Value) -> Value {
+        json!({
+            "metadata": self,
+            "results": results,
+        })
+    }
+}
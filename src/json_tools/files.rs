@@ -0,0 +1,524 @@
+use crate::json_tools::paths::{check_file_name, PathPolicy};
+use crate::json_tools::storage;
+use crate::json_tools::streaming::for_each_array_item;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+pub struct JsonFiles {
+    paths: PathPolicy,
+}
+
+impl Default for JsonFiles {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// On-disk layout of a collection of records.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum RecordFormat {
+    /// A JSON array of records.
+    Json,
+    /// One JSON record per line.
+    Ndjson,
+}
+
+impl RecordFormat {
+    pub(crate) fn parse(name: &str) -> anyhow::Result<Self> {
+        match name {
+            "json" => Ok(Self::Json),
+            "ndjson" | "jsonl" => Ok(Self::Ndjson),
+            _ => anyhow::bail!("Unknown format '{}'. Use 'json' or 'ndjson'", name),
+        }
+    }
+
+    /// Format implied by an output file name: `.ndjson`/`.jsonl` mean NDJSON.
+    pub(crate) fn from_extension(file_path: &str) -> Self {
+        match Path::new(file_path).extension().and_then(|e| e.to_str()) {
+            Some("ndjson") | Some("jsonl") => Self::Ndjson,
+            _ => Self::Json,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Ndjson => "ndjson",
+        }
+    }
+
+    /// Detect the format from the file extension, falling back to the first
+    /// non-whitespace byte (`[` means a JSON array). An `array_path` below the root
+    /// always means a JSON document.
+    pub(crate) fn detect(file_path: &str, array_path: &str) -> anyhow::Result<Self> {
+        if array_path.trim() != "$" {
+            return Ok(Self::Json);
+        }
+        let extension = Path::new(file_path).extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        if matches!(extension.as_deref(), Some("ndjson") | Some("jsonl")) {
+            return Ok(Self::Ndjson);
+        }
+
+        let first = BufReader::new(storage::open(file_path)?).bytes()
+            .filter_map(|b| b.ok())
+            .find(|b| !b.is_ascii_whitespace());
+        Ok(if first == Some(b'[') { Self::Json } else { Self::Ndjson })
+    }
+}
+
+/// Call `visit` for every record of `file_path`, streaming either NDJSON lines or the
+/// elements of the array at `array_path`. Returns the number of records visited.
+pub(crate) fn for_each_record<F>(file_path: &str, format: RecordFormat, array_path: &str, mut visit: F) -> anyhow::Result<usize>
+where
+    F: FnMut(Value) -> anyhow::Result<()>,
+{
+    match format {
+        RecordFormat::Json => for_each_array_item(file_path, array_path, |_, value| visit(value))?
+            .ok_or_else(|| anyhow::anyhow!("Array path '{}' was not found in '{}'", array_path, file_path)),
+        RecordFormat::Ndjson => {
+            let mut count = 0;
+            for (line_number, line) in BufReader::new(storage::open(file_path)?).lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let value = serde_json::from_str(&line)
+                    .map_err(|e| anyhow::anyhow!("Invalid JSON on line {} of '{}': {}", line_number + 1, file_path, e))?;
+                visit(value)?;
+                count += 1;
+            }
+            Ok(count)
+        }
+    }
+}
+
+/// Incrementally writes records to a single file as a JSON array or NDJSON.
+pub(crate) struct RecordWriter {
+    writer: RecordSink,
+    format: RecordFormat,
+    count: usize,
+}
+
+/// Where a `RecordWriter` puts its bytes: straight into a local file, or into a buffer
+/// that is stored in one piece on the path's backend when the writer finishes.
+enum RecordSink {
+    File(BufWriter<File>),
+    /// Path, content so far, and whether an existing file may be replaced.
+    Buffer(String, Vec<u8>, bool),
+}
+
+impl Write for RecordSink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::File(writer) => writer.write(buf),
+            Self::Buffer(_, buffer, _) => buffer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::File(writer) => writer.flush(),
+            Self::Buffer(..) => Ok(()),
+        }
+    }
+}
+
+impl RecordWriter {
+    pub(crate) fn create(path: &Path, format: RecordFormat) -> anyhow::Result<Self> {
+        Self::create_with(path, format, !storage::is_create_only(path))
+    }
+
+    /// Like [`RecordWriter::create`], but a local file that exists is only truncated and
+    /// rewritten when `replace` is set; otherwise opening it fails.
+    pub(crate) fn create_with(path: &Path, format: RecordFormat, replace: bool) -> anyhow::Result<Self> {
+        let name = path.to_string_lossy();
+        let writer = if storage::is_local(&name) {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| anyhow::anyhow!("Failed to create directories: {}", e))?;
+            }
+            let existed = path.exists();
+            let file = fs::OpenOptions::new().write(true).create(true).truncate(replace).create_new(!replace).open(path)
+                .map_err(|e| match e.kind() {
+                    std::io::ErrorKind::AlreadyExists => storage::already_exists(&name),
+                    _ => anyhow::anyhow!("Failed to create file '{}': {}", path.display(), e),
+                })?;
+            if !existed {
+                storage::apply_file_mode(path)?;
+            }
+            RecordSink::File(BufWriter::new(file))
+        } else {
+            storage::check_allowed(&name)?;
+            RecordSink::Buffer(name.into_owned(), Vec::new(), replace)
+        };
+        Self::start(writer, format)
+    }
+
+    /// A writer into a local file that is already open, such as a temporary sibling.
+    pub(crate) fn from_file(file: File, format: RecordFormat) -> anyhow::Result<Self> {
+        Self::start(RecordSink::File(BufWriter::new(file)), format)
+    }
+
+    fn start(mut writer: RecordSink, format: RecordFormat) -> anyhow::Result<Self> {
+        if format == RecordFormat::Json {
+            writer.write_all(b"[")?;
+        }
+        Ok(Self { writer, format, count: 0 })
+    }
+
+    pub(crate) fn write(&mut self, value: &Value) -> anyhow::Result<()> {
+        match self.format {
+            RecordFormat::Json => {
+                self.writer.write_all(if self.count == 0 { b"\n  " } else { b",\n  " })?;
+                serde_json::to_writer(&mut self.writer, value)?;
+            }
+            RecordFormat::Ndjson => {
+                serde_json::to_writer(&mut self.writer, value)?;
+                self.writer.write_all(b"\n")?;
+            }
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    pub(crate) fn finish(mut self) -> anyhow::Result<usize> {
+        if self.format == RecordFormat::Json {
+            self.writer.write_all(if self.count == 0 { b"]\n" } else { b"\n]\n" })?;
+        }
+        match &mut self.writer {
+            RecordSink::File(writer) => writer.flush()?,
+            RecordSink::Buffer(path, buffer, true) => storage::write(path, buffer)?,
+            RecordSink::Buffer(path, buffer, false) => storage::write_new(path, buffer)?,
+        }
+        Ok(self.count)
+    }
+}
+
+/// JSON Schema for the `overwrite` argument, accepted by every tool that writes a file: by
+/// the handler for tools with an `output_path`, and by json-write and json-split themselves.
+pub(crate) fn overwrite_schema(target: &str) -> Value {
+    json!({
+        "type": "boolean",
+        "description": format!("Replace {} if it already exists (default: true); false makes the call fail instead, so a new file cannot clobber an existing one", target),
+        "default": true
+    })
+}
+
+/// The error for a call with `overwrite: false` whose output file already exists.
+pub(crate) fn refuse_overwrite(args: &HashMap<String, Value>, output_path: &str) -> anyhow::Result<Option<ToolResult>> {
+    let overwrite = args.get("overwrite").and_then(|v| v.as_bool()).unwrap_or(true);
+    if overwrite || !storage::exists(output_path)? {
+        return Ok(None);
+    }
+    Ok(Some(ToolResult::error(storage::already_exists(output_path).to_string())))
+}
+
+/// Recursively merge `source` into `target`: objects are merged key by key, anything
+/// else in `source` replaces the value in `target`.
+pub fn deep_merge(target: &mut Value, source: Value) {
+    match (target, source) {
+        (Value::Object(target_obj), Value::Object(source_obj)) => {
+            for (key, value) in source_obj {
+                match target_obj.get_mut(&key) {
+                    Some(existing) => deep_merge(existing, value),
+                    None => {
+                        target_obj.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, source) => *target = source,
+    }
+}
+
+impl JsonFiles {
+    pub fn new() -> Self {
+        Self { paths: PathPolicy::default() }
+    }
+
+    /// Keep output files derived from `output_pattern` within `paths`' allowed roots.
+    pub fn with_path_policy(paths: PathPolicy) -> Self {
+        Self { paths }
+    }
+
+    fn create_split_tool() -> Tool {
+        Tool {
+            name: "json-split".to_string(),
+            description: "Split a large JSON array (or NDJSON file) into multiple files using streaming I/O, either into a fixed number of parts or into files of at most N records.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "Path to the JSON array or NDJSON file to split"
+                    },
+                    "max_records": {
+                        "type": "integer",
+                        "description": "Maximum number of records per output file",
+                        "minimum": 1
+                    },
+                    "parts": {
+                        "type": "integer",
+                        "description": "Number of output files to produce (alternative to max_records)",
+                        "minimum": 1
+                    },
+                    "array_path": {
+                        "type": "string",
+                        "description": "Simple JSONPath to the array to split for JSON input (default: '$')",
+                        "default": "$"
+                    },
+                    "input_format": {
+                        "type": "string",
+                        "enum": ["auto", "json", "ndjson"],
+                        "default": "auto",
+                        "description": "Input format. 'auto' uses the file extension, then the first character"
+                    },
+                    "output_format": {
+                        "type": "string",
+                        "enum": ["json", "ndjson"],
+                        "description": "Output format (default: same as input)"
+                    },
+                    "output_dir": {
+                        "type": "string",
+                        "description": "Directory for the output files (default: the input file's directory)"
+                    },
+                    "output_pattern": {
+                        "type": "string",
+                        "description": "File name pattern with {stem}, {index} (1-based, zero-padded) and {ext} placeholders (default: '{stem}_part{index}.{ext}')",
+                        "default": "{stem}_part{index}.{ext}"
+                    },
+                    "overwrite": overwrite_schema("the output files")
+                },
+                "required": ["file_path"]
+            })
+        }
+    }
+
+    fn create_concat_tool() -> Tool {
+        Tool {
+            name: "json-concat".to_string(),
+            description: "Combine multiple JSON files into one: concatenate arrays/NDJSON records, deep-merge objects in order, or zip NDJSON streams. Inputs are streamed one at a time.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_paths": {
+                        "type": "array",
+                        "items": {"type": "string"},
+                        "description": "Input files, in order"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "File to write the combined output to"
+                    },
+                    "mode": {
+                        "type": "string",
+                        "enum": ["concat", "merge", "zip"],
+                        "default": "concat",
+                        "description": "'concat' appends the records of every input, 'merge' deep-merges objects with later files winning, 'zip' emits one array per line index containing the record from each input (null once an input is exhausted)"
+                    },
+                    "output_format": {
+                        "type": "string",
+                        "enum": ["json", "ndjson"],
+                        "description": "Output format for concat/zip (default: from the output extension, '.ndjson'/'.jsonl' means NDJSON)"
+                    }
+                },
+                "required": ["file_paths", "output_path"]
+            })
+        }
+    }
+
+    async fn handle_concat(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let usage = "Usage example:\n{\n  \"file_paths\": [\"./a.json\", \"./b.json\"],\n  \"output_path\": \"./combined.json\",\n  \"mode\": \"concat\"\n}";
+        let file_paths: Vec<&str> = args.get("file_paths")
+            .and_then(|v| v.as_array())
+            .map(|paths| paths.iter().filter_map(|p| p.as_str()).collect())
+            .filter(|paths: &Vec<&str>| !paths.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("file_paths is required. {}", usage))?;
+
+        let output_path = args.get("output_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("output_path is required. {}", usage))?;
+
+        let mode = args.get("mode").and_then(|v| v.as_str()).unwrap_or("concat");
+        let output_format = match args.get("output_format").and_then(|v| v.as_str()) {
+            Some(name) => RecordFormat::parse(name)?,
+            None => RecordFormat::from_extension(output_path),
+        };
+
+        let written = match mode {
+            "concat" => {
+                let mut writer = RecordWriter::create(Path::new(output_path), output_format)?;
+                for input in &file_paths {
+                    let format = RecordFormat::detect(input, "$")?;
+                    for_each_record(input, format, "$", |value| writer.write(&value))?;
+                }
+                writer.finish()?
+            }
+            "merge" => {
+                let mut merged = Value::Object(Default::default());
+                for input in &file_paths {
+                    let content = fs::read_to_string(input)
+                        .map_err(|e| anyhow::anyhow!("Failed to read file '{}': {}", input, e))?;
+                    let value: Value = serde_json::from_str(&content)
+                        .map_err(|e| anyhow::anyhow!("Failed to parse JSON in '{}': {}", input, e))?;
+                    if !value.is_object() {
+                        return Ok(ToolResult::error(format!(
+                            "Merge mode requires every input to be an object, but '{}' is not", input
+                        )));
+                    }
+                    deep_merge(&mut merged, value);
+                }
+                storage::create_parent_dirs(output_path)?;
+                storage::write(output_path, serde_json::to_string_pretty(&merged)?.as_bytes())?;
+                1
+            }
+            "zip" => {
+                let mut readers = Vec::new();
+                for input in &file_paths {
+                    let file = File::open(input)
+                        .map_err(|e| anyhow::anyhow!("Failed to open file '{}': {}", input, e))?;
+                    readers.push(BufReader::new(file).lines().filter(|l| {
+                        l.as_ref().map(|l| !l.trim().is_empty()).unwrap_or(true)
+                    }));
+                }
+
+                let mut writer = RecordWriter::create(Path::new(output_path), output_format)?;
+                loop {
+                    let mut row = Vec::with_capacity(readers.len());
+                    let mut any = false;
+                    for (reader, input) in readers.iter_mut().zip(&file_paths) {
+                        match reader.next() {
+                            Some(line) => {
+                                let value: Value = serde_json::from_str(&line?)
+                                    .map_err(|e| anyhow::anyhow!("Invalid NDJSON in '{}': {}", input, e))?;
+                                row.push(value);
+                                any = true;
+                            }
+                            None => row.push(Value::Null),
+                        }
+                    }
+                    if !any {
+                        break;
+                    }
+                    writer.write(&Value::Array(row))?;
+                }
+                writer.finish()?
+            }
+            _ => return Ok(ToolResult::error(format!(
+                "Unknown mode: {}. Use 'concat', 'merge' or 'zip'", mode
+            ))),
+        };
+
+        Ok(ToolResult::success(format!(
+            "Combined {} file(s) into '{}' using {} mode ({} record(s) written)",
+            file_paths.len(), output_path, mode, written
+        )))
+    }
+
+    async fn handle_split(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let file_path = args.get("file_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "file_path is required. Usage example:\n{{\n  \"file_path\": \"./big.json\",\n  \"max_records\": 10000\n}}"
+            ))?;
+
+        let max_records = args.get("max_records").and_then(|v| v.as_u64()).filter(|&n| n > 0);
+        let parts = args.get("parts").and_then(|v| v.as_u64()).filter(|&n| n > 0);
+        if max_records.is_some() == parts.is_some() {
+            return Ok(ToolResult::error(
+                "Specify exactly one of 'max_records' or 'parts'. Usage example:\n{\n  \"file_path\": \"./big.json\",\n  \"parts\": 4\n}".to_string()
+            ));
+        }
+
+        let array_path = args.get("array_path").and_then(|v| v.as_str()).unwrap_or("$");
+        let input_format = match args.get("input_format").and_then(|v| v.as_str()).unwrap_or("auto") {
+            "auto" => RecordFormat::detect(file_path, array_path)?,
+            other => RecordFormat::parse(other)?,
+        };
+        let output_format = match args.get("output_format").and_then(|v| v.as_str()) {
+            Some(name) => RecordFormat::parse(name)?,
+            None => input_format,
+        };
+
+        let input = Path::new(file_path);
+        let output_dir = args.get("output_dir")
+            .and_then(|v| v.as_str())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| input.parent().map(Path::to_path_buf).unwrap_or_default());
+        let pattern = args.get("output_pattern")
+            .and_then(|v| v.as_str())
+            .unwrap_or("{stem}_part{index}.{ext}");
+        if let Err(e) = check_file_name(pattern, "output_pattern") {
+            return Ok(ToolResult::error(format!("{}. Use output_dir to choose the directory", e)));
+        }
+        let overwrite = args.get("overwrite").and_then(|v| v.as_bool()).unwrap_or(true);
+        let stem = input.file_stem().and_then(|s| s.to_str()).unwrap_or("part");
+
+        // Splitting into a fixed number of parts needs the record count up front
+        let records_per_file = match (max_records, parts) {
+            (Some(max), _) => max as usize,
+            (None, Some(parts)) => {
+                let total = for_each_record(file_path, input_format, array_path, |_| Ok(()))?;
+                total.div_ceil(parts as usize).max(1)
+            }
+            (None, None) => unreachable!("validated above"),
+        };
+
+        let mut outputs: Vec<(PathBuf, usize)> = Vec::new();
+        let mut current: Option<(PathBuf, RecordWriter)> = None;
+        let total = for_each_record(file_path, input_format, array_path, |value| {
+            if current.as_ref().map(|(_, w)| w.count >= records_per_file).unwrap_or(true) {
+                if let Some((path, writer)) = current.take() {
+                    outputs.push((path, writer.finish()?));
+                }
+                let name = pattern
+                    .replace("{stem}", stem)
+                    .replace("{index}", &format!("{:04}", outputs.len() + 1))
+                    .replace("{ext}", output_format.extension());
+                check_file_name(&name, "output_pattern")?;
+                let path = self.paths.resolve_derived(&output_dir.join(name))?;
+                let writer = RecordWriter::create_with(&path, output_format, overwrite)?;
+                current = Some((path, writer));
+            }
+            current.as_mut().map(|(_, w)| w.write(&value)).unwrap_or(Ok(()))
+        })?;
+        if let Some((path, writer)) = current.take() {
+            outputs.push((path, writer.finish()?));
+        }
+
+        let files: Vec<Value> = outputs.iter()
+            .map(|(path, count)| json!({"file": path.to_string_lossy(), "records": count}))
+            .collect();
+
+        Ok(ToolResult::success(format!(
+            "Split {} records from '{}' into {} file(s):\n\n{}",
+            total,
+            file_path,
+            files.len(),
+            serde_json::to_string_pretty(&files)?
+        )))
+    }
+}
+
+#[async_trait]
+impl ToolHandler for JsonFiles {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_split_tool(), Self::create_concat_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-split" => self.handle_split(&tool_call.arguments).await,
+            "json-concat" => self.handle_concat(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use crate::json_tools::encryption;
+use crate::json_tools::storage;
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// File formats that can be read into, and written from, the JSON data model.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DocumentFormat {
+    Json,
+    Toml,
+    MessagePack,
+    Cbor,
+}
+
+impl DocumentFormat {
+    /// Resolve the format from an explicit `format` argument, falling back to the file
+    /// extension and finally to JSON.
+    pub fn resolve(file_path: &str, explicit: Option<&str>) -> anyhow::Result<Self> {
+        match explicit {
+            Some(name) => Self::parse(name),
+            None => Ok(Self::from_path(file_path)),
+        }
+    }
+
+    pub fn parse(name: &str) -> anyhow::Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "toml" => Ok(Self::Toml),
+            "msgpack" | "messagepack" => Ok(Self::MessagePack),
+            "cbor" => Ok(Self::Cbor),
+            _ => anyhow::bail!("Unknown document format '{}'. Supported formats: json, toml, msgpack, cbor", name),
+        }
+    }
+
+    pub fn from_path(file_path: &str) -> Self {
+        let ext = Path::new(encryption::plain_path(file_path)).extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match ext.as_deref() {
+            Some("toml") => Self::Toml,
+            Some("msgpack" | "mpk") => Self::MessagePack,
+            Some("cbor") => Self::Cbor,
+            _ => Self::Json,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Toml => "toml",
+            Self::MessagePack => "msgpack",
+            Self::Cbor => "cbor",
+        }
+    }
+}
+
+/// Read a whole document and convert it to a JSON value.
+pub fn read_document(file_path: &str, format: DocumentFormat) -> anyhow::Result<Value> {
+    let content = encryption::open(file_path, storage::read(file_path)?)?;
+    parse_document(&content, format)
+        .map_err(|e| anyhow::anyhow!("Failed to parse {} in '{}': {}", format.name().to_uppercase(), file_path, e))
+}
+
+/// JSON Schema for the `content` argument accepted instead of `file_path`.
+pub fn content_schema() -> Value {
+    serde_json::json!({
+        "description": "Inline JSON to use instead of reading file_path: either JSON text or a JSON value"
+    })
+}
+
+/// The document given inline as `content`: JSON text is parsed, any other value is used as is.
+pub fn inline_document(content: &Value) -> anyhow::Result<Value> {
+    match content {
+        Value::String(text) => serde_json::from_str(text)
+            .map_err(|e| anyhow::anyhow!("Failed to parse inline content: {}", e)),
+        other => Ok(other.clone()),
+    }
+}
+
+pub fn parse_document(content: &[u8], format: DocumentFormat) -> anyhow::Result<Value> {
+    match format {
+        DocumentFormat::Json => Ok(serde_json::from_slice(content)?),
+        DocumentFormat::Toml => Ok(toml_to_json(std::str::from_utf8(content)?.parse::<toml::Table>()?.into())),
+        DocumentFormat::MessagePack => Ok(rmp_serde::from_slice(content)?),
+        DocumentFormat::Cbor => Ok(ciborium::from_reader(content)?),
+    }
+}
+
+/// Serialize a JSON value in the given format.
+pub fn serialize_document(value: &Value, format: DocumentFormat, pretty: bool) -> anyhow::Result<Vec<u8>> {
+    match format {
+        DocumentFormat::Json if pretty => Ok(serde_json::to_vec_pretty(value)?),
+        DocumentFormat::Json => Ok(serde_json::to_vec(value)?),
+        DocumentFormat::Toml => {
+            let toml::Value::Table(table) = json_to_toml(value, "$")? else {
+                anyhow::bail!("TOML documents must be objects at the top level");
+            };
+            let text = if pretty { toml::to_string_pretty(&table)? } else { toml::to_string(&table)? };
+            Ok(text.into_bytes())
+        }
+        // Objects are written as maps keyed by field name rather than positional arrays
+        DocumentFormat::MessagePack => Ok(rmp_serde::to_vec_named(value)?),
+        DocumentFormat::Cbor => {
+            let mut bytes = Vec::new();
+            ciborium::into_writer(value, &mut bytes)?;
+            Ok(bytes)
+        }
+    }
+}
+
+/// Serialize a JSON value for writing to `file_path`, encrypted when the path is an
+/// encrypted file. A text file that already exists keeps its line endings and final newline.
+pub fn serialize_for_path(file_path: &str, value: &Value, format: DocumentFormat, pretty: bool) -> anyhow::Result<Vec<u8>> {
+    serialize_with_layout(file_path, value, format, pretty, TextLayout::of_file(file_path, format)?)
+}
+
+/// Serialize a JSON value for writing to `file_path` with the given text `layout`, or as
+/// the serializer lays it out when `None`.
+pub fn serialize_with_layout(
+    file_path: &str,
+    value: &Value,
+    format: DocumentFormat,
+    pretty: bool,
+    layout: Option<TextLayout>,
+) -> anyhow::Result<Vec<u8>> {
+    let mut content = serialize_document(value, format, pretty)?;
+    if let Some(layout) = layout {
+        content = layout.apply(&content);
+    }
+    encryption::seal(file_path, content)
+}
+
+/// Line endings and final newline of a text file, so a rewrite of a file maintained on
+/// Windows does not change every line in version control.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextLayout {
+    pub crlf: bool,
+    pub trailing_newline: bool,
+}
+
+impl TextLayout {
+    /// The layout of `content`: CRLF if its first line ends with one.
+    pub fn detect(content: &[u8]) -> Self {
+        let crlf = content.iter().position(|&b| b == b'\n').is_some_and(|end| end > 0 && content[end - 1] == b'\r');
+        Self { crlf, trailing_newline: content.ends_with(b"\n") }
+    }
+
+    /// The layout of the existing text file at `file_path`, or `None` for a new or binary file.
+    pub fn of_file(file_path: &str, format: DocumentFormat) -> anyhow::Result<Option<Self>> {
+        if matches!(format, DocumentFormat::MessagePack | DocumentFormat::Cbor) || !storage::exists(file_path)? {
+            return Ok(None);
+        }
+        let content = encryption::open(file_path, storage::read(file_path)?)?;
+        Ok(Some(Self::detect(&content)))
+    }
+
+    /// `layout` changed by the `line_endings` and `trailing_newline` arguments. A new file
+    /// without either gets the serializer's layout.
+    pub fn with_overrides(layout: Option<Self>, args: &HashMap<String, Value>) -> Result<Option<Self>, String> {
+        let crlf = match args.get("line_endings").and_then(|v| v.as_str()) {
+            None | Some("preserve") => None,
+            Some("lf") => Some(false),
+            Some("crlf") => Some(true),
+            Some(other) => return Err(format!("Invalid line_endings '{}'. Use 'preserve', 'lf' or 'crlf'", other)),
+        };
+        let trailing_newline = args.get("trailing_newline").and_then(|v| v.as_bool());
+        if crlf.is_none() && trailing_newline.is_none() {
+            return Ok(layout);
+        }
+        let base = layout.unwrap_or(Self { crlf: false, trailing_newline: false });
+        Ok(Some(Self {
+            crlf: crlf.unwrap_or(base.crlf),
+            trailing_newline: trailing_newline.unwrap_or(base.trailing_newline),
+        }))
+    }
+
+    /// `content` with every line break in this layout's style and the final newline added
+    /// or removed.
+    pub fn apply(self, content: &[u8]) -> Vec<u8> {
+        let mut lines: Vec<&[u8]> = content.split(|&b| b == b'\n')
+            .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+            .collect();
+        while lines.len() > 1 && lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        let newline: &[u8] = if self.crlf { b"\r\n" } else { b"\n" };
+        let mut laid_out = lines.join(newline);
+        if self.trailing_newline {
+            laid_out.extend_from_slice(newline);
+        }
+        laid_out
+    }
+}
+
+/// JSON Schema for the `line_endings` argument of tools that rewrite a text file.
+pub fn line_endings_schema() -> Value {
+    serde_json::json!({
+        "type": "string",
+        "enum": ["preserve", "lf", "crlf"],
+        "description": "Line endings of the written file: 'preserve' keeps those of the file being replaced (LF for a new file), 'lf' or 'crlf' (default: preserve)"
+    })
+}
+
+/// JSON Schema for the `trailing_newline` argument of tools that rewrite a text file.
+pub fn trailing_newline_schema() -> Value {
+    serde_json::json!({
+        "type": "boolean",
+        "description": "End the written file with a newline, or not (default: as the file being replaced)"
+    })
+}
+
+/// TOML datetimes have no JSON equivalent and are converted to RFC 3339 strings.
+fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::Number(i.into()),
+        toml::Value::Float(f) => Number::from_f64(f).map(Value::Number).unwrap_or(Value::Null),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(dt) => Value::String(dt.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => Value::Object(
+            table.into_iter().map(|(k, v)| (k, toml_to_json(v))).collect::<Map<String, Value>>(),
+        ),
+    }
+}
+
+fn json_to_toml(value: &Value, path: &str) -> anyhow::Result<toml::Value> {
+    Ok(match value {
+        Value::Null => anyhow::bail!("TOML cannot represent null (at {})", path),
+        Value::Bool(b) => toml::Value::Boolean(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => toml::Value::Integer(i),
+            None if n.is_u64() => anyhow::bail!("Integer {} at {} is larger than TOML's largest integer ({})", n, path, i64::MAX),
+            None => toml::Value::Float(n.as_f64()
+                .ok_or_else(|| anyhow::anyhow!("Number {} at {} cannot be represented in TOML", n, path))?),
+        },
+        Value::String(s) => toml::Value::String(s.clone()),
+        Value::Array(items) => toml::Value::Array(items.iter().enumerate()
+            .map(|(i, item)| json_to_toml(item, &format!("{}[{}]", path, i)))
+            .collect::<anyhow::Result<_>>()?),
+        Value::Object(obj) => toml::Value::Table(obj.iter()
+            .map(|(k, v)| Ok((k.clone(), json_to_toml(v, &format!("{}.{}", path, k))?)))
+            .collect::<anyhow::Result<_>>()?),
+    })
+}
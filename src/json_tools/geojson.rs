@@ -0,0 +1,520 @@
+use crate::json_tools::compare::json_path;
+use crate::json_tools::formats::{read_document, serialize_for_path, DocumentFormat};
+use crate::json_tools::sql::{where_schema, Predicate};
+use crate::json_tools::storage;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+
+/// Errors and warnings listed before the rest are only counted.
+const MAX_ISSUES: usize = 100;
+
+const GEOMETRY_TYPES: &[&str] = &["Point", "MultiPoint", "LineString", "MultiLineString", "Polygon", "MultiPolygon", "GeometryCollection"];
+
+/// What json-validate reports about a GeoJSON document (RFC 7946).
+#[derive(Default)]
+pub struct GeoReport {
+    /// `<JSONPath>: <problem>` for each violation of the format.
+    pub errors: Vec<String>,
+    /// Things the RFC says should not be done but that readers accept, such as clockwise
+    /// exterior rings.
+    pub warnings: Vec<String>,
+    pub features: usize,
+    pub geometries: BTreeMap<String, usize>,
+    /// `[west, south, east, north]` over every position.
+    pub bbox: Option<[f64; 4]>,
+}
+
+impl GeoReport {
+    fn error(&mut self, tokens: &[String], message: String) {
+        if self.errors.len() < MAX_ISSUES {
+            self.errors.push(format!("{}: {}", json_path(tokens), message));
+        }
+    }
+
+    fn warning(&mut self, tokens: &[String], message: String) {
+        if self.warnings.len() < MAX_ISSUES {
+            self.warnings.push(format!("{}: {}", json_path(tokens), message));
+        }
+    }
+
+    fn object(&mut self, value: &Value, tokens: &mut Vec<String>) {
+        let Some(object) = value.as_object() else {
+            return self.error(tokens, format!("expected a GeoJSON object, found {}", value));
+        };
+        match object.get("type") {
+            Some(Value::String(kind)) if kind == "Feature" => self.feature(object, tokens),
+            Some(Value::String(kind)) if kind == "FeatureCollection" => {
+                match object.get("features") {
+                    Some(Value::Array(features)) => {
+                        for (i, feature) in features.iter().enumerate() {
+                            tokens.extend(["features".to_string(), i.to_string()]);
+                            match feature.as_object() {
+                                Some(feature) if feature.get("type") == Some(&json!("Feature")) => self.feature(feature, tokens),
+                                _ => self.error(tokens, "expected a Feature".to_string()),
+                            }
+                            tokens.truncate(tokens.len() - 2);
+                        }
+                    }
+                    _ => self.error(tokens, "a FeatureCollection needs a \"features\" array".to_string()),
+                }
+            }
+            Some(Value::String(kind)) if GEOMETRY_TYPES.contains(&kind.as_str()) => self.geometry(object, tokens),
+            Some(Value::String(kind)) => self.error(tokens, format!("unknown type '{}'", kind)),
+            Some(other) => self.error(tokens, format!("\"type\" must be a string, found {}", other)),
+            None => self.error(tokens, "missing \"type\"".to_string()),
+        }
+        self.bbox_member(object, tokens);
+    }
+
+    fn feature(&mut self, feature: &Map<String, Value>, tokens: &mut Vec<String>) {
+        self.features += 1;
+        tokens.push("geometry".to_string());
+        match feature.get("geometry") {
+            Some(Value::Null) => {}
+            Some(Value::Object(geometry)) if geometry.get("type").and_then(|t| t.as_str()).is_some_and(|t| GEOMETRY_TYPES.contains(&t)) => {
+                self.geometry(geometry, tokens);
+                self.bbox_member(geometry, tokens);
+            }
+            Some(_) => self.error(tokens, "expected a geometry object or null".to_string()),
+            None => {
+                tokens.pop();
+                self.error(tokens, "a Feature needs a \"geometry\" member (null when it has no location)".to_string());
+                tokens.push("geometry".to_string());
+            }
+        }
+        tokens.pop();
+        match feature.get("properties") {
+            Some(Value::Object(_) | Value::Null) => {}
+            Some(_) => {
+                tokens.push("properties".to_string());
+                self.error(tokens, "expected an object or null".to_string());
+                tokens.pop();
+            }
+            None => self.warning(tokens, "a Feature should have a \"properties\" member".to_string()),
+        }
+        if let Some(id) = feature.get("id").filter(|id| !id.is_string() && !id.is_number()) {
+            tokens.push("id".to_string());
+            self.error(tokens, format!("a Feature id must be a string or a number, found {}", id));
+            tokens.pop();
+        }
+    }
+
+    fn geometry(&mut self, geometry: &Map<String, Value>, tokens: &mut Vec<String>) {
+        let kind = geometry.get("type").and_then(|t| t.as_str()).unwrap_or_default();
+        *self.geometries.entry(kind.to_string()).or_default() += 1;
+        if kind == "GeometryCollection" {
+            let Some(Value::Array(members)) = geometry.get("geometries") else {
+                return self.error(tokens, "a GeometryCollection needs a \"geometries\" array".to_string());
+            };
+            for (i, member) in members.iter().enumerate() {
+                tokens.extend(["geometries".to_string(), i.to_string()]);
+                match member.as_object() {
+                    Some(member) if member.get("type").and_then(|t| t.as_str()).is_some_and(|t| GEOMETRY_TYPES.contains(&t)) => {
+                        if member.get("type") == Some(&json!("GeometryCollection")) {
+                            self.warning(tokens, "nested GeometryCollections should be avoided".to_string());
+                        }
+                        self.geometry(member, tokens);
+                    }
+                    _ => self.error(tokens, "expected a geometry".to_string()),
+                }
+                tokens.truncate(tokens.len() - 2);
+            }
+            return;
+        }
+        let Some(coordinates) = geometry.get("coordinates") else {
+            return self.error(tokens, format!("a {} needs \"coordinates\"", kind));
+        };
+        tokens.push("coordinates".to_string());
+        // Multi* geometries are arrays of their single counterparts
+        let single = kind.trim_start_matches("Multi");
+        let parts: Vec<(Vec<String>, &Value)> = match (single == kind, coordinates) {
+            (true, _) => vec![(Vec::new(), coordinates)],
+            (false, Value::Array(parts)) => parts.iter().enumerate().map(|(i, part)| (vec![i.to_string()], part)).collect(),
+            _ => {
+                self.error(tokens, format!("expected an array of {} coordinates", single));
+                Vec::new()
+            }
+        };
+        for (suffix, part) in parts {
+            tokens.extend(suffix.iter().cloned());
+            match single {
+                "Point" => self.position(part, tokens),
+                "LineString" => {
+                    self.positions(part, tokens, 2, "a LineString needs at least two positions");
+                }
+                _ => self.polygon(part, tokens),
+            }
+            tokens.truncate(tokens.len() - suffix.len());
+        }
+        tokens.pop();
+    }
+
+    fn polygon(&mut self, rings: &Value, tokens: &mut Vec<String>) {
+        let Some(rings) = rings.as_array() else {
+            return self.error(tokens, "expected an array of linear rings".to_string());
+        };
+        for (i, ring) in rings.iter().enumerate() {
+            tokens.push(i.to_string());
+            let positions = self.positions(ring, tokens, 4, "a linear ring needs at least four positions");
+            if positions.len() >= 4 {
+                if positions.first() != positions.last() {
+                    self.error(tokens, "the ring is not closed; its last position must equal its first".to_string());
+                } else {
+                    // Right-hand rule: exterior rings counterclockwise, holes clockwise
+                    let counterclockwise = signed_area(&positions) > 0.0;
+                    if counterclockwise != (i == 0) {
+                        let (ring, winding) = if i == 0 { ("exterior", "counterclockwise") } else { ("interior", "clockwise") };
+                        self.warning(tokens, format!("{} rings should be {} (right-hand rule)", ring, winding));
+                    }
+                }
+            }
+            tokens.pop();
+        }
+    }
+
+    /// Check an array of positions, returning the valid ones.
+    fn positions(&mut self, value: &Value, tokens: &mut Vec<String>, minimum: usize, too_few: &str) -> Vec<(f64, f64)> {
+        let Some(items) = value.as_array() else {
+            self.error(tokens, "expected an array of positions".to_string());
+            return Vec::new();
+        };
+        if items.len() < minimum {
+            self.error(tokens, too_few.to_string());
+        }
+        let mut positions = Vec::new();
+        for (i, item) in items.iter().enumerate() {
+            tokens.push(i.to_string());
+            self.position(item, tokens);
+            positions.extend(point(item));
+            tokens.pop();
+        }
+        positions
+    }
+
+    fn position(&mut self, value: &Value, tokens: &[String]) {
+        let numbers: Option<Vec<f64>> = value.as_array().map(|items| items.iter().filter_map(|n| n.as_f64()).collect());
+        let count = value.as_array().map_or(0, Vec::len);
+        let Some(numbers) = numbers.filter(|numbers| numbers.len() == count && count >= 2) else {
+            return self.error(tokens, format!("a position is an array of two or three numbers, found {}", value));
+        };
+        if count > 3 {
+            self.warning(tokens, "positions should have at most three elements".to_string());
+        }
+        let (lon, lat) = (numbers[0], numbers[1]);
+        if !(-180.0..=180.0).contains(&lon) {
+            self.error(tokens, format!("longitude {} is outside -180..180 (coordinates are [longitude, latitude])", lon));
+        }
+        if !(-90.0..=90.0).contains(&lat) {
+            self.error(tokens, format!("latitude {} is outside -90..90 (coordinates are [longitude, latitude])", lat));
+        }
+        let bbox = self.bbox.get_or_insert([lon, lat, lon, lat]);
+        *bbox = [bbox[0].min(lon), bbox[1].min(lat), bbox[2].max(lon), bbox[3].max(lat)];
+    }
+
+    fn bbox_member(&mut self, object: &Map<String, Value>, tokens: &mut Vec<String>) {
+        let Some(bbox) = object.get("bbox") else {
+            return;
+        };
+        tokens.push("bbox".to_string());
+        let numbers: Vec<f64> = bbox.as_array().into_iter().flatten().filter_map(|n| n.as_f64()).collect();
+        let valid = bbox.as_array().is_some_and(|items| items.len() == numbers.len())
+            && numbers.len() >= 4
+            && numbers.len() % 2 == 0;
+        if !valid {
+            self.error(tokens, "a bbox is an array of 2n numbers: all minimums, then all maximums".to_string());
+        } else if numbers[1] > numbers[numbers.len() / 2 + 1] {
+            self.error(tokens, "the bbox's southern latitude is greater than its northern".to_string());
+        }
+        tokens.pop();
+    }
+}
+
+/// Check `document` against RFC 7946.
+pub fn check(document: &Value) -> GeoReport {
+    let mut report = GeoReport::default();
+    report.object(document, &mut Vec::new());
+    report
+}
+
+/// Twice the signed area of a closed ring; positive when it runs counterclockwise.
+fn signed_area(ring: &[(f64, f64)]) -> f64 {
+    ring.windows(2).map(|pair| pair[0].0 * pair[1].1 - pair[1].0 * pair[0].1).sum()
+}
+
+fn point(value: &Value) -> Option<(f64, f64)> {
+    let items = value.as_array()?;
+    Some((items.first()?.as_f64()?, items.get(1)?.as_f64()?))
+}
+
+fn points(value: &Value) -> Vec<(f64, f64)> {
+    value.as_array().into_iter().flatten().filter_map(point).collect()
+}
+
+/// A bounding box that does not cross the antimeridian.
+#[derive(Clone, Copy)]
+struct Rect {
+    west: f64,
+    south: f64,
+    east: f64,
+    north: f64,
+}
+
+impl Rect {
+    /// The query box as one or two boxes; a west edge east of the east edge crosses the
+    /// antimeridian.
+    fn split(bbox: [f64; 4]) -> Vec<Rect> {
+        let [west, south, east, north] = bbox;
+        if west <= east {
+            vec![Rect { west, south, east, north }]
+        } else {
+            vec![Rect { west, south, east: 180.0, north }, Rect { west: -180.0, south, east, north }]
+        }
+    }
+
+    fn contains(&self, (x, y): (f64, f64)) -> bool {
+        (self.west..=self.east).contains(&x) && (self.south..=self.north).contains(&y)
+    }
+
+    fn corners(&self) -> [(f64, f64); 4] {
+        [(self.west, self.south), (self.east, self.south), (self.east, self.north), (self.west, self.north)]
+    }
+
+    fn crosses(&self, a: (f64, f64), b: (f64, f64)) -> bool {
+        if self.contains(a) || self.contains(b) {
+            return true;
+        }
+        let corners = self.corners();
+        (0..4).any(|i| segments_intersect(a, b, corners[i], corners[(i + 1) % 4]))
+    }
+
+    fn intersects(&self, kind: &str, coordinates: &Value) -> bool {
+        let lines_cross = |line: &Value| points(line).windows(2).any(|pair| self.crosses(pair[0], pair[1]));
+        let polygon_overlaps = |polygon: &Value| {
+            let rings: Vec<Vec<(f64, f64)>> = polygon.as_array().into_iter().flatten().map(points).collect();
+            rings.iter().any(|ring| ring.windows(2).any(|pair| self.crosses(pair[0], pair[1])))
+                || in_polygon(self.corners()[0], &rings)
+        };
+        let parts = || coordinates.as_array().into_iter().flatten();
+        match kind {
+            "Point" => point(coordinates).is_some_and(|p| self.contains(p)),
+            "MultiPoint" => points(coordinates).into_iter().any(|p| self.contains(p)),
+            "LineString" => lines_cross(coordinates),
+            "MultiLineString" => parts().any(lines_cross),
+            "Polygon" => polygon_overlaps(coordinates),
+            "MultiPolygon" => parts().any(polygon_overlaps),
+            _ => false,
+        }
+    }
+}
+
+fn orientation(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> f64 {
+    (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+}
+
+fn segments_intersect(a: (f64, f64), b: (f64, f64), c: (f64, f64), d: (f64, f64)) -> bool {
+    let (d1, d2) = (orientation(c, d, a), orientation(c, d, b));
+    let (d3, d4) = (orientation(a, b, c), orientation(a, b, d));
+    ((d1 > 0.0) != (d2 > 0.0) || d1 == 0.0 || d2 == 0.0) && ((d3 > 0.0) != (d4 > 0.0) || d3 == 0.0 || d4 == 0.0)
+        && a.0.min(b.0) <= c.0.max(d.0) && c.0.min(d.0) <= a.0.max(b.0)
+        && a.1.min(b.1) <= c.1.max(d.1) && c.1.min(d.1) <= a.1.max(b.1)
+}
+
+/// Whether `p` is inside a polygon's exterior ring and outside its holes.
+fn in_polygon(p: (f64, f64), rings: &[Vec<(f64, f64)>]) -> bool {
+    let in_ring = |ring: &Vec<(f64, f64)>| {
+        ring.windows(2).filter(|pair| {
+            let (a, b) = (pair[0], pair[1]);
+            (a.1 > p.1) != (b.1 > p.1) && p.0 < (b.0 - a.0) * (p.1 - a.1) / (b.1 - a.1) + a.0
+        }).count() % 2 == 1
+    };
+    rings.first().is_some_and(in_ring) && !rings[1..].iter().any(in_ring)
+}
+
+/// Every position of a geometry, for `within`.
+fn geometry_points(geometry: &Value, found: &mut Vec<(f64, f64)>) {
+    if let Some(members) = geometry.get("geometries").and_then(|g| g.as_array()) {
+        members.iter().for_each(|member| geometry_points(member, found));
+    }
+    fn walk(value: &Value, found: &mut Vec<(f64, f64)>) {
+        match point(value) {
+            Some(p) if value.as_array().is_some_and(|items| items.iter().all(Value::is_number)) => found.push(p),
+            _ => value.as_array().into_iter().flatten().for_each(|item| walk(item, found)),
+        }
+    }
+    if let Some(coordinates) = geometry.get("coordinates") {
+        walk(coordinates, found);
+    }
+}
+
+fn geometry_intersects(geometry: &Value, rects: &[Rect]) -> bool {
+    if let Some(members) = geometry.get("geometries").and_then(|g| g.as_array()) {
+        return members.iter().any(|member| geometry_intersects(member, rects));
+    }
+    let kind = geometry.get("type").and_then(|t| t.as_str()).unwrap_or_default();
+    let coordinates = geometry.get("coordinates").unwrap_or(&Value::Null);
+    rects.iter().any(|rect| rect.intersects(kind, coordinates))
+}
+
+fn write_atomically(path: &str, document: &Value) -> anyhow::Result<Result<(), String>> {
+    let format = DocumentFormat::from_path(path);
+    let content = match serialize_for_path(path, document, format, true) {
+        Ok(content) => content,
+        Err(e) => return Ok(Err(format!("Cannot write '{}' as {}: {}", path, format.name(), e))),
+    };
+    storage::write(path, &content)?;
+    Ok(Ok(()))
+}
+
+pub struct JsonGeo;
+
+impl Default for JsonGeo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonGeo {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn create_query_tool() -> Tool {
+        let mut where_property = where_schema();
+        where_property["description"] = json!(
+            "Filter on each feature's properties, e.g. \"kind = 'park' and area > 1000\"; supports =, !=, <, <=, >, >=, like, matches, in (...), is [not] null, and, or, not"
+        );
+        Tool {
+            name: "json-geo-query".to_string(),
+            description: "Select the features of a GeoJSON file inside or overlapping a bounding box and/or matching a predicate on their properties. Returns a FeatureCollection.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "GeoJSON FeatureCollection or Feature"
+                    },
+                    "bbox": {
+                        "type": "array",
+                        "items": {"type": "number"},
+                        "minItems": 4,
+                        "maxItems": 4,
+                        "description": "[west, south, east, north] in degrees; a west edge greater than the east edge crosses the antimeridian"
+                    },
+                    "spatial": {
+                        "type": "string",
+                        "enum": ["intersects", "within"],
+                        "description": "intersects: any part of the geometry is in the box; within: all of it is (default: intersects)",
+                        "default": "intersects"
+                    },
+                    "where": where_property,
+                    "output_path": {
+                        "type": "string",
+                        "description": "Also write the matching FeatureCollection to this file"
+                    }
+                },
+                "required": ["file_path"]
+            })
+        }
+    }
+
+    async fn handle_query(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let usage = "Usage example:\n{\n  \"file_path\": \"./parks.geojson\",\n  \"bbox\": [2.25, 48.81, 2.42, 48.90],\n  \"where\": \"area > 1000\"\n}";
+        let file_path = args.get("file_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("file_path is required. {}", usage))?;
+        let bbox = match args.get("bbox") {
+            None | Some(Value::Null) => None,
+            Some(bbox) => match bbox.as_array().map(|items| items.iter().filter_map(|n| n.as_f64()).collect::<Vec<_>>()) {
+                Some(numbers) if numbers.len() == 4 && bbox.as_array().map_or(0, Vec::len) == 4 && numbers[1] <= numbers[3] => {
+                    Some([numbers[0], numbers[1], numbers[2], numbers[3]])
+                }
+                _ => return Ok(ToolResult::error(format!(
+                    "Invalid bbox {}; expected [west, south, east, north] with south <= north", bbox
+                ))),
+            },
+        };
+        let predicate = match Predicate::from_args(args) {
+            Ok(predicate) => predicate,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+        if bbox.is_none() && predicate.is_none() {
+            anyhow::bail!("bbox or where is required. {}", usage);
+        }
+        let within = match args.get("spatial").and_then(|v| v.as_str()).unwrap_or("intersects") {
+            "intersects" => false,
+            "within" => true,
+            other => return Ok(ToolResult::error(format!("Invalid spatial '{}'. Use 'intersects' or 'within'", other))),
+        };
+
+        let document = read_document(file_path, DocumentFormat::from_path(file_path))?;
+        let report = check(&document);
+        if !report.errors.is_empty() {
+            return Ok(ToolResult::error(format!(
+                "'{}' is not valid GeoJSON ({} error(s), first: {}). Run json-validate with mode 'geojson' for the full list",
+                file_path, report.errors.len(), report.errors[0]
+            )));
+        }
+        let features = match document.get("type").and_then(|t| t.as_str()) {
+            Some("FeatureCollection") => document["features"].as_array().cloned().unwrap_or_default(),
+            Some("Feature") => vec![document.clone()],
+            _ => return Ok(ToolResult::error(format!(
+                "'{}' holds a bare geometry; json-geo-query selects from a FeatureCollection or Feature", file_path
+            ))),
+        };
+
+        let rects = bbox.map(Rect::split).unwrap_or_default();
+        let total = features.len();
+        let matching: Vec<Value> = features.into_iter()
+            .filter(|feature| {
+                let geometry = &feature["geometry"];
+                if bbox.is_none() {
+                    return true;
+                }
+                if geometry.is_null() {
+                    return false;
+                }
+                if within {
+                    let mut found = Vec::new();
+                    geometry_points(geometry, &mut found);
+                    !found.is_empty() && found.iter().all(|p| rects.iter().any(|rect| rect.contains(*p)))
+                } else {
+                    geometry_intersects(geometry, &rects)
+                }
+            })
+            .filter(|feature| predicate.as_ref().map_or(true, |predicate| {
+                let properties = feature.get("properties").filter(|p| p.is_object()).cloned().unwrap_or_else(|| json!({}));
+                predicate.matches(&properties)
+            }))
+            .collect();
+
+        let count = matching.len();
+        let collection = json!({"type": "FeatureCollection", "features": matching});
+        let mut note = String::new();
+        if let Some(output_path) = args.get("output_path").and_then(|v| v.as_str()) {
+            if let Err(message) = write_atomically(output_path, &collection)? {
+                return Ok(ToolResult::error(message));
+            }
+            note = format!(" (written to '{}')", output_path);
+        }
+        Ok(ToolResult::success(format!(
+            "{} of {} feature(s) in '{}' match{}:\n\n{}", count, total, file_path, note, serde_json::to_string_pretty(&collection)?
+        )))
+    }
+}
+
+#[async_trait]
+impl ToolHandler for JsonGeo {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_query_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-geo-query" => self.handle_query(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use crate::json_tools::compare::{diff, json_path};
+use crate::json_tools::formats::{parse_document, read_document, DocumentFormat};
+use crate::json_tools::patch::parse_pointer;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Extensions of the files json-git-changes compares; other changed files are ignored.
+const DOCUMENT_EXTENSIONS: &[&str] = &["json", "toml", "msgpack", "mpk", "cbor"];
+
+/// Run git in `dir` and return what it printed.
+fn git(dir: &Path, args: &[&str]) -> Result<Vec<u8>, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Cannot run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("git {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(output.stdout)
+}
+
+/// Revisions reach git as arguments, so one that looks like an option is refused.
+fn check_revision(rev: &str) -> Result<&str, String> {
+    if rev.is_empty() || rev.starts_with('-') || rev.contains(char::is_whitespace) {
+        return Err(format!("Invalid revision '{}'. Use a commit, branch, tag or an expression like 'HEAD~1'", rev));
+    }
+    Ok(rev)
+}
+
+/// The directory git runs in for `file_path`, and the file's name within it.
+fn split_file(file_path: &str) -> (PathBuf, String) {
+    let path = Path::new(file_path);
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    (dir, name)
+}
+
+/// `file` as it was at `rev`: a path relative to the repository root, or `./name` for a
+/// file in `dir`.
+fn document_at(dir: &Path, rev: &str, file: &str) -> Result<Value, String> {
+    let content = git(dir, &["show", &format!("{}:{}", rev, file)])?;
+    let format = DocumentFormat::from_path(file);
+    parse_document(&content, format)
+        .map_err(|e| format!("Failed to parse {} in '{}' at {}: {}", format.name().to_uppercase(), file.trim_start_matches("./"), rev, e))
+}
+
+/// The JSONPaths an RFC 6902 patch touches, in patch order.
+fn changed_paths(patch: &[Value]) -> Vec<String> {
+    let mut paths: Vec<String> = Vec::new();
+    for operation in patch {
+        for key in ["from", "path"] {
+            let Some(pointer) = operation.get(key).and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let path = parse_pointer(pointer).map(|tokens| json_path(&tokens)).unwrap_or_else(|_| pointer.to_string());
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+    }
+    paths
+}
+
+pub struct JsonGit;
+
+impl Default for JsonGit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonGit {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn create_show_tool() -> Tool {
+        Tool {
+            name: "json-git-show".to_string(),
+            description: "Read a JSON (or TOML/MessagePack/CBOR) file as it was at a git revision, e.g. rev 'HEAD~1', a branch or a tag.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "File inside a git working copy; it need not exist there any more"
+                    },
+                    "rev": {
+                        "type": "string",
+                        "description": "Revision to read, e.g. 'HEAD~1', 'main' or 'v1.2.0' (default: 'HEAD')",
+                        "default": "HEAD"
+                    }
+                },
+                "required": ["file_path"]
+            })
+        }
+    }
+
+    fn create_diff_tool() -> Tool {
+        Tool {
+            name: "json-git-diff".to_string(),
+            description: "Diff a file's working copy against a git revision as an RFC 6902 patch, with the JSONPaths that changed.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "File inside a git working copy"
+                    },
+                    "rev": {
+                        "type": "string",
+                        "description": "Revision to compare against (default: 'HEAD', the last commit)",
+                        "default": "HEAD"
+                    }
+                },
+                "required": ["file_path"]
+            })
+        }
+    }
+
+    fn create_changes_tool() -> Tool {
+        Tool {
+            name: "json-git-changes".to_string(),
+            description: "Report which JSONPaths changed in each JSON, TOML, MessagePack or CBOR file over a git commit range, e.g. 'v1.0..HEAD'.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "range": {
+                        "type": "string",
+                        "description": "Commit range 'from..to'; 'from..' or a single revision compare with HEAD"
+                    },
+                    "file_path": {
+                        "type": "string",
+                        "description": "Only report this file"
+                    },
+                    "directory": {
+                        "type": "string",
+                        "description": "Report the files under this directory of the working copy"
+                    }
+                },
+                "required": ["range"]
+            })
+        }
+    }
+
+    async fn handle_show(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let file_path = args.get("file_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "file_path is required. Usage example:\n{{\n  \"file_path\": \"./config.json\",\n  \"rev\": \"HEAD~1\"\n}}"
+            ))?;
+        let rev = match check_revision(args.get("rev").and_then(|v| v.as_str()).unwrap_or("HEAD")) {
+            Ok(rev) => rev,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+        let (dir, name) = split_file(file_path);
+        let document = match document_at(&dir, rev, &format!("./{}", name)) {
+            Ok(document) => document,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+        Ok(ToolResult::success(format!(
+            "'{}' at {}:\n\n{}", file_path, rev, serde_json::to_string_pretty(&document)?
+        )))
+    }
+
+    async fn handle_diff(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let file_path = args.get("file_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "file_path is required. Usage example:\n{{\n  \"file_path\": \"./config.json\",\n  \"rev\": \"main\"\n}}"
+            ))?;
+        let rev = match check_revision(args.get("rev").and_then(|v| v.as_str()).unwrap_or("HEAD")) {
+            Ok(rev) => rev,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+        let (dir, name) = split_file(file_path);
+        let before = match document_at(&dir, rev, &format!("./{}", name)) {
+            Ok(document) => document,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+        let current = read_document(file_path, DocumentFormat::from_path(file_path))?;
+
+        let patch = diff(&before, &current, true);
+        let summary = json!({"rev": rev, "changed_paths": changed_paths(&patch), "patch": patch});
+        let verdict = if patch.is_empty() { "is unchanged since" } else { "differs from" };
+        Ok(ToolResult::success(format!(
+            "'{}' {} {}:\n\n{}", file_path, verdict, rev, serde_json::to_string_pretty(&summary)?
+        )))
+    }
+
+    async fn handle_changes(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let usage = "Usage example:\n{\n  \"range\": \"v1.0..HEAD\",\n  \"directory\": \"./config\"\n}";
+        let range = args.get("range")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("range is required. {}", usage))?;
+        let (from, to) = match range.split_once("..") {
+            Some((from, to)) if !to.starts_with('.') => (from, if to.is_empty() { "HEAD" } else { to }),
+            Some(_) => return Ok(ToolResult::error(format!(
+                "Symmetric ranges like '{}' are not supported; use 'from..to'", range
+            ))),
+            None => (range, "HEAD"),
+        };
+        let (from, to) = match (check_revision(from), check_revision(to)) {
+            (Ok(from), Ok(to)) => (from, to),
+            (Err(message), _) | (_, Err(message)) => return Ok(ToolResult::error(message)),
+        };
+        let (dir, pathspec) = match (args.get("file_path").and_then(|v| v.as_str()), args.get("directory").and_then(|v| v.as_str())) {
+            (Some(file_path), None) => split_file(file_path),
+            (None, Some(directory)) => (PathBuf::from(directory), ".".to_string()),
+            (Some(_), Some(_)) => return Ok(ToolResult::error("Provide either file_path or directory, not both".to_string())),
+            (None, None) => anyhow::bail!("file_path or directory is required. {}", usage),
+        };
+
+        let listing = git(&dir, &["diff", "--name-status", "--no-renames", "-z", from, to, "--", &pathspec]);
+        let root = git(&dir, &["rev-parse", "--show-toplevel"]);
+        let (listing, root) = match (listing, root) {
+            (Ok(listing), Ok(root)) => (listing, PathBuf::from(String::from_utf8_lossy(&root).trim())),
+            (Err(message), _) | (_, Err(message)) => return Ok(ToolResult::error(message)),
+        };
+        let fields: Vec<String> = listing.split(|&b| b == 0)
+            .filter(|field| !field.is_empty())
+            .map(|field| String::from_utf8_lossy(field).into_owned())
+            .collect();
+
+        let mut files = Vec::new();
+        for entry in fields.chunks(2) {
+            let [status, file] = entry else {
+                continue;
+            };
+            let extension = Path::new(file).extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase());
+            if !extension.is_some_and(|e| DOCUMENT_EXTENSIONS.contains(&e.as_str())) {
+                continue;
+            }
+            let read = |rev: &str| document_at(&root, rev, file);
+            let (status, before, after) = match status.as_str() {
+                "A" => ("added", Ok(Value::Null), read(to)),
+                "D" => ("deleted", read(from), Ok(Value::Null)),
+                _ => ("modified", read(from), read(to)),
+            };
+            let mut item = json!({"file": file, "status": status});
+            match (before, after) {
+                (Ok(before), Ok(after)) if status == "modified" => {
+                    item["changed_paths"] = json!(changed_paths(&diff(&before, &after, true)));
+                }
+                (Ok(_), Ok(_)) => {}
+                (Err(message), _) | (_, Err(message)) => item["error"] = json!(message),
+            }
+            files.push(item);
+        }
+
+        Ok(ToolResult::success(format!(
+            "{} document(s) changed between {} and {}:\n\n{}", files.len(), from, to, serde_json::to_string_pretty(&json!({"files": files}))?
+        )))
+    }
+}
+
+#[async_trait]
+impl ToolHandler for JsonGit {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_show_tool(), Self::create_diff_tool(), Self::create_changes_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-git-show" => self.handle_show(&tool_call.arguments).await,
+            "json-git-diff" => self.handle_diff(&tool_call.arguments).await,
+            "json-git-changes" => self.handle_changes(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
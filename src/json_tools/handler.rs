@@ -1,13 +1,74 @@
-use crate::json_tools::{operations::JsonOperations, query::JsonQuery, streaming::JsonStreaming};
-use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::json_tools::{
+    batch, binary::JsonBinary, jobs::{self, JobRegistry}, check::JsonCheck, codegen::JsonCodegen, compare::JsonCompare, config::ToolConfig, help, hints::RecoveryHint, i18n::Language, paths::PathPolicy, plugins::PluginRegistry, convert::JsonConvert, dates::JsonDates, describe::JsonDescribe, discovery::JsonDiscovery, embedded::JsonEmbedded, files::{overwrite_schema, refuse_overwrite, JsonFiles}, geojson::JsonGeo, integrity::JsonIntegrity, journal::Journal, jsonld::JsonLd, operations::JsonOperations, outputs::TempOutputs,
+    jsonpath::JsonPathDialect, migrate::JsonMigrate, mock::JsonMock, openapi::JsonOpenApi, query::JsonQuery,
+    relational::JsonRelational, resources, schemadiff::JsonSchemaDiff, script::JsonScript, snapshot::JsonSnapshot, sql::JsonSql, stats::{FileProbe, FileTraffic, ServerStats}, storage, streaming::JsonStreaming, template::JsonTemplate,
+    transaction::JsonTransaction, units::JsonUnits, update::JsonUpdate, upload::JsonUpload,
+};
+#[cfg(feature = "git")]
+use crate::json_tools::git::JsonGit;
+#[cfg(feature = "sqlite")]
+use crate::json_tools::sqlite::JsonSqlite;
+#[cfg(feature = "wasm")]
+use crate::json_tools::wasm::WasmTransforms;
+use crate::mcp::protocol::{Resource, ResourceContents, Tool, ToolCall, ToolResult};
 use crate::mcp::server::ToolHandler;
 use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
 
 pub struct JsonToolsHandler {
     operations: JsonOperations,
     query: JsonQuery,
     streaming: JsonStreaming,
+    check: JsonCheck,
+    files: JsonFiles,
+    convert: JsonConvert,
+    sql: JsonSql,
+    relational: JsonRelational,
+    template: JsonTemplate,
+    script: JsonScript,
+    integrity: JsonIntegrity,
+    transaction: JsonTransaction,
+    snapshot: JsonSnapshot,
+    discovery: JsonDiscovery,
+    embedded: JsonEmbedded,
+    update: JsonUpdate,
+    dates: JsonDates,
+    units: JsonUnits,
+    migrate: JsonMigrate,
+    compare: JsonCompare,
+    schema_diff: JsonSchemaDiff,
+    openapi: JsonOpenApi,
+    json_ld: JsonLd,
+    geo: JsonGeo,
+    codegen: JsonCodegen,
+    mock: JsonMock,
+    #[cfg(feature = "git")]
+    git: JsonGit,
+    #[cfg(feature = "sqlite")]
+    sqlite: JsonSqlite,
+    #[cfg(feature = "wasm")]
+    wasm: WasmTransforms,
+    describe: JsonDescribe,
+    binary: JsonBinary,
+    upload: JsonUpload,
+    jobs: JobRegistry,
+    plugins: PluginRegistry,
+    paths: PathPolicy,
+    dialect: JsonPathDialect,
+    journal: Option<Journal>,
+    tool_config: ToolConfig,
+    language: Language,
+    temp_outputs: TempOutputs,
+    stats: Arc<ServerStats>,
+}
+
+impl Default for JsonToolsHandler {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl JsonToolsHandler {
@@ -16,6 +77,207 @@ impl JsonToolsHandler {
             operations: JsonOperations::new(),
             query: JsonQuery::new(),
             streaming: JsonStreaming::new(),
+            check: JsonCheck::new(),
+            files: JsonFiles::new(),
+            convert: JsonConvert::new(),
+            sql: JsonSql::new(),
+            relational: JsonRelational::new(),
+            template: JsonTemplate::new(),
+            script: JsonScript::new(),
+            integrity: JsonIntegrity::new(),
+            transaction: JsonTransaction::new(),
+            snapshot: JsonSnapshot::new(),
+            discovery: JsonDiscovery::new(),
+            embedded: JsonEmbedded::new(),
+            update: JsonUpdate::new(),
+            dates: JsonDates::new(),
+            units: JsonUnits::new(),
+            migrate: JsonMigrate::new(),
+            compare: JsonCompare::new(),
+            schema_diff: JsonSchemaDiff::new(),
+            openapi: JsonOpenApi::new(),
+            json_ld: JsonLd::new(),
+            geo: JsonGeo::new(),
+            codegen: JsonCodegen::new(),
+            mock: JsonMock::new(),
+            #[cfg(feature = "git")]
+            git: JsonGit::new(),
+            #[cfg(feature = "sqlite")]
+            sqlite: JsonSqlite::new(),
+            #[cfg(feature = "wasm")]
+            wasm: WasmTransforms::new(),
+            describe: JsonDescribe::new(),
+            binary: JsonBinary::new(),
+            upload: JsonUpload::new(),
+            jobs: JobRegistry::new(),
+            plugins: PluginRegistry::default(),
+            paths: PathPolicy::default(),
+            dialect: JsonPathDialect::default(),
+            journal: None,
+            tool_config: ToolConfig::default(),
+            language: Language::default(),
+            temp_outputs: TempOutputs::default(),
+            stats: Arc::new(ServerStats::new()),
+        }
+    }
+
+    /// Counters reported by json-server-stats.
+    pub fn stats(&self) -> &ServerStats {
+        &self.stats
+    }
+
+    /// The counters themselves, for reporting them from outside the handler (telemetry).
+    pub fn shared_stats(&self) -> Arc<ServerStats> {
+        Arc::clone(&self.stats)
+    }
+
+    /// Resolve relative paths and restrict file access according to `paths`.
+    pub fn with_path_policy(mut self, paths: PathPolicy) -> Self {
+        self.discovery = JsonDiscovery::with_roots(paths.roots());
+        self.files = JsonFiles::with_path_policy(paths.clone());
+        self.snapshot = JsonSnapshot::with_path_policy(paths.clone());
+        self.migrate = JsonMigrate::with_path_policy(paths.clone());
+        self.paths = paths;
+        self
+    }
+
+    /// Evaluate json-query, json-extract, json-update and json-convert-units with `dialect` unless a call picks another.
+    pub fn with_jsonpath_dialect(mut self, dialect: JsonPathDialect) -> Self {
+        self.query = JsonQuery::with_dialect(dialect);
+        self.update = JsonUpdate::with_dialect(dialect);
+        self.units = JsonUnits::with_dialect(dialect);
+        self.dialect = dialect;
+        self
+    }
+
+    /// Journal json-write and json-format through `journal` (see `--journal-dir`).
+    pub fn with_journal(mut self, journal: Journal) -> Self {
+        self.operations = JsonOperations::with_journal(journal.clone());
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Disable and alias tools as `tool_config` says (see `--config`).
+    pub fn with_tool_config(mut self, tool_config: ToolConfig) -> Self {
+        self.tool_config = tool_config;
+        self
+    }
+
+    /// Offer and proxy the tools of external plugins (see `--plugin-dir`).
+    pub fn with_plugins(mut self, plugins: PluginRegistry) -> Self {
+        self.plugins = plugins;
+        self
+    }
+
+    /// Run json-custom-transform with these WebAssembly modules (see `--wasm-dir`).
+    #[cfg(feature = "wasm")]
+    pub fn with_wasm_transforms(mut self, wasm: WasmTransforms) -> Self {
+        self.wasm = wasm;
+        self
+    }
+
+    /// Answer json-help and common errors in `language` unless a call asks for another.
+    pub fn with_language(mut self, language: Language) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// Write `temp_output` results under `temp_outputs` (see `--temp-dir`).
+    pub fn with_temp_outputs(mut self, temp_outputs: TempOutputs) -> Self {
+        self.temp_outputs = temp_outputs;
+        self
+    }
+
+    /// Where `temp_output` results go, for sweeping expired ones from outside the handler.
+    pub fn temp_outputs(&self) -> &TempOutputs {
+        &self.temp_outputs
+    }
+
+    /// A handler with the same configuration and counters, for running background jobs.
+    /// Sessions (chunked writes, read cursors, jobs) are not shared.
+    pub fn detached(&self) -> Self {
+        let mut handler = Self::new()
+            .with_path_policy(self.paths.clone())
+            .with_jsonpath_dialect(self.dialect);
+        if let Some(journal) = &self.journal {
+            handler = handler.with_journal(journal.clone());
+        }
+        handler.tool_config = self.tool_config.clone();
+        handler.plugins = self.plugins.clone();
+        #[cfg(feature = "wasm")]
+        {
+            handler.wasm = self.wasm.clone();
+        }
+        handler.language = self.language;
+        handler.temp_outputs = self.temp_outputs.clone();
+        handler.stats = Arc::clone(&self.stats);
+        handler
+    }
+
+    /// Help topics, per-tool help and runnable examples, offered as MCP resources.
+    pub async fn resources(&self) -> anyhow::Result<Vec<Resource>> {
+        Ok(resources::list(&self.get_tools().await?, Self::help_topic))
+    }
+
+    pub async fn read_resource(&self, uri: &str) -> anyhow::Result<Option<ResourceContents>> {
+        Ok(resources::read(&self.get_tools().await?, Self::help_topic, uri))
+    }
+
+    async fn dispatch(&self, tool_call: ToolCall, language: Language) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            // Plugins cannot offer built-in names, and their names may share built-in prefixes
+            name if self.plugins.handles(name) => self.plugins.call(tool_call).await,
+            "json-help" => self.handle_json_help(&tool_call.arguments, language).await,
+            "json-server-stats" => self.stats.handle_stats(&tool_call.arguments),
+            "json-job-start" | "json-job-status" | "json-job-result" => jobs::handle_job_tool(self, &self.jobs, tool_call).await,
+            "json-validate-query" => self.query.call_tool(tool_call).await,
+            "json-write-begin" | "json-write-chunk" | "json-write-commit" => self.upload.call_tool(tool_call).await,
+            name if name.starts_with("json-write") || name.starts_with("json-validate") || name.starts_with("json-format") => {
+                self.operations.call_tool(tool_call).await
+            },
+            name if name.starts_with("json-query") || name.starts_with("json-extract") => {
+                self.query.call_tool(tool_call).await
+            },
+            name if name.starts_with("json-read") || name.starts_with("json-tail") || name == "json-children" => {
+                self.streaming.call_tool(tool_call).await
+            },
+            name if name.starts_with("json-check") => {
+                self.check.call_tool(tool_call).await
+            },
+            name if name.starts_with("json-split") || name.starts_with("json-concat") => {
+                self.files.call_tool(tool_call).await
+            },
+            "csv-to-json" | "xml-to-json" | "json-to-xml" => self.convert.call_tool(tool_call).await,
+            "json-sql" => self.sql.call_tool(tool_call).await,
+            "json-join" | "json-group" | "json-dedupe" => self.relational.call_tool(tool_call).await,
+            "json-template" => self.template.call_tool(tool_call).await,
+            "json-script" => self.script.call_tool(tool_call).await,
+            "json-hash" => self.integrity.call_tool(tool_call).await,
+            "json-transaction" => self.transaction.call_tool(tool_call).await,
+            "json-snapshot" | "json-restore" => self.snapshot.call_tool(tool_call).await,
+            "json-batch" => batch::run_batch(self, &tool_call.arguments).await,
+            "json-list-files" => self.discovery.call_tool(tool_call).await,
+            "json-escape" | "json-unescape" => self.embedded.call_tool(tool_call).await,
+            "json-update" => self.update.call_tool(tool_call).await,
+            "json-normalize-dates" => self.dates.call_tool(tool_call).await,
+            "json-convert-units" => self.units.call_tool(tool_call).await,
+            "json-migrate" => self.migrate.call_tool(tool_call).await,
+            "json-equal" | "json-merge3" | "json-diff" => self.compare.call_tool(tool_call).await,
+            "json-schema-diff" => self.schema_diff.call_tool(tool_call).await,
+            "json-openapi-validate" => self.openapi.call_tool(tool_call).await,
+            "json-ld-expand" | "json-ld-compact" | "json-ld-flatten" => self.json_ld.call_tool(tool_call).await,
+            "json-geo-query" => self.geo.call_tool(tool_call).await,
+            "json-codegen" => self.codegen.call_tool(tool_call).await,
+            "json-mock" => self.mock.call_tool(tool_call).await,
+            #[cfg(feature = "git")]
+            "json-git-show" | "json-git-diff" | "json-git-changes" => self.git.call_tool(tool_call).await,
+            #[cfg(feature = "sqlite")]
+            "json-sqlite-load" | "json-sqlite-query" => self.sqlite.call_tool(tool_call).await,
+            #[cfg(feature = "wasm")]
+            "json-custom-transform" => self.wasm.call_tool(tool_call).await,
+            "json-describe" => self.describe.call_tool(tool_call).await,
+            "json-base64-extract" | "json-base64-embed" => self.binary.call_tool(tool_call).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
         }
     }
 
@@ -30,6 +292,15 @@ impl JsonToolsHandler {
                         "type": "string",
                         "description": "Specific topic to get help about. Options: 'overview', 'reading', 'writing', 'querying', 'streaming', 'examples', 'tools'",
                         "enum": ["overview", "reading", "writing", "querying", "streaming", "examples", "tools"]
+                    },
+                    "format": {
+                        "type": "string",
+                        "description": "'markdown' (default) for text, or 'json' for structured help that client UIs can render: every tool's parameters with types, required flags, defaults and examples (topic is ignored)",
+                        "enum": ["markdown", "json"]
+                    },
+                    "tool": {
+                        "type": "string",
+                        "description": "With format 'json', describe only this tool"
                     }
                 },
                 "required": []
@@ -37,12 +308,43 @@ impl JsonToolsHandler {
         }
     }
 
-    async fn handle_json_help(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+    async fn handle_json_help(&self, args: &HashMap<String, Value>, language: Language) -> anyhow::Result<ToolResult> {
+        match args.get("format").and_then(|v| v.as_str()).unwrap_or("markdown") {
+            "markdown" => {}
+            "json" => {
+                let tools = self.get_tools().await?;
+                let tool = args.get("tool").and_then(|v| v.as_str());
+                let tools_topic = Self::help_topic("tools").unwrap_or_default();
+                return Ok(match help::structured_help(&tools, tools_topic, tool) {
+                    Some(help) => ToolResult::success(serde_json::to_string_pretty(&help)?),
+                    None => ToolResult::error(format!(
+                        "Unknown tool: {}. Call json-help with format 'json' and no tool to list them all",
+                        tool.unwrap_or_default()
+                    )),
+                });
+            }
+            other => return Ok(ToolResult::error(format!("Unknown help format '{}'. Use 'markdown' or 'json'", other))),
+        }
+
         let topic = args.get("topic")
             .and_then(|v| v.as_str())
             .unwrap_or("overview");
+        if let (true, Some(overview)) = (topic == "overview", language.overview()) {
+            return Ok(ToolResult::success(overview.to_string()));
+        }
+        let Some(help_text) = Self::help_topic(topic) else {
+            return Ok(ToolResult::success(language.unknown_help_topic().to_string()));
+        };
 
-        let help_text = match topic {
+        Ok(ToolResult::success(match language.english_only_note() {
+            Some(note) => format!("{}\n\n{}", note, help_text),
+            None => help_text.to_string(),
+        }))
+    }
+
+    /// Markdown text of a json-help topic.
+    fn help_topic(topic: &str) -> Option<&'static str> {
+        Some(match topic {
             "overview" => {
                 r#"# JSON MCP Server Help
 
@@ -52,14 +354,101 @@ This server provides comprehensive JSON file operations for LLMs. Available tool
 - **json-read**: Read and parse JSON files of any size with automatic streaming for large files
 - **json-write**: Write or update JSON files with various merge strategies  
 - **json-query**: Query JSON files using JSONPath expressions
-- **json-validate**: Validate JSON structure and content
+- **json-validate**: Validate JSON structure and content, or GeoJSON with `mode: "geojson"`
+- **json-extract**: Write JSONPath query results to a new JSON/NDJSON file
+- **json-split**: Shard a large array or NDJSON file into multiple files
+- **json-concat**: Concatenate, deep-merge or zip multiple files into one
+- **csv-to-json**: Convert CSV/TSV files into a JSON array or NDJSON file
+- **xml-to-json** / **json-to-xml**: Convert between XML and JSON
+- **json-sql**: Query arrays of objects with a small SQL subset (SELECT ... FROM $.path WHERE ...)
+- **json-join**: Join two arrays of records on key fields (inner/left/right/outer)
+- **json-group**: Group records by fields and compute count/sum/avg/min/max per group
+- **json-dedupe**: Remove duplicate records (whole values or key fields), streaming NDJSON
+- **json-template**: Render a Jinja-style template with JSON data into a JSON or text file
+- **json-script**: Transform JSON with a sandboxed script when JSONPath is not enough (computed fields, restructuring)
+- **json-hash**: SHA-256 of raw bytes and canonical (JCS) form; compare files semantically
+- **json-transaction**: Apply writes and patches across several files atomically (all or nothing)
+- **json-snapshot** / **json-restore**: Capture a directory of JSON files and restore it later
+- **json-batch**: Run several tool calls in one request, feeding outputs into later steps
+- **json-list-files**: List JSON/NDJSON/YAML/TOML files with size, mtime and a validity flag
+- **json-format**: Pretty-print with a chosen indent or minify a file, in place or to output_path
+- **json-unescape** / **json-escape**: Decode string-encoded JSON into structured JSON, and back
+- **json-tail**: Last N records of an NDJSON file, optionally following appends for a bounded time
+- **json-children**: List the children of a huge object with their sizes, then descend or fetch one
+- **json-validate-query**: Check JSONPath syntax without running it, with suggested fixes
+- **json-update**: Set, increment, replace text in or delete every node a JSONPath selects, atomically
+- **json-normalize-dates**: Rewrite epoch, RFC 2822 and locale-style timestamps as RFC 3339, reporting conversions per path
+- **json-convert-units**: Convert numbers at given JSONPaths between units (ms->s, bytes->MB, cents->dollars) and rewrite the file
+- **json-migrate**: Apply a migration spec (rename, move, change default, split) to one file or a glob of files, with per-file changes
+- **json-equal**: Decide whether two documents are semantically equal (key order ignored, optional float tolerance and array-order insensitivity), with the first difference
+- **json-merge3**: Three-way merge of two edited copies against their base, listing conflicts with paths and all three values
+- **json-diff**: Compute an RFC 6902 patch (with move detection) that turns one document into another, to review before applying
+- **json-schema-diff**: Classify the changes between two JSON Schema versions as compatible or breaking
+- **json-openapi-validate**: Validate a request or response payload against an operation's schema in an OpenAPI 3.x document
+- **json-ld-expand** / **json-ld-compact** / **json-ld-flatten**: Normalize JSON-LD documents to full IRIs, to the terms of a given context, or to a flat list of nodes
+- **json-geo-query**: Select GeoJSON features by bounding box and/or a predicate on their properties
+- **json-codegen**: Generate TypeScript interfaces or Rust serde structs from a JSON Schema or sample data
+- **json-mock**: Generate realistic variants of a sample response for seeding test environments
+- **json-git-show** / **json-git-diff** / **json-git-changes**: Read a file at a git revision, diff the working copy against one, or list the JSONPaths changed in a commit range (requires the `git` feature)
+- **json-sqlite-load** / **json-sqlite-query**: Load records into a SQLite table and run SQL against it, with JSON1 functions for nested fields, exporting the rows to JSON (requires the `sqlite` feature)
+- **json-custom-transform**: Transform JSON with a sandboxed WebAssembly module the server loaded (requires the `wasm` feature)
+- **json-describe**: List a file's fields with types, optionality, enum-like values and numeric ranges
+- **json-base64-extract** / **json-base64-embed**: Write a base64 field's bytes to a file, or encode a file into a field
+- **json-write-begin** / **json-write-chunk** / **json-write-commit**: Upload a document too large for one call in pieces, validated before it is written
+- **json-check**: Evaluate assertions (exists, equals, contains, length) before acting
+- **json-job-start** / **json-job-status** / **json-job-result**: Run a slow tool call in the background and poll for its result
+- **json-server-stats**: Requests served, per-tool latency histograms, errors and bytes read/written
 - **json-help**: Get help about tools (this tool)
 
 ## Required Parameters by Tool:
 - **json-read**: `file_path` (required)
 - **json-write**: `file_path`, `data` (both required)
-- **json-query**: `file_path`, `query` (both required)
-- **json-validate**: `file_path` (required)
+- **json-query**: `query` plus `file_path` or inline `content`
+- **json-validate**: `file_path` or inline `content`
+- **json-extract**: `file_path`, `query`, `output_path` (all required)
+- **json-split**: `file_path` and one of `max_records` / `parts`
+- **json-concat**: `file_paths`, `output_path` (both required)
+- **csv-to-json**: `file_path`, `output_path` (both required)
+- **xml-to-json** / **json-to-xml**: `file_path` (required)
+- **json-sql**: `file_path`, `query` (both required)
+- **json-join**: `left_file`, `left_key` (both required)
+- **json-group**: `file_path`, `group_by` (both required)
+- **json-dedupe**: `file_path` (required)
+- **json-template**: `template_path` or `template` (one required)
+- **json-script**: `expression` or `script`, plus `file_path` or inline `data`
+- **json-hash**: `file_path` or `file_paths` (one required)
+- **json-transaction**: `operations` (required)
+- **json-snapshot** / **json-restore**: `directory` (required)
+- **json-batch**: `steps` (required)
+- **json-list-files**: none (`directory` optional)
+- **json-format**: `file_path` (required)
+- **json-unescape** / **json-escape**: `content` or `file_path` (one required)
+- **json-tail**: `file_path` (required)
+- **json-children**: `file_path` (required; `path` defaults to `$`)
+- **json-validate-query**: `query` (required)
+- **json-update**: `file_path`, `query` (both required), plus `value` for set or `find` for replace
+- **json-normalize-dates**: `file_path` (required)
+- **json-convert-units**: `file_path`, `conversions` (both required)
+- **json-migrate**: `migration` or `migration_path`, plus `file_path`, `file_paths` or `directory` with `pattern`
+- **json-equal**: `left_file` or `left_content`, and `right_file` or `right_content`
+- **json-merge3**: `base_file`, `ours_file`, `theirs_file` (all required)
+- **json-diff**: `left_file` or `left_content`, and `right_file` or `right_content`
+- **json-schema-diff**: `old_file` or `old_content`, and `new_file` or `new_content`
+- **json-openapi-validate**: `spec_path`, `operation_id` (or `method` and `path`), and `file_path` or `content`
+- **json-ld-expand** / **json-ld-flatten**: `file_path` or `content`; **json-ld-compact**: also `context` or `context_path`
+- **json-geo-query**: `file_path`, and `bbox` and/or `where`
+- **json-codegen**: `target`, and `schema_path`, `schema` or `file_path`
+- **json-mock**: `file_path` or `rules`
+- **json-git-show** / **json-git-diff**: `file_path` (required); **json-git-changes**: `range`, plus `file_path` or `directory`
+- **json-sqlite-load**: `file_path`, `database_path`, `table`; **json-sqlite-query**: `database_path`, `query`
+- **json-custom-transform**: `module`, plus `file_path` or inline `data`
+- **json-describe**: `file_path` (required)
+- **json-base64-extract**: `file_path`, `pointer`, `output_path` (all required)
+- **json-base64-embed**: `file_path`, `pointer`, `binary_path` (all required)
+- **json-write-begin**: `file_path`; **json-write-chunk**: `session_id`, `data`; **json-write-commit**: `session_id`
+- **json-job-start**: `tool` (required), `arguments`; **json-job-status**: optional `job_id`; **json-job-result**: `job_id`
+- **json-check**: `file_path`, `checks` (both required)
+- **json-server-stats**: none (`format` optional)
 - **json-help**: none (all parameters optional)
 
 ## Quick Start Examples:
@@ -72,12 +461,18 @@ This server provides comprehensive JSON file operations for LLMs. Available tool
 ```
 
 ## Key Features:
+- TOML, MessagePack and CBOR files can be read, queried and written through the JSON data model
 - Support for extremely large JSON files via automatic streaming
 - JSONPath querying for complex data extraction
 - Multiple write/update modes (replace, merge, append)
 - Validation and error handling with detailed diagnostics
 - LLM-optimized responses and formatting
 - Robust error handling for malformed JSON
+- Optional redaction of secrets and PII (`redact`) so config files can be inspected safely
+- Project-relative paths via `base_dir` on any tool (or the server's `--default-base-dir`); `--allowed-root` confines access
+- `~`, `$HOME` and `%USERPROFILE%` in paths expand to the home directory; responses show canonical absolute paths
+- Add `language` ('en', 'es', 'ja', 'zh') to any call for help and common error messages in that language
+- Add `temp_output: true` to any call to get a large result written to a server temp file, deleted after a while, instead of inline
 
 Use 'json-help' with specific topics for detailed guidance:
 - topic: 'reading' - Learn about reading JSON files
@@ -96,9 +491,21 @@ Reads and parses JSON files with automatic streaming for large files.
 **Parameters:**
 - `file_path` (required): Path to JSON file
 - `json_path` (optional): JSONPath to extract specific data
-- `format` (optional): Output format - "pretty", "compact", "raw" (default: "pretty")
 - `offset` (optional): Starting position for streaming (default: 0)
 - `limit` (optional): Maximum number of items to return (default: 1000)
+- `format` (optional): File format - "json", "toml", "msgpack" or "cbor" (default: from the file extension)
+- `expand_env` (optional): `true`, or environment variables (e.g. `["APP_*"]`) narrowing the server's `environment.expand` allowlist, used to expand `${VAR}` / `${VAR:-default}` placeholders in the returned values. Secret-looking variables are shown as `[REDACTED]`
+- `redact` (optional): Mask secrets and PII in the output (`true`, or an array of extra key names)
+- `decode_base64_path` (optional): JSONPath within each result selecting base64 strings to return as `{"base64": {bytes, media_type, sha256}}` instead of the encoded text
+- `lossy` (optional): Replace invalid UTF-8 sequences with U+FFFD instead of failing; their byte offsets are reported as `utf8_replacements`
+- `expand_embedded_json` (optional): Parse strings holding serialized JSON objects or arrays and return them as `{"embedded_json": <parsed>}`
+- `where` (optional): Predicate on each record, e.g. `name matches "^A.*" and age >= 30` (see json-query)
+- `since` / `until` (optional): Keep only records with a timestamp in `[since, until)`; RFC 3339, `YYYY-MM-DD` or epoch seconds/milliseconds
+- `time_field` (optional): Dotted field holding the timestamp (default: first of `timestamp`, `@timestamp`, `time`, `ts`, `date`, `created_at`)
+- `array_path` (optional): Simple JSONPath to an array to paginate (e.g. `$.records`)
+- `page` (optional): 1-based page number when using `array_path` (default: 1)
+- `page_size` (optional): Items per page when using `array_path` (default: 100)
+- `session_id` (optional): Continue an `array_path` read from the `session_id` in the previous page's metadata; the next page is read from the saved parser position instead of re-reading the file up to it
 
 **Examples:**
 ```json
@@ -115,8 +522,19 @@ Reads and parses JSON files with automatic streaming for large files.
   "name": "json-read", 
   "arguments": {
     "file_path": "./users.json",
-    "json_path": "$.users[*].name",
-    "format": "compact"
+    "json_path": "$.users[*].name"
+  }
+}
+```
+
+```json
+{
+  "name": "json-read",
+  "arguments": {
+    "file_path": "./export.json",
+    "array_path": "$.records",
+    "page": 2,
+    "page_size": 50
   }
 }
 ```
@@ -137,15 +555,24 @@ Creates or updates JSON files with flexible merge strategies.
 **Parameters:**
 - `file_path` (required): Path to JSON file
 - `data` (required): JSON data to write
-- `mode` (optional): Write mode - "replace", "merge", "append" (default: "replace")
+- `mode` (optional): Write mode - "replace", "merge", "append", or "create" to fail rather than overwrite an existing file (default: "replace")
+- `overwrite` (optional): false fails rather than touch an existing file (default: true)
 - `create_path` (optional): Create directory if needed (default: true)
 - `backup` (optional): Create backup before writing (default: false)
+- `format` (optional): File format - "json", "toml", "msgpack" or "cbor" (default: from the file extension)
+- `expand_env` (optional): `true`, or environment variables (e.g. `["APP_*"]`) narrowing the server's `environment.expand` allowlist, used to expand `${VAR}` / `${VAR:-default}` placeholders in `data` before writing. Secret-looking variables are refused
+- `if_hash` (optional): The `content_hash` from a previous `json-read`/`json-write`; the write fails with a conflict if the file changed since
+- `return_content` (optional): Include the final document in the result, up to `max_return_bytes` (default: 65536)
+- `special_numbers` (optional): NaN or Infinity in an existing TOML, MessagePack or CBOR file that merge/append reads - "reject" (default), "stringify" or "clamp"
+- `large_numbers` (optional): Integers beyond ±2^53−1 - "keep" (default), "reject", "stringify" or "clamp" to ±9007199254740991
 
 **Write Modes:**
 - **replace**: Completely replace file content
 - **merge**: Merge with existing JSON (objects only)
 - **append**: Append to arrays or create new array
 
+**Encrypted files:** paths ending in `.enc` (AES-256-GCM) or `.age` are written encrypted and read back decrypted by every tool, with the keys from the `encryption` section of the `--config` file; the plaintext never reaches the disk. With `sops_command` configured, SOPS-encrypted JSON and YAML documents are decrypted and re-encrypted through sops the same way.
+
 **Examples:**
 ```json
 {
@@ -166,7 +593,18 @@ Creates or updates JSON files with flexible merge strategies.
     "mode": "append"
   }
 }
-```"#
+```
+
+## Chunked Writes
+When a document is too large to pass as one `data` argument, upload its JSON text in pieces:
+1. `json-write-begin` with `file_path` returns a `session_id`
+2. `json-write-chunk` with `session_id`, `index` (0, 1, ...) and `data` (the next piece of text, split anywhere)
+3. `json-write-commit` with `session_id` (and optionally `sha256` of the whole text) parses the assembled text and writes the file atomically; nothing is written if it is not valid JSON
+
+Pass `abort: true` to json-write-commit to discard a session. Idle sessions expire after 30 minutes.
+
+## Crash Safety
+When the server runs with `--journal-dir`, json-write and json-format journal each write before applying it. A write interrupted by a crash or power loss is completed or rolled back when the server restarts, so files are never left truncated."#
             },
             "querying" => {
                 r#"# Querying JSON with JSONPath
@@ -176,8 +614,28 @@ Execute complex JSONPath queries on JSON files.
 
 **Parameters:**
 - `file_path` (required): Path to JSON file
+- `content` (alternative to `file_path`): Inline JSON text or value you already have, so no temp file is needed
 - `query` (required): JSONPath expression
 - `format` (optional): Output format - "json", "text", "table" (default: "json")
+- `limit` (optional): Maximum number of matches to return (default: all)
+- `offset` (optional): Number of matches to skip (default: 0)
+- `include_paths` (optional): Return matches as `{path, pointer, value}` with the normalized JSONPath (`$['users'][0]`) and JSON Pointer (`/users/0`) of each, for targeted follow-up writes
+- `computed` (optional): Fields added to each object match, as name -> expression over its fields: `{"total": "price * qty", "label": "first + \" \" + last", "day": "created.date(\"%Y-%m-%d\")"}` (json-script syntax)
+- `redact` (optional): `true` masks API keys, tokens, emails, SSNs and values under keys like `password`; an array of key names (e.g. `["pin"]`) masks those keys as well
+- `decode_base64_path` (optional): Describe base64 strings within each match (length, media type, hash) instead of returning them
+- `expand_embedded_json` (optional): Return strings holding serialized JSON objects or arrays (double-encoded log payloads) parsed, as `{"embedded_json": <parsed>}`
+- `key_match` (optional): `"case_insensitive"` resolves `$.Users` to `$.users`; `"fuzzy"` also fixes small typos (`$.usres`). The message and `key_corrections` metadata show the keys actually used
+- `where` (optional): Filter matches after path selection with predicates the bundled JSONPath filters lack: `name matches "^A.*"`, `tags.0 == "x"`, `age >= 30 and not (role in ("bot", "test"))`, `email is not null`. Fields are dotted paths in each match (`@` is the match itself)
+- `since` / `until` / `time_field` (optional): Keep only matches whose timestamp falls in the window, as for json-read; query the records themselves (e.g. `$.events[*]`)
+- `dialect` (optional): `"rfc9535"` evaluates the query strictly per the IETF standard instead of with jsonpath-rust (the server default is set with `--jsonpath-dialect`); metadata reports the `dialect` used
+- `execution` (optional): `"streaming"` evaluates simple paths (keys, indices, `*`) while parsing, `"in_memory"` loads the document; `"auto"` (default) streams JSON and NDJSON files of 8 MiB or more. Metadata reports the `plan` used and why
+
+**Result Metadata:**
+Results from `json-read` and `json-query` are wrapped in an envelope:
+`{"metadata": {"total_matched", "returned", "truncated", "next_offset", "elapsed_ms"}, "results": [...]}`.
+When `truncated` is true, call again with `offset` set to `next_offset`.
+When json-query matches nothing, `metadata.diagnostics` names the longest prefix that still matched, the keys available there (`keys`, or `element_keys` for arrays) and `suggestions` of corrected queries that do return results.
+`json-read` also returns `content_hash`; pass it to `json-write` as `if_hash` to detect concurrent modifications.
 
 **JSONPath Syntax:**
 - `$` - Root element
@@ -227,13 +685,15 @@ The json-read tool automatically handles large files via streaming without loadi
 - Fast queries on massive datasets  
 - Memory-efficient processing
 - Progressive results for interactive use
+- `s3://bucket/key` objects (with the `s3` feature) are streamed in range requests
+- `https://` URLs (with the `http` feature) are streamed as they download; `mem://name` files live in server memory
 
 **Parameters for Large Files:**
 - `file_path` (required): Path to large JSON file
 - `json_path` (optional): JSONPath to filter data during streaming
 - `limit` (optional): Maximum number of results (default: 1000)
 - `offset` (optional): Skip number of results (default: 0)
-- `format` (optional): Output format - "pretty", "compact", "raw"
+- `format` (optional): File format - "json", "toml", "msgpack" or "cbor" (default: from the file extension)
 
 **Examples:**
 ```json
@@ -247,6 +707,29 @@ The json-read tool automatically handles large files via streaming without loadi
 }
 ```
 
+**Paginating a Nested Array:**
+When the root is an object, use `array_path` with `page`/`page_size`. The array is
+streamed without loading the rest of the document, and the response reports
+`total_items`, `total_pages` and `has_more`.
+
+While more pages remain, the metadata includes a `session_id`. Pass it (with the
+same `file_path`) instead of `page` to get the next page: the server seeks to where
+the previous page ended, so each page costs the same however deep it is in a
+multi-GB array. The session ends after the last page, after 30 idle minutes, or
+when the file changes.
+
+```json
+{
+  "name": "json-read",
+  "arguments": {
+    "file_path": "./large-dataset.json",
+    "array_path": "$.records",
+    "page": 1,
+    "page_size": 100
+  }
+}
+```
+
 **Best Practices:**
 - Use specific JSONPath queries to filter early
 - Set reasonable limits for large datasets
@@ -320,42 +803,347 @@ The json-read tool automatically handles large files via streaming without loadi
 ## json-read
 **Purpose**: Read and parse JSON files with automatic streaming
 **Required**: `file_path`
-**Optional**: `query`, `limit`, `offset`
+**Optional**: `query`, `limit`, `offset`, `format`, `array_path`, `page`, `page_size`, `session_id`, `where`, `since`, `until`, `time_field`, `expand_env`, `redact`, `decode_base64_path`, `lossy`, `expand_embedded_json`
 **Example**: `{"file_path": "./data.json", "query": "$.users"}`
 
 ## json-write  
 **Purpose**: Write or update JSON files with various merge strategies
 **Required**: `file_path`, `data`
-**Optional**: `mode`, `create_dirs`, `pretty`
+**Optional**: `mode`, `overwrite` (false fails if the file exists), `create_dirs`, `pretty`, `format`, `expand_env`, `if_hash`, `return_content`, `max_return_bytes`, `special_numbers` ("reject", "stringify", "clamp"), `large_numbers` ("keep", "reject", "stringify", "clamp"), `line_endings` ("preserve", "lf", "crlf"), `trailing_newline`
 **Example**: `{"file_path": "./output.json", "data": {"key": "value"}, "mode": "replace"}`
 
 ## json-query
 **Purpose**: Execute JSONPath queries on JSON files
-**Required**: `file_path`, `query`
-**Optional**: `format`
+**Required**: `file_path` (or inline `content`), `query`
+**Optional**: `format`, `limit`, `offset`, `include_paths`, `computed`, `key_match`, `where`, `since`, `until`, `time_field`, `dialect`, `execution` ("auto", "streaming", "in_memory"), `decode_base64_path`, `expand_embedded_json`
 **Example**: `{"file_path": "./data.json", "query": "$.users[?(@.age > 25)].name"}`
 
 ## json-validate
 **Purpose**: Validate JSON file syntax and structure
+**Required**: `file_path`, or inline JSON text as `content`
+**Optional**: `schema`, `mode` (`json` or `geojson`)
+**GeoJSON**: With `mode: "geojson"` the document is also checked against RFC 7946: object `type`s, Feature `geometry`/`properties`, positions of two or three numbers with longitude in -180..180 and latitude in -90..90, LineStrings of two or more positions, closed linear rings of four or more, and `bbox` members. Errors give the JSONPath of each problem; warnings cover what readers accept but the RFC advises against, such as clockwise exterior rings
+**Example**: `{"file_path": "./data.json"}`, `{"file_path": "./parks.geojson", "mode": "geojson"}`
+
+## json-extract
+**Purpose**: Write query results to a file instead of returning them
+**Required**: `file_path`, `query`, `output_path`
+**Optional**: `output_format` ("json", "ndjson" or "parquet"), `pretty`, `overwrite` (false fails if output_path exists)
+Parquet output requires flat objects and a server built with `--features parquet`.
+**Example**: `{"file_path": "./data.json", "query": "$.users[*]", "output_path": "./users.ndjson", "output_format": "ndjson"}`
+
+## json-split
+**Purpose**: Split a large array or NDJSON file into multiple files with streaming I/O
+**Required**: `file_path`, plus `max_records` or `parts`
+**Optional**: `array_path`, `input_format`, `output_format`, `output_dir`, `output_pattern`
+**Example**: `{"file_path": "./events.ndjson", "max_records": 50000, "output_dir": "./shards"}`
+
+## json-concat
+**Purpose**: Combine multiple files into one without loading all inputs at once
+**Required**: `file_paths`, `output_path`
+**Optional**: `mode` ("concat", "merge", "zip"), `output_format`
+**Example**: `{"file_paths": ["./base.json", "./override.json"], "output_path": "./config.json", "mode": "merge"}`
+
+## csv-to-json
+**Purpose**: Bring CSV/TSV data into the JSON workflow
+**Required**: `file_path`, `output_path`
+**Optional**: `output_format`, `delimiter`, `has_headers` (true/false/"auto"), `infer_types`, `overwrite` (false fails if output_path exists)
+**Example**: `{"file_path": "./sales.csv", "output_path": "./sales.json"}`
+
+## xml-to-json / json-to-xml
+**Purpose**: Convert XML payloads to JSON for querying, and back
+**Required**: `file_path`
+**Optional**: `output_path` (result is returned when omitted), `overwrite` (false fails if output_path exists), `attribute_prefix` (default `@`), `text_key` (default `#text`), `strip_namespaces` (xml-to-json), `root_name` and `pretty` (json-to-xml)
+**Example**: `{"file_path": "./feed.xml", "strip_namespaces": true}`
+
+## json-sql
+**Purpose**: Filter, sort and project arrays of objects using familiar SQL
+**Required**: `file_path`, `query`
+**Optional**: `redact`
+**Syntax**: `SELECT * | field [AS name], ... FROM <JSONPath> [WHERE ...] [ORDER BY field [ASC|DESC], ...] [LIMIT n] [OFFSET n]`
+**WHERE**: `=`, `!=`/`<>`, `<`, `<=`, `>`, `>=`, `LIKE` (`%`, `_`), `MATCHES` (regex), `IN (...)`, `IS [NOT] NULL`, `AND`, `OR`, `NOT`, parentheses. Strings use single quotes; fields are dotted paths (`address.city`)
+**Example**: `{"file_path": "./users.json", "query": "SELECT name, age FROM $.users WHERE age > 25 ORDER BY name LIMIT 10"}`
+
+## json-join
+**Purpose**: Correlate records from two exports by a shared key
+**Required**: `left_file`, `left_key` (field or array of fields, dotted paths allowed)
+**Optional**: `right_file` (default: left_file), `left_array_path`/`right_array_path` (default `$`), `right_key` (default: left_key), `mode` ("inner", "left", "right", "outer"), `output_shape` ("nested" or "merged"), `output_path`
+**Example**: `{"left_file": "./orders.json", "right_file": "./customers.json", "left_key": "customer_id", "right_key": "id", "mode": "left"}`
+
+## json-group
+**Purpose**: Summarize records per category (group-by / pivot)
+**Required**: `file_path`, `group_by` (field or array of fields)
+**Optional**: `array_path`, `aggregates` (array of `{op, field, as}` with op count/sum/avg/min/max; default count), `output` ("table" or "nested"), `output_path`
+**Example**: `{"file_path": "./sales.json", "group_by": ["region", "year"], "aggregates": [{"op": "sum", "field": "amount"}, {"op": "count"}]}`
+
+## json-dedupe
+**Purpose**: Drop repeated records, keeping the first occurrence
+**Required**: `file_path`
+**Optional**: `array_path`, `key` (field or array of fields; default compares whole values), `output_path` (default: update the file in place)
+**Example**: `{"file_path": "./events.ndjson", "key": ["user_id", "event"], "output_path": "./unique.ndjson"}`
+
+## json-template
+**Purpose**: Generate configs or reports from data (minijinja / Jinja2 syntax)
+**Required**: `template_path` or inline `template`
+**Optional**: `data_path` (context file), `data` (inline context, merged over data_path), `output_path`, `output_format` ("json" validates and pretty-prints, "text" is verbatim)
+**Tip**: Use the `tojson` filter to insert values into JSON templates: `{"name": {{ user.name|tojson }}}`
+**Example**: `{"template_path": "./report.md.j2", "data_path": "./stats.json", "output_path": "./report.md"}`
+
+## json-script
+**Purpose**: Transformations JSONPath cannot express: conditional restructuring, computed fields, regrouping
+**Required**: `expression` (one value) or `script` (statements whose output is JSON), plus `file_path` or inline `data`
+**Optional**: `output_path`, `max_steps` (default 1000000), `max_output_bytes` (default 10 MB)
+**Syntax**: [rhai](https://rhai.rs); the input is `data` and the value of the last expression is the result. Objects are `#{key: value}` maps, arrays have `filter`, `map`, `reduce` and `sort`, and `date(value, format)` formats timestamps
+**Limits**: Strings up to 10 MB, arrays and objects up to 1000000 items, function calls 32 deep; no file, network or module access
+**Example**: `{"file_path": "./users.json", "expression": "data.users.filter(|u| u.active).map(|u| u.email)"}`
+
+## json-hash
+**Purpose**: Verify a file has not changed, or that differently formatted files hold the same data
+**Required**: `file_path`, or `file_paths` to hash and compare several files
+**Returns**: `sha256` of the raw bytes and `canonical_sha256` of the RFC 8785 (JCS) form; `semantically_equal` when comparing
+**Example**: `{"file_paths": ["./config.json", "./config.min.json"]}`
+
+## json-transaction
+**Purpose**: Update related files together so they never end up half-updated
+**Required**: `operations` (array of `{file_path, action, data | patch, if_hash}`)
+**Actions**: `write` (replace with `data`), `merge_patch` (RFC 7386; null removes a key), `patch` (RFC 6902 add/remove/replace/move/copy/test), `delete`
+**Optional**: `dry_run` (validate and report without writing)
+**Behavior**: All operations are applied in memory first, staged to temp files, then renamed into place; any failure restores every original file
+**Example**: `{"operations": [{"file_path": "./app.json", "action": "merge_patch", "data": {"port": 8080}}, {"file_path": "./proxy.json", "action": "patch", "patch": [{"op": "replace", "path": "/upstream/port", "value": 8080}]}]}`
+
+## json-snapshot / json-restore
+**Purpose**: Safety net before bulk edits: capture every JSON file under a directory and put it back later
+**Required**: `directory`
+**Snapshot options**: `snapshot_dir` (default `<directory>/.json-snapshots`), `name` (label appended to the id), `extensions` (default json, ndjson, jsonl, toml), `list` (show existing snapshots)
+**Restore options**: `snapshot` (id, default most recent), `prune` (delete matching files created since), `dry_run`
+**Storage**: Files are stored once per SHA-256 under `objects/`, with one manifest per snapshot; restore verifies every object before writing
+**Example**: `{"directory": "./config", "name": "before-rename"}` then `{"directory": "./config"}` with json-restore
+
+## json-batch
+**Purpose**: Execute a multi-step plan server-side in one round trip
+**Required**: `steps` (array of `{id, tool, arguments}`)
+**Optional**: `stop_on_error` (default true)
+**References**: Any argument value `{"$from": "<step id>", "path": "/json/pointer"}` is replaced by that step's output; JSON bodies (e.g. query envelopes) are parsed so the pointer can address into them
+**Example**: `{"steps": [{"id": "q", "tool": "json-query", "arguments": {"file_path": "./users.json", "query": "$.users[?(@.active)]"}}, {"tool": "json-write", "arguments": {"file_path": "./active.json", "data": {"$from": "q", "path": "/results"}}}]}`
+
+## json-list-files
+**Purpose**: Discover which data files exist before guessing paths
+**Required**: none; without `directory` the allowed roots are indexed recursively
+**Optional**: `directory`, `recursive` (skips hidden directories), `extensions` (default json, ndjson, jsonl, yaml, yml, toml), `fingerprint`, `max_files` (default 1000)
+**Returns**: Relative `path`, `format`, `bytes`, `modified` and `valid` (true/false, or null when not checked) with the parse `error` for invalid files
+**Fingerprint**: `top_level` type, `records` (array length or NDJSON lines) and the `keys` seen in the first 100 records, streamed so large files stay cheap
+**Example**: `{"directory": "./data", "recursive": true}`
+
+## json-format
+**Purpose**: Normalize machine-generated JSON before committing or diffing it
+**Required**: `file_path`
+**Optional**: `indent` (spaces 0-8 or "tab", default 2), `minify`, `output_path` (default: rewrite in place), `line_endings` ("preserve", "lf", "crlf"), `trailing_newline`
+**Behavior**: The document is parsed and re-serialized, so key order and values are kept; CRLF line endings and a missing final newline are kept unless overridden; files already in the requested style are left untouched
+**Example**: `{"file_path": "./api-response.json", "indent": 4}`
+
+## json-unescape / json-escape
+**Purpose**: Work with double-encoded payloads such as `"{\"id\": 1}"` in logs and API responses
+**Required**: inline `content`, or `file_path`
+**Optional**: `pointer` (JSON Pointer to the value inside the file, default the whole document), `output_path` (default: rewrite in place), `recursive` (json-unescape only; keep decoding nested encodings, default true)
+**Behavior**: json-unescape parses the string into JSON; json-escape replaces a value with its compact JSON text
+**Example**: `{"file_path": "./events.json", "pointer": "/events/0/payload"}`
+
+## json-tail
+**Purpose**: Watch log-style NDJSON files without reading them from the start
+**Required**: `file_path`
+**Optional**: `count` (records from the end, default 10), `follow_for_ms` (wait up to 60000 ms and also return records appended meanwhile)
+**Returns**: Records in file order plus `file_offset`, `appended_records` and `invalid_lines` (lines that are not JSON are skipped) in the metadata
+**Example**: `{"file_path": "./logs/app.ndjson", "count": 20, "follow_for_ms": 5000}`
+
+## json-children
+**Purpose**: Explore a single huge object (not an array of records) without reading it whole
+**Required**: `file_path`
+**Optional**: `path` (simple JSONPath to the node, default `$`), `offset` / `limit` (page through children, default 100), `fetch` (return the node's value instead), `max_bytes` (largest node fetch returns, default 65536)
+**Returns**: One entry per child with `key`, `path` (pass it back as `path` to descend), `type`, `bytes` (compact JSON size), `children` for objects and arrays, and `value` for short scalars
+**Example**: `{"file_path": "./state.json", "path": "$.sessions"}` then `{"file_path": "./state.json", "path": "$.sessions.abc123", "fetch": true}`
+
+## json-validate-query
+**Purpose**: Check a JSONPath expression before running it (json-query performs the same check first)
+**Required**: `query`
+**Returns**: `valid`, and for invalid queries the `error` with its `column`, what the parser `expected`, and a `suggestion` with the corrected query and the `changes` made
+**Fixes**: missing `$` or `.` after it, missing `@` in filters (`?(.age > 1)`, `?(age > 1)`), `=` for `==`, `and`/`or`/`not`, `True`/`None`, typographic quotes, trailing dots, JSON Pointers (`/users/0`)
+**Example**: `{"query": "$.users[?(age > 25 and active = true)]"}`
+
+## json-update
+**Purpose**: Query-and-update in one call: modify every node a JSONPath selects and write the file atomically
+**Required**: `file_path`, `query`
+**Optional**: `operation` (`set` (default), `increment`, `replace`, `delete`), `field` (change this field of each selected object; `set` creates it), `value` (new value, or the amount to increment by, default 1), `find` / `replace_with` / `regex` (for `replace`), `if_hash`, `dialect`
+**Returns**: The JSON Pointers changed, nodes skipped with the reason (e.g. `not a number`), and the new `content_hash`
+**Example**: `{"file_path": "./users.json", "query": "$.users[?(@.age > 30)]", "field": "senior", "value": true}`
+
+## json-normalize-dates
+**Purpose**: Make every timestamp in a file RFC 3339 (UTC) before comparing, sorting or filtering by time
+**Required**: `file_path`
+**Optional**: `output_path` (default: update in place), `fields` (only these keys; their numbers are epoch values), `day_first` (03/04/2025 is 3 April), `assume_offset` for times without one (default `Z`), `dry_run`
+**Detects**: epoch seconds/milliseconds under time-like keys (`created_at`, `updatedAt`, `timestamp`), RFC 2822, `2025-03-14 14:30:00`, `03/14/2025 2:30 PM`, `14.03.2025`, `March 14, 2025`, common log format. RFC 3339 values and plain `2025-03-14` dates are left as they are
+**Returns**: `converted` and, per path (`[*]` for array elements), the count, source `formats` and one `example`
+**Example**: `{"file_path": "./events.json", "dry_run": true}`
+
+## json-convert-units
+**Purpose**: Migrate configs and data between units, e.g. a `timeout` from milliseconds to seconds, without hand-editing every value
+**Required**: `file_path`, `conversions` (JSONPath -> `"from->to"` or `{"from", "to", "decimals"}`)
+**Optional**: `output_path` (default: update in place), `dry_run`, `dialect`
+**Units**: `bytes`, `KB`/`MB`/`GB`/`TB`, `KiB`/`MiB`/`GiB`/`TiB`; `ns`, `us`, `ms`, `s`, `min`, `h`, `d`; `cents`, `dollars` (or `euros`); `mm`, `cm`, `m`, `km`. Units are case-insensitive; converting across dimensions is an error
+**Returns**: `converted` and per path the count, one `example` and any `skipped` pointers with the reason (e.g. `not a number`). Whole results are written as integers
+**Example**: `{"file_path": "./config.json", "conversions": {"$.server.timeout": "ms->s", "$.items[*].price": {"from": "cents", "to": "dollars", "decimals": 2}}}`
+
+## json-migrate
+**Purpose**: The structured version of "update all service configs": one spec applied to many files, re-runnable
+**Required**: `migration` (steps) or `migration_path` (a JSON file with them), and the files: `file_path`, `file_paths`, or `directory` with a glob `pattern` (`*`, `?`, `**`; default `*.json`)
+**Optional**: `dry_run`
+**Steps**: `{"op": "rename", "path", "to"}`, `{"op": "move", "from", "path"}`, `{"op": "change_default", "path", "value", "old_value"}`, `{"op": "split", "path", "into", "separator"}`, `{"op": "set", "path", "value"}`, `{"op": "remove", "path"}`. Paths are JSON Pointers; a `*` token matches every member or element
+**Returns**: Per file, whether it `changed`, the `changes` made and the steps `skipped` with the reason (`not found`, `keeps its configured value 15`). Nothing is written if any file cannot be read
+**Example**: `{"directory": "./services", "pattern": "*/config.json", "migration": [{"op": "rename", "path": "/server/host", "to": "hostname"}, {"op": "change_default", "path": "/server/timeout", "value": 30, "old_value": 10}]}`
+
+## json-equal
+**Purpose**: Answer "is the output the same as the expected file?" without reading a diff, e.g. after a round-trip or a migration
+**Required**: `left_file` or `left_content`, and `right_file` or `right_content`
+**Optional**: `tolerance` (numbers differing by at most this much are equal), `ignore_array_order`
+**Returns**: `equal` and, when false, `first_difference` with `path` (JSONPath), `pointer`, `reason` (`values differ`, `types differ (...)`, `missing on the right`, `array lengths differ (...)`, `no equal element on the right`) and the `left` and `right` values. Key order and formatting never matter; `1` equals `1.0`
+**Example**: `{"left_file": "./expected.json", "right_file": "./actual.json", "tolerance": 1e-9, "ignore_array_order": true}`
+
+## json-merge3
+**Purpose**: Combine your edits with someone else's edits to the same file instead of overwriting them
+**Required**: `base_file` (the common ancestor), `ours_file`, `theirs_file`
+**Optional**: `output_path` (default: return the merged document), `favor` (`ours` default, or `theirs`: what a conflicting location keeps), `conflicts_path` (also write the document with `{"<<<<<<< ours", "||||||| base", ">>>>>>> theirs"}` objects at each conflict)
+**Merging**: Objects merge key by key; arrays element by element when neither side changed their length, and appends by both sides are combined. A location both sides changed differently (including one removing what the other changed) is a conflict
+**Returns**: `clean`, and `conflicts` with `path`, `pointer`, `reason` and the `base`, `ours` and `theirs` values (absent where removed)
+**Example**: `{"base_file": "./config.base.json", "ours_file": "./config.json", "theirs_file": "./config.remote.json", "output_path": "./config.json", "conflicts_path": "./config.conflicts.json"}`
+
+## json-diff
+**Purpose**: Review a change as a patch before applying it, instead of overwriting a file with a new version
+**Required**: `left_file` or `left_content` (the current document), and `right_file` or `right_content` (the desired one)
+**Optional**: `detect_moves` (default true), `output_path` (also write the patch there)
+**Returns**: The RFC 6902 operations; changed array elements and objects are patched in place, elements that changed position and renamed keys become `move`. Apply it with json-transaction (`"action": "patch"`)
+**Example**: `{"left_file": "./config.json", "right_file": "./config.proposed.json", "output_path": "./config.patch.json"}`
+
+## json-schema-diff
+**Purpose**: Gate schema changes in API reviews: is the new version safe for existing documents and their readers?
+**Required**: `old_file` or `old_content`, and `new_file` or `new_content`
+**Breaking**: Removed required properties, properties that became required or optional, new required properties, narrowed or changed `type`, removed `enum` values, added or changed `const`, `pattern`, `format`, `multipleOf`, raised minimums or lowered maximums (values, lengths, item and property counts), `additionalProperties` becoming `false`
+**Compatible**: New optional properties, widened types, added `enum` values, relaxed or removed bounds and restrictions. Changes to `allOf`/`anyOf`/`oneOf`/`not`/`if` are `unknown` and need a person to judge
+**Returns**: `compatible`, counts of `breaking`, `compatible_changes` and `unknown`, and each change with its instance `path` (`$.user.email`, `[*]` for array items), `change`, `compatibility` and the `old`/`new` values. Local `$ref`s are followed
+**Example**: `{"old_file": "./schemas/user.v1.json", "new_file": "./schemas/user.v2.json"}`
+
+## json-openapi-validate
+**Purpose**: Check an example or captured payload against the API contract before sending it or trusting it
+**Required**: `spec_path` (OpenAPI 3.0 or 3.1), `operation_id` or `method` and `path` (the template, e.g. `/users/{id}`), and `file_path` or `content` (the payload)
+**Optional**: `direction` (`request` or `response`, default `request`), `status` (for responses: exact code, then `2XX`, then `default`; default the first 2xx), `media_type` (default `application/json`)
+**Returns**: `valid`, the `operation`, the `schema` location in the spec, and `errors` with each instance `path`, failed `keyword` and `message`. Local `$ref`s are resolved; `type`, `enum`, `const`, bounds, `pattern`, `required`, `properties`, `additionalProperties`, `items`, `allOf`/`anyOf`/`oneOf`/`not` and `nullable` are checked, and `format` is not
+**Example**: `{"spec_path": "./openapi.json", "operation_id": "createUser", "content": {"name": "ann"}}`
+
+## json-ld-expand / json-ld-compact / json-ld-flatten
+**Purpose**: Normalize linked-data documents from different sources so json-query and the other tools see the same keys
+**Required**: `file_path` or `content`; for compact, `context` (inline) or `context_path` (a context file, or a document with an `@context`)
+**Optional**: `context` or `context_path` (expand: applied beneath the document's own; flatten: compact the nodes with it), `output_path`
+**Returns**: Expand: an array of nodes with full IRIs and every value as an array of `@value`/`@id` objects. Compact: the document with the given `@context` and its terms. Flatten: one node per `@id` (blank nodes labelled `_:b0`, ...), nested nodes replaced by `{"@id": ...}` references, sorted by `@id`
+**Limits**: Contexts must be inline or local files (remote context URLs are not fetched); `@list`, `@set`, `@language` and `@index` containers, `@reverse` and `@graph` are supported; `@nest`, `@included`, type-scoped and other 1.1 containers are not
+**Example**: `{"file_path": "./people.jsonld", "context": {"@vocab": "http://schema.org/"}}`
+
+## json-geo-query
+**Purpose**: Answer "which features are in this area / have this property" without reading a whole GeoJSON file
+**Required**: `file_path` (a FeatureCollection or Feature), and `bbox` (`[west, south, east, north]`; west > east crosses the antimeridian) and/or `where` (a predicate on each feature's `properties`, as for json-query)
+**Optional**: `spatial` (`intersects`, the default: any part of the geometry touches the box; `within`: all of it is inside), `output_path`
+**Returns**: The matching features as a FeatureCollection, with how many of the total matched. Features without geometry never match a `bbox`
+**Example**: `{"file_path": "./parks.geojson", "bbox": [2.25, 48.81, 2.42, 48.90], "where": "area > 1000"}`
+
+## json-codegen
+**Purpose**: Keep client and service types in step with the documents they read, without writing them by hand
+**Required**: `target` (`typescript` or `rust`), and one of `schema_path` (a JSON Schema file), `schema` (inline) or `file_path` (sample data; the schema is inferred, and keys missing from some array elements become optional)
+**Optional**: `root_name` (default: the schema's `title`, else `Root`), `output_path`
+**Returns**: The generated code. Nested objects become named types (from their `title`, `$ref` or property name), string enums become union types or Rust enums, objects with only `additionalProperties` become `Record`/`HashMap`, and recursive Rust fields are boxed
+**Limits**: Only `$ref`s within the schema are followed; `allOf` is merged, `anyOf`/`oneOf` become unions (Rust: `serde_json::Value` unless it is a single type plus null); `not`, `if`/`then` and `patternProperties` are ignored
+**Example**: `{"target": "typescript", "file_path": "./response.json", "root_name": "User", "output_path": "./src/user.ts"}`
+
+## json-mock
+**Purpose**: Seed test environments with many plausible responses from one real example
+**Required**: `file_path` (the example) or `rules`
+**Optional**: `rules` (an object of field name or dotted path to rule: `email`, `uuid`, `name`, `first_name`, `last_name`, `username`, `city`, `street`, `company`, `phone`, `int:1..100`, `float:0..1`, `bool`, `enum:[a,b,c]`, `date[:FROM..TO]`, `datetime[:FROM..TO]`, `string[:LENGTH]`, `seq[:START]`, `const:VALUE`; without `file_path` the rules alone define the records), `count` (default 5; up to 100 inline, 100000 with `output_path`), `seed` (default random; reported so a run can be repeated), `date_from` / `date_to` (default: within a year of each sample date), `output_path` (a JSON array, or one variant per line for `.ndjson`/`.jsonl`)
+**Returns**: The variants, or the first of them when written to a file. UUIDs, ids (keys such as `id`, `user_id`, `orderId`: same length and prefix, not repeated), emails (same domain), dates and epoch timestamps (same format), names, usernames, cities, street addresses, companies and phone numbers (same format) are regenerated; other numbers vary by up to half either way, booleans are random, and arrays of objects change length. Other strings, nulls and the structure are kept. Fields with a rule follow it instead, and rules that matched no field are reported
+**Example**: `{"file_path": "./examples/user.json", "count": 50, "seed": 7, "output_path": "./fixtures/users.ndjson"}`, `{"rules": {"id": "seq", "email": "email", "age": "int:18..90", "role": "enum:[admin,member]"}, "count": 20}`
+
+## json-git-show / json-git-diff / json-git-changes
+**Purpose**: See what a config looked like before, or what changed in it, without leaving the server. Requires a server built with `--features git` and a `git` executable
+**Required**: `file_path` (show, diff); `range` (`from..to`, `from..` or one revision, compared with HEAD) and `file_path` or `directory` (changes)
+**Optional**: `rev` (show, diff; default `HEAD`)
+**Returns**: The document at `rev` (show); the RFC 6902 `patch` from `rev` to the working copy and its `changed_paths` (diff); per changed document its `status` (`added`, `deleted`, `modified`) and `changed_paths` (changes)
+**Example**: `{"file_path": "./config.json", "rev": "HEAD~1"}`, `{"range": "v1.0..HEAD", "directory": "./config"}`
+
+## json-sqlite-load / json-sqlite-query
+**Purpose**: Joins, aggregates and window functions over large record sets, beyond what JSONPath and json-sql can express. Requires a server built with `--features sqlite`
+**Required**: `file_path`, `database_path` and `table` (load); `database_path` and `query` (query)
+**Optional**: `array_path` (default `$`), `mode` (`replace` or `append`), `index_columns` (load); `params` (bound to `?`), `limit` (default 1000), `output_path`, `parse_json` (default true), `allow_writes` (default false) (query)
+**Returns**: The table's columns and row count (load); the rows as objects, or the number exported to `output_path` (query)
+**Columns**: One per top-level key, typed INTEGER, REAL or TEXT from the values seen (untyped when mixed; arrays and objects are stored as JSON text), plus `_json` holding the whole record, e.g. `json_extract(_json, '$.address.city')`
+**Example**: `{"file_path": "./orders.ndjson", "database_path": "./orders.sqlite", "table": "orders"}`, `{"database_path": "./orders.sqlite", "query": "SELECT customer, sum(total) AS spent FROM orders GROUP BY customer ORDER BY spent DESC LIMIT 10"}`
+
+## json-custom-transform
+**Purpose**: Run logic no built-in tool offers, written in any language that compiles to WebAssembly. Requires a server built with `--features wasm` and started with `--wasm-dir`
+**Required**: `module` (a `.wasm` file name without the extension), and `file_path` or `data`
+**Optional**: `output_path`
+**Returns**: The JSON the module produced, or where it was written
+**Sandbox**: Modules get no imports (no files, network or clock), each call runs in a fresh instance, and a call stops when it exceeds `--wasm-fuel` instructions or `--wasm-max-memory` MiB of memory
+**Example**: `{"module": "redact", "file_path": "./users.json", "output_path": "./users.redacted.json"}`
+
+## json-describe
+**Purpose**: Learn a file's shape before writing queries or updates, so field names and values are right the first time
 **Required**: `file_path`
-**Optional**: `schema`
-**Example**: `{"file_path": "./data.json"}`
+**Optional**: `array_path` (stream the records of this array; NDJSON is always streamed), `max_enum_values` (default 10), `max_fields` (default 200), `profile`, `top_k` (default 5)
+**Returns**: One entry per path with `path` (JSONPath, `[*]` for array elements), `field` (dotted name within a record, for `where`), `types`, `present`, `optional`, and `values` (counts of low-cardinality strings), `min`/`max` for numbers or `length` for arrays
+**Profile**: `null_rate`, `distinct`, `top_values` (value, count, share), `mean`, `stddev`, `percentiles` and `histogram` for numbers, `string_length` (min, max, mean, buckets) for strings
+**Example**: `{"file_path": "./users.json"}`
+
+## json-base64-extract / json-base64-embed
+**Purpose**: Move binary payloads (images, attachments) between base64 JSON fields and files without reading the bytes
+**Required**: `file_path`, `pointer`, and `output_path` (extract) or `binary_path` (embed); both must lie within the allowed roots
+**Optional**: `overwrite` (extract), `data_url` and `output_path` (embed)
+**Returns**: Byte count, detected media type and SHA-256
+**Example**: `{"file_path": "./message.json", "pointer": "/attachments/0/content", "output_path": "./photo.jpg"}`
+
+## json-server-stats
+**Purpose**: Inspect server activity since startup, e.g. which tools are slow or failing
+**Optional**: `format` (`json` default, or `prometheus` text exposition)
+**Returns**: `requests` by JSON-RPC method, totals, and per tool `calls`, `errors`, `bytes_read`, `bytes_written` and `latency_ms` (`mean`, `max`, cumulative `buckets` keyed by upper bound in ms)
+**Example**: `{"format": "prometheus"}`
+
+## json-write-begin / json-write-chunk / json-write-commit
+**Purpose**: Write a document too large for a single `data` argument by sending its JSON text in pieces
+**Required**: `file_path` (begin); `session_id` and `data`, a string holding the next piece of text (chunk); `session_id` (commit)
+**Optional**: `pretty`, `create_dirs` (begin); `index`, the 0-based chunk number that rejects out-of-order chunks and acknowledges a resent one (chunk); `sha256` of the whole text, `abort` (commit)
+**Returns**: The session_id (begin), bytes received (chunk), bytes written and content_hash (commit). Commit writes atomically only if the assembled text parses as JSON
+**Example**: begin `{"file_path": "./big.json"}`, chunk `{"session_id": "...", "index": 0, "data": "{\"records\": ["}`, ..., commit `{"session_id": "..."}`
+
+## json-job-start / json-job-status / json-job-result
+**Purpose**: Run an expensive call (full-file validation, large conversions, splits) without hitting client timeouts
+**Required**: `tool` and its `arguments` (start); `job_id` (result)
+**Optional**: `job_id` (status; lists every job when omitted), `wait_ms` (result: wait up to this long for the job, max 60000)
+**Returns**: A job_id (start); `status` (`running`, `succeeded`, `failed`) and `elapsed_ms` (status); the tool's own output (result), after which the job is forgotten
+**Example**: `{"tool": "json-split", "arguments": {"file_path": "./huge.json", "max_records": 100000}}`
+
+## json-check
+**Purpose**: Evaluate assertions against a file and return pass/fail with explanations
+**Required**: `file_path`, `checks` (array of `{path, op, value}`)
+**Operators**: `exists`, `not_exists`, `equals`, `not_equals`, `contains`, `length_gte`, `length_lte`, `length_eq`, `type`
+**Example**: `{"file_path": "./config.json", "checks": [{"path": "$.version", "op": "equals", "value": 2}]}`
 
 ## json-help
 **Purpose**: Get help about tools and usage patterns
 **Required**: none
-**Optional**: `topic`
-**Example**: `{"topic": "reading"}` or `{}`
+**Optional**: `topic`, `format` ("markdown" or "json" for structured help with parameters, defaults and examples as data), `tool`
+**Example**: `{"topic": "reading"}` or `{"format": "json", "tool": "json-read"}`
 
 ## Common Error Fixes:
 - **"file_path is required"** → Add: `"file_path": "./your-file.json"`
 - **"data is required"** → Add: `"data": {"your": "json data"}`
 - **"query is required"** → Add: `"query": "$.your.jsonpath"`"#
             },
-            _ => "Unknown help topic. Available topics: overview, reading, writing, querying, streaming, examples, tools"
-        };
-
-        Ok(ToolResult::success(help_text.to_string()))
+            _ => return None,
+        })
     }
 }
 
@@ -368,26 +1156,161 @@ impl ToolHandler for JsonToolsHandler {
         tools.extend(self.operations.get_tools().await?);
         tools.extend(self.query.get_tools().await?);
         tools.extend(self.streaming.get_tools().await?);
+        tools.extend(self.check.get_tools().await?);
+        tools.extend(self.files.get_tools().await?);
+        tools.extend(self.convert.get_tools().await?);
+        tools.extend(self.sql.get_tools().await?);
+        tools.extend(self.relational.get_tools().await?);
+        tools.extend(self.template.get_tools().await?);
+        tools.extend(self.script.get_tools().await?);
+        tools.extend(self.integrity.get_tools().await?);
+        tools.extend(self.transaction.get_tools().await?);
+        tools.extend(self.snapshot.get_tools().await?);
+        tools.extend(self.discovery.get_tools().await?);
+        tools.extend(self.embedded.get_tools().await?);
+        tools.extend(self.update.get_tools().await?);
+        tools.extend(self.dates.get_tools().await?);
+        tools.extend(self.units.get_tools().await?);
+        tools.extend(self.migrate.get_tools().await?);
+        tools.extend(self.compare.get_tools().await?);
+        tools.extend(self.schema_diff.get_tools().await?);
+        tools.extend(self.openapi.get_tools().await?);
+        tools.extend(self.json_ld.get_tools().await?);
+        tools.extend(self.geo.get_tools().await?);
+        tools.extend(self.codegen.get_tools().await?);
+        tools.extend(self.mock.get_tools().await?);
+        #[cfg(feature = "git")]
+        tools.extend(self.git.get_tools().await?);
+        #[cfg(feature = "sqlite")]
+        tools.extend(self.sqlite.get_tools().await?);
+        #[cfg(feature = "wasm")]
+        tools.extend(self.wasm.get_tools().await?);
+        tools.extend(self.describe.get_tools().await?);
+        tools.extend(self.binary.get_tools().await?);
+        tools.extend(self.upload.get_tools().await?);
         
+        tools.push(batch::create_batch_tool());
+        tools.extend(self.plugins.tools());
+
+        // Every file tool accepts a base directory for relative paths, and every tool writing an
+        // output_path can be told not to replace an existing file
+        for tool in &mut tools {
+            if let Some(properties) = tool.input_schema.get_mut("properties").and_then(|p| p.as_object_mut()) {
+                properties.insert("base_dir".to_string(), PathPolicy::base_dir_schema());
+                if properties.contains_key("output_path") {
+                    properties.entry("overwrite").or_insert_with(|| overwrite_schema("output_path"));
+                }
+            }
+        }
+
+        tools.push(ServerStats::create_stats_tool());
+        tools.extend(jobs::create_job_tools());
+
         // Add help tool
         tools.push(Self::create_json_help_tool());
+
+        for tool in &mut tools {
+            if let Some(properties) = tool.input_schema.get_mut("properties").and_then(|p| p.as_object_mut()) {
+                properties.insert("language".to_string(), Language::schema());
+                properties.insert("temp_output".to_string(), TempOutputs::schema());
+            }
+        }
         
-        Ok(tools)
+        self.tool_config.apply(tools)
     }
 
     async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
-        match tool_call.name.as_str() {
-            "json-help" => self.handle_json_help(&tool_call.arguments).await,
-            name if name.starts_with("json-write") || name.starts_with("json-validate") => {
-                self.operations.call_tool(tool_call).await
-            },
-            name if name.starts_with("json-query") => {
-                self.query.call_tool(tool_call).await
-            },
-            name if name.starts_with("json-read") => {
-                self.streaming.call_tool(tool_call).await
+        let mut tool_call = match self.tool_config.route(tool_call) {
+            Ok(tool_call) => tool_call,
+            Err(disabled) => return Ok(self.language.localize(disabled)),
+        };
+        let language = match tool_call.arguments.remove("language") {
+            Some(Value::String(name)) => match Language::parse(&name) {
+                Ok(language) => language,
+                Err(e) => return Ok(ToolResult::error(e)),
             },
-            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+            Some(other) => return Ok(ToolResult::error(format!("language must be a string, got: {}", other))),
+            None => self.language,
+        };
+        let temp_output = match tool_call.arguments.remove("temp_output") {
+            Some(Value::Bool(temp_output)) => temp_output,
+            Some(other) => return Ok(ToolResult::error(format!("temp_output must be true or false, got: {}", other))),
+            None => false,
+        };
+        let (name, arguments) = (tool_call.name.clone(), tool_call.arguments.clone());
+        match self.run_tool_call(tool_call, language).await {
+            Ok(result) if temp_output && result.is_error != Some(true) => self.temp_outputs.divert(&name, result),
+            Ok(mut result) if result.is_error == Some(true) => {
+                result = language.localize(result);
+                if let (Some(hint), Some(content)) = (self.recovery_hint(&name, &arguments).await, result.content.first_mut()) {
+                    content.text = hint.append_to(&content.text);
+                }
+                Ok(result)
+            }
+            Ok(result) => Ok(result),
+            Err(e) => {
+                let message = language.translate_error(&e.to_string());
+                Err(anyhow::anyhow!(match self.recovery_hint(&name, &arguments).await {
+                    // Missing-argument errors already carry a usage example
+                    Some(hint) if !hint.only_fills_required() => hint.append_to(&message),
+                    _ => message,
+                }))
+            }
+        }
+    }
+}
+
+impl JsonToolsHandler {
+    /// A corrected version of a call that failed, for errors to show the caller.
+    pub async fn recovery_hint(&self, name: &str, arguments: &HashMap<String, Value>) -> Option<RecoveryHint> {
+        let tools = self.get_tools().await.ok()?;
+        RecoveryHint::for_call(&tools, name, arguments)
+    }
+}
+
+impl JsonToolsHandler {
+    /// Resolve paths, run the tool and record it in the stats, explaining missing inputs.
+    async fn run_tool_call(&self, mut tool_call: ToolCall, language: Language) -> anyhow::Result<ToolResult> {
+        if let Err(e) = self.paths.resolve_arguments(&mut tool_call.arguments) {
+            return Ok(ToolResult::error(e.to_string()));
+        }
+        // overwrite: false is checked up front for a clear error, and again as the file is written
+        let mut create_only = Vec::new();
+        if let Some(output_path) = tool_call.arguments.get("output_path").and_then(|v| v.as_str()) {
+            if let Some(refused) = refuse_overwrite(&tool_call.arguments, output_path)? {
+                return Ok(refused);
+            }
+            if tool_call.arguments.get("overwrite").and_then(|v| v.as_bool()) == Some(false) {
+                create_only.push(PathBuf::from(output_path));
+            }
+        }
+        let missing = PathPolicy::missing_inputs(&tool_call.arguments);
+
+        let name = tool_call.name.clone();
+        let probe = FileProbe::new(PathPolicy::file_arguments(&tool_call.arguments));
+        let started = Instant::now();
+        let (result, bytes_read) = storage::count_reads(storage::create_only(create_only, self.dispatch(tool_call, language))).await;
+        let error = match &result {
+            Ok(result) if result.is_error == Some(true) => Some(result.content.first().map(|c| c.text.clone()).unwrap_or_default()),
+            Ok(_) => None,
+            Err(e) => Some(e.to_string()),
+        };
+        self.stats.record_tool_call(&name, started.elapsed(), error.as_deref(), FileTraffic { read: bytes_read, written: probe.finish() });
+        if missing.is_empty() {
+            return result;
+        }
+
+        // Explain missing input files instead of surfacing a bare "No such file" error
+        let hints = missing.iter().map(|path| self.paths.not_found_hint(path)).collect::<Vec<_>>().join("\n");
+        match result {
+            Ok(mut result) if result.is_error == Some(true) => {
+                if let Some(content) = result.content.first_mut() {
+                    content.text = format!("{}\n\n{}", content.text, hints);
+                }
+                Ok(result)
+            }
+            Err(e) => Err(anyhow::anyhow!("{}\n\n{}", e, hints)),
+            ok => ok,
         }
     }
 }
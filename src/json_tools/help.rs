@@ -0,0 +1,139 @@
+use crate::mcp::protocol::Tool;
+use regex::Regex;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// The json-help topics, in the order the overview presents them.
+pub const HELP_TOPICS: &[&str] = &["overview", "reading", "writing", "querying", "streaming", "examples", "tools"];
+
+/// Structured json-help: each tool's parameters, taken from its input schema, with the
+/// summary and examples of its section in the `tools` help topic. With `only`, just that
+/// tool; `None` if it does not exist.
+pub fn structured_help(tools: &[Tool], tools_topic: &str, only: Option<&str>) -> Option<Value> {
+    let sections = topic_sections(tools_topic);
+    let mut tools: Vec<&Tool> = tools.iter().filter(|tool| only.map_or(true, |name| tool.name == name)).collect();
+    if tools.is_empty() && only.is_some() {
+        return None;
+    }
+    tools.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let described: Vec<Value> = tools.into_iter()
+        .map(|tool| {
+            let section = sections.get(tool.name.as_str());
+            json!({
+                "name": tool.name,
+                "description": tool.description,
+                "summary": section.and_then(|s| s.summary.clone()),
+                "parameters": parameters(&tool.input_schema),
+                "examples": section.map(|s| s.examples.clone()).unwrap_or_default(),
+            })
+        })
+        .collect();
+    Some(json!({
+        "server": {"name": "json-mcp-server", "version": env!("CARGO_PKG_VERSION")},
+        "topics": HELP_TOPICS,
+        "tools": described,
+    }))
+}
+
+/// One parameter per schema property: required ones first, then in schema order.
+fn parameters(schema: &Value) -> Vec<Value> {
+    let required: Vec<&str> = schema["required"].as_array()
+        .map(|names| names.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let Some(properties) = schema["properties"].as_object() else {
+        return Vec::new();
+    };
+
+    let mut parameters: Vec<(bool, Value)> = properties.iter()
+        .map(|(name, property)| {
+            let is_required = required.contains(&name.as_str());
+            let mut parameter = Map::new();
+            parameter.insert("name".to_string(), json!(name));
+            parameter.insert("type".to_string(), property.get("type").cloned().unwrap_or(Value::Null));
+            parameter.insert("required".to_string(), json!(is_required));
+            if let Some(description) = property.get("description") {
+                parameter.insert("description".to_string(), description.clone());
+            }
+            if let Some(default) = property.get("default").cloned().or_else(|| described_default(property)) {
+                parameter.insert("default".to_string(), default);
+            }
+            for key in ["enum", "minimum", "maximum", "items"] {
+                if let Some(value) = property.get(key) {
+                    parameter.insert(key.to_string(), value.clone());
+                }
+            }
+            (is_required, Value::Object(parameter))
+        })
+        .collect();
+    parameters.sort_by_key(|(is_required, _)| !is_required);
+    parameters.into_iter().map(|(_, parameter)| parameter).collect()
+}
+
+/// A default stated in the description as "(default: 100)" or "(default: 'json')". Prose
+/// defaults ("default: all matches") are left to the description.
+fn described_default(property: &Value) -> Option<Value> {
+    let description = property.get("description")?.as_str()?;
+    let start = description.find("(default: ")? + "(default: ".len();
+    let text = description[start..].split([')', ',', ';']).next()?.trim();
+    if let Ok(value) = serde_json::from_str::<Value>(text) {
+        return Some(value);
+    }
+    let quoted = text.strip_prefix('\'').and_then(|t| t.strip_suffix('\''))
+        .or_else(|| text.strip_prefix('`').and_then(|t| t.strip_suffix('`')))?;
+    Some(json!(quoted))
+}
+
+#[derive(Default)]
+struct Section {
+    summary: Option<String>,
+    examples: Vec<Value>,
+}
+
+/// Sections of the `tools` topic by tool name. A section may cover several tools
+/// ("## json-snapshot / json-restore"); when it has one example per tool they are paired in
+/// order, otherwise every example belongs to the first tool.
+fn topic_sections(topic: &str) -> HashMap<&str, Section> {
+    let inline_code = Regex::new(r"`(\{.*?\})`").expect("valid regex");
+    let mut sections = HashMap::new();
+    for (names, block) in tool_blocks(topic) {
+        let summary = block.lines()
+            .find_map(|line| line.strip_prefix("**Purpose**: "))
+            .map(|purpose| purpose.trim().to_string());
+        let examples: Vec<Value> = block.lines()
+            .filter(|line| line.starts_with("**Example"))
+            .flat_map(|line| inline_code.captures_iter(line).filter_map(|c| serde_json::from_str::<Value>(&c[1]).ok()).collect::<Vec<_>>())
+            .collect();
+
+        let paired = names.len() > 1 && examples.len() == names.len();
+        for (index, name) in names.iter().enumerate() {
+            let examples = match (paired, index) {
+                (true, _) => vec![examples[index].clone()],
+                (false, 0) => examples.clone(),
+                (false, _) => Vec::new(),
+            };
+            sections.insert(*name, Section {
+                summary: summary.clone(),
+                examples: examples.into_iter().map(|arguments| json!({"name": name, "arguments": arguments})).collect(),
+            });
+        }
+    }
+    sections
+}
+
+/// The markdown section of the `tools` topic that documents `tool`, heading included.
+pub fn tool_section(tools_topic: &str, tool: &str) -> Option<String> {
+    tool_blocks(tools_topic)
+        .find(|(names, _)| names.contains(&tool))
+        .map(|(_, block)| format!("## {}", block.trim_end()))
+}
+
+/// Each `## ` section of the `tools` topic with the tool names in its heading, skipping
+/// sections that document no tool.
+fn tool_blocks(topic: &str) -> impl Iterator<Item = (Vec<&str>, &str)> {
+    topic.split("\n## ").skip(1).filter_map(|block| {
+        let heading = block.lines().next().unwrap_or_default();
+        let names: Vec<&str> = heading.split('/').map(str::trim).filter(|name| name.starts_with("json-") || name.ends_with("-to-json")).collect();
+        (!names.is_empty()).then_some((names, block))
+    })
+}
@@ -0,0 +1,203 @@
+use crate::json_tools::paths::PathPolicy;
+use crate::mcp::protocol::Tool;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// Untouched argument values longer than this are elided from the corrected call, so a
+/// failed write does not echo its whole payload back.
+const MAX_ECHOED_BYTES: usize = 1024;
+
+/// A corrected version of a failed tool call: the caller's own arguments with whatever the
+/// tool's input schema shows to be wrong fixed, and a note per fix.
+#[derive(Debug, Clone)]
+pub struct RecoveryHint {
+    name: String,
+    arguments: Map<String, Value>,
+    fixes: Vec<String>,
+    elided: bool,
+}
+
+impl RecoveryHint {
+    /// The corrected call, or `None` when nothing about the call can be inferred to be wrong
+    /// (the file is missing, the query matches nothing, ...).
+    pub fn for_call(tools: &[Tool], name: &str, arguments: &HashMap<String, Value>) -> Option<Self> {
+        let mut fixes = Vec::new();
+        let tool = match tools.iter().find(|tool| tool.name == name) {
+            Some(tool) => tool,
+            None => {
+                let tool = closest(name, tools.iter().map(|tool| tool.name.as_str()))
+                    .and_then(|closest| tools.iter().find(|tool| tool.name == closest))?;
+                fixes.push(format!("tool '{}' does not exist; using '{}'", name, tool.name));
+                tool
+            }
+        };
+        let properties = tool.input_schema["properties"].as_object().cloned().unwrap_or_default();
+
+        let mut names: Vec<&String> = arguments.keys().collect();
+        names.sort();
+        let mut corrected = Map::new();
+        let mut touched = Vec::new();
+        for key in names {
+            let mut value = arguments[key].clone();
+            let key = if properties.contains_key(key) {
+                key.clone()
+            } else {
+                // Arguments with no likely intended name are kept as given
+                match closest(key, properties.keys().map(String::as_str)).filter(|name| !arguments.contains_key(*name)) {
+                    Some(name) => {
+                        fixes.push(format!("unknown argument '{}'; renamed to '{}'", key, name));
+                        touched.push(name.to_string());
+                        name.to_string()
+                    }
+                    None => key.clone(),
+                }
+            };
+            if let Some(property) = properties.get(&key) {
+                if let Some(fix) = fix_value(&key, property, &mut value) {
+                    fixes.push(fix);
+                    touched.push(key.clone());
+                }
+            }
+            corrected.insert(key, value);
+        }
+
+        for required in tool.input_schema["required"].as_array().into_iter().flatten().filter_map(Value::as_str) {
+            if !corrected.contains_key(required) {
+                fixes.push(format!("missing required argument '{}'", required));
+                corrected.insert(required.to_string(), json!(format!("<{}>", required)));
+                touched.push(required.to_string());
+            }
+        }
+        if fixes.is_empty() {
+            return None;
+        }
+
+        let mut elided = false;
+        for (key, value) in corrected.iter_mut() {
+            let size = value.to_string().len();
+            if size > MAX_ECHOED_BYTES && !touched.contains(key) {
+                *value = json!(format!("<unchanged, {} bytes>", size));
+                elided = true;
+            }
+        }
+        Some(Self { name: tool.name.clone(), arguments: corrected, fixes, elided })
+    }
+
+    /// Whether the only fixes are placeholders for missing required arguments, which errors
+    /// that include a usage example already cover.
+    pub fn only_fills_required(&self) -> bool {
+        self.fixes.iter().all(|fix| fix.starts_with("missing required argument"))
+    }
+
+    /// The error message followed by the fixes and the corrected call.
+    pub fn append_to(&self, message: &str) -> String {
+        let mut text = format!("{}\n\nCorrected call to retry:", message);
+        for fix in &self.fixes {
+            text.push_str(&format!("\n  - {}", fix));
+        }
+        if self.elided {
+            text.push_str("\n  (long unchanged arguments are elided; send them as before)");
+        }
+        let call = json!({"name": self.name, "arguments": self.arguments});
+        text.push_str(&format!("\n{}", call));
+        text
+    }
+}
+
+/// Fix one argument value against its schema property, describing the fix.
+fn fix_value(key: &str, property: &Value, value: &mut Value) -> Option<String> {
+    let types: Vec<&str> = match &property["type"] {
+        Value::String(kind) => vec![kind.as_str()],
+        Value::Array(kinds) => kinds.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+
+    if let Value::String(text) = value {
+        if PathPolicy::is_path_argument(key) {
+            let path = unquote_path(text);
+            if path != *text {
+                let fix = format!("{} {} re-quoted as {}", key, json!(text), json!(path));
+                *text = path;
+                return Some(fix);
+            }
+        }
+        if let Some(options) = property["enum"].as_array() {
+            let names: Vec<&str> = options.iter().filter_map(Value::as_str).collect();
+            if !names.is_empty() && !names.contains(&text.as_str()) {
+                let choice = names.iter().copied()
+                    .find(|name| name.eq_ignore_ascii_case(text.trim()))
+                    .or_else(|| closest(text, names.iter().copied()));
+                // Without a likely intended value, guessing one could be destructive
+                // ("replace" for a write), so the caller has to choose
+                let fix = match choice {
+                    Some(choice) => format!("{} '{}' is not one of {}; using '{}'", key, text, names.join(", "), choice),
+                    None => format!("{} '{}' is not one of {}; choose one", key, text, names.join(", ")),
+                };
+                *text = choice.map_or_else(|| format!("<one of: {}>", names.join(" | ")), str::to_string);
+                return Some(fix);
+            }
+        }
+        if types.is_empty() || types.contains(&"string") {
+            return None;
+        }
+        let parsed = match serde_json::from_str::<Value>(text.trim()) {
+            Ok(parsed) if fits(&types, &parsed) => parsed,
+            _ if types.contains(&"array") => json!([text.clone()]),
+            _ => return None,
+        };
+        let fix = format!("{} was given as a string; passing it as {}", key, type_name(&parsed));
+        *value = parsed;
+        return Some(fix);
+    }
+
+    if types.contains(&"array") && !fits(&types, value) {
+        *value = json!([value.take()]);
+        return Some(format!("{} must be an array; wrapped the value in one", key));
+    }
+    None
+}
+
+/// A path without the quotes, backticks or `file://` scheme a caller wrapped it in.
+fn unquote_path(text: &str) -> String {
+    let mut path = text.trim();
+    loop {
+        let stripped = [('"', '"'), ('\'', '\''), ('`', '`'), ('<', '>')].iter()
+            .find_map(|(open, close)| path.strip_prefix(*open).and_then(|p| p.strip_suffix(*close)));
+        match stripped {
+            Some(inner) if !inner.is_empty() => path = inner.trim(),
+            _ => break,
+        }
+    }
+    path.strip_prefix("file://").unwrap_or(path).to_string()
+}
+
+/// Whether a value has one of the schema types; integers are numbers too.
+fn fits(types: &[&str], value: &Value) -> bool {
+    let kind = type_name(value);
+    types.contains(&kind) || (kind == "integer" && types.contains(&"number"))
+}
+
+/// The JSON schema type of a value, "integer" for numbers without a fraction.
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// The candidate closest to `wanted`, ignoring case and `-`/`_`, if it is close enough to be
+/// the intended one.
+fn closest<'a>(wanted: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let normalize = |text: &str| text.to_lowercase().replace(['-', '_'], "");
+    let wanted = normalize(wanted);
+    candidates
+        .map(|candidate| (strsim::jaro_winkler(&wanted, &normalize(candidate)), candidate))
+        .filter(|(score, _)| *score >= 0.85)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, candidate)| candidate)
+}
@@ -0,0 +1,195 @@
+use crate::json_tools::storage::{Capabilities, FileInfo, Storage};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::net::IpAddr;
+
+/// The `storage.http` section of the `--config` file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpConfig {
+    /// Hosts tools may read from; empty allows none.
+    pub allowed_hosts: Vec<String>,
+    /// Headers sent with every request, e.g. `Authorization`. `${NAME}` in a value is
+    /// replaced by the environment variable, so tokens stay out of the config file.
+    pub headers: BTreeMap<String, String>,
+    /// Seconds a request may take, from connecting to the end of the body.
+    pub timeout_secs: u64,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self { allowed_hosts: Vec::new(), headers: BTreeMap::new(), timeout_secs: 60 }
+    }
+}
+
+/// Read-only `http://` and `https://` URLs.
+pub struct HttpStorage {
+    config: HttpConfig,
+    #[cfg(feature = "http")]
+    agent: ureq::Agent,
+}
+
+/// The host of an `http(s)://host[:port]/...` URL.
+fn host(url: &str) -> anyhow::Result<&str> {
+    let rest = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let authority = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let host = match authority.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+        None => authority.split(':').next().unwrap_or_default(),
+    };
+    if host.is_empty() {
+        anyhow::bail!("'{}' has no host", url);
+    }
+    Ok(host)
+}
+
+/// Whether `ip` could reach the server's own host or a cloud metadata service (loopback,
+/// link-local such as 169.254.169.254, unspecified). URLs may never connect to these.
+fn is_blocked(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => ip.is_loopback() || ip.is_link_local() || ip.is_unspecified(),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_blocked(IpAddr::V4(ip)),
+            None => ip.is_loopback() || ip.is_unspecified() || (ip.segments()[0] & 0xffc0) == 0xfe80,
+        },
+    }
+}
+
+/// Resolves host names like the system does, but refuses names with a blocked address, so
+/// an allowed name that resolves (or is rebound) to one cannot be used to reach it.
+#[cfg(feature = "http")]
+struct PublicAddresses;
+
+#[cfg(feature = "http")]
+impl ureq::Resolver for PublicAddresses {
+    fn resolve(&self, netloc: &str) -> std::io::Result<Vec<std::net::SocketAddr>> {
+        use std::net::ToSocketAddrs;
+        let addresses: Vec<_> = netloc.to_socket_addrs()?.collect();
+        if let Some(address) = addresses.iter().find(|address| is_blocked(address.ip())) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!("'{}' resolves to {}, a loopback or link-local address", netloc, address.ip()),
+            ));
+        }
+        Ok(addresses)
+    }
+}
+
+/// `value` with each `${NAME}` replaced by the environment variable `NAME`.
+#[cfg(feature = "http")]
+fn expand_env(value: &str) -> anyhow::Result<String> {
+    let mut expanded = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let end = rest[start..].find('}')
+            .ok_or_else(|| anyhow::anyhow!("Unclosed '${{' in HTTP header value '{}'", value))?;
+        let name = &rest[start + 2..start + end];
+        let variable = std::env::var(name)
+            .map_err(|_| anyhow::anyhow!("The HTTP header value '{}' needs the environment variable {}", value, name))?;
+        expanded.push_str(&rest[..start]);
+        expanded.push_str(&variable);
+        rest = &rest[start + end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+impl HttpStorage {
+    pub fn new(config: HttpConfig) -> Self {
+        Self {
+            #[cfg(feature = "http")]
+            // Redirects are not followed, as they could lead to hosts that are not allowed
+            agent: ureq::AgentBuilder::new()
+                .timeout(std::time::Duration::from_secs(config.timeout_secs))
+                .resolver(PublicAddresses)
+                .redirects(0)
+                .build(),
+            config,
+        }
+    }
+
+    #[cfg(not(feature = "http"))]
+    fn request(&self, _method: &str, _url: &str) -> anyhow::Result<Option<Response>> {
+        anyhow::bail!("http:// and https:// paths need a server built with the 'http' feature")
+    }
+
+    /// The response to `method` on `url`, or `None` for 404.
+    #[cfg(feature = "http")]
+    fn request(&self, method: &str, url: &str) -> anyhow::Result<Option<Response>> {
+        let mut request = self.agent.request(method, url);
+        for (name, value) in &self.config.headers {
+            request = request.set(name, &expand_env(value)?);
+        }
+        match request.call() {
+            Ok(response) if (300..400).contains(&response.status()) => anyhow::bail!(
+                "HTTP {} of '{}' was redirected to '{}'; use that URL if its host is allowed",
+                method, url, response.header("Location").unwrap_or_default()
+            ),
+            Ok(response) => Ok(Some(response)),
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(ureq::Error::Status(status, response)) => {
+                anyhow::bail!("HTTP {} of '{}' failed with status {} {}", method, url, status, response.status_text())
+            }
+            Err(e) => anyhow::bail!("HTTP {} of '{}' failed: {}", method, url, e),
+        }
+    }
+}
+
+#[cfg(not(feature = "http"))]
+enum Response {}
+
+#[cfg(not(feature = "http"))]
+impl Response {
+    fn header(&self, _: &str) -> Option<&str> {
+        match *self {}
+    }
+
+    fn into_reader(self) -> Box<dyn Read + Send> {
+        match self {}
+    }
+}
+
+#[cfg(feature = "http")]
+use ureq::Response;
+
+impl Storage for HttpStorage {
+    fn name(&self) -> &'static str {
+        "http"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities { seekable: false, writable: false }
+    }
+
+    fn check_allowed(&self, path: &str) -> anyhow::Result<()> {
+        let host = host(path)?;
+        if host.parse().is_ok_and(is_blocked) {
+            anyhow::bail!("'{}' names a loopback or link-local address, which URLs may not reach", path);
+        }
+        let allowed = &self.config.allowed_hosts;
+        if allowed.is_empty() {
+            anyhow::bail!("'{}' cannot be read: no HTTP hosts are allowed. List them in allowed_hosts of the storage.http section of the --config file", path);
+        }
+        if !allowed.iter().any(|allowed| allowed.eq_ignore_ascii_case(host)) {
+            anyhow::bail!("Host '{}' of '{}' is not one of the allowed hosts ({})", host, path, allowed.join(", "));
+        }
+        Ok(())
+    }
+
+    fn info(&self, path: &str) -> anyhow::Result<Option<FileInfo>> {
+        Ok(self.request("HEAD", path)?.map(|response| FileInfo {
+            size: response.header("Content-Length").and_then(|l| l.parse().ok()).unwrap_or(0),
+            modified: response.header("Last-Modified")
+                .and_then(|date| chrono::DateTime::parse_from_rfc2822(date).ok())
+                .map(std::time::SystemTime::from),
+        }))
+    }
+
+    fn open(&self, path: &str) -> anyhow::Result<Box<dyn Read + Send>> {
+        let response = self.request("GET", path)?
+            .ok_or_else(|| anyhow::anyhow!("'{}' does not exist (HTTP 404)", path))?;
+        Ok(Box::new(response.into_reader()))
+    }
+}
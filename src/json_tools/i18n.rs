@@ -0,0 +1,402 @@
+use crate::mcp::protocol::ToolResult;
+use regex::Regex;
+use serde_json::{json, Value};
+use std::sync::OnceLock;
+
+/// Language of json-help text and of common error messages. Only English has every help
+/// topic; other languages translate the overview and fall back to English elsewhere.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+    Japanese,
+    Chinese,
+}
+
+impl Language {
+    pub const NAMES: [&'static str; 4] = ["en", "es", "ja", "zh"];
+
+    /// Accepts language codes with or without a region (`es`, `es-MX`, `zh_CN`) and the
+    /// languages' names.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        let lowered = name.trim().to_lowercase();
+        let code = lowered.split(['-', '_']).next().unwrap_or_default();
+        match code {
+            "en" | "english" => Ok(Self::English),
+            "es" | "spanish" | "español" | "espanol" => Ok(Self::Spanish),
+            "ja" | "japanese" | "日本語" => Ok(Self::Japanese),
+            "zh" | "chinese" | "中文" => Ok(Self::Chinese),
+            _ => Err(format!("Unknown language '{}'. Use one of: {}", name, Self::NAMES.join(", "))),
+        }
+    }
+
+    /// JSON Schema for the `language` argument accepted by every tool.
+    pub fn schema() -> Value {
+        json!({
+            "type": "string",
+            "description": "Language for help and error messages: en, es, ja or zh (default: the server's --language)"
+        })
+    }
+
+    /// The json-help overview, when translated.
+    pub fn overview(self) -> Option<&'static str> {
+        match self {
+            Self::English => None,
+            Self::Spanish => Some(OVERVIEW_ES),
+            Self::Japanese => Some(OVERVIEW_JA),
+            Self::Chinese => Some(OVERVIEW_ZH),
+        }
+    }
+
+    /// Note put above help topics that are only available in English.
+    pub fn english_only_note(self) -> Option<&'static str> {
+        match self {
+            Self::English => None,
+            Self::Spanish => Some("> Este tema solo está disponible en inglés."),
+            Self::Japanese => Some("> このトピックは英語のみです。"),
+            Self::Chinese => Some("> 此主题仅提供英文版本。"),
+        }
+    }
+
+    pub fn unknown_help_topic(self) -> &'static str {
+        match self {
+            Self::English => "Unknown help topic. Available topics: overview, reading, writing, querying, streaming, examples, tools",
+            Self::Spanish => "Tema de ayuda desconocido. Temas disponibles: overview, reading, writing, querying, streaming, examples, tools",
+            Self::Japanese => "不明なヘルプトピックです。利用できるトピック: overview, reading, writing, querying, streaming, examples, tools",
+            Self::Chinese => "未知的帮助主题。可用主题：overview, reading, writing, querying, streaming, examples, tools",
+        }
+    }
+
+    /// Translate an error result's text; successful results are left as they are.
+    pub fn localize(self, mut result: ToolResult) -> ToolResult {
+        if result.is_error == Some(true) {
+            for content in &mut result.content {
+                content.text = self.translate_error(&content.text);
+            }
+        }
+        result
+    }
+
+    /// Translate the recognized parts of an error message; anything else stays English.
+    pub fn translate_error(self, message: &str) -> String {
+        let column = match self {
+            Self::English => return message.to_string(),
+            Self::Spanish => 0,
+            Self::Japanese => 1,
+            Self::Chinese => 2,
+        };
+        static PATTERNS: OnceLock<Vec<(Regex, [&'static str; 3])>> = OnceLock::new();
+        let patterns = PATTERNS.get_or_init(|| {
+            ERROR_TRANSLATIONS.iter()
+                .map(|(pattern, translations)| (Regex::new(pattern).expect("valid regex"), *translations))
+                .collect()
+        });
+        patterns.iter().fold(message.to_string(), |text, (pattern, translations)| {
+            pattern.replace_all(&text, translations[column]).into_owned()
+        })
+    }
+}
+
+/// Error message fragments and their Spanish, Japanese and Chinese translations.
+const ERROR_TRANSLATIONS: &[(&str, [&str; 3])] = &[
+    (
+        r"(\S+) is required\. Usage example:",
+        ["${1} es obligatorio. Ejemplo de uso:", "${1} は必須です。使用例:", "${1} 为必填项。用法示例："],
+    ),
+    (
+        r"Unknown tool: (\S+)",
+        ["Herramienta desconocida: ${1}", "不明なツール: ${1}", "未知工具：${1}"],
+    ),
+    (
+        r"Tool '([^']+)' is disabled on this server",
+        ["La herramienta '${1}' está desactivada en este servidor", "ツール '${1}' はこのサーバーで無効化されています", "工具 '${1}' 已在此服务器上禁用"],
+    ),
+    (
+        r"Path '([^']*)' is outside the allowed roots \(([^)]*)\)",
+        ["La ruta '${1}' está fuera de las raíces permitidas (${2})", "パス '${1}' は許可されたルート (${2}) の外にあります", "路径 '${1}' 不在允许的根目录 (${2}) 内"],
+    ),
+    (
+        r"'([^']*)' does not exist\.",
+        ["'${1}' no existe.", "'${1}' は存在しません。", "'${1}' 不存在。"],
+    ),
+    (
+        r"Did you mean:",
+        ["Quizá quisiste decir:", "もしかして:", "您是否要找："],
+    ),
+    (
+        r"Data files in '([^']*)':",
+        ["Archivos de datos en '${1}':", "'${1}' 内のデータファイル:", "'${1}' 中的数据文件："],
+    ),
+    (
+        r"Failed to read file '([^']*)': ",
+        ["No se pudo leer el archivo '${1}': ", "ファイル '${1}' を読み込めませんでした: ", "无法读取文件 '${1}'："],
+    ),
+    (
+        r"Failed to write file '([^']*)': ",
+        ["No se pudo escribir el archivo '${1}': ", "ファイル '${1}' に書き込めませんでした: ", "无法写入文件 '${1}'："],
+    ),
+    (
+        r"Conflict: '([^']*)' was modified since it was read \(expected (\S+), found (\S+)\)\. Re-read the file and apply the change again",
+        [
+            "Conflicto: '${1}' se modificó después de leerlo (se esperaba ${2}, se encontró ${3}). Vuelve a leer el archivo y aplica el cambio de nuevo",
+            "競合: '${1}' は読み込み後に変更されました (期待値 ${2}、実際 ${3})。ファイルを読み直して変更を再度適用してください",
+            "冲突：'${1}' 在读取后已被修改（预期 ${2}，实际 ${3}）。请重新读取文件并再次应用更改",
+        ],
+    ),
+];
+
+const OVERVIEW_ES: &str = r#"# Ayuda de JSON MCP Server
+
+Este servidor ofrece operaciones completas sobre archivos JSON para LLM. Herramientas disponibles:
+
+## Herramientas principales:
+- **json-read**: Lee y analiza archivos JSON de cualquier tamaño, con streaming automático para archivos grandes
+- **json-write**: Escribe o actualiza archivos JSON con distintas estrategias de combinación
+- **json-query**: Consulta archivos JSON con expresiones JSONPath
+- **json-validate**: Valida la estructura y el contenido JSON
+- **json-extract**: Escribe los resultados de una consulta JSONPath en un nuevo archivo JSON/NDJSON
+- **json-split**: Divide un array grande o un archivo NDJSON en varios archivos
+- **json-concat**: Concatena, fusiona en profundidad o combina por posición varios archivos en uno
+- **csv-to-json**: Convierte archivos CSV/TSV en un array JSON o un archivo NDJSON
+- **xml-to-json** / **json-to-xml**: Convierte entre XML y JSON
+- **json-sql**: Consulta arrays de objetos con un subconjunto de SQL (SELECT ... FROM $.path WHERE ...)
+- **json-join**: Une dos arrays de registros por campos clave (inner/left/right/outer)
+- **json-group**: Agrupa registros por campos y calcula count/sum/avg/min/max por grupo
+- **json-dedupe**: Elimina registros duplicados (valores completos o campos clave), con streaming para NDJSON
+- **json-template**: Genera un archivo JSON o de texto a partir de una plantilla estilo Jinja y datos JSON
+- **json-script**: Transforma JSON con un script aislado cuando JSONPath no basta (campos calculados, reestructuración)
+- **json-hash**: SHA-256 de los bytes y de la forma canónica (JCS); compara archivos semánticamente
+- **json-transaction**: Aplica escrituras y parches en varios archivos de forma atómica (todo o nada)
+- **json-snapshot** / **json-restore**: Captura un directorio de archivos JSON y lo restaura más tarde
+- **json-batch**: Ejecuta varias llamadas en una sola petición, pasando resultados a pasos posteriores
+- **json-list-files**: Lista archivos JSON/NDJSON/YAML/TOML con tamaño, fecha de modificación y validez
+- **json-format**: Formatea con la sangría elegida o minimiza un archivo, en el sitio o en output_path
+- **json-unescape** / **json-escape**: Decodifica JSON codificado como cadena a JSON estructurado, y viceversa
+- **json-tail**: Últimos N registros de un archivo NDJSON, siguiendo opcionalmente las nuevas líneas durante un tiempo limitado
+- **json-children**: Lista los hijos de un objeto enorme con sus tamaños, para descender u obtener uno
+- **json-validate-query**: Comprueba la sintaxis JSONPath sin ejecutarla, con correcciones sugeridas
+- **json-update**: Asigna, incrementa, reemplaza texto o elimina cada nodo que selecciona un JSONPath, de forma atómica
+- **json-normalize-dates**: Reescribe marcas de tiempo epoch, RFC 2822 y de formato local como RFC 3339, con un informe por ruta
+- **json-convert-units**: Convierte números entre unidades en las rutas JSONPath indicadas (ms->s, bytes->MB, cents->dollars) y reescribe el archivo
+- **json-migrate**: Aplica una especificación de migración (renombrar, mover, cambiar valor por defecto, dividir) a un archivo o a un glob de archivos, con los cambios por archivo
+- **json-equal**: Decide si dos documentos son semánticamente iguales (sin importar el orden de las claves, con tolerancia numérica y orden de arrays opcionales) e indica la primera diferencia
+- **json-merge3**: Fusión a tres bandas de dos copias editadas respecto a su base, con los conflictos, sus rutas y los tres valores
+- **json-diff**: Calcula un parche RFC 6902 (con detección de movimientos) que transforma un documento en otro, para revisarlo antes de aplicarlo
+- **json-schema-diff**: Clasifica los cambios entre dos versiones de un JSON Schema como compatibles o incompatibles
+- **json-openapi-validate**: Valida una carga de solicitud o respuesta contra el esquema de una operación en un documento OpenAPI 3.x
+- **json-ld-expand** / **json-ld-compact** / **json-ld-flatten**: Normaliza documentos JSON-LD a IRI completos, a los términos de un contexto dado o a una lista plana de nodos
+- **json-geo-query**: Selecciona entidades GeoJSON por cuadro delimitador o por un predicado sobre sus propiedades
+- **json-codegen**: Genera interfaces TypeScript o structs serde de Rust a partir de un JSON Schema o de datos de ejemplo
+- **json-mock**: Genera variantes realistas de una respuesta de ejemplo para poblar entornos de prueba
+- **json-git-show** / **json-git-diff** / **json-git-changes**: Lee un archivo en una revisión de git, compara la copia de trabajo con una revisión o lista las rutas JSONPath cambiadas en un rango de commits (requiere la característica `git`)
+- **json-sqlite-load** / **json-sqlite-query**: Carga registros en una tabla SQLite y ejecuta SQL sobre ella, con funciones JSON1 para campos anidados, y exporta las filas a JSON (requiere la característica `sqlite`)
+- **json-custom-transform**: Transforma JSON con un módulo WebAssembly aislado que cargó el servidor (requiere la característica `wasm`)
+- **json-describe**: Lista los campos de un archivo con tipos, opcionalidad, valores enumerados y rangos numéricos
+- **json-base64-extract** / **json-base64-embed**: Escribe los bytes de un campo base64 en un archivo, o codifica un archivo en un campo
+- **json-write-begin** / **json-write-chunk** / **json-write-commit**: Sube por partes un documento demasiado grande para una llamada; se valida antes de escribirlo
+- **json-check**: Evalúa aserciones (exists, equals, contains, length) antes de actuar
+- **json-job-start** / **json-job-status** / **json-job-result**: Ejecuta una llamada lenta en segundo plano y consulta su resultado
+- **json-server-stats**: Peticiones atendidas, histogramas de latencia por herramienta, errores y bytes leídos/escritos
+- **json-help**: Ayuda sobre las herramientas (esta herramienta)
+
+Parámetros de cada herramienta: json-help con topic 'tools' (en inglés) o el inputSchema de tools/list.
+
+## Ejemplos rápidos:
+```json
+{"name": "json-read", "arguments": {"file_path": "./data.json"}}
+{"name": "json-write", "arguments": {"file_path": "./output.json", "data": {"key": "value"}}}
+{"name": "json-query", "arguments": {"file_path": "./data.json", "query": "$.users[0].name"}}
+{"name": "json-validate", "arguments": {"file_path": "./data.json"}}
+{"name": "json-help", "arguments": {"topic": "reading", "language": "es"}}
+```
+
+## Características principales:
+- Los archivos TOML, MessagePack y CBOR se pueden leer, consultar y escribir mediante el modelo de datos JSON
+- Soporte para archivos JSON muy grandes mediante streaming automático
+- Consultas JSONPath para extraer datos complejos
+- Varios modos de escritura (replace, merge, append)
+- Validación con diagnósticos detallados, también para JSON mal formado
+- Ocultación opcional de secretos y datos personales (`redact`) para inspeccionar archivos de configuración con seguridad
+- Rutas relativas al proyecto con `base_dir` en cualquier herramienta (o `--default-base-dir` del servidor); `--allowed-root` limita el acceso
+- `~`, `$HOME` y `%USERPROFILE%` en las rutas se expanden al directorio personal; las respuestas muestran rutas absolutas canónicas
+- Añade `language` ('en', 'es', 'ja', 'zh') a cualquier llamada para recibir la ayuda y los mensajes de error habituales en ese idioma
+- Añade `temp_output: true` a cualquier llamada para que un resultado grande se escriba en un archivo temporal del servidor, que se borra al cabo de un tiempo, en lugar de devolverlo
+
+Usa 'json-help' con un tema para obtener ayuda detallada (en inglés):
+- topic: 'reading' - Lectura de archivos JSON
+- topic: 'writing' - Escritura y actualización de archivos JSON
+- topic: 'querying' - Consultas JSONPath
+- topic: 'streaming' - Manejo de archivos grandes
+- topic: 'examples' - Ejemplos prácticos
+- topic: 'tools' - Ayuda detallada de cada herramienta"#;
+
+const OVERVIEW_JA: &str = r#"# JSON MCP Server ヘルプ
+
+このサーバーは LLM 向けに JSON ファイル操作を幅広く提供します。利用できるツール:
+
+## 主なツール:
+- **json-read**: あらゆるサイズの JSON ファイルを読み込んで解析します（大きなファイルは自動でストリーミング）
+- **json-write**: さまざまなマージ方法で JSON ファイルを書き込み・更新します
+- **json-query**: JSONPath 式で JSON ファイルを検索します
+- **json-validate**: JSON の構造と内容を検証します
+- **json-extract**: JSONPath の検索結果を新しい JSON/NDJSON ファイルに書き出します
+- **json-split**: 大きな配列や NDJSON ファイルを複数のファイルに分割します
+- **json-concat**: 複数のファイルを連結・ディープマージ・位置ごとに結合して 1 つにまとめます
+- **csv-to-json**: CSV/TSV ファイルを JSON 配列または NDJSON ファイルに変換します
+- **xml-to-json** / **json-to-xml**: XML と JSON を相互に変換します
+- **json-sql**: オブジェクトの配列を SQL のサブセットで検索します (SELECT ... FROM $.path WHERE ...)
+- **json-join**: 2 つのレコード配列をキー項目で結合します (inner/left/right/outer)
+- **json-group**: レコードを項目でグループ化し、グループごとに count/sum/avg/min/max を計算します
+- **json-dedupe**: 重複レコード（値全体またはキー項目）を削除します。NDJSON はストリーミング処理します
+- **json-template**: Jinja 風テンプレートと JSON データから JSON またはテキストファイルを生成します
+- **json-script**: JSONPath では表せない変換 (計算フィールド、再構成) をサンドボックス化されたスクリプトで行います
+- **json-hash**: 生バイトと正規形 (JCS) の SHA-256 を計算し、ファイルを意味的に比較します
+- **json-transaction**: 複数ファイルへの書き込みとパッチをアトミックに適用します（すべて成功するか、すべて取り消し）
+- **json-snapshot** / **json-restore**: JSON ファイルのディレクトリを保存し、後で復元します
+- **json-batch**: 複数のツール呼び出しを 1 回のリクエストで実行し、結果を後続のステップに渡します
+- **json-list-files**: JSON/NDJSON/YAML/TOML ファイルをサイズ・更新日時・妥当性とともに一覧表示します
+- **json-format**: 指定したインデントで整形、またはミニファイします（上書きまたは output_path へ出力）
+- **json-unescape** / **json-escape**: 文字列としてエンコードされた JSON を構造化 JSON に戻し、またその逆を行います
+- **json-tail**: NDJSON ファイルの最後の N 件を返し、一定時間追記を追跡することもできます
+- **json-children**: 巨大なオブジェクトの子要素をサイズ付きで一覧し、さらに降りたり 1 つを取得したりできます
+- **json-validate-query**: JSONPath を実行せずに構文を検査し、修正案を示します
+- **json-update**: JSONPath が選ぶすべてのノードに対して、設定・加算・文字列置換・削除をアトミックに行います
+- **json-normalize-dates**: エポック秒・RFC 2822・ロケール形式のタイムスタンプを RFC 3339 に書き換え、パスごとに変換内容を報告します
+- **json-convert-units**: 指定した JSONPath の数値を単位変換 (ms->s、bytes->MB、cents->dollars) し、ファイルを書き換えます
+- **json-migrate**: 移行仕様 (キー名変更、移動、デフォルト値変更、分割) を 1 つのファイルまたは glob で選んだファイル群に適用し、ファイルごとの変更を報告します
+- **json-equal**: 2 つのドキュメントが意味的に等しいかを判定し (キーの順序は無視、数値の許容誤差と配列順序の無視は任意)、最初の差分を返します
+- **json-merge3**: 共通の基点から編集された 2 つのコピーを 3 方向マージし、競合をパスと 3 つの値とともに一覧表示します
+- **json-diff**: あるドキュメントを別のドキュメントに変換する RFC 6902 パッチ (移動検出付き) を計算し、適用前に確認できるようにします
+- **json-schema-diff**: 2 つのバージョンの JSON Schema 間の変更を、互換性のある変更と破壊的変更に分類します
+- **json-openapi-validate**: OpenAPI 3.x ドキュメント内の操作のスキーマに対して、リクエストまたはレスポンスのペイロードを検証します
+- **json-ld-expand** / **json-ld-compact** / **json-ld-flatten**: JSON-LD ドキュメントを完全な IRI、指定したコンテキストの用語、またはノードのフラットな一覧に正規化します
+- **json-geo-query**: バウンディングボックスやプロパティの条件で GeoJSON のフィーチャーを選択します
+- **json-codegen**: JSON Schema またはサンプルデータから TypeScript のインターフェースや Rust の serde 構造体を生成します
+- **json-mock**: サンプルのレスポンスからテスト環境用のリアルなバリエーションを生成します
+- **json-git-show** / **json-git-diff** / **json-git-changes**: git のリビジョン時点のファイルを読み取り、作業コピーとリビジョンを比較し、コミット範囲で変更された JSONPath を一覧表示します (`git` フィーチャーが必要)
+- **json-sqlite-load** / **json-sqlite-query**: レコードを SQLite テーブルに読み込んで SQL を実行し、ネストしたフィールドには JSON1 関数を使い、結果の行を JSON にエクスポートします (`sqlite` フィーチャーが必要)
+- **json-custom-transform**: サーバーが読み込んだサンドボックス化された WebAssembly モジュールで JSON を変換します (`wasm` フィーチャーが必要)
+- **json-describe**: ファイルの項目を型・任意かどうか・列挙的な値・数値の範囲とともに一覧表示します
+- **json-base64-extract** / **json-base64-embed**: base64 項目のバイト列をファイルに書き出し、またはファイルを項目に埋め込みます
+- **json-write-begin** / **json-write-chunk** / **json-write-commit**: 1 回の呼び出しに収まらない文書を分割してアップロードし、検証してから書き込みます
+- **json-check**: 操作の前にアサーション (exists, equals, contains, length) を評価します
+- **json-job-start** / **json-job-status** / **json-job-result**: 時間のかかる呼び出しをバックグラウンドで実行し、結果を確認します
+- **json-server-stats**: 処理したリクエスト数、ツールごとのレイテンシ分布、エラー、読み書きしたバイト数
+- **json-help**: ツールのヘルプ（このツール）
+
+各ツールのパラメーターは、json-help の topic 'tools'（英語）または tools/list の inputSchema を参照してください。
+
+## クイックスタート例:
+```json
+{"name": "json-read", "arguments": {"file_path": "./data.json"}}
+{"name": "json-write", "arguments": {"file_path": "./output.json", "data": {"key": "value"}}}
+{"name": "json-query", "arguments": {"file_path": "./data.json", "query": "$.users[0].name"}}
+{"name": "json-validate", "arguments": {"file_path": "./data.json"}}
+{"name": "json-help", "arguments": {"topic": "reading", "language": "ja"}}
+```
+
+## 主な機能:
+- TOML・MessagePack・CBOR ファイルも JSON データモデルで読み込み・検索・書き込みができます
+- 自動ストリーミングによる巨大な JSON ファイルへの対応
+- 複雑なデータ抽出のための JSONPath 検索
+- 複数の書き込みモード (replace, merge, append)
+- 不正な JSON にも詳しい診断を返す検証機能
+- 設定ファイルを安全に確認するための、秘密情報・個人情報の伏せ字化 (`redact`)
+- すべてのツールで `base_dir` によるプロジェクト相対パスが使えます（またはサーバーの `--default-base-dir`）。`--allowed-root` でアクセスを制限できます
+- パス中の `~`・`$HOME`・`%USERPROFILE%` はホームディレクトリに展開され、応答には正規化された絶対パスが表示されます
+- どの呼び出しにも `language`（'en', 'es', 'ja', 'zh'）を付けると、ヘルプと主なエラーメッセージがその言語で返されます
+- どの呼び出しにも `temp_output: true` を付けると、大きな結果はそのまま返さずサーバーの一時ファイルに書き出されます（一定時間後に削除）
+
+詳しい説明は 'json-help' にトピックを指定してください（英語）:
+- topic: 'reading' - JSON ファイルの読み込み
+- topic: 'writing' - JSON ファイルの書き込み・更新
+- topic: 'querying' - JSONPath による検索
+- topic: 'streaming' - 大きなファイルの扱い
+- topic: 'examples' - 実用的な使用例
+- topic: 'tools' - 各ツールの詳しいヘルプ"#;
+
+const OVERVIEW_ZH: &str = r#"# JSON MCP Server 帮助
+
+本服务器为 LLM 提供全面的 JSON 文件操作。可用工具：
+
+## 核心工具：
+- **json-read**：读取并解析任意大小的 JSON 文件，大文件自动流式处理
+- **json-write**：以多种合并策略写入或更新 JSON 文件
+- **json-query**：使用 JSONPath 表达式查询 JSON 文件
+- **json-validate**：验证 JSON 的结构和内容
+- **json-extract**：将 JSONPath 查询结果写入新的 JSON/NDJSON 文件
+- **json-split**：将大型数组或 NDJSON 文件拆分为多个文件
+- **json-concat**：将多个文件连接、深度合并或按位置合并为一个文件
+- **csv-to-json**：将 CSV/TSV 文件转换为 JSON 数组或 NDJSON 文件
+- **xml-to-json** / **json-to-xml**：在 XML 与 JSON 之间转换
+- **json-sql**：用 SQL 子集查询对象数组（SELECT ... FROM $.path WHERE ...）
+- **json-join**：按键字段连接两个记录数组（inner/left/right/outer）
+- **json-group**：按字段分组记录，并计算每组的 count/sum/avg/min/max
+- **json-dedupe**：删除重复记录（整条值或键字段），NDJSON 采用流式处理
+- **json-template**：用 Jinja 风格模板和 JSON 数据生成 JSON 或文本文件
+- **json-script**：在 JSONPath 不够用时，用沙箱脚本转换 JSON（计算字段、重组结构）
+- **json-hash**：计算原始字节和规范形式（JCS）的 SHA-256；按语义比较文件
+- **json-transaction**：跨多个文件原子地应用写入和补丁（要么全部成功，要么全部回滚）
+- **json-snapshot** / **json-restore**：保存 JSON 文件目录，之后再恢复
+- **json-batch**：在一次请求中运行多个工具调用，并将输出传给后续步骤
+- **json-list-files**：列出 JSON/NDJSON/YAML/TOML 文件及其大小、修改时间和有效性
+- **json-format**：按指定缩进美化或压缩文件，可原地修改或写入 output_path
+- **json-unescape** / **json-escape**：将字符串编码的 JSON 解码为结构化 JSON，或反向编码
+- **json-tail**：返回 NDJSON 文件的最后 N 条记录，可在限定时间内跟踪追加内容
+- **json-children**：列出巨大对象的子项及其大小，然后深入或获取其中之一
+- **json-validate-query**：不执行即检查 JSONPath 语法，并给出修改建议
+- **json-update**：对 JSONPath 选中的每个节点原子地进行设置、递增、文本替换或删除
+- **json-normalize-dates**：将 epoch、RFC 2822 和本地格式的时间戳改写为 RFC 3339，并按路径报告转换情况
+- **json-convert-units**：在指定的 JSONPath 上换算数值单位（ms->s、bytes->MB、cents->dollars）并重写文件
+- **json-migrate**：将迁移规范（重命名、移动、修改默认值、拆分字段）应用到单个文件或 glob 匹配的多个文件，并按文件报告更改
+- **json-equal**：判断两个文档在语义上是否相等（忽略键顺序，可选数值容差和忽略数组顺序），并返回第一处差异
+- **json-merge3**：基于共同基线对两份编辑后的副本进行三方合并，列出冲突及其路径和三个值
+- **json-diff**：计算将一个文档转换为另一个文档的 RFC 6902 补丁（支持移动检测），以便在应用前审查
+- **json-schema-diff**：将两个 JSON Schema 版本之间的更改分类为兼容或破坏性更改
+- **json-openapi-validate**：根据 OpenAPI 3.x 文档中某个操作的模式验证请求或响应负载
+- **json-ld-expand** / **json-ld-compact** / **json-ld-flatten**：将 JSON-LD 文档规范化为完整 IRI、给定上下文的术语或扁平的节点列表
+- **json-geo-query**：按边界框或属性条件选择 GeoJSON 要素
+- **json-codegen**：根据 JSON Schema 或示例数据生成 TypeScript 接口或 Rust serde 结构体
+- **json-mock**：根据示例响应生成逼真的变体，用于填充测试环境
+- **json-git-show** / **json-git-diff** / **json-git-changes**：读取文件在某个 git 修订版本时的内容，将工作副本与修订版本比较，或列出提交范围内更改的 JSONPath（需要 `git` 特性）
+- **json-sqlite-load** / **json-sqlite-query**：将记录加载到 SQLite 表中并对其运行 SQL，嵌套字段可使用 JSON1 函数，并将结果行导出为 JSON（需要 `sqlite` 特性）
+- **json-custom-transform**：使用服务器加载的沙箱化 WebAssembly 模块转换 JSON（需要 `wasm` 特性）
+- **json-describe**：列出文件的字段及其类型、是否可选、类枚举值和数值范围
+- **json-base64-extract** / **json-base64-embed**：将 base64 字段的字节写入文件，或将文件编码进字段
+- **json-write-begin** / **json-write-chunk** / **json-write-commit**：分块上传单次调用放不下的文档，写入前先验证
+- **json-check**：在操作前评估断言（exists、equals、contains、length）
+- **json-job-start** / **json-job-status** / **json-job-result**：在后台运行耗时的工具调用并轮询结果
+- **json-server-stats**：已处理的请求、各工具的延迟直方图、错误以及读写字节数
+- **json-help**：获取工具帮助（即本工具）
+
+各工具的参数请参阅 json-help 的 topic 'tools'（英文）或 tools/list 中的 inputSchema。
+
+## 快速示例：
+```json
+{"name": "json-read", "arguments": {"file_path": "./data.json"}}
+{"name": "json-write", "arguments": {"file_path": "./output.json", "data": {"key": "value"}}}
+{"name": "json-query", "arguments": {"file_path": "./data.json", "query": "$.users[0].name"}}
+{"name": "json-validate", "arguments": {"file_path": "./data.json"}}
+{"name": "json-help", "arguments": {"topic": "reading", "language": "zh"}}
+```
+
+## 主要特性：
+- 可通过 JSON 数据模型读取、查询和写入 TOML、MessagePack 和 CBOR 文件
+- 通过自动流式处理支持超大 JSON 文件
+- 使用 JSONPath 进行复杂数据提取
+- 多种写入模式（replace、merge、append）
+- 验证功能，对格式错误的 JSON 也提供详细诊断
+- 可选的密钥和个人信息脱敏（`redact`），便于安全查看配置文件
+- 任何工具都可通过 `base_dir` 使用项目相对路径（或服务器的 `--default-base-dir`）；`--allowed-root` 限制访问范围
+- 路径中的 `~`、`$HOME` 和 `%USERPROFILE%` 会展开为主目录；响应中显示规范化的绝对路径
+- 在任何调用中加入 `language`（'en'、'es'、'ja'、'zh'），帮助和常见错误信息即以该语言返回
+- 在任何调用中加入 `temp_output: true`，较大的结果会写入服务器的临时文件（一段时间后删除），而不是直接返回
+
+使用带主题的 'json-help' 获取详细说明（英文）：
+- topic: 'reading' - 读取 JSON 文件
+- topic: 'writing' - 写入和更新 JSON 文件
+- topic: 'querying' - JSONPath 查询
+- topic: 'streaming' - 处理大文件
+- topic: 'examples' - 实用示例
+- topic: 'tools' - 各工具的详细帮助"#;
@@ -0,0 +1,206 @@
+use crate::json_tools::formats::{parse_document, DocumentFormat};
+use crate::json_tools::storage;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::io;
+
+pub struct JsonIntegrity;
+
+impl Default for JsonIntegrity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonIntegrity {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn create_hash_tool() -> Tool {
+        Tool {
+            name: "json-hash".to_string(),
+            description: "Compute SHA-256 checksums of files: of the raw bytes (detects any change) and of the canonical JCS (RFC 8785) form (ignores formatting and key order). With several files, reports whether they are semantically equal.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "File to hash"
+                    },
+                    "file_paths": {
+                        "type": "array",
+                        "items": {"type": "string"},
+                        "description": "Several files to hash and compare (instead of file_path)"
+                    }
+                }
+            })
+        }
+    }
+
+    async fn handle_hash(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let file_paths: Vec<&str> = match (
+            args.get("file_path").and_then(|v| v.as_str()),
+            args.get("file_paths").and_then(|v| v.as_array()),
+        ) {
+            (Some(path), None) => vec![path],
+            (None, Some(paths)) if !paths.is_empty() => paths.iter().filter_map(|p| p.as_str()).collect(),
+            _ => return Ok(ToolResult::error(
+                "Provide 'file_path' or a non-empty 'file_paths' array. Usage example:\n{\n  \"file_paths\": [\"./a.json\", \"./b.json\"]\n}".to_string()
+            )),
+        };
+
+        let mut reports = Vec::new();
+        let mut canonical_hashes = Vec::new();
+        for file_path in &file_paths {
+            let bytes = storage::read(file_path)?;
+            let format = DocumentFormat::from_path(file_path);
+            let canonical = match parse_document(&bytes, format) {
+                Ok(value) => Some(sha256_hex(canonical_json(&value).as_bytes())),
+                Err(e) => {
+                    reports.push(json!({
+                        "file_path": file_path,
+                        "bytes": bytes.len(),
+                        "sha256": sha256_hex(&bytes),
+                        "canonical_sha256": null,
+                        "parse_error": e.to_string(),
+                    }));
+                    canonical_hashes.push(None);
+                    continue;
+                }
+            };
+            reports.push(json!({
+                "file_path": file_path,
+                "bytes": bytes.len(),
+                "sha256": sha256_hex(&bytes),
+                "canonical_sha256": canonical,
+            }));
+            canonical_hashes.push(canonical);
+        }
+
+        let mut output = json!({ "files": reports });
+        if file_paths.len() > 1 {
+            let equal = canonical_hashes[0].is_some() && canonical_hashes.iter().all(|h| *h == canonical_hashes[0]);
+            output["semantically_equal"] = json!(equal);
+        }
+
+        Ok(ToolResult::success(format!(
+            "SHA-256 checksums for {} file(s):\n\n{}",
+            file_paths.len(),
+            serde_json::to_string_pretty(&output)?
+        )))
+    }
+}
+
+/// Lowercase hex SHA-256 digest.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    hex_digest(&Sha256::digest(bytes))
+}
+
+pub(crate) fn hex_digest(digest: &[u8]) -> String {
+    digest.iter().fold(String::with_capacity(64), |mut hex, byte| {
+        let _ = write!(hex, "{:02x}", byte);
+        hex
+    })
+}
+
+/// Content hash of a file as returned by json-read and checked by json-write's `if_hash`,
+/// e.g. `sha256:9f86d0...`. The file is streamed through the hasher.
+pub fn file_content_hash(file_path: &str) -> anyhow::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut storage::open(file_path)?, &mut hasher)?;
+    Ok(format!("sha256:{}", hex_digest(&hasher.finalize())))
+}
+
+/// Whether `expected` (with or without the `sha256:` prefix) matches `actual`.
+pub fn hash_matches(expected: &str, actual: &str) -> bool {
+    let strip = |h: &str| h.trim().trim_start_matches("sha256:").to_ascii_lowercase();
+    strip(expected) == strip(actual)
+}
+
+/// Serialize a value using the JSON Canonicalization Scheme (RFC 8785): no whitespace,
+/// object keys sorted by UTF-16 code units and numbers in ECMAScript form.
+pub fn canonical_json(value: &Value) -> String {
+    let mut out = String::new();
+    write_canonical(&mut out, value);
+    out
+}
+
+fn write_canonical(out: &mut String, value: &Value) {
+    match value {
+        Value::Null | Value::Bool(_) | Value::String(_) => out.push_str(&value.to_string()),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => out.push_str(&i.to_string()),
+            (_, Some(u)) => out.push_str(&u.to_string()),
+            _ => out.push_str(&ecmascript_number(n.as_f64().unwrap_or(0.0))),
+        },
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(out, item);
+            }
+            out.push(']');
+        }
+        Value::Object(obj) => {
+            let mut entries: Vec<_> = obj.iter().collect();
+            entries.sort_by(|a, b| a.0.encode_utf16().cmp(b.0.encode_utf16()));
+            out.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(out, item);
+            }
+            out.push('}');
+        }
+    }
+}
+
+/// Format a float the way ECMAScript's `Number.prototype.toString` does.
+fn ecmascript_number(f: f64) -> String {
+    if f == 0.0 {
+        return "0".to_string();
+    }
+    // `{:e}` gives the shortest round-tripping digits, e.g. "1.2345e-7"
+    let scientific = format!("{:e}", f.abs());
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let k = digits.len() as i32;
+    let n = exponent.parse::<i32>().unwrap_or(0) + 1;
+
+    let body = if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat((-n) as usize), digits)
+    } else {
+        let fraction = if k > 1 { format!(".{}", &digits[1..]) } else { String::new() };
+        format!("{}{}e{}{}", &digits[..1], fraction, if n > 0 { "+" } else { "-" }, (n - 1).abs())
+    };
+    if f < 0.0 { format!("-{}", body) } else { body }
+}
+
+#[async_trait]
+impl ToolHandler for JsonIntegrity {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_hash_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-hash" => self.handle_hash(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use crate::json_tools::handler::JsonToolsHandler;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Jobs allowed to run at once.
+const MAX_RUNNING_JOBS: usize = 8;
+/// Finished jobs whose result was not collected are discarded after this long.
+const JOB_RETENTION: Duration = Duration::from_secs(60 * 60);
+/// Longest json-job-result `wait_ms`.
+const MAX_RESULT_WAIT_MS: u64 = 60_000;
+
+/// Tools that depend on state held by the foreground handler, so cannot run as jobs.
+const FOREGROUND_ONLY: &[&str] = &[
+    "json-job-start", "json-job-status", "json-job-result",
+    "json-write-begin", "json-write-chunk", "json-write-commit",
+];
+
+enum JobState {
+    Running,
+    Finished(ToolResult),
+}
+
+struct Job {
+    tool: String,
+    started_at: chrono::DateTime<chrono::Utc>,
+    started: Instant,
+    finished: Option<Instant>,
+    state: JobState,
+}
+
+impl Job {
+    fn describe(&self, job_id: &str) -> Value {
+        let elapsed = self.finished.unwrap_or_else(Instant::now).duration_since(self.started);
+        let status = match &self.state {
+            JobState::Running => "running",
+            JobState::Finished(result) if result.is_error == Some(true) => "failed",
+            JobState::Finished(_) => "succeeded",
+        };
+        json!({
+            "job_id": job_id,
+            "tool": self.tool,
+            "status": status,
+            "started_at": self.started_at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            "elapsed_ms": elapsed.as_millis() as u64,
+        })
+    }
+}
+
+/// Tool calls running in the background, started by json-job-start and collected with
+/// json-job-result.
+#[derive(Default)]
+pub struct JobRegistry {
+    jobs: Arc<Mutex<HashMap<String, Job>>>,
+}
+
+impl JobRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `tool_call` through `handler` on a blocking thread, so neither the server loop
+    /// nor the async workers wait for it.
+    async fn start(&self, handler: JsonToolsHandler, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        if FOREGROUND_ONLY.contains(&tool_call.name.as_str()) {
+            return Ok(ToolResult::error(format!("{} cannot run as a background job; call it directly", tool_call.name)));
+        }
+        if !handler.get_tools().await?.iter().any(|tool| tool.name == tool_call.name) {
+            return Ok(ToolResult::error(format!("Unknown tool: {}. Use json-help to list the tools", tool_call.name)));
+        }
+
+        let job_id = uuid::Uuid::new_v4().to_string();
+        {
+            let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+            jobs.retain(|_, job| job.finished.map_or(true, |finished| finished.elapsed() < JOB_RETENTION));
+            let running = jobs.values().filter(|job| matches!(job.state, JobState::Running)).count();
+            if running >= MAX_RUNNING_JOBS {
+                return Ok(ToolResult::error(format!(
+                    "{} jobs are already running. Wait for one to finish (json-job-status) before starting another",
+                    running
+                )));
+            }
+            jobs.insert(job_id.clone(), Job {
+                tool: tool_call.name.clone(),
+                started_at: chrono::Utc::now(),
+                started: Instant::now(),
+                finished: None,
+                state: JobState::Running,
+            });
+        }
+
+        let tool = tool_call.name.clone();
+        let jobs = Arc::clone(&self.jobs);
+        let id = job_id.clone();
+        let runtime = tokio::runtime::Handle::current();
+        tokio::task::spawn_blocking(move || {
+            let result = runtime.block_on(handler.call_tool(tool_call))
+                .unwrap_or_else(|e| ToolResult::error(e.to_string()));
+            let mut jobs = jobs.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(job) = jobs.get_mut(&id) {
+                job.finished = Some(Instant::now());
+                job.state = JobState::Finished(result);
+            }
+        });
+
+        Ok(ToolResult::success(format!(
+            "Started {} as a background job. job_id: {}\nPoll json-job-status with this job_id, then collect the output with json-job-result",
+            tool, job_id
+        )))
+    }
+
+    fn status(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        let report = match args.get("job_id").and_then(|v| v.as_str()) {
+            Some(job_id) => match jobs.get(job_id) {
+                Some(job) => job.describe(job_id),
+                None => return Ok(unknown_job(job_id)),
+            },
+            None => {
+                let mut all: Vec<(&String, &Job)> = jobs.iter().collect();
+                all.sort_by_key(|(_, job)| job.started);
+                json!({"jobs": all.into_iter().map(|(id, job)| job.describe(id)).collect::<Vec<_>>()})
+            }
+        };
+        Ok(ToolResult::success(format!("Job status:\n\n{}", serde_json::to_string_pretty(&report)?)))
+    }
+
+    /// Hand over a finished job's result, exactly as the tool returned it, and forget the
+    /// job. With `wait_ms`, a running job is given that long to finish first.
+    async fn result(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let job_id = args.get("job_id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "job_id is required. Usage example:\n{{\n  \"job_id\": \"<job_id from json-job-start>\"\n}}"
+            ))?;
+        let wait = Duration::from_millis(args.get("wait_ms").and_then(|v| v.as_u64()).unwrap_or(0).min(MAX_RESULT_WAIT_MS));
+        let deadline = Instant::now() + wait;
+        while Instant::now() < deadline && self.is_running(job_id) {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        match jobs.get(job_id).map(|job| &job.state) {
+            None => Ok(unknown_job(job_id)),
+            Some(JobState::Running) => Ok(ToolResult::error(format!(
+                "Job {} is still running. Poll json-job-status until it reports succeeded or failed",
+                job_id
+            ))),
+            Some(JobState::Finished(_)) => match jobs.remove(job_id).map(|job| job.state) {
+                Some(JobState::Finished(result)) => Ok(result),
+                _ => Ok(unknown_job(job_id)),
+            },
+        }
+    }
+
+    fn is_running(&self, job_id: &str) -> bool {
+        let jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        jobs.get(job_id).is_some_and(|job| matches!(job.state, JobState::Running))
+    }
+}
+
+fn unknown_job(job_id: &str) -> ToolResult {
+    ToolResult::error(format!(
+        "No job '{}'. Its result was already collected, or it finished more than {} minutes ago",
+        job_id, JOB_RETENTION.as_secs() / 60
+    ))
+}
+
+pub fn create_job_tools() -> Vec<Tool> {
+    vec![
+        Tool {
+            name: "json-job-start".to_string(),
+            description: "Start a long-running tool call (full-file validation, large conversions, splits, ...) in the background and return a job_id immediately, avoiding client-side timeouts".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "tool": {
+                        "type": "string",
+                        "description": "Tool to run, e.g. 'json-split'"
+                    },
+                    "arguments": {
+                        "type": "object",
+                        "description": "The tool's arguments, as for a direct call"
+                    }
+                },
+                "required": ["tool"]
+            }),
+        },
+        Tool {
+            name: "json-job-status".to_string(),
+            description: "Report whether background jobs are running, succeeded or failed, with elapsed time. Without job_id, lists every job".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "job_id": {
+                        "type": "string",
+                        "description": "job_id returned by json-job-start (default: all jobs)"
+                    }
+                }
+            }),
+        },
+        Tool {
+            name: "json-job-result".to_string(),
+            description: "Return a finished background job's output, exactly as the tool would have returned it, and discard the job".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "job_id": {
+                        "type": "string",
+                        "description": "job_id returned by json-job-start"
+                    },
+                    "wait_ms": {
+                        "type": "integer",
+                        "description": "Wait up to this long for a running job to finish before answering (default: 0, max: 60000)",
+                        "minimum": 0
+                    }
+                },
+                "required": ["job_id"]
+            }),
+        },
+    ]
+}
+
+/// Execute json-job-start, json-job-status or json-job-result. Jobs run through a handler
+/// detached from `handler` with the same configuration.
+pub async fn handle_job_tool(handler: &JsonToolsHandler, registry: &JobRegistry, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+    let args = &tool_call.arguments;
+    match tool_call.name.as_str() {
+        "json-job-start" => {
+            let tool = args.get("tool")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow::anyhow!(
+                    "tool is required. Usage example:\n{{\n  \"tool\": \"json-validate\",\n  \"arguments\": {{\"file_path\": \"./huge.json\"}}\n}}"
+                ))?;
+            let arguments = match args.get("arguments").cloned().unwrap_or_else(|| json!({})) {
+                Value::Object(arguments) => arguments.into_iter().collect(),
+                _ => return Ok(ToolResult::error("arguments must be an object".to_string())),
+            };
+            registry.start(handler.detached(), ToolCall { name: tool.to_string(), arguments }).await
+        }
+        "json-job-status" => registry.status(args),
+        "json-job-result" => registry.result(args).await,
+        _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+    }
+}
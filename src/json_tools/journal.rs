@@ -0,0 +1,159 @@
+use crate::json_tools::integrity::sha256_hex;
+use crate::json_tools::storage;
+use serde_json::{json, Value};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Write-ahead journal for file writes. Each write first records its intent (target,
+/// temporary file and SHA-256 of the new content) in the journal directory, then writes
+/// and syncs the temporary file, renames it over the target and deletes the record. After
+/// a crash, `recover` completes writes whose temporary file is intact and rolls back the
+/// rest, so a target is always either its old or its new content, never truncated.
+#[derive(Debug, Clone)]
+pub struct Journal {
+    dir: PathBuf,
+}
+
+/// What `Journal::recover` did with an interrupted write.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Recovery {
+    /// The new content was complete and has been moved into place.
+    Completed(PathBuf),
+    /// The new content was incomplete; the target keeps its previous content.
+    RolledBack(PathBuf),
+}
+
+/// A write whose intent is journaled and whose content is on disk next to the target, but
+/// not yet in place.
+#[derive(Debug)]
+pub struct PendingWrite {
+    record: PathBuf,
+    target: PathBuf,
+    temp: PathBuf,
+}
+
+impl Journal {
+    pub fn open(dir: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)
+            .map_err(|e| anyhow::anyhow!("Failed to create journal directory '{}': {}", dir.display(), e))?;
+        Ok(Self { dir })
+    }
+
+    /// Replace `target` with `content` through the journal.
+    pub fn write(&self, target: &Path, content: &[u8]) -> anyhow::Result<()> {
+        self.prepare(target, content)?.commit()
+    }
+
+    /// Journal the intent and write the new content beside `target`, without touching it.
+    pub fn prepare(&self, target: &Path, content: &[u8]) -> anyhow::Result<PendingWrite> {
+        // Absolute paths keep records valid whatever directory recovery runs from
+        let target = if target.is_absolute() {
+            target.to_path_buf()
+        } else {
+            std::env::current_dir()?.join(target)
+        };
+        let (temp, mut file) = storage::create_temp_sibling(&target, "wal")?;
+
+        let id = uuid::Uuid::new_v4();
+        let record = self.dir.join(format!("{}.json", id));
+        let entry = json!({
+            "target": target.to_string_lossy(),
+            "temp": temp.to_string_lossy(),
+            "sha256": sha256_hex(content),
+            "bytes": content.len(),
+            "created_at": chrono::Utc::now().to_rfc3339(),
+        });
+        let partial = self.dir.join(format!("{}.json.tmp", id));
+        let journaled = File::create(&partial)
+            .map_err(|e| anyhow::anyhow!("Failed to write '{}': {}", partial.display(), e))
+            .and_then(|mut record_file| write_synced(&mut record_file, &partial, entry.to_string().as_bytes()))
+            .and_then(|()| fs::rename(&partial, &record)
+                .map_err(|e| anyhow::anyhow!("Failed to journal write to '{}': {}", target.display(), e)));
+        if let Err(e) = journaled {
+            let _ = fs::remove_file(&temp);
+            return Err(e);
+        }
+        sync_dir(&self.dir);
+
+        write_synced(&mut file, &temp, content)?;
+        storage::carry_metadata(&target, &temp)?;
+        Ok(PendingWrite { record, target, temp })
+    }
+
+    /// Finish or undo every write left in the journal by a crash.
+    pub fn recover(&self) -> anyhow::Result<Vec<Recovery>> {
+        let mut actions = Vec::new();
+        let mut records: Vec<PathBuf> = fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .collect();
+        records.sort();
+        for path in records {
+            let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            if name.ends_with(".json.tmp") {
+                // The intent was never fully recorded, so nothing was written
+                fs::remove_file(&path)?;
+                continue;
+            }
+            if !name.ends_with(".json") {
+                continue;
+            }
+            let entry: Value = serde_json::from_slice(&fs::read(&path)?)
+                .map_err(|e| anyhow::anyhow!("Unreadable journal record '{}': {}", path.display(), e))?;
+            let field = |key: &str| entry[key].as_str().map(PathBuf::from)
+                .ok_or_else(|| anyhow::anyhow!("Journal record '{}' has no {}", path.display(), key));
+            let (target, temp) = (field("target")?, field("temp")?);
+            let expected = entry["sha256"].as_str().unwrap_or_default();
+
+            let complete = fs::read(&temp).is_ok_and(|bytes| sha256_hex(&bytes) == expected);
+            if complete {
+                fs::rename(&temp, &target)
+                    .map_err(|e| anyhow::anyhow!("Failed to complete write to '{}': {}", target.display(), e))?;
+                sync_parent(&target);
+                actions.push(Recovery::Completed(target));
+            } else if fs::read(&target).is_ok_and(|bytes| sha256_hex(&bytes) == expected) {
+                // Renamed into place before the crash; only the record was left behind
+            } else {
+                if temp.exists() {
+                    fs::remove_file(&temp)?;
+                }
+                actions.push(Recovery::RolledBack(target));
+            }
+            fs::remove_file(&path)?;
+        }
+        sync_dir(&self.dir);
+        Ok(actions)
+    }
+}
+
+impl PendingWrite {
+    /// Move the new content into place and retire the journal record.
+    pub fn commit(self) -> anyhow::Result<()> {
+        fs::rename(&self.temp, &self.target)
+            .map_err(|e| anyhow::anyhow!("Failed to replace '{}': {}", self.target.display(), e))?;
+        sync_parent(&self.target);
+        fs::remove_file(&self.record)?;
+        Ok(())
+    }
+}
+
+fn write_synced(file: &mut File, path: &Path, content: &[u8]) -> anyhow::Result<()> {
+    file.write_all(content)
+        .and_then(|()| file.sync_all())
+        .map_err(|e| anyhow::anyhow!("Failed to write '{}': {}", path.display(), e))
+}
+
+fn sync_parent(path: &Path) {
+    if let Some(parent) = path.parent() {
+        sync_dir(parent);
+    }
+}
+
+/// Make renames in `dir` durable. Directories cannot be opened for syncing on every
+/// platform (Windows), where this is skipped.
+fn sync_dir(dir: &Path) {
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+}
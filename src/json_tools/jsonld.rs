@@ -0,0 +1,930 @@
+use crate::json_tools::formats::{inline_document, read_document, serialize_for_path, DocumentFormat};
+use crate::json_tools::storage;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Characters an IRI must end with for its term to be used as a compact IRI prefix.
+const PREFIX_ENDINGS: &[char] = &['/', '#', ':', '?', '[', ']', '@'];
+
+/// A term of an active context.
+#[derive(Clone, Default)]
+struct Term {
+    /// The IRI the term expands to; `None` for a term mapped to null, which is dropped.
+    id: Option<String>,
+    /// `@id`, `@vocab` or a datatype IRI from the definition's `@type`.
+    kind: Option<String>,
+    /// `@list`, `@set`, `@language` or `@index`.
+    container: Option<String>,
+    /// The definition's `@language`; `Some(None)` when it is explicitly null.
+    language: Option<Option<String>>,
+    reverse: bool,
+}
+
+/// A processed `@context`.
+#[derive(Clone, Default)]
+struct Context {
+    terms: BTreeMap<String, Term>,
+    vocab: Option<String>,
+    base: Option<String>,
+    language: Option<String>,
+}
+
+impl Context {
+    /// The context that results from processing `local` on top of this one.
+    fn with(&self, local: &Value) -> Result<Context, String> {
+        let mut result = self.clone();
+        let locals = match local {
+            Value::Array(items) => items.iter().collect(),
+            other => vec![other],
+        };
+        for local in locals {
+            let definitions = match local {
+                Value::Null => {
+                    result = Context::default();
+                    continue;
+                }
+                Value::String(iri) => return Err(format!(
+                    "Remote context '{}' cannot be loaded; pass the context inline or with context_path", iri
+                )),
+                Value::Object(definitions) => definitions,
+                other => return Err(format!("Invalid @context {}; expected an object, an array or null", other)),
+            };
+            if let Some(base) = definitions.get("@base") {
+                result.base = base.as_str().map(str::to_string);
+            }
+            if let Some(vocab) = definitions.get("@vocab") {
+                result.vocab = match vocab {
+                    Value::String(vocab) => result.expand_iri(vocab, true),
+                    Value::Null => None,
+                    other => return Err(format!("Invalid @vocab {}; expected a string or null", other)),
+                };
+            }
+            if let Some(language) = definitions.get("@language") {
+                result.language = language.as_str().map(str::to_lowercase);
+            }
+            let mut defined = HashSet::new();
+            for term in definitions.keys() {
+                result.define(definitions, term, &mut defined)?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Add the definition of `term` from `local`, first defining the terms its IRIs use.
+    fn define(&mut self, local: &Map<String, Value>, term: &str, defined: &mut HashSet<String>) -> Result<(), String> {
+        if term.starts_with('@') {
+            return Ok(());
+        }
+        // A term being defined that refers back to itself expands without its own definition
+        if !defined.insert(term.to_string()) {
+            return Ok(());
+        }
+        let definition = match &local[term] {
+            Value::Null => Map::from_iter([("@id".to_string(), Value::Null)]),
+            Value::String(id) => Map::from_iter([("@id".to_string(), json!(id))]),
+            Value::Object(definition) => definition.clone(),
+            other => return Err(format!("Invalid definition {} for term '{}'", other, term)),
+        };
+
+        let mut result = Term { reverse: definition.contains_key("@reverse"), ..Term::default() };
+        result.id = match definition.get("@reverse").or_else(|| definition.get("@id")) {
+            Some(Value::Null) => None,
+            Some(Value::String(id)) => self.expand_defining(id, local, defined)?,
+            Some(other) => return Err(format!("Invalid @id {} for term '{}'", other, term)),
+            None if term.contains(':') => self.expand_defining(term, local, defined)?,
+            None => match &self.vocab {
+                Some(vocab) => Some(format!("{}{}", vocab, term)),
+                None => return Err(format!("Term '{}' has no @id and the context has no @vocab", term)),
+            },
+        };
+        result.kind = match definition.get("@type") {
+            Some(Value::String(kind)) if kind == "@id" || kind == "@vocab" => Some(kind.clone()),
+            Some(Value::String(kind)) => self.expand_defining(kind, local, defined)?,
+            Some(other) => return Err(format!("Invalid @type {} for term '{}'", other, term)),
+            None => None,
+        };
+        let containers: Vec<&str> = match definition.get("@container") {
+            Some(Value::String(container)) => vec![container.as_str()],
+            Some(Value::Array(containers)) => containers.iter().filter_map(|c| c.as_str()).collect(),
+            _ => Vec::new(),
+        };
+        for container in containers {
+            match container {
+                "@list" | "@set" | "@language" | "@index" => result.container = Some(container.to_string()),
+                other => return Err(format!("Container {} of term '{}' is not supported", other, term)),
+            }
+        }
+        if let Some(language) = definition.get("@language") {
+            result.language = Some(language.as_str().map(str::to_lowercase));
+        }
+
+        self.terms.insert(term.to_string(), result);
+        Ok(())
+    }
+
+    /// Expand an IRI in a term definition after defining the term or prefix it names.
+    fn expand_defining(&mut self, value: &str, local: &Map<String, Value>, defined: &mut HashSet<String>) -> Result<Option<String>, String> {
+        let prefix = value.split_once(':').map_or(value, |(prefix, _)| prefix);
+        for dependency in [value, prefix] {
+            if local.contains_key(dependency) {
+                self.define(local, dependency, defined)?;
+            }
+        }
+        Ok(self.expand_iri(value, true))
+    }
+
+    /// Expand a term, compact IRI or relative IRI. `vocab` resolves against terms and
+    /// `@vocab` (properties and types); otherwise against `@base` (`@id` values).
+    fn expand_iri(&self, value: &str, vocab: bool) -> Option<String> {
+        if value.starts_with('@') {
+            return Some(value.to_string());
+        }
+        if vocab {
+            if let Some(term) = self.terms.get(value) {
+                return term.id.clone();
+            }
+        }
+        if let Some((prefix, suffix)) = value.split_once(':') {
+            if prefix != "_" && !suffix.starts_with("//") {
+                if let Some(Term { id: Some(id), .. }) = self.terms.get(prefix) {
+                    return Some(format!("{}{}", id, suffix));
+                }
+            }
+            return Some(value.to_string());
+        }
+        match (vocab, &self.vocab, &self.base) {
+            (true, Some(vocab), _) => Some(format!("{}{}", vocab, value)),
+            (false, _, Some(base)) => Some(resolve(base, value)),
+            _ => Some(value.to_string()),
+        }
+    }
+
+    /// The shortest way to write `iri`: a plain term, a `@vocab`-relative name, a compact
+    /// IRI or, for `@id` values, an IRI relative to `@base`.
+    fn compact_iri(&self, iri: &str, vocab: bool) -> String {
+        if iri.starts_with('@') {
+            return iri.to_string();
+        }
+        if vocab {
+            let plain = self.terms.iter()
+                .filter(|(_, term)| term.id.as_deref() == Some(iri) && !term.reverse && term.kind.is_none() && term.container.is_none() && term.language.is_none())
+                .map(|(name, _)| name.clone());
+            if let Some(term) = shortest(plain) {
+                return term;
+            }
+            if let Some(suffix) = self.vocab.as_deref().and_then(|vocab| iri.strip_prefix(vocab)) {
+                if !suffix.is_empty() && !suffix.contains(':') && !self.terms.contains_key(suffix) {
+                    return suffix.to_string();
+                }
+            }
+        }
+        let compact = self.terms.iter()
+            .filter(|(name, term)| !name.contains(':') && !term.reverse)
+            .filter_map(|(name, term)| {
+                let id = term.id.as_deref().filter(|id| id.ends_with(PREFIX_ENDINGS))?;
+                let suffix = iri.strip_prefix(id).filter(|suffix| !suffix.is_empty())?;
+                Some(format!("{}:{}", name, suffix))
+            })
+            .filter(|candidate| !self.terms.contains_key(candidate));
+        if let Some(compact) = shortest(compact) {
+            return compact;
+        }
+        if !vocab {
+            if let Some(relative) = self.base.as_deref().and_then(|base| iri.strip_prefix(base)) {
+                if !relative.is_empty() {
+                    return relative.to_string();
+                }
+            }
+        }
+        iri.to_string()
+    }
+
+    /// The term to write an expanded `value` of property `iri` under, if one fits it.
+    fn select_term(&self, iri: &str, value: &Value) -> Option<String> {
+        let reference = value.as_object().is_some_and(|v| v.contains_key("@id") && !v.contains_key("@value") && v.keys().all(|k| k == "@id" || k == "@index"));
+        let list = value.get("@list").is_some();
+        let value_type = value.get("@type").and_then(|t| t.as_str()).filter(|_| value.get("@value").is_some());
+        let language = value.get("@language").and_then(|l| l.as_str());
+        let text = value.get("@value").is_some_and(Value::is_string);
+        let mut best: Option<(u8, &String)> = None;
+        for (name, term) in &self.terms {
+            if term.reverse || term.id.as_deref() != Some(iri) {
+                continue;
+            }
+            let score = match (term.container.as_deref(), term.kind.as_deref()) {
+                (Some("@list"), _) if list => 3,
+                (Some("@list"), _) => continue,
+                _ if list => 0,
+                (Some("@language"), _) if language.is_some() && value.as_object().map_or(0, Map::len) == 2 => 3,
+                (Some("@language"), _) => continue,
+                (Some("@index"), _) if value.get("@index").is_some() => 3,
+                (Some("@index"), _) => continue,
+                (_, Some("@id" | "@vocab")) if reference => 3,
+                (_, Some(kind)) if value_type == Some(kind) => 3,
+                (_, Some(_)) => continue,
+                (_, None) => match &term.language {
+                    Some(term_language) if text && term_language.as_deref() == language => 3,
+                    Some(_) => continue,
+                    None if reference || value_type.is_some() => 1,
+                    None => 2,
+                },
+            };
+            let better = match best {
+                None => true,
+                Some((best_score, best_name)) => score > best_score
+                    || (score == best_score && (name.len(), name) < (best_name.len(), best_name)),
+            };
+            if better {
+                best = Some((score, name));
+            }
+        }
+        best.map(|(_, name)| name.clone())
+    }
+}
+
+/// Resolve a relative IRI against `base`, for the common forms: `#fragment`, `/path` and
+/// a sibling name.
+fn resolve(base: &str, relative: &str) -> String {
+    if relative.is_empty() {
+        return base.to_string();
+    }
+    if relative.starts_with('#') {
+        return format!("{}{}", base.split('#').next().unwrap_or(base), relative);
+    }
+    if relative.starts_with('/') {
+        let authority_end = base.find("://").map(|scheme| scheme + 3)
+            .and_then(|start| base[start..].find('/').map(|slash| start + slash))
+            .unwrap_or(base.len());
+        return format!("{}{}", &base[..authority_end], relative);
+    }
+    let directory = base.rfind('/').map_or(base, |slash| &base[..=slash]);
+    format!("{}{}", directory, relative)
+}
+
+fn shortest(candidates: impl Iterator<Item = String>) -> Option<String> {
+    candidates.min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+}
+
+fn as_array(value: Value) -> Vec<Value> {
+    match value {
+        Value::Null => Vec::new(),
+        Value::Array(items) => items,
+        other => vec![other],
+    }
+}
+
+fn push_unique(values: &mut Vec<Value>, value: Value) {
+    if value.get("@list").is_some() || !values.contains(&value) {
+        values.push(value);
+    }
+}
+
+fn expand(context: &Context, property: Option<&str>, element: &Value) -> Result<Value, String> {
+    match element {
+        Value::Null => Ok(Value::Null),
+        Value::Array(items) => {
+            let mut result = Vec::new();
+            for item in items {
+                match expand(context, property, item)? {
+                    Value::Null => {}
+                    Value::Array(nested) => result.extend(nested),
+                    other => result.push(other),
+                }
+            }
+            Ok(Value::Array(result))
+        }
+        Value::Object(object) => expand_object(context, property, object),
+        scalar => match property {
+            None | Some("@graph") => Ok(Value::Null),
+            Some(property) => Ok(expand_value(context, property, scalar)),
+        },
+    }
+}
+
+fn expand_value(context: &Context, property: &str, value: &Value) -> Value {
+    let term = context.terms.get(property);
+    match (term.and_then(|t| t.kind.as_deref()), value) {
+        (Some(kind @ ("@id" | "@vocab")), Value::String(iri)) => {
+            json!({"@id": context.expand_iri(iri, kind == "@vocab").unwrap_or_else(|| iri.clone())})
+        }
+        (Some(kind), _) if !kind.starts_with('@') => json!({"@value": value, "@type": kind}),
+        (_, Value::String(_)) => {
+            let language = match term.and_then(|t| t.language.clone()) {
+                Some(language) => language,
+                None => context.language.clone(),
+            };
+            match language {
+                Some(language) => json!({"@value": value, "@language": language}),
+                None => json!({"@value": value}),
+            }
+        }
+        _ => json!({"@value": value}),
+    }
+}
+
+fn expand_object(context: &Context, property: Option<&str>, object: &Map<String, Value>) -> Result<Value, String> {
+    let context = match object.get("@context") {
+        Some(local) => context.with(local)?,
+        None => context.clone(),
+    };
+    let mut keys: Vec<&String> = object.keys().collect();
+    keys.sort();
+
+    let mut result = Map::new();
+    for key in keys {
+        let value = &object[key];
+        if key == "@context" || value.is_null() && key != "@value" {
+            continue;
+        }
+        let Some(expanded) = context.expand_iri(key, true) else {
+            continue;
+        };
+        if expanded.starts_with('@') {
+            let keyword_value = match expanded.as_str() {
+                "@id" => match value {
+                    Value::String(id) => json!(context.expand_iri(id, false)),
+                    _ => return Err(format!("@id must be a string, found {}", value)),
+                },
+                "@type" => match value {
+                    Value::String(kind) => json!(context.expand_iri(kind, true)),
+                    Value::Array(kinds) => kinds.iter()
+                        .map(|kind| kind.as_str().map(|kind| json!(context.expand_iri(kind, true))))
+                        .collect::<Option<Vec<_>>>()
+                        .map(Value::Array)
+                        .ok_or_else(|| format!("@type must be a string or an array of strings, found {}", value))?,
+                    _ => return Err(format!("@type must be a string or an array of strings, found {}", value)),
+                },
+                "@value" if value.is_object() || value.is_array() => {
+                    return Err(format!("@value must be a string, number, boolean or null, found {}", value));
+                }
+                "@language" => match value {
+                    Value::String(language) => json!(language.to_lowercase()),
+                    _ => return Err(format!("@language must be a string, found {}", value)),
+                },
+                "@graph" => Value::Array(as_array(expand(&context, Some("@graph"), value)?)),
+                "@list" => Value::Array(as_array(expand(&context, property, value)?)),
+                "@set" => expand(&context, property, value)?,
+                "@reverse" => {
+                    let Value::Object(reversed) = expand(&context, Some("@reverse"), value)? else {
+                        return Err(format!("@reverse must be an object, found {}", value));
+                    };
+                    let entry = result.entry("@reverse").or_insert_with(|| json!({}));
+                    for (iri, values) in reversed {
+                        let target = entry[&iri].as_array().cloned().unwrap_or_default();
+                        entry[&iri] = Value::Array(target.into_iter().chain(as_array(values)).collect());
+                    }
+                    continue;
+                }
+                "@nest" | "@included" => return Err(format!("{} is not supported", expanded)),
+                _ => value.clone(),
+            };
+            result.insert(expanded, keyword_value);
+            continue;
+        }
+        if !expanded.contains(':') {
+            continue;
+        }
+
+        let term = context.terms.get(key.as_str());
+        let container = term.and_then(|t| t.container.as_deref());
+        let mut values = match (container, value) {
+            (Some("@language"), Value::Object(languages)) => {
+                let mut values = Vec::new();
+                for (language, strings) in languages {
+                    for text in as_array(strings.clone()) {
+                        values.push(match language.as_str() {
+                            "@none" => json!({"@value": text}),
+                            language => json!({"@value": text, "@language": language.to_lowercase()}),
+                        });
+                    }
+                }
+                values
+            }
+            (Some("@index"), Value::Object(indexes)) => {
+                let mut values = Vec::new();
+                for (index, items) in indexes {
+                    for mut item in as_array(expand(&context, Some(key), items)?) {
+                        if let (Some(item), false) = (item.as_object_mut(), index == "@none") {
+                            item.entry("@index").or_insert_with(|| json!(index));
+                        }
+                        values.push(item);
+                    }
+                }
+                values
+            }
+            _ => as_array(expand(&context, Some(key), value)?),
+        };
+        let is_list = values.len() == 1 && values[0].get("@list").is_some();
+        if container == Some("@list") && !is_list {
+            values = vec![json!({"@list": values})];
+        }
+        let target = if term.is_some_and(|t| t.reverse) {
+            result.entry("@reverse").or_insert_with(|| json!({})).as_object_mut().expect("@reverse is an object")
+        } else {
+            &mut result
+        };
+        let entry = target.entry(expanded).or_insert_with(|| json!([]));
+        if let Value::Array(existing) = entry {
+            existing.extend(values);
+        }
+    }
+
+    if let Some(value) = result.get("@value") {
+        if value.is_null() {
+            return Ok(Value::Null);
+        }
+        if result.get("@type").is_some_and(|t| !t.is_string()) {
+            return Err("A value object's @type must be a single string".to_string());
+        }
+        return Ok(if matches!(property, None | Some("@graph")) { Value::Null } else { Value::Object(result) });
+    }
+    if let Some(Value::String(kind)) = result.get("@type") {
+        result["@type"] = json!([kind]);
+    }
+    if let Some(set) = result.remove("@set") {
+        return Ok(set);
+    }
+    let top = matches!(property, None | Some("@graph"));
+    if result.len() == 1 && result.contains_key("@language")
+        || top && (result.is_empty() || result.contains_key("@list") || result.len() == 1 && result.contains_key("@id"))
+    {
+        return Ok(Value::Null);
+    }
+    Ok(Value::Object(result))
+}
+
+/// Expand `document`, processing `context` first when one is given.
+fn expand_document(document: &Value, context: Option<&Value>) -> Result<Vec<Value>, String> {
+    let active = match context {
+        Some(context) => Context::default().with(context)?,
+        None => Context::default(),
+    };
+    match expand(&active, None, document)? {
+        Value::Object(mut object) if object.len() == 1 && object.contains_key("@graph") => Ok(as_array(object.remove("@graph").unwrap_or_default())),
+        expanded => Ok(as_array(expanded)),
+    }
+}
+
+fn compact(context: &Context, property: Option<&str>, element: &Value) -> Value {
+    match element {
+        Value::Array(items) => {
+            let items: Vec<Value> = items.iter().map(|item| compact(context, property, item)).filter(|item| !item.is_null()).collect();
+            let container = property.and_then(|p| context.terms.get(p)).and_then(|t| t.container.as_deref());
+            match items.len() {
+                1 if !matches!(container, Some("@list" | "@set")) => items.into_iter().next().unwrap_or_default(),
+                _ => Value::Array(items),
+            }
+        }
+        Value::Object(object) => compact_object(context, property, object),
+        scalar => scalar.clone(),
+    }
+}
+
+/// A value object or node reference as the bare value its term implies, if it does.
+fn compact_value(context: &Context, term: Option<&Term>, object: &Map<String, Value>) -> Option<Value> {
+    let kind = term.and_then(|t| t.kind.as_deref());
+    if let (Some(Value::String(id)), 1) = (object.get("@id"), object.len()) {
+        return Some(match kind {
+            Some("@id") => json!(context.compact_iri(id, false)),
+            Some("@vocab") => json!(context.compact_iri(id, true)),
+            _ => json!({"@id": context.compact_iri(id, false)}),
+        });
+    }
+    let value = object.get("@value")?;
+    let language = match term.and_then(|t| t.language.clone()) {
+        Some(language) => language,
+        None => context.language.clone(),
+    };
+    match (object.get("@type").and_then(|t| t.as_str()), object.get("@language").and_then(|l| l.as_str()), object.len()) {
+        (Some(value_type), None, 2) if kind == Some(value_type) => Some(value.clone()),
+        (None, Some(value_language), 2) if kind.is_none() && language.as_deref() == Some(value_language) => Some(value.clone()),
+        (None, None, 1) if kind.is_none() && (!value.is_string() || language.is_none()) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+/// Add a compacted value under `name`, as an array when there are several or the term
+/// asks for one.
+fn add_value(object: &mut Map<String, Value>, name: &str, value: Value, as_array: bool) {
+    match object.get_mut(name) {
+        Some(Value::Array(existing)) => existing.push(value),
+        Some(existing) => *existing = json!([existing.take(), value]),
+        None if as_array && !value.is_array() => {
+            object.insert(name.to_string(), json!([value]));
+        }
+        None => {
+            object.insert(name.to_string(), value);
+        }
+    }
+}
+
+fn compact_object(context: &Context, property: Option<&str>, object: &Map<String, Value>) -> Value {
+    let term = property.and_then(|p| context.terms.get(p));
+    if object.contains_key("@value") || object.contains_key("@id") && object.len() == 1 {
+        if let Some(value) = compact_value(context, term, object) {
+            return value;
+        }
+    }
+    if let Some(list) = object.get("@list") {
+        let items = match compact(context, property, list) {
+            Value::Array(items) => items,
+            item => vec![item],
+        };
+        if term.and_then(|t| t.container.as_deref()) == Some("@list") {
+            return Value::Array(items);
+        }
+        return json!({"@list": items});
+    }
+
+    let mut result = Map::new();
+    for (key, value) in object {
+        match key.as_str() {
+            "@id" => {
+                result.insert(key.clone(), json!(context.compact_iri(value.as_str().unwrap_or_default(), false)));
+            }
+            "@type" => {
+                let compact_type = |kind: &Value| json!(context.compact_iri(kind.as_str().unwrap_or_default(), true));
+                let types = match value {
+                    Value::Array(kinds) if kinds.len() == 1 => compact_type(&kinds[0]),
+                    Value::Array(kinds) => kinds.iter().map(compact_type).collect(),
+                    kind => compact_type(kind),
+                };
+                result.insert(key.clone(), types);
+            }
+            "@graph" => {
+                let nodes = as_array(compact(context, Some("@graph"), value));
+                result.insert(key.clone(), Value::Array(nodes));
+            }
+            "@reverse" => {
+                let mut reversed = Map::new();
+                for (iri, values) in value.as_object().into_iter().flatten() {
+                    let reverse_term = context.terms.iter()
+                        .find(|(_, term)| term.reverse && term.id.as_deref() == Some(iri.as_str()))
+                        .map(|(name, _)| name.clone());
+                    match reverse_term {
+                        Some(name) => {
+                            let as_array = context.terms[&name].container.as_deref() == Some("@set");
+                            for item in values.as_array().into_iter().flatten() {
+                                add_value(&mut result, &name, compact(context, Some(&name), item), as_array);
+                            }
+                        }
+                        None => {
+                            let name = context.compact_iri(iri, true);
+                            reversed.insert(name.clone(), compact(context, Some(&name), values));
+                        }
+                    }
+                }
+                if !reversed.is_empty() {
+                    result.insert(key.clone(), Value::Object(reversed));
+                }
+            }
+            keyword if keyword.starts_with('@') => {
+                result.insert(key.clone(), value.clone());
+            }
+            iri => {
+                let values = value.as_array().map(Vec::as_slice).unwrap_or_default();
+                if values.is_empty() {
+                    let name = context.select_term(iri, &Value::Null).unwrap_or_else(|| context.compact_iri(iri, true));
+                    result.insert(name, json!([]));
+                }
+                for item in values {
+                    let name = context.select_term(iri, item).unwrap_or_else(|| context.compact_iri(iri, true));
+                    let container = context.terms.get(&name).and_then(|t| t.container.as_deref());
+                    match container {
+                        Some("@language") => {
+                            let language = item.get("@language").and_then(|l| l.as_str()).unwrap_or("@none").to_string();
+                            let languages = result.entry(name).or_insert_with(|| json!({}));
+                            if let Some(languages) = languages.as_object_mut() {
+                                add_value(languages, &language, item["@value"].clone(), false);
+                            }
+                        }
+                        Some("@index") => {
+                            let mut item = item.clone();
+                            let index = item.as_object_mut().and_then(|i| i.remove("@index"));
+                            let index = index.as_ref().and_then(|i| i.as_str()).unwrap_or("@none").to_string();
+                            let compacted = compact(context, Some(&name), &item);
+                            let indexes = result.entry(name).or_insert_with(|| json!({}));
+                            if let Some(indexes) = indexes.as_object_mut() {
+                                add_value(indexes, &index, compacted, false);
+                            }
+                        }
+                        _ => {
+                            let compacted = compact(context, Some(&name), item);
+                            add_value(&mut result, &name, compacted, matches!(container, Some("@set" | "@list")));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Value::Object(result)
+}
+
+/// Put the `@context` that was compacted against first.
+fn with_context(local: &Value, body: Map<String, Value>) -> Value {
+    let mut result = Map::new();
+    if !local.is_null() && local != &json!({}) {
+        result.insert("@context".to_string(), local.clone());
+    }
+    result.extend(body);
+    Value::Object(result)
+}
+
+fn compact_document(expanded: Vec<Value>, local: &Value) -> Result<Value, String> {
+    let context = Context::default().with(local)?;
+    let body = match compact(&context, None, &Value::Array(expanded)) {
+        Value::Object(object) => object,
+        Value::Array(nodes) if nodes.is_empty() => Map::new(),
+        nodes => Map::from_iter([("@graph".to_string(), Value::Array(as_array(nodes)))]),
+    };
+    Ok(with_context(local, body))
+}
+
+/// Node objects by graph and `@id`, built while flattening.
+#[derive(Default)]
+struct NodeMap {
+    graphs: BTreeMap<String, BTreeMap<String, Map<String, Value>>>,
+    blank_nodes: HashMap<String, String>,
+    next_blank_node: usize,
+}
+
+impl NodeMap {
+    /// A fresh blank node identifier, or the one already given to `existing`.
+    fn blank_node(&mut self, existing: Option<&str>) -> String {
+        if let Some(id) = existing.and_then(|existing| self.blank_nodes.get(existing)) {
+            return id.clone();
+        }
+        let id = format!("_:b{}", self.next_blank_node);
+        self.next_blank_node += 1;
+        if let Some(existing) = existing {
+            self.blank_nodes.insert(existing.to_string(), id.clone());
+        }
+        id
+    }
+
+    fn node(&mut self, graph: &str, id: &str) -> &mut Map<String, Value> {
+        self.graphs.entry(graph.to_string()).or_default()
+            .entry(id.to_string())
+            .or_insert_with(|| Map::from_iter([("@id".to_string(), json!(id))]))
+    }
+
+    /// Add an expanded node object and the nodes nested in it to `graph`, returning its `@id`.
+    fn add(&mut self, graph: &str, element: &Map<String, Value>) -> String {
+        let id = match element.get("@id").and_then(|id| id.as_str()) {
+            Some(id) if id.starts_with("_:") => self.blank_node(Some(id)),
+            Some(id) => id.to_string(),
+            None => self.blank_node(None),
+        };
+        self.node(graph, &id);
+        for (key, value) in element {
+            match key.as_str() {
+                "@id" => {}
+                "@type" => {
+                    let types: Vec<Value> = as_array(value.clone()).into_iter()
+                        .map(|kind| match kind.as_str() {
+                            Some(blank) if blank.starts_with("_:") => json!(self.blank_node(Some(blank))),
+                            _ => kind,
+                        })
+                        .collect();
+                    let node = self.node(graph, &id);
+                    let existing = node.entry("@type").or_insert_with(|| json!([]));
+                    if let Value::Array(existing) = existing {
+                        for kind in types {
+                            push_unique(existing, kind);
+                        }
+                    }
+                }
+                "@graph" => {
+                    self.graphs.entry(id.clone()).or_default();
+                    for item in value.as_array().into_iter().flatten() {
+                        if let Some(item) = item.as_object() {
+                            self.add(&id, item);
+                        }
+                    }
+                }
+                "@reverse" => {
+                    for (property, values) in value.as_object().into_iter().flatten() {
+                        for item in values.as_array().into_iter().flatten() {
+                            if let Some(item) = item.as_object().filter(|item| !item.contains_key("@value")) {
+                                let subject = self.add(graph, item);
+                                let node = self.node(graph, &subject);
+                                if let Value::Array(existing) = node.entry(property.clone()).or_insert_with(|| json!([])) {
+                                    push_unique(existing, json!({"@id": id}));
+                                }
+                            }
+                        }
+                    }
+                }
+                keyword if keyword.starts_with('@') => {
+                    self.node(graph, &id).insert(key.clone(), value.clone());
+                }
+                property => {
+                    let values: Vec<Value> = value.as_array().into_iter().flatten().map(|item| self.flatten_value(graph, item)).collect();
+                    let node = self.node(graph, &id);
+                    if let Value::Array(existing) = node.entry(property.to_string()).or_insert_with(|| json!([])) {
+                        for value in values {
+                            push_unique(existing, value);
+                        }
+                    }
+                }
+            }
+        }
+        id
+    }
+
+    /// A property value with nested nodes replaced by references to them.
+    fn flatten_value(&mut self, graph: &str, value: &Value) -> Value {
+        if value.get("@value").is_some() {
+            return value.clone();
+        }
+        if let Some(list) = value.get("@list").and_then(|l| l.as_array()) {
+            let items: Vec<Value> = list.iter().map(|item| self.flatten_value(graph, item)).collect();
+            return json!({"@list": items});
+        }
+        match value.as_object() {
+            Some(node) => json!({"@id": self.add(graph, node)}),
+            None => value.clone(),
+        }
+    }
+
+    /// The default graph's nodes by `@id`, named graphs nested in the node that names them.
+    fn into_nodes(mut self) -> Vec<Value> {
+        let mut default = self.graphs.remove("@default").unwrap_or_default();
+        for (name, nodes) in self.graphs {
+            let nodes: Vec<Value> = nodes.into_values().filter(|node| node.len() > 1).map(Value::Object).collect();
+            default.entry(name.clone())
+                .or_insert_with(|| Map::from_iter([("@id".to_string(), json!(name))]))
+                .insert("@graph".to_string(), Value::Array(nodes));
+        }
+        default.into_values().filter(|node| node.len() > 1).map(Value::Object).collect()
+    }
+}
+
+fn flatten_document(expanded: &[Value]) -> Vec<Value> {
+    let mut nodes = NodeMap::default();
+    for item in expanded {
+        if let Some(node) = item.as_object() {
+            nodes.add("@default", node);
+        }
+    }
+    nodes.into_nodes()
+}
+
+fn write_atomically(path: &str, document: &Value) -> anyhow::Result<Result<(), String>> {
+    let format = DocumentFormat::from_path(path);
+    let content = match serialize_for_path(path, document, format, true) {
+        Ok(content) => content,
+        Err(e) => return Ok(Err(format!("Cannot write '{}' as {}: {}", path, format.name(), e))),
+    };
+    storage::write(path, &content)?;
+    Ok(Ok(()))
+}
+
+pub struct JsonLd;
+
+impl Default for JsonLd {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonLd {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn create_tool(name: &str, description: &str, context_description: &str) -> Tool {
+        Tool {
+            name: name.to_string(),
+            description: description.to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "JSON-LD document to process"
+                    },
+                    "content": {
+                        "description": "JSON-LD document inline, as JSON text or a JSON value (instead of file_path)"
+                    },
+                    "context": {
+                        "description": context_description
+                    },
+                    "context_path": {
+                        "type": "string",
+                        "description": "File holding the context, either the context itself or a document with an @context (instead of context)"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "Also write the result to this file"
+                    }
+                }
+            })
+        }
+    }
+
+    /// The context given as `context` or in the file at `context_path`.
+    fn context(args: &HashMap<String, Value>) -> anyhow::Result<Option<Value>> {
+        match (args.get("context"), args.get("context_path").and_then(|v| v.as_str())) {
+            (Some(_), Some(_)) => anyhow::bail!("Provide either context or context_path, not both"),
+            (Some(context), None) => Ok(Some(match context {
+                Value::Object(wrapper) if wrapper.len() == 1 && wrapper.contains_key("@context") => wrapper["@context"].clone(),
+                context => context.clone(),
+            })),
+            (None, Some(path)) => {
+                let document = read_document(path, DocumentFormat::from_path(path))?;
+                Ok(Some(document.get("@context").cloned().unwrap_or(document)))
+            }
+            (None, None) => Ok(None),
+        }
+    }
+
+    async fn handle(&self, action: &str, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let usage = format!(
+            "Usage example:\n{{\n  \"file_path\": \"./people.jsonld\",\n  \"context_path\": \"./context.jsonld\"\n}}\nTool: json-ld-{}", action
+        );
+        let (name, document) = match (args.get("file_path").and_then(|v| v.as_str()), args.get("content")) {
+            (Some(file_path), None) => (format!("'{}'", file_path), read_document(file_path, DocumentFormat::from_path(file_path))?),
+            (None, Some(content)) => ("the content".to_string(), inline_document(content)?),
+            (Some(_), Some(_)) => return Ok(ToolResult::error("Provide either file_path or content, not both".to_string())),
+            (None, None) => anyhow::bail!("file_path (or content) is required. {}", usage),
+        };
+        let context = Self::context(args)?;
+        if action == "compact" && context.is_none() {
+            anyhow::bail!("context (or context_path) is required to compact. {}", usage);
+        }
+
+        let processed = match action {
+            // A context given to expand applies beneath the document's own
+            "expand" => expand_document(&document, context.as_ref()).map(Value::Array),
+            "compact" => expand_document(&document, None)
+                .and_then(|expanded| compact_document(expanded, context.as_ref().unwrap_or(&Value::Null))),
+            _ => expand_document(&document, None).and_then(|expanded| {
+                let nodes = flatten_document(&expanded);
+                match &context {
+                    Some(local) => {
+                        let active = Context::default().with(local)?;
+                        let nodes = nodes.iter().map(|node| compact(&active, None, node)).collect();
+                        Ok(with_context(local, Map::from_iter([("@graph".to_string(), Value::Array(nodes))])))
+                    }
+                    None => Ok(Value::Array(nodes)),
+                }
+            }),
+        };
+        let result = match processed {
+            Ok(result) => result,
+            Err(message) => return Ok(ToolResult::error(format!("Cannot {} {}: {}", action, name, message))),
+        };
+
+        let mut note = String::new();
+        if let Some(output_path) = args.get("output_path").and_then(|v| v.as_str()) {
+            if let Err(message) = write_atomically(output_path, &result)? {
+                return Ok(ToolResult::error(message));
+            }
+            note = format!(" (written to '{}')", output_path);
+        }
+        let verb = match action {
+            "expand" => "Expanded",
+            "compact" => "Compacted",
+            _ => "Flattened",
+        };
+        Ok(ToolResult::success(format!(
+            "{} {}{}:\n\n{}", verb, name, note, serde_json::to_string_pretty(&result)?
+        )))
+    }
+}
+
+#[async_trait]
+impl ToolHandler for JsonLd {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![
+            Self::create_tool(
+                "json-ld-expand",
+                "Expand a JSON-LD document: terms become full IRIs and every value an explicit array of @value/@id objects, ready for querying without its @context.",
+                "Extra context applied before the document's own @context (optional)",
+            ),
+            Self::create_tool(
+                "json-ld-compact",
+                "Compact a JSON-LD document against a given @context, so the same data from different sources uses the same short keys.",
+                "The @context to compact against (required, or use context_path)",
+            ),
+            Self::create_tool(
+                "json-ld-flatten",
+                "Flatten a JSON-LD document into a list of nodes, one per @id, with nested nodes replaced by references and blank nodes labelled.",
+                "@context to compact the flattened nodes with (optional; without it they stay expanded)",
+            ),
+        ])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-ld-expand" => self.handle("expand", &tool_call.arguments).await,
+            "json-ld-compact" => self.handle("compact", &tool_call.arguments).await,
+            "json-ld-flatten" => self.handle("flatten", &tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use crate::json_tools::rfc9535::Rfc9535Query;
+use jsonpath_rust::JsonPath;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// The JSONPath implementation that evaluates a query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JsonPathDialect {
+    /// The `jsonpath-rust` crate: lenient about function argument types.
+    #[default]
+    JsonpathRust,
+    /// The built-in evaluator that follows RFC 9535 strictly.
+    Rfc9535,
+}
+
+impl JsonPathDialect {
+    pub const NAMES: [&'static str; 2] = ["jsonpath-rust", "rfc9535"];
+
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "jsonpath-rust" | "jsonpath_rust" | "default" => Ok(Self::JsonpathRust),
+            "rfc9535" | "rfc-9535" | "ietf" => Ok(Self::Rfc9535),
+            _ => Err(format!("Unknown JSONPath dialect '{}'. Use one of: {}", name, Self::NAMES.join(", "))),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::JsonpathRust => "jsonpath-rust",
+            Self::Rfc9535 => "rfc9535",
+        }
+    }
+
+    /// The `dialect` argument, falling back to the server's configured default.
+    pub fn from_args(args: &HashMap<String, Value>, default: Self) -> Result<Self, String> {
+        match args.get("dialect").and_then(|v| v.as_str()) {
+            Some(name) => Self::parse(name),
+            None => Ok(default),
+        }
+    }
+
+    /// Evaluate `query` against `document`. Errors are formatted for tool output.
+    pub fn query<'a>(self, document: &'a Value, query: &str) -> Result<Vec<&'a Value>, String> {
+        match self {
+            Self::JsonpathRust => document.query(query).map_err(|e| format!("JSONPath query error: {}", e)),
+            Self::Rfc9535 => Rfc9535Query::parse(query)
+                .map(|parsed| parsed.select(document))
+                .map_err(|e| format!("JSONPath query error (rfc9535) {}", e)),
+        }
+    }
+
+    /// Like [`query`](Self::query), pairing each value with its normalized path (`$['users'][0]`).
+    pub fn query_with_paths<'a>(self, document: &'a Value, query: &str) -> Result<Vec<(String, &'a Value)>, String> {
+        match self {
+            Self::JsonpathRust => document.query_with_path(query)
+                .map(|found| found.into_iter().map(|r| (r.path, r.val)).collect())
+                .map_err(|e| format!("JSONPath query error: {}", e)),
+            Self::Rfc9535 => Rfc9535Query::parse(query)
+                .map(|parsed| parsed.select_with_paths(document))
+                .map_err(|e| format!("JSONPath query error (rfc9535) {}", e)),
+        }
+    }
+}
+
+/// Convert a normalized path such as `$['users'][0]` to a JSON Pointer (`/users/0`) for
+/// tools that address locations by pointer.
+pub fn normalized_path_to_pointer(path: &str) -> Option<String> {
+    let mut rest = path.strip_prefix('$')?;
+    let mut pointer = String::new();
+    while !rest.is_empty() {
+        rest = rest.strip_prefix('[')?;
+        let token = if let Some(quoted) = rest.strip_prefix('\'') {
+            let mut name = String::new();
+            let mut chars = quoted.char_indices();
+            loop {
+                match chars.next()? {
+                    (i, '\'') => {
+                        rest = &quoted[i + 1..];
+                        break;
+                    }
+                    (i, '\\') => name.push(match chars.next()?.1 {
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'u' => {
+                            let code = u32::from_str_radix(quoted.get(i + 2..i + 6)?, 16).ok()?;
+                            chars.nth(3)?;
+                            char::from_u32(code)?
+                        }
+                        other => other,
+                    }),
+                    (_, c) => name.push(c),
+                }
+            }
+            name
+        } else {
+            let end = rest.find(']')?;
+            let index = &rest[..end];
+            if index.is_empty() || !index.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            rest = &rest[end..];
+            index.to_string()
+        };
+        rest = rest.strip_prefix(']')?;
+        pointer.push('/');
+        pointer.push_str(&token.replace('~', "~0").replace('/', "~1"));
+    }
+    Some(pointer)
+}
+
+/// JSON Schema for the `dialect` argument.
+pub fn dialect_schema() -> Value {
+    json!({
+        "type": "string",
+        "enum": JsonPathDialect::NAMES,
+        "description": "JSONPath implementation: 'rfc9535' follows the IETF standard strictly (function argument types, comparison rules); 'jsonpath-rust' is more lenient (default: the server's --jsonpath-dialect)"
+    })
+}
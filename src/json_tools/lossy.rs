@@ -0,0 +1,149 @@
+use crate::json_tools::encryption;
+use crate::json_tools::formats::{parse_document, DocumentFormat};
+use crate::json_tools::storage;
+use serde_json::{json, Value};
+use std::io::{self, Read};
+use std::sync::{Arc, Mutex};
+
+/// How many replacement offsets are reported; further replacements are only counted.
+const MAX_REPORTED_OFFSETS: usize = 100;
+const READ_CHUNK_BYTES: usize = 64 * 1024;
+const REPLACEMENT_CHARACTER: &[u8] = "\u{FFFD}".as_bytes();
+
+/// JSON Schema for the `lossy` argument.
+pub fn lossy_schema() -> Value {
+    json!({
+        "type": "boolean",
+        "description": "Replace invalid UTF-8 byte sequences with U+FFFD (�) instead of failing, so a file with a few bad bytes stays readable (default: false). The byte offsets of the replaced sequences are reported as 'utf8_replacements' in the metadata"
+    })
+}
+
+/// The invalid UTF-8 sequences replaced while reading a file.
+#[derive(Debug, Default, Clone)]
+pub struct Replacements {
+    count: usize,
+    /// Byte offsets in the file where the first replaced sequences started.
+    offsets: Vec<u64>,
+}
+
+impl Replacements {
+    fn record(&mut self, offset: u64) {
+        self.count += 1;
+        if self.offsets.len() < MAX_REPORTED_OFFSETS {
+            self.offsets.push(offset);
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// The replacements as reported in result metadata.
+    pub fn describe(&self) -> Value {
+        json!({
+            "count": self.count,
+            "offsets": self.offsets,
+            "offsets_truncated": self.count > self.offsets.len(),
+        })
+    }
+}
+
+/// A reader that passes valid UTF-8 through and replaces each invalid byte sequence by
+/// U+FFFD, recording in `replacements` where the sequence started in the input.
+pub struct LossyReader<R> {
+    inner: R,
+    /// Input not decoded yet because it ends within a character; starts at `offset`.
+    pending: Vec<u8>,
+    offset: u64,
+    decoded: Vec<u8>,
+    returned: usize,
+    at_end: bool,
+    replacements: Arc<Mutex<Replacements>>,
+}
+
+impl<R: Read> LossyReader<R> {
+    pub fn new(inner: R, replacements: Arc<Mutex<Replacements>>) -> Self {
+        Self { inner, pending: Vec::new(), offset: 0, decoded: Vec::new(), returned: 0, at_end: false, replacements }
+    }
+
+    fn fill(&mut self) -> io::Result<()> {
+        let mut chunk = vec![0; READ_CHUNK_BYTES];
+        let read = self.inner.read(&mut chunk)?;
+        self.at_end = read == 0;
+        self.pending.extend_from_slice(&chunk[..read]);
+        self.decoded.clear();
+        self.returned = 0;
+        let mut replacements = self.replacements.lock().unwrap_or_else(|e| e.into_inner());
+        let consumed = decode_into(&self.pending, self.offset, self.at_end, &mut self.decoded, &mut replacements);
+        self.pending.drain(..consumed);
+        self.offset += consumed as u64;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for LossyReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.returned == self.decoded.len() && !self.at_end {
+            self.fill()?;
+        }
+        let count = (self.decoded.len() - self.returned).min(buf.len());
+        buf[..count].copy_from_slice(&self.decoded[self.returned..self.returned + count]);
+        self.returned += count;
+        Ok(count)
+    }
+}
+
+/// Append `input` (found at byte `base` of the file) to `out` with invalid sequences
+/// replaced. Returns how much of it was consumed: a character cut off at the end of the
+/// input is left for the next call unless `at_end`.
+fn decode_into(input: &[u8], base: u64, at_end: bool, out: &mut Vec<u8>, replacements: &mut Replacements) -> usize {
+    let mut position = 0;
+    loop {
+        match std::str::from_utf8(&input[position..]) {
+            Ok(valid) => {
+                out.extend_from_slice(valid.as_bytes());
+                return input.len();
+            }
+            Err(e) => {
+                let valid_end = position + e.valid_up_to();
+                out.extend_from_slice(&input[position..valid_end]);
+                let invalid = match e.error_len() {
+                    Some(len) => len,
+                    None if at_end => input.len() - valid_end,
+                    None => return valid_end,
+                };
+                replacements.record(base + valid_end as u64);
+                out.extend_from_slice(REPLACEMENT_CHARACTER);
+                position = valid_end + invalid;
+            }
+        }
+    }
+}
+
+/// Read a whole document like `formats::read_document`, replacing invalid UTF-8 first in
+/// the text formats (JSON and TOML).
+pub fn read_document_lossy(file_path: &str, format: DocumentFormat) -> anyhow::Result<(Value, Replacements)> {
+    let content = encryption::open(file_path, storage::read(file_path)?)?;
+    let mut replacements = Replacements::default();
+    let content = match format {
+        DocumentFormat::Json | DocumentFormat::Toml => {
+            let mut decoded = Vec::with_capacity(content.len());
+            decode_into(&content, 0, true, &mut decoded, &mut replacements);
+            decoded
+        }
+        DocumentFormat::MessagePack | DocumentFormat::Cbor => content,
+    };
+    let value = parse_document(&content, format)
+        .map_err(|e| anyhow::anyhow!("Failed to parse {} in '{}': {}", format.name().to_uppercase(), file_path, e))?;
+    Ok((value, replacements))
+}
+
+/// `error` with a pointer to `lossy` when it was caused by invalid UTF-8.
+pub fn suggest_lossy(error: anyhow::Error) -> anyhow::Error {
+    let message = format!("{:#}", error);
+    if message.contains("UTF-8") || message.contains("unicode code point") {
+        anyhow::anyhow!("{}. Pass lossy: true to replace invalid UTF-8 sequences and read the rest", message)
+    } else {
+        error
+    }
+}
@@ -0,0 +1,484 @@
+use crate::json_tools::discovery::collect_files;
+use crate::json_tools::formats::{read_document, serialize_for_path, DocumentFormat};
+use crate::json_tools::patch::{parse_pointer, pointer_get, pointer_get_mut};
+use crate::json_tools::paths::PathPolicy;
+use crate::json_tools::storage;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use regex::Regex;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Extensions of the files a `pattern` can select.
+const MIGRATABLE_EXTENSIONS: &[&str] = &["json", "toml", "msgpack", "mpk", "cbor"];
+/// At most this many files are migrated in one call.
+const MAX_FILES: usize = 1000;
+
+/// One step of a migration spec. Paths are JSON Pointers in which a `*` token matches every
+/// member of an object or element of an array.
+enum Step {
+    Rename { path: String, to: String },
+    Move { from: String, path: String },
+    ChangeDefault { path: String, value: Value, old_value: Option<Value> },
+    Split { path: String, into: Vec<String>, separator: String, keep: bool },
+    Set { path: String, value: Value },
+    Remove { path: String },
+}
+
+impl Step {
+    fn parse(spec: &Value) -> Result<Self, String> {
+        let text = |key: &str| spec.get(key).and_then(|v| v.as_str()).map(str::to_string)
+            .ok_or_else(|| format!("needs '{}'", key));
+        let value = |key: &str| spec.get(key).cloned().ok_or_else(|| format!("needs '{}'", key));
+        let path = text("path")?;
+        match spec.get("op").and_then(|v| v.as_str()) {
+            Some("rename") => Ok(Self::Rename { path, to: text("to")? }),
+            Some("move") => {
+                let from = text("from")?;
+                let wildcards = |pointer: &str| pointer.split('/').filter(|token| *token == "*").count();
+                if wildcards(&path) > wildcards(&from) {
+                    return Err("each '*' in path must stand for a '*' in from".to_string());
+                }
+                Ok(Self::Move { from, path })
+            }
+            Some("change_default") => Ok(Self::ChangeDefault { path, value: value("value")?, old_value: spec.get("old_value").cloned() }),
+            Some("split") => {
+                let into: Vec<String> = spec.get("into").and_then(|v| v.as_array())
+                    .map(|names| names.iter().filter_map(|n| n.as_str().map(str::to_string)).collect())
+                    .filter(|names: &Vec<String>| names.len() >= 2)
+                    .ok_or("needs 'into', two or more field names")?;
+                let separator = spec.get("separator").and_then(|v| v.as_str()).unwrap_or(" ").to_string();
+                if separator.is_empty() {
+                    return Err("'separator' must not be empty".to_string());
+                }
+                Ok(Self::Split { path, into, separator, keep: spec.get("keep").and_then(|v| v.as_bool()).unwrap_or(false) })
+            }
+            Some("set") => Ok(Self::Set { path, value: value("value")? }),
+            Some("remove") => Ok(Self::Remove { path }),
+            Some(other) => Err(format!("unknown op '{}'; use rename, move, change_default, split, set or remove", other)),
+            None => Err("needs 'op' (rename, move, change_default, split, set or remove)".to_string()),
+        }
+    }
+
+    fn path(&self) -> &str {
+        match self {
+            Self::Rename { path, .. } | Self::Move { path, .. } | Self::ChangeDefault { path, .. }
+            | Self::Split { path, .. } | Self::Set { path, .. } | Self::Remove { path } => path,
+        }
+    }
+
+    /// Apply the step everywhere its path matches, returning the changes and the reasons
+    /// locations were skipped.
+    fn apply(&self, document: &mut Value) -> anyhow::Result<(Vec<String>, Vec<String>)> {
+        let (mut changes, mut skipped) = (Vec::new(), Vec::new());
+        let selector = match self {
+            Self::Move { from, .. } => from,
+            other => other.path(),
+        };
+        let matches = expand(document, &parse_pointer(selector)?);
+        if matches.is_empty() {
+            skipped.push(format!("{}: not found", selector));
+        }
+        // Later array elements first, so removing or moving one does not shift the others
+        for (tokens, captures) in matches.into_iter().rev() {
+            let pointer = to_pointer(&tokens);
+            match self.apply_at(document, &tokens, &captures)? {
+                Ok(change) => changes.push(change),
+                Err(reason) => skipped.push(format!("{}: {}", pointer, reason)),
+            }
+        }
+        changes.reverse();
+        skipped.reverse();
+        Ok((changes, skipped))
+    }
+
+    fn apply_at(&self, document: &mut Value, tokens: &[String], captures: &[String]) -> anyhow::Result<Result<String, String>> {
+        let pointer = to_pointer(tokens);
+        let exists = pointer_get(document, tokens).is_ok();
+        Ok(match self {
+            Self::Rename { to, .. } => {
+                let Some((last, parent)) = tokens.split_last() else {
+                    return Ok(Err("the document root has no name".to_string()));
+                };
+                match pointer_get_mut(document, parent) {
+                    Ok(Value::Object(map)) if map.contains_key(last) && map.contains_key(to) => Err(format!("'{}' already exists", to)),
+                    Ok(Value::Object(map)) if map.contains_key(last) => {
+                        // Rebuild the object so the renamed key keeps its position
+                        *map = std::mem::take(map).into_iter()
+                            .map(|(key, value)| if key == *last { (to.clone(), value) } else { (key, value) })
+                            .collect();
+                        let mut renamed = parent.to_vec();
+                        renamed.push(to.clone());
+                        Ok(format!("renamed {} to {}", pointer, to_pointer(&renamed)))
+                    }
+                    _ => Err("not found".to_string()),
+                }
+            }
+            Self::Move { path, .. } => {
+                let mut captures = captures.iter();
+                let destination: Vec<String> = parse_pointer(path)?.into_iter()
+                    .map(|token| if token == "*" { captures.next().cloned().unwrap_or(token) } else { token })
+                    .collect();
+                if !exists {
+                    Err("not found".to_string())
+                } else if pointer_get(document, &destination).is_ok() {
+                    Err(format!("{} already exists", to_pointer(&destination)))
+                } else if destination.starts_with(tokens) {
+                    Err(format!("cannot move into itself ({})", to_pointer(&destination)))
+                } else {
+                    let value = remove_at(document, tokens).expect("the source exists");
+                    match insert_at(document, &destination, value.clone()) {
+                        Ok(()) => Ok(format!("moved {} to {}", pointer, to_pointer(&destination))),
+                        Err(reason) => {
+                            insert_at(document, tokens, value).expect("the source's parent exists");
+                            Err(reason)
+                        }
+                    }
+                }
+            }
+            Self::ChangeDefault { value, old_value, .. } => match pointer_get(document, tokens) {
+                Err(_) => insert_at(document, tokens, value.clone())
+                    .map(|()| format!("added default {} = {}", pointer, value)),
+                Ok(current) if Some(current) == old_value.as_ref() => {
+                    let current = current.clone();
+                    *pointer_get_mut(document, tokens)? = value.clone();
+                    Ok(format!("changed default {} from {} to {}", pointer, current, value))
+                }
+                Ok(current) if current == value => Err("already has the new default".to_string()),
+                Ok(current) => Err(format!("keeps its configured value {}", current)),
+            },
+            Self::Split { into, separator, keep, .. } => {
+                let Some((last, parent)) = tokens.split_last() else {
+                    return Ok(Err("the document root cannot be split".to_string()));
+                };
+                match pointer_get_mut(document, parent) {
+                    Ok(Value::Object(map)) => match map.get(last) {
+                        Some(Value::String(text)) => {
+                            let parts: Vec<String> = text.splitn(into.len(), separator.as_str()).map(str::to_string).collect();
+                            if parts.len() < into.len() {
+                                Err(format!("has {} part(s) separated by {:?}, not {}", parts.len(), separator, into.len()))
+                            } else {
+                                if !keep && !into.contains(last) {
+                                    map.shift_remove(last);
+                                }
+                                for (name, part) in into.iter().zip(parts) {
+                                    map.insert(name.clone(), Value::String(part));
+                                }
+                                Ok(format!("split {} into {}", pointer, into.join(", ")))
+                            }
+                        }
+                        Some(_) => Err("not a string".to_string()),
+                        None => Err("not found".to_string()),
+                    },
+                    _ => Err("not found".to_string()),
+                }
+            }
+            Self::Set { value, .. } => match pointer_get_mut(document, tokens) {
+                Ok(current) if current == value => Err("already has this value".to_string()),
+                Ok(current) => {
+                    *current = value.clone();
+                    Ok(format!("set {} = {}", pointer, value))
+                }
+                Err(_) => insert_at(document, tokens, value.clone()).map(|()| format!("set {} = {}", pointer, value)),
+            },
+            Self::Remove { .. } => match remove_at(document, tokens) {
+                Some(_) => Ok(format!("removed {}", pointer)),
+                None => Err("not found".to_string()),
+            },
+        })
+    }
+}
+
+/// The concrete locations a pointer with `*` tokens matches, each with the members the
+/// wildcards stood for. A path without wildcards always yields itself, existing or not.
+fn expand(document: &Value, tokens: &[String]) -> Vec<(Vec<String>, Vec<String>)> {
+    let mut found = vec![(Vec::new(), Vec::new())];
+    for token in tokens {
+        let mut next = Vec::new();
+        for (prefix, captures) in found {
+            if token != "*" {
+                let mut prefix: Vec<String> = prefix;
+                prefix.push(token.clone());
+                next.push((prefix, captures));
+                continue;
+            }
+            let members: Vec<String> = match pointer_get(document, &prefix) {
+                Ok(Value::Object(map)) => map.keys().cloned().collect(),
+                Ok(Value::Array(items)) => (0..items.len()).map(|i| i.to_string()).collect(),
+                _ => Vec::new(),
+            };
+            for member in members {
+                let (mut prefix, mut captures) = (prefix.clone(), captures.clone());
+                prefix.push(member.clone());
+                captures.push(member);
+                next.push((prefix, captures));
+            }
+        }
+        found = next;
+    }
+    found
+}
+
+fn to_pointer(tokens: &[String]) -> String {
+    tokens.iter().map(|t| format!("/{}", t.replace('~', "~0").replace('/', "~1"))).collect()
+}
+
+/// Set the value at `tokens`, creating missing parent objects.
+fn insert_at(document: &mut Value, tokens: &[String], value: Value) -> Result<(), String> {
+    let Some((last, parent)) = tokens.split_last() else {
+        *document = value;
+        return Ok(());
+    };
+    let mut current = document;
+    for token in parent {
+        current = match current {
+            Value::Object(map) => map.entry(token.clone()).or_insert_with(|| Value::Object(Map::new())),
+            Value::Array(items) => match token.parse::<usize>().ok().and_then(|i| items.get_mut(i)) {
+                Some(item) => item,
+                None => return Err(format!("no element {} in the array", token)),
+            },
+            _ => return Err(format!("cannot create '{}' inside a scalar", token)),
+        };
+    }
+    match current {
+        Value::Object(map) => {
+            map.insert(last.clone(), value);
+            Ok(())
+        }
+        Value::Array(items) => match last.parse::<usize>() {
+            Ok(index) if index <= items.len() => {
+                items.insert(index, value);
+                Ok(())
+            }
+            _ => Err(format!("no element {} in the array", last)),
+        },
+        _ => Err(format!("cannot create '{}' inside a scalar", last)),
+    }
+}
+
+fn remove_at(document: &mut Value, tokens: &[String]) -> Option<Value> {
+    let (last, parent) = tokens.split_last()?;
+    match pointer_get_mut(document, parent).ok()? {
+        Value::Object(map) => map.shift_remove(last),
+        Value::Array(items) => last.parse::<usize>().ok().filter(|&i| i < items.len()).map(|i| items.remove(i)),
+        _ => None,
+    }
+}
+
+/// A glob over `/`-separated relative paths: `*` and `?` stay within one directory, `**`
+/// spans any number of them.
+fn glob_regex(pattern: &str) -> Result<Regex, String> {
+    let mut source = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    source.push_str("(?:.*/)?");
+                } else {
+                    source.push_str(".*");
+                }
+            }
+            '*' => source.push_str("[^/]*"),
+            '?' => source.push_str("[^/]"),
+            c => source.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    source.push('$');
+    Regex::new(&source).map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))
+}
+
+pub struct JsonMigrate {
+    paths: PathPolicy,
+}
+
+impl Default for JsonMigrate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonMigrate {
+    pub fn new() -> Self {
+        Self { paths: PathPolicy::default() }
+    }
+
+    /// Only migrate files a directory pattern selects within `paths`' allowed roots.
+    pub fn with_path_policy(paths: PathPolicy) -> Self {
+        Self { paths }
+    }
+
+    fn create_migrate_tool() -> Tool {
+        Tool {
+            name: "json-migrate".to_string(),
+            description: "Apply a declarative migration (rename key, move path, change default, split field, set, remove) to one file or to every file a glob selects, reporting the changes per file. Steps are idempotent: a step whose source is gone is skipped, so a migration can be re-run safely.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "One file to migrate"
+                    },
+                    "file_paths": {
+                        "type": "array",
+                        "items": {"type": "string"},
+                        "description": "Several files to migrate"
+                    },
+                    "directory": {
+                        "type": "string",
+                        "description": "Directory searched with pattern"
+                    },
+                    "pattern": {
+                        "type": "string",
+                        "description": "Glob relative to directory selecting the files, e.g. 'services/*/config.json' or '**/*.toml' (default: '*.json')"
+                    },
+                    "migration": {
+                        "type": "array",
+                        "description": "Steps applied in order. Paths are JSON Pointers; a '*' token matches every member or element. Ops: {op: 'rename', path, to} renames the key in place; {op: 'move', from, path} moves a value (a '*' in path takes the member the '*' in from matched); {op: 'change_default', path, value, old_value} adds the field when missing or replaces old_value; {op: 'split', path, into: [names], separator} splits a string into sibling fields; {op: 'set', path, value}; {op: 'remove', path}",
+                        "items": {"type": "object"}
+                    },
+                    "migration_path": {
+                        "type": "string",
+                        "description": "JSON file holding the migration steps (an array, or an object with 'steps'), instead of migration"
+                    },
+                    "dry_run": {
+                        "type": "boolean",
+                        "description": "Report the changes without writing anything (default: false)",
+                        "default": false
+                    }
+                }
+            })
+        }
+    }
+
+    async fn handle_migrate(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let spec = match (args.get("migration"), args.get("migration_path").and_then(|v| v.as_str())) {
+            (Some(migration), None) => migration.clone(),
+            (None, Some(path)) => match read_document(path, DocumentFormat::from_path(path))? {
+                Value::Object(mut spec) => spec.remove("steps").unwrap_or(Value::Null),
+                steps => steps,
+            },
+            (Some(_), Some(_)) => return Ok(ToolResult::error("Provide either migration or migration_path, not both".to_string())),
+            (None, None) => anyhow::bail!(
+                "migration is required. Usage example:\n{{\n  \"directory\": \"./services\",\n  \"pattern\": \"*/config.json\",\n  \"migration\": [\n    {{\"op\": \"rename\", \"path\": \"/server/host\", \"to\": \"hostname\"}},\n    {{\"op\": \"change_default\", \"path\": \"/server/timeout\", \"value\": 30, \"old_value\": 10}}\n  ]\n}}"
+            ),
+        };
+        let Some(specs) = spec.as_array().filter(|steps| !steps.is_empty()) else {
+            return Ok(ToolResult::error("The migration must be a non-empty array of steps".to_string()));
+        };
+        let mut steps = Vec::new();
+        for (index, spec) in specs.iter().enumerate() {
+            match Step::parse(spec) {
+                Ok(step) => steps.push(step),
+                Err(message) => return Ok(ToolResult::error(format!("Migration step {} {}: {}", index + 1, spec, message))),
+            }
+        }
+        let dry_run = args.get("dry_run").and_then(|v| v.as_bool()).unwrap_or(false);
+
+        let mut files: Vec<PathBuf> = args.get("file_path").and_then(|v| v.as_str()).map(PathBuf::from).into_iter().collect();
+        if let Some(Value::Array(paths)) = args.get("file_paths") {
+            files.extend(paths.iter().filter_map(|v| v.as_str()).map(PathBuf::from));
+        }
+        if let Some(directory) = args.get("directory").and_then(|v| v.as_str()) {
+            let pattern = args.get("pattern").and_then(|v| v.as_str()).unwrap_or("*.json");
+            let matcher = match glob_regex(pattern) {
+                Ok(matcher) => matcher,
+                Err(message) => return Ok(ToolResult::error(message)),
+            };
+            let root = Path::new(directory);
+            if !root.is_dir() {
+                return Ok(ToolResult::error(format!("'{}' is not a directory", directory)));
+            }
+            let extensions: Vec<String> = MIGRATABLE_EXTENSIONS.iter().map(|e| e.to_string()).collect();
+            let mut found: Vec<PathBuf> = collect_files(root, true, &extensions, &|_| false)?
+                .into_iter()
+                .filter(|path| path.strip_prefix(root).is_ok_and(|relative| {
+                    let relative: Vec<String> = relative.components().map(|c| c.as_os_str().to_string_lossy().into_owned()).collect();
+                    matcher.is_match(&relative.join("/"))
+                }))
+                .collect();
+            found.sort();
+            for path in found {
+                files.push(self.paths.resolve_derived(&path)?);
+            }
+        }
+        files.dedup();
+        if files.is_empty() {
+            return Ok(ToolResult::error(
+                "No files to migrate. Give file_path, file_paths, or a directory and pattern that match at least one file".to_string()
+            ));
+        }
+        if files.len() > MAX_FILES {
+            return Ok(ToolResult::error(format!(
+                "The selection matches {} files, more than the {} one call migrates. Narrow the pattern", files.len(), MAX_FILES
+            )));
+        }
+
+        // Migrate every file in memory first, so a file that cannot be read stops the run
+        // before anything is written
+        let mut results = Vec::new();
+        for file in &files {
+            let path = file.to_string_lossy().to_string();
+            let format = DocumentFormat::from_path(&path);
+            let mut document = read_document(&path, format)
+                .map_err(|e| anyhow::anyhow!("Cannot migrate '{}': {}; no file was changed", path, e))?;
+            let (mut changes, mut skipped) = (Vec::new(), Vec::new());
+            for (index, step) in steps.iter().enumerate() {
+                let (changed, missed) = step.apply(&mut document)?;
+                changes.extend(changed);
+                skipped.extend(missed.into_iter().map(|reason| format!("step {}: {}", index + 1, reason)));
+            }
+            let content = if changes.is_empty() { None } else {
+                match serialize_for_path(&path, &document, format, true) {
+                    Ok(content) => Some(content),
+                    Err(e) => return Ok(ToolResult::error(format!("Cannot write '{}' as {}: {}; no file was changed", path, format.name(), e))),
+                }
+            };
+            results.push((path, changes, skipped, content));
+        }
+
+        let mut changed_files = 0;
+        let mut report = Vec::new();
+        for (path, changes, skipped, content) in results {
+            if let Some(content) = content {
+                changed_files += 1;
+                if !dry_run {
+                    storage::write(&path, &content)?;
+                }
+            }
+            let mut entry = json!({"file": path, "changed": !changes.is_empty(), "changes": changes});
+            if !skipped.is_empty() {
+                entry["skipped"] = json!(skipped);
+            }
+            report.push(entry);
+        }
+
+        let outcome = if dry_run {
+            format!("{} would change; nothing was written", changed_files)
+        } else {
+            format!("{} changed", changed_files)
+        };
+        Ok(ToolResult::success(format!(
+            "Migrated {} file(s), {}:\n\n{}",
+            files.len(), outcome, serde_json::to_string_pretty(&json!({"files": report}))?
+        )))
+    }
+}
+
+#[async_trait]
+impl ToolHandler for JsonMigrate {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_migrate_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-migrate" => self.handle_migrate(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
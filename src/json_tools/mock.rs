@@ -0,0 +1,613 @@
+use crate::json_tools::dates::key_words;
+use crate::json_tools::files::{RecordFormat, RecordWriter};
+use crate::json_tools::formats::{read_document, DocumentFormat};
+use crate::json_tools::timewindow::parse_timestamp;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, SecondsFormat, TimeZone, Utc};
+use serde_json::{json, Map, Number, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+const FIRST_NAMES: &[&str] = &[
+    "Ada", "Amara", "Ben", "Carlos", "Chen", "Dana", "Elena", "Farah", "Grace", "Hiro", "Ines", "Jonas",
+    "Kofi", "Lena", "Mateo", "Maya", "Nikhil", "Olivia", "Priya", "Rafael", "Sara", "Tomas", "Yara", "Zoe",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Andersen", "Baker", "Costa", "Dubois", "Evans", "Fischer", "Garcia", "Haddad", "Ito", "Jensen", "Kim", "Lopez",
+    "Mensah", "Novak", "Okafor", "Patel", "Quinn", "Rossi", "Schmidt", "Tanaka", "Usman", "Varga", "Wong", "Young",
+];
+
+const CITIES: &[&str] = &[
+    "Amsterdam", "Austin", "Barcelona", "Chicago", "Denver", "Dublin", "Lagos", "Lisbon",
+    "Melbourne", "Montreal", "Nairobi", "Osaka", "Portland", "Seoul", "Toronto", "Vienna",
+];
+
+const STREETS: &[&str] = &[
+    "Oak Street", "Maple Avenue", "Cedar Lane", "Elm Road", "Harbor Drive", "Hillside Way",
+    "Lake Street", "Mill Road", "Park Avenue", "River Road", "Station Road", "Sunset Boulevard",
+];
+
+const COMPANIES: &[&str] = &[
+    "Acme Corp", "Blue Harbor Ltd", "Brightline Labs", "Cobalt Systems", "Evergreen Foods", "Globex",
+    "Initech", "Northwind Traders", "Pinecone Analytics", "Redwood Logistics", "Summit Health", "Umbrella Partners",
+];
+
+/// Words in a key name that mark its numbers as epoch timestamps.
+const TIME_WORDS: &[&str] = &["at", "time", "timestamp", "ts", "date", "created", "updated", "modified", "expires"];
+
+/// Most variants one call may generate, and how many it may return without an output_path.
+const MAX_COUNT: usize = 100_000;
+const MAX_INLINE: usize = 100;
+
+const USAGE: &str = "{\n  \"file_path\": \"./examples/user-response.json\",\n  \"count\": 50,\n  \"rules\": {\"age\": \"int:18..90\", \"role\": \"enum:[admin,member]\"},\n  \"output_path\": \"./fixtures/users.ndjson\"\n}";
+
+const RULE_KINDS: &str = "email, uuid, name, first_name, last_name, username, city, street, company, phone, int:MIN..MAX, float:MIN..MAX, bool, enum:[a,b,c], date[:FROM..TO], datetime[:FROM..TO], string[:LENGTH], seq[:START], const:VALUE";
+
+/// What a value is taken to be, from its key and its shape.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Fake {
+    FirstName,
+    LastName,
+    FullName,
+    Username,
+    Email,
+    City,
+    Street,
+    Company,
+    Phone,
+    Uuid,
+    Id,
+    Timestamp,
+}
+
+/// How to generate a field, as given in `rules`.
+#[derive(Debug, Clone, PartialEq)]
+enum Rule {
+    Fake(Fake),
+    Int(i64, i64),
+    /// Bounds and the number of decimals to round to.
+    Float(f64, f64, usize),
+    Bool,
+    Enum(Vec<Value>),
+    /// Dates (or date-times with `time`) between the bounds, by default the tool's date range.
+    Date { from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>, time: bool },
+    Text(usize),
+    /// The next number of a sequence.
+    Seq(i64),
+    Const(Value),
+}
+
+impl Rule {
+    fn parse(text: &str) -> Result<Self, String> {
+        let (kind, argument) = match text.split_once(':') {
+            Some((kind, argument)) => (kind.trim(), Some(argument.trim())),
+            None => (text.trim(), None),
+        };
+        let range = || argument.and_then(|a| a.split_once(".."))
+            .map(|(low, high)| (low.trim(), high.trim()))
+            .ok_or_else(|| format!("'{}' needs a range, e.g. {}:1..100", text, kind));
+        let fake = match kind {
+            "email" => Some(Fake::Email),
+            "uuid" => Some(Fake::Uuid),
+            "name" | "full_name" => Some(Fake::FullName),
+            "first_name" => Some(Fake::FirstName),
+            "last_name" => Some(Fake::LastName),
+            "username" => Some(Fake::Username),
+            "city" => Some(Fake::City),
+            "street" | "address" => Some(Fake::Street),
+            "company" => Some(Fake::Company),
+            "phone" => Some(Fake::Phone),
+            _ => None,
+        };
+        if let (Some(fake), None) = (fake, argument) {
+            return Ok(Rule::Fake(fake));
+        }
+        match (kind, argument) {
+            ("int", Some(_)) => {
+                let (low, high) = range()?;
+                match (low.parse::<i64>(), high.parse::<i64>()) {
+                    (Ok(low), Ok(high)) if low <= high => Ok(Rule::Int(low, high)),
+                    _ => Err(format!("'{}' needs whole numbers MIN..MAX with MIN <= MAX", text)),
+                }
+            }
+            ("float", Some(_)) => {
+                let (low_text, high_text) = range()?;
+                let decimals = [low_text, high_text].iter()
+                    .map(|bound| bound.split_once('.').map_or(0, |(_, d)| d.len()))
+                    .max()
+                    .filter(|d| *d > 0)
+                    .unwrap_or(2);
+                match (low_text.parse::<f64>(), high_text.parse::<f64>()) {
+                    (Ok(low), Ok(high)) if low <= high => Ok(Rule::Float(low, high, decimals.min(6))),
+                    _ => Err(format!("'{}' needs numbers MIN..MAX with MIN <= MAX", text)),
+                }
+            }
+            ("bool", None) => Ok(Rule::Bool),
+            ("enum", Some(list)) => {
+                let values = serde_json::from_str::<Vec<Value>>(list).unwrap_or_else(|_| {
+                    list.trim_start_matches('[').trim_end_matches(']')
+                        .split(',')
+                        .map(|value| json!(value.trim()))
+                        .collect()
+                });
+                if values.is_empty() || values == [json!("")] {
+                    return Err(format!("'{}' needs at least one value, e.g. enum:[a,b,c]", text));
+                }
+                Ok(Rule::Enum(values))
+            }
+            ("date" | "datetime", _) => {
+                let (from, to) = match argument {
+                    None => (None, None),
+                    Some(_) => {
+                        let (low, high) = range()?;
+                        match (parse_timestamp(&json!(low)), parse_timestamp(&json!(high))) {
+                            (Some(from), Some(to)) if from <= to => (Some(from), Some(to)),
+                            _ => return Err(format!("'{}' needs dates FROM..TO with FROM not after TO", text)),
+                        }
+                    }
+                };
+                Ok(Rule::Date { from, to, time: kind == "datetime" })
+            }
+            ("string", length) => match length.map_or(Ok(8), str::parse::<usize>) {
+                Ok(length @ 1..=1000) => Ok(Rule::Text(length)),
+                _ => Err(format!("'{}' needs a length from 1 to 1000", text)),
+            },
+            ("seq", start) => start.map_or(Ok(1), str::parse::<i64>)
+                .map(Rule::Seq)
+                .map_err(|_| format!("'{}' needs a whole number to start from", text)),
+            ("const", Some(value)) => Ok(Rule::Const(serde_json::from_str(value).unwrap_or_else(|_| json!(value)))),
+            _ => Err(format!("unknown rule '{}'. Rules: {}", text, RULE_KINDS)),
+        }
+    }
+}
+
+/// A document with a null at every field the rules name, for generating from rules alone.
+fn rules_sample(rules: &[(String, Rule)]) -> Value {
+    let mut sample = Map::new();
+    for (field, _) in rules {
+        let parts: Vec<&str> = field.split('.').collect();
+        let mut target = &mut sample;
+        for part in &parts[..parts.len() - 1] {
+            let entry = target.entry(part.to_string()).or_insert_with(|| json!({}));
+            if !entry.is_object() {
+                *entry = json!({});
+            }
+            target = entry.as_object_mut().expect("made an object above");
+        }
+        target.entry(parts[parts.len() - 1].to_string()).or_insert(Value::Null);
+    }
+    Value::Object(sample)
+}
+
+fn is_uuid(text: &str) -> bool {
+    text.len() == 36 && text.char_indices().all(|(i, c)| match i {
+        8 | 13 | 18 | 23 => c == '-',
+        _ => c.is_ascii_hexdigit(),
+    })
+}
+
+fn is_email(text: &str) -> bool {
+    text.split_once('@').is_some_and(|(user, domain)| {
+        !user.is_empty() && domain.contains('.') && !domain.contains('@') && !text.contains(char::is_whitespace)
+    })
+}
+
+/// Two or three capitalized words, like "Ann Lee".
+fn is_person_name(text: &str) -> bool {
+    let words: Vec<&str> = text.split(' ').collect();
+    (2..=3).contains(&words.len()) && words.iter().all(|word| {
+        word.starts_with(|c: char| c.is_uppercase()) && word.chars().all(|c| c.is_alphabetic() || c == '-' || c == '\'')
+    })
+}
+
+fn is_id_key(key: &str) -> bool {
+    key.eq_ignore_ascii_case("id") || ["_id", "-id", "Id", "ID"].iter().any(|suffix| key.ends_with(suffix))
+}
+
+/// A date or date-time string such as `2024-05-01` or `2024-05-01T12:00:00Z`.
+fn is_date(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    bytes.len() >= 10 && bytes[..4].iter().all(u8::is_ascii_digit) && bytes[4] == b'-'
+        && parse_timestamp(&json!(text)).is_some()
+}
+
+fn detect(key: Option<&str>, value: &Value) -> Option<Fake> {
+    let words = key.map(key_words).unwrap_or_default();
+    let last = words.last().map(String::as_str);
+    let joined = words.concat();
+    match value {
+        Value::String(text) => {
+            if is_uuid(text) {
+                return Some(Fake::Uuid);
+            }
+            if is_email(text) {
+                return Some(Fake::Email);
+            }
+            if is_date(text) {
+                return Some(Fake::Timestamp);
+            }
+            match joined.as_str() {
+                "firstname" | "givenname" | "forename" => return Some(Fake::FirstName),
+                "lastname" | "surname" | "familyname" => return Some(Fake::LastName),
+                "username" | "login" | "handle" | "screenname" => return Some(Fake::Username),
+                "city" | "town" => return Some(Fake::City),
+                "company" | "companyname" | "organization" | "organisation" | "employer" => return Some(Fake::Company),
+                _ => {}
+            }
+            match last {
+                Some("street") => Some(Fake::Street),
+                Some("address" | "line1") if text.starts_with(|c: char| c.is_ascii_digit()) => Some(Fake::Street),
+                Some("phone" | "mobile" | "tel" | "telephone" | "fax") => Some(Fake::Phone),
+                Some("number") if joined.ends_with("phonenumber") => Some(Fake::Phone),
+                Some("name") if is_person_name(text) => Some(Fake::FullName),
+                _ if key.is_some_and(is_id_key) => Some(Fake::Id),
+                _ => None,
+            }
+        }
+        Value::Number(number) => {
+            if key.is_some_and(is_id_key) && number.is_u64() {
+                return Some(Fake::Id);
+            }
+            let plausible = parse_timestamp(value).is_some_and(|t| (1971..=2200).contains(&t.year()));
+            (plausible && number.is_u64() && words.iter().any(|w| TIME_WORDS.contains(&w.as_str()))).then_some(Fake::Timestamp)
+        }
+        _ => None,
+    }
+}
+
+/// A new id shaped like `sample`: the same number of digits, or the same character classes
+/// after any prefix such as `usr_`.
+fn reshape_id(rng: &mut fastrand::Rng, sample: &Value) -> Value {
+    if let Some(number) = sample.as_u64() {
+        let digits = number.to_string().len().clamp(1, 18) as u32;
+        let low = if digits == 1 { 1 } else { 10u64.pow(digits - 1) };
+        return json!(rng.u64(low..10u64.pow(digits)));
+    }
+    let text = sample.as_str().unwrap_or_default();
+    let (prefix, body) = match text.rfind(['_', '-', ':']) {
+        Some(at) => text.split_at(at + 1),
+        None => ("", text),
+    };
+    let hex = body.chars().all(|c| c.is_ascii_hexdigit()) && body.chars().any(|c| c.is_ascii_alphabetic());
+    let body: String = body.chars().map(|c| match c {
+        _ if hex && c.is_ascii_uppercase() => rng.digit(16).to_ascii_uppercase(),
+        _ if hex => rng.digit(16),
+        '0'..='9' => rng.digit(10),
+        'a'..='z' => rng.lowercase(),
+        'A'..='Z' => rng.uppercase(),
+        other => other,
+    }).collect();
+    json!(format!("{}{}", prefix, body))
+}
+
+/// Makes variants of a sample document from a seeded random source.
+struct Mocker {
+    rng: fastrand::Rng,
+    /// Where generated dates fall; by default within a year either side of the sample's.
+    dates: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    /// Ids handed out so far per key, so they do not repeat.
+    ids: HashMap<String, HashSet<String>>,
+    /// Field name or dotted path, and how to generate it.
+    rules: Vec<(String, Rule)>,
+    /// Which rules have been applied, to report those that matched nothing.
+    matched: Vec<bool>,
+}
+
+impl Mocker {
+    fn new(seed: u64, dates: Option<(DateTime<Utc>, DateTime<Utc>)>, rules: Vec<(String, Rule)>) -> Self {
+        let matched = vec![false; rules.len()];
+        Self { rng: fastrand::Rng::with_seed(seed), dates, ids: HashMap::new(), rules, matched }
+    }
+
+    fn pick(&mut self, pool: &[&'static str]) -> &'static str {
+        pool[self.rng.usize(..pool.len())]
+    }
+
+    /// The rule for the field at `path` (object keys joined by dots): one naming the path,
+    /// else one naming the key.
+    fn rule_for(&self, key: Option<&str>, path: &str) -> Option<usize> {
+        self.rules.iter().position(|(field, _)| field == path)
+            .or_else(|| key.and_then(|key| self.rules.iter().position(|(field, _)| field == key)))
+    }
+
+    fn vary(&mut self, value: &Value, key: Option<&str>, path: &str) -> Value {
+        // Rules for arrays apply to their elements
+        if let Some(index) = self.rule_for(key, path).filter(|_| !value.is_array()) {
+            self.matched[index] = true;
+            return self.apply(index);
+        }
+        if let Some(fake) = detect(key, value) {
+            return self.fake(fake, value, key);
+        }
+        match value {
+            Value::Bool(_) => Value::Bool(self.rng.bool()),
+            Value::Number(number) => self.number(number),
+            Value::Array(items) if !items.is_empty() => {
+                // Lists of records change length; tuples such as coordinates keep theirs
+                let count = if items.iter().all(Value::is_object) {
+                    self.rng.usize(items.len().div_ceil(2)..=items.len() * 2)
+                } else {
+                    items.len()
+                };
+                (0..count).map(|i| self.vary(&items[i % items.len()], key, path)).collect()
+            }
+            Value::Object(map) => map.iter().map(|(k, v)| {
+                let child = if path.is_empty() { k.clone() } else { format!("{}.{}", path, k) };
+                (k.clone(), self.vary(v, Some(k), &child))
+            }).collect(),
+            other => other.clone(),
+        }
+    }
+
+    fn apply(&mut self, index: usize) -> Value {
+        if let Rule::Fake(fake) = self.rules[index].1 {
+            return self.fake(fake, &Value::Null, None);
+        }
+        let dates = self.dates;
+        let rng = &mut self.rng;
+        match &mut self.rules[index].1 {
+            Rule::Int(low, high) => json!(rng.i64(*low..=*high)),
+            Rule::Float(low, high, decimals) => {
+                let scale = 10f64.powi(*decimals as i32);
+                json!(((*low + (*high - *low) * rng.f64()) * scale).round() / scale)
+            }
+            Rule::Bool => json!(rng.bool()),
+            Rule::Enum(values) => values[rng.usize(..values.len())].clone(),
+            Rule::Date { from, to, time } => {
+                let now = Utc::now();
+                let (from, to) = match (*from, *to, dates) {
+                    (Some(from), Some(to), _) => (from, to),
+                    (_, _, Some(range)) => range,
+                    _ => (now - Duration::days(365), now),
+                };
+                let when = Utc.timestamp_opt(rng.i64(from.timestamp()..=to.timestamp()), 0).single().unwrap_or(now);
+                json!(if *time { when.to_rfc3339_opts(SecondsFormat::Secs, true) } else { when.format("%Y-%m-%d").to_string() })
+            }
+            Rule::Text(length) => json!((0..*length).map(|_| rng.alphanumeric()).collect::<String>()),
+            Rule::Seq(next) => {
+                *next += 1;
+                json!(*next - 1)
+            }
+            Rule::Const(value) => value.clone(),
+            Rule::Fake(_) => Value::Null,
+        }
+    }
+
+    /// Within half the sample's magnitude either way, with as many decimals.
+    fn number(&mut self, number: &Number) -> Value {
+        if let Some(n) = number.as_i64() {
+            let (a, b) = (n - n / 2, n.saturating_add(n / 2));
+            return json!(self.rng.i64(a.min(b)..=a.max(b)));
+        }
+        let n = number.as_f64().unwrap_or_default();
+        let decimals = number.to_string().split_once('.').map_or(0, |(_, d)| d.len().min(6));
+        let scale = 10f64.powi(decimals as i32);
+        json!((n * (0.5 + self.rng.f64()) * scale).round() / scale)
+    }
+
+    fn fake(&mut self, fake: Fake, sample: &Value, key: Option<&str>) -> Value {
+        let text = sample.as_str().unwrap_or_default();
+        match fake {
+            Fake::FirstName => json!(self.pick(FIRST_NAMES)),
+            Fake::LastName => json!(self.pick(LAST_NAMES)),
+            Fake::FullName => json!(format!("{} {}", self.pick(FIRST_NAMES), self.pick(LAST_NAMES))),
+            Fake::Username => {
+                let (first, last) = (self.pick(FIRST_NAMES), self.pick(LAST_NAMES));
+                json!(format!("{}{}{}", &first[..1], last, self.rng.u32(1..100)).to_lowercase())
+            }
+            Fake::Email => {
+                let domain = text.rsplit_once('@').map_or("example.com", |(_, domain)| domain);
+                let (first, last) = (self.pick(FIRST_NAMES), self.pick(LAST_NAMES));
+                json!(format!("{}.{}@{}", first.to_lowercase(), last.to_lowercase(), domain))
+            }
+            Fake::City => json!(self.pick(CITIES)),
+            Fake::Street => json!(format!("{} {}", self.rng.u32(1..1000), self.pick(STREETS))),
+            Fake::Company => json!(self.pick(COMPANIES)),
+            Fake::Phone => {
+                // Keep everything up to the first digit, such as a country code's "+"
+                let text = if text.is_empty() { "+1 555-010-0000" } else { text };
+                let first_digit = text.find(|c: char| c.is_ascii_digit()).unwrap_or(0);
+                json!(text.char_indices()
+                    .map(|(i, c)| if c.is_ascii_digit() && i > first_digit { self.rng.digit(10) } else { c })
+                    .collect::<String>())
+            }
+            Fake::Uuid => {
+                let mut bytes = [0u8; 16];
+                self.rng.fill(&mut bytes);
+                let uuid = uuid::Builder::from_random_bytes(bytes).into_uuid().to_string();
+                json!(if text.contains(|c: char| c.is_ascii_uppercase()) { uuid.to_uppercase() } else { uuid })
+            }
+            Fake::Id => {
+                let key = key.unwrap_or_default().to_string();
+                let mut id = reshape_id(&mut self.rng, sample);
+                for _ in 0..32 {
+                    if self.ids.entry(key.clone()).or_default().insert(id.to_string()) {
+                        break;
+                    }
+                    id = reshape_id(&mut self.rng, sample);
+                }
+                id
+            }
+            Fake::Timestamp => self.timestamp(sample),
+        }
+    }
+
+    /// A random moment in the date range, written the way the sample is.
+    fn timestamp(&mut self, sample: &Value) -> Value {
+        let Some(at) = parse_timestamp(sample) else {
+            return sample.clone();
+        };
+        let (from, to) = self.dates.unwrap_or((at - Duration::days(365), at + Duration::days(365)));
+        let seconds = self.rng.i64(from.timestamp()..=to.timestamp());
+        let millis = self.rng.u32(0..1000);
+        let when = Utc.timestamp_opt(seconds, millis * 1_000_000).single().unwrap_or(at);
+        let Value::String(text) = sample else {
+            return if sample.as_f64().unwrap_or_default() < 1e11 { json!(seconds) } else { json!(when.timestamp_millis()) };
+        };
+        if NaiveDate::parse_from_str(text, "%Y-%m-%d").is_ok() {
+            return json!(when.format("%Y-%m-%d").to_string());
+        }
+        match DateTime::parse_from_rfc3339(text) {
+            Ok(original) => {
+                let format = if text.contains('.') { SecondsFormat::Millis } else { SecondsFormat::Secs };
+                json!(when.with_timezone(original.offset()).to_rfc3339_opts(format, text.ends_with(['Z', 'z'])))
+            }
+            Err(_) => json!(when.format(if text.contains('T') { "%Y-%m-%dT%H:%M:%S" } else { "%Y-%m-%d %H:%M:%S" }).to_string()),
+        }
+    }
+}
+
+pub struct JsonMock;
+
+impl Default for JsonMock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonMock {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn create_mock_tool() -> Tool {
+        Tool {
+            name: "json-mock".to_string(),
+            description: "Generate realistic variants of a sample response file for seeding test environments. Ids, UUIDs, dates, names, emails, phone numbers, cities, numbers and booleans are randomized by field name and shape; the structure, enum-like strings and other text are kept. Per-field rules (email, uuid, int:1..100, enum:[a,b,c], ...) override that, or describe records on their own without a sample.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "Example response to vary (optional with rules)"
+                    },
+                    "rules": {
+                        "type": "object",
+                        "additionalProperties": {"type": "string"},
+                        "description": format!("How to generate fields, by key name (anywhere) or dotted path from the root (array indices left out, e.g. 'items.qty'). Without file_path the rules alone define the records. Rules: {}", RULE_KINDS)
+                    },
+                    "count": {
+                        "type": "integer",
+                        "description": format!("Number of variants (default: 5; at most {} returned inline, {} with output_path)", MAX_INLINE, MAX_COUNT),
+                        "minimum": 1
+                    },
+                    "seed": {
+                        "type": "integer",
+                        "description": "Seed for reproducible output (default: random; the seed used is reported)"
+                    },
+                    "date_from": {
+                        "type": "string",
+                        "description": "Earliest generated date, e.g. '2024-01-01' (default: a year before each sample date)"
+                    },
+                    "date_to": {
+                        "type": "string",
+                        "description": "Latest generated date (default: a year after each sample date, or now with date_from)"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "Write the variants here as a JSON array, or one per line for .ndjson/.jsonl"
+                    }
+                },
+                "required": []
+            })
+        }
+    }
+
+    async fn handle_mock(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let file_path = args.get("file_path").and_then(|v| v.as_str());
+        let mut rules = Vec::new();
+        match args.get("rules") {
+            Some(Value::Object(given)) => {
+                for (field, rule) in given {
+                    let parsed = match rule.as_str() {
+                        Some(rule) => Rule::parse(rule),
+                        None => Err(format!("rules are strings such as \"int:1..100\", not {}", rule)),
+                    };
+                    match parsed {
+                        Ok(rule) => rules.push((field.clone(), rule)),
+                        Err(message) => return Ok(ToolResult::error(format!("Invalid rule for '{}': {}", field, message))),
+                    }
+                }
+            }
+            Some(other) => return Ok(ToolResult::error(format!("rules must be an object of field names to rules, not {}", other))),
+            None => {}
+        }
+        if file_path.is_none() && rules.is_empty() {
+            anyhow::bail!("file_path (or rules) is required. Usage example:\n{}", USAGE);
+        }
+        let count = args.get("count").and_then(|v| v.as_u64()).unwrap_or(5) as usize;
+        let output_path = args.get("output_path").and_then(|v| v.as_str());
+        if count == 0 || count > MAX_COUNT {
+            return Ok(ToolResult::error(format!("count must be between 1 and {}", MAX_COUNT)));
+        }
+        if output_path.is_none() && count > MAX_INLINE {
+            return Ok(ToolResult::error(format!(
+                "{} variants are too many to return inline (at most {}); pass output_path to write them to a file", count, MAX_INLINE
+            )));
+        }
+
+        let mut bounds = [None, None];
+        for (bound, name) in bounds.iter_mut().zip(["date_from", "date_to"]) {
+            if let Some(value) = args.get(name) {
+                match parse_timestamp(value) {
+                    Some(at) => *bound = Some(at),
+                    None => return Ok(ToolResult::error(format!("{} must be a date or timestamp, e.g. \"2024-01-01\", not {}", name, value))),
+                }
+            }
+        }
+        let dates = match bounds {
+            [Some(from), Some(to)] if from > to => return Ok(ToolResult::error("date_from must not be after date_to".to_string())),
+            [Some(from), Some(to)] => Some((from, to)),
+            [Some(from), None] => Some((from, Utc::now().max(from))),
+            [None, Some(to)] => Some((to - Duration::days(365), to)),
+            [None, None] => None,
+        };
+
+        let (sample, source) = match file_path {
+            Some(file_path) => (read_document(file_path, DocumentFormat::from_path(file_path))?, format!("variant(s) of '{}'", file_path)),
+            None => (rules_sample(&rules), "record(s) from the rules".to_string()),
+        };
+        let seed = args.get("seed").and_then(|v| v.as_u64()).unwrap_or_else(|| fastrand::u64(..));
+        let mut mocker = Mocker::new(seed, dates, rules);
+        let generated = match output_path {
+            Some(output_path) => {
+                let mut writer = RecordWriter::create(Path::new(output_path), RecordFormat::from_extension(output_path))?;
+                let mut first = None;
+                for _ in 0..count {
+                    let variant = mocker.vary(&sample, None, "");
+                    writer.write(&variant)?;
+                    first.get_or_insert(variant);
+                }
+                writer.finish()?;
+                format!(" (written to '{}'). The first:\n\n{}", output_path, serde_json::to_string_pretty(&first)?)
+            }
+            None => {
+                let variants: Vec<Value> = (0..count).map(|_| mocker.vary(&sample, None, "")).collect();
+                format!(":\n\n{}", serde_json::to_string_pretty(&variants)?)
+            }
+        };
+        let unmatched: Vec<&str> = mocker.rules.iter().zip(&mocker.matched)
+            .filter(|(_, matched)| !**matched)
+            .map(|((field, _), _)| field.as_str())
+            .collect();
+        let warning = if unmatched.is_empty() { String::new() } else { format!(". Rules that matched no field: {}", unmatched.join(", ")) };
+        Ok(ToolResult::success(format!("Generated {} {} with seed {}{}{}", count, source, seed, warning, generated)))
+    }
+}
+
+#[async_trait]
+impl ToolHandler for JsonMock {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_mock_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-mock" => self.handle_mock(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
@@ -1,6 +1,76 @@
+pub mod batch;
+pub mod binary;
+pub mod check;
+pub mod codegen;
+pub mod compare;
+pub mod cli;
+#[cfg(feature = "parquet")]
+pub mod columnar;
+pub mod config;
+pub mod convert;
+pub mod dates;
+pub mod describe;
+pub mod discovery;
+pub mod embedded;
+pub mod encryption;
+pub mod envelope;
+pub mod files;
+pub mod formats;
+pub mod geojson;
+#[cfg(feature = "git")]
+pub mod git;
 pub mod handler;
+pub mod help;
+pub mod http;
+pub mod hints;
+pub mod i18n;
+pub mod integrity;
+pub mod jobs;
+pub mod journal;
+pub mod jsonld;
+pub mod jsonpath;
+pub mod lossy;
+pub mod migrate;
+pub mod mock;
+pub mod numbers;
+pub mod openapi;
 pub mod operations;
+pub mod outputs;
+pub mod patch;
+pub mod paths;
+pub mod planner;
+pub mod plugins;
 pub mod query;
+pub mod relational;
+pub mod resolve;
+pub mod resources;
+pub mod rfc9535;
+pub mod s3;
+pub mod schema;
+pub mod schemadiff;
+pub mod script;
+pub mod secrets;
+pub mod selftest;
+pub mod snapshot;
+pub mod sops;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod sql;
+pub mod stats;
+pub mod storage;
 pub mod streaming;
+pub mod syntax;
+pub mod telemetry;
+pub mod template;
+pub mod timewindow;
+pub mod transaction;
+pub mod units;
+pub mod update;
+pub mod upload;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod winpath;
 
 pub use handler::JsonToolsHandler;
+pub use jsonpath::JsonPathDialect;
+pub use paths::{PathPolicy, SymlinkPolicy};
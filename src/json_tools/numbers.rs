@@ -0,0 +1,256 @@
+use crate::json_tools::encryption;
+use crate::json_tools::formats::{parse_document, DocumentFormat};
+use crate::json_tools::storage;
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::{json, Map, Number, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+/// The largest integer every JSON reader holds exactly: readers that parse numbers as
+/// doubles (JavaScript among them) round anything beyond ±2^53−1.
+pub const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// What json-write does with a number the written document cannot hold as is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumberPolicy {
+    /// Write the number unchanged.
+    Keep,
+    /// Fail the write, naming the number and where it is.
+    Reject,
+    /// Write the number as a string.
+    Stringify,
+    /// Write the nearest number that can be held.
+    Clamp,
+}
+
+impl NumberPolicy {
+    /// The policy in argument `name`, one of `allowed`, or `default` when absent.
+    pub fn from_args(
+        args: &HashMap<String, Value>,
+        name: &str,
+        allowed: &[&str],
+        default: Self,
+    ) -> Result<Self, String> {
+        let Some(value) = args.get(name) else {
+            return Ok(default);
+        };
+        let policy = match value.as_str() {
+            Some(policy) if allowed.contains(&policy) => policy,
+            _ => return Err(format!("Invalid {} {}: use one of {}", name, value, allowed.join(", "))),
+        };
+        Ok(match policy {
+            "keep" => Self::Keep,
+            "reject" => Self::Reject,
+            "stringify" => Self::Stringify,
+            _ => Self::Clamp,
+        })
+    }
+}
+
+/// JSON Schema for json-write's `special_numbers` argument.
+pub fn special_numbers_schema() -> Value {
+    json!({
+        "type": "string",
+        "enum": ["reject", "stringify", "clamp"],
+        "description": "What to do with NaN and Infinity in an existing TOML, MessagePack or CBOR document that merge or append mode reads, since JSON cannot hold them: 'reject' fails the write (default), 'stringify' writes \"NaN\", \"Infinity\" or \"-Infinity\", 'clamp' writes the largest finite number of the same sign, and null for NaN"
+    })
+}
+
+/// JSON Schema for json-write's `large_numbers` argument.
+pub fn large_numbers_schema() -> Value {
+    json!({
+        "type": "string",
+        "enum": ["keep", "reject", "stringify", "clamp"],
+        "description": "What to do with integers beyond ±9007199254740991 (2^53−1), which readers that parse numbers as doubles round: 'keep' writes them exactly (default), 'reject' fails the write, 'stringify' writes them as strings, 'clamp' writes ±9007199254740991"
+    })
+}
+
+/// Apply `policy` to the integers in `value` beyond ±`MAX_SAFE_INTEGER`. Returns how many
+/// were changed, or the error for the first one under `Reject`.
+pub fn limit_integers(value: &mut Value, policy: NumberPolicy, path: &str) -> Result<usize, String> {
+    match value {
+        Value::Number(n) if policy != NumberPolicy::Keep => {
+            let negative = n.as_i64().is_some_and(|i| i < 0);
+            let magnitude = match (n.as_i64(), n.as_u64()) {
+                (Some(i), _) => i.unsigned_abs(),
+                (None, Some(u)) => u,
+                _ => return Ok(0),
+            };
+            if magnitude <= MAX_SAFE_INTEGER {
+                return Ok(0);
+            }
+            *value = match policy {
+                NumberPolicy::Stringify => Value::String(n.to_string()),
+                NumberPolicy::Clamp if negative => json!(-(MAX_SAFE_INTEGER as i64)),
+                NumberPolicy::Clamp => json!(MAX_SAFE_INTEGER),
+                _ => return Err(format!(
+                    "Integer {} at {} is beyond ±{}, which readers that parse numbers as doubles round. Pass large_numbers 'keep', 'stringify' or 'clamp' to write it",
+                    n, path, MAX_SAFE_INTEGER
+                )),
+            };
+            Ok(1)
+        }
+        Value::Array(items) => items.iter_mut().enumerate()
+            .map(|(i, item)| limit_integers(item, policy, &format!("{}[{}]", path, i)))
+            .sum(),
+        Value::Object(obj) => obj.iter_mut()
+            .map(|(k, v)| limit_integers(v, policy, &format!("{}.{}", path, k)))
+            .sum(),
+        _ => Ok(0),
+    }
+}
+
+/// Read a whole document like `formats::read_document`, but handle the NaN and Infinity
+/// values TOML, MessagePack and CBOR allow by `special` instead of reading them as null.
+/// Returns the document and how many such values it held.
+pub fn read_document_with(file_path: &str, format: DocumentFormat, special: NumberPolicy) -> anyhow::Result<(Value, usize)> {
+    let content = encryption::open(file_path, storage::read(file_path)?)?;
+    let decoded = match format {
+        DocumentFormat::Json => return Ok((read_json(file_path, &content)?, 0)),
+        DocumentFormat::Toml => std::str::from_utf8(&content)?.parse::<toml::Table>()
+            .map(|table| Decoded::from(toml::Value::Table(table)))
+            .map_err(anyhow::Error::from),
+        DocumentFormat::MessagePack => rmp_serde::from_slice(&content).map_err(anyhow::Error::from),
+        DocumentFormat::Cbor => ciborium::from_reader(content.as_slice()).map_err(anyhow::Error::from),
+    }
+    .map_err(|e| anyhow::anyhow!("Failed to parse {} in '{}': {}", format.name().to_uppercase(), file_path, e))?;
+    let mut count = 0;
+    let value = decoded.into_json(special, "$", &mut count)?;
+    Ok((value, count))
+}
+
+fn read_json(file_path: &str, content: &[u8]) -> anyhow::Result<Value> {
+    parse_document(content, DocumentFormat::Json)
+        .map_err(|e| anyhow::anyhow!("Failed to parse JSON in '{}': {}", file_path, e))
+}
+
+/// A decoded document that, unlike `serde_json::Value`, keeps non-finite floats.
+enum Decoded {
+    Null,
+    Bool(bool),
+    Number(Number),
+    Float(f64),
+    String(String),
+    Array(Vec<Decoded>),
+    Object(Vec<(String, Decoded)>),
+}
+
+impl Decoded {
+    fn into_json(self, special: NumberPolicy, path: &str, count: &mut usize) -> anyhow::Result<Value> {
+        Ok(match self {
+            Decoded::Null => Value::Null,
+            Decoded::Bool(b) => Value::Bool(b),
+            Decoded::Number(n) => Value::Number(n),
+            Decoded::Float(f) => match Number::from_f64(f) {
+                Some(n) => Value::Number(n),
+                None => {
+                    *count += 1;
+                    let name = if f.is_nan() { "NaN" } else if f > 0.0 { "Infinity" } else { "-Infinity" };
+                    match special {
+                        NumberPolicy::Stringify => json!(name),
+                        NumberPolicy::Clamp if f.is_nan() => Value::Null,
+                        NumberPolicy::Clamp if f > 0.0 => json!(f64::MAX),
+                        NumberPolicy::Clamp => json!(f64::MIN),
+                        _ => anyhow::bail!(
+                            "{} at {} cannot be written as JSON. Pass special_numbers 'stringify' or 'clamp' to write it",
+                            name, path
+                        ),
+                    }
+                }
+            },
+            Decoded::String(s) => Value::String(s),
+            Decoded::Array(items) => Value::Array(items.into_iter().enumerate()
+                .map(|(i, item)| item.into_json(special, &format!("{}[{}]", path, i), count))
+                .collect::<anyhow::Result<_>>()?),
+            Decoded::Object(entries) => Value::Object(entries.into_iter()
+                .map(|(k, v)| {
+                    let v = v.into_json(special, &format!("{}.{}", path, k), count)?;
+                    Ok((k, v))
+                })
+                .collect::<anyhow::Result<Map<String, Value>>>()?),
+        })
+    }
+}
+
+/// TOML datetimes are read as RFC 3339 strings, as by `formats::read_document`.
+impl From<toml::Value> for Decoded {
+    fn from(value: toml::Value) -> Self {
+        match value {
+            toml::Value::String(s) => Decoded::String(s),
+            toml::Value::Integer(i) => Decoded::Number(i.into()),
+            toml::Value::Float(f) => Decoded::Float(f),
+            toml::Value::Boolean(b) => Decoded::Bool(b),
+            toml::Value::Datetime(dt) => Decoded::String(dt.to_string()),
+            toml::Value::Array(items) => Decoded::Array(items.into_iter().map(Decoded::from).collect()),
+            toml::Value::Table(table) => Decoded::Object(table.into_iter().map(|(k, v)| (k, v.into())).collect()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Decoded {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(DecodedVisitor)
+    }
+}
+
+struct DecodedVisitor;
+
+impl<'de> Visitor<'de> for DecodedVisitor {
+    type Value = Decoded;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any value JSON can represent")
+    }
+
+    fn visit_bool<E>(self, b: bool) -> Result<Decoded, E> {
+        Ok(Decoded::Bool(b))
+    }
+
+    fn visit_i64<E>(self, i: i64) -> Result<Decoded, E> {
+        Ok(Decoded::Number(i.into()))
+    }
+
+    fn visit_u64<E>(self, u: u64) -> Result<Decoded, E> {
+        Ok(Decoded::Number(u.into()))
+    }
+
+    fn visit_f64<E>(self, f: f64) -> Result<Decoded, E> {
+        Ok(Decoded::Float(f))
+    }
+
+    fn visit_str<E>(self, s: &str) -> Result<Decoded, E> {
+        Ok(Decoded::String(s.to_string()))
+    }
+
+    fn visit_string<E>(self, s: String) -> Result<Decoded, E> {
+        Ok(Decoded::String(s))
+    }
+
+    fn visit_unit<E>(self) -> Result<Decoded, E> {
+        Ok(Decoded::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<Decoded, E> {
+        Ok(Decoded::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Decoded, D::Error> {
+        Decoded::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Decoded, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Decoded::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Decoded, A::Error> {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry::<String, Decoded>()? {
+            entries.push(entry);
+        }
+        Ok(Decoded::Object(entries))
+    }
+}
@@ -0,0 +1,246 @@
+use crate::json_tools::compare::pointer;
+use crate::json_tools::formats::{inline_document, read_document, DocumentFormat};
+use crate::json_tools::patch::{parse_pointer, pointer_get};
+use crate::json_tools::schema::{validate, MAX_ERRORS};
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// The operations a path item can hold.
+const METHODS: &[&str] = &["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+
+const USAGE: &str = "{\n  \"spec_path\": \"./openapi.json\",\n  \"operation_id\": \"createUser\",\n  \"file_path\": \"./examples/new-user.json\"\n}";
+
+/// Follow `$ref`s to components of the same document, extending `tokens` with the location
+/// of what was found.
+fn follow<'a>(spec: &'a Value, mut value: &'a Value, tokens: &mut Vec<String>) -> Result<&'a Value, String> {
+    for _ in 0..32 {
+        let Some(reference) = value.get("$ref").and_then(|r| r.as_str()) else {
+            return Ok(value);
+        };
+        let target = reference.strip_prefix('#')
+            .and_then(|p| parse_pointer(p).ok())
+            .ok_or_else(|| format!("cannot resolve '{}'; only references within the document are supported", reference))?;
+        value = pointer_get(spec, &target).map_err(|_| format!("'{}' does not exist in the document", reference))?;
+        *tokens = target;
+    }
+    Err("too many nested $refs".to_string())
+}
+
+/// Where an operation lives: its path, method and the operation object.
+struct Operation<'a> {
+    path: String,
+    method: String,
+    tokens: Vec<String>,
+    value: &'a Value,
+}
+
+impl Operation<'_> {
+    fn name(&self) -> String {
+        match self.value.get("operationId").and_then(|v| v.as_str()) {
+            Some(id) => format!("{} ({} {})", id, self.method.to_uppercase(), self.path),
+            None => format!("{} {}", self.method.to_uppercase(), self.path),
+        }
+    }
+}
+
+fn operations(spec: &Value) -> Vec<Operation<'_>> {
+    let mut found = Vec::new();
+    for (path, item) in spec.get("paths").and_then(|p| p.as_object()).into_iter().flatten() {
+        let mut tokens = vec!["paths".to_string(), path.clone()];
+        let Ok(item) = follow(spec, item, &mut tokens) else {
+            continue;
+        };
+        for method in METHODS {
+            if let Some(value) = item.get(*method) {
+                let mut tokens = tokens.clone();
+                tokens.push(method.to_string());
+                found.push(Operation { path: path.clone(), method: method.to_string(), tokens, value });
+            }
+        }
+    }
+    found
+}
+
+/// The media type entry to validate against: the one asked for, else JSON, else the first.
+fn media_entry<'a>(content: &'a serde_json::Map<String, Value>, requested: Option<&str>) -> Result<(&'a String, &'a Value), String> {
+    let available = || content.keys().cloned().collect::<Vec<_>>().join(", ");
+    match requested {
+        Some(media_type) => content.get_key_value(media_type)
+            .ok_or_else(|| format!("no '{}' content; available: {}", media_type, available())),
+        None => content.get_key_value("application/json")
+            .or_else(|| content.iter().find(|(name, _)| name.contains("json")))
+            .or_else(|| content.iter().next())
+            .ok_or_else(|| "no content is described".to_string()),
+    }
+}
+
+pub struct JsonOpenApi;
+
+impl Default for JsonOpenApi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonOpenApi {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn create_validate_tool() -> Tool {
+        Tool {
+            name: "json-openapi-validate".to_string(),
+            description: "Validate a JSON payload against the request or response schema of an operation in an OpenAPI 3.x document on disk, resolving $refs. Returns valid: true/false with each error's path and reason.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "spec_path": {
+                        "type": "string",
+                        "description": "OpenAPI 3.0 or 3.1 document (JSON; TOML, MessagePack and CBOR also work)"
+                    },
+                    "operation_id": {
+                        "type": "string",
+                        "description": "operationId of the operation, or use method and path"
+                    },
+                    "method": {
+                        "type": "string",
+                        "description": "HTTP method of the operation, e.g. 'post' (with path)"
+                    },
+                    "path": {
+                        "type": "string",
+                        "description": "Path template of the operation as written in the spec, e.g. '/users/{id}' (with method)"
+                    },
+                    "direction": {
+                        "type": "string",
+                        "enum": ["request", "response"],
+                        "description": "Validate against the request body or a response (default: request)",
+                        "default": "request"
+                    },
+                    "status": {
+                        "type": "string",
+                        "description": "Response status code, e.g. '201' or '404'; matched exactly, then as '2XX', then 'default' (default: the first 2xx response)"
+                    },
+                    "media_type": {
+                        "type": "string",
+                        "description": "Content type to use (default: application/json, else the first JSON type)"
+                    },
+                    "file_path": {
+                        "type": "string",
+                        "description": "Payload to validate, or use content"
+                    },
+                    "content": {
+                        "description": "Payload to validate inline, as JSON text or a JSON value"
+                    }
+                },
+                "required": ["spec_path"]
+            })
+        }
+    }
+
+    async fn handle_validate(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let spec_path = args.get("spec_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("spec_path is required. Usage example:\n{}", USAGE))?;
+        let payload = match (args.get("file_path").and_then(|v| v.as_str()), args.get("content")) {
+            (Some(file_path), None) => read_document(file_path, DocumentFormat::from_path(file_path))?,
+            (None, Some(content)) => inline_document(content)?,
+            (Some(_), Some(_)) => return Ok(ToolResult::error("Provide either file_path or content, not both".to_string())),
+            (None, None) => anyhow::bail!("file_path (or content) with the payload is required. Usage example:\n{}", USAGE),
+        };
+        let spec = read_document(spec_path, DocumentFormat::from_path(spec_path))?;
+        match spec.get("openapi").and_then(|v| v.as_str()) {
+            Some(version) if version.starts_with("3.") => {}
+            _ => return Ok(ToolResult::error(format!(
+                "'{}' is not an OpenAPI 3.x document (it needs \"openapi\": \"3.x\"; Swagger 2.0 is not supported)", spec_path
+            ))),
+        }
+
+        let all = operations(&spec);
+        let operation = match (args.get("operation_id").and_then(|v| v.as_str()), args.get("method").and_then(|v| v.as_str()), args.get("path").and_then(|v| v.as_str())) {
+            (Some(id), _, _) => all.iter().find(|op| op.value.get("operationId").and_then(|v| v.as_str()) == Some(id)),
+            (None, Some(method), Some(path)) => all.iter().find(|op| op.method.eq_ignore_ascii_case(method) && op.path == path),
+            _ => anyhow::bail!("operation_id (or method and path) is required. Usage example:\n{}", USAGE),
+        };
+        let Some(operation) = operation else {
+            let known: Vec<String> = all.iter().map(Operation::name).collect();
+            return Ok(ToolResult::error(format!(
+                "No such operation in '{}'. Operations: {}", spec_path, known.join(", ")
+            )));
+        };
+
+        let direction = args.get("direction").and_then(|v| v.as_str()).unwrap_or("request");
+        let mut tokens = operation.tokens.clone();
+        let located = match direction {
+            "request" => {
+                tokens.push("requestBody".to_string());
+                operation.value.get("requestBody")
+                    .ok_or_else(|| format!("{} has no request body", operation.name()))
+                    .and_then(|body| follow(&spec, body, &mut tokens))
+            }
+            "response" => {
+                tokens.push("responses".to_string());
+                let responses = operation.value.get("responses").and_then(|r| r.as_object());
+                let status = args.get("status").map(|s| s.as_str().map(str::to_string).unwrap_or_else(|| s.to_string()));
+                let key = responses.and_then(|responses| match &status {
+                    Some(status) => [status.clone(), format!("{}XX", status.get(..1).unwrap_or_default()), "default".to_string()]
+                        .into_iter()
+                        .find(|key| responses.contains_key(key) || responses.contains_key(&key.to_lowercase()))
+                        .map(|key| if responses.contains_key(&key) { key } else { key.to_lowercase() }),
+                    None => responses.keys().find(|key| key.starts_with('2')).or_else(|| responses.get_key_value("default").map(|(k, _)| k)).cloned(),
+                });
+                match (key, responses) {
+                    (Some(key), Some(responses)) => {
+                        tokens.push(key.clone());
+                        follow(&spec, &responses[&key], &mut tokens)
+                    }
+                    _ => Err(format!("{} has no response for status {}", operation.name(), status.as_deref().unwrap_or("2xx"))),
+                }
+            }
+            other => return Ok(ToolResult::error(format!("Invalid direction '{}'. Use 'request' or 'response'", other))),
+        };
+        let content = located.and_then(|found| {
+            let content = found.get("content").and_then(|c| c.as_object())
+                .ok_or_else(|| format!("the {} of {} has no content", direction, operation.name()))?;
+            let (media_type, entry) = media_entry(content, args.get("media_type").and_then(|v| v.as_str()))?;
+            tokens.extend(["content".to_string(), media_type.clone(), "schema".to_string()]);
+            let schema = entry.get("schema").ok_or_else(|| format!("the '{}' {} of {} has no schema", media_type, direction, operation.name()))?;
+            follow(&spec, schema, &mut tokens)
+        });
+        let schema = match content {
+            Ok(schema) => schema,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+
+        let errors = validate(&spec, schema, &payload);
+        let mut report = json!({
+            "valid": errors.is_empty(),
+            "operation": operation.name(),
+            "schema": format!("#{}", pointer(&tokens)),
+            "errors": errors.iter().map(|e| e.to_json()).collect::<Vec<_>>(),
+        });
+        if errors.len() >= MAX_ERRORS {
+            report["truncated"] = json!(true);
+        }
+        let verdict = if errors.is_empty() { "matches".to_string() } else { format!("has {} error(s) against", errors.len()) };
+        Ok(ToolResult::success(format!(
+            "Payload {} the {} schema of {}:\n\n{}", verdict, direction, operation.name(), serde_json::to_string_pretty(&report)?
+        )))
+    }
+}
+
+#[async_trait]
+impl ToolHandler for JsonOpenApi {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_validate_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-openapi-validate" => self.handle_validate(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
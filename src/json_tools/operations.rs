@@ -1,3 +1,12 @@
+use crate::json_tools::integrity::{file_content_hash, hash_matches, sha256_hex};
+use crate::json_tools::journal::Journal;
+use crate::json_tools::secrets::{expand_env_schema, EnvExpansion, SecretValues};
+use crate::json_tools::encryption;
+use crate::json_tools::files::overwrite_schema;
+use crate::json_tools::formats::{line_endings_schema, serialize_with_layout, trailing_newline_schema, DocumentFormat, TextLayout};
+use crate::json_tools::geojson;
+use crate::json_tools::numbers::{large_numbers_schema, limit_integers, read_document_with, special_numbers_schema, NumberPolicy};
+use crate::json_tools::storage;
 use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
 use crate::mcp::server::ToolHandler;
 use serde_json::{json, Value};
@@ -5,11 +14,36 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
-pub struct JsonOperations;
+/// Default limit for `return_content`, in bytes of pretty-printed JSON.
+const DEFAULT_MAX_RETURN_BYTES: usize = 64 * 1024;
+
+pub struct JsonOperations {
+    journal: Option<Journal>,
+}
+
+impl Default for JsonOperations {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl JsonOperations {
     pub fn new() -> Self {
-        Self
+        Self { journal: None }
+    }
+
+    /// Route json-write and json-format through `journal`, so a crash mid-write leaves
+    /// either the old or the new file, never a truncated one.
+    pub fn with_journal(journal: Journal) -> Self {
+        Self { journal: Some(journal) }
+    }
+
+    fn write_file(&self, path: &str, content: &[u8]) -> anyhow::Result<()> {
+        match &self.journal {
+            // A file that may only be created needs no journal: a crash leaves no file at all
+            Some(journal) if storage::is_local(path) && !storage::is_create_only(Path::new(path)) => journal.write(Path::new(path), content),
+            _ => storage::write(path, content),
+        }
     }
 
     fn create_write_tool() -> Tool {
@@ -28,10 +62,11 @@ impl JsonOperations {
                     },
                     "mode": {
                         "type": "string",
-                        "enum": ["replace", "merge", "append"],
+                        "enum": ["replace", "merge", "append", "create"],
                         "default": "replace",
-                        "description": "Write mode: 'replace' overwrites file, 'merge' merges with existing JSON (objects only), 'append' appends to arrays"
+                        "description": "Write mode: 'replace' overwrites file, 'merge' merges with existing JSON (objects only), 'append' appends to arrays, 'create' writes a new file and fails if the file already exists (the same as overwrite: false)"
                     },
+                    "overwrite": overwrite_schema("the file"),
                     "create_dirs": {
                         "type": "boolean",
                         "default": true,
@@ -41,7 +76,30 @@ impl JsonOperations {
                         "type": "boolean",
                         "default": true,
                         "description": "Format JSON with indentation"
-                    }
+                    },
+                    "format": {
+                        "type": "string",
+                        "enum": ["json", "toml", "msgpack", "cbor"],
+                        "description": "File format to write (default: from the file extension: .toml, .msgpack/.mpk, .cbor, otherwise JSON)"
+                    },
+                    "expand_env": expand_env_schema(),
+                    "if_hash": {
+                        "type": "string",
+                        "description": "Only write if the file's current content_hash (from json-read or a previous write) equals this value; otherwise fail with a conflict"
+                    },
+                    "return_content": {
+                        "type": "boolean",
+                        "description": "Include the final document in the result, saving a follow-up read (default: false)"
+                    },
+                    "max_return_bytes": {
+                        "type": "integer",
+                        "description": "Largest document returned by return_content, in bytes of pretty-printed JSON (default: 65536)",
+                        "minimum": 1
+                    },
+                    "special_numbers": special_numbers_schema(),
+                    "large_numbers": large_numbers_schema(),
+                    "line_endings": line_endings_schema(),
+                    "trailing_newline": trailing_newline_schema()
                 },
                 "required": ["file_path", "data"]
             }),
@@ -57,11 +115,50 @@ impl JsonOperations {
                 "properties": {
                     "file_path": {
                         "type": "string",
-                        "description": "Path to the JSON file to validate"
+                        "description": "Path to the JSON file to validate (use this or 'content')"
+                    },
+                    "content": {
+                        "type": "string",
+                        "description": "Inline JSON text to validate instead of reading file_path"
                     },
                     "schema": {
                         "description": "Optional JSON schema to validate against"
+                    },
+                    "mode": {
+                        "type": "string",
+                        "enum": ["json", "geojson"],
+                        "description": "geojson: also check GeoJSON (RFC 7946) structure - object types, geometry coordinates, ring closure and coordinate ranges (default: json)",
+                        "default": "json"
                     }
+                }
+            }),
+        }
+    }
+
+    fn create_format_tool() -> Tool {
+        Tool {
+            name: "json-format".to_string(),
+            description: "Pretty-print or minify a JSON file without changing its data, in place or to a new file".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "Path to the JSON file to format"
+                    },
+                    "indent": {
+                        "description": "Spaces per indentation level (0-8), or \"tab\" (default: 2)"
+                    },
+                    "minify": {
+                        "type": "boolean",
+                        "description": "Write the document on a single line without whitespace (default: false)"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "File to write the formatted JSON to (default: rewrite file_path in place)"
+                    },
+                    "line_endings": line_endings_schema(),
+                    "trailing_newline": trailing_newline_schema()
                 },
                 "required": ["file_path"]
             }),
@@ -80,6 +177,18 @@ impl JsonOperations {
                 "data is required. Usage example:\n{{\n  \"file_path\": \"./output.json\",\n  \"data\": {{\"key\": \"value\"}}\n}}"
             ))?;
 
+        // Expand allowlisted ${VAR} placeholders before the data is merged or written; secrets
+        // are refused rather than written to disk
+        let mut data = data.clone();
+        let expanded = match EnvExpansion::from_args(args)? {
+            Some(expansion) => match expansion.expand(&mut data, SecretValues::Refuse) {
+                Ok(count) => count,
+                Err(e) => return Ok(ToolResult::error(format!("Environment expansion failed: {}", e))),
+            },
+            None => 0,
+        };
+        let data = &data;
+
         let mode = args.get("mode")
             .and_then(|v| v.as_str())
             .unwrap_or("replace");
@@ -92,23 +201,60 @@ impl JsonOperations {
             .and_then(|v| v.as_bool())
             .unwrap_or(true);
 
+        let format = DocumentFormat::resolve(file_path, args.get("format").and_then(|v| v.as_str()))?;
+        let policies = NumberPolicy::from_args(args, "special_numbers", &["reject", "stringify", "clamp"], NumberPolicy::Reject)
+            .and_then(|special| {
+                let large = NumberPolicy::from_args(args, "large_numbers", &["keep", "reject", "stringify", "clamp"], NumberPolicy::Keep)?;
+                Ok((special, large))
+            });
+        let (special_numbers, large_numbers) = match policies {
+            Ok(policies) => policies,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+
+        // Abort if the file changed since the caller last saw it
+        let exists = storage::exists(file_path)?;
+        if let Some(expected) = args.get("if_hash").and_then(|v| v.as_str()) {
+            if !exists {
+                return Ok(ToolResult::error(format!(
+                    "Conflict: '{}' no longer exists, but if_hash '{}' was given. Re-read the file before writing",
+                    file_path, expected
+                )));
+            }
+            let current = file_content_hash(file_path)?;
+            if !hash_matches(expected, &current) {
+                return Ok(ToolResult::error(format!(
+                    "Conflict: '{}' was modified since it was read (expected {}, found {}). Re-read the file and apply the change again",
+                    file_path, expected, current
+                )));
+            }
+        }
+
+        let create_only = mode == "create" || args.get("overwrite").and_then(|v| v.as_bool()) == Some(false);
+        if mode == "create" && exists {
+            return Ok(ToolResult::error(format!(
+                "'{}' already exists, and mode 'create' never overwrites. Use mode 'replace' to replace it", file_path
+            )));
+        }
+        if create_only && exists {
+            return Ok(ToolResult::error(storage::already_exists(file_path).to_string()));
+        }
+
         // Create parent directories if needed
-        if create_dirs {
+        if create_dirs && storage::is_local(file_path) {
             if let Some(parent) = Path::new(file_path).parent() {
                 fs::create_dir_all(parent)
                     .map_err(|e| anyhow::anyhow!("Failed to create directories: {}", e))?;
             }
         }
 
-        let final_data = match mode {
-            "replace" => data.clone(),
+        let mut special_count = 0;
+        let mut final_data = match mode {
+            "replace" | "create" => data.clone(),
             "merge" => {
-                if Path::new(file_path).exists() {
-                    let existing_content = fs::read_to_string(file_path)
-                        .map_err(|e| anyhow::anyhow!("Failed to read existing file: {}", e))?;
-                    
-                    let mut existing_json: Value = serde_json::from_str(&existing_content)
-                        .map_err(|e| anyhow::anyhow!("Failed to parse existing JSON: {}", e))?;
+                if exists {
+                    let (mut existing_json, count) = read_document_with(file_path, format, special_numbers)?;
+                    special_count = count;
 
                     if let (Some(existing_obj), Some(new_obj)) = (existing_json.as_object_mut(), data.as_object()) {
                         for (key, value) in new_obj {
@@ -123,12 +269,9 @@ impl JsonOperations {
                 }
             },
             "append" => {
-                if Path::new(file_path).exists() {
-                    let existing_content = fs::read_to_string(file_path)
-                        .map_err(|e| anyhow::anyhow!("Failed to read existing file: {}", e))?;
-                    
-                    let mut existing_json: Value = serde_json::from_str(&existing_content)
-                        .map_err(|e| anyhow::anyhow!("Failed to parse existing JSON: {}", e))?;
+                if exists {
+                    let (mut existing_json, count) = read_document_with(file_path, format, special_numbers)?;
+                    special_count = count;
 
                     if let Some(existing_array) = existing_json.as_array_mut() {
                         if let Some(new_array) = data.as_array() {
@@ -151,39 +294,177 @@ impl JsonOperations {
             _ => return Ok(ToolResult::error(format!("Unknown write mode: {}", mode))),
         };
 
-        // Write the file
-        let content = if pretty {
-            serde_json::to_string_pretty(&final_data)?
-        } else {
-            serde_json::to_string(&final_data)?
+        let large_count = match limit_integers(&mut final_data, large_numbers, "$") {
+            Ok(count) => count,
+            Err(message) => return Ok(ToolResult::error(message)),
         };
 
-        fs::write(file_path, content)
-            .map_err(|e| anyhow::anyhow!("Failed to write file '{}': {}", file_path, e))?;
+        // Write the file, keeping the line endings of the file it replaces unless told otherwise
+        let layout = match TextLayout::with_overrides(TextLayout::of_file(file_path, format)?, args) {
+            Ok(layout) => layout,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+        let content = match serialize_with_layout(file_path, &final_data, format, pretty, layout) {
+            Ok(content) => content,
+            Err(e) => return Ok(ToolResult::error(format!("Cannot write '{}' as {}: {}", file_path, format.name(), e))),
+        };
 
-        Ok(ToolResult::success(format!(
-            "Successfully wrote JSON to '{}' using {} mode",
-            file_path, mode
-        )))
+        if create_only {
+            storage::write_new(file_path, &content)?;
+        } else {
+            self.write_file(file_path, &content)?;
+        }
+
+        let mut notes = if expanded > 0 {
+            format!(" (expanded {} environment placeholder(s))", expanded)
+        } else {
+            String::new()
+        };
+        if special_count > 0 {
+            notes.push_str(&format!(" (wrote {} NaN or Infinity value(s) by special_numbers)", special_count));
+        }
+        if large_count > 0 {
+            notes.push_str(&format!(" (changed {} large integer(s) by large_numbers)", large_count));
+        }
+        let mut message = format!(
+            "Successfully wrote JSON to '{}' using {} mode{} (content_hash: sha256:{})",
+            file_path, mode, notes, sha256_hex(&content)
+        );
+        if args.get("return_content").and_then(|v| v.as_bool()).unwrap_or(false) {
+            let limit = args.get("max_return_bytes")
+                .and_then(|v| v.as_u64())
+                .map_or(DEFAULT_MAX_RETURN_BYTES, |v| v as usize);
+            let document = serde_json::to_string_pretty(&final_data)?;
+            if document.len() <= limit {
+                message.push_str(&format!("\n\nFinal document:\n{}", document));
+            } else {
+                message.push_str(&format!(
+                    "\n\nFinal document not returned: {} bytes exceeds max_return_bytes ({}). Use json-read with array_path or json-query to inspect it",
+                    document.len(), limit
+                ));
+            }
+        }
+        Ok(ToolResult::success(message))
     }
 
-    async fn handle_validate(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+    async fn handle_format(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
         let file_path = args.get("file_path")
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow::anyhow!(
-                "file_path is required. Usage example:\n{{\n  \"file_path\": \"./data.json\"\n}}"
+                "file_path is required. Usage example:\n{{\n  \"file_path\": \"./data.json\",\n  \"indent\": 2\n}}"
             ))?;
+        let output_path = args.get("output_path").and_then(|v| v.as_str()).unwrap_or(file_path);
+        let minify = args.get("minify").and_then(|v| v.as_bool()).unwrap_or(false);
+        let indent = match args.get("indent") {
+            None => "  ".to_string(),
+            Some(Value::String(s)) if s == "tab" || s == "\t" => "\t".to_string(),
+            Some(value) => match value.as_u64() {
+                Some(n) if n <= 8 => " ".repeat(n as usize),
+                _ => return Ok(ToolResult::error(format!(
+                    "Invalid indent {}: use a number of spaces from 0 to 8, or \"tab\"", value
+                ))),
+            },
+        };
 
-        // Check if file exists
-        if !Path::new(file_path).exists() {
-            return Ok(ToolResult::error(format!("File '{}' does not exist", file_path)));
+        let original = storage::read(file_path)?;
+        let document: Value = match serde_json::from_slice(&original) {
+            Ok(document) => document,
+            Err(e) => return Ok(ToolResult::error(format!("Cannot format '{}': invalid JSON: {}", file_path, e))),
+        };
+
+        let formatted = if minify {
+            serde_json::to_vec(&document)?
+        } else {
+            let mut bytes = Vec::with_capacity(original.len());
+            let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+            let mut serializer = serde_json::Serializer::with_formatter(&mut bytes, formatter);
+            serde::Serialize::serialize(&document, &mut serializer)?;
+            bytes.push(b'\n');
+            bytes
+        };
+        let formatted = match TextLayout::with_overrides(Some(TextLayout::detect(&original)), args) {
+            Ok(Some(layout)) => layout.apply(&formatted),
+            Ok(None) => formatted,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+
+        let style = if minify {
+            "minified".to_string()
+        } else if indent == "\t" {
+            "indented with tabs".to_string()
+        } else {
+            format!("indented with {} space(s)", indent.len())
+        };
+        if output_path == file_path && formatted == original {
+            return Ok(ToolResult::success(format!(
+                "'{}' is already {} ({} bytes); nothing to do", file_path, style, original.len()
+            )));
+        }
+
+        if let (true, Some(parent)) = (storage::is_local(output_path), Path::new(output_path).parent()) {
+            fs::create_dir_all(parent)
+                .map_err(|e| anyhow::anyhow!("Failed to create directories: {}", e))?;
         }
+        self.write_file(output_path, &formatted)?;
 
-        // Read and parse the file
-        let content = fs::read_to_string(file_path)
-            .map_err(|e| anyhow::anyhow!("Failed to read file '{}': {}", file_path, e))?;
+        Ok(ToolResult::success(format!(
+            "Formatted '{}' ({}) into '{}': {} bytes -> {} bytes (content_hash: {})",
+            file_path, style, output_path, original.len(), formatted.len(), file_content_hash(output_path)?
+        )))
+    }
+
+    fn geojson_result(source: &str, document: &Value) -> ToolResult {
+        let report = geojson::check(document);
+        let warnings: String = report.warnings.iter().map(|w| format!("\n- Warning: {}", w)).collect();
+        if !report.errors.is_empty() {
+            let errors: String = report.errors.iter().map(|e| format!("\n- {}", e)).collect();
+            return ToolResult::error(format!(
+                "GeoJSON validation failed for {}: {} error(s){}{}", source, report.errors.len(), errors, warnings
+            ));
+        }
+        let geometries: Vec<String> = report.geometries.iter().map(|(kind, count)| format!("{} {}", kind, count)).collect();
+        let bbox = report.bbox.map_or("none (no positions)".to_string(), |b| format!("[{}, {}, {}, {}]", b[0], b[1], b[2], b[3]));
+        ToolResult::success(format!(
+            "GeoJSON {} is valid:\n- Type: {}\n- Features: {}\n- Geometries: {}\n- Bounding box: {}{}",
+            source,
+            document["type"].as_str().unwrap_or_default(),
+            report.features,
+            if geometries.is_empty() { "none".to_string() } else { geometries.join(", ") },
+            bbox,
+            warnings
+        ))
+    }
+
+    async fn handle_validate(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let (source, content) = match (args.get("file_path").and_then(|v| v.as_str()), args.get("content")) {
+            (Some(file_path), None) => {
+                // Check if file exists
+                if !storage::exists(file_path)? {
+                    return Ok(ToolResult::error(format!("File '{}' does not exist", file_path)));
+                }
+
+                // Read and parse the file
+                let content = storage::read(file_path)?;
+                let content = String::from_utf8(encryption::open(file_path, content)?)
+                    .map_err(|e| anyhow::anyhow!("Failed to read file '{}': {}", file_path, e))?;
+                (format!("'{}'", file_path), content)
+            }
+            (None, Some(Value::String(content))) => ("inline content".to_string(), content.clone()),
+            (None, Some(other)) => ("inline content".to_string(), serde_json::to_string(other)?),
+            (Some(_), Some(_)) => return Ok(ToolResult::error("Provide either file_path or content, not both".to_string())),
+            (None, None) => anyhow::bail!(
+                "file_path is required. Usage example:\n{{\n  \"file_path\": \"./data.json\"\n}}\nTo validate JSON text you already have, pass it as \"content\" instead"
+            ),
+        };
+
+        let geojson_mode = match args.get("mode").and_then(|v| v.as_str()).unwrap_or("json") {
+            "json" => false,
+            "geojson" => true,
+            other => return Ok(ToolResult::error(format!("Invalid mode '{}'. Use 'json' or 'geojson'", other))),
+        };
 
         match serde_json::from_str::<Value>(&content) {
+            Ok(json_value) if geojson_mode => Ok(Self::geojson_result(&source, &json_value)),
             Ok(json_value) => {
                 let size = content.len();
                 let type_name = match &json_value {
@@ -196,8 +477,8 @@ impl JsonOperations {
                 };
 
                 Ok(ToolResult::success(format!(
-                    "JSON file '{}' is valid:\n- Type: {}\n- Size: {} bytes\n- Structure: {}",
-                    file_path,
+                    "JSON {} is valid:\n- Type: {}\n- Size: {} bytes\n- Structure: {}",
+                    if args.contains_key("content") { source } else { format!("file {}", source) },
                     type_name,
                     size,
                     if json_value.is_object() {
@@ -210,8 +491,8 @@ impl JsonOperations {
                 )))
             },
             Err(e) => Ok(ToolResult::error(format!(
-                "JSON validation failed for '{}': {}",
-                file_path, e
+                "JSON validation failed for {}: {}",
+                source, e
             ))),
         }
     }
@@ -223,6 +504,7 @@ impl ToolHandler for JsonOperations {
         Ok(vec![
             Self::create_write_tool(),
             Self::create_validate_tool(),
+            Self::create_format_tool(),
         ])
     }
 
@@ -230,6 +512,7 @@ impl ToolHandler for JsonOperations {
         match tool_call.name.as_str() {
             "json-write" => self.handle_write(&tool_call.arguments).await,
             "json-validate" => self.handle_validate(&tool_call.arguments).await,
+            "json-format" => self.handle_format(&tool_call.arguments).await,
             _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
         }
     }
@@ -0,0 +1,90 @@
+use crate::mcp::protocol::ToolResult;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// A server directory for tool results too large to return inline. A call with
+/// `temp_output` gets its result written to an auto-named file there instead, and files
+/// older than the TTL are deleted by `sweep`, so agents do not litter project directories.
+#[derive(Debug, Clone)]
+pub struct TempOutputs {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl Default for TempOutputs {
+    fn default() -> Self {
+        Self { dir: std::env::temp_dir().join("json-mcp-server-output"), ttl: Duration::from_secs(60 * 60) }
+    }
+}
+
+impl TempOutputs {
+    /// Keep temp outputs in `dir`, created if missing, for `ttl` after they are written.
+    pub fn new(dir: PathBuf, ttl: Duration) -> anyhow::Result<Self> {
+        fs::create_dir_all(&dir)
+            .map_err(|e| anyhow::anyhow!("Failed to create temp output directory '{}': {}", dir.display(), e))?;
+        Ok(Self { dir, ttl })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// JSON Schema for the `temp_output` argument accepted by every tool.
+    pub fn schema() -> Value {
+        json!({
+            "type": "boolean",
+            "description": "Write the result to a new file in the server's temp output directory and return its path instead of the result (default: false). The file is deleted after the server's --temp-ttl"
+        })
+    }
+
+    /// Replace the payload of `tool`'s successful `result` (the part after the summary) by
+    /// the path of a new file holding it.
+    pub fn divert(&self, tool: &str, result: ToolResult) -> anyhow::Result<ToolResult> {
+        let text: Vec<&str> = result.content.iter().map(|c| c.text.as_str()).collect();
+        let text = text.join("\n");
+        let (summary, payload) = text.split_once("\n\n").unwrap_or(("", text.as_str()));
+        let extension = if serde_json::from_str::<Value>(payload).is_ok() { "json" } else { "txt" };
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| anyhow::anyhow!("Failed to create temp output directory '{}': {}", self.dir.display(), e))?;
+        // Plugin and aliased tool names are not trusted to be file names
+        let tool_name: String = tool.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        let path = self.dir.join(format!("{}-{}.{}", tool_name, uuid::Uuid::new_v4().simple(), extension));
+        fs::write(&path, payload)
+            .map_err(|e| anyhow::anyhow!("Failed to write file '{}': {}", path.display(), e))?;
+
+        let summary = match summary.trim_end_matches(':') {
+            "" => format!("Result of {}", tool),
+            summary => summary.to_string(),
+        };
+        Ok(ToolResult::success(format!(
+            "{}; wrote the result ({} bytes) to '{}', which is deleted after {} minute(s)",
+            summary, payload.len(), path.display(), self.ttl.as_secs().div_ceil(60)
+        )))
+    }
+
+    /// Delete the files in the directory older than the TTL. Returns how many were deleted.
+    pub fn sweep(&self) -> anyhow::Result<usize> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => anyhow::bail!("Failed to list temp output directory '{}': {}", self.dir.display(), e),
+        };
+        let now = SystemTime::now();
+        let mut deleted = 0;
+        for entry in entries {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            let age = metadata.modified().ok().and_then(|modified| now.duration_since(modified).ok());
+            if metadata.is_file() && age.is_some_and(|age| age >= self.ttl) {
+                fs::remove_file(entry.path())
+                    .map_err(|e| anyhow::anyhow!("Failed to delete '{}': {}", entry.path().display(), e))?;
+                deleted += 1;
+            }
+        }
+        Ok(deleted)
+    }
+}
@@ -0,0 +1,155 @@
+use serde_json::{Map, Value};
+
+/// Split an RFC 6901 JSON Pointer into unescaped reference tokens.
+pub fn parse_pointer(pointer: &str) -> anyhow::Result<Vec<String>> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let rest = pointer.strip_prefix('/')
+        .ok_or_else(|| anyhow::anyhow!("JSON Pointer '{}' must be empty or start with '/'", pointer))?;
+    Ok(rest.split('/').map(|token| token.replace("~1", "/").replace("~0", "~")).collect())
+}
+
+fn array_index(token: &str, len: usize, allow_end: bool) -> anyhow::Result<usize> {
+    if token == "-" && allow_end {
+        return Ok(len);
+    }
+    let valid = !token.is_empty() && token.chars().all(|c| c.is_ascii_digit()) && (token == "0" || !token.starts_with('0'));
+    let index: usize = if valid { token.parse()? } else { anyhow::bail!("'{}' is not a valid array index", token) };
+    let limit = if allow_end { len } else { len.saturating_sub(1) };
+    if index > limit || (!allow_end && len == 0) {
+        anyhow::bail!("array index {} is out of bounds (length {})", index, len);
+    }
+    Ok(index)
+}
+
+/// Resolve a pointer to a value.
+pub fn pointer_get<'a>(doc: &'a Value, tokens: &[String]) -> anyhow::Result<&'a Value> {
+    tokens.iter().try_fold(doc, |current, token| match current {
+        Value::Object(obj) => obj.get(token)
+            .ok_or_else(|| anyhow::anyhow!("key '{}' does not exist", token)),
+        Value::Array(items) => Ok(&items[array_index(token, items.len(), false)?]),
+        _ => anyhow::bail!("cannot descend into a scalar with '{}'", token),
+    })
+}
+
+/// Resolve a pointer to a mutable value.
+pub fn pointer_get_mut<'a>(doc: &'a mut Value, tokens: &[String]) -> anyhow::Result<&'a mut Value> {
+    tokens.iter().try_fold(doc, |current, token| match current {
+        Value::Object(obj) => obj.get_mut(token)
+            .ok_or_else(|| anyhow::anyhow!("key '{}' does not exist", token)),
+        Value::Array(items) => {
+            let index = array_index(token, items.len(), false)?;
+            Ok(&mut items[index])
+        }
+        _ => anyhow::bail!("cannot descend into a scalar with '{}'", token),
+    })
+}
+
+fn add(doc: &mut Value, tokens: &[String], value: Value) -> anyhow::Result<()> {
+    let Some((last, parent)) = tokens.split_last() else {
+        *doc = value;
+        return Ok(());
+    };
+    match pointer_get_mut(doc, parent)? {
+        Value::Object(obj) => {
+            obj.insert(last.clone(), value);
+        }
+        Value::Array(items) => {
+            let index = array_index(last, items.len(), true)?;
+            items.insert(index, value);
+        }
+        _ => anyhow::bail!("cannot add '{}' to a scalar", last),
+    }
+    Ok(())
+}
+
+fn remove(doc: &mut Value, tokens: &[String]) -> anyhow::Result<Value> {
+    let Some((last, parent)) = tokens.split_last() else {
+        anyhow::bail!("cannot remove the document root");
+    };
+    match pointer_get_mut(doc, parent)? {
+        Value::Object(obj) => obj.shift_remove(last)
+            .ok_or_else(|| anyhow::anyhow!("key '{}' does not exist", last)),
+        Value::Array(items) => {
+            let index = array_index(last, items.len(), false)?;
+            Ok(items.remove(index))
+        }
+        _ => anyhow::bail!("cannot remove '{}' from a scalar", last),
+    }
+}
+
+/// Apply an RFC 6902 JSON Patch. The patch is all-or-nothing: on error `doc` is
+/// left unchanged and the message names the failing operation.
+pub fn apply_json_patch(doc: &mut Value, operations: &[Value]) -> anyhow::Result<()> {
+    let mut patched = doc.clone();
+    for (i, operation) in operations.iter().enumerate() {
+        apply_operation(&mut patched, operation)
+            .map_err(|e| anyhow::anyhow!("patch operation {} ({}) failed: {}", i, operation, e))?;
+    }
+    *doc = patched;
+    Ok(())
+}
+
+fn apply_operation(doc: &mut Value, operation: &Value) -> anyhow::Result<()> {
+    let op = operation.get("op").and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("missing 'op'"))?;
+    let path = parse_pointer(operation.get("path").and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("missing 'path'"))?)?;
+    let value = || operation.get("value").cloned().ok_or_else(|| anyhow::anyhow!("'{}' requires a 'value'", op));
+    let from = || -> anyhow::Result<Vec<String>> {
+        parse_pointer(operation.get("from").and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("'{}' requires a 'from'", op))?)
+    };
+
+    match op {
+        "add" => add(doc, &path, value()?),
+        "remove" => remove(doc, &path).map(|_| ()),
+        "replace" => {
+            *pointer_get_mut(doc, &path)? = value()?;
+            Ok(())
+        }
+        "move" => {
+            let from = from()?;
+            if path.len() > from.len() && path.starts_with(&from) {
+                anyhow::bail!("cannot move a value into one of its own children");
+            }
+            let moved = remove(doc, &from)?;
+            add(doc, &path, moved)
+        }
+        "copy" => {
+            let copied = pointer_get(doc, &from()?)?.clone();
+            add(doc, &path, copied)
+        }
+        "test" => {
+            let expected = value()?;
+            let actual = pointer_get(doc, &path)?;
+            if *actual != expected {
+                anyhow::bail!("test failed: expected {}, found {}", expected, actual);
+            }
+            Ok(())
+        }
+        other => anyhow::bail!("unknown op '{}'. Use add, remove, replace, move, copy or test", other),
+    }
+}
+
+/// Apply an RFC 7386 JSON Merge Patch: objects merge recursively, `null` removes a key
+/// and anything else replaces the target.
+pub fn apply_merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(target) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                target.shift_remove(key);
+            } else {
+                apply_merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
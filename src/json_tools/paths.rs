@@ -0,0 +1,437 @@
+use crate::json_tools::storage;
+use crate::json_tools::winpath::{strip_long_path_prefix, strip_slash_before_drive, WindowsPath};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+
+/// Arguments naming files a tool reads; these get suggestions when they do not exist.
+const INPUT_PATH_ARGUMENTS: &[&str] = &["file_path", "data_path", "template_path", "left_file", "right_file", "directory", "binary_path", "migration_path", "base_file", "ours_file", "theirs_file", "old_file", "new_file", "spec_path", "context_path", "schema_path"];
+const DATA_EXTENSIONS: &[&str] = &["json", "ndjson", "jsonl", "jsonld", "geojson", "toml", "csv", "xml"];
+
+/// Tool arguments that hold a single path.
+const PATH_ARGUMENTS: &[&str] = &[
+    "file_path", "output_path", "data_path", "template_path", "left_file", "right_file",
+    "directory", "output_dir", "snapshot_dir", "binary_path", "migration_path", "base_file", "ours_file",
+    "theirs_file", "conflicts_path", "old_file", "new_file", "spec_path", "context_path", "schema_path",
+    "database_path",
+];
+
+/// Symlinks followed while resolving one path before giving up, as the OS does for loops.
+const MAX_SYMLINK_HOPS: usize = 40;
+
+/// How path arguments that pass through symlinks are treated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SymlinkPolicy {
+    /// Follow symlinks wherever they point; a link inside an allowed root may leave it.
+    Follow,
+    /// Reject paths that pass through a symlink inside the allowed roots (or the base
+    /// directory, or anywhere when neither is configured).
+    Refuse,
+    /// Follow symlinks, but only to targets within the allowed roots.
+    #[default]
+    WithinRoots,
+}
+
+impl SymlinkPolicy {
+    pub const NAMES: [&'static str; 3] = ["follow", "refuse", "within-roots"];
+
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "follow" => Ok(Self::Follow),
+            "refuse" | "deny" => Ok(Self::Refuse),
+            "within-roots" | "within_roots" | "default" => Ok(Self::WithinRoots),
+            _ => Err(format!("Unknown symlink policy '{}'. Use one of: {}", name, Self::NAMES.join(", "))),
+        }
+    }
+}
+
+/// How relative paths in tool arguments are resolved, and which directories tools may touch.
+#[derive(Debug, Clone, Default)]
+pub struct PathPolicy {
+    default_base_dir: Option<PathBuf>,
+    allowed_roots: Vec<PathBuf>,
+    symlinks: SymlinkPolicy,
+}
+
+impl PathPolicy {
+    /// Build a policy from the `--default-base-dir` and `--allowed-root` options. Both
+    /// must name existing directories, and the base directory must lie within the roots.
+    pub fn new(default_base_dir: Option<PathBuf>, allowed_roots: Vec<PathBuf>) -> anyhow::Result<Self> {
+        let allowed_roots = allowed_roots.iter()
+            .map(|root| root.canonicalize()
+                .map_err(|e| anyhow::anyhow!("Allowed root '{}' is not accessible: {}", root.display(), e)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let mut policy = Self { default_base_dir: None, allowed_roots, symlinks: SymlinkPolicy::default() };
+        if let Some(dir) = default_base_dir {
+            policy.default_base_dir = Some(policy.base_dir(&dir.to_string_lossy())?);
+        }
+        Ok(policy)
+    }
+
+    /// Treat symlinks in path arguments according to `symlinks` (see `--symlink-policy`).
+    pub fn with_symlink_policy(mut self, symlinks: SymlinkPolicy) -> Self {
+        self.symlinks = symlinks;
+        self
+    }
+
+    /// Directories tools may work in: the allowed roots, else the default base directory.
+    pub fn roots(&self) -> Vec<PathBuf> {
+        match (&self.default_base_dir, self.allowed_roots.is_empty()) {
+            (_, false) => self.allowed_roots.clone(),
+            (Some(base), true) => vec![base.clone()],
+            (None, true) => Vec::new(),
+        }
+    }
+
+    /// JSON Schema for the `base_dir` argument accepted by every tool.
+    pub fn base_dir_schema() -> Value {
+        json!({
+            "type": "string",
+            "description": "Directory that relative paths in this call are resolved against (default: the server's --default-base-dir, or its working directory)"
+        })
+    }
+
+    /// Resolve the path arguments of a tool call in place: relative paths are joined to
+    /// `base_dir` (or the default base directory), and every path is checked against the
+    /// allowed roots. The `base_dir` argument itself is consumed.
+    pub fn resolve_arguments(&self, args: &mut HashMap<String, Value>) -> anyhow::Result<()> {
+        let base = match args.remove("base_dir") {
+            Some(Value::String(dir)) => Some(self.base_dir(&dir)?),
+            Some(other) => anyhow::bail!("base_dir must be a string, got: {}", other),
+            None => self.default_base_dir.clone(),
+        };
+
+        for (key, value) in args.iter_mut() {
+            match (key.as_str(), value) {
+                (key, Value::String(path)) if PATH_ARGUMENTS.contains(&key) => {
+                    *path = self.resolve(base.as_deref(), path)?;
+                }
+                ("file_paths", Value::Array(paths)) => {
+                    for path in paths.iter_mut() {
+                        if let Value::String(path) = path {
+                            *path = self.resolve(base.as_deref(), path)?;
+                        }
+                    }
+                }
+                // json-transaction operations each name a file
+                ("operations", Value::Array(operations)) => {
+                    for operation in operations.iter_mut() {
+                        if let Some(Value::String(path)) = operation.get_mut("file_path") {
+                            *path = self.resolve(base.as_deref(), path)?;
+                        }
+                    }
+                }
+                // json-batch steps are resolved when they run; pass the base directory on
+                ("steps", Value::Array(steps)) => {
+                    if let Some(base) = &base {
+                        for step in steps.iter_mut() {
+                            if let Some(Value::Object(arguments)) = step.get_mut("arguments") {
+                                arguments.entry("base_dir").or_insert_with(|| json!(base.to_string_lossy()));
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn base_dir(&self, dir: &str) -> anyhow::Result<PathBuf> {
+        let path = expand_path(dir)?;
+        if !path.is_dir() {
+            anyhow::bail!("base_dir '{}' is not an existing directory", dir);
+        }
+        let canonical = path.canonicalize()?;
+        self.check_allowed(&canonical, dir)?;
+        Ok(canonical)
+    }
+
+    /// Expand, absolutize and resolve `path` to its real location, so tools (and their
+    /// responses) always see where the file actually is. Symlinks are handled according to
+    /// the symlink policy.
+    fn resolve(&self, base: Option<&Path>, path: &str) -> anyhow::Result<String> {
+        // Paths of other storage backends are checked against their own configuration, and
+        // are only usable under allowed roots when enabled there
+        if !storage::is_local(path) {
+            if !self.allowed_roots.is_empty() {
+                storage::check_enabled(path)?;
+            }
+            storage::check_allowed(path)?;
+            return Ok(path.to_string());
+        }
+        let expanded = expand_path(path)?;
+        let absolute = match base {
+            _ if expanded.is_absolute() => expanded,
+            Some(base) => base.join(expanded),
+            None => std::env::current_dir()?.join(expanded),
+        };
+
+        // Symlinks above the roots (such as /tmp on macOS) are part of the setup, not of the path
+        let in_scope = |dir: &Path| match (self.allowed_roots.is_empty(), base) {
+            (false, _) => self.allowed_roots.iter().any(|root| is_within(dir, root)),
+            (true, Some(base)) => is_within(dir, base),
+            (true, None) => true,
+        };
+        let (resolved, link) = real_path(&absolute, &in_scope)?;
+        match (self.symlinks, link) {
+            (SymlinkPolicy::Refuse, Some(link)) => anyhow::bail!(
+                "Path '{}' goes through the symlink '{}', and the server's symlink policy is refuse. Use the real path '{}' if it is within the allowed roots",
+                path, display_path(&link), display_path(&resolved)
+            ),
+            // The link itself sits inside a root, which is all `follow` asks
+            (SymlinkPolicy::Follow, Some(_)) => {}
+            (_, Some(link)) if self.check_allowed(&resolved, path).is_err() => anyhow::bail!(
+                "Path '{}' goes through the symlink '{}' to '{}', which is outside the allowed roots ({})",
+                path, display_path(&link), display_path(&resolved), self.roots_list()
+            ),
+            _ => self.check_allowed(&resolved, path)?,
+        }
+        Ok(display_path(&resolved))
+    }
+
+    /// Check a path a tool derived from its resolved arguments (an output pattern joined to
+    /// a directory, a manifest entry joined to a root) against the allowed roots and the
+    /// symlink policy, as if it had been an argument. Returns its real location.
+    pub fn resolve_derived(&self, path: &Path) -> anyhow::Result<PathBuf> {
+        self.resolve(None, &path.to_string_lossy()).map(PathBuf::from)
+    }
+
+    /// Every path named in resolved arguments, inputs and outputs alike.
+    pub fn file_arguments(args: &HashMap<String, Value>) -> Vec<String> {
+        let mut paths: Vec<String> = PATH_ARGUMENTS.iter()
+            .filter_map(|key| args.get(*key).and_then(|v| v.as_str()))
+            .map(str::to_string)
+            .collect();
+        if let Some(Value::Array(file_paths)) = args.get("file_paths") {
+            paths.extend(file_paths.iter().filter_map(|v| v.as_str()).map(str::to_string));
+        }
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+
+    /// Whether an argument holds a single path.
+    pub fn is_path_argument(key: &str) -> bool {
+        PATH_ARGUMENTS.contains(&key)
+    }
+
+    /// Input paths in resolved arguments that do not exist.
+    pub fn missing_inputs(args: &HashMap<String, Value>) -> Vec<String> {
+        let mut paths: Vec<&str> = INPUT_PATH_ARGUMENTS.iter()
+            .filter_map(|key| args.get(*key).and_then(|v| v.as_str()))
+            .collect();
+        if let Some(Value::Array(file_paths)) = args.get("file_paths") {
+            paths.extend(file_paths.iter().filter_map(|v| v.as_str()));
+        }
+        paths.into_iter()
+            .filter(|path| storage::is_local(path) && !Path::new(path).exists())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Explain a missing path: similarly named files near it ("did you mean") and the data
+    /// files in the closest existing directory, without looking outside the allowed roots.
+    pub fn not_found_hint(&self, path: &str) -> String {
+        let missing = Path::new(path);
+        let mut hint = format!("'{}' does not exist.", path);
+        let Some(directory) = missing.ancestors().skip(1).find(|dir| dir.is_dir()) else {
+            return hint;
+        };
+        if self.check_allowed(directory, path).is_err() {
+            return hint;
+        }
+
+        let wanted = missing.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+        let mut candidates = Vec::new();
+        collect_candidates(directory, 2, &mut candidates);
+        let mut scored: Vec<(f64, &PathBuf)> = candidates.iter()
+            .filter_map(|candidate| {
+                let name = candidate.file_name()?.to_string_lossy().to_lowercase();
+                let score = strsim::jaro_winkler(&wanted, &name);
+                (score >= 0.8).then_some((score, candidate))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+        if !scored.is_empty() {
+            hint.push_str(" Did you mean:");
+            for (_, candidate) in scored.iter().take(3) {
+                hint.push_str(&format!("\n  - {}", candidate.display()));
+            }
+        }
+
+        let mut nearby: Vec<String> = candidates.iter()
+            .filter(|c| c.parent() == Some(directory) && has_data_extension(c))
+            .filter_map(|c| c.file_name().map(|n| n.to_string_lossy().into_owned()))
+            .collect();
+        nearby.sort();
+        if !nearby.is_empty() {
+            let more = nearby.len().saturating_sub(10);
+            nearby.truncate(10);
+            hint.push_str(&format!("\nData files in '{}': {}", directory.display(), nearby.join(", ")));
+            if more > 0 {
+                hint.push_str(&format!(" (and {} more)", more));
+            }
+        }
+        hint
+    }
+
+    fn check_allowed(&self, canonical: &Path, original: &str) -> anyhow::Result<()> {
+        if self.allowed_roots.is_empty() || self.allowed_roots.iter().any(|root| is_within(canonical, root)) {
+            return Ok(());
+        }
+        anyhow::bail!("Path '{}' is outside the allowed roots ({})", original, self.roots_list())
+    }
+
+    fn roots_list(&self) -> String {
+        self.allowed_roots.iter().map(|r| r.display().to_string()).collect::<Vec<_>>().join(", ")
+    }
+}
+
+/// Check that `name`, built from a tool argument such as an output pattern, names a file
+/// in its directory: no path separators and no `..`.
+pub fn check_file_name(name: &str, argument: &str) -> anyhow::Result<()> {
+    if name.is_empty() || name == "." || name.contains("..") || name.contains(['/', '\\']) || (cfg!(windows) && name.contains(':')) {
+        anyhow::bail!("{} must produce a plain file name without directories, got '{}'", argument, name);
+    }
+    Ok(())
+}
+
+/// Whether `path` is `root` or below it. On Windows, drive letters, UNC shares and the
+/// `\\?\` prefix are normalized and case is ignored, as the file system does.
+fn is_within(path: &Path, root: &Path) -> bool {
+    if cfg!(windows) {
+        WindowsPath::parse(&path.to_string_lossy()).starts_with(&WindowsPath::parse(&root.to_string_lossy()))
+    } else {
+        path.starts_with(root)
+    }
+}
+
+/// Files under `directory`, descending `depth` levels and skipping hidden entries.
+fn collect_candidates(directory: &Path, depth: usize, out: &mut Vec<PathBuf>) {
+    const MAX_CANDIDATES: usize = 1000;
+    let Ok(entries) = std::fs::read_dir(directory) else { return };
+    for entry in entries.flatten() {
+        if out.len() >= MAX_CANDIDATES {
+            return;
+        }
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(t) if t.is_dir() && depth > 1 => collect_candidates(&path, depth - 1, out),
+            Ok(t) if t.is_file() => out.push(path),
+            _ => {}
+        }
+    }
+}
+
+fn has_data_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| DATA_EXTENSIONS.iter().any(|x| x.eq_ignore_ascii_case(e)))
+}
+
+/// Expand a leading `~`, `$HOME` / `${HOME}` and `%USERPROFILE%` / `%HOME%` to the user's
+/// home directory and normalize `/` and `\` separators for the current platform. On Windows,
+/// a slash before a drive letter (`/C:/data`) is dropped.
+pub fn expand_path(path: &str) -> anyhow::Result<PathBuf> {
+    let trimmed = path.trim();
+    let trimmed = if cfg!(windows) { strip_slash_before_drive(trimmed) } else { trimmed };
+    let home_prefix = ["~", "$HOME", "${HOME}", "%USERPROFILE%", "%HOME%"]
+        .iter()
+        .find(|prefix| {
+            trimmed.strip_prefix(**prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '\\']))
+        });
+
+    let expanded = match home_prefix {
+        Some(prefix) => {
+            let home = home_dir().ok_or_else(|| anyhow::anyhow!(
+                "Cannot expand '{}' in '{}': neither HOME nor USERPROFILE is set", prefix, path
+            ))?;
+            format!("{}{}", home.to_string_lossy(), &trimmed[prefix.len()..])
+        }
+        None if trimmed.starts_with('~') => anyhow::bail!(
+            "Cannot expand '{}': only '~' for the current user is supported", path
+        ),
+        None => trimmed.to_string(),
+    };
+
+    let separator = std::path::MAIN_SEPARATOR.to_string();
+    Ok(PathBuf::from(expanded.replace(['/', '\\'], &separator)))
+}
+
+fn home_dir() -> Option<PathBuf> {
+    ["HOME", "USERPROFILE"].iter()
+        .filter_map(std::env::var_os)
+        .find(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Paths as shown to clients, without Windows' `\\?\` long-path prefix.
+fn display_path(path: &Path) -> String {
+    strip_long_path_prefix(&path.to_string_lossy())
+}
+
+/// Resolve `path` like `canonicalize`, but also through dangling symlinks and into
+/// components that do not exist yet, so a link to a file about to be created cannot escape
+/// the roots. Also returns the first symlink met inside a directory `in_scope` accepts.
+fn real_path(path: &Path, in_scope: &dyn Fn(&Path) -> bool) -> anyhow::Result<(PathBuf, Option<PathBuf>)> {
+    let mut resolved = PathBuf::new();
+    let mut pending = Vec::new();
+    push_components(path, &mut resolved, &mut pending);
+    let mut first_link = None;
+    let mut hops = 0;
+    while let Some(name) = pending.pop() {
+        if name == ".." {
+            resolved.pop();
+            continue;
+        }
+        let candidate = resolved.join(&name);
+        match std::fs::symlink_metadata(&candidate) {
+            Ok(meta) if meta.file_type().is_symlink() => {
+                hops += 1;
+                if hops > MAX_SYMLINK_HOPS {
+                    anyhow::bail!("Too many levels of symlinks resolving '{}'", path.display());
+                }
+                if first_link.is_none() && in_scope(&resolved) {
+                    first_link = Some(candidate.clone());
+                }
+                let target = std::fs::read_link(&candidate)?;
+                push_components(&target, &mut resolved, &mut pending);
+            }
+            _ => resolved.push(name),
+        }
+    }
+    // Existing paths take their canonical spelling (case, Windows prefixes) from the OS
+    let resolved = resolved.canonicalize().unwrap_or(resolved);
+    Ok((resolved, first_link))
+}
+
+/// Queue the components of `path` on `pending` (a stack, so in reverse). An absolute
+/// `path` restarts `resolved` from its root.
+fn push_components(path: &Path, resolved: &mut PathBuf, pending: &mut Vec<OsString>) {
+    let mut names = Vec::new();
+    let mut after_prefix = false;
+    for component in path.components() {
+        match component {
+            Component::Prefix(prefix) => {
+                *resolved = PathBuf::from(prefix.as_os_str());
+                after_prefix = true;
+            }
+            Component::RootDir => {
+                if !after_prefix {
+                    *resolved = PathBuf::new();
+                }
+                resolved.push(component.as_os_str());
+            }
+            Component::CurDir => {}
+            Component::ParentDir => names.push(OsString::from("..")),
+            Component::Normal(name) => names.push(name.to_os_string()),
+        }
+    }
+    pending.extend(names.into_iter().rev());
+}
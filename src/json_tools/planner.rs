@@ -0,0 +1,172 @@
+use crate::json_tools::encryption;
+use crate::json_tools::formats::DocumentFormat;
+use crate::json_tools::resolve::KeyMatch;
+use crate::json_tools::storage;
+use crate::json_tools::streaming::{parse_streamable_path, StreamStep};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+
+/// JSON files at least this large are streamed when the query allows it. Smaller ones are
+/// loaded, which is as fast and explains an empty result without reading the file again.
+pub const STREAMING_THRESHOLD: u64 = 8 * 1024 * 1024;
+/// How much of a file is read to tell NDJSON from a single JSON document.
+const SNIFF_BYTES: u64 = 64 * 1024;
+
+/// The `execution` argument of json-query.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Execution {
+    Auto,
+    Streaming,
+    InMemory,
+}
+
+impl Execution {
+    /// Read the optional `execution` argument (default: auto).
+    pub fn from_args(args: &HashMap<String, Value>) -> anyhow::Result<Self> {
+        match args.get("execution").and_then(|v| v.as_str()) {
+            None | Some("auto") => Ok(Self::Auto),
+            Some("streaming") => Ok(Self::Streaming),
+            Some("in_memory") => Ok(Self::InMemory),
+            Some(other) => anyhow::bail!("Unknown execution '{}'. Use 'auto', 'streaming' or 'in_memory'", other),
+        }
+    }
+}
+
+/// JSON Schema for the `execution` argument.
+pub fn execution_schema() -> Value {
+    json!({
+        "type": "string",
+        "enum": ["auto", "streaming", "in_memory"],
+        "description": "How to run the query: 'streaming' evaluates simple paths (keys, indices, wildcards) while parsing a JSON or NDJSON file, 'in_memory' loads the whole document, 'auto' picks by file size, layout and query (default). The plan used is reported as 'plan' in the metadata"
+    })
+}
+
+/// How the top level of a JSON file is laid out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Layout {
+    Object,
+    Array,
+    Scalar,
+    /// One JSON value per line, queried as an array of records.
+    Ndjson,
+}
+
+impl Layout {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Object => "object",
+            Self::Array => "array",
+            Self::Scalar => "scalar",
+            Self::Ndjson => "ndjson",
+        }
+    }
+
+    /// The layout of `file_path`, from a `.ndjson`/`.jsonl` extension or by checking
+    /// whether the first line is a whole JSON value followed by more.
+    fn detect(file_path: &str) -> anyhow::Result<Self> {
+        let extension = Path::new(file_path).extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase());
+        if matches!(extension.as_deref(), Some("ndjson" | "jsonl")) {
+            return Ok(Self::Ndjson);
+        }
+        let mut head = Vec::new();
+        storage::open(file_path)?.take(SNIFF_BYTES).read_to_end(&mut head)?;
+        let text = String::from_utf8_lossy(&head);
+        let text = text.trim_start();
+        if let Some((first, rest)) = text.split_once('\n') {
+            if !rest.trim().is_empty() && serde_json::from_str::<serde::de::IgnoredAny>(first).is_ok() {
+                return Ok(Self::Ndjson);
+            }
+        }
+        Ok(match text.chars().next() {
+            Some('{') => Self::Object,
+            Some('[') => Self::Array,
+            _ => Self::Scalar,
+        })
+    }
+}
+
+/// How json-query evaluates a query.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Strategy {
+    /// Load the whole document and evaluate the query on it.
+    InMemory,
+    /// Evaluate these steps while parsing, keeping only the matches.
+    Streaming(Vec<StreamStep>),
+}
+
+/// The strategy chosen for a query, with what it was chosen from.
+#[derive(Debug)]
+pub struct QueryPlan {
+    pub strategy: Strategy,
+    /// `None` for inline content and files that are converted or decrypted first.
+    pub layout: Option<Layout>,
+    file_bytes: Option<u64>,
+    /// The file does not exist, so reading it reports the backend's not-found error.
+    missing: bool,
+    reason: String,
+}
+
+impl QueryPlan {
+    /// Choose how to run `query` over `file_path`, or over inline content when `None`.
+    pub fn new(file_path: Option<&str>, query: &str, key_match: KeyMatch, execution: Execution) -> anyhow::Result<Self> {
+        let in_memory = |layout, file_bytes, reason: String| Self { strategy: Strategy::InMemory, layout, file_bytes, missing: false, reason };
+        let Some(file_path) = file_path else {
+            return Ok(in_memory(None, None, "inline content is already in memory".to_string()));
+        };
+        let Some(size) = storage::info(file_path)?.map(|info| info.size) else {
+            // Reading a missing file fails with its backend's own not-found error
+            return Ok(Self { missing: true, ..in_memory(None, None, "the file does not exist".to_string()) });
+        };
+        let file_bytes = Some(size);
+        let format = DocumentFormat::from_path(file_path);
+        if format != DocumentFormat::Json {
+            return Ok(in_memory(None, file_bytes, format!("{} documents are converted to JSON in memory", format.name().to_uppercase())));
+        }
+        if encryption::is_encrypted(file_path) {
+            return Ok(in_memory(None, file_bytes, "encrypted files are decrypted in memory".to_string()));
+        }
+
+        let layout = Some(Layout::detect(file_path)?);
+        if execution == Execution::InMemory {
+            return Ok(in_memory(layout, file_bytes, "execution 'in_memory' was requested".to_string()));
+        }
+        if key_match != KeyMatch::Exact {
+            return Ok(in_memory(layout, file_bytes, "key_match resolves keys against the whole document".to_string()));
+        }
+        let Some(steps) = parse_streamable_path(query) else {
+            return Ok(in_memory(layout, file_bytes, "the query uses filters, slices, unions, recursive descent or functions, which need the whole document".to_string()));
+        };
+        let reason = match execution {
+            Execution::Streaming => "execution 'streaming' was requested".to_string(),
+            _ if size < STREAMING_THRESHOLD => {
+                return Ok(in_memory(layout, file_bytes, format!("{} bytes is below the {}-byte streaming threshold", size, STREAMING_THRESHOLD)));
+            }
+            _ => format!("the query is a simple path over {} bytes", size),
+        };
+        Ok(Self { strategy: Strategy::Streaming(steps), layout, file_bytes, missing: false, reason })
+    }
+
+    pub fn is_streaming(&self) -> bool {
+        matches!(self.strategy, Strategy::Streaming(_))
+    }
+
+    pub fn is_missing(&self) -> bool {
+        self.missing
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    /// The plan as reported in json-query's result metadata.
+    pub fn describe(&self) -> Value {
+        json!({
+            "strategy": if self.is_streaming() { "streaming" } else { "in_memory" },
+            "layout": self.layout.map(Layout::name),
+            "file_bytes": self.file_bytes,
+            "reason": self.reason,
+        })
+    }
+}
@@ -0,0 +1,144 @@
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Time a plugin gets to describe its tools at startup.
+const DESCRIBE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Time a plugin gets to answer one tool call.
+const CALL_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// What a plugin prints for `--describe`.
+#[derive(Deserialize)]
+struct Description {
+    tools: Vec<Tool>,
+}
+
+/// An external executable that provides tools. It is run once per call:
+/// `<plugin> --describe` prints `{"tools": [...]}` (MCP tool definitions) and
+/// `<plugin> --call` reads `{"name", "arguments"}` on stdin and prints an MCP tool result.
+#[derive(Debug)]
+struct Plugin {
+    executable: PathBuf,
+    tools: Vec<Tool>,
+}
+
+/// Plugins found in the `--plugin-dir` directories, whose tools the server lists and
+/// proxies next to its own.
+#[derive(Debug, Clone, Default)]
+pub struct PluginRegistry {
+    plugins: Vec<Arc<Plugin>>,
+}
+
+impl PluginRegistry {
+    /// Describe every executable file in `dirs`. A plugin that fails to describe itself, or
+    /// offers a tool named like one in `reserved` or in another plugin, is an error, so a
+    /// plugin can never silently shadow a built-in tool.
+    pub async fn discover(dirs: &[PathBuf], reserved: &[String]) -> anyhow::Result<Self> {
+        let mut executables = Vec::new();
+        for dir in dirs {
+            let entries = std::fs::read_dir(dir)
+                .map_err(|e| anyhow::anyhow!("Cannot read plugin directory '{}': {}", dir.display(), e))?;
+            for entry in entries {
+                let path = entry?.path();
+                if is_executable(&path) {
+                    executables.push(path);
+                }
+            }
+        }
+        executables.sort();
+
+        let mut registry = Self::default();
+        let mut names: Vec<String> = reserved.to_vec();
+        for executable in executables {
+            let output = run(&executable, "--describe", None, DESCRIBE_TIMEOUT).await?;
+            let description: Description = serde_json::from_slice(&output)
+                .map_err(|e| anyhow::anyhow!("Plugin '{}' printed an invalid --describe result: {}", executable.display(), e))?;
+            for tool in &description.tools {
+                if names.contains(&tool.name) {
+                    anyhow::bail!("Plugin '{}' offers tool '{}', which already exists", executable.display(), tool.name);
+                }
+                names.push(tool.name.clone());
+            }
+            registry.plugins.push(Arc::new(Plugin { executable, tools: description.tools }));
+        }
+        Ok(registry)
+    }
+
+    pub fn tools(&self) -> Vec<Tool> {
+        self.plugins.iter().flat_map(|plugin| plugin.tools.clone()).collect()
+    }
+
+    pub fn handles(&self, name: &str) -> bool {
+        self.plugin_for(name).is_some()
+    }
+
+    /// Run a call in the plugin that offers the tool. Failures of the plugin itself (it
+    /// crashed, timed out or printed something that is not a tool result) are error results.
+    pub async fn call(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        let Some(plugin) = self.plugin_for(&tool_call.name) else {
+            return Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name)));
+        };
+        let input = serde_json::to_vec(&tool_call)?;
+        let output = match run(&plugin.executable, "--call", Some(&input), CALL_TIMEOUT).await {
+            Ok(output) => output,
+            Err(e) => return Ok(ToolResult::error(e.to_string())),
+        };
+        Ok(serde_json::from_slice(&output).unwrap_or_else(|e| ToolResult::error(format!(
+            "Plugin '{}' printed an invalid tool result: {}", plugin.executable.display(), e
+        ))))
+    }
+
+    fn plugin_for(&self, name: &str) -> Option<&Plugin> {
+        self.plugins.iter().find(|plugin| plugin.tools.iter().any(|tool| tool.name == name)).map(Arc::as_ref)
+    }
+}
+
+/// Run a plugin with one flag and optional stdin, returning its stdout. A nonzero exit is an
+/// error carrying the plugin's stderr.
+async fn run(executable: &Path, flag: &str, input: Option<&[u8]>, timeout: Duration) -> anyhow::Result<Vec<u8>> {
+    let mut child = Command::new(executable)
+        .arg(flag)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Cannot run plugin '{}': {}", executable.display(), e))?;
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    // Written while the output is read, so a plugin that answers before reading all of its
+    // input cannot deadlock on full pipes. One that exits without reading it is judged by
+    // its exit status
+    let write = async move {
+        if let Some(input) = input {
+            let _ = stdin.write_all(input).await;
+        }
+    };
+    let (_, output) = tokio::join!(write, tokio::time::timeout(timeout, child.wait_with_output()));
+    let output = output
+        .map_err(|_| anyhow::anyhow!("Plugin '{}' did not finish {} within {} s", executable.display(), flag, timeout.as_secs()))??;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Plugin '{}' {} failed ({}): {}",
+            executable.display(), flag, output.status, String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata().is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    let executable_extension = path.extension()
+        .is_some_and(|ext| ["exe", "cmd", "bat"].iter().any(|known| ext.eq_ignore_ascii_case(known)));
+    executable_extension && path.is_file()
+}
@@ -1,16 +1,89 @@
+use crate::json_tools::binary::{decode_base64_schema, Base64Summary};
+use crate::json_tools::embedded::{expand_embedded_output, expand_embedded_schema};
+use crate::json_tools::envelope::ResultMetadata;
+use crate::json_tools::files::{for_each_record, RecordFormat};
+use crate::json_tools::formats::{content_schema, inline_document, read_document, DocumentFormat};
+use crate::json_tools::jsonpath::{dialect_schema, normalized_path_to_pointer, JsonPathDialect};
+use crate::json_tools::planner::{execution_schema, Execution, Layout, QueryPlan, Strategy};
+use crate::json_tools::resolve::{diagnose_empty, key_match_schema, resolve_keys, KeyMatch};
+use crate::json_tools::script::{evaluate, script_engine, to_dynamic, DEFAULT_MAX_STEPS};
+use crate::json_tools::secrets::{redact_output, redact_schema};
+use crate::json_tools::sql::{where_schema, Predicate};
+use crate::json_tools::storage;
+use crate::json_tools::streaming::{stream_ndjson_query, stream_query};
+use crate::json_tools::syntax::{check_syntax, syntax_report};
+use crate::json_tools::timewindow::{time_bound_schema, time_field_schema, TimeWindow};
 use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
 use crate::mcp::server::ToolHandler;
 use async_trait::async_trait;
-use serde_json::{json, Value};
-use jsonpath_rust::JsonPath;
+use rhai::Scope;
+use serde_json::{json, Map, Value};
 use std::collections::HashMap;
-use std::fs;
+use std::time::Instant;
 
-pub struct JsonQuery;
+#[cfg(feature = "parquet")]
+use crate::json_tools::columnar::write_parquet;
+
+#[cfg(not(feature = "parquet"))]
+fn write_parquet(_output_path: &str, _records: &[Value]) -> anyhow::Result<u64> {
+    anyhow::bail!("this server was built without the 'parquet' feature")
+}
+
+pub struct JsonQuery {
+    dialect: JsonPathDialect,
+}
+
+impl Default for JsonQuery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl JsonQuery {
     pub fn new() -> Self {
-        Self
+        Self::with_dialect(JsonPathDialect::default())
+    }
+
+    /// Evaluate queries with `dialect` unless a call names another one.
+    pub fn with_dialect(dialect: JsonPathDialect) -> Self {
+        Self { dialect }
+    }
+
+    fn create_extract_tool() -> Tool {
+        Tool {
+            name: "json-extract".to_string(),
+            description: "Run a JSONPath query and write the results to a new JSON or NDJSON file instead of returning them, keeping large result sets out of the conversation.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "Path to the JSON file to query"
+                    },
+                    "query": {
+                        "type": "string",
+                        "description": "JSONPath expression selecting the results to extract (e.g., '$.users[?(@.active == true)]')"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "Path of the file to write the results to. Parent directories are created"
+                    },
+                    "output_format": {
+                        "type": "string",
+                        "description": "'json' writes an array of results, 'ndjson' writes one result per line, 'parquet' writes flat objects as a Parquet table (requires the 'parquet' feature)",
+                        "enum": ["json", "ndjson", "parquet"],
+                        "default": "json"
+                    },
+                    "pretty": {
+                        "type": "boolean",
+                        "description": "Format JSON output with indentation (ignored for ndjson and parquet)",
+                        "default": true
+                    },
+                    "dialect": dialect_schema()
+                },
+                "required": ["file_path", "query", "output_path"]
+            })
+        }
     }
 
     fn create_query_tool() -> Tool {
@@ -22,8 +95,9 @@ impl JsonQuery {
                 "properties": {
                     "file_path": {
                         "type": "string",
-                        "description": "Path to the JSON file to query"
+                        "description": "Path to the JSON file to query (use this or 'content')"
                     },
+                    "content": content_schema(),
                     "query": {
                         "type": "string",
                         "description": "JSONPath expression to execute (e.g., '$.users[?(@.age > 25)].name')"
@@ -33,20 +107,79 @@ impl JsonQuery {
                         "description": "Output format: 'json' (default), 'text', or 'table'",
                         "enum": ["json", "text", "table"],
                         "default": "json"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "description": "Maximum number of matches to return (default: all)",
+                        "minimum": 1
+                    },
+                    "offset": {
+                        "type": "integer",
+                        "description": "Number of matches to skip (default: 0)",
+                        "default": 0,
+                        "minimum": 0
+                    },
+                    "include_paths": {
+                        "type": "boolean",
+                        "description": "Return each match as {path, pointer, value}: its normalized JSONPath (e.g. $['users'][0]) and JSON Pointer (/users/0), for follow-up writes to exactly those locations (default: false)",
+                        "default": false
+                    },
+                    "computed": {
+                        "type": "object",
+                        "additionalProperties": {"type": "string"},
+                        "description": "Fields to add to each object match, as name -> expression over the match's fields (the whole match is 'item'), evaluated in order so later fields can use earlier ones. Expressions use the json-script (rhai) syntax. E.g. {\"total\": \"price * qty\", \"label\": \"name + \\\" (\\\" + sku + \\\")\\\"\", \"day\": \"created.date(\\\"%Y-%m-%d\\\")\"}"
+                    },
+                    "key_match": key_match_schema(),
+                    "where": where_schema(),
+                    "since": time_bound_schema("since"),
+                    "until": time_bound_schema("until"),
+                    "time_field": time_field_schema(),
+                    "dialect": dialect_schema(),
+                    "execution": execution_schema(),
+                    "decode_base64_path": decode_base64_schema(),
+                    "expand_embedded_json": expand_embedded_schema(),
+                    "redact": redact_schema()
+                },
+                "required": ["query"]
+            })
+        }
+    }
+
+    fn create_validate_query_tool() -> Tool {
+        Tool {
+            name: "json-validate-query".to_string(),
+            description: "Check a JSONPath expression's syntax without running it. Reports the error position and suggests a corrected query for common mistakes (missing '@' or '$', '=' instead of '==', and/or instead of &&/||, typographic quotes, JSON Pointers).".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "query": {
+                        "type": "string",
+                        "description": "JSONPath expression to check"
                     }
                 },
-                "required": ["file_path", "query"]
+                "required": ["query"]
             })
         }
     }
 
-    async fn handle_query(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
-        let file_path = args.get("file_path")
+    async fn handle_validate_query(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let query = args.get("query")
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow::anyhow!(
-                "file_path is required. Usage example:\n{{\n  \"file_path\": \"./data.json\",\n  \"query\": \"$.users[0].name\"\n}}"
+                "query is required. Usage example:\n{{\n  \"query\": \"$.users[?(@.age > 25)].name\"\n}}"
             ))?;
 
+        match check_syntax(query) {
+            Ok(()) => Ok(ToolResult::success(format!(
+                "JSONPath '{}' is valid:\n\n{}", query, serde_json::to_string_pretty(&json!({"valid": true, "query": query}))?
+            ))),
+            Err(error) => Ok(ToolResult::error(format!(
+                "JSONPath '{}' is invalid {}:\n\n{}", query, error.summary(), serde_json::to_string_pretty(&syntax_report(query, &error))?
+            ))),
+        }
+    }
+
+    async fn handle_query(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
         let query = args.get("query")
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow::anyhow!(
@@ -57,38 +190,276 @@ impl JsonQuery {
             .and_then(|v| v.as_str())
             .unwrap_or("json");
 
-        // Read the file
-        let content = fs::read_to_string(file_path)
-            .map_err(|e| anyhow::anyhow!("Failed to read file '{}': {}", file_path, e))?;
+        let limit = args.get("limit")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize);
+        let offset = args.get("offset")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as usize;
 
-        // Parse JSON content
-        let json_value: Value = serde_json::from_str(&content)
-            .map_err(|e| anyhow::anyhow!("Failed to parse JSON: {}", e))?;
+        let include_paths = args.get("include_paths")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
 
-        // Execute JSONPath query
-        let results = match json_value.query(query) {
-            Ok(values) => {
-                // Convert the results to JSON values
-                values.into_iter().map(|v| v.clone()).collect::<Vec<Value>>()
-            },
-            Err(e) => return Ok(ToolResult::error(format!("JSONPath query error: {}", e))),
+        let started = Instant::now();
+        let dialect = match JsonPathDialect::from_args(args, self.dialect) {
+            Ok(dialect) => dialect,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+        // Catch syntax errors before loading the document, with a suggested fix
+        // (the RFC 9535 parser reports its own errors with a column)
+        let syntax = match dialect {
+            JsonPathDialect::JsonpathRust => check_syntax(query),
+            JsonPathDialect::Rfc9535 => Ok(()),
+        };
+        if let Err(error) = syntax {
+            return Ok(ToolResult::error(format!(
+                "Invalid JSONPath '{}' {}:\n\n{}", query, error.summary(), serde_json::to_string_pretty(&syntax_report(query, &error))?
+            )));
+        }
+
+        let key_match = KeyMatch::from_args(args)?;
+        let execution = Execution::from_args(args)?;
+        let window = TimeWindow::from_args(args)?;
+        let predicate = match Predicate::from_args(args) {
+            Ok(predicate) => predicate,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+
+        let file_path = args.get("file_path").and_then(|v| v.as_str());
+        let source = match (file_path, args.get("content")) {
+            (Some(file_path), None) => format!("'{}'", file_path),
+            (None, Some(_)) => "inline content".to_string(),
+            (Some(_), Some(_)) => return Ok(ToolResult::error("Provide either file_path or content, not both".to_string())),
+            (None, None) => anyhow::bail!(
+                "file_path is required. Usage example:\n{{\n  \"file_path\": \"./data.json\",\n  \"query\": \"$.users[0].name\"\n}}\nTo query JSON you already have, pass it as \"content\" instead of file_path"
+            ),
+        };
+        let mut matches = Matches {
+            window: window.as_ref(),
+            predicate: predicate.as_ref(),
+            offset,
+            limit: limit.unwrap_or(usize::MAX),
+            path_matches: 0,
+            total: 0,
+            page: Vec::new(),
+        };
+
+        // Stream simple paths over large JSON files so only the matches are held in memory;
+        // anything else reads the whole document (TOML files are converted to the JSON data model)
+        let plan = QueryPlan::new(file_path, query, key_match, execution)?;
+        if execution == Execution::Streaming && !plan.is_streaming() && !plan.is_missing() {
+            return Ok(ToolResult::error(format!("Cannot stream this query: {}. Use execution 'auto' or 'in_memory'", plan.reason())));
+        }
+        let mut document = None;
+        let (resolved_query, corrections) = match (&plan.strategy, file_path) {
+            (Strategy::Streaming(steps), Some(file_path)) => {
+                match plan.layout {
+                    Some(Layout::Ndjson) => stream_ndjson_query(file_path, steps, |path, value| matches.push(path, value))?,
+                    _ => stream_query(file_path, steps, |path, value| matches.push(path, value))?,
+                }
+                (query.to_string(), Vec::new())
+            }
+            _ => {
+                let loaded = match file_path {
+                    Some(file_path) => load_document(file_path, &plan)?,
+                    None => inline_document(&args["content"])?,
+                };
+                // Rewrite mis-cased or misspelled keys to the ones present in the document
+                let (resolved_query, corrections) = match key_match {
+                    KeyMatch::Exact => (query.to_string(), Vec::new()),
+                    mode => resolve_keys(&loaded, query, mode),
+                };
+                match dialect.query_with_paths(&loaded, &resolved_query) {
+                    Ok(found) => found.into_iter().for_each(|(path, value)| matches.push(path, value.clone())),
+                    Err(message) => return Ok(ToolResult::error(message)),
+                }
+                document = Some(loaded);
+                (resolved_query, corrections)
+            }
         };
 
+        let Matches { path_matches, total: total_matched, mut page, .. } = matches;
+        let mut decoded = None;
+        if let Some(summary) = Base64Summary::from_args(args) {
+            match page.iter_mut().map(|(_, value)| summary.apply(value)).sum::<Result<usize, String>>() {
+                Ok(count) => decoded = Some(count),
+                Err(message) => return Ok(ToolResult::error(format!("decode_base64_path: {}", message))),
+            }
+        }
+        if let Some(computed) = args.get("computed") {
+            let Some(computed) = computed.as_object() else {
+                return Ok(ToolResult::error("computed must be an object of field name -> expression, e.g. {\"total\": \"price * qty\"}".to_string()));
+            };
+            if let Err(message) = add_computed_fields(computed, &mut page) {
+                return Ok(ToolResult::error(message));
+            }
+        }
+        let page: Vec<Value> = page.into_iter()
+            .map(|(path, value)| if include_paths {
+                json!({"path": path, "pointer": normalized_path_to_pointer(&path), "value": value})
+            } else {
+                value
+            })
+            .collect();
+        let next_offset = Some(offset + page.len()).filter(|&next| next < total_matched);
+        let mut metadata = ResultMetadata::new(total_matched, page.len(), next_offset, started)
+            .with("dialect", json!(dialect.name()))
+            .with("plan", plan.describe());
+        if let Some(window) = &window {
+            metadata = metadata.with("time_window", window.describe());
+        }
+        if let Some(decoded) = decoded {
+            metadata = metadata.with("base64_decoded", json!(decoded));
+        }
+        if !corrections.is_empty() {
+            metadata = metadata
+                .with("resolved_query", json!(resolved_query))
+                .with("key_corrections", json!(corrections.iter()
+                    .map(|c| json!({"requested": c.requested, "actual": c.actual}))
+                    .collect::<Vec<_>>()));
+        }
+        if total_matched == 0 {
+            let diagnostics = if path_matches > 0 {
+                json!({"reason": format!(
+                    "the path matched {} value(s), but the where/since/until filters excluded all of them", path_matches
+                )})
+            } else {
+                match document {
+                    Some(document) => self.diagnose(dialect, &document, &resolved_query),
+                    // Loading a file too large to query in memory only to explain the miss
+                    // would defeat streaming it
+                    None => json!({"note": "the query was streamed, so no diagnostics were collected. Run it with execution 'in_memory' for suggestions"}),
+                }
+            };
+            metadata = metadata.with("diagnostics", diagnostics);
+        }
+
+        let mut results_value = Value::Array(page);
+        if let Some(expanded) = expand_embedded_output(args, &mut results_value) {
+            metadata = metadata.with("embedded_json_expanded", json!(expanded));
+        }
+        if let Some(redacted) = redact_output(args, &mut results_value)? {
+            metadata = metadata.with("redacted", json!(redacted));
+        }
+
         // Format output based on requested format
-        let results_value = Value::Array(results);
         let output = match format {
-            "json" => serde_json::to_string_pretty(&results_value)?,
-            "text" => self.format_as_text(&results_value),
-            "table" => self.format_as_table(&results_value),
+            "json" => serde_json::to_string_pretty(&metadata.wrap(results_value))?,
+            "text" => format!("{}\n\nMetadata: {}", self.format_as_text(&results_value), serde_json::to_string(&metadata)?),
+            "table" => format!("{}\n\nMetadata: {}", self.format_as_table(&results_value), serde_json::to_string(&metadata)?),
             _ => return Ok(ToolResult::error(format!("Unknown format: {}", format))),
         };
 
+        let resolved_note = if corrections.is_empty() {
+            String::new()
+        } else {
+            format!(
+                " (resolved to '{}': {})",
+                resolved_query,
+                corrections.iter().map(|c| format!("'{}' -> '{}'", c.requested, c.actual)).collect::<Vec<_>>().join(", ")
+            )
+        };
+        Ok(ToolResult::success(format!(
+            "Query results from {} using JSONPath '{}'{}:\n\n{}",
+            source, query, resolved_note, output
+        )))
+    }
+
+    async fn handle_extract(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let file_path = args.get("file_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "file_path is required. Usage example:\n{{\n  \"file_path\": \"./data.json\",\n  \"query\": \"$.users[*]\",\n  \"output_path\": \"./users.json\"\n}}"
+            ))?;
+
+        let query = args.get("query")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "query is required. Usage example:\n{{\n  \"file_path\": \"./data.json\",\n  \"query\": \"$.users[*]\",\n  \"output_path\": \"./users.json\"\n}}"
+            ))?;
+
+        let output_path = args.get("output_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "output_path is required. Usage example:\n{{\n  \"file_path\": \"./data.json\",\n  \"query\": \"$.users[*]\",\n  \"output_path\": \"./users.json\"\n}}"
+            ))?;
+
+        let output_format = args.get("output_format")
+            .and_then(|v| v.as_str())
+            .unwrap_or("json");
+
+        let pretty = args.get("pretty")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+
+        let dialect = match JsonPathDialect::from_args(args, self.dialect) {
+            Ok(dialect) => dialect,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+
+        let document = read_document(file_path, DocumentFormat::from_path(file_path))?;
+        let results = match self.execute_query(dialect, &document, query) {
+            Ok(results) => results,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+
+        let content = match output_format {
+            "json" if pretty => Some(serde_json::to_string_pretty(&results)?),
+            "json" => Some(serde_json::to_string(&results)?),
+            "ndjson" => {
+                let mut lines = String::new();
+                for result in &results {
+                    lines.push_str(&serde_json::to_string(result)?);
+                    lines.push('\n');
+                }
+                Some(lines)
+            },
+            "parquet" => None,
+            _ => return Ok(ToolResult::error(format!(
+                "Unknown output_format: {}. Use 'json', 'ndjson' or 'parquet'",
+                output_format
+            ))),
+        };
+
+        storage::create_parent_dirs(output_path)?;
+
+        let bytes = match content {
+            Some(content) => {
+                storage::write(output_path, content.as_bytes())?;
+                content.len() as u64
+            }
+            None => match write_parquet(output_path, &results) {
+                Ok(bytes) => bytes,
+                Err(e) => return Ok(ToolResult::error(format!("Cannot write '{}' as parquet: {}", output_path, e))),
+            },
+        };
+
         Ok(ToolResult::success(format!(
-            "Query results from '{}' using JSONPath '{}':\n\n{}",
-            file_path, query, output
+            "Extracted {} results from '{}' using JSONPath '{}' ({}) to '{}' ({}, {} bytes)",
+            results.len(), file_path, query, dialect.name(), output_path, output_format, bytes
         )))
     }
 
+    /// Run `query` against a loaded document. JSONPath errors are returned as `Err` so
+    /// callers can surface them as tool errors.
+    fn execute_query(&self, dialect: JsonPathDialect, document: &Value, query: &str) -> Result<Vec<Value>, String> {
+        dialect.query(document, query).map(|values| values.into_iter().cloned().collect())
+    }
+
+    /// Explain an empty result, keeping only suggested queries that actually match.
+    fn diagnose(&self, dialect: JsonPathDialect, document: &Value, query: &str) -> Value {
+        let mut diagnostics = diagnose_empty(document, query);
+        if let Some(Value::Array(suggestions)) = diagnostics.get_mut("suggestions") {
+            suggestions.retain(|suggestion| suggestion.as_str()
+                .and_then(|q| self.execute_query(dialect, document, q).ok())
+                .is_some_and(|results| !results.is_empty()));
+            suggestions.dedup();
+            suggestions.truncate(3);
+        }
+        diagnostics
+    }
+
     fn format_as_text(&self, value: &Value) -> String {
         match value {
             Value::Array(arr) => {
@@ -149,15 +520,90 @@ impl JsonQuery {
     }
 }
 
+/// Read the document `plan` was made for: NDJSON files as the array of their records.
+fn load_document(file_path: &str, plan: &QueryPlan) -> anyhow::Result<Value> {
+    if plan.layout != Some(Layout::Ndjson) {
+        return read_document(file_path, DocumentFormat::from_path(file_path));
+    }
+    let mut records = Vec::new();
+    for_each_record(file_path, RecordFormat::Ndjson, "$", |record| {
+        records.push(record);
+        Ok(())
+    })?;
+    Ok(Value::Array(records))
+}
+
+/// Filters json-query matches and keeps the requested page of them, counting the rest,
+/// so a streamed query holds only one page in memory.
+struct Matches<'a> {
+    window: Option<&'a TimeWindow>,
+    predicate: Option<&'a Predicate>,
+    offset: usize,
+    limit: usize,
+    /// Matches of the path, before `where` and the time window.
+    path_matches: usize,
+    total: usize,
+    page: Vec<(String, Value)>,
+}
+
+impl Matches<'_> {
+    fn push(&mut self, path: String, value: Value) {
+        self.path_matches += 1;
+        if self.window.is_some_and(|window| !window.contains(&value))
+            || self.predicate.is_some_and(|predicate| !predicate.matches(&value)) {
+            return;
+        }
+        if self.total >= self.offset && self.page.len() < self.limit {
+            self.page.push((path, value));
+        }
+        self.total += 1;
+    }
+}
+
+/// Add each computed field to every match, evaluating its expression with the match's
+/// fields (and the match itself as `item`) in scope.
+fn add_computed_fields(computed: &Map<String, Value>, page: &mut [(String, Value)]) -> Result<(), String> {
+    let engine = script_engine(DEFAULT_MAX_STEPS);
+    let mut expressions = Vec::new();
+    for (name, source) in computed {
+        let source = source.as_str()
+            .ok_or_else(|| format!("computed field '{}' must be an expression string", name))?;
+        let expression = engine.compile_expression(source)
+            .map_err(|e| format!("computed field '{}': invalid expression '{}': {}", name, source, e))?;
+        expressions.push((name, expression));
+    }
+    for (path, value) in page.iter_mut() {
+        let Value::Object(fields) = value else {
+            return Err(format!(
+                "computed fields need object matches, but {} is {}; query the objects that hold the values instead",
+                path, value
+            ));
+        };
+        for (name, expression) in &expressions {
+            let mut scope = Scope::new();
+            for (field, value) in fields.iter() {
+                scope.push_dynamic(field.as_str(), to_dynamic(value));
+            }
+            scope.push_dynamic("item", to_dynamic(&Value::Object(fields.clone())));
+            let result = evaluate(&engine, &mut scope, expression, DEFAULT_MAX_STEPS)
+                .map_err(|e| format!("computed field '{}' at {}: {}", name, path, e))?;
+            fields.insert(name.to_string(), result);
+        }
+    }
+    Ok(())
+}
+
 #[async_trait]
 impl ToolHandler for JsonQuery {
     async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
-        Ok(vec![Self::create_query_tool()])
+        Ok(vec![Self::create_query_tool(), Self::create_extract_tool(), Self::create_validate_query_tool()])
     }
 
     async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
         match tool_call.name.as_str() {
             "json-query" => self.handle_query(&tool_call.arguments).await,
+            "json-extract" => self.handle_extract(&tool_call.arguments).await,
+            "json-validate-query" => self.handle_validate_query(&tool_call.arguments).await,
             _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
         }
     }
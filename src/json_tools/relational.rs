@@ -0,0 +1,620 @@
+use crate::json_tools::files::{for_each_record, RecordFormat, RecordWriter};
+use crate::json_tools::formats::{read_document, serialize_for_path, DocumentFormat};
+use crate::json_tools::integrity::canonical_json;
+use crate::json_tools::sql::{compare_values, lookup_field};
+use crate::json_tools::storage;
+use crate::json_tools::streaming::{parse_simple_path, PathSegment};
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+pub struct JsonRelational;
+
+impl Default for JsonRelational {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonRelational {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn create_join_tool() -> Tool {
+        Tool {
+            name: "json-join".to_string(),
+            description: "Join two arrays of records (from the same or different JSON/NDJSON files) on key fields, with inner, left, right or outer semantics. Writes the joined records to a file or returns them.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "left_file": {
+                        "type": "string",
+                        "description": "JSON or NDJSON file containing the left records"
+                    },
+                    "right_file": {
+                        "type": "string",
+                        "description": "JSON or NDJSON file containing the right records (default: left_file)"
+                    },
+                    "left_array_path": {
+                        "type": "string",
+                        "description": "Simple JSONPath to the left array in a JSON file (default: '$')",
+                        "default": "$"
+                    },
+                    "right_array_path": {
+                        "type": "string",
+                        "description": "Simple JSONPath to the right array in a JSON file (default: '$')",
+                        "default": "$"
+                    },
+                    "left_key": {
+                        "description": "Field (dotted path like 'user.id') or array of fields identifying left records",
+                        "oneOf": [
+                            {"type": "string"},
+                            {"type": "array", "items": {"type": "string"}}
+                        ]
+                    },
+                    "right_key": {
+                        "description": "Field or array of fields identifying right records (default: left_key)",
+                        "oneOf": [
+                            {"type": "string"},
+                            {"type": "array", "items": {"type": "string"}}
+                        ]
+                    },
+                    "mode": {
+                        "type": "string",
+                        "enum": ["inner", "left", "right", "outer"],
+                        "description": "Which unmatched records to keep (default: inner)",
+                        "default": "inner"
+                    },
+                    "output_shape": {
+                        "type": "string",
+                        "enum": ["nested", "merged"],
+                        "description": "'nested' produces {\"left\": ..., \"right\": ...}; 'merged' combines the fields of both objects, prefixing clashing right fields with 'right_' (default: nested)",
+                        "default": "nested"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "File to write the joined records to (.ndjson/.jsonl for NDJSON). Records are returned when omitted"
+                    }
+                },
+                "required": ["left_file", "left_key"]
+            })
+        }
+    }
+
+    fn create_group_tool() -> Tool {
+        Tool {
+            name: "json-group".to_string(),
+            description: "Group an array of objects by one or more fields and compute count/sum/avg/min/max aggregates per group, returning a table of rows or a nested object.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "JSON or NDJSON file containing the records"
+                    },
+                    "array_path": {
+                        "type": "string",
+                        "description": "Simple JSONPath to the array in a JSON file (default: '$')",
+                        "default": "$"
+                    },
+                    "group_by": {
+                        "description": "Field (dotted path) or array of fields to group by",
+                        "oneOf": [
+                            {"type": "string"},
+                            {"type": "array", "items": {"type": "string"}}
+                        ]
+                    },
+                    "aggregates": {
+                        "type": "array",
+                        "description": "Aggregates to compute per group (default: [{\"op\": \"count\"}])",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "op": {
+                                    "type": "string",
+                                    "enum": ["count", "sum", "avg", "min", "max"]
+                                },
+                                "field": {
+                                    "type": "string",
+                                    "description": "Field to aggregate (required except for count)"
+                                },
+                                "as": {
+                                    "type": "string",
+                                    "description": "Output name (default: 'count' or '<op>_<field>')"
+                                }
+                            },
+                            "required": ["op"]
+                        }
+                    },
+                    "output": {
+                        "type": "string",
+                        "enum": ["table", "nested"],
+                        "description": "'table' returns one row per group; 'nested' returns an object keyed by each group_by value in turn (default: table)",
+                        "default": "table"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "File to write the result to. The result is returned when omitted"
+                    }
+                },
+                "required": ["file_path", "group_by"]
+            })
+        }
+    }
+
+    fn create_dedupe_tool() -> Tool {
+        Tool {
+            name: "json-dedupe".to_string(),
+            description: "Remove duplicate elements from an array or NDJSON file, comparing whole values or selected key fields, and report how many were removed. Records are streamed, keeping the first occurrence.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "JSON or NDJSON file containing the records"
+                    },
+                    "array_path": {
+                        "type": "string",
+                        "description": "Simple JSONPath to the array in a JSON file (default: '$')",
+                        "default": "$"
+                    },
+                    "key": {
+                        "description": "Field (dotted path) or array of fields that identify duplicates (default: compare whole values, ignoring object key order)",
+                        "oneOf": [
+                            {"type": "string"},
+                            {"type": "array", "items": {"type": "string"}}
+                        ]
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "File to write the unique records to (.ndjson/.jsonl for NDJSON). When omitted the input file is updated in place"
+                    }
+                },
+                "required": ["file_path"]
+            })
+        }
+    }
+
+    async fn handle_join(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let left_file = args.get("left_file")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "left_file is required. Usage example:\n{{\n  \"left_file\": \"./orders.json\",\n  \"right_file\": \"./customers.json\",\n  \"left_key\": \"customer_id\",\n  \"right_key\": \"id\"\n}}"
+            ))?;
+        let right_file = args.get("right_file").and_then(|v| v.as_str()).unwrap_or(left_file);
+        let left_array_path = args.get("left_array_path").and_then(|v| v.as_str()).unwrap_or("$");
+        let right_array_path = args.get("right_array_path").and_then(|v| v.as_str()).unwrap_or("$");
+
+        let left_key = key_fields(args.get("left_key"))
+            .ok_or_else(|| anyhow::anyhow!(
+                "left_key is required: a field name or an array of field names. Usage example:\n{{\n  \"left_file\": \"./orders.json\",\n  \"right_file\": \"./customers.json\",\n  \"left_key\": \"customer_id\",\n  \"right_key\": \"id\"\n}}"
+            ))?;
+        let right_key = key_fields(args.get("right_key")).unwrap_or_else(|| left_key.clone());
+        if left_key.len() != right_key.len() {
+            return Ok(ToolResult::error(format!(
+                "left_key has {} field(s) but right_key has {}; they must pair up",
+                left_key.len(), right_key.len()
+            )));
+        }
+
+        let mode = args.get("mode").and_then(|v| v.as_str()).unwrap_or("inner");
+        let (keep_left, keep_right) = match mode {
+            "inner" => (false, false),
+            "left" => (true, false),
+            "right" => (false, true),
+            "outer" => (true, true),
+            _ => return Ok(ToolResult::error(format!(
+                "Unknown join mode: {}. Use 'inner', 'left', 'right' or 'outer'", mode
+            ))),
+        };
+        let merged = match args.get("output_shape").and_then(|v| v.as_str()).unwrap_or("nested") {
+            "nested" => false,
+            "merged" => true,
+            other => return Ok(ToolResult::error(format!(
+                "Unknown output_shape: {}. Use 'nested' or 'merged'", other
+            ))),
+        };
+
+        // Index the right side in memory, then stream the left side past it
+        let mut right_records = Vec::new();
+        let mut right_index: HashMap<String, Vec<usize>> = HashMap::new();
+        for_each_record(right_file, RecordFormat::detect(right_file, right_array_path)?, right_array_path, |record| {
+            if let Some(key) = record_key(&record, &right_key) {
+                right_index.entry(key).or_default().push(right_records.len());
+            }
+            right_records.push(record);
+            Ok(())
+        })?;
+
+        let mut right_matched = vec![false; right_records.len()];
+        let mut joined = Vec::new();
+        let mut left_count = 0;
+        for_each_record(left_file, RecordFormat::detect(left_file, left_array_path)?, left_array_path, |left| {
+            left_count += 1;
+            let matches = record_key(&left, &left_key)
+                .and_then(|key| right_index.get(&key))
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            if matches.is_empty() && keep_left {
+                joined.push(combine(Some(&left), None, merged)?);
+            }
+            for &i in matches {
+                right_matched[i] = true;
+                joined.push(combine(Some(&left), Some(&right_records[i]), merged)?);
+            }
+            Ok(())
+        })?;
+        if keep_right {
+            for (record, _) in right_records.iter().zip(&right_matched).filter(|(_, matched)| !**matched) {
+                joined.push(combine(None, Some(record), merged)?);
+            }
+        }
+
+        let summary = format!(
+            "{} join of {} left and {} right records produced {} records",
+            mode, left_count, right_records.len(), joined.len()
+        );
+        emit_records(args, joined, &summary)
+    }
+
+    async fn handle_dedupe(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let file_path = args.get("file_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "file_path is required. Usage example:\n{{\n  \"file_path\": \"./events.ndjson\",\n  \"key\": \"id\"\n}}"
+            ))?;
+        let array_path = args.get("array_path").and_then(|v| v.as_str()).unwrap_or("$");
+        let key = key_fields(args.get("key"));
+        let input_format = RecordFormat::detect(file_path, array_path)?;
+
+        let mut seen = HashSet::new();
+        let mut is_new = |record: &Value| match &key {
+            Some(fields) => {
+                let values: Vec<Value> = fields.iter().map(|field| lookup_field(record, field)).collect();
+                seen.insert(canonical_json(&Value::Array(values)))
+            }
+            None => seen.insert(canonical_json(record)),
+        };
+
+        let output_path = args.get("output_path").and_then(|v| v.as_str());
+        let (total, kept) = if array_path.trim() != "$" && output_path.is_none() {
+            // A nested array is rewritten inside its document
+            let mut document = read_document(file_path, DocumentFormat::Json)?;
+            let Some(Value::Array(items)) = value_at_path_mut(&mut document, &parse_simple_path(array_path)?) else {
+                return Ok(ToolResult::error(format!("No array found at '{}' in '{}'", array_path, file_path)));
+            };
+            let total = items.len();
+            items.retain(|item| is_new(item));
+            let kept = items.len();
+            storage::write(file_path, &serialize_for_path(file_path, &document, DocumentFormat::Json, true)?)?;
+            (total, kept)
+        } else {
+            // Stream into the output, or into a temporary file that replaces the input. Other
+            // backends store the output once it is complete, so it replaces the input directly
+            let (mut writer, temp) = match output_path {
+                Some(path) => (RecordWriter::create(Path::new(path), RecordFormat::from_extension(path))?, None),
+                None if !storage::is_local(file_path) => (RecordWriter::create(Path::new(file_path), input_format)?, None),
+                None => {
+                    let (temp, file) = storage::create_temp_sibling(Path::new(file_path), "dedupe")?;
+                    (RecordWriter::from_file(file, input_format)?, Some(temp))
+                }
+            };
+            let counts = for_each_record(file_path, input_format, array_path, |record| {
+                if is_new(&record) {
+                    writer.write(&record)?;
+                }
+                Ok(())
+            }).and_then(|total| Ok((total, writer.finish()?)));
+            match temp {
+                Some(temp) => counts
+                    .and_then(|counts| {
+                        storage::carry_metadata(Path::new(file_path), &temp)?;
+                        fs::rename(&temp, file_path)
+                            .map_err(|e| anyhow::anyhow!("Failed to replace '{}': {}", file_path, e))?;
+                        Ok(counts)
+                    })
+                    .map_err(|e| {
+                        let _ = fs::remove_file(&temp);
+                        e
+                    })?,
+                None => counts?,
+            }
+        };
+
+        Ok(ToolResult::success(format!(
+            "Removed {} duplicate(s) from {} records in '{}' ({} kept{}), comparing {}",
+            total - kept,
+            total,
+            file_path,
+            kept,
+            output_path.map(|path| format!(", written to '{}'", path)).unwrap_or_default(),
+            match &key {
+                Some(fields) => format!("key field(s) {}", fields.join(", ")),
+                None => "whole values".to_string(),
+            }
+        )))
+    }
+
+    async fn handle_group(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let file_path = args.get("file_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "file_path is required. Usage example:\n{{\n  \"file_path\": \"./sales.json\",\n  \"group_by\": \"region\",\n  \"aggregates\": [{{\"op\": \"sum\", \"field\": \"amount\"}}]\n}}"
+            ))?;
+        let group_by = key_fields(args.get("group_by"))
+            .ok_or_else(|| anyhow::anyhow!(
+                "group_by is required: a field name or an array of field names. Usage example:\n{{\n  \"file_path\": \"./sales.json\",\n  \"group_by\": [\"region\", \"year\"]\n}}"
+            ))?;
+        let array_path = args.get("array_path").and_then(|v| v.as_str()).unwrap_or("$");
+        let nested = match args.get("output").and_then(|v| v.as_str()).unwrap_or("table") {
+            "table" => false,
+            "nested" => true,
+            other => return Ok(ToolResult::error(format!("Unknown output: {}. Use 'table' or 'nested'", other))),
+        };
+
+        let aggregates = match args.get("aggregates") {
+            None => vec![Aggregate { op: AggregateOp::Count, field: None, name: "count".to_string() }],
+            Some(Value::Array(specs)) => match specs.iter().map(Aggregate::parse).collect::<anyhow::Result<Vec<_>>>() {
+                Ok(aggregates) => aggregates,
+                Err(e) => return Ok(ToolResult::error(format!("Invalid aggregate: {}", e))),
+            },
+            Some(other) => return Ok(ToolResult::error(format!("aggregates must be an array, got: {}", other))),
+        };
+
+        // Groups keep the order in which their first record was seen
+        let mut groups: Vec<(Vec<Value>, Vec<Accumulator>)> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        let records = for_each_record(file_path, RecordFormat::detect(file_path, array_path)?, array_path, |record| {
+            let key: Vec<Value> = group_by.iter().map(|field| lookup_field(&record, field)).collect();
+            let position = *positions.entry(serde_json::to_string(&key)?).or_insert_with(|| {
+                groups.push((key, aggregates.iter().map(|_| Accumulator::default()).collect()));
+                groups.len() - 1
+            });
+            for (aggregate, accumulator) in aggregates.iter().zip(&mut groups[position].1) {
+                accumulator.add(aggregate.field.as_ref().map(|field| lookup_field(&record, field)));
+            }
+            Ok(())
+        })?;
+
+        let summary = format!("Grouped {} records from '{}' into {} groups", records, file_path, groups.len());
+        let rows = groups.into_iter().map(|(key, accumulators)| {
+            let values: Map<String, Value> = aggregates.iter().zip(accumulators)
+                .map(|(aggregate, accumulator)| (aggregate.name.clone(), accumulator.finish(aggregate.op)))
+                .collect();
+            (key, values)
+        });
+
+        if !nested {
+            let table = rows.map(|(key, values)| {
+                let mut row: Map<String, Value> = group_by.iter().cloned().zip(key).collect();
+                row.extend(values);
+                Value::Object(row)
+            }).collect();
+            return emit_records(args, table, &summary);
+        }
+
+        let mut tree = Map::new();
+        for (key, values) in rows {
+            let mut level = &mut tree;
+            for part in &key[..key.len() - 1] {
+                level = level.entry(group_label(part))
+                    .or_insert_with(|| Value::Object(Map::new()))
+                    .as_object_mut()
+                    .expect("group levels are always objects");
+            }
+            level.insert(group_label(&key[key.len() - 1]), Value::Object(values));
+        }
+        let output = serde_json::to_string_pretty(&tree)?;
+        match args.get("output_path").and_then(|v| v.as_str()) {
+            Some(output_path) => {
+                storage::create_parent_dirs(output_path)?;
+                storage::write(output_path, output.as_bytes())?;
+                Ok(ToolResult::success(format!("{} and wrote them to '{}'", summary, output_path)))
+            }
+            None => Ok(ToolResult::success(format!("{}:\n\n{}", summary, output))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AggregateOp {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+#[derive(Debug)]
+struct Aggregate {
+    op: AggregateOp,
+    field: Option<String>,
+    name: String,
+}
+
+impl Aggregate {
+    fn parse(spec: &Value) -> anyhow::Result<Self> {
+        let op = match spec.get("op").and_then(|v| v.as_str()) {
+            Some("count") => AggregateOp::Count,
+            Some("sum") => AggregateOp::Sum,
+            Some("avg") => AggregateOp::Avg,
+            Some("min") => AggregateOp::Min,
+            Some("max") => AggregateOp::Max,
+            _ => anyhow::bail!("'op' must be one of count, sum, avg, min, max in {}", spec),
+        };
+        let field = spec.get("field").and_then(|v| v.as_str()).map(str::to_string);
+        if field.is_none() && op != AggregateOp::Count {
+            anyhow::bail!("'field' is required for every aggregate except count in {}", spec);
+        }
+        let name = match (spec.get("as").and_then(|v| v.as_str()), &field) {
+            (Some(name), _) => name.to_string(),
+            (None, Some(field)) if op != AggregateOp::Count => {
+                format!("{}_{}", spec["op"].as_str().unwrap_or_default(), field.replace('.', "_"))
+            }
+            (None, Some(field)) => format!("count_{}", field.replace('.', "_")),
+            (None, None) => "count".to_string(),
+        };
+        Ok(Self { op, field, name })
+    }
+}
+
+/// Running state for one aggregate of one group. `count` with a field counts non-null
+/// values; sum and avg only consider numbers.
+#[derive(Debug, Default)]
+struct Accumulator {
+    count: usize,
+    numbers: usize,
+    sum: f64,
+    min: Option<Value>,
+    max: Option<Value>,
+}
+
+impl Accumulator {
+    fn add(&mut self, value: Option<Value>) {
+        let value = match value {
+            None => {
+                self.count += 1;
+                return;
+            }
+            Some(Value::Null) => return,
+            Some(value) => value,
+        };
+        self.count += 1;
+        if let Some(n) = value.as_f64() {
+            self.numbers += 1;
+            self.sum += n;
+        }
+        if self.min.as_ref().map_or(true, |min| compare_values(&value, min).is_lt()) {
+            self.min = Some(value.clone());
+        }
+        if self.max.as_ref().map_or(true, |max| compare_values(&value, max).is_gt()) {
+            self.max = Some(value);
+        }
+    }
+
+    fn finish(self, op: AggregateOp) -> Value {
+        match op {
+            AggregateOp::Count => json!(self.count),
+            AggregateOp::Sum => number(self.sum),
+            AggregateOp::Avg if self.numbers == 0 => Value::Null,
+            AggregateOp::Avg => number(self.sum / self.numbers as f64),
+            AggregateOp::Min => self.min.unwrap_or(Value::Null),
+            AggregateOp::Max => self.max.unwrap_or(Value::Null),
+        }
+    }
+}
+
+/// Whole floats are emitted as integers so sums of integer fields stay integers.
+fn number(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+        json!(n as i64)
+    } else {
+        json!(n)
+    }
+}
+
+fn group_label(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Accept a single field name or an array of field names.
+fn key_fields(value: Option<&Value>) -> Option<Vec<String>> {
+    match value? {
+        Value::String(field) => Some(vec![field.clone()]),
+        Value::Array(fields) if !fields.is_empty() => fields.iter()
+            .map(|f| f.as_str().map(str::to_string))
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Serialized key values of a record, or `None` when any key is missing or null
+/// (null keys never match, as in SQL).
+fn record_key(record: &Value, fields: &[String]) -> Option<String> {
+    let values: Vec<Value> = fields.iter().map(|field| lookup_field(record, field)).collect();
+    if values.iter().any(Value::is_null) {
+        return None;
+    }
+    serde_json::to_string(&values).ok()
+}
+
+fn value_at_path_mut<'a>(value: &'a mut Value, segments: &[PathSegment]) -> Option<&'a mut Value> {
+    segments.iter().try_fold(value, |current, segment| match segment {
+        PathSegment::Key(key) => current.get_mut(key.as_str()),
+        PathSegment::Index(index) => current.get_mut(*index),
+    })
+}
+
+fn combine(left: Option<&Value>, right: Option<&Value>, merged: bool) -> anyhow::Result<Value> {
+    if !merged {
+        return Ok(json!({
+            "left": left.cloned().unwrap_or(Value::Null),
+            "right": right.cloned().unwrap_or(Value::Null),
+        }));
+    }
+
+    let as_object = |record: Option<&Value>| -> anyhow::Result<Map<String, Value>> {
+        match record {
+            None => Ok(Map::new()),
+            Some(Value::Object(obj)) => Ok(obj.clone()),
+            Some(other) => anyhow::bail!("output_shape 'merged' requires object records, found: {}", other),
+        }
+    };
+    let mut combined = as_object(left)?;
+    for (key, value) in as_object(right)? {
+        match combined.get(&key) {
+            Some(existing) if *existing != value => {
+                combined.insert(format!("right_{}", key), value);
+            }
+            Some(_) => {}
+            None => {
+                combined.insert(key, value);
+            }
+        }
+    }
+    Ok(Value::Object(combined))
+}
+
+/// Write records to the optional `output_path` (NDJSON for `.ndjson`/`.jsonl`) or return
+/// them inline after the summary.
+fn emit_records(args: &HashMap<String, Value>, records: Vec<Value>, summary: &str) -> anyhow::Result<ToolResult> {
+    match args.get("output_path").and_then(|v| v.as_str()) {
+        Some(output_path) => {
+            let mut writer = RecordWriter::create(Path::new(output_path), RecordFormat::from_extension(output_path))?;
+            for record in &records {
+                writer.write(record)?;
+            }
+            writer.finish()?;
+            Ok(ToolResult::success(format!("{} and wrote them to '{}'", summary, output_path)))
+        }
+        None => Ok(ToolResult::success(format!(
+            "{}:\n\n{}",
+            summary,
+            serde_json::to_string_pretty(&records)?
+        ))),
+    }
+}
+
+#[async_trait]
+impl ToolHandler for JsonRelational {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_join_tool(), Self::create_group_tool(), Self::create_dedupe_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-join" => self.handle_join(&tool_call.arguments).await,
+            "json-group" => self.handle_group(&tool_call.arguments).await,
+            "json-dedupe" => self.handle_dedupe(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
@@ -0,0 +1,352 @@
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Fuzzy key matches need at least this Jaro-Winkler similarity (on lowercase keys).
+const FUZZY_THRESHOLD: f64 = 0.85;
+
+/// How key segments of a JSONPath are matched against the keys in a document.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyMatch {
+    Exact,
+    CaseInsensitive,
+    Fuzzy,
+}
+
+impl KeyMatch {
+    /// Read the optional `key_match` argument (default: exact).
+    pub fn from_args(args: &HashMap<String, Value>) -> anyhow::Result<Self> {
+        match args.get("key_match").and_then(|v| v.as_str()) {
+            None | Some("exact") => Ok(Self::Exact),
+            Some("case_insensitive") => Ok(Self::CaseInsensitive),
+            Some("fuzzy") => Ok(Self::Fuzzy),
+            Some(other) => anyhow::bail!("Unknown key_match '{}'. Use 'exact', 'case_insensitive' or 'fuzzy'", other),
+        }
+    }
+
+    /// The key of `obj` that `wanted` refers to: an exact match first, then (depending
+    /// on the mode) a case-insensitive one, then the most similar key.
+    fn find<'a>(self, obj: &'a serde_json::Map<String, Value>, wanted: &str) -> Option<&'a str> {
+        if let Some((key, _)) = obj.get_key_value(wanted) {
+            return Some(key);
+        }
+        if self == Self::Exact {
+            return None;
+        }
+        let lower = wanted.to_lowercase();
+        if let Some(key) = obj.keys().find(|key| key.to_lowercase() == lower) {
+            return Some(key);
+        }
+        if self == Self::Fuzzy {
+            return obj.keys()
+                .map(|key| (strsim::jaro_winkler(&lower, &key.to_lowercase()), key))
+                .filter(|(score, _)| *score >= FUZZY_THRESHOLD)
+                .max_by(|a, b| a.0.total_cmp(&b.0))
+                .map(|(_, key)| key.as_str());
+        }
+        None
+    }
+}
+
+/// JSON Schema for the `key_match` argument.
+pub fn key_match_schema() -> Value {
+    json!({
+        "type": "string",
+        "enum": ["exact", "case_insensitive", "fuzzy"],
+        "description": "How object keys in the query are matched: 'case_insensitive' resolves $.Users to $.users, 'fuzzy' also tolerates small typos ($.usres). The response reports the keys actually used (default: exact)"
+    })
+}
+
+/// One step of a JSONPath, as far as key resolution is concerned.
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(i64),
+    Wildcard,
+}
+
+/// Split `query` into leading key / index / wildcard segments (with their source text)
+/// and the remainder starting at the first segment that is anything else, such as a
+/// filter, slice, union or recursive descent.
+fn split_query(query: &str) -> (Vec<(Segment, String)>, String) {
+    let query = query.trim();
+    let Some(rest) = query.strip_prefix('$') else {
+        return (Vec::new(), query.to_string());
+    };
+    let chars: Vec<char> = rest.chars().collect();
+    let mut segments = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let segment = match chars[i] {
+            '.' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                Some(Segment::Wildcard)
+            }
+            '.' if chars.get(i + 1).is_some_and(|&c| c != '.') => {
+                i += 1;
+                while i < chars.len() && chars[i] != '.' && chars[i] != '[' {
+                    i += 1;
+                }
+                Some(Segment::Key(chars[start + 1..i].iter().collect()))
+            }
+            '[' => bracket_end(&chars, i).and_then(|end| {
+                let inner: String = chars[i + 1..end].iter().collect();
+                let inner = inner.trim();
+                let segment = if inner == "*" {
+                    Some(Segment::Wildcard)
+                } else if let Ok(index) = inner.parse::<i64>() {
+                    Some(Segment::Index(index))
+                } else {
+                    quoted_key(inner).map(Segment::Key)
+                };
+                i = end + 1;
+                segment
+            }),
+            _ => None,
+        };
+        match segment {
+            Some(segment) => segments.push((segment, chars[start..i].iter().collect())),
+            None => return (segments, chars[start..].iter().collect()),
+        }
+    }
+    (segments, String::new())
+}
+
+/// Position of the `]` closing the bracket at `open`, skipping quoted text.
+fn bracket_end(chars: &[char], open: usize) -> Option<usize> {
+    let mut quote = None;
+    for (offset, &c) in chars[open + 1..].iter().enumerate() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, ']') => return Some(open + 1 + offset),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// `'name'` or `"name"` with no other content (unions and filters are not keys).
+fn quoted_key(inner: &str) -> Option<String> {
+    let quote = inner.chars().next().filter(|c| *c == '\'' || *c == '"')?;
+    let body = inner.strip_prefix(quote)?.strip_suffix(quote)?;
+    (!body.contains(quote)).then(|| body.to_string())
+}
+
+/// JSONPath text selecting `key` as a child.
+fn key_segment(key: &str) -> String {
+    if !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        format!(".{}", key)
+    } else {
+        format!("['{}']", key.replace('\'', "\\'"))
+    }
+}
+
+/// Apply one segment to every node, returning the children it selects.
+fn step<'a>(nodes: &[&'a Value], segment: &Segment) -> Vec<&'a Value> {
+    nodes.iter()
+        .flat_map(|node| -> Vec<&'a Value> {
+            match (segment, node) {
+                (Segment::Key(key), Value::Object(obj)) => obj.get(key).into_iter().collect(),
+                (Segment::Index(index), Value::Array(items)) => {
+                    let position = if *index < 0 { items.len() as i64 + index } else { *index };
+                    usize::try_from(position).ok().and_then(|p| items.get(p)).into_iter().collect()
+                }
+                (Segment::Wildcard, Value::Array(items)) => items.iter().collect(),
+                (Segment::Wildcard, Value::Object(obj)) => obj.values().collect(),
+                _ => Vec::new(),
+            }
+        })
+        .collect()
+}
+
+/// A key in the query that was replaced by the key present in the document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyCorrection {
+    pub requested: String,
+    pub actual: String,
+}
+
+/// Rewrite the key segments of `query` to the keys that exist in `document` according
+/// to `mode`, returning the rewritten query and the keys that were changed. Segments
+/// after the first filter, slice or recursive descent are left as written.
+pub fn resolve_keys(document: &Value, query: &str, mode: KeyMatch) -> (String, Vec<KeyCorrection>) {
+    if !query.trim().starts_with('$') {
+        return (query.to_string(), Vec::new());
+    }
+    let (segments, remainder) = split_query(query);
+    let mut nodes = vec![document];
+    let mut rewritten = String::from("$");
+    let mut corrections = Vec::new();
+
+    for (segment, text) in segments {
+        let Segment::Key(wanted) = &segment else {
+            nodes = step(&nodes, &segment);
+            rewritten.push_str(&text);
+            continue;
+        };
+        let actual = nodes.iter()
+            .filter_map(|node| node.as_object())
+            .find_map(|obj| mode.find(obj, wanted))
+            .unwrap_or(wanted)
+            .to_string();
+        if &actual == wanted {
+            rewritten.push_str(&text);
+        } else {
+            rewritten.push_str(&key_segment(&actual));
+            corrections.push(KeyCorrection { requested: wanted.clone(), actual: actual.clone() });
+        }
+        nodes = step(&nodes, &Segment::Key(actual));
+    }
+    rewritten.push_str(&remainder);
+    (rewritten, corrections)
+}
+
+/// The first key, index or wildcard segment of a query that selects nothing.
+struct Failure<'a> {
+    prefix: String,
+    segment: Segment,
+    text: String,
+    nodes: Vec<&'a Value>,
+    suggestions: Vec<String>,
+}
+
+/// Walk the leading segments of `query` and describe the first one that selects nothing,
+/// with candidate replacements: a similarly named key, or `[*]` to reach into arrays.
+fn first_failure<'a>(document: &'a Value, query: &str) -> Option<Failure<'a>> {
+    let (segments, remainder) = split_query(query);
+    let mut nodes = vec![document];
+    let mut prefix = String::from("$");
+
+    for (position, (segment, text)) in segments.iter().enumerate() {
+        let next = step(&nodes, segment);
+        if !next.is_empty() {
+            prefix.push_str(text);
+            nodes = next;
+            continue;
+        }
+
+        let rest: String = segments[position + 1..].iter().map(|(_, text)| text.as_str()).chain([remainder.as_str()]).collect();
+        let mut suggestions = Vec::new();
+        if let Segment::Key(wanted) = segment {
+            let similar = nodes.iter()
+                .filter_map(|node| node.as_object())
+                .find_map(|obj| KeyMatch::Fuzzy.find(obj, wanted));
+            if let Some(actual) = similar {
+                suggestions.push(format!("{}{}{}", prefix, key_segment(actual), rest));
+            }
+            // `$.users.name` where users is an array of objects
+            let in_elements = nodes.iter()
+                .filter_map(|node| node.as_array())
+                .flatten()
+                .filter_map(|item| item.as_object())
+                .find_map(|obj| KeyMatch::Fuzzy.find(obj, wanted));
+            if let Some(actual) = in_elements {
+                suggestions.push(format!("{}[*]{}{}", prefix, key_segment(actual), rest));
+            }
+        }
+        return Some(Failure { prefix, segment: segment.clone(), text: text.clone(), nodes, suggestions });
+    }
+    None
+}
+
+/// Explain why `query` selected nothing in `document`: the longest prefix that still
+/// matched, what is available there, and candidate queries that might have been meant.
+/// Candidates are complete as far as keys and indices go, but filters are not checked.
+pub fn diagnose_empty(document: &Value, query: &str) -> Value {
+    if !query.trim().starts_with('$') {
+        return json!({"reason": "JSONPath queries must start with '$'", "suggestions": [format!("$.{}", query.trim())]});
+    }
+    let Some(failure) = first_failure(document, query) else {
+        let (segments, remainder) = split_query(query);
+        let prefix: String = std::iter::once("$").chain(segments.iter().map(|(_, text)| text.as_str())).collect();
+        let nodes = segments.iter().fold(vec![document], |nodes, (segment, _)| step(&nodes, segment));
+        return json!({
+            "reason": format!("'{}' matched {} value(s), but '{}' selected nothing from them", prefix, nodes.len(), remainder),
+            "matched_prefix": prefix,
+            "failed_segment": remainder,
+            "available": describe_nodes(&nodes),
+            "suggestions": [],
+        });
+    };
+
+    // A correction can expose the next mistake; follow a few rounds of fixes
+    let mut suggestions = Vec::new();
+    let mut pending = failure.suggestions.clone();
+    for _ in 0..4 {
+        let mut next = Vec::new();
+        for candidate in pending {
+            match first_failure(document, &candidate) {
+                None => suggestions.push(candidate),
+                Some(later) => next.extend(later.suggestions),
+            }
+        }
+        pending = next;
+    }
+
+    let reason = match &failure.segment {
+        Segment::Key(wanted) => format!("no key '{}' at {}", wanted, failure.prefix),
+        Segment::Index(index) => format!("index {} is not available at {}", index, failure.prefix),
+        Segment::Wildcard => format!("{} has no children to select with '{}'", failure.prefix, failure.text),
+    };
+    json!({
+        "reason": reason,
+        "matched_prefix": failure.prefix,
+        "failed_segment": failure.text,
+        "available": describe_nodes(&failure.nodes),
+        "suggestions": suggestions,
+    })
+}
+
+/// Summarize the values a query prefix selected: their types, keys and array sizes.
+fn describe_nodes(nodes: &[&Value]) -> Value {
+    const MAX_KEYS: usize = 50;
+    fn add<'a>(list: &mut Vec<&'a str>, key: &'a str) {
+        if list.len() < MAX_KEYS && !list.contains(&key) {
+            list.push(key);
+        }
+    }
+    let mut types: Vec<&str> = Vec::new();
+    let mut keys: Vec<&str> = Vec::new();
+    let mut element_keys: Vec<&str> = Vec::new();
+    let mut array_lengths = Vec::new();
+    for node in nodes {
+        let type_name = match node {
+            Value::Null => "null",
+            Value::Bool(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        };
+        if !types.contains(&type_name) {
+            types.push(type_name);
+        }
+        match node {
+            Value::Object(obj) => obj.keys().for_each(|key| add(&mut keys, key.as_str())),
+            Value::Array(items) => {
+                if array_lengths.len() < 5 {
+                    array_lengths.push(items.len());
+                }
+                items.iter()
+                    .filter_map(|item| item.as_object())
+                    .take(100)
+                    .for_each(|obj| obj.keys().for_each(|key| add(&mut element_keys, key.as_str())));
+            }
+            _ => {}
+        }
+    }
+
+    let mut summary = json!({"matched_values": nodes.len(), "types": types});
+    if !keys.is_empty() {
+        summary["keys"] = json!(keys);
+    }
+    if !array_lengths.is_empty() {
+        summary["array_lengths"] = json!(array_lengths);
+    }
+    if !element_keys.is_empty() {
+        summary["element_keys"] = json!(element_keys);
+    }
+    summary
+}
@@ -0,0 +1,128 @@
+use crate::json_tools::help::{tool_section, HELP_TOPICS};
+use crate::mcp::protocol::{Resource, ResourceContents, Tool};
+use serde_json::{json, Value};
+
+/// A runnable example: a complete tools/call `params` payload for a common task.
+struct Example {
+    name: &'static str,
+    description: &'static str,
+    tool: &'static str,
+    arguments: fn() -> Value,
+}
+
+const EXAMPLES: &[Example] = &[
+    Example {
+        name: "read-paged",
+        description: "Read a large array one page at a time",
+        tool: "json-read",
+        arguments: || json!({"file_path": "./large-dataset.json", "array_path": "$.records", "page": 1, "page_size": 100}),
+    },
+    Example {
+        name: "query-filter",
+        description: "Select the names of users older than 25",
+        tool: "json-query",
+        arguments: || json!({"file_path": "./data.json", "query": "$.users[?(@.age > 25)].name"}),
+    },
+    Example {
+        name: "merge-write",
+        description: "Set top-level keys of an existing object, keeping the other keys",
+        tool: "json-write",
+        arguments: || json!({"file_path": "./config.json", "data": {"theme": "dark", "enabled": true}, "mode": "merge"}),
+    },
+    Example {
+        name: "append-write",
+        description: "Append records to the array in a file",
+        tool: "json-write",
+        arguments: || json!({"file_path": "./items.json", "data": [{"id": 123, "name": "New Item"}], "mode": "append"}),
+    },
+    Example {
+        name: "validate-schema",
+        description: "Validate a file against an inline JSON schema",
+        tool: "json-validate",
+        arguments: || json!({
+            "file_path": "./user.json",
+            "schema": {"type": "object", "required": ["name"], "properties": {"name": {"type": "string"}}}
+        }),
+    },
+    Example {
+        name: "extract-ndjson",
+        description: "Write query results to an NDJSON file instead of returning them",
+        tool: "json-extract",
+        arguments: || json!({"file_path": "./data.json", "query": "$.users[*]", "output_path": "./users.ndjson", "output_format": "ndjson"}),
+    },
+    Example {
+        name: "split-shards",
+        description: "Split an NDJSON file into shards of at most 50000 records",
+        tool: "json-split",
+        arguments: || json!({"file_path": "./events.ndjson", "max_records": 50000, "output_dir": "./shards"}),
+    },
+    Example {
+        name: "sql-select",
+        description: "Filter, sort and project records with SQL",
+        tool: "json-sql",
+        arguments: || json!({"file_path": "./users.json", "query": "SELECT name, age FROM $.users WHERE age > 25 ORDER BY name LIMIT 10"}),
+    },
+    Example {
+        name: "check-config",
+        description: "Assert a value in a file before relying on it",
+        tool: "json-check",
+        arguments: || json!({"file_path": "./config.json", "checks": [{"path": "$.version", "op": "equals", "value": 2}]}),
+    },
+];
+
+/// The resources offered alongside `tools`: every help topic (`help://reading`), the help
+/// section of each documented tool (`help://json-read`) and the runnable examples
+/// (`example://merge-write`) whose tool is offered.
+pub fn list(tools: &[Tool], help_topic: fn(&str) -> Option<&'static str>) -> Vec<Resource> {
+    let tools_topic = help_topic("tools").unwrap_or_default();
+    let topics = HELP_TOPICS.iter().map(|topic| Resource {
+        uri: format!("help://{}", topic),
+        name: format!("json-help: {}", topic),
+        description: Some(format!("The '{}' json-help topic", topic)),
+        mime_type: "text/markdown".to_string(),
+    });
+    let tool_help = tools.iter()
+        .filter(|tool| tool_section(tools_topic, &tool.name).is_some())
+        .map(|tool| Resource {
+            uri: format!("help://{}", tool.name),
+            name: format!("{} help", tool.name),
+            description: Some(tool.description.clone()),
+            mime_type: "text/markdown".to_string(),
+        });
+    let examples = EXAMPLES.iter()
+        .filter(|example| tools.iter().any(|tool| tool.name == example.tool))
+        .map(|example| Resource {
+            uri: format!("example://{}", example.name),
+            name: format!("{} example", example.tool),
+            description: Some(example.description.to_string()),
+            mime_type: "application/json".to_string(),
+        });
+
+    topics.chain(tool_help).chain(examples).collect()
+}
+
+/// The contents of one resource from `list`, or `None` for an unknown URI.
+pub fn read(tools: &[Tool], help_topic: fn(&str) -> Option<&'static str>, uri: &str) -> Option<ResourceContents> {
+    let (mime_type, text) = if let Some(name) = uri.strip_prefix("help://") {
+        let text = if HELP_TOPICS.contains(&name) {
+            help_topic(name)?.to_string()
+        } else {
+            tools.iter().find(|tool| tool.name == name)?;
+            tool_section(help_topic("tools").unwrap_or_default(), name)?
+        };
+        ("text/markdown", text)
+    } else if let Some(name) = uri.strip_prefix("example://") {
+        let example = EXAMPLES.iter().find(|example| example.name == name)?;
+        tools.iter().find(|tool| tool.name == example.tool)?;
+        let payload = json!({"name": example.tool, "arguments": (example.arguments)()});
+        ("application/json", serde_json::to_string_pretty(&payload).ok()?)
+    } else {
+        return None;
+    };
+
+    Some(ResourceContents {
+        uri: uri.to_string(),
+        mime_type: mime_type.to_string(),
+        text,
+    })
+}
@@ -0,0 +1,921 @@
+//! A JSONPath evaluator that follows RFC 9535 to the letter: strict well-typedness for
+//! filter functions, I-Regexp semantics for `match`/`search`, and the standard comparison
+//! rules (a missing value only equals another missing value, numbers compare by value).
+
+use regex::Regex;
+use serde_json::{json, Value};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Largest integer the RFC allows in index and slice selectors (2^53 - 1).
+const MAX_INDEX: i64 = 9_007_199_254_740_991;
+
+/// A parse or well-typedness error, with the 1-based column where it was detected.
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at column {}: {}", self.column, self.message)
+    }
+}
+
+/// A parsed RFC 9535 query.
+#[derive(Debug)]
+pub struct Rfc9535Query {
+    query: Query,
+}
+
+impl Rfc9535Query {
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser { text, pos: 0 };
+        if parser.peek() != Some('$') {
+            return parser.error("a query must start with '$'");
+        }
+        let query = parser.query()?;
+        if parser.pos < text.len() {
+            return parser.error("unexpected trailing characters");
+        }
+        Ok(Self { query })
+    }
+
+    /// The nodes selected from `document`, in the order the RFC specifies.
+    pub fn select<'a>(&self, document: &'a Value) -> Vec<&'a Value> {
+        select(&self.query, document, document)
+    }
+
+    /// The selected nodes with their normalized paths (e.g. `$['users'][0]`).
+    pub fn select_with_paths<'a>(&self, document: &'a Value) -> Vec<(String, &'a Value)> {
+        select_located(&self.query, document, document, "$".to_string())
+    }
+}
+
+#[derive(Debug)]
+struct Query {
+    absolute: bool,
+    segments: Vec<Segment>,
+}
+
+impl Query {
+    /// Whether the query can select at most one node (name and index selectors only).
+    fn is_singular(&self) -> bool {
+        self.segments.iter().all(|segment| {
+            !segment.descendant
+                && segment.selectors.len() == 1
+                && matches!(segment.selectors[0], Selector::Name(_) | Selector::Index(_))
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Segment {
+    descendant: bool,
+    selectors: Vec<Selector>,
+}
+
+#[derive(Debug)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    Filter(Box<Logical>),
+}
+
+#[derive(Debug)]
+enum Logical {
+    Or(Vec<Logical>),
+    And(Vec<Logical>),
+    Not(Box<Logical>),
+    Compare(Operand, CompareOp, Operand),
+    Exists(Query),
+    Test(Function),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug)]
+enum Operand {
+    Literal(Value),
+    Query(Query),
+    Function(Function),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FunctionType {
+    Value,
+    Logical,
+    Nodes,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum FunctionName {
+    Length,
+    Count,
+    Match,
+    Search,
+    Value,
+}
+
+impl FunctionName {
+    fn lookup(name: &str) -> Option<Self> {
+        Some(match name {
+            "length" => Self::Length,
+            "count" => Self::Count,
+            "match" => Self::Match,
+            "search" => Self::Search,
+            "value" => Self::Value,
+            _ => return None,
+        })
+    }
+
+    fn parameters(self) -> &'static [FunctionType] {
+        match self {
+            Self::Length => &[FunctionType::Value],
+            Self::Count | Self::Value => &[FunctionType::Nodes],
+            Self::Match | Self::Search => &[FunctionType::Value, FunctionType::Value],
+        }
+    }
+
+    fn result(self) -> FunctionType {
+        match self {
+            Self::Length | Self::Count | Self::Value => FunctionType::Value,
+            Self::Match | Self::Search => FunctionType::Logical,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Function {
+    name: FunctionName,
+    args: Vec<Operand>,
+    /// Pattern compiled at parse time when match/search is given a string literal.
+    pattern: Option<Option<Regex>>,
+}
+
+struct Parser<'s> {
+    text: &'s str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn peek_second(&self) -> Option<char> {
+        self.text[self.pos..].chars().nth(1)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let matched = self.text[self.pos..].starts_with(token);
+        if matched {
+            self.pos += token.len();
+        }
+        matched
+    }
+
+    fn skip_blank(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, ParseError> {
+        Err(ParseError { column: self.text[..self.pos].chars().count() + 1, message: message.into() })
+    }
+
+    /// `$` or `@` followed by any number of segments.
+    fn query(&mut self) -> Result<Query, ParseError> {
+        let absolute = match self.bump() {
+            Some('$') => true,
+            Some('@') => false,
+            _ => return self.error("expected '$' or '@'"),
+        };
+        let mut segments = Vec::new();
+        loop {
+            let before = self.pos;
+            self.skip_blank();
+            match self.peek() {
+                Some('.' | '[') => segments.push(self.segment()?),
+                _ => {
+                    self.pos = before;
+                    break;
+                }
+            }
+        }
+        Ok(Query { absolute, segments })
+    }
+
+    fn segment(&mut self) -> Result<Segment, ParseError> {
+        if self.eat("..") {
+            let selectors = match self.peek() {
+                Some('[') => self.bracketed()?,
+                Some('*') => {
+                    self.pos += 1;
+                    vec![Selector::Wildcard]
+                }
+                _ => vec![Selector::Name(self.shorthand_name()?)],
+            };
+            return Ok(Segment { descendant: true, selectors });
+        }
+        let selectors = if self.eat(".") {
+            if self.eat("*") {
+                vec![Selector::Wildcard]
+            } else {
+                vec![Selector::Name(self.shorthand_name()?)]
+            }
+        } else {
+            self.bracketed()?
+        };
+        Ok(Segment { descendant: false, selectors })
+    }
+
+    fn shorthand_name(&mut self) -> Result<String, ParseError> {
+        let start = self.pos;
+        match self.peek() {
+            Some(c) if c.is_ascii_alphabetic() || c == '_' || !c.is_ascii() => {}
+            _ => return self.error("expected a member name, '*' or '['"),
+        }
+        while let Some(c) = self.peek() {
+            if !(c.is_ascii_alphanumeric() || c == '_' || !c.is_ascii()) {
+                break;
+            }
+            self.pos += c.len_utf8();
+        }
+        Ok(self.text[start..self.pos].to_string())
+    }
+
+    fn bracketed(&mut self) -> Result<Vec<Selector>, ParseError> {
+        if !self.eat("[") {
+            return self.error("expected '['");
+        }
+        let mut selectors = Vec::new();
+        loop {
+            self.skip_blank();
+            selectors.push(self.selector()?);
+            self.skip_blank();
+            if self.eat(",") {
+                continue;
+            }
+            if self.eat("]") {
+                return Ok(selectors);
+            }
+            return self.error("expected ',' or ']'");
+        }
+    }
+
+    fn selector(&mut self) -> Result<Selector, ParseError> {
+        match self.peek() {
+            Some('\'' | '"') => Ok(Selector::Name(self.string_literal()?)),
+            Some('*') => {
+                self.pos += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some('?') => {
+                self.pos += 1;
+                self.skip_blank();
+                Ok(Selector::Filter(Box::new(self.logical_or()?)))
+            }
+            Some(c) if c == '-' || c == ':' || c.is_ascii_digit() => self.index_or_slice(),
+            _ => self.error("expected a selector (name, '*', index, slice or '?' filter)"),
+        }
+    }
+
+    fn index_or_slice(&mut self) -> Result<Selector, ParseError> {
+        let start = self.optional_integer()?;
+        self.skip_blank();
+        if !self.eat(":") {
+            return match start {
+                Some(index) => Ok(Selector::Index(index)),
+                None => self.error("expected an index"),
+            };
+        }
+        self.skip_blank();
+        let end = self.optional_integer()?;
+        self.skip_blank();
+        let step = if self.eat(":") {
+            self.skip_blank();
+            self.optional_integer()?
+        } else {
+            None
+        };
+        Ok(Selector::Slice(start, end, step))
+    }
+
+    fn optional_integer(&mut self) -> Result<Option<i64>, ParseError> {
+        match self.peek() {
+            Some(c) if c == '-' || c.is_ascii_digit() => self.integer().map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn integer(&mut self) -> Result<i64, ParseError> {
+        let start = self.pos;
+        let negative = self.eat("-");
+        let digits_start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let digits = &self.text[digits_start..self.pos];
+        if digits.is_empty() {
+            return self.error("expected digits");
+        }
+        if digits.len() > 1 && digits.starts_with('0') || negative && digits == "0" {
+            self.pos = start;
+            return self.error("integers must not have leading zeros or be '-0'");
+        }
+        match self.text[start..self.pos].parse::<i64>() {
+            Ok(value) if value.unsigned_abs() <= MAX_INDEX as u64 => Ok(value),
+            _ => {
+                self.pos = start;
+                self.error("integer is outside the interoperable range of +/-(2^53 - 1)")
+            }
+        }
+    }
+
+    fn string_literal(&mut self) -> Result<String, ParseError> {
+        let quote = self.bump().unwrap_or('"');
+        let mut value = String::new();
+        loop {
+            match self.bump() {
+                None => return self.error("unterminated string literal"),
+                Some(c) if c == quote => return Ok(value),
+                Some('\\') => {
+                    let escaped = match self.bump() {
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('/') => '/',
+                        Some('\\') => '\\',
+                        Some(c) if c == quote => c,
+                        Some('u') => self.unicode_escape()?,
+                        _ => return self.error("invalid escape sequence"),
+                    };
+                    value.push(escaped);
+                }
+                Some(c) if (c as u32) < 0x20 => return self.error("control characters must be escaped in string literals"),
+                Some(c) => value.push(c),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, ParseError> {
+        let hex = self.text.get(self.pos..self.pos + 4).filter(|h| h.chars().all(|c| c.is_ascii_hexdigit()));
+        match hex {
+            Some(hex) => {
+                self.pos += 4;
+                Ok(u32::from_str_radix(hex, 16).unwrap_or_default())
+            }
+            None => self.error("expected four hex digits after '\\u'"),
+        }
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, ParseError> {
+        let high = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.eat("\\u") {
+                return self.error("unpaired surrogate in '\\u' escape");
+            }
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return self.error("unpaired surrogate in '\\u' escape");
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        match char::from_u32(code) {
+            Some(c) => Ok(c),
+            None => self.error("unpaired surrogate in '\\u' escape"),
+        }
+    }
+
+    fn logical_or(&mut self) -> Result<Logical, ParseError> {
+        let mut terms = vec![self.logical_and()?];
+        loop {
+            let before = self.pos;
+            self.skip_blank();
+            if !self.eat("||") {
+                self.pos = before;
+                break;
+            }
+            self.skip_blank();
+            terms.push(self.logical_and()?);
+        }
+        Ok(if terms.len() == 1 { terms.remove(0) } else { Logical::Or(terms) })
+    }
+
+    fn logical_and(&mut self) -> Result<Logical, ParseError> {
+        let mut terms = vec![self.basic()?];
+        loop {
+            let before = self.pos;
+            self.skip_blank();
+            if !self.eat("&&") {
+                self.pos = before;
+                break;
+            }
+            self.skip_blank();
+            terms.push(self.basic()?);
+        }
+        Ok(if terms.len() == 1 { terms.remove(0) } else { Logical::And(terms) })
+    }
+
+    fn basic(&mut self) -> Result<Logical, ParseError> {
+        if self.eat("!") {
+            self.skip_blank();
+            let negated = if self.peek() == Some('(') {
+                self.parenthesized()?
+            } else {
+                let start = self.pos;
+                let operand = self.operand()?;
+                self.test(operand, start)?
+            };
+            return Ok(Logical::Not(Box::new(negated)));
+        }
+        if self.peek() == Some('(') {
+            return self.parenthesized();
+        }
+        let start = self.pos;
+        let left = self.operand()?;
+        let before = self.pos;
+        self.skip_blank();
+        let Some(op) = self.compare_op() else {
+            self.pos = before;
+            return self.test(left, start);
+        };
+        self.check_comparable(&left, start)?;
+        self.skip_blank();
+        let right_start = self.pos;
+        let right = self.operand()?;
+        self.check_comparable(&right, right_start)?;
+        Ok(Logical::Compare(left, op, right))
+    }
+
+    fn parenthesized(&mut self) -> Result<Logical, ParseError> {
+        self.pos += 1;
+        self.skip_blank();
+        let inner = self.logical_or()?;
+        self.skip_blank();
+        if !self.eat(")") {
+            return self.error("expected ')'");
+        }
+        Ok(inner)
+    }
+
+    fn compare_op(&mut self) -> Option<CompareOp> {
+        for (token, op) in [
+            ("==", CompareOp::Eq), ("!=", CompareOp::Ne), ("<=", CompareOp::Le),
+            (">=", CompareOp::Ge), ("<", CompareOp::Lt), (">", CompareOp::Gt),
+        ] {
+            if self.eat(token) {
+                return Some(op);
+            }
+        }
+        None
+    }
+
+    /// A query or function used on its own as a filter condition.
+    fn test(&mut self, operand: Operand, start: usize) -> Result<Logical, ParseError> {
+        match operand {
+            Operand::Query(query) => Ok(Logical::Exists(query)),
+            Operand::Function(function) if function.name.result() != FunctionType::Value => Ok(Logical::Test(function)),
+            Operand::Function(_) => {
+                self.pos = start;
+                self.error("this function returns a value and must be compared, not used as a test")
+            }
+            Operand::Literal(_) => {
+                self.pos = start;
+                self.error("a literal must be compared with something, not used as a test")
+            }
+        }
+    }
+
+    fn check_comparable(&mut self, operand: &Operand, start: usize) -> Result<(), ParseError> {
+        let problem = match operand {
+            Operand::Query(query) if !query.is_singular() => "only singular queries (names and indexes) can be compared",
+            Operand::Function(function) if function.name.result() != FunctionType::Value => "match() and search() return a logical result and cannot be compared",
+            _ => return Ok(()),
+        };
+        self.pos = start;
+        self.error(problem)
+    }
+
+    fn operand(&mut self) -> Result<Operand, ParseError> {
+        match self.peek() {
+            Some('@' | '$') => Ok(Operand::Query(self.query()?)),
+            Some('\'' | '"') => Ok(Operand::Literal(Value::String(self.string_literal()?))),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(c) if c.is_ascii_lowercase() => {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+                    self.pos += 1;
+                }
+                let name = &self.text[start..self.pos];
+                if self.peek() == Some('(') {
+                    return self.function(name, start);
+                }
+                match name {
+                    "true" => Ok(Operand::Literal(Value::Bool(true))),
+                    "false" => Ok(Operand::Literal(Value::Bool(false))),
+                    "null" => Ok(Operand::Literal(Value::Null)),
+                    _ => {
+                        self.pos = start;
+                        self.error(format!("unknown name '{}': expected a literal, '@', '$' or a function call", name))
+                    }
+                }
+            }
+            _ => self.error("expected a literal, '@', '$' or a function call"),
+        }
+    }
+
+    fn number(&mut self) -> Result<Operand, ParseError> {
+        let start = self.pos;
+        self.eat("-");
+        let digits_start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let integer = &self.text[digits_start..self.pos];
+        if integer.is_empty() || integer.len() > 1 && integer.starts_with('0') {
+            self.pos = start;
+            return self.error("invalid number literal");
+        }
+        if self.peek() == Some('.') && self.peek_second().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+            while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += 1;
+            }
+        }
+        if matches!(self.peek(), Some('e' | 'E')) {
+            self.pos += 1;
+            if matches!(self.peek(), Some('+' | '-')) {
+                self.pos += 1;
+            }
+            if !self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                return self.error("expected digits in the exponent");
+            }
+            while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += 1;
+            }
+        }
+        match serde_json::from_str::<Value>(&self.text[start..self.pos]) {
+            Ok(number) => Ok(Operand::Literal(number)),
+            Err(_) => {
+                self.pos = start;
+                self.error("invalid number literal")
+            }
+        }
+    }
+
+    fn function(&mut self, name: &str, start: usize) -> Result<Operand, ParseError> {
+        let Some(function) = FunctionName::lookup(name) else {
+            self.pos = start;
+            return self.error(format!("unknown function '{}' (expected length, count, match, search or value)", name));
+        };
+        self.pos += 1;
+        let mut args = Vec::new();
+        self.skip_blank();
+        if !self.eat(")") {
+            loop {
+                self.skip_blank();
+                let arg_start = self.pos;
+                let arg = self.operand()?;
+                let expected = function.parameters().get(args.len()).copied();
+                self.check_argument(name, &arg, expected, arg_start)?;
+                args.push(arg);
+                self.skip_blank();
+                if self.eat(",") {
+                    continue;
+                }
+                if self.eat(")") {
+                    break;
+                }
+                return self.error("expected ',' or ')'");
+            }
+        }
+        if args.len() != function.parameters().len() {
+            self.pos = start;
+            return self.error(format!("{}() takes {} argument(s), got {}", name, function.parameters().len(), args.len()));
+        }
+        let pattern = match (function, args.get(1)) {
+            (FunctionName::Match | FunctionName::Search, Some(Operand::Literal(Value::String(pattern)))) => {
+                Some(compile_pattern(pattern, matches!(function, FunctionName::Match)))
+            }
+            _ => None,
+        };
+        Ok(Operand::Function(Function { name: function, args, pattern }))
+    }
+
+    fn check_argument(&mut self, name: &str, arg: &Operand, expected: Option<FunctionType>, start: usize) -> Result<(), ParseError> {
+        let problem = match (expected, arg) {
+            (None, _) => format!("too many arguments for {}()", name),
+            (Some(FunctionType::Value), Operand::Query(query)) if !query.is_singular() => {
+                format!("{}() needs a single value; use a singular query (names and indexes only) or count()/value()", name)
+            }
+            (Some(FunctionType::Value), Operand::Function(inner)) if inner.name.result() != FunctionType::Value => {
+                format!("{}() needs a value argument, not a logical result", name)
+            }
+            (Some(FunctionType::Nodes), Operand::Query(_)) => return Ok(()),
+            (Some(FunctionType::Nodes), _) => format!("{}() takes a query ('@...' or '$...') as its argument", name),
+            _ => return Ok(()),
+        };
+        self.pos = start;
+        self.error(problem)
+    }
+}
+
+/// Compile an I-Regexp (RFC 9485) pattern: `.` does not match line breaks, and `match()`
+/// anchors the whole string. Invalid patterns never match.
+fn compile_pattern(pattern: &str, anchored: bool) -> Option<Regex> {
+    let mut translated = String::with_capacity(pattern.len());
+    let mut in_class = false;
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                translated.push(c);
+                translated.extend(chars.next());
+            }
+            '[' => {
+                in_class = true;
+                translated.push(c);
+            }
+            ']' => {
+                in_class = false;
+                translated.push(c);
+            }
+            '.' if !in_class => translated.push_str("[^\\n\\r]"),
+            _ => translated.push(c),
+        }
+    }
+    let source = if anchored { format!("^(?:{})$", translated) } else { translated };
+    Regex::new(&source).ok()
+}
+
+/// Where a selected node sits in the document: nothing (`()`) inside filters, the
+/// normalized path when the caller asked for paths.
+trait Location: Clone {
+    fn key(&self, key: &str) -> Self;
+    fn index(&self, index: usize) -> Self;
+}
+
+impl Location for () {
+    fn key(&self, _key: &str) -> Self {}
+    fn index(&self, _index: usize) -> Self {}
+}
+
+impl Location for String {
+    fn key(&self, key: &str) -> Self {
+        format!("{}['{}']", self, escape_name(key))
+    }
+
+    fn index(&self, index: usize) -> Self {
+        format!("{}[{}]", self, index)
+    }
+}
+
+/// Escape a member name for a normalized path (RFC 9535 section 2.7).
+pub(crate) fn escape_name(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\'' => escaped.push_str("\\'"),
+            '\u{8}' => escaped.push_str("\\b"),
+            '\u{c}' => escaped.push_str("\\f"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn select<'a>(query: &Query, root: &'a Value, current: &'a Value) -> Vec<&'a Value> {
+    select_located(query, root, current, ()).into_iter().map(|(_, value)| value).collect()
+}
+
+fn select_located<'a, L: Location>(query: &Query, root: &'a Value, current: &'a Value, start: L) -> Vec<(L, &'a Value)> {
+    let mut nodes = vec![(start, if query.absolute { root } else { current })];
+    for segment in &query.segments {
+        let mut next = Vec::new();
+        for node in nodes {
+            if segment.descendant {
+                let mut descendants = Vec::new();
+                collect_descendants(node, &mut descendants);
+                for descendant in &descendants {
+                    for selector in &segment.selectors {
+                        apply(selector, descendant, root, &mut next);
+                    }
+                }
+            } else {
+                for selector in &segment.selectors {
+                    apply(selector, &node, root, &mut next);
+                }
+            }
+        }
+        nodes = next;
+    }
+    nodes
+}
+
+fn collect_descendants<'a, L: Location>(node: (L, &'a Value), out: &mut Vec<(L, &'a Value)>) {
+    let (location, value) = node;
+    out.push((location.clone(), value));
+    match value {
+        Value::Array(items) => items.iter().enumerate()
+            .for_each(|(i, item)| collect_descendants((location.index(i), item), out)),
+        Value::Object(map) => map.iter()
+            .for_each(|(key, item)| collect_descendants((location.key(key), item), out)),
+        _ => {}
+    }
+}
+
+fn apply<'a, L: Location>(selector: &Selector, node: &(L, &'a Value), root: &'a Value, out: &mut Vec<(L, &'a Value)>) {
+    let (location, value) = node;
+    match (selector, *value) {
+        (Selector::Name(name), Value::Object(map)) => out.extend(map.get(name).map(|item| (location.key(name), item))),
+        (Selector::Wildcard, Value::Array(items)) => {
+            out.extend(items.iter().enumerate().map(|(i, item)| (location.index(i), item)));
+        }
+        (Selector::Wildcard, Value::Object(map)) => out.extend(map.iter().map(|(key, item)| (location.key(key), item))),
+        (Selector::Index(index), Value::Array(items)) => {
+            let len = items.len() as i64;
+            let position = if *index < 0 { len + index } else { *index };
+            if (0..len).contains(&position) {
+                out.push((location.index(position as usize), &items[position as usize]));
+            }
+        }
+        (Selector::Slice(start, end, step), Value::Array(items)) => {
+            out.extend(slice_indices(items.len() as i64, *start, *end, step.unwrap_or(1)).map(|i| (location.index(i), &items[i])));
+        }
+        (Selector::Filter(condition), Value::Array(items)) => out.extend(items.iter().enumerate()
+            .filter(|(_, item)| evaluate(condition, root, item))
+            .map(|(i, item)| (location.index(i), item))),
+        (Selector::Filter(condition), Value::Object(map)) => out.extend(map.iter()
+            .filter(|(_, item)| evaluate(condition, root, item))
+            .map(|(key, item)| (location.key(key), item))),
+        _ => {}
+    }
+}
+
+/// Array positions selected by `[start:end:step]`, per RFC 9535 section 2.3.4.2.2.
+fn slice_indices(len: i64, start: Option<i64>, end: Option<i64>, step: i64) -> Box<dyn Iterator<Item = usize>> {
+    let normalize = |i: i64| if i >= 0 { i } else { len + i };
+    if step > 0 {
+        let lower = normalize(start.unwrap_or(0)).clamp(0, len);
+        let upper = normalize(end.unwrap_or(len)).clamp(0, len);
+        Box::new((lower..upper).step_by(step as usize).map(|i| i as usize))
+    } else if step < 0 {
+        let upper = normalize(start.unwrap_or(len - 1)).clamp(-1, len - 1);
+        let lower = normalize(end.unwrap_or(-len - 1)).clamp(-1, len - 1);
+        let stride = step.unsigned_abs() as usize;
+        Box::new(((lower + 1)..=upper).rev().step_by(stride).map(|i| i as usize))
+    } else {
+        Box::new(std::iter::empty())
+    }
+}
+
+fn evaluate(condition: &Logical, root: &Value, current: &Value) -> bool {
+    match condition {
+        Logical::Or(terms) => terms.iter().any(|term| evaluate(term, root, current)),
+        Logical::And(terms) => terms.iter().all(|term| evaluate(term, root, current)),
+        Logical::Not(inner) => !evaluate(inner, root, current),
+        Logical::Exists(query) => !select(query, root, current).is_empty(),
+        Logical::Test(function) => match call(function, root, current) {
+            Evaluated::Logical(result) => result,
+            Evaluated::Value(_) => false,
+        },
+        Logical::Compare(left, op, right) => {
+            let left = value_of(left, root, current);
+            let right = value_of(right, root, current);
+            compare(left.as_deref(), *op, right.as_deref())
+        }
+    }
+}
+
+enum Evaluated<'a> {
+    /// `None` is the RFC's "Nothing": a missing value, distinct from JSON null.
+    Value(Option<Cow<'a, Value>>),
+    Logical(bool),
+}
+
+fn value_of<'a>(operand: &Operand, root: &'a Value, current: &'a Value) -> Option<Cow<'a, Value>> {
+    match operand {
+        Operand::Literal(value) => Some(Cow::Owned(value.clone())),
+        Operand::Query(query) => select(query, root, current).into_iter().next().map(Cow::Borrowed),
+        Operand::Function(function) => match call(function, root, current) {
+            Evaluated::Value(value) => value,
+            _ => None,
+        },
+    }
+}
+
+fn call<'a>(function: &Function, root: &'a Value, current: &'a Value) -> Evaluated<'a> {
+    let nodes = |i: usize| match &function.args[i] {
+        Operand::Query(query) => select(query, root, current),
+        _ => Vec::new(),
+    };
+    match function.name {
+        FunctionName::Length => Evaluated::Value(value_of(&function.args[0], root, current).and_then(|value| match value.as_ref() {
+            Value::String(s) => Some(s.chars().count()),
+            Value::Array(items) => Some(items.len()),
+            Value::Object(map) => Some(map.len()),
+            _ => None,
+        }).map(|len| Cow::Owned(json!(len)))),
+        FunctionName::Count => Evaluated::Value(Some(Cow::Owned(json!(nodes(0).len())))),
+        FunctionName::Value => {
+            let nodes = nodes(0);
+            Evaluated::Value((nodes.len() == 1).then(|| Cow::Borrowed(nodes[0])))
+        }
+        FunctionName::Match | FunctionName::Search => {
+            let subject = value_of(&function.args[0], root, current);
+            let Some(Value::String(subject)) = subject.as_deref() else {
+                return Evaluated::Logical(false);
+            };
+            let compiled;
+            let pattern = match &function.pattern {
+                Some(pattern) => pattern.as_ref(),
+                None => {
+                    compiled = match value_of(&function.args[1], root, current).as_deref() {
+                        Some(Value::String(pattern)) => compile_pattern(pattern, matches!(function.name, FunctionName::Match)),
+                        _ => None,
+                    };
+                    compiled.as_ref()
+                }
+            };
+            Evaluated::Logical(pattern.is_some_and(|pattern| pattern.is_match(subject)))
+        }
+    }
+}
+
+fn compare(left: Option<&Value>, op: CompareOp, right: Option<&Value>) -> bool {
+    match op {
+        CompareOp::Eq => equal(left, right),
+        CompareOp::Ne => !equal(left, right),
+        CompareOp::Lt => less(left, right),
+        CompareOp::Le => less(left, right) || equal(left, right),
+        CompareOp::Gt => less(right, left),
+        CompareOp::Ge => less(right, left) || equal(left, right),
+    }
+}
+
+fn equal(left: Option<&Value>, right: Option<&Value>) -> bool {
+    match (left, right) {
+        (None, None) => true,
+        (Some(left), Some(right)) => values_equal(left, right),
+        _ => false,
+    }
+}
+
+fn values_equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => number_cmp(a, b) == Some(Ordering::Equal),
+        (Value::Array(a), Value::Array(b)) => a.len() == b.len() && a.iter().zip(b).all(|(x, y)| values_equal(x, y)),
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len() && a.iter().all(|(key, x)| b.get(key).is_some_and(|y| values_equal(x, y)))
+        }
+        _ => left == right,
+    }
+}
+
+fn less(left: Option<&Value>, right: Option<&Value>) -> bool {
+    match (left, right) {
+        (Some(Value::Number(a)), Some(Value::Number(b))) => number_cmp(a, b) == Some(Ordering::Less),
+        (Some(Value::String(a)), Some(Value::String(b))) => a < b,
+        _ => false,
+    }
+}
+
+fn number_cmp(a: &serde_json::Number, b: &serde_json::Number) -> Option<Ordering> {
+    if let (Some(a), Some(b)) = (a.as_i64(), b.as_i64()) {
+        return Some(a.cmp(&b));
+    }
+    if let (Some(a), Some(b)) = (a.as_u64(), b.as_u64()) {
+        return Some(a.cmp(&b));
+    }
+    a.as_f64()?.partial_cmp(&b.as_f64()?)
+}
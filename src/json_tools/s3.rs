@@ -0,0 +1,572 @@
+use crate::json_tools::storage::{Capabilities, FileInfo, SeekRead, Storage};
+use serde::Deserialize;
+use std::io::Read;
+use std::sync::{Arc, RwLock};
+
+/// Objects are read in ranges of this size and uploaded in parts of this size when larger.
+#[cfg(feature = "s3")]
+const PART_SIZE: u64 = 8 * 1024 * 1024;
+
+/// The `storage.s3` section of the `--config` file. Credentials come from a subset of the
+/// standard AWS chain: the `AWS_ACCESS_KEY_ID`/`AWS_SECRET_ACCESS_KEY`/`AWS_SESSION_TOKEN`
+/// variables, static keys of a profile in the shared credentials file, the ECS container
+/// endpoint, then the EC2 instance role through IMDSv2. Role assumption, SSO,
+/// `credential_process` and profiles of `~/.aws/config` are not supported.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct S3Config {
+    /// Region requests are signed for (default: `AWS_REGION`, `AWS_DEFAULT_REGION`, then
+    /// us-east-1).
+    pub region: Option<String>,
+    /// Endpoint of an S3-compatible service such as MinIO (default: `AWS_ENDPOINT_URL_S3`,
+    /// `AWS_ENDPOINT_URL`, then AWS).
+    pub endpoint: Option<String>,
+    /// Address objects as `endpoint/bucket/key` rather than `bucket.endpoint/key` (default:
+    /// only with a custom endpoint).
+    pub path_style: Option<bool>,
+    /// Profile of the shared credentials file (default: `AWS_PROFILE`, then `default`).
+    pub profile: Option<String>,
+    /// Buckets tools may use; empty allows none.
+    pub buckets: Vec<String>,
+}
+
+/// Bucket and key of an `s3://bucket/key` URI.
+fn parse_uri(uri: &str) -> anyhow::Result<(&str, &str)> {
+    let rest = uri.strip_prefix("s3://").unwrap_or(uri);
+    match rest.split_once('/') {
+        Some((bucket, key)) if !bucket.is_empty() && !key.is_empty() => Ok((bucket, key)),
+        _ => anyhow::bail!("'{}' is not an object URI; use s3://bucket/key", uri),
+    }
+}
+
+static CONFIG: RwLock<Option<Arc<S3Config>>> = RwLock::new(None);
+
+/// Use `config` for every S3 request from now on. Without it, the defaults above apply.
+pub(crate) fn install(config: S3Config) {
+    *CONFIG.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(config));
+    #[cfg(feature = "s3")]
+    {
+        *CLIENT.write().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+fn config() -> Arc<S3Config> {
+    CONFIG.read().unwrap_or_else(|e| e.into_inner()).clone().unwrap_or_default()
+}
+
+/// `s3://bucket/key` objects, read range by range so records can be streamed without
+/// downloading the whole object first.
+pub struct S3Storage;
+
+impl Storage for S3Storage {
+    fn name(&self) -> &'static str {
+        "s3"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities { seekable: true, writable: true }
+    }
+
+    /// Refuse URIs outside the configured buckets.
+    fn check_allowed(&self, uri: &str) -> anyhow::Result<()> {
+        let (bucket, _) = parse_uri(uri)?;
+        let config = config();
+        if config.buckets.is_empty() {
+            anyhow::bail!("'{}' cannot be used: no S3 buckets are allowed. List them in buckets of the storage.s3 section of the --config file", uri);
+        }
+        if !config.buckets.iter().any(|allowed| allowed == bucket) {
+            anyhow::bail!("Bucket '{}' of '{}' is not one of the allowed buckets ({})", bucket, uri, config.buckets.join(", "));
+        }
+        Ok(())
+    }
+
+    fn info(&self, uri: &str) -> anyhow::Result<Option<FileInfo>> {
+        let (bucket, key) = parse_uri(uri)?;
+        client()?.head(bucket, key)
+    }
+
+    fn open(&self, uri: &str) -> anyhow::Result<Box<dyn Read + Send>> {
+        Ok(Box::new(self.open_seekable(uri)?))
+    }
+
+    fn open_seekable(&self, uri: &str) -> anyhow::Result<Box<dyn SeekRead>> {
+        let (bucket, key) = parse_uri(uri)?;
+        let client = client()?;
+        let size = client.head(bucket, key)?
+            .ok_or_else(|| anyhow::anyhow!("Object '{}' does not exist", uri))?.size;
+        Ok(client.reader(bucket, key, size))
+    }
+
+    fn read(&self, uri: &str) -> anyhow::Result<Vec<u8>> {
+        let (bucket, key) = parse_uri(uri)?;
+        client()?.get(bucket, key, None)
+    }
+
+    /// Store `content` at `uri`, with a multipart upload when it is large.
+    fn write(&self, uri: &str, content: &[u8]) -> anyhow::Result<()> {
+        let (bucket, key) = parse_uri(uri)?;
+        client()?.put(bucket, key, content)
+    }
+}
+
+#[cfg(not(feature = "s3"))]
+enum Client {}
+
+#[cfg(not(feature = "s3"))]
+impl Client {
+    fn get(&self, _: &str, _: &str, _: Option<(u64, u64)>) -> anyhow::Result<Vec<u8>> {
+        match *self {}
+    }
+
+    fn head(&self, _: &str, _: &str) -> anyhow::Result<Option<FileInfo>> {
+        match *self {}
+    }
+
+    fn put(&self, _: &str, _: &str, _: &[u8]) -> anyhow::Result<()> {
+        match *self {}
+    }
+
+    fn reader(self: Arc<Self>, _: &str, _: &str, _: u64) -> Box<dyn SeekRead> {
+        match *self {}
+    }
+}
+
+#[cfg(not(feature = "s3"))]
+fn client() -> anyhow::Result<Arc<Client>> {
+    anyhow::bail!("s3:// paths need a server built with the 's3' feature")
+}
+
+#[cfg(feature = "s3")]
+static CLIENT: RwLock<Option<Arc<Client>>> = RwLock::new(None);
+
+#[cfg(feature = "s3")]
+fn client() -> anyhow::Result<Arc<Client>> {
+    if let Some(client) = CLIENT.read().unwrap_or_else(|e| e.into_inner()).clone() {
+        return Ok(client);
+    }
+    let client = Arc::new(Client::new(&config())?);
+    *CLIENT.write().unwrap_or_else(|e| e.into_inner()) = Some(client.clone());
+    Ok(client)
+}
+
+#[cfg(feature = "s3")]
+use client::Client;
+
+/// AWS Signature Version 4, as far as the S3 client needs it: requests signed in the
+/// `Authorization` header over a fully hashed payload. Presigned URLs, chunked (streaming)
+/// payload signing and SigV4a are not supported. Header values are trimmed but inner runs
+/// of spaces are kept, and paths are encoded once without normalization, as S3 expects.
+#[cfg(feature = "s3")]
+pub mod sigv4 {
+    use crate::json_tools::integrity::{hex_digest, sha256_hex};
+    use chrono::{DateTime, Utc};
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    /// A request to sign.
+    pub struct SigningRequest<'a> {
+        pub method: &'a str,
+        /// The URI-encoded path.
+        pub path: &'a str,
+        /// The canonical query string: encoded `name=value` pairs sorted and joined by `&`.
+        pub query: &'a str,
+        /// Headers to sign, `host` and `x-amz-date` among them.
+        pub headers: &'a [(String, String)],
+        /// Hex SHA-256 of the body.
+        pub payload_hash: &'a str,
+        pub region: &'a str,
+        pub service: &'a str,
+        pub time: DateTime<Utc>,
+    }
+
+    impl SigningRequest<'_> {
+        /// The hex signature of the request.
+        pub fn signature(&self, secret_key: &str) -> String {
+            let mut headers: Vec<(String, &str)> = self.headers.iter()
+                .map(|(name, value)| (name.to_ascii_lowercase(), value.trim()))
+                .collect();
+            headers.sort();
+            let signed_names = headers.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(";");
+            let canonical_headers: String = headers.iter().map(|(name, value)| format!("{}:{}\n", name, value)).collect();
+            let canonical_request = format!(
+                "{}\n{}\n{}\n{}\n{}\n{}",
+                self.method, self.path, self.query, canonical_headers, signed_names, self.payload_hash
+            );
+            let string_to_sign = format!(
+                "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+                self.time.format("%Y%m%dT%H%M%SZ"), self.scope(), sha256_hex(canonical_request.as_bytes())
+            );
+            let key = signing_key(secret_key, &self.time.format("%Y%m%d").to_string(), self.region, self.service);
+            hex_digest(&hmac(&key, string_to_sign.as_bytes()))
+        }
+
+        /// The `Authorization` header value for the request.
+        pub fn authorization(&self, access_key: &str, secret_key: &str) -> String {
+            let mut names: Vec<String> = self.headers.iter().map(|(name, _)| name.to_ascii_lowercase()).collect();
+            names.sort();
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                access_key, self.scope(), names.join(";"), self.signature(secret_key)
+            )
+        }
+
+        fn scope(&self) -> String {
+            format!("{}/{}/{}/aws4_request", self.time.format("%Y%m%d"), self.region, self.service)
+        }
+    }
+
+    /// The key derived from `secret_key` for one day (`YYYYMMDD`), region and service.
+    pub fn signing_key(secret_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+        [region, service, "aws4_request"].iter()
+            .fold(hmac(format!("AWS4{}", secret_key).as_bytes(), date.as_bytes()), |key, part| hmac(&key, part.as_bytes()))
+    }
+
+    fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+        mac.update(data);
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    /// Percent-encode all but the unreserved characters (and `/` in paths), as SigV4 asks.
+    pub fn uri_encode(text: &str, encode_slash: bool) -> String {
+        text.bytes().map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            b'/' if !encode_slash => "/".to_string(),
+            _ => format!("%{:02X}", byte),
+        }).collect()
+    }
+}
+
+#[cfg(feature = "s3")]
+mod client {
+    use super::sigv4::{uri_encode, SigningRequest};
+    use super::{S3Config, PART_SIZE};
+    use crate::json_tools::storage::{FileInfo, SeekRead};
+    use crate::json_tools::integrity::sha256_hex;
+    use chrono::{DateTime, Utc};
+    use serde_json::Value;
+    use std::io::{self, Read, Seek, SeekFrom};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// Timeout for instance and container metadata lookups, which fail fast off AWS.
+    const METADATA_TIMEOUT: Duration = Duration::from_secs(1);
+
+    #[derive(Clone)]
+    struct Credentials {
+        access_key: String,
+        secret_key: String,
+        token: Option<String>,
+        expires: Option<DateTime<Utc>>,
+    }
+
+    impl Credentials {
+        fn from_env() -> Option<Self> {
+            Some(Self {
+                access_key: std::env::var("AWS_ACCESS_KEY_ID").ok().filter(|v| !v.is_empty())?,
+                secret_key: std::env::var("AWS_SECRET_ACCESS_KEY").ok().filter(|v| !v.is_empty())?,
+                token: std::env::var("AWS_SESSION_TOKEN").ok().filter(|v| !v.is_empty()),
+                expires: None,
+            })
+        }
+
+        /// The profile's keys in the shared credentials file.
+        fn from_profile(profile: &str) -> Option<Self> {
+            let path = match std::env::var("AWS_SHARED_CREDENTIALS_FILE") {
+                Ok(path) => path.into(),
+                Err(_) => crate::json_tools::paths::expand_path("~/.aws/credentials").ok()?,
+            };
+            let text = std::fs::read_to_string(path).ok()?;
+            let (mut section, mut keys) = (String::new(), std::collections::HashMap::new());
+            for line in text.lines().map(str::trim) {
+                if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                    section = name.trim().to_string();
+                } else if let Some((key, value)) = line.split_once('=').filter(|_| section == profile) {
+                    keys.insert(key.trim().to_string(), value.trim().to_string());
+                }
+            }
+            Some(Self {
+                access_key: keys.remove("aws_access_key_id")?,
+                secret_key: keys.remove("aws_secret_access_key")?,
+                token: keys.remove("aws_session_token"),
+                expires: None,
+            })
+        }
+
+        /// Role credentials in the JSON the container and instance metadata services return.
+        fn from_metadata(response: ureq::Response) -> anyhow::Result<Self> {
+            let body: Value = serde_json::from_str(&response.into_string()?)?;
+            let field = |name: &str| body[name].as_str().map(String::from)
+                .ok_or_else(|| anyhow::anyhow!("The credentials response has no {}", name));
+            Ok(Self {
+                access_key: field("AccessKeyId")?,
+                secret_key: field("SecretAccessKey")?,
+                token: field("Token").ok(),
+                expires: body["Expiration"].as_str().and_then(|e| DateTime::parse_from_rfc3339(e).ok()).map(|e| e.with_timezone(&Utc)),
+            })
+        }
+    }
+
+    pub(super) struct Client {
+        agent: ureq::Agent,
+        region: String,
+        /// `https://host[:port]`, without a trailing slash.
+        endpoint: String,
+        path_style: bool,
+        profile: String,
+        credentials: Mutex<Option<Credentials>>,
+    }
+
+    impl Client {
+        pub(super) fn new(config: &S3Config) -> anyhow::Result<Self> {
+            let env = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+            let region = config.region.clone()
+                .or_else(|| env("AWS_REGION"))
+                .or_else(|| env("AWS_DEFAULT_REGION"))
+                .unwrap_or_else(|| "us-east-1".to_string());
+            let custom = config.endpoint.clone().or_else(|| env("AWS_ENDPOINT_URL_S3")).or_else(|| env("AWS_ENDPOINT_URL"));
+            let path_style = config.path_style.unwrap_or(custom.is_some());
+            let endpoint = custom.unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", region));
+            if !endpoint.starts_with("http://") && !endpoint.starts_with("https://") {
+                anyhow::bail!("The S3 endpoint '{}' must start with http:// or https://", endpoint);
+            }
+            Ok(Self {
+                agent: ureq::AgentBuilder::new().timeout_connect(Duration::from_secs(10)).build(),
+                region,
+                endpoint: endpoint.trim_end_matches('/').to_string(),
+                path_style,
+                profile: config.profile.clone().or_else(|| env("AWS_PROFILE")).unwrap_or_else(|| "default".to_string()),
+                credentials: Mutex::new(None),
+            })
+        }
+
+        /// Credentials from the first source of the chain that has them, cached until
+        /// shortly before they expire.
+        fn credentials(&self) -> anyhow::Result<Credentials> {
+            let mut cached = self.credentials.lock().unwrap_or_else(|e| e.into_inner());
+            let fresh = |c: &Credentials| c.expires.map_or(true, |at| at - chrono::Duration::minutes(5) > Utc::now());
+            if let Some(credentials) = cached.as_ref().filter(|c| fresh(c)) {
+                return Ok(credentials.clone());
+            }
+            let credentials = match Credentials::from_env().or_else(|| Credentials::from_profile(&self.profile)) {
+                Some(credentials) => credentials,
+                None => self.role_credentials().map_err(|e| anyhow::anyhow!(
+                    "No AWS credentials found: set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY, add profile '{}' to ~/.aws/credentials, or run with a container or instance role ({})",
+                    self.profile, e
+                ))?,
+            };
+            *cached = Some(credentials.clone());
+            Ok(credentials)
+        }
+
+        /// Credentials of the ECS container role, or else of the EC2 instance role (IMDSv2).
+        fn role_credentials(&self) -> anyhow::Result<Credentials> {
+            let agent = ureq::AgentBuilder::new().timeout(METADATA_TIMEOUT).build();
+            let container = std::env::var("AWS_CONTAINER_CREDENTIALS_FULL_URI").ok().or_else(|| {
+                std::env::var("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI").ok().map(|uri| format!("http://169.254.170.2{}", uri))
+            });
+            if let Some(uri) = container {
+                let mut request = agent.get(&uri);
+                if let Ok(token) = std::env::var("AWS_CONTAINER_AUTHORIZATION_TOKEN") {
+                    request = request.set("Authorization", &token);
+                }
+                return Credentials::from_metadata(request.call()?);
+            }
+            if std::env::var("AWS_EC2_METADATA_DISABLED").is_ok_and(|v| v.eq_ignore_ascii_case("true")) {
+                anyhow::bail!("the instance metadata service is disabled");
+            }
+            let imds = "http://169.254.169.254/latest";
+            let token = agent.put(&format!("{}/api/token", imds))
+                .set("X-aws-ec2-metadata-token-ttl-seconds", "21600")
+                .call()?
+                .into_string()?;
+            let roles = format!("{}/meta-data/iam/security-credentials/", imds);
+            let role = agent.get(&roles).set("X-aws-ec2-metadata-token", &token).call()?.into_string()?;
+            let role = role.lines().next().ok_or_else(|| anyhow::anyhow!("the instance has no role"))?;
+            Credentials::from_metadata(agent.get(&format!("{}{}", roles, role)).set("X-aws-ec2-metadata-token", &token).call()?)
+        }
+
+        /// Send a request signed with AWS Signature Version 4, returning the response
+        /// whatever its status.
+        fn call(&self, method: &str, bucket: &str, key: &str, query: &[(&str, &str)], headers: &[(&str, &str)], body: &[u8]) -> anyhow::Result<ureq::Response> {
+            let (scheme, authority) = self.endpoint.split_once("://").expect("endpoint has a scheme");
+            let (host, path) = if self.path_style {
+                (authority.to_string(), format!("/{}/{}", uri_encode(bucket, false), uri_encode(key, false)))
+            } else {
+                (format!("{}.{}", bucket, authority), format!("/{}", uri_encode(key, false)))
+            };
+            let mut query: Vec<(String, String)> = query.iter().map(|(k, v)| (uri_encode(k, true), uri_encode(v, true))).collect();
+            query.sort();
+            let query = query.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("&");
+
+            let credentials = self.credentials()?;
+            let now = Utc::now();
+            let payload_hash = sha256_hex(body);
+            let mut signed = vec![
+                ("host".to_string(), host.clone()),
+                ("x-amz-content-sha256".to_string(), payload_hash.clone()),
+                ("x-amz-date".to_string(), now.format("%Y%m%dT%H%M%SZ").to_string()),
+            ];
+            if let Some(token) = &credentials.token {
+                signed.push(("x-amz-security-token".to_string(), token.clone()));
+            }
+            let authorization = SigningRequest {
+                method, path: &path, query: &query, headers: &signed, payload_hash: &payload_hash,
+                region: &self.region, service: "s3", time: now,
+            }.authorization(&credentials.access_key, &credentials.secret_key);
+
+            let url = if query.is_empty() { format!("{}://{}{}", scheme, host, path) } else { format!("{}://{}{}?{}", scheme, host, path, query) };
+            let mut request = self.agent.request(method, &url).set("Authorization", &authorization);
+            for (name, value) in signed.iter().filter(|(name, _)| name != "host") {
+                request = request.set(name, value);
+            }
+            for (name, value) in headers {
+                request = request.set(name, value);
+            }
+            let result = if body.is_empty() && method != "PUT" { request.call() } else { request.send_bytes(body) };
+            match result {
+                Ok(response) | Err(ureq::Error::Status(_, response)) => Ok(response),
+                Err(e) => anyhow::bail!("S3 {} of 's3://{}/{}' failed: {}", method, bucket, key, e),
+            }
+        }
+
+        /// Send a signed request, failing with S3's error code and message unless it succeeds.
+        fn send(&self, method: &str, bucket: &str, key: &str, query: &[(&str, &str)], headers: &[(&str, &str)], body: &[u8]) -> anyhow::Result<ureq::Response> {
+            let response = self.call(method, bucket, key, query, headers, body)?;
+            match response.status() {
+                status if status >= 400 => Err(failure(method, bucket, key, response)),
+                _ => Ok(response),
+            }
+        }
+
+        /// The object's bytes, or the inclusive byte range of them.
+        pub(super) fn get(&self, bucket: &str, key: &str, range: Option<(u64, u64)>) -> anyhow::Result<Vec<u8>> {
+            let range = range.map(|(start, end)| format!("bytes={}-{}", start, end));
+            let headers: Vec<(&str, &str)> = range.iter().map(|r| ("Range", r.as_str())).collect();
+            let mut content = Vec::new();
+            self.send("GET", bucket, key, &[], &headers, &[])?.into_reader().read_to_end(&mut content)?;
+            Ok(content)
+        }
+
+        /// The object's size and modification time, or `None` when it does not exist.
+        pub(super) fn head(&self, bucket: &str, key: &str) -> anyhow::Result<Option<FileInfo>> {
+            let response = self.call("HEAD", bucket, key, &[], &[], &[])?;
+            match response.status() {
+                404 => Ok(None),
+                status if status >= 400 => Err(failure("HEAD", bucket, key, response)),
+                _ => Ok(Some(FileInfo {
+                    size: response.header("Content-Length").and_then(|l| l.parse().ok()).unwrap_or(0),
+                    modified: response.header("Last-Modified")
+                        .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+                        .map(std::time::SystemTime::from),
+                })),
+            }
+        }
+
+        pub(super) fn reader(self: Arc<Self>, bucket: &str, key: &str, size: u64) -> Box<dyn SeekRead> {
+            Box::new(RangeReader { client: self, bucket: bucket.to_string(), key: key.to_string(), size, position: 0, buffer: Vec::new(), offset: 0 })
+        }
+
+        pub(super) fn put(&self, bucket: &str, key: &str, content: &[u8]) -> anyhow::Result<()> {
+            if content.len() as u64 <= PART_SIZE {
+                self.send("PUT", bucket, key, &[], &[], content)?;
+                return Ok(());
+            }
+            let created = self.send("POST", bucket, key, &[("uploads", "")], &[], &[])?.into_string()?;
+            let upload_id = xml_field(&created, "UploadId")
+                .ok_or_else(|| anyhow::anyhow!("S3 did not start a multipart upload to 's3://{}/{}'", bucket, key))?;
+            let upload = || -> anyhow::Result<()> {
+                let mut parts = String::new();
+                for (index, part) in content.chunks(PART_SIZE as usize).enumerate() {
+                    let number = (index + 1).to_string();
+                    let response = self.send("PUT", bucket, key, &[("partNumber", &number), ("uploadId", &upload_id)], &[], part)?;
+                    let etag = response.header("ETag").unwrap_or_default();
+                    parts.push_str(&format!("<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>", number, etag));
+                }
+                let complete = format!("<CompleteMultipartUpload>{}</CompleteMultipartUpload>", parts);
+                // Completion reports some failures in the body of a 200 response
+                let response = self.send("POST", bucket, key, &[("uploadId", &upload_id)], &[], complete.as_bytes())?.into_string()?;
+                if response.contains("<Error>") {
+                    anyhow::bail!("S3 could not complete the upload to 's3://{}/{}': {}", bucket, key, xml_field(&response, "Message").unwrap_or_default());
+                }
+                Ok(())
+            };
+            let result = upload();
+            if result.is_err() {
+                let _ = self.send("DELETE", bucket, key, &[("uploadId", &upload_id)], &[], &[]);
+            }
+            result
+        }
+    }
+
+    /// Reads an object one ranged GET per part, from wherever it was last sought to.
+    struct RangeReader {
+        client: Arc<Client>,
+        bucket: String,
+        key: String,
+        size: u64,
+        position: u64,
+        buffer: Vec<u8>,
+        offset: usize,
+    }
+
+    impl Read for RangeReader {
+        fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+            if self.offset == self.buffer.len() {
+                if self.position >= self.size {
+                    return Ok(0);
+                }
+                let end = (self.position + PART_SIZE).min(self.size) - 1;
+                self.buffer = self.client.get(&self.bucket, &self.key, Some((self.position, end))).map_err(io::Error::other)?;
+                if self.buffer.is_empty() {
+                    return Ok(0);
+                }
+                self.position += self.buffer.len() as u64;
+                self.offset = 0;
+            }
+            let count = out.len().min(self.buffer.len() - self.offset);
+            out[..count].copy_from_slice(&self.buffer[self.offset..self.offset + count]);
+            self.offset += count;
+            Ok(count)
+        }
+    }
+
+    impl Seek for RangeReader {
+        fn seek(&mut self, from: SeekFrom) -> io::Result<u64> {
+            let current = self.position - (self.buffer.len() - self.offset) as u64;
+            let target = match from {
+                SeekFrom::Start(offset) => Some(offset),
+                SeekFrom::Current(delta) => current.checked_add_signed(delta),
+                SeekFrom::End(delta) => self.size.checked_add_signed(delta),
+            };
+            let target = target.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "seek before the start of the object"))?;
+            // Stay in the buffered part when possible, so small hops cost no request
+            let buffer_start = self.position - self.buffer.len() as u64;
+            if (buffer_start..=self.position).contains(&target) {
+                self.offset = (target - buffer_start) as usize;
+            } else {
+                self.position = target;
+                self.buffer.clear();
+                self.offset = 0;
+            }
+            Ok(target)
+        }
+    }
+
+    /// The error of a failed request, with the code and message from S3's XML body.
+    fn failure(method: &str, bucket: &str, key: &str, response: ureq::Response) -> anyhow::Error {
+        let status = response.status();
+        let body = response.into_string().unwrap_or_default();
+        let code = xml_field(&body, "Code").unwrap_or_default();
+        let message = xml_field(&body, "Message").unwrap_or_default();
+        anyhow::anyhow!("S3 {} of 's3://{}/{}' failed with status {}{}", method, bucket, key, status,
+            if code.is_empty() { String::new() } else { format!(": {} {}", code, message) })
+    }
+
+    /// The text of the first `<name>` element of an S3 XML response.
+    fn xml_field(xml: &str, name: &str) -> Option<String> {
+        let start = xml.find(&format!("<{}>", name))? + name.len() + 2;
+        let end = start + xml[start..].find(&format!("</{}>", name))?;
+        Some(xml[start..end].to_string())
+    }
+}
@@ -0,0 +1,285 @@
+use crate::json_tools::compare::json_path;
+use crate::json_tools::patch::{parse_pointer, pointer_get};
+use regex::Regex;
+use serde_json::{json, Value};
+
+/// Errors collected before validation stops reporting more.
+pub const MAX_ERRORS: usize = 100;
+
+/// `$ref`s followed at one instance location before validation gives up, so a schema that
+/// refers to itself without descending into the instance cannot loop.
+const MAX_REF_DEPTH: usize = 64;
+
+/// One way an instance fails its schema.
+#[derive(Debug, Clone)]
+pub struct SchemaError {
+    /// JSONPath of the failing value within the instance.
+    pub path: String,
+    pub keyword: &'static str,
+    pub message: String,
+}
+
+impl SchemaError {
+    pub fn to_json(&self) -> Value {
+        json!({"path": self.path, "keyword": self.keyword, "message": self.message})
+    }
+}
+
+/// Validate `instance` against `schema`, a JSON Schema (draft 4 through 2020-12 keywords, and
+/// OpenAPI 3.0's `nullable`) whose local `$ref`s resolve against `root`. Annotations such as
+/// `format`, `readOnly` and `discriminator` are not checked.
+pub fn validate(root: &Value, schema: &Value, instance: &Value) -> Vec<SchemaError> {
+    let mut validator = Validator { root, errors: Vec::new() };
+    validator.check(schema, instance, &mut Vec::new(), 0);
+    validator.errors.truncate(MAX_ERRORS);
+    validator.errors
+}
+
+struct Validator<'a> {
+    root: &'a Value,
+    errors: Vec<SchemaError>,
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "integer" => value.as_f64().is_some_and(|n| n.fract() == 0.0),
+        "number" => value.is_number(),
+        other => type_name(value) == other,
+    }
+}
+
+impl Validator<'_> {
+    fn fail(&mut self, tokens: &[String], keyword: &'static str, message: String) {
+        self.errors.push(SchemaError { path: json_path(tokens), keyword, message });
+    }
+
+    /// Whether `instance` satisfies `schema`, without reporting why not.
+    fn passes(&self, schema: &Value, instance: &Value, tokens: &mut Vec<String>, depth: usize) -> bool {
+        let mut probe = Validator { root: self.root, errors: Vec::new() };
+        probe.check(schema, instance, tokens, depth);
+        probe.errors.is_empty()
+    }
+
+    fn check(&mut self, schema: &Value, instance: &Value, tokens: &mut Vec<String>, depth: usize) {
+        if self.errors.len() >= MAX_ERRORS {
+            return;
+        }
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => return self.fail(tokens, "false", "no value is allowed here".to_string()),
+            Value::Object(schema) => schema,
+            _ => return,
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(|r| r.as_str()) {
+            let target = reference.strip_prefix('#')
+                .and_then(|pointer| parse_pointer(pointer).ok())
+                .and_then(|pointer| pointer_get(self.root, &pointer).ok());
+            match target {
+                _ if depth >= MAX_REF_DEPTH => {
+                    return self.fail(tokens, "$ref", format!("'{}' nests more than {} references deep", reference, MAX_REF_DEPTH));
+                }
+                Some(target) => self.check(target, instance, tokens, depth + 1),
+                None => return self.fail(tokens, "$ref", format!("cannot resolve '{}'; only references within the document are supported", reference)),
+            }
+        }
+
+        let null_allowed = schema.get("nullable") == Some(&Value::Bool(true)) && instance.is_null();
+        if let Some(types) = schema.get("type") {
+            let names: Vec<&str> = match types {
+                Value::String(name) => vec![name.as_str()],
+                Value::Array(names) => names.iter().filter_map(|n| n.as_str()).collect(),
+                _ => Vec::new(),
+            };
+            if !null_allowed && !names.iter().any(|name| has_type(instance, name)) {
+                return self.fail(tokens, "type", format!("expected {}, found {}", names.join(" or "), type_name(instance)));
+            }
+        }
+        if let Some(values) = schema.get("enum").and_then(|v| v.as_array()) {
+            if !null_allowed && !values.contains(instance) {
+                let allowed: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                self.fail(tokens, "enum", format!("{} is not one of {}", instance, allowed.join(", ")));
+            }
+        }
+        if let Some(expected) = schema.get("const") {
+            if expected != instance {
+                self.fail(tokens, "const", format!("expected {}, found {}", expected, instance));
+            }
+        }
+
+        self.check_composition(schema, instance, tokens, depth);
+        match instance {
+            Value::Number(number) => self.check_number(schema, number.as_f64().unwrap_or_default(), tokens),
+            Value::String(text) => self.check_string(schema, text, tokens),
+            Value::Array(items) => self.check_array(schema, items, tokens),
+            Value::Object(members) => self.check_object(schema, members, tokens),
+            _ => {}
+        }
+    }
+
+    fn check_composition(&mut self, schema: &serde_json::Map<String, Value>, instance: &Value, tokens: &mut Vec<String>, depth: usize) {
+        if let Some(all) = schema.get("allOf").and_then(|v| v.as_array()) {
+            for branch in all {
+                self.check(branch, instance, tokens, depth);
+            }
+        }
+        if let Some(any) = schema.get("anyOf").and_then(|v| v.as_array()) {
+            if !any.iter().any(|branch| self.passes(branch, instance, tokens, depth)) {
+                self.fail(tokens, "anyOf", format!("matches none of the {} anyOf schemas", any.len()));
+            }
+        }
+        if let Some(one) = schema.get("oneOf").and_then(|v| v.as_array()) {
+            let matched = one.iter().filter(|branch| self.passes(branch, instance, tokens, depth)).count();
+            if matched != 1 {
+                self.fail(tokens, "oneOf", format!("matches {} of the {} oneOf schemas, expected exactly one", matched, one.len()));
+            }
+        }
+        if let Some(not) = schema.get("not") {
+            if self.passes(not, instance, tokens, depth) {
+                self.fail(tokens, "not", "matches a schema it must not match".to_string());
+            }
+        }
+        if let Some(condition) = schema.get("if") {
+            let branch = if self.passes(condition, instance, tokens, depth) { schema.get("then") } else { schema.get("else") };
+            if let Some(branch) = branch {
+                self.check(branch, instance, tokens, depth);
+            }
+        }
+    }
+
+    fn check_number(&mut self, schema: &serde_json::Map<String, Value>, number: f64, tokens: &[String]) {
+        let bound = |keyword: &str| schema.get(keyword).and_then(|v| v.as_f64());
+        // Draft 4 and OpenAPI 3.0 write exclusive bounds as booleans next to minimum/maximum
+        let exclusive = |keyword: &str| schema.get(keyword) == Some(&Value::Bool(true));
+        if let Some(minimum) = bound("minimum") {
+            if number < minimum || (exclusive("exclusiveMinimum") && number == minimum) {
+                let relation = if exclusive("exclusiveMinimum") { "greater than" } else { "at least" };
+                self.fail(tokens, "minimum", format!("{} is not {} {}", number, relation, minimum));
+            }
+        }
+        if let Some(maximum) = bound("maximum") {
+            if number > maximum || (exclusive("exclusiveMaximum") && number == maximum) {
+                let relation = if exclusive("exclusiveMaximum") { "less than" } else { "at most" };
+                self.fail(tokens, "maximum", format!("{} is not {} {}", number, relation, maximum));
+            }
+        }
+        if let Some(minimum) = bound("exclusiveMinimum").filter(|&m| number <= m) {
+            self.fail(tokens, "exclusiveMinimum", format!("{} is not greater than {}", number, minimum));
+        }
+        if let Some(maximum) = bound("exclusiveMaximum").filter(|&m| number >= m) {
+            self.fail(tokens, "exclusiveMaximum", format!("{} is not less than {}", number, maximum));
+        }
+        if let Some(divisor) = bound("multipleOf").filter(|&d| d > 0.0) {
+            let quotient = number / divisor;
+            if (quotient - quotient.round()).abs() > 1e-9 {
+                self.fail(tokens, "multipleOf", format!("{} is not a multiple of {}", number, divisor));
+            }
+        }
+    }
+
+    fn check_string(&mut self, schema: &serde_json::Map<String, Value>, text: &str, tokens: &[String]) {
+        let length = text.chars().count() as u64;
+        if let Some(minimum) = schema.get("minLength").and_then(|v| v.as_u64()).filter(|&m| length < m) {
+            self.fail(tokens, "minLength", format!("length {} is shorter than {}", length, minimum));
+        }
+        if let Some(maximum) = schema.get("maxLength").and_then(|v| v.as_u64()).filter(|&m| length > m) {
+            self.fail(tokens, "maxLength", format!("length {} is longer than {}", length, maximum));
+        }
+        if let Some(pattern) = schema.get("pattern").and_then(|v| v.as_str()) {
+            match Regex::new(pattern) {
+                Ok(regex) if !regex.is_match(text) => self.fail(tokens, "pattern", format!("{:?} does not match '{}'", text, pattern)),
+                Ok(_) => {}
+                Err(e) => self.fail(tokens, "pattern", format!("the schema's pattern '{}' is invalid: {}", pattern, e)),
+            }
+        }
+    }
+
+    fn check_array(&mut self, schema: &serde_json::Map<String, Value>, items: &[Value], tokens: &mut Vec<String>) {
+        let count = items.len() as u64;
+        if let Some(minimum) = schema.get("minItems").and_then(|v| v.as_u64()).filter(|&m| count < m) {
+            self.fail(tokens, "minItems", format!("{} item(s), fewer than {}", count, minimum));
+        }
+        if let Some(maximum) = schema.get("maxItems").and_then(|v| v.as_u64()).filter(|&m| count > m) {
+            self.fail(tokens, "maxItems", format!("{} item(s), more than {}", count, maximum));
+        }
+        if schema.get("uniqueItems") == Some(&Value::Bool(true)) {
+            if let Some(duplicate) = (1..items.len()).find(|&i| items[..i].contains(&items[i])) {
+                tokens.push(duplicate.to_string());
+                self.fail(tokens, "uniqueItems", format!("{} appears more than once", items[duplicate]));
+                tokens.pop();
+            }
+        }
+        // `prefixItems` (2020-12) or an array of `items` (older drafts) covers the first items
+        let (prefix, rest) = match (schema.get("prefixItems"), schema.get("items")) {
+            (Some(Value::Array(prefix)), rest) => (prefix.as_slice(), rest.or(schema.get("additionalItems"))),
+            (_, Some(Value::Array(prefix))) => (prefix.as_slice(), schema.get("additionalItems")),
+            (_, rest) => (&[][..], rest),
+        };
+        for (index, item) in items.iter().enumerate() {
+            let item_schema = prefix.get(index).or(rest);
+            if let Some(item_schema) = item_schema {
+                tokens.push(index.to_string());
+                self.check(item_schema, item, tokens, 0);
+                tokens.pop();
+            }
+        }
+    }
+
+    fn check_object(&mut self, schema: &serde_json::Map<String, Value>, members: &serde_json::Map<String, Value>, tokens: &mut Vec<String>) {
+        let count = members.len() as u64;
+        if let Some(minimum) = schema.get("minProperties").and_then(|v| v.as_u64()).filter(|&m| count < m) {
+            self.fail(tokens, "minProperties", format!("{} properties, fewer than {}", count, minimum));
+        }
+        if let Some(maximum) = schema.get("maxProperties").and_then(|v| v.as_u64()).filter(|&m| count > m) {
+            self.fail(tokens, "maxProperties", format!("{} properties, more than {}", count, maximum));
+        }
+        for name in schema.get("required").and_then(|v| v.as_array()).into_iter().flatten().filter_map(|n| n.as_str()) {
+            if !members.contains_key(name) {
+                self.fail(tokens, "required", format!("missing required property '{}'", name));
+            }
+        }
+
+        let properties = schema.get("properties").and_then(|v| v.as_object());
+        let patterns: Vec<(Regex, &Value)> = schema.get("patternProperties").and_then(|v| v.as_object()).into_iter().flatten()
+            .filter_map(|(pattern, schema)| Regex::new(pattern).ok().map(|regex| (regex, schema)))
+            .collect();
+        for (name, value) in members {
+            tokens.push(name.clone());
+            let mut matched = false;
+            if let Some(property) = properties.and_then(|p| p.get(name)) {
+                matched = true;
+                self.check(property, value, tokens, 0);
+            }
+            for (regex, property) in &patterns {
+                if regex.is_match(name) {
+                    matched = true;
+                    self.check(property, value, tokens, 0);
+                }
+            }
+            if !matched {
+                match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => {
+                        tokens.pop();
+                        self.fail(tokens, "additionalProperties", format!("property '{}' is not allowed", name));
+                        continue;
+                    }
+                    Some(additional) => self.check(additional, value, tokens, 0),
+                    None => {}
+                }
+            }
+            tokens.pop();
+        }
+    }
+}
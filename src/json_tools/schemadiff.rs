@@ -0,0 +1,365 @@
+use crate::json_tools::compare::side_document;
+use crate::json_tools::patch::{parse_pointer, pointer_get};
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+
+const USAGE: &str = "{\n  \"old_file\": \"./schemas/user.v1.json\",\n  \"new_file\": \"./schemas/user.v2.json\"\n}";
+
+/// `$ref`s followed from one location before giving up on a cycle of references.
+const MAX_REF_DEPTH: usize = 32;
+
+/// The schema of an array without `items`: anything.
+static ANY: Value = Value::Bool(true);
+
+/// Lower bounds, where raising the value rejects documents the old schema accepted.
+const LOWER_BOUNDS: &[&str] = &["minimum", "exclusiveMinimum", "minLength", "minItems", "minProperties"];
+/// Upper bounds, where lowering the value rejects documents the old schema accepted.
+const UPPER_BOUNDS: &[&str] = &["maximum", "exclusiveMaximum", "maxLength", "maxItems", "maxProperties"];
+/// Keywords whose value is compared as a whole: adding or changing one is breaking.
+const RESTRICTIONS: &[&str] = &["pattern", "format", "multipleOf", "uniqueItems"];
+/// Keywords not compared in detail; any change to them needs a person to judge.
+const COMPOSITIONS: &[&str] = &["allOf", "anyOf", "oneOf", "not", "if", "then", "else", "patternProperties", "dependentRequired", "dependentSchemas"];
+
+/// Whether a change can break existing documents or their readers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compatibility {
+    Compatible,
+    Breaking,
+    Unknown,
+}
+
+impl Compatibility {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Compatible => "compatible",
+            Self::Breaking => "breaking",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+struct Change {
+    path: String,
+    change: String,
+    compatibility: Compatibility,
+    old: Option<Value>,
+    new: Option<Value>,
+}
+
+/// Walks two schemas side by side, resolving local `$ref`s against each root.
+struct SchemaDiff<'a> {
+    old_root: &'a Value,
+    new_root: &'a Value,
+    changes: Vec<Change>,
+}
+
+impl<'a> SchemaDiff<'a> {
+    fn record(&mut self, path: &str, change: impl Into<String>, compatibility: Compatibility, old: Option<&Value>, new: Option<&Value>) {
+        self.changes.push(Change { path: path.to_string(), change: change.into(), compatibility, old: old.cloned(), new: new.cloned() });
+    }
+
+    /// Follow `$ref`s to local definitions; external references are compared as they are.
+    fn resolve<'r>(root: &'r Value, mut schema: &'r Value) -> &'r Value {
+        for _ in 0..MAX_REF_DEPTH {
+            let target = schema.get("$ref")
+                .and_then(|r| r.as_str())
+                .and_then(|r| r.strip_prefix('#'))
+                .and_then(|pointer| parse_pointer(pointer).ok())
+                .and_then(|tokens| pointer_get(root, &tokens).ok());
+            match target {
+                Some(target) => schema = target,
+                None => break,
+            }
+        }
+        schema
+    }
+
+    /// Compare two schema locations. `seen` holds the pairs already compared, by address within
+    /// the two roots, so a recursive definition is compared once rather than forever.
+    fn compare(&mut self, old: &Value, new: &Value, path: &str, seen: &mut HashSet<(*const Value, *const Value)>) {
+        let old = Self::resolve(self.old_root, old);
+        let new = Self::resolve(self.new_root, new);
+        // Equal text is not enough to skip a location: a `$ref` inside may point at a changed definition
+        if !seen.insert((old as *const Value, new as *const Value)) {
+            return;
+        }
+        let empty = Map::new();
+        let (o, n) = match (keywords(old, &empty), keywords(new, &empty)) {
+            (Some(o), Some(n)) => (o, n),
+            _ if *new == Value::Bool(false) => {
+                return self.record(path, "schema now rejects every value", Compatibility::Breaking, Some(old), Some(new));
+            }
+            _ if *old == Value::Bool(false) => {
+                return self.record(path, "schema now accepts values", Compatibility::Compatible, Some(old), Some(new));
+            }
+            _ => return self.record(path, "schema replaced", Compatibility::Unknown, Some(old), Some(new)),
+        };
+
+        self.compare_types(o, n, path);
+        self.compare_enums(o, n, path);
+        self.compare_bounds(o, n, path);
+        for keyword in RESTRICTIONS {
+            match (o.get(*keyword), n.get(*keyword)) {
+                (None, Some(value)) => self.record(path, format!("{} added", keyword), Compatibility::Breaking, None, Some(value)),
+                (Some(value), None) => self.record(path, format!("{} removed", keyword), Compatibility::Compatible, Some(value), None),
+                (Some(a), Some(b)) if a != b => self.record(path, format!("{} changed", keyword), Compatibility::Breaking, Some(a), Some(b)),
+                _ => {}
+            }
+        }
+        for keyword in COMPOSITIONS {
+            if o.get(*keyword) != n.get(*keyword) {
+                self.record(path, format!("{} changed", keyword), Compatibility::Unknown, o.get(*keyword), n.get(*keyword));
+            }
+        }
+        self.compare_properties(o, n, path, seen);
+
+        let (old_items, new_items) = (items(o), items(n));
+        if old_items.is_some() || new_items.is_some() {
+            self.compare(old_items.unwrap_or(&ANY), new_items.unwrap_or(&ANY), &format!("{}[*]", path), seen);
+        }
+    }
+
+    fn compare_types(&mut self, o: &Map<String, Value>, n: &Map<String, Value>, path: &str) {
+        let types = |schema: &Map<String, Value>| -> Option<Vec<String>> {
+            match schema.get("type")? {
+                Value::String(name) => Some(vec![name.clone()]),
+                Value::Array(names) => Some(names.iter().filter_map(|n| n.as_str().map(str::to_string)).collect()),
+                _ => None,
+            }
+        };
+        let accepts = |types: &Option<Vec<String>>, name: &str| match types {
+            None => true,
+            Some(types) => types.iter().any(|t| t == name || (t == "number" && name == "integer")),
+        };
+        let (old_types, new_types) = (types(o), types(n));
+        if old_types == new_types {
+            return;
+        }
+        let all: Vec<String> = ["null", "boolean", "object", "array", "number", "string"].iter().map(|t| t.to_string()).collect();
+        let narrowed = old_types.as_ref().unwrap_or(&all).iter().any(|t| !accepts(&new_types, t));
+        let widened = new_types.as_ref().unwrap_or(&all).iter().any(|t| !accepts(&old_types, t));
+        let (change, compatibility) = match (narrowed, widened) {
+            (true, true) => ("type changed", Compatibility::Breaking),
+            (true, false) => ("type narrowed", Compatibility::Breaking),
+            (false, _) => ("type widened", Compatibility::Compatible),
+        };
+        self.record(path, change, compatibility, o.get("type"), n.get("type"));
+    }
+
+    fn compare_enums(&mut self, o: &Map<String, Value>, n: &Map<String, Value>, path: &str) {
+        match (o.get("const"), n.get("const")) {
+            (a, b) if a == b => {}
+            (None, Some(value)) => self.record(path, "const added", Compatibility::Breaking, None, Some(value)),
+            (Some(value), None) => self.record(path, "const removed", Compatibility::Compatible, Some(value), None),
+            (a, b) => self.record(path, "const changed", Compatibility::Breaking, a, b),
+        }
+        match (o.get("enum").and_then(|v| v.as_array()), n.get("enum").and_then(|v| v.as_array())) {
+            (None, Some(_)) => self.record(path, "enum added", Compatibility::Breaking, None, n.get("enum")),
+            (Some(_), None) => self.record(path, "enum removed", Compatibility::Compatible, o.get("enum"), None),
+            (Some(old_values), Some(new_values)) => {
+                let removed: Vec<Value> = old_values.iter().filter(|v| !new_values.contains(v)).cloned().collect();
+                let added: Vec<Value> = new_values.iter().filter(|v| !old_values.contains(v)).cloned().collect();
+                if !removed.is_empty() {
+                    self.record(path, "enum values removed", Compatibility::Breaking, Some(&Value::Array(removed)), None);
+                }
+                if !added.is_empty() {
+                    self.record(path, "enum values added", Compatibility::Compatible, None, Some(&Value::Array(added)));
+                }
+            }
+            (None, None) => {}
+        }
+    }
+
+    fn compare_bounds(&mut self, o: &Map<String, Value>, n: &Map<String, Value>, path: &str) {
+        for (keywords, tighter) in [(LOWER_BOUNDS, std::cmp::Ordering::Greater), (UPPER_BOUNDS, std::cmp::Ordering::Less)] {
+            for keyword in keywords {
+                let (old_bound, new_bound) = (o.get(*keyword), n.get(*keyword));
+                let (change, compatibility) = match (old_bound.and_then(|v| v.as_f64()), new_bound.and_then(|v| v.as_f64())) {
+                    (None, None) => continue,
+                    (None, Some(_)) => ("added", Compatibility::Breaking),
+                    (Some(_), None) => ("removed", Compatibility::Compatible),
+                    (Some(a), Some(b)) => match b.partial_cmp(&a) {
+                        Some(std::cmp::Ordering::Equal) | None => continue,
+                        Some(order) if order == tighter => ("tightened", Compatibility::Breaking),
+                        Some(_) => ("relaxed", Compatibility::Compatible),
+                    },
+                };
+                self.record(path, format!("{} {}", keyword, change), compatibility, old_bound, new_bound);
+            }
+        }
+    }
+
+    fn compare_properties(&mut self, o: &Map<String, Value>, n: &Map<String, Value>, path: &str, seen: &mut HashSet<(*const Value, *const Value)>) {
+        let empty = Map::new();
+        let required = |schema: &Map<String, Value>| -> Vec<String> {
+            schema.get("required").and_then(|v| v.as_array())
+                .map(|names| names.iter().filter_map(|n| n.as_str().map(str::to_string)).collect())
+                .unwrap_or_default()
+        };
+        let (old_required, new_required) = (required(o), required(n));
+        let old_properties = o.get("properties").and_then(|v| v.as_object()).unwrap_or(&empty);
+        let new_properties = n.get("properties").and_then(|v| v.as_object()).unwrap_or(&empty);
+        let closed = |schema: &Map<String, Value>| schema.get("additionalProperties") == Some(&Value::Bool(false));
+        let property_path = |name: &str| {
+            if !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                format!("{}.{}", path, name)
+            } else {
+                format!("{}['{}']", path, name.replace('\'', "\\'"))
+            }
+        };
+
+        for (name, schema) in old_properties {
+            let was_required = old_required.contains(name);
+            match new_properties.get(name) {
+                None if was_required => self.record(&property_path(name), "required property removed", Compatibility::Breaking, Some(schema), None),
+                None if closed(n) => self.record(&property_path(name), "property removed and no longer allowed", Compatibility::Breaking, Some(schema), None),
+                None => self.record(&property_path(name), "property removed", Compatibility::Compatible, Some(schema), None),
+                Some(other) => {
+                    match (was_required, new_required.contains(name)) {
+                        (false, true) => self.record(&property_path(name), "property became required", Compatibility::Breaking, None, None),
+                        (true, false) => self.record(&property_path(name), "property became optional", Compatibility::Breaking, None, None),
+                        _ => {}
+                    }
+                    self.compare(schema, other, &property_path(name), seen);
+                }
+            }
+        }
+        for (name, schema) in new_properties.iter().filter(|(name, _)| !old_properties.contains_key(*name)) {
+            if new_required.contains(name) {
+                self.record(&property_path(name), "required property added", Compatibility::Breaking, None, Some(schema));
+            } else {
+                self.record(&property_path(name), "optional property added", Compatibility::Compatible, None, Some(schema));
+            }
+        }
+        // Required names without a property schema
+        for name in new_required.iter().filter(|name| !old_required.contains(name) && !new_properties.contains_key(*name) && !old_properties.contains_key(*name)) {
+            self.record(&property_path(name), "property became required", Compatibility::Breaking, None, None);
+        }
+
+        match (closed(o), closed(n)) {
+            (false, true) => self.record(path, "additional properties no longer allowed", Compatibility::Breaking, o.get("additionalProperties"), n.get("additionalProperties")),
+            (true, false) => self.record(path, "additional properties allowed", Compatibility::Compatible, o.get("additionalProperties"), n.get("additionalProperties")),
+            _ => {
+                if let (Some(a @ Value::Object(_)), Some(b @ Value::Object(_))) = (o.get("additionalProperties"), n.get("additionalProperties")) {
+                    self.compare(a, b, &format!("{}.*", path), seen);
+                }
+            }
+        }
+    }
+}
+
+/// The keywords of a schema; `true` accepts anything, like an empty schema.
+fn keywords<'s>(schema: &'s Value, empty: &'s Map<String, Value>) -> Option<&'s Map<String, Value>> {
+    match schema {
+        Value::Object(map) => Some(map),
+        Value::Bool(true) => Some(empty),
+        _ => None,
+    }
+}
+
+/// The schema of every array item, when `items` is a single schema.
+fn items(schema: &Map<String, Value>) -> Option<&Value> {
+    schema.get("items").filter(|items| items.is_object() || items.is_boolean())
+}
+
+pub struct JsonSchemaDiff;
+
+impl Default for JsonSchemaDiff {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonSchemaDiff {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn create_schema_diff_tool() -> Tool {
+        Tool {
+            name: "json-schema-diff".to_string(),
+            description: "Compare two versions of a JSON Schema and classify each change as compatible or breaking (removed or newly required fields, narrowed types, tightened enums and bounds), for API governance checks.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "old_file": {
+                        "type": "string",
+                        "description": "The current schema, or use old_content"
+                    },
+                    "old_content": {
+                        "description": "The current schema inline"
+                    },
+                    "new_file": {
+                        "type": "string",
+                        "description": "The proposed schema, or use new_content"
+                    },
+                    "new_content": {
+                        "description": "The proposed schema inline"
+                    }
+                }
+            })
+        }
+    }
+
+    async fn handle_schema_diff(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let (old_name, old) = match side_document(args, "old", USAGE)? {
+            Ok(side) => side,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+        let (new_name, new) = match side_document(args, "new", USAGE)? {
+            Ok(side) => side,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+
+        let mut schema_diff = SchemaDiff { old_root: &old, new_root: &new, changes: Vec::new() };
+        schema_diff.compare(&old, &new, "$", &mut HashSet::new());
+        let changes = schema_diff.changes;
+        let count = |compatibility| changes.iter().filter(|c| c.compatibility == compatibility).count();
+        let (breaking, unknown) = (count(Compatibility::Breaking), count(Compatibility::Unknown));
+        let report = json!({
+            "compatible": breaking == 0 && unknown == 0,
+            "breaking": breaking,
+            "compatible_changes": count(Compatibility::Compatible),
+            "unknown": unknown,
+            "changes": changes.iter().map(|change| {
+                let mut entry = Map::new();
+                entry.insert("path".to_string(), json!(change.path));
+                entry.insert("change".to_string(), json!(change.change));
+                entry.insert("compatibility".to_string(), json!(change.compatibility.name()));
+                if let Some(old) = &change.old {
+                    entry.insert("old".to_string(), old.clone());
+                }
+                if let Some(new) = &change.new {
+                    entry.insert("new".to_string(), new.clone());
+                }
+                Value::Object(entry)
+            }).collect::<Vec<_>>(),
+        });
+
+        let verdict = match (breaking, unknown) {
+            (0, 0) => "is backward compatible with".to_string(),
+            (0, unknown) => format!("has {} change(s) to review against", unknown),
+            (breaking, _) => format!("has {} breaking change(s) against", breaking),
+        };
+        Ok(ToolResult::success(format!(
+            "{} {} {}:\n\n{}", new_name, verdict, old_name, serde_json::to_string_pretty(&report)?
+        )))
+    }
+}
+
+#[async_trait]
+impl ToolHandler for JsonSchemaDiff {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_schema_diff_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-schema-diff" => self.handle_schema_diff(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use crate::json_tools::formats::{read_document, DocumentFormat};
+use crate::json_tools::storage;
+use crate::json_tools::timewindow::parse_timestamp;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use rhai::{Dynamic, Engine, EvalAltResult, Scope, AST};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+pub const DEFAULT_MAX_STEPS: u64 = 1_000_000;
+const MAX_STEPS_LIMIT: u64 = 100_000_000;
+const DEFAULT_MAX_OUTPUT_BYTES: u64 = 10 * 1024 * 1024;
+/// Longest string a script may build, in bytes.
+const MAX_STRING_BYTES: usize = 10 * 1024 * 1024;
+/// Most elements in one array, or entries in one object, a script may build.
+const MAX_ITEMS: usize = 1_000_000;
+/// Deepest a script's function calls may nest.
+const MAX_CALL_LEVELS: usize = 32;
+
+/// A rhai engine limited to `max_steps` operations per evaluation, with caps on string,
+/// array and object sizes and on call depth, no modules and no output to stdout. It adds a
+/// `date(value, format)` function that formats RFC 3339 strings and epoch numbers with
+/// chrono's strftime syntax (RFC 3339 without a format).
+pub fn script_engine(max_steps: u64) -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(max_steps)
+        .set_max_string_size(MAX_STRING_BYTES)
+        .set_max_array_size(MAX_ITEMS)
+        .set_max_map_size(MAX_ITEMS)
+        .set_max_call_levels(MAX_CALL_LEVELS)
+        .set_max_expr_depths(64, 32)
+        // stdout carries the MCP protocol
+        .on_print(|_| {})
+        .on_debug(|_, _, _| {});
+    engine.register_fn("date", |value: Dynamic| format_date(value, None));
+    engine.register_fn("date", |value: Dynamic, format: &str| format_date(value, Some(format)));
+    engine
+}
+
+fn format_date(value: Dynamic, format: Option<&str>) -> Result<String, Box<EvalAltResult>> {
+    let json = to_json(&value).unwrap_or(Value::Null);
+    let timestamp = parse_timestamp(&json).ok_or_else(|| format!("date: {} is not a timestamp", json))?;
+    let Some(format) = format else {
+        return Ok(timestamp.to_rfc3339());
+    };
+    let mut formatted = String::new();
+    write!(formatted, "{}", timestamp.format(format)).map_err(|_| format!("date: invalid format '{}'", format))?;
+    Ok(formatted)
+}
+
+/// A JSON value as a script value: objects become maps, null becomes `()`.
+pub fn to_dynamic(value: &Value) -> Dynamic {
+    rhai::serde::to_dynamic(value).unwrap_or(Dynamic::UNIT)
+}
+
+/// A script's value as JSON. Objects come out with their keys sorted.
+pub fn to_json(value: &Dynamic) -> Result<Value, String> {
+    rhai::serde::from_dynamic(value).map_err(|e| e.to_string())
+}
+
+/// Run a compiled script, describing limit violations in terms of the sandbox.
+pub fn evaluate(engine: &Engine, scope: &mut Scope, ast: &AST, max_steps: u64) -> Result<Value, String> {
+    let value = engine.eval_ast_with_scope::<Dynamic>(scope, ast).map_err(|e| match *e {
+        EvalAltResult::ErrorTooManyOperations(..) => format!(
+            "stopped after {} steps (max_steps). Simplify it or raise max_steps", max_steps
+        ),
+        EvalAltResult::ErrorDataTooLarge(ref what, ..) => format!(
+            "stopped: {} too large (strings are limited to {} bytes, arrays and objects to {} items)",
+            what, MAX_STRING_BYTES, MAX_ITEMS
+        ),
+        EvalAltResult::ErrorStackOverflow(..) => format!("stopped: function calls nested deeper than {}", MAX_CALL_LEVELS),
+        e => format!("error: {}", e),
+    })?;
+    to_json(&value).map_err(|e| format!("value is not JSON: {}", e))
+}
+
+pub struct JsonScript;
+
+impl Default for JsonScript {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonScript {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn create_script_tool() -> Tool {
+        Tool {
+            name: "json-script".to_string(),
+            description: "Transform JSON with a small sandboxed rhai script for what JSONPath cannot express: conditional restructuring, computed fields, regrouping. The input is available as `data`, and the value of the last expression is the result; runs with step, memory and output-size limits.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "JSON (or TOML/MessagePack/CBOR) file given to the script as `data` (use this or 'data')"
+                    },
+                    "data": {
+                        "description": "Inline input given to the script as `data`, instead of file_path"
+                    },
+                    "expression": {
+                        "type": "string",
+                        "description": "One expression whose value is the result, e.g. 'data.users.filter(|u| u.active).map(|u| u.email)' (use this or 'script')"
+                    },
+                    "script": {
+                        "type": "string",
+                        "description": "Statements whose last expression is the result, e.g. 'let out = []; for u in data.users { if u.age > 25 { out.push(#{name: u.name, senior: u.age >= 65}); } } out'"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "File to write the result to as pretty JSON. The result is returned when omitted"
+                    },
+                    "max_steps": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": MAX_STEPS_LIMIT,
+                        "description": "Operations the script may execute before it is stopped (default: 1000000)"
+                    },
+                    "max_output_bytes": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": "Largest result accepted, in bytes of JSON (default: 10485760)"
+                    }
+                }
+            })
+        }
+    }
+
+    async fn handle_script(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let (source, is_expression) = match (
+            args.get("expression").and_then(|v| v.as_str()),
+            args.get("script").and_then(|v| v.as_str()),
+        ) {
+            (Some(expression), None) => (expression, true),
+            (None, Some(script)) => (script, false),
+            _ => return Ok(ToolResult::error(
+                "Provide exactly one of 'expression' or 'script'. Usage example:\n{\n  \"file_path\": \"./users.json\",\n  \"expression\": \"data.users.filter(|u| u.active).map(|u| u.email)\"\n}".to_string()
+            )),
+        };
+        let data = match (args.get("file_path").and_then(|v| v.as_str()), args.get("data")) {
+            (Some(path), None) => read_document(path, DocumentFormat::from_path(path))?,
+            (None, Some(data)) => data.clone(),
+            _ => return Ok(ToolResult::error("Provide exactly one of 'file_path' or 'data' as the script's input".to_string())),
+        };
+        let max_steps = args.get("max_steps").and_then(|v| v.as_u64()).unwrap_or(DEFAULT_MAX_STEPS).clamp(1, MAX_STEPS_LIMIT);
+        let max_output_bytes = args.get("max_output_bytes").and_then(|v| v.as_u64()).unwrap_or(DEFAULT_MAX_OUTPUT_BYTES);
+
+        let engine = script_engine(max_steps);
+        let compiled = if is_expression { engine.compile_expression(source) } else { engine.compile(source) };
+        let ast = match compiled {
+            Ok(ast) => ast,
+            Err(e) => return Ok(ToolResult::error(format!("Script syntax error: {}", e))),
+        };
+        let mut scope = Scope::new();
+        scope.push_dynamic("data", to_dynamic(&data));
+        let result = match evaluate(&engine, &mut scope, &ast, max_steps) {
+            Ok(result) => result,
+            Err(message) => return Ok(ToolResult::error(format!("Script {}", message))),
+        };
+
+        let output = serde_json::to_string_pretty(&result)?;
+        if output.len() as u64 > max_output_bytes {
+            return Ok(ToolResult::error(format!(
+                "Script result is {} bytes, over max_output_bytes ({}). Write less, or raise the limit", output.len(), max_output_bytes
+            )));
+        }
+        match args.get("output_path").and_then(|v| v.as_str()) {
+            Some(output_path) => {
+                storage::create_parent_dirs(output_path)?;
+                storage::write(output_path, output.as_bytes())?;
+                Ok(ToolResult::success(format!("Wrote script result to '{}' ({} bytes)", output_path, output.len())))
+            }
+            None => Ok(ToolResult::success(format!("Script result:\n\n{}", output))),
+        }
+    }
+}
+
+#[async_trait]
+impl ToolHandler for JsonScript {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_script_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-script" => self.handle_script(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+/// Placeholder written in place of values that must not reach the model's context.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// JSON Schema for the `expand_env` argument shared by json-read and json-write.
+pub fn expand_env_schema() -> Value {
+    json!({
+        "description": "Expand ${VAR} and ${VAR:-default} placeholders in string values from the environment variables the server's config allows: true for all of them, or an array of names to narrow them further (a trailing '*' allows a prefix, e.g. 'APP_*'). Write '$${' for a literal '${'",
+        "oneOf": [
+            {"type": "boolean"},
+            {"type": "array", "items": {"type": "string"}}
+        ]
+    })
+}
+
+/// The `environment` section of the `--config` file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnvironmentConfig {
+    /// Variables `expand_env` may read (a trailing `*` allows a prefix). Tool arguments can
+    /// only narrow this list; when it is empty, expansion is refused.
+    pub expand: Vec<String>,
+}
+
+static EXPAND_ALLOWLIST: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+impl EnvironmentConfig {
+    /// Make this the allowlist every tool uses, replacing any installed before.
+    pub fn install(self) {
+        *EXPAND_ALLOWLIST.write().unwrap_or_else(|e| e.into_inner()) = self.expand;
+    }
+}
+
+/// What expansion does with a variable whose name or value looks like a secret.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SecretValues {
+    /// Put [`REDACTED`] in its place, for values returned to the model.
+    Redact,
+    /// Fail, for values that would be written to a file.
+    Refuse,
+}
+
+/// Expands `${VAR}` placeholders in string values from the environment variables both the
+/// server's allowlist and the call allow.
+#[derive(Debug)]
+pub struct EnvExpansion {
+    allowlist: Vec<String>,
+    /// The names the call narrowed expansion to, if any.
+    requested: Option<Vec<String>>,
+}
+
+impl EnvExpansion {
+    /// Read the `expand_env` argument. Returns `None` when expansion was not requested.
+    pub fn from_args(args: &HashMap<String, Value>) -> anyhow::Result<Option<Self>> {
+        let requested = match args.get("expand_env") {
+            None | Some(Value::Bool(false)) => return Ok(None),
+            Some(Value::Bool(true)) => None,
+            Some(value) => Some(value.as_array()
+                .and_then(|names| names.iter().map(|n| n.as_str().map(str::to_string)).collect::<Option<Vec<_>>>())
+                .ok_or_else(|| anyhow::anyhow!(
+                    "expand_env must be true or an array of environment variable names, e.g. [\"HOME\", \"APP_*\"]"
+                ))?),
+        };
+        let allowlist = EXPAND_ALLOWLIST.read().unwrap_or_else(|e| e.into_inner()).clone();
+        if allowlist.is_empty() {
+            anyhow::bail!(
+                "expand_env is disabled on this server. List the environment variables tools may expand in environment.expand in the --config file"
+            );
+        }
+        Ok(Some(Self { allowlist, requested }))
+    }
+
+    fn allows(&self, name: &str) -> bool {
+        let matches = |list: &[String]| list.iter().any(|allowed| match allowed.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => allowed == name,
+        });
+        matches(&self.allowlist) && self.requested.as_deref().map_or(true, matches)
+    }
+
+    /// Expand placeholders in every string of `value`, returning the number of
+    /// placeholders replaced. Variables whose name or value looks like a secret are
+    /// handled by `secrets`.
+    pub fn expand(&self, value: &mut Value, secrets: SecretValues) -> anyhow::Result<usize> {
+        match value {
+            Value::String(s) if s.contains("${") => {
+                let (expanded, count) = self.expand_str(s, secrets)?;
+                *s = expanded;
+                Ok(count)
+            }
+            Value::Array(items) => items.iter_mut().map(|item| self.expand(item, secrets)).sum(),
+            Value::Object(obj) => obj.values_mut().map(|item| self.expand(item, secrets)).sum(),
+            _ => Ok(0),
+        }
+    }
+
+    fn expand_str(&self, text: &str, secrets: SecretValues) -> anyhow::Result<(String, usize)> {
+        let mut output = String::with_capacity(text.len());
+        let mut count = 0;
+        let mut rest = text;
+
+        while let Some(start) = rest.find("${") {
+            // `$${` escapes a literal `${`
+            if rest[..start].ends_with('$') {
+                output.push_str(&rest[..start - 1]);
+                output.push_str("${");
+                rest = &rest[start + 2..];
+                continue;
+            }
+            output.push_str(&rest[..start]);
+            let end = rest[start..].find('}')
+                .ok_or_else(|| anyhow::anyhow!("Unterminated placeholder in '{}'", text))? + start;
+            let placeholder = &rest[start + 2..end];
+            let (name, default) = match placeholder.split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (placeholder, None),
+            };
+
+            if !self.allows(name) {
+                let mut allowed = format!("server: {}", self.allowlist.join(", "));
+                if let Some(requested) = &self.requested {
+                    allowed.push_str(&format!("; requested: {}", requested.join(", ")));
+                }
+                anyhow::bail!("Environment variable '{}' is not in the expand_env allowlist ({})", name, allowed);
+            }
+            let resolved = match (std::env::var(name), default) {
+                (Ok(value), _) => value,
+                (Err(_), Some(default)) => default.to_string(),
+                (Err(_), None) => anyhow::bail!(
+                    "Environment variable '{}' is not set and the placeholder has no default (use ${{{}:-default}})",
+                    name, name
+                ),
+            };
+            if is_secret_name(name) || looks_like_secret(&resolved) {
+                match secrets {
+                    SecretValues::Redact => output.push_str(REDACTED),
+                    SecretValues::Refuse => anyhow::bail!(
+                        "Environment variable '{}' looks like a secret and is not written to files", name
+                    ),
+                }
+            } else {
+                output.push_str(&resolved);
+            }
+            count += 1;
+            rest = &rest[end + 1..];
+        }
+
+        output.push_str(rest);
+        Ok((output, count))
+    }
+}
+
+/// Names such as `DB_PASSWORD` or `GITHUB_TOKEN` that conventionally hold secrets.
+pub fn is_secret_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    ["password", "passwd", "secret", "token", "api_key", "apikey", "private_key", "credential"]
+        .iter()
+        .any(|marker| name.contains(marker))
+}
+
+/// Values with the shape of well-known credentials (cloud keys, bearer tokens, PEM keys).
+pub fn looks_like_secret(value: &str) -> bool {
+    const PREFIXES: &[&str] = &["sk-", "sk_live_", "rk_live_", "ghp_", "gho_", "github_pat_", "xoxb-", "xoxp-", "AKIA", "ASIA", "AIza", "glpat-"];
+    value.contains("-----BEGIN") && value.contains("PRIVATE KEY")
+        || PREFIXES.iter().any(|prefix| value.starts_with(prefix) && value.len() >= prefix.len() + 12)
+}
+
+/// JSON Schema for the `redact` argument shared by the read and query tools.
+pub fn redact_schema() -> Value {
+    json!({
+        "description": "Mask secrets and PII in the output: true enables built-in detection (API keys, tokens, private keys, emails, SSNs, and keys named like password/secret/token); an array of key names additionally masks values under those keys",
+        "oneOf": [
+            {"type": "boolean"},
+            {"type": "array", "items": {"type": "string"}}
+        ]
+    })
+}
+
+/// Masks values that look like secrets or personal data before they are returned.
+#[derive(Debug)]
+pub struct Redactor {
+    keys: Vec<String>,
+}
+
+impl Redactor {
+    /// Read the `redact` argument. Returns `None` when redaction was not requested.
+    pub fn from_args(args: &HashMap<String, Value>) -> anyhow::Result<Option<Self>> {
+        match args.get("redact") {
+            None | Some(Value::Bool(false)) => Ok(None),
+            Some(Value::Bool(true)) => Ok(Some(Self { keys: Vec::new() })),
+            Some(Value::Array(keys)) => keys.iter()
+                .map(|k| k.as_str().map(str::to_ascii_lowercase))
+                .collect::<Option<Vec<_>>>()
+                .map(|keys| Some(Self { keys }))
+                .ok_or_else(|| anyhow::anyhow!("redact must be true or an array of key names, e.g. [\"password\", \"ssn\"]")),
+            Some(other) => anyhow::bail!("redact must be true or an array of key names, got: {}", other),
+        }
+    }
+
+    fn is_sensitive_key(&self, key: &str) -> bool {
+        is_secret_name(key) || self.keys.iter().any(|k| k.eq_ignore_ascii_case(key))
+    }
+
+    /// Redact `value` in place, returning the number of values or substrings masked.
+    pub fn redact(&self, value: &mut Value) -> usize {
+        match value {
+            Value::String(s) => {
+                let (masked, count) = redact_text(s);
+                if count > 0 {
+                    *s = masked;
+                }
+                count
+            }
+            Value::Array(items) => items.iter_mut().map(|item| self.redact(item)).sum(),
+            Value::Object(obj) => obj.iter_mut()
+                .map(|(key, item)| {
+                    if self.is_sensitive_key(key) && !item.is_null() && !item.is_object() && !item.is_array() {
+                        *item = Value::String(REDACTED.to_string());
+                        1
+                    } else {
+                        self.redact(item)
+                    }
+                })
+                .sum(),
+            _ => 0,
+        }
+    }
+}
+
+fn secret_patterns() -> &'static [Regex] {
+    static PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        [
+            // Private keys
+            r"-----BEGIN [A-Z ]*PRIVATE KEY-----[\s\S]*?-----END [A-Z ]*PRIVATE KEY-----",
+            // Provider API keys and tokens
+            r"\b(?:sk|rk|pk)_(?:live|test)_[A-Za-z0-9]{16,}",
+            r"\bsk-[A-Za-z0-9_-]{20,}",
+            r"\bgh[pousr]_[A-Za-z0-9]{30,}",
+            r"\bgithub_pat_[A-Za-z0-9_]{30,}",
+            r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b",
+            r"\bAIza[0-9A-Za-z_-]{35}",
+            r"\bxox[abprs]-[A-Za-z0-9-]{10,}",
+            r"\bglpat-[A-Za-z0-9_-]{20,}",
+            r"(?i)\bbearer\s+[A-Za-z0-9._~+/-]{16,}=*",
+            // Email addresses
+            r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b",
+            // US social security numbers
+            r"\b\d{3}-\d{2}-\d{4}\b",
+        ]
+        .iter()
+        .map(|pattern| Regex::new(pattern).expect("built-in redaction patterns are valid"))
+        .collect()
+    })
+}
+
+fn redact_text(text: &str) -> (String, usize) {
+    let mut output = text.to_string();
+    let mut count = 0;
+    for pattern in secret_patterns() {
+        let matches = pattern.find_iter(&output).count();
+        if matches > 0 {
+            count += matches;
+            output = pattern.replace_all(&output, REDACTED).into_owned();
+        }
+    }
+    (output, count)
+}
+
+/// Apply the optional `redact` argument to output values, returning the number of
+/// masked values when redaction was requested.
+pub fn redact_output(args: &HashMap<String, Value>, values: &mut Value) -> anyhow::Result<Option<usize>> {
+    Ok(Redactor::from_args(args)?.map(|redactor| redactor.redact(values)))
+}
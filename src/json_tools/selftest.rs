@@ -0,0 +1,206 @@
+use crate::json_tools::handler::JsonToolsHandler;
+use crate::mcp::protocol::ToolCall;
+use crate::mcp::server::ToolHandler;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Result of exercising one tool.
+#[derive(Debug)]
+pub struct SelfTestOutcome {
+    pub tool: String,
+    pub passed: bool,
+    pub elapsed: Duration,
+    /// First line of the tool's response, or the reason it failed.
+    pub detail: String,
+}
+
+/// Scratch directory holding the fixtures, removed when the self-test ends.
+struct Scratch(PathBuf);
+
+impl Scratch {
+    fn path(&self, name: &str) -> String {
+        self.0.join(name).to_string_lossy().into_owned()
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+const FIXTURES: &[(&str, &str)] = &[
+    ("data.json", r#"{"users": [{"id": 1, "name": "ann", "age": 31, "active": true}, {"id": 2, "name": "bob", "age": 25, "active": false}], "config": {"debug": false}}"#),
+    ("orders.json", r#"[{"id": 1, "user_id": 1, "total": 10}, {"id": 2, "user_id": 2, "total": 5}, {"id": 3, "user_id": 1, "total": 7}]"#),
+    ("events.ndjson", "{\"ts\": \"2025-01-01T00:00:00Z\", \"kind\": \"click\"}\n{\"ts\": \"2025-01-02T00:00:00Z\", \"kind\": \"view\"}\n{\"ts\": \"2025-01-02T00:00:00Z\", \"kind\": \"view\"}\n"),
+    ("people.csv", "name,age\nann,31\nbob,25\n"),
+    ("catalog.xml", "<catalog><book id=\"1\"><title>Rust</title></book></catalog>"),
+    ("config.j2", "{\"name\": {{ name|tojson }}}"),
+    ("message.json", r#"{"payload": "{\"id\": 1}", "attachment": "iVBORw0KGgoAAQI="}"#),
+    ("snapshots/a.json", r#"{"a": 1}"#),
+    ("person.jsonld", r#"{"@context": {"@vocab": "http://schema.org/", "knows": {"@type": "@id"}}, "@id": "http://example.org/ann", "name": "Ann", "knows": "http://example.org/bob"}"#),
+    ("places.geojson", r#"{"type": "FeatureCollection", "features": [{"type": "Feature", "geometry": {"type": "Point", "coordinates": [2.35, 48.85]}, "properties": {"name": "Paris"}}, {"type": "Feature", "geometry": {"type": "Point", "coordinates": [-0.13, 51.51]}, "properties": {"name": "London"}}]}"#),
+    ("openapi.json", r##"{"openapi": "3.0.3", "paths": {"/users": {"post": {"operationId": "createUser", "requestBody": {"content": {"application/json": {"schema": {"$ref": "#/components/schemas/User"}}}}}}}, "components": {"schemas": {"User": {"type": "object", "required": ["name"], "properties": {"name": {"type": "string"}}}}}}"##),
+];
+
+/// Identifiers returned by one case and used by later ones: an argument whose value is
+/// `<name>` receives the value that followed `name: ` in the latest response.
+const SESSION_ID: &str = "<session_id>";
+const JOB_ID: &str = "<job_id>";
+const CAPTURED: &[&str] = &["session_id", "job_id"];
+
+/// Calls run in order against fixtures in a scratch directory. Every call reads or writes
+/// only inside that directory, so the self-test leaves nothing behind.
+fn cases(scratch: &Scratch) -> Vec<(&'static str, Value)> {
+    let p = |name: &str| scratch.path(name);
+    #[allow(unused_mut)]
+    let mut cases = vec![
+        ("json-help", json!({})),
+        ("json-read", json!({"file_path": p("data.json"), "query": "$.users[*].name"})),
+        ("json-write", json!({"file_path": p("out/written.json"), "data": {"ok": true}, "create_dirs": true})),
+        ("json-validate", json!({"file_path": p("data.json")})),
+        ("json-format", json!({"file_path": p("data.json"), "output_path": p("out/formatted.json")})),
+        ("json-query", json!({"file_path": p("data.json"), "query": "$.users[?(@.age > 30)]", "include_paths": true})),
+        ("json-validate-query", json!({"query": "$.users[?(@.age > 30)]"})),
+        ("json-extract", json!({"file_path": p("data.json"), "query": "$.users[*]", "output_path": p("out/users.json")})),
+        ("json-split", json!({"file_path": p("orders.json"), "max_records": 2, "output_dir": p("out/shards")})),
+        ("json-concat", json!({"file_paths": [p("orders.json"), p("orders.json")], "output_path": p("out/concat.json")})),
+        ("csv-to-json", json!({"file_path": p("people.csv"), "output_path": p("out/people.json")})),
+        ("xml-to-json", json!({"file_path": p("catalog.xml"), "output_path": p("out/catalog.json")})),
+        ("json-to-xml", json!({"file_path": p("out/catalog.json")})),
+        ("json-sql", json!({"file_path": p("data.json"), "query": "SELECT name FROM $.users WHERE age > 30 ORDER BY name"})),
+        ("json-join", json!({"left_file": p("orders.json"), "right_file": p("data.json"), "right_array_path": "$.users", "left_key": "user_id", "right_key": "id"})),
+        ("json-group", json!({"file_path": p("orders.json"), "group_by": "user_id", "aggregates": [{"op": "sum", "field": "total"}]})),
+        ("json-dedupe", json!({"file_path": p("events.ndjson"), "output_path": p("out/unique.ndjson")})),
+        ("json-template", json!({"template_path": p("config.j2"), "data": {"name": "api"}, "output_path": p("out/config.json")})),
+        ("json-script", json!({"file_path": p("data.json"), "expression": "data.users.map(|u| u.name)"})),
+        ("json-hash", json!({"file_paths": [p("data.json"), p("out/formatted.json")]})),
+        ("json-transaction", json!({"operations": [{"file_path": p("out/written.json"), "action": "merge_patch", "data": {"ok": false}}]})),
+        ("json-snapshot", json!({"directory": p("snapshots")})),
+        ("json-restore", json!({"directory": p("snapshots"), "dry_run": true})),
+        ("json-batch", json!({"steps": [{"tool": "json-read", "arguments": {"file_path": p("data.json")}}]})),
+        ("json-list-files", json!({"directory": scratch.0.to_string_lossy()})),
+        ("json-unescape", json!({"file_path": p("message.json"), "pointer": "/payload", "output_path": p("out/message.json")})),
+        ("json-escape", json!({"content": {"id": 1}})),
+        ("json-tail", json!({"file_path": p("events.ndjson"), "count": 2})),
+        ("json-children", json!({"file_path": p("data.json"), "path": "$.users"})),
+        ("json-update", json!({"file_path": p("out/users.json"), "query": "$[*]", "field": "checked", "value": true})),
+        ("json-normalize-dates", json!({"file_path": p("data.json"), "dry_run": true})),
+        ("json-convert-units", json!({"file_path": p("data.json"), "conversions": {"$.users[*].age": "s->min"}, "dry_run": true})),
+        ("json-migrate", json!({"file_path": p("data.json"), "migration": [{"op": "rename", "path": "/users/*/name", "to": "full_name"}], "dry_run": true})),
+        ("json-equal", json!({"left_file": p("data.json"), "right_content": {"users": []}})),
+        ("json-merge3", json!({"base_file": p("data.json"), "ours_file": p("data.json"), "theirs_file": p("data.json")})),
+        ("json-diff", json!({"left_file": p("data.json"), "right_content": {"users": []}})),
+        ("json-schema-diff", json!({"old_content": {"type": "object"}, "new_content": {"type": "object", "required": ["id"]}})),
+        ("json-openapi-validate", json!({"spec_path": p("openapi.json"), "operation_id": "createUser", "content": {"name": "ann"}})),
+        ("json-ld-expand", json!({"file_path": p("person.jsonld")})),
+        ("json-ld-compact", json!({"file_path": p("person.jsonld"), "context": {"schema": "http://schema.org/"}})),
+        ("json-ld-flatten", json!({"file_path": p("person.jsonld"), "output_path": p("out/person.flat.jsonld")})),
+        ("json-geo-query", json!({"file_path": p("places.geojson"), "bbox": [2.0, 48.0, 3.0, 49.0]})),
+        ("json-codegen", json!({"target": "typescript", "file_path": p("data.json"), "output_path": p("out/data.ts")})),
+        ("json-mock", json!({"file_path": p("data.json"), "count": 3, "seed": 1})),
+        ("json-describe", json!({"file_path": p("data.json")})),
+        ("json-base64-extract", json!({"file_path": p("message.json"), "pointer": "/attachment", "output_path": p("out/attachment.png")})),
+        ("json-base64-embed", json!({"file_path": p("out/written.json"), "pointer": "/attachment", "binary_path": p("out/attachment.png")})),
+        ("json-write-begin", json!({"file_path": p("out/chunked.json")})),
+        ("json-write-chunk", json!({"session_id": SESSION_ID, "index": 0, "data": "{\"chunked\": "})),
+        ("json-write-commit", json!({"session_id": SESSION_ID, "abort": true})),
+        ("json-job-start", json!({"tool": "json-validate", "arguments": {"file_path": p("data.json")}})),
+        ("json-job-status", json!({"job_id": JOB_ID})),
+        ("json-job-result", json!({"job_id": JOB_ID, "wait_ms": 10000})),
+        ("json-check", json!({"file_path": p("data.json"), "checks": [{"path": "$.users", "op": "length_eq", "value": 2}]})),
+        ("json-server-stats", json!({})),
+    ];
+    #[cfg(feature = "git")]
+    cases.extend([
+        ("json-git-show", json!({"file_path": p("data.json"), "rev": "HEAD"})),
+        ("json-git-diff", json!({"file_path": p("data.json")})),
+        ("json-git-changes", json!({"range": "HEAD", "directory": scratch.0.to_string_lossy()})),
+    ]);
+    #[cfg(feature = "sqlite")]
+    cases.extend([
+        ("json-sqlite-load", json!({"file_path": p("data.json"), "array_path": "$.users", "database_path": p("out/users.sqlite"), "table": "users"})),
+        ("json-sqlite-query", json!({"database_path": p("out/users.sqlite"), "query": "SELECT count(*) AS users FROM users"})),
+    ]);
+    cases
+}
+
+/// Commit the fixtures to a repository in the scratch directory for the json-git-* cases.
+#[cfg(feature = "git")]
+fn commit_fixtures(root: &Path) -> anyhow::Result<()> {
+    let steps: [&[&str]; 3] = [
+        &["init", "-q"],
+        &["add", "-A"],
+        &["-c", "user.name=self-test", "-c", "user.email=self-test@localhost", "-c", "commit.gpgsign=false", "commit", "-q", "-m", "fixtures"],
+    ];
+    for args in steps {
+        let status = std::process::Command::new("git").arg("-C").arg(root).args(args).status()?;
+        anyhow::ensure!(status.success(), "git {} failed in the scratch directory", args.join(" "));
+    }
+    Ok(())
+}
+
+/// Exercise every tool the handler offers against throwaway fixtures. A tool passes when
+/// its call succeeds; tools without a case fail, so new tools must add one.
+pub async fn run_self_test(handler: &JsonToolsHandler) -> anyhow::Result<Vec<SelfTestOutcome>> {
+    let root = std::env::temp_dir().join(format!("json-mcp-self-test-{}", uuid::Uuid::new_v4()));
+    let scratch = Scratch(root);
+    for (name, content) in FIXTURES {
+        let path = scratch.0.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, content)?;
+    }
+    fs::create_dir_all(scratch.0.join("out"))?;
+    #[cfg(feature = "git")]
+    commit_fixtures(&scratch.0)?;
+
+    let mut outcomes = Vec::new();
+    let mut covered = HashSet::new();
+    let mut captured: HashMap<&str, String> = HashMap::new();
+    for (tool, mut arguments) in cases(&scratch) {
+        covered.insert(tool);
+        for name in CAPTURED {
+            if arguments[*name] == format!("<{}>", name) {
+                arguments[*name] = json!(captured.get(name).cloned().unwrap_or_default());
+            }
+        }
+        let arguments: HashMap<String, Value> = serde_json::from_value(arguments)?;
+        let started = Instant::now();
+        let result = handler.call_tool(ToolCall { name: tool.to_string(), arguments }).await;
+        let (passed, detail) = match result {
+            Ok(result) => {
+                let text = result.content.first().map(|c| c.text.as_str()).unwrap_or_default();
+                for name in CAPTURED {
+                    if let Some((_, rest)) = text.split_once(&format!("{}: ", name)) {
+                        captured.insert(name, rest.split_whitespace().next().unwrap_or_default().to_string());
+                    }
+                }
+                (result.is_error != Some(true), first_line(text, &scratch.0))
+            }
+            Err(e) => (false, first_line(&e.to_string(), &scratch.0)),
+        };
+        outcomes.push(SelfTestOutcome { tool: tool.to_string(), passed, elapsed: started.elapsed(), detail });
+    }
+
+    for tool in handler.get_tools().await? {
+        if !covered.contains(tool.name.as_str()) {
+            outcomes.push(SelfTestOutcome {
+                tool: tool.name,
+                passed: false,
+                elapsed: Duration::ZERO,
+                detail: "no self-test case".to_string(),
+            });
+        }
+    }
+    Ok(outcomes)
+}
+
+/// The first line of a response, with the scratch directory shortened for readability.
+fn first_line(text: &str, scratch: &Path) -> String {
+    text.lines().next().unwrap_or_default().replace(&*scratch.to_string_lossy(), "<scratch>")
+}
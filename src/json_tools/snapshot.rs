@@ -0,0 +1,313 @@
+use crate::json_tools::discovery::{collect_files, parse_extensions, relative_name};
+use crate::json_tools::integrity::sha256_hex;
+use crate::json_tools::paths::PathPolicy;
+use crate::json_tools::storage;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Default archive location, relative to the snapshotted directory.
+const DEFAULT_SNAPSHOT_DIR: &str = ".json-snapshots";
+const DEFAULT_EXTENSIONS: &[&str] = &["json", "ndjson", "jsonl", "toml"];
+
+pub struct JsonSnapshot {
+    paths: PathPolicy,
+}
+
+impl Default for JsonSnapshot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonSnapshot {
+    pub fn new() -> Self {
+        Self { paths: PathPolicy::default() }
+    }
+
+    /// Keep the archive and restored files within `paths`' allowed roots.
+    pub fn with_path_policy(paths: PathPolicy) -> Self {
+        Self { paths }
+    }
+
+    fn create_snapshot_tool() -> Tool {
+        Tool {
+            name: "json-snapshot".to_string(),
+            description: "Capture every JSON file under a directory into a content-addressed archive with a manifest, as a safety net before bulk edits. Restore it later with json-restore.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "directory": {
+                        "type": "string",
+                        "description": "Directory whose files are captured (recursively)"
+                    },
+                    "snapshot_dir": {
+                        "type": "string",
+                        "description": "Where snapshots are stored (default: '<directory>/.json-snapshots'). Identical files are stored once across snapshots"
+                    },
+                    "name": {
+                        "type": "string",
+                        "description": "Optional label appended to the snapshot id, e.g. 'before-rename'"
+                    },
+                    "extensions": {
+                        "type": "array",
+                        "items": {"type": "string"},
+                        "description": "File extensions to capture (default: json, ndjson, jsonl, toml)"
+                    },
+                    "list": {
+                        "type": "boolean",
+                        "description": "List existing snapshots instead of taking a new one (default: false)"
+                    }
+                },
+                "required": ["directory"]
+            })
+        }
+    }
+
+    fn create_restore_tool() -> Tool {
+        Tool {
+            name: "json-restore".to_string(),
+            description: "Restore a directory from a snapshot taken with json-snapshot. Every archived file is verified before anything is written.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "directory": {
+                        "type": "string",
+                        "description": "Directory that was snapshotted"
+                    },
+                    "snapshot_dir": {
+                        "type": "string",
+                        "description": "Where snapshots are stored (default: '<directory>/.json-snapshots')"
+                    },
+                    "snapshot": {
+                        "type": "string",
+                        "description": "Snapshot id to restore (default: the most recent)"
+                    },
+                    "prune": {
+                        "type": "boolean",
+                        "description": "Also delete matching files created since the snapshot (default: false)"
+                    },
+                    "dry_run": {
+                        "type": "boolean",
+                        "description": "Report what would change without writing (default: false)"
+                    }
+                },
+                "required": ["directory"]
+            })
+        }
+    }
+
+    async fn handle_snapshot(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let directory = args.get("directory")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("directory is required. Usage example:\n{{\n  \"directory\": \"./config\",\n  \"name\": \"before-refactor\"\n}}"))?;
+        let root = Path::new(directory);
+        let snapshot_dir = snapshot_dir(args, root);
+
+        if args.get("list").and_then(|v| v.as_bool()).unwrap_or(false) {
+            let snapshots: Vec<Value> = list_manifests(&snapshot_dir)?.into_iter()
+                .map(|(_, manifest)| json!({
+                    "id": manifest["id"],
+                    "created": manifest["created"],
+                    "files": manifest["files"].as_array().map_or(0, |f| f.len()),
+                }))
+                .collect();
+            return Ok(ToolResult::success(format!(
+                "{} snapshot(s) in '{}':\n\n{}",
+                snapshots.len(), snapshot_dir.display(), serde_json::to_string_pretty(&snapshots)?
+            )));
+        }
+
+        if !root.is_dir() {
+            return Ok(ToolResult::error(format!("'{}' is not a directory", directory)));
+        }
+        let extensions = parse_extensions(args.get("extensions"), DEFAULT_EXTENSIONS)?;
+        let objects = self.paths.resolve_derived(&snapshot_dir.join("objects"))?;
+        fs::create_dir_all(&objects)
+            .map_err(|e| anyhow::anyhow!("Failed to create '{}': {}", objects.display(), e))?;
+
+        let mut files = collect_files(root, true, &extensions, &|dir| dir == snapshot_dir)?;
+        files.sort();
+
+        let mut entries = Vec::with_capacity(files.len());
+        let (mut total_bytes, mut stored) = (0u64, 0usize);
+        for path in &files {
+            let content = fs::read(path)
+                .map_err(|e| anyhow::anyhow!("Failed to read file '{}': {}", path.display(), e))?;
+            let hash = sha256_hex(&content);
+            let object = objects.join(&hash);
+            if !object.exists() {
+                fs::write(&object, &content)
+                    .map_err(|e| anyhow::anyhow!("Failed to write '{}': {}", object.display(), e))?;
+                stored += 1;
+            }
+            total_bytes += content.len() as u64;
+            entries.push(json!({
+                "path": relative_name(root, path),
+                "sha256": hash,
+                "bytes": content.len(),
+            }));
+        }
+
+        let created = chrono::Utc::now();
+        let mut id = created.format("%Y%m%dT%H%M%S%.3fZ").to_string();
+        if let Some(name) = args.get("name").and_then(|v| v.as_str()) {
+            let label: String = name.chars()
+                .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+                .collect();
+            id = format!("{}-{}", id, label);
+        }
+        let manifest = json!({
+            "id": id,
+            "created": created.to_rfc3339(),
+            "directory": directory,
+            "extensions": extensions,
+            "files": entries,
+        });
+        let manifest_path = self.paths.resolve_derived(&snapshot_dir.join(format!("{}.manifest.json", id)))?;
+        fs::write(&manifest_path, serde_json::to_vec_pretty(&manifest)?)
+            .map_err(|e| anyhow::anyhow!("Failed to write manifest '{}': {}", manifest_path.display(), e))?;
+
+        Ok(ToolResult::success(format!(
+            "Snapshot '{}' captured {} file(s) ({} bytes, {} new object(s)) from '{}' into '{}'",
+            id, files.len(), total_bytes, stored, directory, snapshot_dir.display()
+        )))
+    }
+
+    async fn handle_restore(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let directory = args.get("directory")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("directory is required. Usage example:\n{{\n  \"directory\": \"./config\",\n  \"snapshot\": \"20240101T120000.000Z-before-refactor\"\n}}"))?;
+        let root = Path::new(directory);
+        let snapshot_dir = snapshot_dir(args, root);
+        let prune = args.get("prune").and_then(|v| v.as_bool()).unwrap_or(false);
+        let dry_run = args.get("dry_run").and_then(|v| v.as_bool()).unwrap_or(false);
+
+        let manifests = list_manifests(&snapshot_dir)?;
+        let manifest = match args.get("snapshot").and_then(|v| v.as_str()) {
+            Some(id) => manifests.into_iter().find(|(_, m)| m["id"] == id).map(|(_, m)| m),
+            None => manifests.into_iter().last().map(|(_, m)| m),
+        };
+        let Some(manifest) = manifest else {
+            return Ok(ToolResult::error(format!(
+                "No matching snapshot in '{}'. Use json-snapshot with list: true to see available snapshots",
+                snapshot_dir.display()
+            )));
+        };
+
+        // Verify every archived object before touching the directory
+        let objects = self.paths.resolve_derived(&snapshot_dir.join("objects"))?;
+        let mut restores = Vec::new();
+        for entry in manifest["files"].as_array().into_iter().flatten() {
+            let (Some(relative), Some(hash)) = (entry["path"].as_str(), entry["sha256"].as_str()) else {
+                return Ok(ToolResult::error("Snapshot manifest is malformed: every file needs 'path' and 'sha256'".to_string()));
+            };
+            if Path::new(relative).components().any(|c| !matches!(c, std::path::Component::Normal(_))) {
+                return Ok(ToolResult::error(format!("Snapshot manifest contains an unsafe path: '{}'", relative)));
+            }
+            if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b)) {
+                return Ok(ToolResult::error(format!("Snapshot manifest has an invalid sha256 for '{}': '{}'", relative, hash)));
+            }
+            let destination = match self.paths.resolve_derived(&root.join(relative)) {
+                Ok(destination) => destination,
+                Err(e) => return Ok(ToolResult::error(format!("Cannot restore '{}': {}; nothing was restored", relative, e))),
+            };
+            let content = fs::read(objects.join(hash))
+                .map_err(|e| anyhow::anyhow!("Snapshot object {} for '{}' is missing: {}", hash, relative, e))?;
+            if sha256_hex(&content) != hash {
+                return Ok(ToolResult::error(format!(
+                    "Snapshot object {} for '{}' is corrupted; nothing was restored", hash, relative
+                )));
+            }
+            restores.push((destination, content));
+        }
+
+        let snapshot_files: HashSet<PathBuf> = restores.iter().map(|(path, _)| path.clone()).collect();
+        let mut changed = Vec::new();
+        let mut unchanged = 0;
+        for (path, content) in &restores {
+            if fs::read(path).ok().as_deref() == Some(content.as_slice()) {
+                unchanged += 1;
+            } else {
+                changed.push(relative_name(root, path));
+                if !dry_run {
+                    write_atomically(path, content)?;
+                }
+            }
+        }
+
+        let mut removed = Vec::new();
+        if prune && root.is_dir() {
+            // Prune what the snapshot would have captured unless told otherwise
+            let extensions = parse_extensions(args.get("extensions").or(manifest.get("extensions")), DEFAULT_EXTENSIONS)?;
+            for path in collect_files(root, true, &extensions, &|dir| dir == snapshot_dir)? {
+                if !snapshot_files.contains(&path) {
+                    removed.push(relative_name(root, &path));
+                    if !dry_run {
+                        fs::remove_file(&path)
+                            .map_err(|e| anyhow::anyhow!("Failed to remove '{}': {}", path.display(), e))?;
+                    }
+                }
+            }
+        }
+
+        Ok(ToolResult::success(format!(
+            "{} snapshot '{}' to '{}': {} file(s) restored, {} unchanged, {} removed\n\n{}",
+            if dry_run { "Dry run: would restore" } else { "Restored" },
+            manifest["id"].as_str().unwrap_or_default(), directory, changed.len(), unchanged, removed.len(),
+            serde_json::to_string_pretty(&json!({"restored": changed, "removed": removed}))?
+        )))
+    }
+}
+
+fn snapshot_dir(args: &HashMap<String, Value>, root: &Path) -> PathBuf {
+    args.get("snapshot_dir")
+        .and_then(|v| v.as_str())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| root.join(DEFAULT_SNAPSHOT_DIR))
+}
+
+/// Manifests in `snapshot_dir`, oldest first (ids start with a UTC timestamp).
+fn list_manifests(snapshot_dir: &Path) -> anyhow::Result<Vec<(PathBuf, Value)>> {
+    if !snapshot_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut manifests = Vec::new();
+    for entry in fs::read_dir(snapshot_dir)? {
+        let path = entry?.path();
+        if path.to_string_lossy().ends_with(".manifest.json") {
+            let manifest: Value = serde_json::from_slice(&fs::read(&path)?)
+                .map_err(|e| anyhow::anyhow!("Invalid snapshot manifest '{}': {}", path.display(), e))?;
+            manifests.push((path, manifest));
+        }
+    }
+    manifests.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(manifests)
+}
+
+fn write_atomically(path: &Path, content: &[u8]) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| anyhow::anyhow!("Failed to create directories for '{}': {}", path.display(), e))?;
+    }
+    storage::replace_file(path, content, "restore")
+}
+
+#[async_trait]
+impl ToolHandler for JsonSnapshot {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_snapshot_tool(), Self::create_restore_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-snapshot" => self.handle_snapshot(&tool_call.arguments).await,
+            "json-restore" => self.handle_restore(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
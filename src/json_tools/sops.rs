@@ -0,0 +1,172 @@
+use serde_json::Value;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// How much of the end of a file is searched for SOPS metadata before streaming it; SOPS
+/// writes the `sops` section last.
+const METADATA_TAIL: u64 = 64 * 1024;
+
+/// The `--input-type`/`--output-type` sops uses for `file_path`, or `None` for formats
+/// sops cannot handle.
+fn sops_type(file_path: &str) -> Option<&'static str> {
+    let ext = Path::new(file_path).extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase());
+    match ext.as_deref() {
+        Some("yaml" | "yml") => Some("yaml"),
+        Some("toml" | "msgpack" | "mpk" | "cbor") => None,
+        _ => Some("json"),
+    }
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+/// The `sops` section of `content` if it is a SOPS document: JSON or YAML with a top-level
+/// `sops` section holding the MAC.
+fn metadata(file_path: &str, content: &[u8]) -> Option<Value> {
+    if !contains(content, b"sops") {
+        return None;
+    }
+    let document: Value = match sops_type(file_path)? {
+        "yaml" => serde_yaml::from_slice(content).ok()?,
+        _ => serde_json::from_slice(content).ok()?,
+    };
+    let sops = document.get("sops")?;
+    sops.get("mac").is_some().then(|| sops.clone())
+}
+
+/// Whether the end of `file_path` looks like SOPS metadata, without reading the whole file.
+pub fn may_be_sops(file_path: &str) -> bool {
+    let Ok(mut file) = fs::File::open(file_path) else { return false };
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let mut tail = Vec::new();
+    let read = file.seek(SeekFrom::Start(len.saturating_sub(METADATA_TAIL)))
+        .and_then(|_| file.read_to_end(&mut tail));
+    read.is_ok() && contains(&tail, b"sops") && contains(&tail, b"mac")
+}
+
+/// New files named like `secrets.sops.json` are encrypted by the `.sops.yaml` creation rules.
+fn is_sops_name(file_path: &str) -> bool {
+    Path::new(file_path).file_name().is_some_and(|name| name.to_string_lossy().contains(".sops."))
+}
+
+/// The plaintext of `content` read from `file_path`, as JSON, if it is a SOPS document.
+pub fn open(command: &Path, file_path: &str, content: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+    if metadata(file_path, &content).is_none() {
+        return Ok(content);
+    }
+    let input_type = sops_type(file_path).unwrap_or("json");
+    let path = absolute(file_path)?;
+    let args = ["--decrypt", "--input-type", input_type, "--output-type", "json", &path];
+    run(command, &path, &args.map(String::from), None)
+        .map_err(|e| anyhow::anyhow!("sops could not decrypt '{}': {}", file_path, e))
+}
+
+/// What to write to `file_path` for the JSON `content`: encrypted by sops with the keys of
+/// the SOPS document already there, or by the `.sops.yaml` rules for a new `*.sops.*` file.
+pub fn seal(command: &Path, file_path: &str, content: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+    let existing = fs::read(file_path).ok().and_then(|current| metadata(file_path, &current));
+    if existing.is_none() && !is_sops_name(file_path) {
+        return Ok(content);
+    }
+    let output_type = sops_type(file_path)
+        .ok_or_else(|| anyhow::anyhow!("sops can only encrypt JSON and YAML files, not '{}'", file_path))?;
+    let path = absolute(file_path)?;
+    let mut args: Vec<String> = ["--encrypt", "--input-type", "json", "--output-type", output_type, "--filename-override", &path]
+        .map(String::from).to_vec();
+    if let Some(metadata) = &existing {
+        args.extend(key_arguments(file_path, metadata)?);
+    }
+    args.push("/dev/stdin".to_string());
+    run(command, &path, &args, Some(&content))
+        .map_err(|e| anyhow::anyhow!("sops could not encrypt '{}': {}", file_path, e))
+}
+
+/// sops arguments that encrypt to the same keys, with the same rules, as `metadata` records.
+fn key_arguments(file_path: &str, metadata: &Value) -> anyhow::Result<Vec<String>> {
+    if metadata["key_groups"].as_array().is_some_and(|groups| !groups.is_empty()) {
+        anyhow::bail!("'{}' uses SOPS key groups, which cannot be carried over; edit it with sops directly", file_path);
+    }
+    let keys = |section: &str, render: fn(&Value) -> Option<String>| -> Vec<String> {
+        metadata[section].as_array().map(|keys| keys.iter().filter_map(render).collect()).unwrap_or_default()
+    };
+    let providers = [
+        ("--age", keys("age", |key| key["recipient"].as_str().map(String::from))),
+        ("--pgp", keys("pgp", |key| key["fp"].as_str().map(String::from))),
+        ("--kms", keys("kms", |key| {
+            let arn = key["arn"].as_str()?;
+            Some(match key["role"].as_str() {
+                Some(role) if !role.is_empty() => format!("{}+{}", arn, role),
+                _ => arn.to_string(),
+            })
+        })),
+        ("--gcp-kms", keys("gcp_kms", |key| key["resource_id"].as_str().map(String::from))),
+        ("--azure-kv", keys("azure_kv", |key| Some(format!(
+            "{}/keys/{}/{}", key["vault_url"].as_str()?.trim_end_matches('/'), key["name"].as_str()?, key["version"].as_str()?
+        )))),
+        ("--hc-vault-transit", keys("hc_vault", |key| Some(format!(
+            "{}/v1/{}/keys/{}", key["vault_address"].as_str()?.trim_end_matches('/'), key["engine_path"].as_str()?, key["key_name"].as_str()?
+        )))),
+    ];
+
+    let mut args = Vec::new();
+    for (flag, keys) in providers {
+        if !keys.is_empty() {
+            args.push(flag.to_string());
+            args.push(keys.join(","));
+        }
+    }
+    if args.is_empty() {
+        anyhow::bail!("The SOPS metadata of '{}' records no keys to encrypt to", file_path);
+    }
+    for option in ["encrypted_regex", "unencrypted_regex", "encrypted_suffix", "unencrypted_suffix", "encrypted_comment_regex", "unencrypted_comment_regex"] {
+        if let Some(value) = metadata[option].as_str().filter(|value| !value.is_empty()) {
+            args.push(format!("--{}", option.replace('_', "-")));
+            args.push(value.to_string());
+        }
+    }
+    if metadata["mac_only_encrypted"] == true {
+        args.push("--mac-only-encrypted".to_string());
+    }
+    if let Some(threshold) = metadata["shamir_threshold"].as_u64().filter(|n| *n > 0) {
+        args.push("--shamir-secret-sharing-threshold".to_string());
+        args.push(threshold.to_string());
+    }
+    Ok(args)
+}
+
+fn absolute(file_path: &str) -> anyhow::Result<String> {
+    Ok(std::env::current_dir()?.join(file_path).to_string_lossy().into_owned())
+}
+
+/// Run sops in the directory of `path`, where it looks for `.sops.yaml`, and return what it
+/// printed. Plaintext only passes through pipes.
+fn run(command: &Path, path: &str, args: &[String], input: Option<&[u8]>) -> anyhow::Result<Vec<u8>> {
+    let dir = Path::new(path).parent().unwrap_or(Path::new("."));
+    let mut child = Command::new(command)
+        .args(args)
+        .current_dir(dir)
+        .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("cannot run '{}': {}", command.display(), e))?;
+    // Written from another thread while the output is read, so neither side blocks on a full pipe
+    let writer = input.map(|input| {
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let input = input.to_vec();
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(&input);
+        })
+    });
+    let output = child.wait_with_output()?;
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+    if !output.status.success() {
+        anyhow::bail!("{} ({})", String::from_utf8_lossy(&output.stderr).trim(), output.status);
+    }
+    Ok(output.stdout)
+}
@@ -0,0 +1,715 @@
+use crate::json_tools::envelope::ResultMetadata;
+use crate::json_tools::formats::{read_document, DocumentFormat};
+use crate::json_tools::secrets::{redact_output, redact_schema};
+use crate::json_tools::streaming::{for_each_array_item, parse_simple_path};
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use jsonpath_rust::JsonPath;
+use regex::Regex;
+use serde_json::{json, Map, Value};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::time::Instant;
+
+pub struct JsonSql;
+
+impl Default for JsonSql {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonSql {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn create_sql_tool() -> Tool {
+        Tool {
+            name: "json-sql".to_string(),
+            description: "Query an array of objects with a small SQL subset: SELECT fields FROM <JSONPath> [WHERE ...] [ORDER BY ...] [LIMIT n] [OFFSET n]. Often easier to write than JSONPath filters.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "Path to the JSON file to query"
+                    },
+                    "query": {
+                        "type": "string",
+                        "description": "SQL query, e.g. \"SELECT name, age FROM $.users WHERE age > 25 AND name LIKE 'A%' ORDER BY age DESC LIMIT 10\". Fields are dotted paths within each row (address.city); use * for whole rows. WHERE supports =, !=, <>, <, <=, >, >=, LIKE, MATCHES (regex), IN (...), IS [NOT] NULL, AND, OR, NOT and parentheses"
+                    },
+                    "redact": redact_schema()
+                },
+                "required": ["file_path", "query"]
+            })
+        }
+    }
+
+    async fn handle_sql(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let file_path = args.get("file_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "file_path is required. Usage example:\n{{\n  \"file_path\": \"./data.json\",\n  \"query\": \"SELECT name FROM $.users WHERE age > 25\"\n}}"
+            ))?;
+
+        let query = args.get("query")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "query is required. Usage example:\n{{\n  \"file_path\": \"./data.json\",\n  \"query\": \"SELECT name, age FROM $.users WHERE age > 25 ORDER BY name LIMIT 10\"\n}}"
+            ))?;
+
+        let statement = match parse_select(query) {
+            Ok(statement) => statement,
+            Err(e) => return Ok(ToolResult::error(format!("SQL syntax error: {}", e))),
+        };
+
+        let started = Instant::now();
+        let mut matched = Vec::new();
+        let mut keep = |row: Value| {
+            if statement.filter.as_ref().map_or(true, |filter| filter.matches(&row)) {
+                matched.push(row);
+            }
+        };
+
+        // Simple paths into JSON files are streamed, anything else is evaluated in memory
+        let format = DocumentFormat::from_path(file_path);
+        if format == DocumentFormat::Json && parse_simple_path(&statement.from).is_ok() {
+            let found = for_each_array_item(file_path, &statement.from, |_, row| {
+                keep(row);
+                Ok(())
+            })?;
+            if found.is_none() {
+                return Ok(ToolResult::error(format!(
+                    "FROM path '{}' does not exist in '{}'", statement.from, file_path
+                )));
+            }
+        } else {
+            let document = read_document(file_path, format)?;
+            let selected: Vec<Value> = match document.query(&statement.from) {
+                Ok(values) => values.into_iter().cloned().collect(),
+                Err(e) => return Ok(ToolResult::error(format!("JSONPath query error in FROM: {}", e))),
+            };
+            // A path selecting a single array queries its elements
+            let rows = match <[Value; 1]>::try_from(selected) {
+                Ok([Value::Array(items)]) => items,
+                Ok([single]) => vec![single],
+                Err(selected) => selected,
+            };
+            rows.into_iter().for_each(&mut keep);
+        }
+
+        for (field, descending) in statement.order_by.iter().rev() {
+            matched.sort_by(|a, b| {
+                let ordering = compare_values(&lookup_field(a, field), &lookup_field(b, field));
+                if *descending { ordering.reverse() } else { ordering }
+            });
+        }
+
+        let total_matched = matched.len();
+        let page: Vec<Value> = matched.into_iter()
+            .skip(statement.offset)
+            .take(statement.limit.unwrap_or(usize::MAX))
+            .map(|row| statement.project(row))
+            .collect();
+        let next_offset = Some(statement.offset + page.len()).filter(|&next| next < total_matched);
+        let mut metadata = ResultMetadata::new(total_matched, page.len(), next_offset, started);
+        let mut results = Value::Array(page);
+        if let Some(redacted) = redact_output(args, &mut results)? {
+            metadata = metadata.with("redacted", json!(redacted));
+        }
+        let envelope = metadata.wrap(results);
+
+        Ok(ToolResult::success(format!(
+            "SQL results from '{}':\n\n{}",
+            file_path,
+            serde_json::to_string_pretty(&envelope)?
+        )))
+    }
+}
+
+/// JSON Schema for the `where` argument of the read and query tools.
+pub fn where_schema() -> Value {
+    json!({
+        "type": "string",
+        "description": "Filter applied to each result after path selection, e.g. \"name matches '^A.*' and age >= 30\". Fields are dotted paths within the result (use @ for the result itself); supports =, ==, !=, <, <=, >, >=, like, matches (regex; prefix (?i) to ignore case), in (...), is [not] null, and, or, not and parentheses. Strings may use single or double quotes"
+    })
+}
+
+/// A standalone `where` predicate, evaluated against each result of a read or query.
+#[derive(Debug)]
+pub struct Predicate(Expr);
+
+impl Predicate {
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut parser = Parser { chars: text.chars().collect(), pos: 0, double_quoted_strings: true };
+        let expr = parser.or_expr()?;
+        match parser.next()? {
+            Token::End => Ok(Self(expr)),
+            token => anyhow::bail!("unexpected {} after the end of the predicate", token.describe()),
+        }
+    }
+
+    /// Read the optional `where` argument, reporting syntax errors as `Err` messages.
+    pub fn from_args(args: &HashMap<String, Value>) -> Result<Option<Self>, String> {
+        match args.get("where") {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::String(text)) => Self::parse(text)
+                .map(Some)
+                .map_err(|e| format!("Invalid where predicate '{}': {}", text, e)),
+            Some(other) => Err(format!("where must be a string, got: {}", other)),
+        }
+    }
+
+    pub fn matches(&self, row: &Value) -> bool {
+        self.0.matches(row)
+    }
+}
+
+/// A parsed `SELECT` statement.
+#[derive(Debug)]
+struct SelectStatement {
+    /// Projected fields and their output names; empty for `SELECT *`.
+    columns: Vec<(String, String)>,
+    from: String,
+    filter: Option<Expr>,
+    order_by: Vec<(String, bool)>,
+    limit: Option<usize>,
+    offset: usize,
+}
+
+impl SelectStatement {
+    fn project(&self, row: Value) -> Value {
+        if self.columns.is_empty() {
+            return row;
+        }
+        let projected: Map<String, Value> = self.columns.iter()
+            .map(|(field, name)| (name.clone(), lookup_field(&row, field)))
+            .collect();
+        Value::Object(projected)
+    }
+}
+
+#[derive(Debug)]
+enum Expr {
+    Field(String),
+    Literal(Value),
+    Compare(Box<Expr>, &'static str, Box<Expr>),
+    Like(Box<Expr>, String, bool),
+    Matches(Box<Expr>, Regex, bool),
+    In(Box<Expr>, Vec<Value>, bool),
+    IsNull(Box<Expr>, bool),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+}
+
+impl Expr {
+    fn value(&self, row: &Value) -> Value {
+        match self {
+            Expr::Field(field) if field == "@" => row.clone(),
+            Expr::Field(field) => lookup_field(row, field),
+            Expr::Literal(value) => value.clone(),
+            _ => Value::Bool(self.matches(row)),
+        }
+    }
+
+    /// Comparisons involving null or mismatched types are false, as in SQL.
+    fn matches(&self, row: &Value) -> bool {
+        match self {
+            Expr::Field(_) | Expr::Literal(_) => self.value(row) == Value::Bool(true),
+            Expr::Compare(left, op, right) => {
+                let (left, right) = (left.value(row), right.value(row));
+                let comparable = matches!(
+                    (&left, &right),
+                    (Value::Number(_), Value::Number(_)) | (Value::String(_), Value::String(_)) | (Value::Bool(_), Value::Bool(_))
+                );
+                if !comparable {
+                    return *op == "!=" && !left.is_null() && !right.is_null();
+                }
+                let ordering = compare_values(&left, &right);
+                match *op {
+                    "=" => ordering == Ordering::Equal,
+                    "!=" => ordering != Ordering::Equal,
+                    "<" => ordering == Ordering::Less,
+                    "<=" => ordering != Ordering::Greater,
+                    ">" => ordering == Ordering::Greater,
+                    _ => ordering != Ordering::Less,
+                }
+            }
+            Expr::Like(operand, pattern, negated) => match operand.value(row) {
+                Value::String(s) => like_matches(&s, pattern) != *negated,
+                _ => false,
+            },
+            Expr::Matches(operand, regex, negated) => match operand.value(row) {
+                Value::String(s) => regex.is_match(&s) != *negated,
+                _ => false,
+            },
+            Expr::In(operand, options, negated) => {
+                let value = operand.value(row);
+                !value.is_null() && options.iter().any(|o| compare_values(o, &value) == Ordering::Equal) != *negated
+            }
+            Expr::IsNull(operand, negated) => operand.value(row).is_null() != *negated,
+            Expr::And(left, right) => left.matches(row) && right.matches(row),
+            Expr::Or(left, right) => left.matches(row) || right.matches(row),
+            Expr::Not(inner) => !inner.matches(row),
+        }
+    }
+}
+
+/// Look up a dotted field path (`address.city`, `tags.0`) within a row. Missing fields are null.
+pub(crate) fn lookup_field(row: &Value, field: &str) -> Value {
+    let mut current = row;
+    for part in field.split('.') {
+        let next = match current {
+            Value::Object(obj) => obj.get(part),
+            Value::Array(items) => part.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        };
+        match next {
+            Some(value) => current = value,
+            None => return Value::Null,
+        }
+    }
+    current.clone()
+}
+
+/// Total order over JSON values used for sorting: values of the same type compare
+/// naturally, otherwise by type in the order boolean, number, string, array, object, null.
+pub(crate) fn compare_values(a: &Value, b: &Value) -> Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Bool(_) => 0,
+            Value::Number(_) => 1,
+            Value::String(_) => 2,
+            Value::Array(_) => 3,
+            Value::Object(_) => 4,
+            Value::Null => 5,
+        }
+    }
+
+    match (a, b) {
+        (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+        (Value::Number(x), Value::Number(y)) => match (x.as_i64(), y.as_i64()) {
+            (Some(x), Some(y)) => x.cmp(&y),
+            _ => x.as_f64().unwrap_or(0.0).total_cmp(&y.as_f64().unwrap_or(0.0)),
+        },
+        (Value::String(x), Value::String(y)) => x.cmp(y),
+        (Value::Array(x), Value::Array(y)) => x.iter().zip(y)
+            .map(|(x, y)| compare_values(x, y))
+            .find(|o| o.is_ne())
+            .unwrap_or_else(|| x.len().cmp(&y.len())),
+        (Value::Object(x), Value::Object(y)) => x.len().cmp(&y.len())
+            .then_with(|| a.to_string().cmp(&b.to_string())),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+/// SQL `LIKE` matching where `%` matches any run of characters and `_` exactly one.
+fn like_matches(text: &str, pattern: &str) -> bool {
+    let text: Vec<char> = text.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+    // matched[j] is true when the text consumed so far matches pattern[..j]
+    let mut matched = vec![false; pattern.len() + 1];
+    matched[0] = true;
+    for j in 0..pattern.len() {
+        matched[j + 1] = matched[j] && pattern[j] == '%';
+    }
+    for c in text {
+        let mut next = vec![false; pattern.len() + 1];
+        for j in 0..pattern.len() {
+            next[j + 1] = match pattern[j] {
+                '%' => next[j] || matched[j + 1],
+                '_' => matched[j],
+                p => matched[j] && p == c,
+            };
+        }
+        matched = next;
+    }
+    matched[pattern.len()]
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Identifier(String),
+    Str(String),
+    Number(Value),
+    Symbol(&'static str),
+    End,
+}
+
+impl Token {
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Token::Word(w) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Token::Word(w) | Token::Identifier(w) => format!("'{}'", w),
+            Token::Str(s) => format!("string '{}'", s),
+            Token::Number(n) => format!("number {}", n),
+            Token::Symbol(s) => format!("'{}'", s),
+            Token::End => "end of query".to_string(),
+        }
+    }
+}
+
+const KEYWORDS: &[&str] = &[
+    "SELECT", "FROM", "WHERE", "ORDER", "BY", "ASC", "DESC", "LIMIT", "OFFSET", "AND", "OR", "NOT",
+    "LIKE", "MATCHES", "IN", "IS", "NULL", "TRUE", "FALSE", "AS",
+];
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    /// In `where` predicates `"text"` is a string; in SQL it quotes an identifier.
+    double_quoted_strings: bool,
+}
+
+fn parse_select(query: &str) -> anyhow::Result<SelectStatement> {
+    let mut parser = Parser { chars: query.chars().collect(), pos: 0, double_quoted_strings: false };
+    parser.expect_keyword("SELECT")?;
+
+    let mut columns = Vec::new();
+    if parser.peek()? == Token::Symbol("*") {
+        parser.next()?;
+    } else {
+        loop {
+            let field = parser.field()?;
+            let name = if parser.peek()?.is_keyword("AS") {
+                parser.next()?;
+                parser.field()?
+            } else {
+                field.clone()
+            };
+            columns.push((field, name));
+            if parser.peek()? != Token::Symbol(",") {
+                break;
+            }
+            parser.next()?;
+        }
+    }
+
+    parser.expect_keyword("FROM")?;
+    let from = parser.raw_path()?;
+
+    let mut filter = None;
+    if parser.peek()?.is_keyword("WHERE") {
+        parser.next()?;
+        filter = Some(parser.or_expr()?);
+    }
+
+    let mut order_by = Vec::new();
+    if parser.peek()?.is_keyword("ORDER") {
+        parser.next()?;
+        parser.expect_keyword("BY")?;
+        loop {
+            let field = parser.field()?;
+            let descending = if parser.peek()?.is_keyword("DESC") {
+                parser.next()?;
+                true
+            } else {
+                if parser.peek()?.is_keyword("ASC") {
+                    parser.next()?;
+                }
+                false
+            };
+            order_by.push((field, descending));
+            if parser.peek()? != Token::Symbol(",") {
+                break;
+            }
+            parser.next()?;
+        }
+    }
+
+    let mut limit = None;
+    let mut offset = 0;
+    loop {
+        if parser.peek()?.is_keyword("LIMIT") {
+            parser.next()?;
+            limit = Some(parser.count("LIMIT")?);
+        } else if parser.peek()?.is_keyword("OFFSET") {
+            parser.next()?;
+            offset = parser.count("OFFSET")?;
+        } else {
+            break;
+        }
+    }
+
+    match parser.next()? {
+        Token::End => Ok(SelectStatement { columns, from, filter, order_by, limit, offset }),
+        token => anyhow::bail!("unexpected {} after the end of the statement", token.describe()),
+    }
+}
+
+impl Parser {
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> anyhow::Result<Token> {
+        let saved = self.pos;
+        let token = self.next();
+        self.pos = saved;
+        token
+    }
+
+    fn next(&mut self) -> anyhow::Result<Token> {
+        self.skip_whitespace();
+        let Some(&c) = self.chars.get(self.pos) else {
+            return Ok(Token::End);
+        };
+        let start = self.pos;
+        self.pos += 1;
+
+        match c {
+            '\'' => Ok(Token::Str(self.quoted('\'')?)),
+            '"' if self.double_quoted_strings => Ok(Token::Str(self.quoted('"')?)),
+            '"' | '`' => Ok(Token::Identifier(self.quoted(c)?)),
+            // `@` is the row itself; `@.field` is accepted as in JSONPath filters
+            '@' if self.eat('.') => {
+                let field_start = self.pos;
+                while self.chars.get(self.pos).is_some_and(|c| c.is_alphanumeric() || matches!(c, '_' | '.')) {
+                    self.pos += 1;
+                }
+                Ok(Token::Identifier(self.chars[field_start..self.pos].iter().collect()))
+            }
+            '@' => Ok(Token::Identifier("@".to_string())),
+            ',' => Ok(Token::Symbol(",")),
+            '(' => Ok(Token::Symbol("(")),
+            ')' => Ok(Token::Symbol(")")),
+            '*' => Ok(Token::Symbol("*")),
+            '=' if self.eat('=') => Ok(Token::Symbol("=")),
+            '=' => Ok(Token::Symbol("=")),
+            '!' if self.eat('=') => Ok(Token::Symbol("!=")),
+            '<' if self.eat('>') => Ok(Token::Symbol("!=")),
+            '<' if self.eat('=') => Ok(Token::Symbol("<=")),
+            '<' => Ok(Token::Symbol("<")),
+            '>' if self.eat('=') => Ok(Token::Symbol(">=")),
+            '>' => Ok(Token::Symbol(">")),
+            c if c.is_ascii_digit() || (c == '-' && self.chars.get(self.pos).is_some_and(|d| d.is_ascii_digit())) => {
+                while self.chars.get(self.pos).is_some_and(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E')) {
+                    self.pos += 1;
+                }
+                let text: String = self.chars[start..self.pos].iter().collect();
+                serde_json::from_str(&text)
+                    .map(Token::Number)
+                    .map_err(|_| anyhow::anyhow!("invalid number '{}'", text))
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                while self.chars.get(self.pos).is_some_and(|c| c.is_alphanumeric() || matches!(c, '_' | '.')) {
+                    self.pos += 1;
+                }
+                Ok(Token::Word(self.chars[start..self.pos].iter().collect()))
+            }
+            c => anyhow::bail!("unexpected character '{}' at position {}", c, start),
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let matched = self.chars.get(self.pos) == Some(&c);
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    /// Read up to the closing `quote`; a doubled quote stands for a literal one.
+    fn quoted(&mut self, quote: char) -> anyhow::Result<String> {
+        let mut text = String::new();
+        loop {
+            match self.chars.get(self.pos) {
+                None => anyhow::bail!("unterminated quoted text starting with {}", quote),
+                Some(&c) if c == quote => {
+                    self.pos += 1;
+                    if !self.eat(quote) {
+                        return Ok(text);
+                    }
+                    text.push(quote);
+                }
+                Some(&c) => {
+                    text.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    /// The FROM clause is a raw JSONPath that ends at the first whitespace outside brackets.
+    fn raw_path(&mut self) -> anyhow::Result<String> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let mut depth = 0usize;
+        let mut quote = None;
+        while let Some(&c) = self.chars.get(self.pos) {
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '\'' | '"') => quote = Some(c),
+                (None, '[' | '(') => depth += 1,
+                (None, ']' | ')') => depth = depth.saturating_sub(1),
+                (None, c) if c.is_whitespace() && depth == 0 => break,
+                _ => {}
+            }
+            self.pos += 1;
+        }
+        let path: String = self.chars[start..self.pos].iter().collect();
+        if !path.starts_with('$') {
+            anyhow::bail!("FROM must be followed by a JSONPath starting with '$', got '{}'", path);
+        }
+        Ok(path)
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> anyhow::Result<()> {
+        match self.next()? {
+            token if token.is_keyword(keyword) => Ok(()),
+            token => anyhow::bail!("expected {} but found {}", keyword, token.describe()),
+        }
+    }
+
+    fn field(&mut self) -> anyhow::Result<String> {
+        match self.next()? {
+            Token::Identifier(name) => Ok(name),
+            Token::Word(name) if !KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(&name)) => Ok(name),
+            token => anyhow::bail!("expected a field name but found {}", token.describe()),
+        }
+    }
+
+    fn count(&mut self, clause: &str) -> anyhow::Result<usize> {
+        match self.next()? {
+            Token::Number(n) if n.is_u64() => Ok(n.as_u64().unwrap_or_default() as usize),
+            token => anyhow::bail!("{} requires a non-negative integer but found {}", clause, token.describe()),
+        }
+    }
+
+    fn or_expr(&mut self) -> anyhow::Result<Expr> {
+        let mut expr = self.and_expr()?;
+        while self.peek()?.is_keyword("OR") {
+            self.next()?;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and_expr()?));
+        }
+        Ok(expr)
+    }
+
+    fn and_expr(&mut self) -> anyhow::Result<Expr> {
+        let mut expr = self.not_expr()?;
+        while self.peek()?.is_keyword("AND") {
+            self.next()?;
+            expr = Expr::And(Box::new(expr), Box::new(self.not_expr()?));
+        }
+        Ok(expr)
+    }
+
+    fn not_expr(&mut self) -> anyhow::Result<Expr> {
+        if self.peek()?.is_keyword("NOT") {
+            self.next()?;
+            return Ok(Expr::Not(Box::new(self.not_expr()?)));
+        }
+        if self.peek()? == Token::Symbol("(") {
+            self.next()?;
+            let expr = self.or_expr()?;
+            return match self.next()? {
+                Token::Symbol(")") => Ok(expr),
+                token => anyhow::bail!("expected ')' but found {}", token.describe()),
+            };
+        }
+        self.predicate()
+    }
+
+    fn predicate(&mut self) -> anyhow::Result<Expr> {
+        let operand = Box::new(self.operand()?);
+
+        let token = self.peek()?;
+        if let Token::Symbol(op @ ("=" | "!=" | "<" | "<=" | ">" | ">=")) = token {
+            self.next()?;
+            return Ok(Expr::Compare(operand, op, Box::new(self.operand()?)));
+        }
+        if token.is_keyword("IS") {
+            self.next()?;
+            let negated = self.peek()?.is_keyword("NOT");
+            if negated {
+                self.next()?;
+            }
+            self.expect_keyword("NULL")?;
+            return Ok(Expr::IsNull(operand, negated));
+        }
+
+        let negated = token.is_keyword("NOT");
+        if negated {
+            self.next()?;
+        }
+        let token = self.peek()?;
+        if token.is_keyword("LIKE") {
+            self.next()?;
+            return match self.next()? {
+                Token::Str(pattern) => Ok(Expr::Like(operand, pattern, negated)),
+                token => anyhow::bail!("LIKE requires a quoted pattern but found {}", token.describe()),
+            };
+        }
+        if token.is_keyword("MATCHES") {
+            self.next()?;
+            return match self.next()? {
+                Token::Str(pattern) => Regex::new(&pattern)
+                    .map(|regex| Expr::Matches(operand, regex, negated))
+                    .map_err(|e| anyhow::anyhow!("invalid regular expression '{}': {}", pattern, e)),
+                token => anyhow::bail!("MATCHES requires a quoted regular expression but found {}", token.describe()),
+            };
+        }
+        if token.is_keyword("IN") {
+            self.next()?;
+            if self.next()? != Token::Symbol("(") {
+                anyhow::bail!("IN must be followed by a parenthesised list of values");
+            }
+            let mut options = Vec::new();
+            loop {
+                match self.operand()? {
+                    Expr::Literal(value) => options.push(value),
+                    _ => anyhow::bail!("IN lists may only contain literal values"),
+                }
+                match self.next()? {
+                    Token::Symbol(",") => continue,
+                    Token::Symbol(")") => break,
+                    token => anyhow::bail!("expected ',' or ')' in IN list but found {}", token.describe()),
+                }
+            }
+            return Ok(Expr::In(operand, options, negated));
+        }
+        if negated {
+            anyhow::bail!("expected LIKE, MATCHES or IN after NOT but found {}", token.describe());
+        }
+
+        // A bare operand is true when it is the boolean `true`
+        Ok(*operand)
+    }
+
+    fn operand(&mut self) -> anyhow::Result<Expr> {
+        match self.next()? {
+            Token::Str(s) => Ok(Expr::Literal(Value::String(s))),
+            Token::Number(n) => Ok(Expr::Literal(n)),
+            Token::Identifier(name) => Ok(Expr::Field(name)),
+            token if token.is_keyword("NULL") => Ok(Expr::Literal(Value::Null)),
+            token if token.is_keyword("TRUE") => Ok(Expr::Literal(Value::Bool(true))),
+            token if token.is_keyword("FALSE") => Ok(Expr::Literal(Value::Bool(false))),
+            Token::Word(name) if !KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(&name)) => Ok(Expr::Field(name)),
+            token => anyhow::bail!("expected a field or value but found {}", token.describe()),
+        }
+    }
+}
+
+#[async_trait]
+impl ToolHandler for JsonSql {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_sql_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-sql" => self.handle_sql(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
@@ -0,0 +1,432 @@
+use crate::json_tools::envelope::ResultMetadata;
+use crate::json_tools::files::{for_each_record, RecordFormat, RecordWriter};
+use crate::json_tools::secrets::{redact_output, redact_schema};
+use crate::json_tools::storage;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use rusqlite::hooks::{AuthAction, AuthContext, Authorization};
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::{params_from_iter, Connection, OpenFlags};
+use serde_json::{json, Map, Number, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Instant;
+
+/// Column holding each whole record as JSON text, for the JSON1 functions.
+const RECORD_COLUMN: &str = "_json";
+
+/// Rows json-sqlite-query returns unless `limit` says otherwise.
+const DEFAULT_ROW_LIMIT: usize = 1000;
+
+/// SQLite type of a column, widened as records with other kinds of values are seen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ColumnType {
+    Integer,
+    Real,
+    Text,
+    /// Mixed values: the column gets no declared type, so SQLite keeps each value as is.
+    Any,
+}
+
+impl ColumnType {
+    fn of(value: &Value) -> Option<Self> {
+        match value {
+            Value::Null => None,
+            Value::Bool(_) => Some(Self::Integer),
+            Value::Number(n) if n.is_i64() => Some(Self::Integer),
+            Value::Number(_) => Some(Self::Real),
+            Value::String(_) | Value::Array(_) | Value::Object(_) => Some(Self::Text),
+        }
+    }
+
+    fn widen(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Self::Integer, Self::Real) | (Self::Real, Self::Integer) => Self::Real,
+            _ => Self::Any,
+        }
+    }
+
+    fn declared(column: Option<Self>) -> &'static str {
+        match column {
+            Some(Self::Integer) => " INTEGER",
+            Some(Self::Real) => " REAL",
+            Some(Self::Text) => " TEXT",
+            Some(Self::Any) | None => "",
+        }
+    }
+}
+
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+/// The SQLite value stored for a JSON value; arrays and objects are stored as JSON text.
+fn sql_value(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(*b as i64),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None => SqlValue::Real(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        Value::String(s) => SqlValue::Text(s.clone()),
+        other => SqlValue::Text(other.to_string()),
+    }
+}
+
+/// Error message for a statement that failed to prepare or run.
+fn sql_error(error: rusqlite::Error) -> String {
+    if error.sqlite_error_code() == Some(rusqlite::ErrorCode::AuthorizationForStatementDenied) {
+        return format!("SQL error: {}. ATTACH and VACUUM INTO are not allowed, as they can open files outside the allowed roots", error);
+    }
+    format!("SQL error: {}", error)
+}
+
+/// The JSON value of a result column. Text holding a JSON array or object, as the JSON1
+/// functions return, is parsed when `parse_json` is set; blobs become base64.
+fn json_value(value: ValueRef, parse_json: bool) -> Value {
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(i) => json!(i),
+        ValueRef::Real(f) => Number::from_f64(f).map(Value::Number).unwrap_or(Value::Null),
+        ValueRef::Text(bytes) => {
+            let text = String::from_utf8_lossy(bytes);
+            let trimmed = text.trim_start();
+            if parse_json && (trimmed.starts_with('{') || trimmed.starts_with('[')) {
+                if let Ok(parsed) = serde_json::from_str(&text) {
+                    return parsed;
+                }
+            }
+            Value::String(text.into_owned())
+        }
+        ValueRef::Blob(bytes) => Value::String(STANDARD.encode(bytes)),
+    }
+}
+
+/// The `database_path` argument, which must name a local file.
+fn database_path<'a>(args: &'a HashMap<String, Value>, example: &str) -> anyhow::Result<&'a str> {
+    let database = args.get("database_path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("database_path is required. Usage example:\n{}", example))?;
+    if !storage::is_local(database) {
+        anyhow::bail!("database_path '{}' must be a local file; SQLite cannot open other storage", database);
+    }
+    Ok(database)
+}
+
+pub struct JsonSqlite;
+
+impl Default for JsonSqlite {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonSqlite {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn create_load_tool() -> Tool {
+        Tool {
+            name: "json-sqlite-load".to_string(),
+            description: "Load a JSON array or NDJSON file into a SQLite table for queries beyond JSONPath: joins, aggregates, window functions. Each top-level key becomes a column and the whole record is kept as JSON in the _json column for the JSON1 functions.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "JSON file holding an array of records, or an NDJSON file"
+                    },
+                    "database_path": {
+                        "type": "string",
+                        "description": "SQLite database file; created if it does not exist"
+                    },
+                    "table": {
+                        "type": "string",
+                        "description": "Table to load the records into"
+                    },
+                    "array_path": {
+                        "type": "string",
+                        "description": "Simple path to the array of records in a JSON file, e.g. '$.users' (default: '$')",
+                        "default": "$"
+                    },
+                    "mode": {
+                        "type": "string",
+                        "enum": ["replace", "append"],
+                        "description": "replace drops an existing table first; append adds rows, and columns for new keys (default: replace)",
+                        "default": "replace"
+                    },
+                    "index_columns": {
+                        "type": "array",
+                        "items": {"type": "string"},
+                        "description": "Columns to index, for faster filters and joins on them"
+                    }
+                },
+                "required": ["file_path", "database_path", "table"]
+            })
+        }
+    }
+
+    fn create_query_tool() -> Tool {
+        Tool {
+            name: "json-sqlite-query".to_string(),
+            description: "Run a SQL statement against a SQLite database, e.g. one filled by json-sqlite-load, and return the rows as JSON objects or export them to a JSON/NDJSON file. JSON1 functions such as json_extract(_json, '$.address.city') reach nested fields.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "database_path": {
+                        "type": "string",
+                        "description": "SQLite database file"
+                    },
+                    "query": {
+                        "type": "string",
+                        "description": "One SQL statement, e.g. \"SELECT city, count(*) AS users FROM users GROUP BY city ORDER BY users DESC\""
+                    },
+                    "params": {
+                        "type": "array",
+                        "description": "Values bound to the ? placeholders of the query, in order"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "description": "Most rows to return in the response (default: 1000); an export writes every row",
+                        "default": 1000
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "Export every row to this file: NDJSON for .ndjson/.jsonl, otherwise a JSON array"
+                    },
+                    "parse_json": {
+                        "type": "boolean",
+                        "description": "Return text holding a JSON array or object, as JSON1 functions produce, as JSON rather than a string (default: true)",
+                        "default": true
+                    },
+                    "allow_writes": {
+                        "type": "boolean",
+                        "description": "Allow statements that change the database, such as UPDATE or CREATE INDEX (default: false). ATTACH and VACUUM INTO are always refused",
+                        "default": false
+                    },
+                    "redact": redact_schema()
+                },
+                "required": ["database_path", "query"]
+            })
+        }
+    }
+
+    async fn handle_load(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        const EXAMPLE: &str = "{\n  \"file_path\": \"./users.json\",\n  \"database_path\": \"./users.sqlite\",\n  \"table\": \"users\"\n}";
+        let file_path = args.get("file_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("file_path is required. Usage example:\n{}", EXAMPLE))?;
+        let database = database_path(args, EXAMPLE)?;
+        let table = args.get("table")
+            .and_then(|v| v.as_str())
+            .filter(|t| !t.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("table is required. Usage example:\n{}", EXAMPLE))?;
+        let array_path = args.get("array_path").and_then(|v| v.as_str()).unwrap_or("$");
+        let mode = args.get("mode").and_then(|v| v.as_str()).unwrap_or("replace");
+        if !matches!(mode, "replace" | "append") {
+            return Ok(ToolResult::error(format!("Unknown mode '{}'. Use 'replace' or 'append'", mode)));
+        }
+        let index_columns: Vec<&str> = args.get("index_columns")
+            .and_then(|v| v.as_array())
+            .map(|columns| columns.iter().filter_map(|c| c.as_str()).collect())
+            .unwrap_or_default();
+
+        // The first pass finds the columns and their types, the second inserts the rows
+        let format = RecordFormat::detect(file_path, array_path)?;
+        let mut columns: Vec<(String, Option<ColumnType>)> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        for_each_record(file_path, format, array_path, |record| {
+            let Value::Object(fields) = record else { return Ok(()) };
+            for (key, value) in fields {
+                if key == RECORD_COLUMN {
+                    anyhow::bail!("Records of '{}' have a '{}' key, which is the column holding each whole record", file_path, RECORD_COLUMN);
+                }
+                let position = *positions.entry(key.clone()).or_insert_with(|| {
+                    columns.push((key, None));
+                    columns.len() - 1
+                });
+                if let Some(kind) = ColumnType::of(&value) {
+                    let column = &mut columns[position].1;
+                    *column = Some(column.map_or(kind, |current| current.widen(kind)));
+                }
+            }
+            Ok(())
+        })?;
+        if let Some(missing) = index_columns.iter().find(|c| !positions.contains_key(**c)) {
+            return Ok(ToolResult::error(format!("Cannot index '{}': no record of '{}' has that key", missing, file_path)));
+        }
+
+        let mut connection = Connection::open(database)
+            .map_err(|e| anyhow::anyhow!("Failed to open database '{}': {}", database, e))?;
+        let transaction = connection.transaction()?;
+        if mode == "replace" {
+            transaction.execute(&format!("DROP TABLE IF EXISTS {}", quote(table)), [])?;
+        }
+        let existing: Vec<String> = transaction
+            .prepare(&format!("SELECT name FROM pragma_table_info({})", quote(table)))?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        if existing.is_empty() {
+            let mut definitions: Vec<String> = columns.iter()
+                .map(|(name, kind)| format!("{}{}", quote(name), ColumnType::declared(*kind)))
+                .collect();
+            definitions.push(format!("{} TEXT", quote(RECORD_COLUMN)));
+            transaction.execute(&format!("CREATE TABLE {} ({})", quote(table), definitions.join(", ")), [])?;
+        } else if !existing.iter().any(|name| name == RECORD_COLUMN) {
+            return Ok(ToolResult::error(format!(
+                "Table '{}' of '{}' was not created by json-sqlite-load (it has no {} column); use mode 'replace' or another table",
+                table, database, RECORD_COLUMN
+            )));
+        } else {
+            for (name, kind) in columns.iter().filter(|(name, _)| !existing.contains(name)) {
+                transaction.execute(&format!("ALTER TABLE {} ADD COLUMN {}{}", quote(table), quote(name), ColumnType::declared(*kind)), [])?;
+            }
+        }
+
+        let names: Vec<String> = columns.iter().map(|(name, _)| quote(name)).chain([quote(RECORD_COLUMN)]).collect();
+        let placeholders = vec!["?"; names.len()].join(", ");
+        let mut insert = transaction.prepare(&format!("INSERT INTO {} ({}) VALUES ({})", quote(table), names.join(", "), placeholders))?;
+        let rows = for_each_record(file_path, format, array_path, |record| {
+            let mut values: Vec<SqlValue> = match &record {
+                Value::Object(fields) => columns.iter().map(|(name, _)| fields.get(name).map_or(SqlValue::Null, sql_value)).collect(),
+                _ => vec![SqlValue::Null; columns.len()],
+            };
+            values.push(SqlValue::Text(record.to_string()));
+            insert.execute(params_from_iter(values))?;
+            Ok(())
+        })?;
+        drop(insert);
+        for column in &index_columns {
+            let index = format!("{}_{}", table, column);
+            transaction.execute(&format!("CREATE INDEX IF NOT EXISTS {} ON {} ({})", quote(&index), quote(table), quote(column)), [])?;
+        }
+        transaction.commit()?;
+
+        let summary = json!({
+            "database_path": database,
+            "table": table,
+            "rows": rows,
+            "columns": columns.iter()
+                .map(|(name, kind)| json!({"name": name, "type": ColumnType::declared(*kind).trim()}))
+                .chain([json!({"name": RECORD_COLUMN, "type": "TEXT"})])
+                .collect::<Vec<_>>(),
+            "indexes": index_columns,
+        });
+        Ok(ToolResult::success(format!(
+            "Loaded {} record(s) from '{}' into table '{}' of '{}':\n\n{}",
+            rows, file_path, table, database, serde_json::to_string_pretty(&summary)?
+        )))
+    }
+
+    async fn handle_query(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        const EXAMPLE: &str = "{\n  \"database_path\": \"./users.sqlite\",\n  \"query\": \"SELECT city, count(*) AS users FROM users GROUP BY city\"\n}";
+        let database = database_path(args, EXAMPLE)?;
+        let query = args.get("query")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("query is required. Usage example:\n{}", EXAMPLE))?;
+        let params: Vec<SqlValue> = args.get("params")
+            .and_then(|v| v.as_array())
+            .map(|params| params.iter().map(sql_value).collect())
+            .unwrap_or_default();
+        let limit = args.get("limit").and_then(|v| v.as_u64()).map_or(DEFAULT_ROW_LIMIT, |v| v as usize);
+        let output_path = args.get("output_path").and_then(|v| v.as_str());
+        let parse_json = args.get("parse_json").and_then(|v| v.as_bool()).unwrap_or(true);
+        let allow_writes = args.get("allow_writes").and_then(|v| v.as_bool()).unwrap_or(false);
+
+        if !Path::new(database).exists() {
+            return Ok(ToolResult::error(format!("Database '{}' does not exist; create it with json-sqlite-load", database)));
+        }
+        let flags = if allow_writes { OpenFlags::SQLITE_OPEN_READ_WRITE } else { OpenFlags::SQLITE_OPEN_READ_ONLY };
+        let connection = Connection::open_with_flags(database, flags | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+            .map_err(|e| anyhow::anyhow!("Failed to open database '{}': {}", database, e))?;
+        // ATTACH, and VACUUM INTO (which attaches its target), would open files outside the allowed roots
+        connection.authorizer(Some(|context: AuthContext<'_>| match context.action {
+            AuthAction::Attach { .. } => Authorization::Deny,
+            _ => Authorization::Allow,
+        }));
+
+        let started = Instant::now();
+        let mut statement = match connection.prepare(query) {
+            Ok(statement) => statement,
+            Err(e) => return Ok(ToolResult::error(sql_error(e))),
+        };
+        if !statement.readonly() && !allow_writes {
+            return Ok(ToolResult::error(
+                "The statement changes the database. Pass allow_writes: true to run it".to_string()
+            ));
+        }
+        let names: Vec<String> = statement.column_names().into_iter().map(String::from).collect();
+        let mut rows = match statement.query(params_from_iter(params)) {
+            Ok(rows) => rows,
+            Err(e) => return Ok(ToolResult::error(sql_error(e))),
+        };
+
+        let mut writer = output_path
+            .map(|path| RecordWriter::create(Path::new(path), RecordFormat::from_extension(path)))
+            .transpose()?;
+        let mut page = Vec::new();
+        let mut total = 0;
+        loop {
+            let row = match rows.next() {
+                Ok(Some(row)) => row,
+                Ok(None) => break,
+                Err(e) => return Ok(ToolResult::error(sql_error(e))),
+            };
+            let mut object = Map::new();
+            for (index, name) in names.iter().enumerate() {
+                object.insert(name.clone(), json_value(row.get_ref(index)?, parse_json));
+            }
+            let object = Value::Object(object);
+            if let Some(writer) = writer.as_mut() {
+                writer.write(&object)?;
+            } else if page.len() < limit {
+                page.push(object);
+            }
+            total += 1;
+        }
+        drop(rows);
+        let changed = if statement.readonly() { None } else { Some(connection.changes()) };
+
+        if let (Some(writer), Some(path)) = (writer, output_path) {
+            let written = writer.finish()?;
+            return Ok(ToolResult::success(format!("Exported {} row(s) from '{}' to '{}'", written, database, path)));
+        }
+        let mut metadata = ResultMetadata::new(total, page.len(), None, started);
+        if total > page.len() {
+            metadata = metadata.with("truncated", json!(true));
+        }
+        if let Some(changed) = changed {
+            metadata = metadata.with("rows_changed", json!(changed));
+        }
+        let mut results = Value::Array(page);
+        if let Some(redacted) = redact_output(args, &mut results)? {
+            metadata = metadata.with("redacted", json!(redacted));
+        }
+        Ok(ToolResult::success(format!(
+            "SQLite results from '{}':\n\n{}",
+            database,
+            serde_json::to_string_pretty(&metadata.wrap(results))?
+        )))
+    }
+}
+
+#[async_trait]
+impl ToolHandler for JsonSqlite {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_load_tool(), Self::create_query_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-sqlite-load" => self.handle_load(&tool_call.arguments).await,
+            "json-sqlite-query" => self.handle_query(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use crate::mcp::protocol::{Tool, ToolResult};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Upper bounds of the latency histogram buckets, in milliseconds.
+const LATENCY_BUCKETS_MS: [u64; 9] = [1, 5, 10, 50, 100, 500, 1000, 5000, 30000];
+
+#[derive(Default)]
+struct ToolStats {
+    calls: u64,
+    errors: u64,
+    /// Non-cumulative counts per bucket; the last slot counts calls over every bound.
+    buckets: [u64; LATENCY_BUCKETS_MS.len() + 1],
+    total_ms: f64,
+    max_ms: f64,
+    bytes_read: u64,
+    bytes_written: u64,
+}
+
+/// A per-tool counter: metric name, help text and the field it reads.
+type ToolCounter = (&'static str, &'static str, fn(&ToolStats) -> u64);
+
+/// Size and modification time of a file.
+type FileState = (u64, Option<SystemTime>);
+
+#[derive(Default)]
+struct Counters {
+    requests: BTreeMap<String, u64>,
+    tools: BTreeMap<String, ToolStats>,
+    error_categories: BTreeMap<&'static str, u64>,
+}
+
+/// Counters for requests served and per-tool latency, errors and file traffic, reported
+/// by the json-server-stats tool.
+pub struct ServerStats {
+    started: Instant,
+    counters: Mutex<Counters>,
+}
+
+impl Default for ServerStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ServerStats {
+    pub fn new() -> Self {
+        Self { started: Instant::now(), counters: Mutex::new(Counters::default()) }
+    }
+
+    /// Count a JSON-RPC request by method (`tools/call`, `tools/list`, ...).
+    pub fn record_request(&self, method: &str) {
+        let mut counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        *counters.requests.entry(method.to_string()).or_default() += 1;
+    }
+
+    /// Count a tool call; `error` is the message of a failed call.
+    pub fn record_tool_call(&self, tool: &str, elapsed: Duration, error: Option<&str>, files: FileTraffic) {
+        let elapsed_ms = elapsed.as_secs_f64() * 1000.0;
+        let mut counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(message) = error {
+            *counters.error_categories.entry(error_category(message)).or_default() += 1;
+        }
+        let stats = counters.tools.entry(tool.to_string()).or_default();
+        stats.calls += 1;
+        stats.errors += u64::from(error.is_some());
+        let bucket = LATENCY_BUCKETS_MS.iter().position(|&bound| elapsed_ms <= bound as f64).unwrap_or(LATENCY_BUCKETS_MS.len());
+        stats.buckets[bucket] += 1;
+        stats.total_ms += elapsed_ms;
+        stats.max_ms = stats.max_ms.max(elapsed_ms);
+        stats.bytes_read += files.read;
+        stats.bytes_written += files.written;
+    }
+
+    fn report(&self) -> Value {
+        let counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        let round = |ms: f64| (ms * 1000.0).round() / 1000.0;
+        let mut tools = Map::new();
+        for (name, stats) in &counters.tools {
+            let mut cumulative = 0;
+            let mut buckets = Map::new();
+            for (i, count) in stats.buckets.iter().enumerate() {
+                cumulative += count;
+                let bound = LATENCY_BUCKETS_MS.get(i).map_or("+Inf".to_string(), |b| b.to_string());
+                buckets.insert(bound, json!(cumulative));
+            }
+            tools.insert(name.clone(), json!({
+                "calls": stats.calls,
+                "errors": stats.errors,
+                "bytes_read": stats.bytes_read,
+                "bytes_written": stats.bytes_written,
+                "latency_ms": {
+                    "mean": round(stats.total_ms / stats.calls as f64),
+                    "max": round(stats.max_ms),
+                    "buckets": buckets,
+                },
+            }));
+        }
+        let total = |field: fn(&ToolStats) -> u64| counters.tools.values().map(field).sum::<u64>();
+        json!({
+            "uptime_seconds": self.started.elapsed().as_secs(),
+            "requests": counters.requests,
+            "tool_calls": total(|s| s.calls),
+            "errors": total(|s| s.errors),
+            "bytes_read": total(|s| s.bytes_read),
+            "bytes_written": total(|s| s.bytes_written),
+            "error_categories": counters.error_categories,
+            "tools": tools,
+        })
+    }
+
+    /// Only aggregate counters, for telemetry: calls, errors and latency buckets per tool and
+    /// errors by category, with no file sizes, names or messages. Calls to names outside
+    /// `known` (tools a caller made up) are counted together as "other".
+    pub fn aggregates(&self, known: &[String]) -> Value {
+        let counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        let mut tools: BTreeMap<&str, ToolStats> = BTreeMap::new();
+        for (name, stats) in &counters.tools {
+            let name = if known.contains(name) { name.as_str() } else { "other" };
+            let total = tools.entry(name).or_default();
+            total.calls += stats.calls;
+            total.errors += stats.errors;
+            for (bucket, count) in total.buckets.iter_mut().zip(stats.buckets) {
+                *bucket += count;
+            }
+        }
+        let tools: Map<String, Value> = tools.into_iter()
+            .map(|(name, stats)| {
+                let buckets: Map<String, Value> = stats.buckets.iter().enumerate()
+                    .map(|(i, count)| (LATENCY_BUCKETS_MS.get(i).map_or("+Inf".to_string(), |b| b.to_string()), json!(count)))
+                    .collect();
+                (name.to_string(), json!({"calls": stats.calls, "errors": stats.errors, "latency_ms_buckets": buckets}))
+            })
+            .collect();
+        json!({
+            "uptime_seconds": self.started.elapsed().as_secs(),
+            "tools": tools,
+            "error_categories": counters.error_categories,
+        })
+    }
+
+    /// The same counters in the Prometheus text exposition format.
+    fn prometheus(&self) -> String {
+        let counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out = String::new();
+        let header = |out: &mut String, name: &str, kind: &str, help: &str| {
+            let _ = writeln!(out, "# HELP {} {}\n# TYPE {} {}", name, help, name, kind);
+        };
+
+        header(&mut out, "json_mcp_uptime_seconds", "gauge", "Seconds since the server started.");
+        let _ = writeln!(out, "json_mcp_uptime_seconds {}", self.started.elapsed().as_secs());
+        header(&mut out, "json_mcp_requests_total", "counter", "JSON-RPC requests served, by method.");
+        for (method, count) in &counters.requests {
+            let _ = writeln!(out, "json_mcp_requests_total{{method=\"{}\"}} {}", method, count);
+        }
+
+        let per_tool: [ToolCounter; 4] = [
+            ("json_mcp_tool_calls_total", "Tool calls served.", |s| s.calls),
+            ("json_mcp_tool_errors_total", "Tool calls that returned an error.", |s| s.errors),
+            ("json_mcp_bytes_read_total", "Bytes of files read by tool calls.", |s| s.bytes_read),
+            ("json_mcp_bytes_written_total", "Bytes of files written by tool calls.", |s| s.bytes_written),
+        ];
+        for (name, help, field) in per_tool {
+            header(&mut out, name, "counter", help);
+            for (tool, stats) in &counters.tools {
+                let _ = writeln!(out, "{}{{tool=\"{}\"}} {}", name, tool, field(stats));
+            }
+        }
+
+        let name = "json_mcp_tool_duration_seconds";
+        header(&mut out, name, "histogram", "Tool call latency.");
+        for (tool, stats) in &counters.tools {
+            let mut cumulative = 0;
+            for (i, count) in stats.buckets.iter().enumerate() {
+                cumulative += count;
+                let bound = LATENCY_BUCKETS_MS.get(i).map_or("+Inf".to_string(), |&ms| (ms as f64 / 1000.0).to_string());
+                let _ = writeln!(out, "{}_bucket{{tool=\"{}\",le=\"{}\"}} {}", name, tool, bound, cumulative);
+            }
+            let _ = writeln!(out, "{}_sum{{tool=\"{}\"}} {}", name, tool, stats.total_ms / 1000.0);
+            let _ = writeln!(out, "{}_count{{tool=\"{}\"}} {}", name, tool, stats.calls);
+        }
+        out
+    }
+
+    pub fn create_stats_tool() -> Tool {
+        Tool {
+            name: "json-server-stats".to_string(),
+            description: "Report server counters since startup: requests served, per-tool call counts, errors, latency histograms and bytes of files read and written".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "format": {
+                        "type": "string",
+                        "enum": ["json", "prometheus"],
+                        "description": "Output format: 'json' (default) or the Prometheus text exposition format"
+                    }
+                }
+            }),
+        }
+    }
+
+    pub fn handle_stats(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        match args.get("format").and_then(|v| v.as_str()).unwrap_or("json") {
+            "json" => Ok(ToolResult::success(format!(
+                "Server statistics:\n\n{}", serde_json::to_string_pretty(&self.report())?
+            ))),
+            "prometheus" => Ok(ToolResult::success(self.prometheus())),
+            other => Ok(ToolResult::error(format!("Unknown format '{}'. Use 'json' or 'prometheus'", other))),
+        }
+    }
+}
+
+/// Coarse category of a tool error message, so errors can be counted without keeping the
+/// message, which may name files or contain data.
+pub fn error_category(message: &str) -> &'static str {
+    const CATEGORIES: &[(&str, &[&str])] = &[
+        ("missing_argument", &["is required"]),
+        ("unknown_tool", &["Unknown tool"]),
+        ("disabled_tool", &["is disabled on this server"]),
+        ("access_denied", &["outside the allowed roots", "symlink", "Permission denied"]),
+        ("not_found", &["does not exist", "No such file", "not found"]),
+        ("conflict", &["if_hash", "has changed"]),
+        ("invalid_json", &["Invalid JSON", "parse", "EOF while", "expected value", "trailing characters"]),
+        ("invalid_query", &["JSONPath", "query", "SQL"]),
+        ("invalid_argument", &["must be", "Unknown format", "Invalid", "invalid", "Unknown", "not one of"]),
+    ];
+    CATEGORIES.iter()
+        .find(|(_, phrases)| phrases.iter().any(|phrase| message.contains(phrase)))
+        .map_or("other", |(category, _)| category)
+}
+
+/// Bytes of files a tool call read and wrote.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FileTraffic {
+    pub read: u64,
+    pub written: u64,
+}
+
+/// Snapshot of the files named in a call's arguments, compared after the call to find the
+/// files it wrote. Reads are counted by the storage layer instead (`storage::count_reads`).
+pub struct FileProbe {
+    before: Vec<(String, Option<FileState>)>,
+}
+
+impl FileProbe {
+    pub fn new(paths: Vec<String>) -> Self {
+        let before = paths.into_iter().map(|path| {
+            let state = file_state(&path);
+            (path, state)
+        }).collect();
+        Self { before }
+    }
+
+    /// Bytes of the named files the call created or changed.
+    pub fn finish(self) -> u64 {
+        self.before.into_iter()
+            .filter_map(|(path, before)| file_state(&path).filter(|after| before.as_ref() != Some(after)))
+            .map(|(len, _)| len)
+            .sum()
+    }
+}
+
+fn file_state(path: &str) -> Option<FileState> {
+    let metadata = Path::new(path).metadata().ok().filter(|m| m.is_file())?;
+    Some((metadata.len(), metadata.modified().ok()))
+}
@@ -0,0 +1,613 @@
+use crate::json_tools::http::{HttpConfig, HttpStorage};
+use crate::json_tools::s3::{self, S3Config, S3Storage};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::future::Future;
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::SystemTime;
+
+/// The `storage` section of the `--config` file, one table per backend.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    /// Schemes other than local files that tools may use while `--allowed-root` confines
+    /// file access, e.g. `["s3"]`. Without allowed roots every registered scheme is usable.
+    pub schemes: Vec<String>,
+    pub local: LocalConfig,
+    pub s3: S3Config,
+    pub http: HttpConfig,
+    pub memory: MemoryConfig,
+}
+
+/// What a backend can do beyond reading a file from start to end.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Capabilities {
+    /// Reads can start at any offset, which read sessions and json-tail rely on.
+    pub seekable: bool,
+    /// Files can be created and replaced.
+    pub writable: bool,
+}
+
+/// Size and modification time of a stored file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FileInfo {
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+/// A reader that can also seek, as returned for seekable backends.
+pub trait SeekRead: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> SeekRead for T {}
+
+/// Where files live. Paths without a `scheme://` prefix are local files; every other scheme
+/// maps to a registered backend, so tools read and write through the same calls wherever
+/// the data is.
+pub trait Storage: Send + Sync {
+    /// Short name used in messages, e.g. `local` or `s3`.
+    fn name(&self) -> &'static str;
+
+    fn capabilities(&self) -> Capabilities;
+
+    /// Refuse paths this backend's configuration does not allow, as the path policy does
+    /// for local files.
+    fn check_allowed(&self, _path: &str) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Size and modification time of `path`, or `None` when it does not exist.
+    fn info(&self, path: &str) -> anyhow::Result<Option<FileInfo>>;
+
+    /// A reader of `path` from the start.
+    fn open(&self, path: &str) -> anyhow::Result<Box<dyn Read + Send>>;
+
+    /// A reader of `path` that can seek; only backends with the `seekable` capability
+    /// provide one.
+    fn open_seekable(&self, path: &str) -> anyhow::Result<Box<dyn SeekRead>> {
+        anyhow::bail!("'{}' is on {} storage, which cannot read from an offset", path, self.name())
+    }
+
+    /// The whole content of `path`.
+    fn read(&self, path: &str) -> anyhow::Result<Vec<u8>> {
+        let mut content = Vec::new();
+        self.open(path)?.read_to_end(&mut content)?;
+        Ok(content)
+    }
+
+    /// Replace `path` with `content`, so readers see either the old or the new file.
+    fn write(&self, path: &str, _content: &[u8]) -> anyhow::Result<()> {
+        anyhow::bail!("'{}' is on {} storage, which is read-only", path, self.name())
+    }
+
+    /// Create `path` with `content`, failing if it already exists. Backends that cannot
+    /// write conditionally check first, which leaves a window for another writer.
+    fn write_new(&self, path: &str, content: &[u8]) -> anyhow::Result<()> {
+        if self.info(path)?.is_some() {
+            return Err(already_exists(path));
+        }
+        self.write(path, content)
+    }
+}
+
+/// The error for a file that `overwrite: false` forbids replacing.
+pub(crate) fn already_exists(path: &str) -> anyhow::Error {
+    anyhow::anyhow!("'{}' already exists. Pass overwrite: true to replace it", path)
+}
+
+/// The `storage.local` section of the `--config` file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LocalConfig {
+    /// Permission bits of files the tools create, as an octal string such as `"0640"`,
+    /// instead of what the umask leaves. Replaced files keep their own. Unix only.
+    #[serde(deserialize_with = "octal_mode")]
+    pub file_mode: Option<u32>,
+}
+
+fn octal_mode<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u32>, D::Error> {
+    let Some(mode) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    match u32::from_str_radix(mode.trim_start_matches("0o"), 8) {
+        Ok(bits) if bits <= 0o7777 => Ok(Some(bits)),
+        _ => Err(D::Error::custom(format!("file_mode '{}' is not an octal mode such as \"0640\"", mode))),
+    }
+}
+
+static LOCAL_CONFIG: RwLock<LocalConfig> = RwLock::new(LocalConfig { file_mode: None });
+
+/// Files on the local filesystem.
+pub struct LocalStorage;
+
+impl Storage for LocalStorage {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities { seekable: true, writable: true }
+    }
+
+    fn info(&self, path: &str) -> anyhow::Result<Option<FileInfo>> {
+        match fs::metadata(path) {
+            Ok(metadata) => Ok(Some(FileInfo { size: metadata.len(), modified: metadata.modified().ok() })),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(anyhow::anyhow!("Failed to inspect '{}': {}", path, e)),
+        }
+    }
+
+    fn open(&self, path: &str) -> anyhow::Result<Box<dyn Read + Send>> {
+        Ok(Box::new(open_file(path)?))
+    }
+
+    fn open_seekable(&self, path: &str) -> anyhow::Result<Box<dyn SeekRead>> {
+        Ok(Box::new(open_file(path)?))
+    }
+
+    fn read(&self, path: &str) -> anyhow::Result<Vec<u8>> {
+        fs::read(path).map_err(|e| anyhow::anyhow!("Failed to read file '{}': {}", path, e))
+    }
+
+    /// Write to a temporary file and rename it over `path` so readers never see a partial file.
+    fn write(&self, path: &str, content: &[u8]) -> anyhow::Result<()> {
+        replace_file(Path::new(path), content, "write")
+    }
+
+    /// Write to a temporary file and link it to `path`, which fails if `path` exists by then.
+    fn write_new(&self, path: &str, content: &[u8]) -> anyhow::Result<()> {
+        store_file(Path::new(path), content, "write", false)
+    }
+}
+
+fn open_file(path: &str) -> anyhow::Result<File> {
+    File::open(path).map_err(|e| anyhow::anyhow!("Failed to open file '{}': {}", path, e))
+}
+
+/// Create a new file beside `target` to hold content that will replace it, named
+/// `<target name>.<random>.<purpose>.tmp`. It is opened with `create_new`, so a file or symlink
+/// planted at the name is never written through and concurrent writers never share one, and
+/// with the target's permission bits (or the configured `file_mode`), so the content is never
+/// readable by more users than the target's.
+pub(crate) fn create_temp_sibling(target: &Path, purpose: &str) -> anyhow::Result<(PathBuf, File)> {
+    let mut name = target.file_name().map(|n| n.to_os_string()).unwrap_or_default();
+    name.push(format!(".{}.{}.tmp", &uuid::Uuid::new_v4().simple().to_string()[..12], purpose));
+    let temp = target.with_file_name(name);
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        let mode = match fs::metadata(target) {
+            Ok(metadata) => Some(metadata.permissions().mode() & 0o7777),
+            Err(_) => LOCAL_CONFIG.read().unwrap_or_else(|e| e.into_inner()).file_mode,
+        };
+        if let Some(mode) = mode {
+            options.mode(mode);
+        }
+    }
+    let file = options.open(&temp)
+        .map_err(|e| anyhow::anyhow!("Failed to create '{}': {}", temp.display(), e))?;
+    Ok((temp, file))
+}
+
+/// Replace the local file `target` with `content` through a temporary sibling from
+/// [`create_temp_sibling`], so readers see either the old or the new file.
+pub(crate) fn replace_file(target: &Path, content: &[u8], purpose: &str) -> anyhow::Result<()> {
+    store_file(target, content, purpose, true)
+}
+
+/// Write `content` to a temporary sibling of `target` and move it into place: renamed over
+/// `target` when `replace` is set, otherwise hard-linked to it, which fails atomically if
+/// `target` exists.
+fn store_file(target: &Path, content: &[u8], purpose: &str, replace: bool) -> anyhow::Result<()> {
+    let (temp, mut file) = create_temp_sibling(target, purpose)?;
+    let written = file.write_all(content)
+        .map_err(|e| anyhow::anyhow!("Failed to write '{}': {}", temp.display(), e))
+        .and_then(|()| carry_metadata(target, &temp))
+        .and_then(|()| persist(&temp, target, replace));
+    if written.is_err() || !replace {
+        let _ = fs::remove_file(&temp);
+    }
+    written
+}
+
+/// Move the finished temporary file `temp` to `target`, as described for [`store_file`].
+/// Without `replace`, `temp` is left for the caller to remove.
+pub(crate) fn persist(temp: &Path, target: &Path, replace: bool) -> anyhow::Result<()> {
+    if replace {
+        return fs::rename(temp, target).map_err(|e| anyhow::anyhow!("Failed to replace '{}': {}", target.display(), e));
+    }
+    fs::hard_link(temp, target).map_err(|e| match e.kind() {
+        std::io::ErrorKind::AlreadyExists => already_exists(&target.to_string_lossy()),
+        _ => anyhow::anyhow!("Failed to create '{}': {}", target.display(), e),
+    })
+}
+
+/// Give `temp`, which is about to be renamed over `target`, the target's permissions and,
+/// where the process may set them, its owner and extended attributes, so replacing a file
+/// does not change who can read it. A new target gets the configured `file_mode`.
+pub(crate) fn carry_metadata(target: &Path, temp: &Path) -> anyhow::Result<()> {
+    let original = match fs::metadata(target) {
+        Ok(original) => original,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return apply_file_mode(temp),
+        Err(e) => anyhow::bail!("Failed to inspect '{}': {}", target.display(), e),
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        // Only root may give a file away; the group may still be allowed
+        if std::os::unix::fs::chown(temp, Some(original.uid()), Some(original.gid())).is_err() {
+            let _ = std::os::unix::fs::chown(temp, None, Some(original.gid()));
+        }
+    }
+    fs::set_permissions(temp, original.permissions())
+        .map_err(|e| anyhow::anyhow!("Failed to set the permissions of '{}': {}", temp.display(), e))?;
+    // Last, as a POSIX ACL attribute refines the permission bits just set
+    #[cfg(target_os = "linux")]
+    xattrs::copy(target, temp);
+    Ok(())
+}
+
+/// Give the new file at `path` the configured `file_mode`, if any.
+#[cfg(unix)]
+pub(crate) fn apply_file_mode(path: &Path) -> anyhow::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let Some(mode) = LOCAL_CONFIG.read().unwrap_or_else(|e| e.into_inner()).file_mode else {
+        return Ok(());
+    };
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+        .map_err(|e| anyhow::anyhow!("Failed to set the permissions of '{}': {}", path.display(), e))
+}
+
+#[cfg(not(unix))]
+pub(crate) fn apply_file_mode(_path: &Path) -> anyhow::Result<()> {
+    Ok(())
+}
+
+/// Extended attributes through the Linux system calls, which std does not wrap.
+#[cfg(target_os = "linux")]
+mod xattrs {
+    use std::ffi::{c_char, c_void, CString};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    /// Copy every attribute of `from` that can be read to `to`. Attributes that need
+    /// privileges the process lacks, such as `security.*`, are skipped.
+    pub fn copy(from: &Path, to: &Path) {
+        let (Ok(from), Ok(to)) = (CString::new(from.as_os_str().as_bytes()), CString::new(to.as_os_str().as_bytes())) else {
+            return;
+        };
+        // SAFETY: every pointer is to a NUL-terminated CString or to a buffer of the length
+        // passed with it, and each result is checked before it is used as a length.
+        unsafe {
+            let size = libc::listxattr(from.as_ptr(), std::ptr::null_mut(), 0);
+            if size <= 0 {
+                return;
+            }
+            let mut names = vec![0u8; size as usize];
+            let size = libc::listxattr(from.as_ptr(), names.as_mut_ptr() as *mut c_char, names.len());
+            if size <= 0 {
+                return;
+            }
+            names.truncate(size as usize);
+            for name in names.split(|&b| b == 0).filter(|name| !name.is_empty()) {
+                let Ok(name) = CString::new(name) else { continue };
+                let size = libc::getxattr(from.as_ptr(), name.as_ptr(), std::ptr::null_mut(), 0);
+                if size < 0 {
+                    continue;
+                }
+                let mut value = vec![0u8; size as usize];
+                let size = libc::getxattr(from.as_ptr(), name.as_ptr(), value.as_mut_ptr() as *mut c_void, value.len());
+                if size < 0 {
+                    continue;
+                }
+                libc::setxattr(to.as_ptr(), name.as_ptr(), value.as_ptr() as *const c_void, size as usize, 0);
+            }
+        }
+    }
+}
+
+/// Create the missing parent directories of a local `path`; other backends have none.
+pub fn create_parent_dirs(path: &str) -> anyhow::Result<()> {
+    match Path::new(path).parent() {
+        Some(parent) if is_local(path) => fs::create_dir_all(parent)
+            .map_err(|e| anyhow::anyhow!("Failed to create directories: {}", e)),
+        _ => Ok(()),
+    }
+}
+
+/// The `storage.memory` section of the `--config` file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MemoryConfig {
+    /// Most bytes all `mem://` files may hold together.
+    pub max_bytes: u64,
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self { max_bytes: 64 * 1024 * 1024 }
+    }
+}
+
+/// Scratch files named `mem://name`, kept in memory for the life of the server process and
+/// shared by every tool.
+pub struct MemoryStorage {
+    config: MemoryConfig,
+}
+
+struct MemoryFile {
+    content: Arc<Vec<u8>>,
+    modified: SystemTime,
+}
+
+/// Every `mem://` file by name. Process-wide, so installing a new configuration keeps them.
+static MEMORY_FILES: Mutex<BTreeMap<String, MemoryFile>> = Mutex::new(BTreeMap::new());
+
+impl MemoryStorage {
+    pub fn new(config: MemoryConfig) -> Self {
+        Self { config }
+    }
+
+    fn file(&self, path: &str) -> anyhow::Result<Arc<Vec<u8>>> {
+        let files = MEMORY_FILES.lock().unwrap_or_else(|e| e.into_inner());
+        files.get(path).map(|file| file.content.clone())
+            .ok_or_else(|| anyhow::anyhow!("'{}' does not exist", path))
+    }
+
+    /// Store `content` at `path`, replacing an existing file only when `replace` is set.
+    fn store(&self, path: &str, content: &[u8], replace: bool) -> anyhow::Result<()> {
+        let mut files = MEMORY_FILES.lock().unwrap_or_else(|e| e.into_inner());
+        if !replace && files.contains_key(path) {
+            return Err(already_exists(path));
+        }
+        let others: u64 = files.iter().filter(|(name, _)| *name != path).map(|(_, file)| file.content.len() as u64).sum();
+        if others + content.len() as u64 > self.config.max_bytes {
+            anyhow::bail!(
+                "Writing {} bytes to '{}' would exceed the {} bytes memory storage may hold",
+                content.len(), path, self.config.max_bytes
+            );
+        }
+        files.insert(path.to_string(), MemoryFile { content: Arc::new(content.to_vec()), modified: SystemTime::now() });
+        Ok(())
+    }
+}
+
+/// Lets a shared buffer back a `Cursor`, so readers see a snapshot while the file is replaced.
+struct Shared(Arc<Vec<u8>>);
+
+impl AsRef<[u8]> for Shared {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities { seekable: true, writable: true }
+    }
+
+    fn check_allowed(&self, path: &str) -> anyhow::Result<()> {
+        match path.strip_prefix("mem://") {
+            Some(name) if !name.is_empty() => Ok(()),
+            _ => anyhow::bail!("'{}' is not a memory file; use mem://name", path),
+        }
+    }
+
+    fn info(&self, path: &str) -> anyhow::Result<Option<FileInfo>> {
+        let files = MEMORY_FILES.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(files.get(path).map(|file| FileInfo { size: file.content.len() as u64, modified: Some(file.modified) }))
+    }
+
+    fn open(&self, path: &str) -> anyhow::Result<Box<dyn Read + Send>> {
+        Ok(Box::new(Cursor::new(Shared(self.file(path)?))))
+    }
+
+    fn open_seekable(&self, path: &str) -> anyhow::Result<Box<dyn SeekRead>> {
+        Ok(Box::new(Cursor::new(Shared(self.file(path)?))))
+    }
+
+    fn read(&self, path: &str) -> anyhow::Result<Vec<u8>> {
+        Ok(self.file(path)?.to_vec())
+    }
+
+    fn write(&self, path: &str, content: &[u8]) -> anyhow::Result<()> {
+        self.store(path, content, true)
+    }
+
+    fn write_new(&self, path: &str, content: &[u8]) -> anyhow::Result<()> {
+        self.store(path, content, false)
+    }
+}
+
+type Backends = HashMap<String, Arc<dyn Storage>>;
+
+static BACKENDS: RwLock<Option<Arc<Backends>>> = RwLock::new(None);
+
+static ENABLED_SCHEMES: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// The backend of each scheme; a new backend only needs an entry here.
+fn standard_backends(config: StorageConfig) -> Backends {
+    s3::install(config.s3);
+    let http: Arc<dyn Storage> = Arc::new(HttpStorage::new(config.http));
+    let mut backends: Backends = HashMap::new();
+    backends.insert("s3".to_string(), Arc::new(S3Storage));
+    backends.insert("http".to_string(), http.clone());
+    backends.insert("https".to_string(), http);
+    backends.insert("mem".to_string(), Arc::new(MemoryStorage::new(config.memory)));
+    backends
+}
+
+/// Use `config` for every backend from now on. Without it, each backend's defaults apply.
+pub fn install(config: StorageConfig) {
+    *LOCAL_CONFIG.write().unwrap_or_else(|e| e.into_inner()) = config.local.clone();
+    *ENABLED_SCHEMES.write().unwrap_or_else(|e| e.into_inner()) = config.schemes.iter().map(|s| s.to_ascii_lowercase()).collect();
+    *BACKENDS.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(standard_backends(config)));
+}
+
+fn backends() -> Arc<Backends> {
+    if let Some(backends) = BACKENDS.read().unwrap_or_else(|e| e.into_inner()).clone() {
+        return backends;
+    }
+    let mut installed = BACKENDS.write().unwrap_or_else(|e| e.into_inner());
+    installed.get_or_insert_with(|| Arc::new(standard_backends(StorageConfig::default()))).clone()
+}
+
+/// The `scheme` of a `scheme://...` path. Single letters are left alone, as they are
+/// Windows drives.
+fn scheme(path: &str) -> Option<&str> {
+    let (scheme, _) = path.split_once("://")?;
+    let valid = scheme.len() > 1
+        && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    valid.then_some(scheme)
+}
+
+/// Whether `path` is a local file rather than a `scheme://` path of another backend.
+pub fn is_local(path: &str) -> bool {
+    scheme(path).is_none()
+}
+
+/// The backend that holds `path`.
+pub fn for_path(path: &str) -> anyhow::Result<Arc<dyn Storage>> {
+    let Some(scheme) = scheme(path) else {
+        return Ok(Arc::new(LocalStorage));
+    };
+    let backends = backends();
+    backends.get(&scheme.to_ascii_lowercase()).cloned().ok_or_else(|| {
+        let mut known: Vec<&str> = backends.keys().map(String::as_str).collect();
+        known.sort_unstable();
+        anyhow::anyhow!("No storage backend serves '{}://' paths such as '{}' (known schemes: {})", scheme, path, known.join(", "))
+    })
+}
+
+/// Refuse `path` if its backend does not allow it.
+pub fn check_allowed(path: &str) -> anyhow::Result<()> {
+    for_path(path)?.check_allowed(path)
+}
+
+/// Refuse `path` unless its scheme is listed in the `schemes` setting, as the path policy
+/// requires of non-local paths when allowed roots are configured.
+pub fn check_enabled(path: &str) -> anyhow::Result<()> {
+    let Some(scheme) = scheme(path) else {
+        return Ok(());
+    };
+    let enabled = ENABLED_SCHEMES.read().unwrap_or_else(|e| e.into_inner());
+    if !enabled.iter().any(|enabled| enabled.eq_ignore_ascii_case(scheme)) {
+        anyhow::bail!(
+            "'{}' is refused: {}:// paths are disabled while --allowed-root confines file access. List the scheme in schemes of the storage section of the --config file to enable it",
+            path, scheme
+        );
+    }
+    Ok(())
+}
+
+tokio::task_local! {
+    /// Bytes the tool call running on this task has read, set by [`count_reads`].
+    static BYTES_READ: Arc<AtomicU64>;
+}
+
+/// Run `call` and return its output with the bytes it read through [`read`], [`open`] and
+/// [`open_seekable`]: what the backends returned, not the size of the files.
+pub async fn count_reads<F: Future>(call: F) -> (F::Output, u64) {
+    let counter = Arc::new(AtomicU64::new(0));
+    let output = BYTES_READ.scope(counter.clone(), call).await;
+    (output, counter.load(Ordering::Relaxed))
+}
+
+tokio::task_local! {
+    /// Files the tool call running on this task may create but not replace, set by [`create_only`].
+    static CREATE_ONLY: Vec<PathBuf>;
+}
+
+/// Run `call` so that its writes to `paths` fail if the file exists at the moment of writing,
+/// rather than replacing it: the `overwrite: false` of tools with an `output_path`.
+pub async fn create_only<F: Future>(paths: Vec<PathBuf>, call: F) -> F::Output {
+    CREATE_ONLY.scope(paths, call).await
+}
+
+/// Whether the tool call running on this task may only create `path`.
+pub(crate) fn is_create_only(path: &Path) -> bool {
+    CREATE_ONLY.try_with(|paths| paths.iter().any(|p| p == path)).unwrap_or(false)
+}
+
+/// The counter of the tool call running on this task, if it is being counted.
+fn read_counter() -> Option<Arc<AtomicU64>> {
+    BYTES_READ.try_with(Arc::clone).ok()
+}
+
+/// A reader adding what it returns to the counter of the call that opened it, so bytes are
+/// counted even when it is drained on another thread.
+struct CountingReader<R> {
+    inner: R,
+    counter: Arc<AtomicU64>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.counter.fetch_add(read as u64, Ordering::Relaxed);
+        Ok(read)
+    }
+}
+
+impl<R: Seek> Seek for CountingReader<R> {
+    fn seek(&mut self, position: SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(position)
+    }
+}
+
+pub fn info(path: &str) -> anyhow::Result<Option<FileInfo>> {
+    for_path(path)?.info(path)
+}
+
+pub fn exists(path: &str) -> anyhow::Result<bool> {
+    Ok(info(path)?.is_some())
+}
+
+pub fn open(path: &str) -> anyhow::Result<Box<dyn Read + Send>> {
+    let reader = for_path(path)?.open(path)?;
+    Ok(match read_counter() {
+        Some(counter) => Box::new(CountingReader { inner: reader, counter }),
+        None => reader,
+    })
+}
+
+pub fn open_seekable(path: &str) -> anyhow::Result<Box<dyn SeekRead>> {
+    let reader = for_path(path)?.open_seekable(path)?;
+    Ok(match read_counter() {
+        Some(counter) => Box::new(CountingReader { inner: reader, counter }),
+        None => reader,
+    })
+}
+
+pub fn read(path: &str) -> anyhow::Result<Vec<u8>> {
+    let content = for_path(path)?.read(path)?;
+    if let Some(counter) = read_counter() {
+        counter.fetch_add(content.len() as u64, Ordering::Relaxed);
+    }
+    Ok(content)
+}
+
+/// Replace `path` with `content`, unless the running tool call was told not to replace it
+/// (see [`create_only`]).
+pub fn write(path: &str, content: &[u8]) -> anyhow::Result<()> {
+    if is_create_only(Path::new(path)) {
+        return write_new(path, content);
+    }
+    for_path(path)?.write(path, content)
+}
+
+/// Create `path` with `content`, failing if it already exists.
+pub fn write_new(path: &str, content: &[u8]) -> anyhow::Result<()> {
+    for_path(path)?.write_new(path, content)
+}
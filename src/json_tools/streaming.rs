@@ -1,17 +1,930 @@
+use crate::json_tools::binary::{decode_base64_schema, Base64Summary};
+use crate::json_tools::embedded::{expand_embedded_output, expand_embedded_schema};
+use crate::json_tools::encryption;
+use crate::json_tools::envelope::ResultMetadata;
+use crate::json_tools::formats::{read_document, DocumentFormat};
+use crate::json_tools::integrity::file_content_hash;
+use crate::json_tools::lossy::{lossy_schema, read_document_lossy, suggest_lossy, LossyReader, Replacements};
+use crate::json_tools::rfc9535::escape_name;
+use crate::json_tools::secrets::{expand_env_schema, redact_output, redact_schema, EnvExpansion, SecretValues};
+use crate::json_tools::sql::{where_schema, Predicate};
+use crate::json_tools::storage;
+use crate::json_tools::timewindow::{time_bound_schema, time_field_schema, TimeWindow};
 use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
 use crate::mcp::server::ToolHandler;
 use async_trait::async_trait;
 use serde_json::{json, Value};
 use jsonpath_rust::JsonPath;
+use serde::Deserialize;
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use std::cell::Cell;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::fmt;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 
-pub struct JsonStreaming;
+/// Upper bounds for json-tail, keeping a single call's response and wait reasonable.
+const MAX_TAIL_RECORDS: usize = 10000;
+const MAX_FOLLOW_MS: u64 = 60_000;
+const TAIL_BLOCK_SIZE: u64 = 64 * 1024;
+/// json-children defaults: children listed per call, the largest node `fetch` returns, and
+/// the longest string shown inline in a listing.
+const DEFAULT_CHILDREN_LIMIT: usize = 100;
+const DEFAULT_MAX_FETCH_BYTES: u64 = 64 * 1024;
+const MAX_INLINE_STRING_CHARS: usize = 80;
+/// Read cursors held for json-read `session_id` continuation; the least recently used is
+/// dropped when the limit is reached, and idle cursors expire.
+const MAX_READ_CURSORS: usize = 64;
+const READ_CURSOR_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// A single step of a simple JSONPath such as `$.records[2].items`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Parse a JSONPath made only of child keys and array indices.
+pub fn parse_simple_path(path: &str) -> anyhow::Result<Vec<PathSegment>> {
+    let rest = path.trim().strip_prefix('$')
+        .ok_or_else(|| anyhow::anyhow!("Path '{}' must start with '$'", path))?;
+    let chars: Vec<char> = rest.chars().collect();
+    let mut segments = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '.' => {
+                let start = i + 1;
+                let mut end = start;
+                while end < chars.len() && chars[end] != '.' && chars[end] != '[' {
+                    end += 1;
+                }
+                if end == start {
+                    anyhow::bail!("Empty key at position {} in path '{}'", start, path);
+                }
+                segments.push(PathSegment::Key(chars[start..end].iter().collect()));
+                i = end;
+            }
+            '[' => {
+                let (segment, next) = parse_bracket_segment(&chars, i)
+                    .ok_or_else(|| anyhow::anyhow!(
+                        "Unsupported segment at position {} in path '{}'. Only keys (.name, ['name']) and indices ([0]) are allowed",
+                        i, path
+                    ))?;
+                segments.push(segment);
+                i = next;
+            }
+            c => anyhow::bail!("Unexpected character '{}' at position {} in path '{}'", c, i, path),
+        }
+    }
+
+    Ok(segments)
+}
+
+/// Parse `['key']`, `["key"]` or `[3]` starting at `open`, returning the segment and the
+/// position just past the closing bracket.
+fn parse_bracket_segment(chars: &[char], open: usize) -> Option<(PathSegment, usize)> {
+    let first = *chars.get(open + 1)?;
+    if first == '\'' || first == '"' {
+        let close_quote = (open + 2..chars.len()).find(|&j| chars[j] == first)?;
+        if chars.get(close_quote + 1) != Some(&']') {
+            return None;
+        }
+        let key = chars[open + 2..close_quote].iter().collect();
+        Some((PathSegment::Key(key), close_quote + 2))
+    } else {
+        let close = (open + 1..chars.len()).find(|&j| chars[j] == ']')?;
+        let index = chars[open + 1..close].iter().collect::<String>().trim().parse().ok()?;
+        Some((PathSegment::Index(index), close + 1))
+    }
+}
+
+/// Apply the optional `expand_env` argument to values about to be returned. Secrets are
+/// redacted since the output goes straight into the model's context.
+fn expand_placeholders(args: &HashMap<String, Value>, values: &mut Value) -> anyhow::Result<()> {
+    if let Some(expansion) = EnvExpansion::from_args(args)? {
+        expansion.expand(values, SecretValues::Redact)?;
+    }
+    Ok(())
+}
+
+/// Apply the optional `decode_base64_path` argument, returning how many strings were
+/// replaced by a description of their decoded content.
+fn summarize_base64(args: &HashMap<String, Value>, values: &mut Value) -> Result<Option<usize>, String> {
+    Base64Summary::from_args(args)
+        .map(|summary| summary.apply_all(values).map_err(|e| format!("decode_base64_path: {}", e)))
+        .transpose()
+}
+
+/// One page of an array located inside a JSON document.
+#[derive(Debug)]
+pub struct ArrayPage {
+    pub items: Vec<Value>,
+    pub total_items: usize,
+    /// Byte offset just past the page's last item, where a later page can resume.
+    pub resume_offset: Option<u64>,
+}
+
+/// Stream `file_path` and return `count` elements starting at `start` from the array at
+/// `array_path`, without materializing the rest of the document. Returns `None` when the
+/// path does not exist.
+pub fn read_array_page(
+    file_path: &str,
+    array_path: &str,
+    start: usize,
+    count: usize,
+) -> anyhow::Result<Option<ArrayPage>> {
+    let segments = parse_simple_path(array_path)?;
+    let file = storage::open(file_path)?;
+
+    let consumed = Rc::new(Cell::new(0));
+    let mut sink = PageSink {
+        start,
+        end: start.saturating_add(count),
+        items: Vec::new(),
+        consumed: Rc::clone(&consumed),
+        resume_offset: None,
+    };
+    let reader = CountingReader { inner: BufReader::new(file), count: consumed };
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let total = PathSeed { segments: &segments, sink: &mut sink }
+        .deserialize(&mut deserializer)
+        .and_then(|total| deserializer.end().map(|_| total))
+        .map_err(|e| anyhow::anyhow!("Failed to stream '{}' at '{}': {}", file_path, array_path, e))?;
+
+    Ok(total.map(|total_items| ArrayPage {
+        items: sink.items,
+        total_items,
+        resume_offset: sink.resume_offset,
+    }))
+}
+
+/// Read up to `count` items of an array from `offset`, a position between two items as
+/// recorded in `ArrayPage::resume_offset`. Returns the items and the offset after the last
+/// one, or `None` in its place when the array ended.
+pub fn read_array_items_at(file_path: &str, offset: u64, count: usize) -> anyhow::Result<(Vec<Value>, Option<u64>)> {
+    let mut reader = BufReader::new(storage::open_seekable(file_path)?);
+    reader.seek(SeekFrom::Start(offset))?;
+    let mut position = offset;
+    let mut items = Vec::new();
+    while items.len() < count {
+        let separator = loop {
+            let mut byte = [0u8];
+            if reader.read(&mut byte)? == 0 {
+                break None;
+            }
+            position += 1;
+            if !byte[0].is_ascii_whitespace() {
+                break Some(byte[0]);
+            }
+        };
+        match separator {
+            Some(b']') => return Ok((items, None)),
+            Some(b',') => {}
+            _ => anyhow::bail!("Offset {} of '{}' is not between array items", offset, file_path),
+        }
+        // The stream may read one byte past the item; rewind to just after it
+        let mut stream = serde_json::Deserializer::from_reader(&mut reader).into_iter::<Value>();
+        let item = stream.next()
+            .ok_or_else(|| anyhow::anyhow!("'{}' ends inside an array", file_path))?
+            .map_err(|e| anyhow::anyhow!("Failed to parse '{}' at byte {}: {}", file_path, position, e))?;
+        position += stream.byte_offset() as u64;
+        let logical = reader.stream_position()?;
+        reader.seek_relative(position as i64 - logical as i64)?;
+        items.push(item);
+    }
+    Ok((items, Some(position)))
+}
+
+/// Counts the bytes the JSON parser has taken from the file.
+struct CountingReader<R> {
+    inner: R,
+    count: Rc<Cell<u64>>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count.set(self.count.get() + n as u64);
+        Ok(n)
+    }
+}
+
+/// Stream the array at `array_path` in `file_path`, calling `visit` for every element in
+/// order. Returns the array length, or `None` when the path does not exist.
+pub fn for_each_array_item<F>(file_path: &str, array_path: &str, visit: F) -> anyhow::Result<Option<usize>>
+where
+    F: FnMut(usize, Value) -> anyhow::Result<()>,
+{
+    let segments = parse_simple_path(array_path)?;
+    let mut sink = CallbackSink { visit };
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(storage::open(file_path)?));
+    PathSeed { segments: &segments, sink: &mut sink }
+        .deserialize(&mut deserializer)
+        .and_then(|total| deserializer.end().map(|_| total))
+        .map_err(|e| anyhow::anyhow!("Failed to stream '{}' at '{}': {}", file_path, array_path, e))
+}
+
+/// Receives the elements of the array found at the end of a streamed path.
+trait ArraySink {
+    /// Whether the element at `index` should be materialized and passed to `accept`.
+    fn wants(&mut self, index: usize) -> bool;
+    fn accept(&mut self, index: usize, value: Value) -> Result<(), String>;
+}
+
+struct CallbackSink<F> {
+    visit: F,
+}
+
+impl<F: FnMut(usize, Value) -> anyhow::Result<()>> ArraySink for CallbackSink<F> {
+    fn wants(&mut self, _index: usize) -> bool {
+        true
+    }
+
+    fn accept(&mut self, index: usize, value: Value) -> Result<(), String> {
+        (self.visit)(index, value).map_err(|e| e.to_string())
+    }
+}
+
+struct PageSink {
+    start: usize,
+    end: usize,
+    items: Vec<Value>,
+    consumed: Rc<Cell<u64>>,
+    resume_offset: Option<u64>,
+}
+
+impl ArraySink for PageSink {
+    fn wants(&mut self, index: usize) -> bool {
+        if index == self.end && !self.items.is_empty() {
+            // The parser has read the page's last item, plus the byte after it if that
+            // item was a number (numbers end where the next character begins)
+            let peeked = u64::from(self.items.last().is_some_and(Value::is_number));
+            self.resume_offset = Some(self.consumed.get() - peeked);
+        }
+        index >= self.start && index < self.end
+    }
+
+    fn accept(&mut self, _index: usize, value: Value) -> Result<(), String> {
+        self.items.push(value);
+        Ok(())
+    }
+}
+
+/// Walks a document along `segments`, skipping everything off the path with `IgnoredAny`.
+/// Yields `Some(length)` of the target array, or `None` if the path does not exist.
+struct PathSeed<'a, S> {
+    segments: &'a [PathSegment],
+    sink: &'a mut S,
+}
+
+impl<'de, S: ArraySink> DeserializeSeed<'de> for PathSeed<'_, S> {
+    type Value = Option<usize>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, S: ArraySink> Visitor<'de> for PathSeed<'_, S> {
+    type Value = Option<usize>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.segments.first() {
+            Some(PathSegment::Key(key)) => write!(f, "an object containing key '{}'", key),
+            Some(PathSegment::Index(index)) => write!(f, "an array containing index {}", index),
+            None => write!(f, "an array"),
+        }
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let Some(PathSegment::Key(target)) = self.segments.first() else {
+            return Err(de::Error::invalid_type(de::Unexpected::Map, &self));
+        };
+
+        let mut result = None;
+        let mut matched = false;
+        while let Some(key) = map.next_key::<String>()? {
+            if !matched && key == *target {
+                matched = true;
+                result = map.next_value_seed(PathSeed {
+                    segments: &self.segments[1..],
+                    sink: &mut *self.sink,
+                })?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(result)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        match self.segments.first() {
+            None => {
+                let mut index = 0;
+                loop {
+                    if self.sink.wants(index) {
+                        match seq.next_element::<Value>()? {
+                            Some(value) => self.sink.accept(index, value).map_err(de::Error::custom)?,
+                            None => break,
+                        }
+                    } else if seq.next_element::<IgnoredAny>()?.is_none() {
+                        break;
+                    }
+                    index += 1;
+                }
+                Ok(Some(index))
+            }
+            Some(PathSegment::Index(target)) => {
+                let mut result = None;
+                let mut index = 0;
+                loop {
+                    if index == *target {
+                        match seq.next_element_seed(PathSeed {
+                            segments: &self.segments[1..],
+                            sink: &mut *self.sink,
+                        })? {
+                            Some(found) => result = found,
+                            None => break,
+                        }
+                    } else if seq.next_element::<IgnoredAny>()?.is_none() {
+                        break;
+                    }
+                    index += 1;
+                }
+                Ok(result)
+            }
+            Some(PathSegment::Key(_)) => Err(de::Error::invalid_type(de::Unexpected::Seq, &self)),
+        }
+    }
+}
+
+/// Size and type of a streamed node, computed without materializing it.
+struct NodeSize {
+    kind: &'static str,
+    /// Length of the node serialized as compact JSON.
+    bytes: u64,
+    children: Option<usize>,
+    /// The value itself for scalars small enough to show inline.
+    value: Option<Value>,
+}
+
+impl NodeSize {
+    fn scalar(kind: &'static str, bytes: u64, value: Option<Value>) -> Self {
+        Self { kind, bytes, children: None, value }
+    }
+
+    fn describe(&self, key: Value, path: String) -> Value {
+        let mut entry = json!({"key": key, "path": path, "type": self.kind, "bytes": self.bytes});
+        if let Some(children) = self.children {
+            entry["children"] = json!(children);
+        }
+        if let Some(value) = &self.value {
+            entry["value"] = value.clone();
+        }
+        entry
+    }
+}
+
+/// Length of `text` as a JSON string literal, quotes included.
+fn escaped_len(text: &str) -> u64 {
+    2 + text.chars().map(|c| match c {
+        '"' | '\\' | '\n' | '\r' | '\t' | '\u{8}' | '\u{c}' => 2,
+        c if (c as u32) < 0x20 => 6,
+        c => c.len_utf8() as u64,
+    }).sum::<u64>()
+}
+
+/// The simple JSONPath of a child, in a form `parse_simple_path` accepts.
+fn child_path(parent: &str, segment: &PathSegment) -> String {
+    match segment {
+        PathSegment::Index(index) => format!("{}[{}]", parent, index),
+        PathSegment::Key(key) if !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-') => {
+            format!("{}.{}", parent, key)
+        }
+        PathSegment::Key(key) if key.contains('\'') => format!("{}[\"{}\"]", parent, key),
+        PathSegment::Key(key) => format!("{}['{}']", parent, key),
+    }
+}
+
+/// Measures a node, skipping its contents.
+struct SizeSeed;
+
+impl<'de> DeserializeSeed<'de> for SizeSeed {
+    type Value = NodeSize;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for SizeSeed {
+    type Value = NodeSize;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "any JSON value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(NodeSize::scalar("boolean", if v { 4 } else { 5 }, Some(json!(v))))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(NodeSize::scalar("integer", v.to_string().len() as u64, Some(json!(v))))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(NodeSize::scalar("integer", v.to_string().len() as u64, Some(json!(v))))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        let value = json!(v);
+        Ok(NodeSize::scalar("number", value.to_string().len() as u64, Some(value)))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        let inline = v.chars().count() <= MAX_INLINE_STRING_CHARS;
+        Ok(NodeSize::scalar("string", escaped_len(v), inline.then(|| json!(v))))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(NodeSize::scalar("null", 4, Some(Value::Null)))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let (mut count, mut bytes) = (0, 2);
+        while let Some(item) = seq.next_element_seed(SizeSeed)? {
+            bytes += item.bytes + u64::from(count > 0);
+            count += 1;
+        }
+        Ok(NodeSize { kind: "array", bytes, children: Some(count), value: None })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let (mut count, mut bytes) = (0, 2);
+        while let Some(key) = map.next_key::<String>()? {
+            let member = map.next_value_seed(SizeSeed)?;
+            bytes += escaped_len(&key) + 1 + member.bytes + u64::from(count > 0);
+            count += 1;
+        }
+        Ok(NodeSize { kind: "object", bytes, children: Some(count), value: None })
+    }
+}
+
+/// The children of an object or array, measured one by one.
+struct ChildListing {
+    node: NodeSize,
+    children: Vec<Value>,
+}
+
+/// Lists the children in `offset..offset + limit` of the node it is applied to, measuring
+/// (but not keeping) every child so the node's own size is known too.
+struct ChildrenSeed<'a> {
+    path: &'a str,
+    offset: usize,
+    limit: usize,
+}
+
+impl ChildrenSeed<'_> {
+    fn wants(&self, index: usize) -> bool {
+        index >= self.offset && index - self.offset < self.limit
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for ChildrenSeed<'_> {
+    type Value = ChildListing;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ChildrenSeed<'_> {
+    type Value = ChildListing;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "any JSON value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        SizeSeed.visit_bool(v).map(|node| ChildListing { node, children: Vec::new() })
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        SizeSeed.visit_i64(v).map(|node| ChildListing { node, children: Vec::new() })
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        SizeSeed.visit_u64(v).map(|node| ChildListing { node, children: Vec::new() })
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        SizeSeed.visit_f64(v).map(|node| ChildListing { node, children: Vec::new() })
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        SizeSeed.visit_str(v).map(|node| ChildListing { node, children: Vec::new() })
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        SizeSeed.visit_unit().map(|node| ChildListing { node, children: Vec::new() })
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let (mut count, mut bytes, mut children) = (0, 2, Vec::new());
+        while let Some(item) = seq.next_element_seed(SizeSeed)? {
+            if self.wants(count) {
+                children.push(item.describe(json!(count), child_path(self.path, &PathSegment::Index(count))));
+            }
+            bytes += item.bytes + u64::from(count > 0);
+            count += 1;
+        }
+        Ok(ChildListing { node: NodeSize { kind: "array", bytes, children: Some(count), value: None }, children })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let (mut count, mut bytes, mut children) = (0, 2, Vec::new());
+        while let Some(key) = map.next_key::<String>()? {
+            let member = map.next_value_seed(SizeSeed)?;
+            bytes += escaped_len(&key) + 1 + member.bytes + u64::from(count > 0);
+            if self.wants(count) {
+                let path = child_path(self.path, &PathSegment::Key(key.clone()));
+                children.push(member.describe(json!(key), path));
+            }
+            count += 1;
+        }
+        Ok(ChildListing { node: NodeSize { kind: "object", bytes, children: Some(count), value: None }, children })
+    }
+}
+
+/// Walks a document along `segments` like [`PathSeed`], then applies `target` to the node
+/// found there. Yields `None` when the path does not exist.
+struct NodeSeed<'a, T> {
+    segments: &'a [PathSegment],
+    target: T,
+}
+
+impl<'de, T: DeserializeSeed<'de>> DeserializeSeed<'de> for NodeSeed<'_, T> {
+    type Value = Option<T::Value>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        if self.segments.is_empty() {
+            self.target.deserialize(deserializer).map(Some)
+        } else {
+            deserializer.deserialize_any(self)
+        }
+    }
+}
+
+impl<'de, T: DeserializeSeed<'de>> Visitor<'de> for NodeSeed<'_, T> {
+    type Value = Option<T::Value>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an object or array")
+    }
+
+    fn visit_bool<E: de::Error>(self, _: bool) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_i64<E: de::Error>(self, _: i64) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_u64<E: de::Error>(self, _: u64) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_f64<E: de::Error>(self, _: f64) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_str<E: de::Error>(self, _: &str) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let target = match &self.segments[0] {
+            PathSegment::Key(key) => Some(key),
+            PathSegment::Index(_) => None,
+        };
+        let mut pending = Some(self.target);
+        let mut result = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match pending.take() {
+                Some(seed) if target == Some(&key) => {
+                    result = map.next_value_seed(NodeSeed { segments: &self.segments[1..], target: seed })?;
+                }
+                seed => {
+                    pending = seed;
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let target = match &self.segments[0] {
+            PathSegment::Index(index) => Some(*index),
+            PathSegment::Key(_) => None,
+        };
+        let mut pending = Some(self.target);
+        let mut result = None;
+        let mut index = 0;
+        loop {
+            match pending.take() {
+                Some(seed) if target == Some(index) => {
+                    match seq.next_element_seed(NodeSeed { segments: &self.segments[1..], target: seed })? {
+                        Some(found) => result = found,
+                        None => break,
+                    }
+                }
+                seed => {
+                    pending = seed;
+                    if seq.next_element::<IgnoredAny>()?.is_none() {
+                        break;
+                    }
+                }
+            }
+            index += 1;
+        }
+        Ok(result)
+    }
+}
+
+/// Stream `file_path` to the node at `path` and apply `target` to it.
+fn stream_node<T, V>(file_path: &str, path: &str, target: T) -> anyhow::Result<Option<V>>
+where
+    T: for<'de> DeserializeSeed<'de, Value = V>,
+{
+    let segments = parse_simple_path(path)?;
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(storage::open(file_path)?));
+    NodeSeed { segments: &segments, target }
+        .deserialize(&mut deserializer)
+        .and_then(|found| deserializer.end().map(|_| found))
+        .map_err(|e| anyhow::anyhow!("Failed to stream '{}' at '{}': {}", file_path, path, e))
+}
+
+/// A step of a JSONPath that json-query can evaluate while parsing, such as the three
+/// steps of `$.records[*].name`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamStep {
+    Key(String),
+    Index(usize),
+    Wildcard,
+}
+
+/// Parse a JSONPath made only of child keys, array indices and `*` / `[*]` wildcards, or
+/// `None` for any other query (filters, slices, unions, recursive descent, functions).
+pub fn parse_streamable_path(query: &str) -> Option<Vec<StreamStep>> {
+    let chars: Vec<char> = query.trim().strip_prefix('$')?.chars().collect();
+    let mut steps = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i..].starts_with(&['.', '*']) {
+            steps.push(StreamStep::Wildcard);
+            i += 2;
+        } else if chars[i..].starts_with(&['[', '*', ']']) {
+            steps.push(StreamStep::Wildcard);
+            i += 3;
+        } else if chars[i] == '.' {
+            let end = (i + 1..chars.len()).find(|&j| chars[j] == '.' || chars[j] == '[').unwrap_or(chars.len());
+            let key: String = chars[i + 1..end].iter().collect();
+            if key.is_empty() || key.starts_with(|c: char| c.is_ascii_digit()) || !key.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                return None;
+            }
+            steps.push(StreamStep::Key(key));
+            i = end;
+        } else if chars[i] == '[' {
+            let (segment, next) = parse_bracket_segment(&chars, i)?;
+            steps.push(match segment {
+                PathSegment::Key(key) if key.contains('\\') => return None,
+                PathSegment::Key(key) => StreamStep::Key(key),
+                PathSegment::Index(index) => StreamStep::Index(index),
+            });
+            i = next;
+        } else {
+            return None;
+        }
+    }
+    Some(steps)
+}
+
+/// Stream `file_path` and call `visit` with the normalized path (`$['records'][0]`) and
+/// value of every node `steps` selects, in document order. Only the selected nodes are
+/// materialized, so the document never has to fit in memory.
+pub fn stream_query<F: FnMut(String, Value)>(file_path: &str, steps: &[StreamStep], mut visit: F) -> anyhow::Result<()> {
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(storage::open(file_path)?));
+    QuerySeed { steps, path: "$".to_string(), visit: &mut visit }
+        .deserialize(&mut deserializer)
+        .and_then(|_| deserializer.end())
+        .map_err(|e| anyhow::anyhow!("Failed to stream '{}': {}", file_path, e))
+}
+
+/// Like `stream_query`, for an NDJSON file queried as the array of its records: each
+/// selected line is parsed on its own, and the others are skipped unparsed.
+pub fn stream_ndjson_query<F: FnMut(String, Value)>(file_path: &str, steps: &[StreamStep], mut visit: F) -> anyhow::Result<()> {
+    let mut index = 0;
+    let mut records = Vec::new();
+    for (line_number, line) in BufReader::new(storage::open(file_path)?).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let selected = match steps.first() {
+            None | Some(StreamStep::Wildcard) => true,
+            Some(StreamStep::Index(target)) => index == *target,
+            Some(StreamStep::Key(_)) => false,
+        };
+        if selected {
+            let value = serde_json::from_str(&line)
+                .map_err(|e| anyhow::anyhow!("Invalid JSON on line {} of '{}': {}", line_number + 1, file_path, e))?;
+            match steps.split_first() {
+                Some((_, rest)) => select_steps(value, rest, format!("$[{}]", index), &mut visit),
+                None => records.push(value),
+            }
+        }
+        index += 1;
+    }
+    // `$` selects the whole array of records
+    if steps.is_empty() {
+        visit("$".to_string(), Value::Array(records));
+    }
+    Ok(())
+}
+
+/// Apply `steps` to a parsed `value` at `path`, calling `visit` for every node selected.
+fn select_steps(value: Value, steps: &[StreamStep], path: String, visit: &mut dyn FnMut(String, Value)) {
+    let Some((step, rest)) = steps.split_first() else {
+        return visit(path, value);
+    };
+    match (value, step) {
+        (Value::Object(mut obj), StreamStep::Key(key)) => {
+            if let Some(child) = obj.remove(key) {
+                select_steps(child, rest, format!("{}['{}']", path, escape_name(key)), visit);
+            }
+        }
+        (Value::Object(obj), StreamStep::Wildcard) => {
+            for (key, child) in obj {
+                select_steps(child, rest, format!("{}['{}']", path, escape_name(&key)), visit);
+            }
+        }
+        (Value::Array(items), StreamStep::Index(index)) => {
+            if let Some(child) = items.into_iter().nth(*index) {
+                select_steps(child, rest, format!("{}[{}]", path, index), visit);
+            }
+        }
+        (Value::Array(items), StreamStep::Wildcard) => {
+            for (index, child) in items.into_iter().enumerate() {
+                select_steps(child, rest, format!("{}[{}]", path, index), visit);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Walks a document along `steps`, fanning out at wildcards and skipping everything off
+/// the path with `IgnoredAny`.
+struct QuerySeed<'a> {
+    steps: &'a [StreamStep],
+    path: String,
+    visit: &'a mut dyn FnMut(String, Value),
+}
+
+impl<'de> DeserializeSeed<'de> for QuerySeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        if self.steps.is_empty() {
+            let value = Value::deserialize(deserializer)?;
+            (self.visit)(self.path, value);
+            Ok(())
+        } else {
+            deserializer.deserialize_any(self)
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for QuerySeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a JSON value")
+    }
+
+    fn visit_bool<E: de::Error>(self, _: bool) -> Result<Self::Value, E> {
+        Ok(())
+    }
+
+    fn visit_i64<E: de::Error>(self, _: i64) -> Result<Self::Value, E> {
+        Ok(())
+    }
+
+    fn visit_u64<E: de::Error>(self, _: u64) -> Result<Self::Value, E> {
+        Ok(())
+    }
+
+    fn visit_f64<E: de::Error>(self, _: f64) -> Result<Self::Value, E> {
+        Ok(())
+    }
+
+    fn visit_str<E: de::Error>(self, _: &str) -> Result<Self::Value, E> {
+        Ok(())
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let (step, rest) = self.steps.split_first().expect("steps are not empty");
+        while let Some(key) = map.next_key::<String>()? {
+            let selected = match step {
+                StreamStep::Key(target) => key == *target,
+                StreamStep::Wildcard => true,
+                StreamStep::Index(_) => false,
+            };
+            if selected {
+                let path = format!("{}['{}']", self.path, escape_name(&key));
+                map.next_value_seed(QuerySeed { steps: rest, path, visit: &mut *self.visit })?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let (step, rest) = self.steps.split_first().expect("steps are not empty");
+        let mut index = 0;
+        loop {
+            let selected = match step {
+                StreamStep::Index(target) => index == *target,
+                StreamStep::Wildcard => true,
+                StreamStep::Key(_) => false,
+            };
+            let more = if selected {
+                let path = format!("{}[{}]", self.path, index);
+                seq.next_element_seed(QuerySeed { steps: rest, path, visit: &mut *self.visit })?.is_some()
+            } else {
+                seq.next_element::<IgnoredAny>()?.is_some()
+            };
+            if !more {
+                return Ok(());
+            }
+            index += 1;
+        }
+    }
+}
+
+/// Where a paginated json-read left off, so the next page is read from that byte offset
+/// instead of re-streaming the file from the start.
+struct ReadCursor {
+    file_path: String,
+    array_path: String,
+    page_size: usize,
+    next_page: usize,
+    next_index: usize,
+    byte_offset: u64,
+    total_items: usize,
+    content_hash: String,
+    /// Size and modification time when the cursor was created; any change invalidates it.
+    file_state: (u64, Option<SystemTime>),
+    touched: Instant,
+}
+
+/// One page of an array_path read and the session that continues after it.
+struct PageRead {
+    page: usize,
+    page_size: usize,
+    start: usize,
+    items: Vec<Value>,
+    total_items: usize,
+    content_hash: String,
+    session_id: Option<String>,
+}
+
+pub struct JsonStreaming {
+    cursors: Mutex<HashMap<String, ReadCursor>>,
+}
+
+impl Default for JsonStreaming {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl JsonStreaming {
     pub fn new() -> Self {
-        Self
+        Self { cursors: Mutex::new(HashMap::new()) }
     }
 
     fn create_stream_read_tool() -> Tool {
@@ -41,6 +954,194 @@ impl JsonStreaming {
                         "description": "Number of results to skip (default: 0)",
                         "default": 0,
                         "minimum": 0
+                    },
+                    "format": {
+                        "type": "string",
+                        "enum": ["json", "toml", "msgpack", "cbor"],
+                        "description": "File format (default: from the file extension: .toml, .msgpack/.mpk, .cbor, otherwise JSON). Non-JSON files are converted to the JSON data model"
+                    },
+                    "array_path": {
+                        "type": "string",
+                        "description": "Simple JSONPath to an array to paginate with the streaming parser (e.g., '$.records'). Only keys and indices are supported"
+                    },
+                    "page": {
+                        "type": "integer",
+                        "description": "1-based page number when using array_path (default: 1)",
+                        "default": 1,
+                        "minimum": 1
+                    },
+                    "page_size": {
+                        "type": "integer",
+                        "description": "Number of items per page when using array_path (default: 100)",
+                        "default": 100,
+                        "minimum": 1,
+                        "maximum": 10000
+                    },
+                    "session_id": {
+                        "type": "string",
+                        "description": "session_id from a previous array_path page: continue with the next page from the saved parser position instead of re-reading the file up to it. Fails if the file changed"
+                    },
+                    "where": where_schema(),
+                    "since": time_bound_schema("since"),
+                    "until": time_bound_schema("until"),
+                    "time_field": time_field_schema(),
+                    "expand_env": expand_env_schema(),
+                    "decode_base64_path": decode_base64_schema(),
+                    "redact": redact_schema(),
+                    "lossy": lossy_schema(),
+                    "expand_embedded_json": expand_embedded_schema()
+                },
+                "required": ["file_path"]
+            })
+        }
+    }
+
+    fn create_tail_tool() -> Tool {
+        Tool {
+            name: "json-tail".to_string(),
+            description: "Return the last records of an NDJSON (JSON Lines) file by seeking from the end, optionally waiting a bounded time for newly appended records. Useful for watching logs.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "Path to the NDJSON file"
+                    },
+                    "count": {
+                        "type": "integer",
+                        "description": "Number of records to return from the end of the file (default: 10)",
+                        "default": 10,
+                        "minimum": 0,
+                        "maximum": 10000
+                    },
+                    "follow_for_ms": {
+                        "type": "integer",
+                        "description": "After reading the tail, keep watching the file this long and also return records appended meanwhile (default: 0, max: 60000)",
+                        "default": 0,
+                        "minimum": 0,
+                        "maximum": 60000
+                    }
+                },
+                "required": ["file_path"]
+            })
+        }
+    }
+
+    async fn handle_tail(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let file_path = args.get("file_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "file_path is required. Usage example:\n{{\n  \"file_path\": \"./app.log.ndjson\",\n  \"count\": 20\n}}"
+            ))?;
+        let count = args.get("count")
+            .and_then(|v| v.as_u64())
+            .map_or(10, |v| (v as usize).min(MAX_TAIL_RECORDS));
+        let follow_for_ms = args.get("follow_for_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0)
+            .min(MAX_FOLLOW_MS);
+
+        let started = Instant::now();
+        let mut file = storage::open_seekable(file_path)?;
+        let mut reader = LineRecords::default();
+        let mut offset = read_tail(&mut file, count, &mut reader)?;
+        let mut records = reader.records;
+        let mut invalid = reader.invalid;
+        let tail_len = records.len();
+
+        // Poll for appended lines; a file that shrinks was truncated or rotated and is re-read
+        let mut appended = 0;
+        let mut restarted = false;
+        let deadline = started + Duration::from_millis(follow_for_ms);
+        while follow_for_ms > 0 && appended < MAX_TAIL_RECORDS {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            tokio::time::sleep((deadline - now).min(Duration::from_millis(100))).await;
+            let len = file.seek(SeekFrom::End(0))?;
+            if len < offset {
+                offset = 0;
+                restarted = true;
+            }
+            if len == offset {
+                continue;
+            }
+            let mut reader = LineRecords { limit: MAX_TAIL_RECORDS - appended, ..Default::default() };
+            offset += read_complete_lines(&mut file, offset, len, &mut reader)?;
+            appended += reader.records.len();
+            invalid += reader.invalid;
+            records.extend(reader.records);
+        }
+
+        let returned = records.len();
+        let mut metadata = ResultMetadata::new(returned, returned, None, started)
+            .with("tail_records", json!(tail_len))
+            .with("file_offset", json!(offset));
+        if follow_for_ms > 0 {
+            metadata = metadata
+                .with("appended_records", json!(appended))
+                .with("followed_ms", json!(follow_for_ms));
+        }
+        if invalid > 0 {
+            metadata = metadata.with("invalid_lines", json!(invalid));
+        }
+        if restarted {
+            metadata = metadata.with("file_truncated", json!(true));
+        }
+        let envelope = metadata.wrap(Value::Array(records));
+
+        let followed = if follow_for_ms > 0 {
+            format!(" plus {} appended during {} ms", appended, follow_for_ms)
+        } else {
+            String::new()
+        };
+        Ok(ToolResult::success(format!(
+            "Last {} record(s) of '{}'{}:\n\n{}",
+            tail_len,
+            file_path,
+            followed,
+            serde_json::to_string_pretty(&envelope)?
+        )))
+    }
+
+    fn create_children_tool() -> Tool {
+        Tool {
+            name: "json-children".to_string(),
+            description: "Explore a huge object (or array) one level at a time: list the children of the node at a path with their type and size in bytes, then call again with a child's path to descend, or fetch it once it is small enough. The file is streamed, never loaded whole.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "Path to the JSON file"
+                    },
+                    "path": {
+                        "type": "string",
+                        "description": "Simple JSONPath to the node to explore, using keys and indices only (default: '$'). Use the 'path' of a listed child to descend"
+                    },
+                    "offset": {
+                        "type": "integer",
+                        "description": "Number of children to skip (default: 0)",
+                        "default": 0,
+                        "minimum": 0
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "description": "Maximum number of children to list (default: 100)",
+                        "default": 100,
+                        "minimum": 1,
+                        "maximum": 10000
+                    },
+                    "fetch": {
+                        "type": "boolean",
+                        "description": "Return the node's value instead of listing its children (default: false)",
+                        "default": false
+                    },
+                    "max_bytes": {
+                        "type": "integer",
+                        "description": "With fetch, refuse nodes larger than this many bytes of compact JSON (default: 65536)",
+                        "minimum": 1
                     }
                 },
                 "required": ["file_path"]
@@ -48,6 +1149,70 @@ impl JsonStreaming {
         }
     }
 
+    async fn handle_children(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let file_path = args.get("file_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "file_path is required. Usage example:\n{{\n  \"file_path\": \"./large.json\",\n  \"path\": \"$.config\"\n}}"
+            ))?;
+        let path = args.get("path").and_then(|v| v.as_str()).unwrap_or("$");
+        let offset = args.get("offset")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as usize;
+        let limit = args.get("limit")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_CHILDREN_LIMIT, |v| v.clamp(1, 10000) as usize);
+        let started = Instant::now();
+
+        if args.get("fetch").and_then(|v| v.as_bool()).unwrap_or(false) {
+            let max_bytes = args.get("max_bytes")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(DEFAULT_MAX_FETCH_BYTES);
+            // Measure first so an oversized node is never materialized
+            let Some(size) = stream_node(file_path, path, SizeSeed)? else {
+                return Ok(ToolResult::error(format!("Path '{}' was not found in '{}'", path, file_path)));
+            };
+            if size.bytes > max_bytes {
+                return Ok(ToolResult::error(format!(
+                    "The {} at '{}' is {} bytes, over max_bytes ({}). List its {} children without fetch and descend into them instead",
+                    size.kind, path, size.bytes, max_bytes, size.children.unwrap_or(0)
+                )));
+            }
+            let value = stream_node(file_path, path, std::marker::PhantomData::<Value>)?.unwrap_or(Value::Null);
+            return Ok(ToolResult::success(format!(
+                "Value at '{}' in '{}' ({}, {} bytes):\n\n{}",
+                path, file_path, size.kind, size.bytes, serde_json::to_string_pretty(&value)?
+            )));
+        }
+
+        let Some(listing) = stream_node(file_path, path, ChildrenSeed { path, offset, limit })? else {
+            return Ok(ToolResult::error(format!("Path '{}' was not found in '{}'", path, file_path)));
+        };
+        let total = listing.node.children.unwrap_or(0);
+        let returned = listing.children.len();
+        let next_offset = Some(offset + returned).filter(|&next| returned > 0 && next < total);
+        let mut metadata = ResultMetadata::new(total, returned, next_offset, started)
+            .with("content_hash", json!(file_content_hash(file_path)?))
+            .with("path", json!(path))
+            .with("type", json!(listing.node.kind))
+            .with("bytes", json!(listing.node.bytes));
+        if let Some(value) = listing.node.value {
+            metadata = metadata.with("value", value);
+        }
+        let envelope = metadata.wrap(Value::Array(listing.children));
+
+        Ok(ToolResult::success(format!(
+            "Listed {} of {} child(ren) of the {} at '{}' in '{}' ({} bytes):\n\n{}",
+            returned,
+            total,
+            listing.node.kind,
+            path,
+            file_path,
+            listing.node.bytes,
+            serde_json::to_string_pretty(&envelope)?
+        )))
+    }
+
     async fn handle_stream_read(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
         let file_path = args.get("file_path")
             .and_then(|v| v.as_str())
@@ -55,7 +1220,39 @@ impl JsonStreaming {
                 "file_path is required. Usage example:\n{{\n  \"file_path\": \"./data.json\"\n}}\nOptional parameters: query, limit, offset"
             ))?;
 
-        let query = args.get("query").and_then(|v| v.as_str());
+        let started = Instant::now();
+        let window = TimeWindow::from_args(args)?;
+        let predicate = match Predicate::from_args(args) {
+            Ok(predicate) => predicate,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+        let filter = RecordFilter { query: args.get("query").and_then(|v| v.as_str()), window: window.as_ref(), predicate: predicate.as_ref() };
+
+        // A read session remembers its array_path (unknown sessions are reported when paging)
+        let session_array_path = args.get("session_id").and_then(|v| v.as_str()).map(|session_id| {
+            let cursors = self.cursors.lock().unwrap_or_else(|e| e.into_inner());
+            cursors.get(session_id).map(|cursor| cursor.array_path.clone()).unwrap_or_default()
+        });
+        let lossy = args.get("lossy").and_then(|v| v.as_bool()).unwrap_or(false);
+        if let Some(array_path) = args.get("array_path").and_then(|v| v.as_str()).or(session_array_path.as_deref()) {
+            if lossy {
+                return Ok(ToolResult::error(
+                    "lossy cannot be combined with array_path pagination, whose session positions are byte offsets in the file. Use limit and offset instead".to_string()
+                ));
+            }
+            if window.is_some() || predicate.is_some() {
+                return Ok(ToolResult::error(
+                    "since/until and where cannot be combined with array_path pagination. Use limit and offset to page through filtered records".to_string()
+                ));
+            }
+            if encryption::is_encrypted(file_path) {
+                return Ok(ToolResult::error(format!(
+                    "array_path pagination streams the file from disk and cannot read the encrypted '{}'. Use limit and offset instead", file_path
+                )));
+            }
+            return self.handle_array_page(file_path, array_path, args, started);
+        }
+
         let limit = args.get("limit")
             .and_then(|v| v.as_u64())
             .unwrap_or(1000) as usize;
@@ -63,54 +1260,272 @@ impl JsonStreaming {
             .and_then(|v| v.as_u64())
             .unwrap_or(0) as usize;
 
-        // Try to stream the file
-        let results = self.stream_json_file(file_path, query, limit, offset)?;
+        let format = DocumentFormat::resolve(file_path, args.get("format").and_then(|v| v.as_str()))?;
 
-        let output = serde_json::to_string_pretty(&results)?;
+        // Try to stream the file; encrypted files are decrypted in memory instead
+        let replacements = lossy.then(|| Arc::new(Mutex::new(Replacements::default())));
+        let outcome = if format == DocumentFormat::Json && !encryption::is_encrypted(file_path) {
+            self.stream_json_file(file_path, &filter, limit, offset, replacements.as_ref()).map_err(suggest_lossy)?
+        } else {
+            let mut collector = MatchCollector::new(&filter, limit, offset);
+            match &replacements {
+                Some(replacements) => {
+                    let (document, replaced) = read_document_lossy(file_path, format)?;
+                    *replacements.lock().unwrap_or_else(|e| e.into_inner()) = replaced;
+                    collector.push(document);
+                }
+                None => collector.push(read_document(file_path, format).map_err(suggest_lossy)?),
+            }
+            collector.finish()
+        };
+        let returned = outcome.results.len();
+        let mut results = Value::Array(outcome.results);
+        if let Err(e) = expand_placeholders(args, &mut results) {
+            return Ok(ToolResult::error(format!("Environment expansion failed: {}", e)));
+        }
+        let decoded = match summarize_base64(args, &mut results) {
+            Ok(decoded) => decoded,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+        let mut metadata = ResultMetadata::new(outcome.total_matched, returned, outcome.next_offset, started)
+            .with("content_hash", json!(file_content_hash(file_path)?));
+        if let Some(decoded) = decoded {
+            metadata = metadata.with("base64_decoded", json!(decoded));
+        }
+        if let Some(window) = &window {
+            metadata = metadata.with("time_window", window.describe());
+        }
+        if let Some(expanded) = expand_embedded_output(args, &mut results) {
+            metadata = metadata.with("embedded_json_expanded", json!(expanded));
+        }
+        if let Some(redacted) = redact_output(args, &mut results)? {
+            metadata = metadata.with("redacted", json!(redacted));
+        }
+        let mut replaced = String::new();
+        if let Some(replacements) = &replacements {
+            let replacements = replacements.lock().unwrap_or_else(|e| e.into_inner());
+            if replacements.count() > 0 {
+                replaced = format!(", replaced {} invalid UTF-8 sequence(s)", replacements.count());
+            }
+            metadata = metadata.with("utf8_replacements", replacements.describe());
+        }
+        let envelope = metadata.wrap(results);
 
         Ok(ToolResult::success(format!(
-            "Streamed {} results from '{}' (offset: {}, limit: {}):\n\n{}",
-            match &results {
-                Value::Array(arr) => arr.len(),
-                _ => 1,
-            },
+            "Streamed {} results from '{}' (offset: {}, limit: {}{}):\n\n{}",
+            returned,
             file_path,
             offset,
             limit,
-            output
+            replaced,
+            serde_json::to_string_pretty(&envelope)?
+        )))
+    }
+
+    fn handle_array_page(
+        &self,
+        file_path: &str,
+        array_path: &str,
+        args: &HashMap<String, Value>,
+        started: Instant,
+    ) -> anyhow::Result<ToolResult> {
+        let read = match args.get("session_id").and_then(|v| v.as_str()) {
+            Some(session_id) => self.read_cursor_page(session_id, file_path, array_path)?,
+            None => self.read_first_page(file_path, array_path, args)?,
+        };
+        let PageRead { page, page_size, start, items, total_items, content_hash, session_id } = match read {
+            Ok(read) => read,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+
+        let returned = items.len();
+        let mut items = Value::Array(items);
+        if let Err(e) = expand_placeholders(args, &mut items) {
+            return Ok(ToolResult::error(format!("Environment expansion failed: {}", e)));
+        }
+        let next_offset = Some(start + returned).filter(|&next| next < total_items);
+        let total_pages = total_items.div_ceil(page_size);
+        let decoded = match summarize_base64(args, &mut items) {
+            Ok(decoded) => decoded,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+        let mut metadata = ResultMetadata::new(total_items, returned, next_offset, started)
+            .with("content_hash", json!(content_hash));
+        if let Some(decoded) = decoded {
+            metadata = metadata.with("base64_decoded", json!(decoded));
+        }
+        if let Some(expanded) = expand_embedded_output(args, &mut items) {
+            metadata = metadata.with("embedded_json_expanded", json!(expanded));
+        }
+        if let Some(redacted) = redact_output(args, &mut items)? {
+            metadata = metadata.with("redacted", json!(redacted));
+        }
+        if let Some(session_id) = &session_id {
+            metadata = metadata.with("session_id", json!(session_id));
+        }
+        let envelope = metadata
+            .with("array_path", json!(array_path))
+            .with("page", json!(page))
+            .with("page_size", json!(page_size))
+            .with("total_pages", json!(total_pages))
+            .wrap(items);
+
+        Ok(ToolResult::success(format!(
+            "Page {} of {} from '{}' at '{}' ({} total items):\n\n{}",
+            page,
+            total_pages,
+            file_path,
+            array_path,
+            total_items,
+            serde_json::to_string_pretty(&envelope)?
         )))
     }
 
+    fn read_first_page(&self, file_path: &str, array_path: &str, args: &HashMap<String, Value>) -> anyhow::Result<Result<PageRead, String>> {
+        let page = args.get("page")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(1)
+            .max(1) as usize;
+        let page_size = args.get("page_size")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(100)
+            .clamp(1, 10000) as usize;
+
+        let start = (page - 1) * page_size;
+        // Sessions resume from a byte offset, which only seekable storage can do
+        let state = storage::for_path(file_path)?.capabilities().seekable.then(|| file_state(file_path)).flatten();
+        let Some(array_page) = read_array_page(file_path, array_path, start, page_size)? else {
+            return Ok(Err(format!(
+                "Array path '{}' was not found in '{}'",
+                array_path, file_path
+            )));
+        };
+        let content_hash = file_content_hash(file_path)?;
+        let next_index = start + array_page.items.len();
+        let session_id = match (array_page.resume_offset, state) {
+            (Some(byte_offset), Some(file_state)) if next_index < array_page.total_items => Some(self.open_cursor(ReadCursor {
+                file_path: file_path.to_string(),
+                array_path: array_path.to_string(),
+                page_size,
+                next_page: page + 1,
+                next_index,
+                byte_offset,
+                total_items: array_page.total_items,
+                content_hash: content_hash.clone(),
+                file_state,
+                touched: Instant::now(),
+            })),
+            _ => None,
+        };
+        Ok(Ok(PageRead {
+            page,
+            page_size,
+            start,
+            items: array_page.items,
+            total_items: array_page.total_items,
+            content_hash,
+            session_id,
+        }))
+    }
+
+    /// The next page of a read session, advancing its cursor, or removing it after the
+    /// last page.
+    fn read_cursor_page(&self, session_id: &str, file_path: &str, array_path: &str) -> anyhow::Result<Result<PageRead, String>> {
+        let mut cursors = self.cursors.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(cursor) = cursors.get_mut(session_id) else {
+            return Ok(Err(format!(
+                "No read session '{}'. It was finished or expired after {} idle minutes; read the page with `page` instead",
+                session_id, READ_CURSOR_IDLE_TIMEOUT.as_secs() / 60
+            )));
+        };
+        if cursor.file_path != file_path || cursor.array_path != array_path {
+            return Ok(Err(format!(
+                "Read session '{}' belongs to '{}' at '{}'",
+                session_id, cursor.file_path, cursor.array_path
+            )));
+        }
+        if file_state(file_path) != Some(cursor.file_state) {
+            cursors.remove(session_id);
+            return Ok(Err(format!(
+                "'{}' changed since read session '{}' began; read the page with `page` instead",
+                file_path, session_id
+            )));
+        }
+
+        let (items, resume_offset) = read_array_items_at(file_path, cursor.byte_offset, cursor.page_size)?;
+        let read = PageRead {
+            page: cursor.next_page,
+            page_size: cursor.page_size,
+            start: cursor.next_index,
+            total_items: cursor.total_items,
+            content_hash: cursor.content_hash.clone(),
+            session_id: None,
+            items,
+        };
+        let next_index = read.start + read.items.len();
+        match resume_offset.filter(|_| next_index < read.total_items) {
+            Some(offset) => {
+                cursor.next_page += 1;
+                cursor.next_index = next_index;
+                cursor.byte_offset = offset;
+                cursor.touched = Instant::now();
+                Ok(Ok(PageRead { session_id: Some(session_id.to_string()), ..read }))
+            }
+            None => {
+                cursors.remove(session_id);
+                Ok(Ok(read))
+            }
+        }
+    }
+
+    /// Store a cursor and return its session id, making room by dropping expired cursors
+    /// and then the least recently used one.
+    fn open_cursor(&self, cursor: ReadCursor) -> String {
+        let mut cursors = self.cursors.lock().unwrap_or_else(|e| e.into_inner());
+        cursors.retain(|_, cursor| cursor.touched.elapsed() < READ_CURSOR_IDLE_TIMEOUT);
+        if cursors.len() >= MAX_READ_CURSORS {
+            if let Some(oldest) = cursors.iter().min_by_key(|(_, cursor)| cursor.touched).map(|(id, _)| id.clone()) {
+                cursors.remove(&oldest);
+            }
+        }
+        let session_id = uuid::Uuid::new_v4().to_string();
+        cursors.insert(session_id.clone(), cursor);
+        session_id
+    }
+
     fn stream_json_file(
         &self,
         file_path: &str,
-        query: Option<&str>,
+        filter: &RecordFilter,
         limit: usize,
         offset: usize,
-    ) -> anyhow::Result<Value> {
-        let file = File::open(file_path)
-            .map_err(|e| anyhow::anyhow!("Failed to open file '{}': {}", file_path, e))?;
-
-        let reader = BufReader::new(file);
-        let mut results = Vec::new();
-        let mut current_offset = 0;
-        let mut found_results = 0;
+        replacements: Option<&Arc<Mutex<Replacements>>>,
+    ) -> anyhow::Result<StreamOutcome> {
+        // With `replacements`, invalid UTF-8 is replaced as the file is read
+        let open = |replacements: Option<&Arc<Mutex<Replacements>>>| -> anyhow::Result<Box<dyn Read + Send>> {
+            let file = storage::open(file_path)?;
+            Ok(match replacements {
+                Some(replacements) => Box::new(LossyReader::new(file, replacements.clone())),
+                None => file,
+            })
+        };
+        let sniffed = replacements.map(|_| Arc::new(Mutex::new(Replacements::default())));
+        let reader = BufReader::new(open(sniffed.as_ref())?);
+        let mut collector = MatchCollector::new(filter, limit, offset);
 
         // Try to detect if this is a line-delimited JSON file
         let mut lines = reader.lines();
         let mut is_line_delimited = false;
 
         // Read first few lines to detect format
-        let mut first_lines = Vec::new();
         for _ in 0..5 {
             if let Some(Ok(line)) = lines.next() {
-                let line_clone = line.clone();
-                first_lines.push(line);
-                if line_clone.trim().starts_with('{') && line_clone.trim().ends_with('}') {
-                    if serde_json::from_str::<Value>(&line_clone).is_ok() {
-                        is_line_delimited = true;
-                        break;
-                    }
+                if line.trim().starts_with('{')
+                    && line.trim().ends_with('}')
+                    && serde_json::from_str::<Value>(&line).is_ok()
+                {
+                    is_line_delimited = true;
+                    break;
                 }
             } else {
                 break;
@@ -119,104 +1534,229 @@ impl JsonStreaming {
 
         if is_line_delimited {
             // Process line-delimited JSON
-            let file = File::open(file_path)?;
-            let reader = BufReader::new(file);
-            
+            let reader = BufReader::new(open(replacements)?);
+
             for line in reader.lines() {
                 let line = line?;
                 if line.trim().is_empty() {
                     continue;
                 }
 
-                if current_offset < offset {
-                    current_offset += 1;
-                    continue;
-                }
-
-                if found_results >= limit {
-                    break;
-                }
-
-                if let Ok(json_value) = serde_json::from_str::<Value>(&line) {
-                    let should_include = if let Some(query_str) = query {
-                        // Apply JSONPath query to individual line
-                        match json_value.query(query_str) {
-                            Ok(results) => !results.is_empty(),
-                            Err(_) => false,
-                        }
-                    } else {
-                        true
-                    };
-
-                    if should_include {
-                        results.push(json_value);
-                        found_results += 1;
-                    }
+                match serde_json::from_str::<Value>(&line) {
+                    Ok(json_value) => collector.push(json_value),
+                    Err(_) => collector.skip(),
                 }
-                current_offset += 1;
             }
         } else {
             // Try to parse as regular JSON file and stream through it
-            let content = std::fs::read_to_string(file_path)?;
-            let json_value: Value = serde_json::from_str(&content)?;
+            let json_value: Value = serde_json::from_reader(BufReader::new(open(replacements)?))?;
 
             // If it's an array, we can stream through elements
             if let Value::Array(arr) = json_value {
-                for (_index, item) in arr.iter().enumerate() {
-                    if current_offset < offset {
-                        current_offset += 1;
-                        continue;
-                    }
-
-                    if found_results >= limit {
-                        break;
-                    }
-
-                    let should_include = if let Some(query_str) = query {
-                        match item.query(query_str) {
-                            Ok(results) => !results.is_empty(),
-                            Err(_) => false,
-                        }
-                    } else {
-                        true
-                    };
-
-                    if should_include {
-                        results.push(item.clone());
-                        found_results += 1;
-                    }
-                    current_offset += 1;
+                for item in arr {
+                    collector.push(item);
                 }
             } else {
                 // Single object - apply query if provided
-                let should_include = if let Some(query_str) = query {
-                    match json_value.query(query_str) {
-                        Ok(results) => !results.is_empty(),
-                        Err(_) => false,
-                    }
-                } else {
-                    true
-                };
+                collector.push(json_value);
+            }
+        }
 
-                if should_include && current_offset >= offset && found_results < limit {
-                    results.push(json_value);
-                }
+        Ok(collector.finish())
+    }
+}
+
+/// Size and modification time of a file, used to detect changes under a read cursor.
+fn file_state(file_path: &str) -> Option<(u64, Option<SystemTime>)> {
+    let info = storage::info(file_path).ok()??;
+    Some((info.size, info.modified))
+}
+
+/// Parsed NDJSON records, counting lines that are not valid JSON.
+#[derive(Default)]
+struct LineRecords {
+    records: Vec<Value>,
+    invalid: usize,
+    limit: usize,
+}
+
+impl LineRecords {
+    fn push_line(&mut self, line: &str) {
+        if line.trim().is_empty() || (self.limit > 0 && self.records.len() >= self.limit) {
+            return;
+        }
+        match serde_json::from_str(line) {
+            Ok(value) => self.records.push(value),
+            Err(_) => self.invalid += 1,
+        }
+    }
+}
+
+/// Read the last `count` records of `file` by scanning backwards in blocks, and return
+/// the offset just past the data consumed. Invalid lines are skipped (and counted), and a
+/// final line without a newline is only consumed if it already holds complete JSON.
+fn read_tail<R: Read + Seek>(file: &mut R, count: usize, out: &mut LineRecords) -> anyhow::Result<u64> {
+    let end = file.seek(SeekFrom::End(0))?;
+    let mut start = end;
+    let mut consumed = end;
+    // Bytes between `start` and the earliest line handled so far
+    let mut pending: Vec<u8> = Vec::new();
+    let mut at_last_line = true;
+    let mut newest_first = Vec::new();
+
+    while newest_first.len() < count || at_last_line {
+        if start > 0 {
+            let block_start = start.saturating_sub(TAIL_BLOCK_SIZE);
+            let mut block = vec![0; (start - block_start) as usize];
+            file.seek(SeekFrom::Start(block_start))?;
+            file.read_exact(&mut block)?;
+            block.extend_from_slice(&pending);
+            pending = block;
+            start = block_start;
+        }
+
+        // A line is complete once its preceding newline (or the start of the file) is in view
+        loop {
+            let line = match pending.iter().rposition(|&b| b == b'\n') {
+                Some(newline) => pending.split_off(newline + 1),
+                None if start == 0 && !pending.is_empty() => std::mem::take(&mut pending),
+                None => break,
+            };
+            pending.pop();
+            let is_last_line = std::mem::replace(&mut at_last_line, false);
+            let text = String::from_utf8_lossy(&line);
+            if text.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<Value>(&text) {
+                Ok(value) => newest_first.push(value),
+                Err(_) if is_last_line => consumed -= line.len() as u64,
+                Err(_) => out.invalid += 1,
             }
+            if newest_first.len() >= count {
+                break;
+            }
+        }
+
+        if start == 0 {
+            break;
         }
+    }
+
+    newest_first.truncate(count);
+    out.records.extend(newest_first.into_iter().rev());
+    Ok(consumed)
+}
+
+/// Parse the complete lines between `from` and `to`, returning the number of bytes used.
+fn read_complete_lines<R: Read + Seek>(file: &mut R, from: u64, to: u64, out: &mut LineRecords) -> anyhow::Result<u64> {
+    let mut buffer = vec![0; (to - from) as usize];
+    file.seek(SeekFrom::Start(from))?;
+    file.read_exact(&mut buffer)?;
+    let Some(last_newline) = buffer.iter().rposition(|&b| b == b'\n') else {
+        return Ok(0);
+    };
+    for line in String::from_utf8_lossy(&buffer[..last_newline]).split('\n') {
+        out.push_line(line);
+    }
+    Ok(last_newline as u64 + 1)
+}
+
+/// Items selected by a streamed read, plus what is needed to resume after them.
+struct StreamOutcome {
+    results: Vec<Value>,
+    total_matched: usize,
+    next_offset: Option<usize>,
+}
+
+/// Conditions a streamed record must meet: the JSONPath `query` selects something in
+/// it, its timestamp is in the `window`, and it satisfies the `where` predicate.
+struct RecordFilter<'a> {
+    query: Option<&'a str>,
+    window: Option<&'a TimeWindow>,
+    predicate: Option<&'a Predicate>,
+}
+
+impl RecordFilter<'_> {
+    fn matches(&self, item: &Value) -> bool {
+        self.query.map_or(true, |query| item.query(query).map(|r| !r.is_empty()).unwrap_or(false))
+            && self.window.map_or(true, |window| window.contains(item))
+            && self.predicate.map_or(true, |predicate| predicate.matches(item))
+    }
+}
+
+/// Collects matching items at or after `offset` (up to `limit`) while counting every
+/// match in the input, so callers learn whether more results remain.
+struct MatchCollector<'a> {
+    filter: &'a RecordFilter<'a>,
+    limit: usize,
+    offset: usize,
+    position: usize,
+    matched_from_offset: usize,
+    resume_at: usize,
+    results: Vec<Value>,
+    total_matched: usize,
+}
 
-        Ok(Value::Array(results))
+impl<'a> MatchCollector<'a> {
+    fn new(filter: &'a RecordFilter<'a>, limit: usize, offset: usize) -> Self {
+        Self {
+            filter,
+            limit,
+            offset,
+            position: 0,
+            matched_from_offset: 0,
+            resume_at: offset,
+            results: Vec::new(),
+            total_matched: 0,
+        }
+    }
+
+    fn push(&mut self, item: Value) {
+        let index = self.position;
+        self.position += 1;
+
+        if !self.filter.matches(&item) {
+            return;
+        }
+
+        self.total_matched += 1;
+        if index < self.offset {
+            return;
+        }
+        self.matched_from_offset += 1;
+        if self.results.len() < self.limit {
+            self.results.push(item);
+            self.resume_at = index + 1;
+        }
+    }
+
+    /// Account for an input item that could not be parsed.
+    fn skip(&mut self) {
+        self.position += 1;
+    }
+
+    fn finish(self) -> StreamOutcome {
+        let truncated = self.matched_from_offset > self.results.len();
+        StreamOutcome {
+            next_offset: truncated.then_some(self.resume_at),
+            total_matched: self.total_matched,
+            results: self.results,
+        }
     }
 }
 
 #[async_trait]
 impl ToolHandler for JsonStreaming {
     async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
-        Ok(vec![Self::create_stream_read_tool()])
+        Ok(vec![Self::create_stream_read_tool(), Self::create_tail_tool(), Self::create_children_tool()])
     }
 
     async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
         match tool_call.name.as_str() {
             "json-read" => self.handle_stream_read(&tool_call.arguments).await,
+            "json-tail" => self.handle_tail(&tool_call.arguments).await,
+            "json-children" => self.handle_children(&tool_call.arguments).await,
             _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
         }
     }
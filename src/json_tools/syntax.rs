@@ -0,0 +1,190 @@
+use regex::Regex;
+use serde_json::{json, Value};
+use std::sync::OnceLock;
+
+/// Why a JSONPath expression does not parse, with the 1-based column of the problem.
+#[derive(Debug, Clone)]
+pub struct SyntaxError {
+    pub column: Option<usize>,
+    pub expected: Option<String>,
+    pub message: String,
+}
+
+impl SyntaxError {
+    /// One-line description, e.g. "at column 11: expected atom_expr".
+    pub fn summary(&self) -> String {
+        match (self.column, &self.expected) {
+            (Some(column), Some(expected)) => format!("at column {}: expected {}", column, expected),
+            (Some(column), None) => format!("at column {}", column),
+            _ => self.message.clone(),
+        }
+    }
+}
+
+/// Parse `query` with the JSONPath engine without running it.
+pub fn check_syntax(query: &str) -> Result<(), SyntaxError> {
+    let Err(error) = jsonpath_rust::parser::parse_json_path(query) else {
+        return Ok(());
+    };
+    // The parser reports positions as " --> line:column" and its expectation as "= expected ..."
+    let message = error.to_string();
+    let column = message.split_once("-->")
+        .and_then(|(_, rest)| rest.split_whitespace().next())
+        .and_then(|position| position.split(':').nth(1))
+        .and_then(|column| column.parse().ok());
+    let expected = message.lines()
+        .find_map(|line| line.trim().strip_prefix("= expected "))
+        .map(str::to_string);
+    Err(SyntaxError { column, expected, message })
+}
+
+/// A rewrite for a mistake commonly made when writing JSONPath by hand.
+struct Fix {
+    description: &'static str,
+    apply: fn(&str) -> Option<String>,
+}
+
+fn regex(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).expect("built-in JSONPath fix patterns are valid"))
+}
+
+fn replace_all(re: &Regex, text: &str, replacement: &str) -> Option<String> {
+    re.is_match(text).then(|| re.replace_all(text, replacement).into_owned())
+}
+
+const FIXES: &[Fix] = &[
+    Fix {
+        description: "replaced typographic quotes with plain quotes",
+        apply: |q| q.contains(['\u{201c}', '\u{201d}', '\u{2018}', '\u{2019}']).then(|| {
+            q.replace(['\u{201c}', '\u{201d}'], "\"").replace(['\u{2018}', '\u{2019}'], "'")
+        }),
+    },
+    Fix {
+        description: "converted a JSON Pointer to JSONPath",
+        apply: |q| {
+            let rest = q.trim().strip_prefix('/')?;
+            let mut path = String::from("$");
+            for token in rest.split('/') {
+                let token = token.replace("~1", "/").replace("~0", "~");
+                if !token.is_empty() && token.chars().all(|c| c.is_ascii_digit()) {
+                    path.push_str(&format!("[{}]", token));
+                } else {
+                    path.push_str(&format!("['{}']", token.replace('\'', "\\'")));
+                }
+            }
+            Some(path)
+        },
+    },
+    Fix {
+        description: "added the '$' root",
+        apply: |q| {
+            let q = q.trim();
+            match q.chars().next()? {
+                '$' | '/' => None,
+                '.' | '[' => Some(format!("${}", q)),
+                _ => Some(format!("$.{}", q)),
+            }
+        },
+    },
+    Fix {
+        description: "added the '.' after '$'",
+        apply: |q| {
+            let rest = q.trim().strip_prefix('$')?;
+            rest.chars().next().filter(|c| c.is_alphanumeric() || *c == '_').map(|_| format!("$.{}", rest))
+        },
+    },
+    Fix {
+        description: "removed a trailing '.'",
+        apply: |q| {
+            let trimmed = q.trim();
+            (trimmed.ends_with('.') && !trimmed.ends_with("..")).then(|| trimmed.trim_end_matches('.').to_string())
+        },
+    },
+    Fix {
+        description: "replaced and/or/not with &&, || and !",
+        apply: |q| {
+            static AND: OnceLock<Regex> = OnceLock::new();
+            static OR: OnceLock<Regex> = OnceLock::new();
+            static NOT: OnceLock<Regex> = OnceLock::new();
+            let and = regex(&AND, r"(?i)\s+and\s+");
+            let or = regex(&OR, r"(?i)\s+or\s+");
+            let not = regex(&NOT, r"(?i)\bnot\s+");
+            if !(and.is_match(q) || or.is_match(q) || not.is_match(q)) {
+                return None;
+            }
+            let q = and.replace_all(q, " && ");
+            let q = or.replace_all(&q, " || ");
+            Some(not.replace_all(&q, "!").into_owned())
+        },
+    },
+    Fix {
+        description: "lowercased true/false and replaced None with null",
+        apply: |q| {
+            static CELL: OnceLock<Regex> = OnceLock::new();
+            let re = regex(&CELL, r"\b(True|TRUE|False|FALSE|None|NULL|Null)\b");
+            re.is_match(q).then(|| re.replace_all(q, |caps: &regex::Captures| match &caps[1] {
+                "None" | "NULL" | "Null" => "null".to_string(),
+                other => other.to_lowercase(),
+            }).into_owned())
+        },
+    },
+    Fix {
+        description: "added '@' to field references in the filter",
+        apply: |q| {
+            static CELL: OnceLock<Regex> = OnceLock::new();
+            // `?(.age`, `?(age`, `&& name` followed by a comparison or logical operator
+            let re = regex(&CELL, r"(\?\(?\s*|&&\s*|\|\|\s*|!\s*|\(\s*)\.?([A-Za-z_][A-Za-z0-9_.]*)(\s*(?:==|!=|<=|>=|<|>|=|&&|\|\||\)))");
+            let fixed = re.replace_all(q, |caps: &regex::Captures| {
+                let name = &caps[2];
+                if matches!(name, "true" | "false" | "null") {
+                    caps[0].to_string()
+                } else {
+                    format!("{}@.{}{}", &caps[1], name, &caps[3])
+                }
+            });
+            (fixed != q).then(|| fixed.into_owned())
+        },
+    },
+    Fix {
+        description: "replaced '=' with '==' in the filter",
+        apply: |q| {
+            static CELL: OnceLock<Regex> = OnceLock::new();
+            replace_all(regex(&CELL, r"([^=!<>])=([^=])"), q, "$1==$2")
+        },
+    },
+];
+
+/// Try the known fixes on an invalid query, returning the corrected query and the fixes
+/// applied if the result parses.
+pub fn suggest_fix(query: &str) -> Option<(String, Vec<&'static str>)> {
+    let mut current = query.to_string();
+    let mut applied = Vec::new();
+    for fix in FIXES {
+        if check_syntax(&current).is_ok() {
+            break;
+        }
+        if let Some(fixed) = (fix.apply)(&current).filter(|fixed| *fixed != current) {
+            current = fixed;
+            applied.push(fix.description);
+        }
+    }
+    (check_syntax(&current).is_ok() && !applied.is_empty()).then_some((current, applied))
+}
+
+/// Structured report for json-validate-query and json-query's pre-check.
+pub fn syntax_report(query: &str, error: &SyntaxError) -> Value {
+    let mut report = json!({
+        "valid": false,
+        "query": query,
+        "error": error.summary(),
+        "column": error.column,
+        "expected": error.expected,
+    });
+    if let Some(column) = error.column {
+        report["pointer"] = json!(format!("{}\n{}^", query, " ".repeat(column.saturating_sub(1))));
+    }
+    if let Some((fixed, changes)) = suggest_fix(query) {
+        report["suggestion"] = json!({"query": fixed, "changes": changes});
+    }
+    report
+}
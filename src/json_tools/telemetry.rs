@@ -0,0 +1,105 @@
+use crate::json_tools::stats::ServerStats;
+use serde_json::{json, Value};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+#[cfg(feature = "http")]
+use std::time::Duration;
+
+/// Timeout for posting a report, from connecting to reading the response.
+#[cfg(feature = "http")]
+const HTTP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where telemetry reports go.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TelemetrySink {
+    /// A local JSON Lines file, one report per line.
+    File(PathBuf),
+    /// An HTTPS endpoint each report is POSTed to as JSON (`http` feature).
+    Https(String),
+}
+
+impl TelemetrySink {
+    /// An `https://` URL or a file path. Plain HTTP is refused, so reports never cross the
+    /// network unencrypted, and endpoints need a server built with the `http` feature.
+    pub fn parse(destination: &str) -> Result<Self, String> {
+        if destination.starts_with("http://") {
+            return Err(format!("Telemetry endpoint '{}' uses plain HTTP; use an https:// endpoint or a file", destination));
+        }
+        let Some(rest) = destination.strip_prefix("https://") else {
+            return Ok(Self::File(PathBuf::from(destination)));
+        };
+        if cfg!(not(feature = "http")) {
+            return Err(format!("Telemetry endpoint '{}' needs a server built with the 'http' feature; use a file", destination));
+        }
+        if rest.split(['/', '?', '#']).next().unwrap_or_default().is_empty() {
+            return Err(format!("Telemetry endpoint '{}' has no host", destination));
+        }
+        Ok(Self::Https(destination.to_string()))
+    }
+}
+
+/// Opt-in usage reporting: aggregate counters of the server's stats (tool call counts,
+/// error categories, latency buckets) sent to a file or endpoint. Each report covers the
+/// whole run so far and carries a random id for the run, so a receiver keeps the latest
+/// report per run; nothing identifies the machine or the user.
+#[derive(Clone)]
+pub struct Telemetry {
+    sink: TelemetrySink,
+    run_id: String,
+    stats: Arc<ServerStats>,
+    known_tools: Vec<String>,
+}
+
+impl Telemetry {
+    pub fn new(sink: TelemetrySink, stats: Arc<ServerStats>, known_tools: Vec<String>) -> Self {
+        Self { sink, run_id: uuid::Uuid::new_v4().to_string(), stats, known_tools }
+    }
+
+    /// Whether the user asked every tool not to track them (`DO_NOT_TRACK=1`), which
+    /// overrides `--telemetry`.
+    pub fn do_not_track() -> bool {
+        std::env::var("DO_NOT_TRACK").is_ok_and(|value| !value.is_empty() && value != "0")
+    }
+
+    /// The report as it is sent.
+    pub fn report(&self) -> Value {
+        json!({
+            "run_id": self.run_id,
+            "server_version": env!("CARGO_PKG_VERSION"),
+            "os": std::env::consts::OS,
+            "arch": std::env::consts::ARCH,
+            "usage": self.stats.aggregates(&self.known_tools),
+        })
+    }
+
+    /// Send the current report. This blocks for up to a few seconds on an endpoint.
+    pub fn send(&self) -> anyhow::Result<()> {
+        let body = self.report().to_string();
+        match &self.sink {
+            TelemetrySink::File(path) => {
+                let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+                writeln!(file, "{}", body)?;
+            }
+            TelemetrySink::Https(url) => post(url, &body)?,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "http")]
+fn post(url: &str, body: &str) -> anyhow::Result<()> {
+    let agent = ureq::AgentBuilder::new().timeout(HTTP_TIMEOUT).redirects(0).build();
+    match agent.post(url).set("Content-Type", "application/json").send_string(body) {
+        Ok(response) if (200..300).contains(&response.status()) => Ok(()),
+        Ok(response) => anyhow::bail!("Telemetry endpoint answered {} {}", response.status(), response.status_text()),
+        Err(ureq::Error::Status(status, response)) => anyhow::bail!("Telemetry endpoint answered {} {}", status, response.status_text()),
+        Err(e) => anyhow::bail!("Telemetry endpoint '{}' could not be reached: {}", url, e),
+    }
+}
+
+#[cfg(not(feature = "http"))]
+fn post(url: &str, _body: &str) -> anyhow::Result<()> {
+    anyhow::bail!("Telemetry endpoint '{}' needs a server built with the 'http' feature", url)
+}
@@ -0,0 +1,142 @@
+use crate::json_tools::formats::{read_document, DocumentFormat};
+use crate::json_tools::storage;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use minijinja::Environment;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+pub struct JsonTemplate;
+
+impl Default for JsonTemplate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonTemplate {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn create_template_tool() -> Tool {
+        Tool {
+            name: "json-template".to_string(),
+            description: "Render a Jinja-style template (minijinja) with a JSON data file as context, producing a JSON or text file. Useful for generating configs or reports from queried data.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "template_path": {
+                        "type": "string",
+                        "description": "Path to the template file (use this or 'template')"
+                    },
+                    "template": {
+                        "type": "string",
+                        "description": "Inline template source, e.g. '{\"name\": {{ user.name|tojson }}}'"
+                    },
+                    "data_path": {
+                        "type": "string",
+                        "description": "JSON (or TOML/MessagePack/CBOR) file used as the template context"
+                    },
+                    "data": {
+                        "type": "object",
+                        "description": "Inline context, used instead of or merged over data_path"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "File to write the rendered output to. The output is returned when omitted"
+                    },
+                    "output_format": {
+                        "type": "string",
+                        "enum": ["json", "text"],
+                        "description": "'json' checks that the output parses as JSON and pretty-prints it; 'text' writes it verbatim (default: json for .json output paths, otherwise text)"
+                    }
+                }
+            })
+        }
+    }
+
+    async fn handle_template(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let source = match (
+            args.get("template_path").and_then(|v| v.as_str()),
+            args.get("template").and_then(|v| v.as_str()),
+        ) {
+            (Some(path), None) => fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("Failed to read template '{}': {}", path, e))?,
+            (None, Some(template)) => template.to_string(),
+            _ => return Ok(ToolResult::error(
+                "Provide exactly one of 'template_path' or 'template'. Usage example:\n{\n  \"template_path\": \"./config.json.j2\",\n  \"data_path\": \"./values.json\",\n  \"output_path\": \"./config.json\"\n}".to_string()
+            )),
+        };
+
+        let mut context = match args.get("data_path").and_then(|v| v.as_str()) {
+            Some(path) => read_document(path, DocumentFormat::from_path(path))?,
+            None => json!({}),
+        };
+        if let Some(data) = args.get("data") {
+            match (&mut context, data) {
+                (Value::Object(context), Value::Object(data)) => {
+                    context.extend(data.iter().map(|(k, v)| (k.clone(), v.clone())));
+                }
+                _ => context = data.clone(),
+            }
+        }
+
+        let output_path = args.get("output_path").and_then(|v| v.as_str());
+        let as_json = match args.get("output_format").and_then(|v| v.as_str()) {
+            Some("json") => true,
+            Some("text") => false,
+            Some(other) => return Ok(ToolResult::error(format!("Unknown output_format: {}. Use 'json' or 'text'", other))),
+            None => output_path
+                .and_then(|path| Path::new(path).extension())
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("json")),
+        };
+
+        let env = Environment::new();
+        let rendered = match env.render_str(&source, minijinja::Value::from_serialize(&context)) {
+            Ok(rendered) => rendered,
+            Err(e) => return Ok(ToolResult::error(format!("Template error: {:#}", e))),
+        };
+
+        let output = if as_json {
+            match serde_json::from_str::<Value>(&rendered) {
+                Ok(value) => serde_json::to_string_pretty(&value)?,
+                Err(e) => return Ok(ToolResult::error(format!(
+                    "Rendered output is not valid JSON ({}). Use the 'tojson' filter for values, or output_format 'text'. Output was:\n{}",
+                    e, rendered
+                ))),
+            }
+        } else {
+            rendered
+        };
+
+        match output_path {
+            Some(output_path) => {
+                storage::create_parent_dirs(output_path)?;
+                storage::write(output_path, output.as_bytes())?;
+                Ok(ToolResult::success(format!(
+                    "Rendered template to '{}' ({}, {} bytes)",
+                    output_path, if as_json { "json" } else { "text" }, output.len()
+                )))
+            }
+            None => Ok(ToolResult::success(format!("Rendered template:\n\n{}", output))),
+        }
+    }
+}
+
+#[async_trait]
+impl ToolHandler for JsonTemplate {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_template_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-template" => self.handle_template(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
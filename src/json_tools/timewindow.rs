@@ -0,0 +1,123 @@
+use crate::json_tools::sql::lookup_field;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Field names tried, in order, when no `time_field` is given.
+const DEFAULT_TIME_FIELDS: &[&str] = &["timestamp", "@timestamp", "time", "ts", "date", "created_at"];
+
+/// JSON Schema for the `since` and `until` arguments shared by the read and query tools.
+pub fn time_bound_schema(bound: &str) -> Value {
+    json!({
+        "description": format!(
+            "Only return records whose timestamp is {} this instant: RFC 3339 ('2025-01-31T12:00:00Z'), a date ('2025-01-31', midnight UTC) or epoch seconds/milliseconds",
+            if bound == "since" { "at or after" } else { "before" }
+        ),
+        "type": ["string", "number"]
+    })
+}
+
+/// JSON Schema for the `time_field` argument.
+pub fn time_field_schema() -> Value {
+    json!({
+        "type": "string",
+        "description": "Dotted field holding each record's timestamp for since/until (default: the first of timestamp, @timestamp, time, ts, date, created_at present in the record)"
+    })
+}
+
+/// A half-open `[since, until)` range of instants that records are filtered on.
+#[derive(Debug)]
+pub struct TimeWindow {
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    field: Option<String>,
+}
+
+impl TimeWindow {
+    /// Read `since`, `until` and `time_field`. Returns `None` when neither bound is given.
+    pub fn from_args(args: &HashMap<String, Value>) -> anyhow::Result<Option<Self>> {
+        let bound = |name: &str| -> anyhow::Result<Option<DateTime<Utc>>> {
+            match args.get(name) {
+                None | Some(Value::Null) => Ok(None),
+                Some(value) => parse_timestamp(value).map(Some).ok_or_else(|| anyhow::anyhow!(
+                    "{} must be an RFC 3339 timestamp, a date (YYYY-MM-DD) or epoch seconds/milliseconds, got: {}", name, value
+                )),
+            }
+        };
+        let (since, until) = (bound("since")?, bound("until")?);
+        if since.is_none() && until.is_none() {
+            return Ok(None);
+        }
+        if let (Some(since), Some(until)) = (since, until) {
+            if since >= until {
+                anyhow::bail!("since ({}) must be earlier than until ({})", since.to_rfc3339(), until.to_rfc3339());
+            }
+        }
+        let field = args.get("time_field").and_then(|v| v.as_str()).map(str::to_string);
+        Ok(Some(Self { since, until, field }))
+    }
+
+    /// The record's timestamp, or `None` when it has no parseable timestamp field.
+    pub fn timestamp_of(&self, record: &Value) -> Option<DateTime<Utc>> {
+        match &self.field {
+            Some(field) => parse_timestamp(&lookup_field(record, field)),
+            None => DEFAULT_TIME_FIELDS.iter()
+                .map(|field| record.get(*field))
+                .find(|value| value.is_some_and(|v| !v.is_null()))
+                .flatten()
+                .and_then(parse_timestamp),
+        }
+    }
+
+    /// Whether `record` has a timestamp inside the window.
+    pub fn contains(&self, record: &Value) -> bool {
+        self.timestamp_of(record).is_some_and(|ts| {
+            self.since.map_or(true, |since| ts >= since) && self.until.map_or(true, |until| ts < until)
+        })
+    }
+
+    /// Describe the window for result metadata.
+    pub fn describe(&self) -> Value {
+        json!({
+            "since": self.since.map(|t| t.to_rfc3339()),
+            "until": self.until.map(|t| t.to_rfc3339()),
+            "time_field": self.field,
+        })
+    }
+}
+
+/// Parse RFC 3339 / ISO 8601 strings, plain dates and epoch numbers (or numeric strings).
+/// Epoch values are read as seconds, milliseconds, microseconds or nanoseconds by magnitude.
+pub fn parse_timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::Number(n) => epoch(n.as_f64()?),
+        Value::String(s) => {
+            let s = s.trim();
+            if let Ok(ts) = DateTime::parse_from_rfc3339(s) {
+                return Some(ts.with_timezone(&Utc));
+            }
+            if let Ok(number) = s.parse::<f64>() {
+                return epoch(number);
+            }
+            ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"].iter()
+                .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+                .or_else(|| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok().and_then(|d| d.and_hms_opt(0, 0, 0)))
+                .map(|naive| Utc.from_utc_datetime(&naive))
+        }
+        _ => None,
+    }
+}
+
+fn epoch(value: f64) -> Option<DateTime<Utc>> {
+    let magnitude = value.abs();
+    let nanos = if magnitude < 1e11 {
+        value * 1e9
+    } else if magnitude < 1e14 {
+        value * 1e6
+    } else if magnitude < 1e17 {
+        value * 1e3
+    } else {
+        value
+    };
+    (nanos.is_finite() && nanos.abs() < i64::MAX as f64).then(|| Utc.timestamp_nanos(nanos as i64))
+}
@@ -0,0 +1,303 @@
+use crate::json_tools::formats::{read_document, serialize_for_path, DocumentFormat};
+use crate::json_tools::integrity::{file_content_hash, hash_matches, sha256_hex};
+use crate::json_tools::patch::{apply_json_patch, apply_merge_patch};
+use crate::json_tools::storage;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub struct JsonTransaction;
+
+impl Default for JsonTransaction {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The pending state of one file touched by a transaction.
+struct StagedFile {
+    path: PathBuf,
+    existed: bool,
+    /// New serialized content, or `None` when the file is deleted
+    content: Option<Vec<u8>>,
+    temp: PathBuf,
+    backup: PathBuf,
+}
+
+impl JsonTransaction {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn create_transaction_tool() -> Tool {
+        Tool {
+            name: "json-transaction".to_string(),
+            description: "Apply write, merge-patch, JSON Patch and delete operations across several files atomically: every file is updated or none is, so related config files never end up half-updated.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "operations": {
+                        "type": "array",
+                        "description": "Operations applied in order; several operations may target the same file",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "file_path": {
+                                    "type": "string",
+                                    "description": "File to update (JSON, TOML, MessagePack or CBOR by extension)"
+                                },
+                                "action": {
+                                    "type": "string",
+                                    "enum": ["write", "merge_patch", "patch", "delete"],
+                                    "description": "'write' replaces the document with 'data', 'merge_patch' applies 'data' as an RFC 7386 merge patch (null removes a key), 'patch' applies an RFC 6902 JSON Patch from 'patch', 'delete' removes the file (default: write)"
+                                },
+                                "data": {
+                                    "description": "Document for 'write', or merge patch for 'merge_patch'"
+                                },
+                                "patch": {
+                                    "type": "array",
+                                    "description": "RFC 6902 operations, e.g. [{\"op\": \"replace\", \"path\": \"/version\", \"value\": 2}]"
+                                },
+                                "if_hash": {
+                                    "type": "string",
+                                    "description": "Abort the whole transaction unless the file's current content_hash matches"
+                                }
+                            },
+                            "required": ["file_path"]
+                        }
+                    },
+                    "dry_run": {
+                        "type": "boolean",
+                        "description": "Validate all operations and report the result without touching any file (default: false)"
+                    }
+                },
+                "required": ["operations"]
+            })
+        }
+    }
+
+    async fn handle_transaction(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let operations = args.get("operations")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| anyhow::anyhow!("operations is required. Usage example:\n{{\n  \"operations\": [\n    {{\"file_path\": \"./app.json\", \"action\": \"merge_patch\", \"data\": {{\"port\": 8080}}}},\n    {{\"file_path\": \"./proxy.json\", \"action\": \"patch\", \"patch\": [{{\"op\": \"replace\", \"path\": \"/upstream/port\", \"value\": 8080}}]}}\n  ]\n}}"))?;
+        let dry_run = args.get("dry_run").and_then(|v| v.as_bool()).unwrap_or(false);
+
+        if operations.is_empty() {
+            return Ok(ToolResult::error("operations must contain at least one operation".to_string()));
+        }
+
+        // Apply every operation in memory first; nothing touches the disk until all succeed
+        let mut documents: Vec<(String, bool, Option<Value>)> = Vec::new();
+        for (i, operation) in operations.iter().enumerate() {
+            if let Err(e) = Self::apply_in_memory(&mut documents, operation) {
+                return Ok(ToolResult::error(format!(
+                    "Transaction aborted at operation {}: {}. No files were changed.", i, e
+                )));
+            }
+        }
+
+        let mut staged = Vec::with_capacity(documents.len());
+        for (file_path, existed, document) in &documents {
+            if !existed && document.is_none() {
+                continue;
+            }
+            let content = match document {
+                Some(document) => match serialize_for_path(file_path, document, DocumentFormat::from_path(file_path), true) {
+                    Ok(content) => Some(content),
+                    Err(e) => return Ok(ToolResult::error(format!(
+                        "Transaction aborted: cannot serialize '{}': {}. No files were changed.", file_path, e
+                    ))),
+                },
+                None => None,
+            };
+            let path = PathBuf::from(file_path);
+            staged.push(StagedFile {
+                temp: sibling(&path, "txn.tmp"),
+                backup: sibling(&path, "txn.bak"),
+                path,
+                existed: *existed,
+                content,
+            });
+        }
+
+        let summary: Vec<Value> = staged.iter()
+            .map(|file| match &file.content {
+                Some(content) => json!({
+                    "file_path": file.path.display().to_string(),
+                    "status": if file.existed { "updated" } else { "created" },
+                    "content_hash": format!("sha256:{}", sha256_hex(content)),
+                }),
+                None => json!({
+                    "file_path": file.path.display().to_string(),
+                    "status": "deleted",
+                }),
+            })
+            .collect();
+
+        if dry_run {
+            return Ok(ToolResult::success(format!(
+                "Dry run: transaction of {} operation(s) would change {} file(s):\n\n{}",
+                operations.len(), staged.len(), serde_json::to_string_pretty(&summary)?
+            )));
+        }
+
+        if let Err(e) = Self::stage(&staged) {
+            Self::discard(&staged);
+            return Ok(ToolResult::error(format!("Transaction aborted while staging: {}. No files were changed.", e)));
+        }
+        if let Err(e) = Self::commit(&staged) {
+            let rollback = Self::rollback(&staged);
+            Self::discard(&staged);
+            return Ok(ToolResult::error(match rollback {
+                Ok(()) => format!("Transaction failed while committing: {}. All files were restored.", e),
+                Err(rollback_error) => format!(
+                    "Transaction failed while committing: {}. Rollback also failed: {}. Backups (*.txn.bak) were left in place for manual recovery.",
+                    e, rollback_error
+                ),
+            }));
+        }
+        for file in &staged {
+            let _ = fs::remove_file(&file.backup);
+        }
+
+        Ok(ToolResult::success(format!(
+            "Transaction committed: {} operation(s) across {} file(s):\n\n{}",
+            operations.len(), staged.len(), serde_json::to_string_pretty(&summary)?
+        )))
+    }
+
+    fn apply_in_memory(documents: &mut Vec<(String, bool, Option<Value>)>, operation: &Value) -> anyhow::Result<()> {
+        let file_path = operation.get("file_path").and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("file_path is required"))?;
+        let action = operation.get("action").and_then(|v| v.as_str()).unwrap_or("write");
+
+        let index = match documents.iter().position(|(path, _, _)| path == file_path) {
+            Some(index) => index,
+            None => {
+                let existed = Path::new(file_path).exists();
+                if let Some(expected) = operation.get("if_hash").and_then(|v| v.as_str()) {
+                    if !existed {
+                        anyhow::bail!("'{}' does not exist but if_hash was given", file_path);
+                    }
+                    let actual = file_content_hash(file_path)?;
+                    if !hash_matches(expected, &actual) {
+                        anyhow::bail!("'{}' was modified since it was read (expected {}, found {})", file_path, expected, actual);
+                    }
+                }
+                let document = match (existed, action) {
+                    (true, "merge_patch" | "patch") => Some(read_document(file_path, DocumentFormat::from_path(file_path))?),
+                    _ => None,
+                };
+                documents.push((file_path.to_string(), existed, document));
+                documents.len() - 1
+            }
+        };
+        let document = &mut documents[index].2;
+
+        match action {
+            "write" => {
+                let data = operation.get("data")
+                    .ok_or_else(|| anyhow::anyhow!("'write' on '{}' requires 'data'", file_path))?;
+                *document = Some(data.clone());
+            }
+            "merge_patch" => {
+                let data = operation.get("data")
+                    .ok_or_else(|| anyhow::anyhow!("'merge_patch' on '{}' requires 'data'", file_path))?;
+                apply_merge_patch(document.get_or_insert_with(|| json!({})), data);
+            }
+            "patch" => {
+                let patch = operation.get("patch").and_then(|v| v.as_array())
+                    .ok_or_else(|| anyhow::anyhow!("'patch' on '{}' requires a 'patch' array", file_path))?;
+                let target = document.as_mut()
+                    .ok_or_else(|| anyhow::anyhow!("cannot patch '{}': the file does not exist", file_path))?;
+                apply_json_patch(target, patch).map_err(|e| anyhow::anyhow!("'{}': {}", file_path, e))?;
+            }
+            "delete" => {
+                if document.is_none() && !documents[index].1 {
+                    anyhow::bail!("cannot delete '{}': the file does not exist", file_path);
+                }
+                documents[index].2 = None;
+            }
+            other => anyhow::bail!("unknown action '{}'. Use write, merge_patch, patch or delete", other),
+        }
+        Ok(())
+    }
+
+    /// Write new content next to each target so the final renames stay on one filesystem.
+    fn stage(staged: &[StagedFile]) -> anyhow::Result<()> {
+        for file in staged {
+            let Some(content) = &file.content else { continue };
+            if let Some(parent) = file.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                fs::create_dir_all(parent)
+                    .map_err(|e| anyhow::anyhow!("cannot create directories for '{}': {}", file.path.display(), e))?;
+            }
+            fs::write(&file.temp, content)
+                .map_err(|e| anyhow::anyhow!("cannot write '{}': {}", file.temp.display(), e))?;
+            storage::carry_metadata(&file.path, &file.temp)?;
+        }
+        Ok(())
+    }
+
+    /// Move originals aside, then move staged files into place.
+    fn commit(staged: &[StagedFile]) -> anyhow::Result<()> {
+        for file in staged.iter().filter(|f| f.existed) {
+            fs::rename(&file.path, &file.backup)
+                .map_err(|e| anyhow::anyhow!("cannot back up '{}': {}", file.path.display(), e))?;
+        }
+        for file in staged.iter().filter(|f| f.content.is_some()) {
+            fs::rename(&file.temp, &file.path)
+                .map_err(|e| anyhow::anyhow!("cannot replace '{}': {}", file.path.display(), e))?;
+        }
+        Ok(())
+    }
+
+    fn rollback(staged: &[StagedFile]) -> anyhow::Result<()> {
+        let mut failures = Vec::new();
+        for file in staged {
+            if file.existed {
+                if file.backup.exists() {
+                    if let Err(e) = fs::rename(&file.backup, &file.path) {
+                        failures.push(format!("'{}': {}", file.path.display(), e));
+                    }
+                }
+            } else if file.content.is_some() && file.path.exists() {
+                let _ = fs::remove_file(&file.path);
+            }
+        }
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            anyhow::bail!(failures.join("; "))
+        }
+    }
+
+    fn discard(staged: &[StagedFile]) {
+        for file in staged {
+            let _ = fs::remove_file(&file.temp);
+        }
+    }
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().map(|n| n.to_os_string()).unwrap_or_default();
+    name.push(format!(".{}", suffix));
+    path.with_file_name(name)
+}
+
+#[async_trait]
+impl ToolHandler for JsonTransaction {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_transaction_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-transaction" => self.handle_transaction(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use crate::json_tools::formats::{read_document, serialize_for_path, DocumentFormat};
+use crate::json_tools::jsonpath::{dialect_schema, normalized_path_to_pointer, JsonPathDialect};
+use crate::json_tools::patch::{parse_pointer, pointer_get_mut};
+use crate::json_tools::storage;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// Results are rounded to this many decimals unless a conversion sets its own, which removes
+/// floating-point noise such as 19.99 dollars becoming 1998.9999999999998 cents.
+const DEFAULT_DECIMALS: i32 = 9;
+
+/// Known units as (names, dimension, size in the dimension's base unit).
+const UNITS: &[(&[&str], &str, f64)] = &[
+    (&["b", "byte", "bytes"], "data size", 1.0),
+    (&["kb", "kilobytes"], "data size", 1e3),
+    (&["mb", "megabytes"], "data size", 1e6),
+    (&["gb", "gigabytes"], "data size", 1e9),
+    (&["tb", "terabytes"], "data size", 1e12),
+    (&["kib", "kibibytes"], "data size", 1024.0),
+    (&["mib", "mebibytes"], "data size", 1_048_576.0),
+    (&["gib", "gibibytes"], "data size", 1_073_741_824.0),
+    (&["tib", "tebibytes"], "data size", 1_099_511_627_776.0),
+    (&["ns", "nanoseconds"], "time", 1e-9),
+    (&["us", "µs", "microseconds"], "time", 1e-6),
+    (&["ms", "milliseconds"], "time", 1e-3),
+    (&["s", "sec", "seconds"], "time", 1.0),
+    (&["min", "minutes"], "time", 60.0),
+    (&["h", "hours"], "time", 3600.0),
+    (&["d", "days"], "time", 86_400.0),
+    (&["cents", "cent"], "money", 0.01),
+    (&["dollars", "dollar", "euros", "euro"], "money", 1.0),
+    (&["mm", "millimeters"], "length", 1e-3),
+    (&["cm", "centimeters"], "length", 1e-2),
+    (&["m", "meters"], "length", 1.0),
+    (&["km", "kilometers"], "length", 1e3),
+];
+
+fn unit(name: &str) -> Option<(&'static str, f64)> {
+    let name = name.trim().to_lowercase();
+    UNITS.iter()
+        .find(|(names, _, _)| names.contains(&name.as_str()))
+        .map(|(_, dimension, size)| (*dimension, *size))
+}
+
+/// One configured conversion: multiply by `factor` and round to `decimals`.
+struct Conversion {
+    description: String,
+    factor: f64,
+    decimals: i32,
+}
+
+impl Conversion {
+    /// Parse "ms->s" or {"from": "ms", "to": "s", "decimals": 3}.
+    fn parse(spec: &Value) -> Result<Self, String> {
+        let (from, to, decimals) = match spec {
+            Value::String(text) => match text.split_once("->") {
+                Some((from, to)) => (from.to_string(), to.to_string(), None),
+                None => return Err(format!("'{}' is not a conversion; write it as 'from->to', e.g. 'ms->s'", text)),
+            },
+            Value::Object(spec) => (
+                spec.get("from").and_then(|v| v.as_str()).ok_or("a conversion object needs 'from'")?.to_string(),
+                spec.get("to").and_then(|v| v.as_str()).ok_or("a conversion object needs 'to'")?.to_string(),
+                spec.get("decimals").and_then(|v| v.as_i64()),
+            ),
+            other => return Err(format!("{} is not a conversion; use 'from->to' or {{\"from\", \"to\", \"decimals\"}}", other)),
+        };
+        let known = || UNITS.iter().map(|(names, _, _)| names[0]).collect::<Vec<_>>().join(", ");
+        let (from_dimension, from_size) = unit(&from).ok_or_else(|| format!("Unknown unit '{}'. Known units: {}", from.trim(), known()))?;
+        let (to_dimension, to_size) = unit(&to).ok_or_else(|| format!("Unknown unit '{}'. Known units: {}", to.trim(), known()))?;
+        if from_dimension != to_dimension {
+            return Err(format!("Cannot convert {} ({}) to {} ({})", from.trim(), from_dimension, to.trim(), to_dimension));
+        }
+        Ok(Self {
+            description: format!("{}->{}", from.trim(), to.trim()),
+            factor: from_size / to_size,
+            decimals: decimals.map_or(DEFAULT_DECIMALS, |d| d.clamp(0, 15) as i32),
+        })
+    }
+
+    /// The converted number, as an integer when it has no fraction.
+    fn apply(&self, value: &Value) -> Result<Value, &'static str> {
+        let number = value.as_f64().ok_or("not a number")?;
+        let scale = 10f64.powi(self.decimals);
+        let converted = (number * self.factor * scale).round() / scale;
+        if !converted.is_finite() {
+            return Err("result is not a finite number");
+        }
+        if converted.fract() == 0.0 && converted.abs() < 9e15 {
+            return Ok(json!(converted as i64));
+        }
+        serde_json::Number::from_f64(converted).map(Value::Number).ok_or("result is not a finite number")
+    }
+}
+
+pub struct JsonUnits {
+    dialect: JsonPathDialect,
+}
+
+impl Default for JsonUnits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonUnits {
+    pub fn new() -> Self {
+        Self::with_dialect(JsonPathDialect::default())
+    }
+
+    /// Select values with `dialect` unless a call names another one.
+    pub fn with_dialect(dialect: JsonPathDialect) -> Self {
+        Self { dialect }
+    }
+
+    fn create_convert_units_tool() -> Tool {
+        Tool {
+            name: "json-convert-units".to_string(),
+            description: "Convert numbers between units at the JSONPaths you name and rewrite the file, e.g. when a config migrates from timeout_ms to seconds: bytes/KB/MB/GB/KiB/MiB, ns/us/ms/s/min/h/d, cents/dollars, mm/cm/m/km.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "JSON (or TOML/MessagePack/CBOR) file to convert"
+                    },
+                    "conversions": {
+                        "type": "object",
+                        "description": "JSONPath -> conversion, as 'from->to' or {\"from\", \"to\", \"decimals\"}, e.g. {\"$.server.timeout\": \"ms->s\", \"$.limits.max_upload\": \"bytes->MiB\", \"$.items[*].price\": {\"from\": \"cents\", \"to\": \"dollars\", \"decimals\": 2}}"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "Write the result here instead of updating file_path in place"
+                    },
+                    "dry_run": {
+                        "type": "boolean",
+                        "description": "Report the conversions without writing anything (default: false)",
+                        "default": false
+                    },
+                    "dialect": dialect_schema()
+                },
+                "required": ["file_path", "conversions"]
+            })
+        }
+    }
+
+    async fn handle_convert_units(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let file_path = args.get("file_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "file_path is required. Usage example:\n{{\n  \"file_path\": \"./config.json\",\n  \"conversions\": {{\"$.server.timeout\": \"ms->s\"}}\n}}"
+            ))?;
+        let conversions = args.get("conversions")
+            .and_then(|v| v.as_object())
+            .ok_or_else(|| anyhow::anyhow!(
+                "conversions is required. Usage example:\n{{\n  \"file_path\": \"./config.json\",\n  \"conversions\": {{\"$.server.timeout\": \"ms->s\", \"$.cache.size\": \"bytes->MB\"}}\n}}"
+            ))?;
+        let dialect = match JsonPathDialect::from_args(args, self.dialect) {
+            Ok(dialect) => dialect,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+        let mut parsed = Vec::new();
+        for (query, spec) in conversions {
+            match Conversion::parse(spec) {
+                Ok(conversion) => parsed.push((query, conversion)),
+                Err(message) => return Ok(ToolResult::error(format!("Conversion for '{}': {}", query, message))),
+            }
+        }
+        let dry_run = args.get("dry_run").and_then(|v| v.as_bool()).unwrap_or(false);
+
+        let format = DocumentFormat::from_path(file_path);
+        let mut document = read_document(file_path, format)?;
+        let mut report = Vec::new();
+        let mut total = 0;
+        for (query, conversion) in &parsed {
+            // Resolve every selected location before changing any of them
+            let selected = match dialect.query_with_paths(&document, query) {
+                Ok(found) => found.into_iter().map(|(path, _)| path).collect::<Vec<_>>(),
+                Err(message) => return Ok(ToolResult::error(format!("Conversion for '{}': {}", query, message))),
+            };
+            let mut converted = 0;
+            let mut example = None;
+            let mut skipped = Map::new();
+            for path in selected {
+                let pointer = normalized_path_to_pointer(&path)
+                    .ok_or_else(|| anyhow::anyhow!("Cannot convert matched path '{}' to a JSON Pointer", path))?;
+                let target = pointer_get_mut(&mut document, &parse_pointer(&pointer)?)?;
+                match conversion.apply(target) {
+                    Ok(value) => {
+                        example.get_or_insert_with(|| json!({"pointer": pointer, "from": target.clone(), "to": value.clone()}));
+                        *target = value;
+                        converted += 1;
+                    }
+                    Err(reason) => {
+                        skipped.insert(pointer, json!(reason));
+                    }
+                }
+            }
+            total += converted;
+            let mut entry = json!({"path": query, "conversion": conversion.description, "converted": converted});
+            if let Some(example) = example {
+                entry["example"] = example;
+            }
+            if !skipped.is_empty() {
+                entry["skipped"] = Value::Object(skipped);
+            }
+            report.push(entry);
+        }
+        let summary = serde_json::to_string_pretty(&json!({"converted": total, "conversions": report}))?;
+
+        if dry_run || total == 0 {
+            let note = if dry_run { "nothing was written" } else { "the file was not changed" };
+            return Ok(ToolResult::success(format!(
+                "Found {} value(s) to convert in '{}'; {}:\n\n{}", total, file_path, note, summary
+            )));
+        }
+
+        let output_path = args.get("output_path").and_then(|v| v.as_str()).unwrap_or(file_path);
+        let output_format = DocumentFormat::from_path(output_path);
+        let content = match serialize_for_path(output_path, &document, output_format, true) {
+            Ok(content) => content,
+            Err(e) => return Ok(ToolResult::error(format!("Cannot write '{}' as {}: {}", output_path, output_format.name(), e))),
+        };
+        storage::write(output_path, &content)?;
+
+        Ok(ToolResult::success(format!(
+            "Converted {} value(s) from '{}' and wrote '{}':\n\n{}", total, file_path, output_path, summary
+        )))
+    }
+}
+
+#[async_trait]
+impl ToolHandler for JsonUnits {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_convert_units_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-convert-units" => self.handle_convert_units(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
@@ -0,0 +1,339 @@
+use crate::json_tools::formats::{read_document, serialize_for_path, DocumentFormat};
+use crate::json_tools::integrity::{file_content_hash, hash_matches};
+use crate::json_tools::jsonpath::{dialect_schema, normalized_path_to_pointer, JsonPathDialect};
+use crate::json_tools::patch::{parse_pointer, pointer_get_mut};
+use crate::json_tools::storage;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use regex::Regex;
+use serde_json::{json, Value};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// At most this many changed locations are listed in the result message.
+const MAX_LISTED_CHANGES: usize = 20;
+
+pub struct JsonUpdate {
+    dialect: JsonPathDialect,
+}
+
+impl Default for JsonUpdate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What to do with each selected node.
+enum Operation {
+    Set(Value),
+    Increment(serde_json::Number),
+    Replace { pattern: Regex, replacement: String },
+    Delete,
+}
+
+impl Operation {
+    fn from_args(args: &HashMap<String, Value>) -> Result<Self, String> {
+        let name = args.get("operation").and_then(|v| v.as_str()).unwrap_or("set");
+        match name {
+            "set" => args.get("value").cloned().map(Self::Set)
+                .ok_or_else(|| "operation 'set' requires a value".to_string()),
+            "increment" => match args.get("value") {
+                None => Ok(Self::Increment(1.into())),
+                Some(Value::Number(by)) => Ok(Self::Increment(by.clone())),
+                Some(other) => Err(format!("operation 'increment' takes a numeric value, got {}", other)),
+            },
+            "replace" => {
+                let find = args.get("find").and_then(|v| v.as_str())
+                    .ok_or_else(|| "operation 'replace' requires find (the text to replace)".to_string())?;
+                let is_regex = args.get("regex").and_then(|v| v.as_bool()).unwrap_or(false);
+                let source = if is_regex { find.to_string() } else { regex::escape(find) };
+                let pattern = Regex::new(&source).map_err(|e| format!("Invalid find pattern '{}': {}", find, e))?;
+                let replacement = args.get("replace_with").and_then(|v| v.as_str()).unwrap_or("");
+                // Literal replacements must not expand `$1`-style references
+                let replacement = if is_regex { replacement.to_string() } else { replacement.replace('$', "$$") };
+                Ok(Self::Replace { pattern, replacement })
+            }
+            "delete" => Ok(Self::Delete),
+            other => Err(format!("Unknown operation '{}'. Use 'set', 'increment', 'replace' or 'delete'", other)),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Set(_) => "set",
+            Self::Increment(_) => "increment",
+            Self::Replace { .. } => "replace",
+            Self::Delete => "delete",
+        }
+    }
+
+    /// Modify `target` in place, or explain why it was skipped.
+    fn apply(&self, target: &mut Value) -> Result<(), &'static str> {
+        match (self, &mut *target) {
+            (Self::Set(value), _) => *target = value.clone(),
+            (Self::Increment(by), Value::Number(current)) => *current = add_numbers(current, by).ok_or("result is not a finite number")?,
+            (Self::Increment(_), _) => return Err("not a number"),
+            (Self::Replace { pattern, replacement }, Value::String(text)) => {
+                *text = pattern.replace_all(text, replacement.as_str()).into_owned();
+            }
+            (Self::Replace { .. }, _) => return Err("not a string"),
+            (Self::Delete, _) => unreachable!("deletions are applied to the parent"),
+        }
+        Ok(())
+    }
+}
+
+/// Integer addition when both sides are integers, floating point otherwise.
+fn add_numbers(a: &serde_json::Number, b: &serde_json::Number) -> Option<serde_json::Number> {
+    if let (Some(a), Some(b)) = (a.as_i64(), b.as_i64()) {
+        if let Some(sum) = a.checked_add(b) {
+            return Some(sum.into());
+        }
+    }
+    serde_json::Number::from_f64(a.as_f64()? + b.as_f64()?)
+}
+
+/// Order pointers so that array elements come after lower indexes of the same array.
+fn compare_tokens(a: &[String], b: &[String]) -> Ordering {
+    for (x, y) in a.iter().zip(b) {
+        let order = match (x.parse::<usize>(), y.parse::<usize>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            _ => x.cmp(y),
+        };
+        if order != Ordering::Equal {
+            return order;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+fn remove(document: &mut Value, tokens: &[String]) -> Result<(), &'static str> {
+    let Some((last, parent)) = tokens.split_last() else {
+        return Err("the document root cannot be deleted");
+    };
+    match pointer_get_mut(document, parent) {
+        Ok(Value::Object(map)) => map.shift_remove(last).map(|_| ()).ok_or("no such field"),
+        Ok(Value::Array(items)) => match last.parse::<usize>() {
+            Ok(index) if index < items.len() => {
+                items.remove(index);
+                Ok(())
+            }
+            _ => Err("no such element"),
+        },
+        _ => Err("no such location"),
+    }
+}
+
+impl JsonUpdate {
+    pub fn new() -> Self {
+        Self::with_dialect(JsonPathDialect::default())
+    }
+
+    /// Select nodes with `dialect` unless a call names another one.
+    pub fn with_dialect(dialect: JsonPathDialect) -> Self {
+        Self { dialect }
+    }
+
+    fn create_update_tool() -> Tool {
+        Tool {
+            name: "json-update".to_string(),
+            description: "Select nodes with a JSONPath and modify each one in a single atomic read-modify-write: set a value, increment a number, replace text in a string, or delete the node. Use 'field' to change a field of each selected object.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "Path to the JSON file to update"
+                    },
+                    "query": {
+                        "type": "string",
+                        "description": "JSONPath selecting the nodes to modify (e.g., '$.users[?(@.role == \"guest\")]')"
+                    },
+                    "operation": {
+                        "type": "string",
+                        "enum": ["set", "increment", "replace", "delete"],
+                        "description": "'set' replaces each node with value, 'increment' adds value (default 1) to numbers, 'replace' substitutes find with replace_with in strings, 'delete' removes the node (default: set)",
+                        "default": "set"
+                    },
+                    "field": {
+                        "type": "string",
+                        "description": "Apply the operation to this field of each selected object instead of the object itself. 'set' creates the field when missing"
+                    },
+                    "value": {
+                        "description": "New value for 'set', or the amount to add for 'increment'"
+                    },
+                    "find": {
+                        "type": "string",
+                        "description": "Text to replace for 'replace' (a regular expression when regex is true)"
+                    },
+                    "replace_with": {
+                        "type": "string",
+                        "description": "Replacement text for 'replace'; may use $1-style groups when regex is true (default: empty string)"
+                    },
+                    "regex": {
+                        "type": "boolean",
+                        "description": "Treat find as a regular expression (default: false)",
+                        "default": false
+                    },
+                    "if_hash": {
+                        "type": "string",
+                        "description": "Only update if the file's current content_hash equals this value; otherwise fail with a conflict"
+                    },
+                    "dialect": dialect_schema()
+                },
+                "required": ["file_path", "query"]
+            }),
+        }
+    }
+
+    async fn handle_update(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let file_path = args.get("file_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "file_path is required. Usage example:\n{{\n  \"file_path\": \"./users.json\",\n  \"query\": \"$.users[?(@.age > 30)]\",\n  \"field\": \"senior\",\n  \"value\": true\n}}"
+            ))?;
+
+        let query = args.get("query")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "query is required. Usage example:\n{{\n  \"file_path\": \"./users.json\",\n  \"query\": \"$.users[*].visits\",\n  \"operation\": \"increment\"\n}}"
+            ))?;
+
+        let operation = match Operation::from_args(args) {
+            Ok(operation) => operation,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+        let dialect = match JsonPathDialect::from_args(args, self.dialect) {
+            Ok(dialect) => dialect,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+        let field = args.get("field").and_then(|v| v.as_str());
+
+        if let Some(expected) = args.get("if_hash").and_then(|v| v.as_str()) {
+            let current = file_content_hash(file_path)?;
+            if !hash_matches(expected, &current) {
+                return Ok(ToolResult::error(format!(
+                    "Conflict: '{}' was modified since it was read (expected {}, found {}). Re-read the file and apply the change again",
+                    file_path, expected, current
+                )));
+            }
+        }
+
+        let format = DocumentFormat::from_path(file_path);
+        let mut document = read_document(file_path, format)?;
+
+        // Resolve the selection to pointers before modifying anything
+        let selected = match dialect.query_with_paths(&document, query) {
+            Ok(found) => found,
+            Err(message) => return Ok(ToolResult::error(message)),
+        };
+        let mut targets = Vec::new();
+        for (path, _) in selected {
+            let pointer = normalized_path_to_pointer(&path)
+                .ok_or_else(|| anyhow::anyhow!("Cannot convert matched path '{}' to a JSON Pointer", path))?;
+            let mut tokens = parse_pointer(&pointer)?;
+            if let Some(field) = field {
+                tokens.push(field.to_string());
+            }
+            targets.push(tokens);
+        }
+        targets.sort_by(|a, b| compare_tokens(a, b));
+        targets.dedup();
+        if targets.is_empty() {
+            return Ok(ToolResult::success(format!(
+                "No nodes matched '{}' in '{}'; the file was not changed", query, file_path
+            )));
+        }
+
+        let mut changed = Vec::new();
+        let mut skipped: Vec<(String, &'static str)> = Vec::new();
+        let pointer_of = |tokens: &[String]| tokens.iter()
+            .map(|t| format!("/{}", t.replace('~', "~0").replace('/', "~1")))
+            .collect::<String>();
+        if let Operation::Delete = operation {
+            // Remove later elements first so earlier indexes stay valid
+            for tokens in targets.iter().rev() {
+                match remove(&mut document, tokens) {
+                    Ok(()) => changed.push(pointer_of(tokens)),
+                    Err(reason) => skipped.push((pointer_of(tokens), reason)),
+                }
+            }
+            changed.reverse();
+        } else {
+            for tokens in &targets {
+                let result = match (field, tokens.split_last()) {
+                    // `set` may create the field on each selected object
+                    (Some(_), Some((last, parent))) if matches!(operation, Operation::Set(_)) => {
+                        match pointer_get_mut(&mut document, parent) {
+                            Ok(Value::Object(map)) => operation.apply(map.entry(last.clone()).or_insert(Value::Null)),
+                            _ => Err("not an object"),
+                        }
+                    }
+                    _ => match pointer_get_mut(&mut document, tokens) {
+                        Ok(target) => operation.apply(target),
+                        Err(_) => Err("no such field"),
+                    },
+                };
+                match result {
+                    Ok(()) => changed.push(pointer_of(tokens)),
+                    Err(reason) => skipped.push((pointer_of(tokens), reason)),
+                }
+            }
+        }
+
+        if changed.is_empty() {
+            return Ok(ToolResult::error(format!(
+                "'{}' matched {} node(s) in '{}' but none could be updated with '{}': {}",
+                query, targets.len(), file_path, operation.name(), describe_skipped(&skipped)
+            )));
+        }
+
+        // Write to a temporary file and rename it over the original so readers never see a partial file
+        let content = match serialize_for_path(file_path, &document, format, true) {
+            Ok(content) => content,
+            Err(e) => return Ok(ToolResult::error(format!("Cannot write '{}' as {}: {}", file_path, format.name(), e))),
+        };
+        storage::write(file_path, &content)?;
+
+        let mut listed = changed.iter().take(MAX_LISTED_CHANGES).cloned().collect::<Vec<_>>().join(", ");
+        if changed.len() > MAX_LISTED_CHANGES {
+            listed.push_str(&format!(", ... and {} more", changed.len() - MAX_LISTED_CHANGES));
+        }
+        let skipped_note = if skipped.is_empty() {
+            String::new()
+        } else {
+            format!("\nSkipped {}: {}", skipped.len(), describe_skipped(&skipped))
+        };
+        Ok(ToolResult::success(format!(
+            "Applied '{}' to {} of {} node(s) matching '{}' in '{}' (content_hash: {})\nChanged: {}{}",
+            operation.name(), changed.len(), targets.len(), query, file_path, file_content_hash(file_path)?, listed, skipped_note
+        )))
+    }
+}
+
+fn describe_skipped(skipped: &[(String, &'static str)]) -> String {
+    let mut listed = skipped.iter()
+        .take(MAX_LISTED_CHANGES)
+        .map(|(pointer, reason)| format!("{} ({})", pointer, reason))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if skipped.len() > MAX_LISTED_CHANGES {
+        listed.push_str(&format!(", ... and {} more", skipped.len() - MAX_LISTED_CHANGES));
+    }
+    listed
+}
+
+#[async_trait]
+impl ToolHandler for JsonUpdate {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_update_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-update" => self.handle_update(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use crate::json_tools::integrity::{file_content_hash, hash_matches, sha256_hex};
+use crate::json_tools::storage;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Upload sessions open at once; beginning another fails until one is committed or aborted.
+const MAX_UPLOAD_SESSIONS: usize = 16;
+/// Largest document a session assembles.
+const MAX_UPLOAD_BYTES: usize = 512 * 1024 * 1024;
+/// Sessions without a chunk for this long are discarded.
+const UPLOAD_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+struct Upload {
+    file_path: PathBuf,
+    pretty: bool,
+    create_dirs: bool,
+    text: String,
+    chunks: u64,
+    /// Byte offset in `text` where the last chunk starts, to recognize a retried chunk.
+    last_chunk_start: usize,
+    touched: Instant,
+}
+
+/// Chunked writes: json-write-begin opens a session, json-write-chunk appends pieces of the
+/// document's JSON text and json-write-commit validates the whole and writes the file.
+pub struct JsonUpload {
+    sessions: Mutex<HashMap<String, Upload>>,
+}
+
+impl Default for JsonUpload {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonUpload {
+    pub fn new() -> Self {
+        Self { sessions: Mutex::new(HashMap::new()) }
+    }
+
+    fn create_begin_tool() -> Tool {
+        Tool {
+            name: "json-write-begin".to_string(),
+            description: "Start a chunked write of a document too large for one json-write call. Returns a session_id for json-write-chunk and json-write-commit; nothing is written until commit".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "file_path": {
+                        "type": "string",
+                        "description": "Path of the JSON file to write on commit"
+                    },
+                    "pretty": {
+                        "type": "boolean",
+                        "description": "Re-format the assembled document with indentation on commit; otherwise the text is written as sent (default: false)"
+                    },
+                    "create_dirs": {
+                        "type": "boolean",
+                        "description": "Create parent directories on commit if they don't exist (default: true)"
+                    }
+                },
+                "required": ["file_path"]
+            }),
+        }
+    }
+
+    fn create_chunk_tool() -> Tool {
+        Tool {
+            name: "json-write-chunk".to_string(),
+            description: "Append the next piece of JSON text to a chunked write. Chunks may split the document anywhere, even inside a string".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "session_id": {
+                        "type": "string",
+                        "description": "session_id returned by json-write-begin"
+                    },
+                    "data": {
+                        "type": "string",
+                        "description": "The next piece of the document's JSON text"
+                    },
+                    "index": {
+                        "type": "integer",
+                        "description": "0-based chunk number. When given, out-of-order chunks are rejected and re-sending the previous chunk is acknowledged without appending it twice",
+                        "minimum": 0
+                    }
+                },
+                "required": ["session_id", "data"]
+            }),
+        }
+    }
+
+    fn create_commit_tool() -> Tool {
+        Tool {
+            name: "json-write-commit".to_string(),
+            description: "Finish a chunked write: parse the assembled text as JSON and, if valid, write it to the file atomically. With abort, discard the session instead".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "session_id": {
+                        "type": "string",
+                        "description": "session_id returned by json-write-begin"
+                    },
+                    "sha256": {
+                        "type": "string",
+                        "description": "Expected SHA-256 (hex) of the assembled text; commit fails if a chunk was lost or altered"
+                    },
+                    "abort": {
+                        "type": "boolean",
+                        "description": "Discard the session without writing (default: false)"
+                    }
+                },
+                "required": ["session_id"]
+            }),
+        }
+    }
+
+    fn handle_begin(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let file_path = args.get("file_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "file_path is required. Usage example:\n{{\n  \"file_path\": \"./large.json\"\n}}"
+            ))?;
+
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.retain(|_, upload| upload.touched.elapsed() < UPLOAD_IDLE_TIMEOUT);
+        if sessions.len() >= MAX_UPLOAD_SESSIONS {
+            return Ok(ToolResult::error(format!(
+                "Too many chunked writes in progress ({}). Commit or abort one with json-write-commit first",
+                MAX_UPLOAD_SESSIONS
+            )));
+        }
+        let session_id = uuid::Uuid::new_v4().to_string();
+        sessions.insert(session_id.clone(), Upload {
+            file_path: PathBuf::from(file_path),
+            pretty: args.get("pretty").and_then(|v| v.as_bool()).unwrap_or(false),
+            create_dirs: args.get("create_dirs").and_then(|v| v.as_bool()).unwrap_or(true),
+            text: String::new(),
+            chunks: 0,
+            last_chunk_start: 0,
+            touched: Instant::now(),
+        });
+        Ok(ToolResult::success(format!(
+            "Started chunked write to '{}'. session_id: {}\nSend the JSON text with json-write-chunk (index 0, 1, ...), then call json-write-commit. Idle sessions expire after {} minutes",
+            file_path, session_id, UPLOAD_IDLE_TIMEOUT.as_secs() / 60
+        )))
+    }
+
+    fn handle_chunk(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let session_id = required_session_id(args, "json-write-chunk")?;
+        let data = args.get("data")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!(
+                "data is required and must be a string of JSON text. Usage example:\n{{\n  \"session_id\": \"...\",\n  \"data\": \"{{\\\"records\\\": [\",\n  \"index\": 0\n}}"
+            ))?;
+
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(upload) = sessions.get_mut(session_id) else {
+            return Ok(unknown_session(session_id));
+        };
+        upload.touched = Instant::now();
+
+        if let Some(index) = args.get("index").and_then(|v| v.as_u64()) {
+            if index + 1 == upload.chunks && &upload.text[upload.last_chunk_start..] == data {
+                return Ok(ToolResult::success(format!(
+                    "Chunk {} was already received; {} chunk(s), {} bytes so far",
+                    index, upload.chunks, upload.text.len()
+                )));
+            }
+            if index != upload.chunks {
+                return Ok(ToolResult::error(format!(
+                    "Chunk {} is out of order: expected chunk {}. Send the missing chunks first",
+                    index, upload.chunks
+                )));
+            }
+        }
+        if upload.text.len() + data.len() > MAX_UPLOAD_BYTES {
+            return Ok(ToolResult::error(format!(
+                "Chunked write would exceed {} bytes. Split the document into several files (see json-split) instead",
+                MAX_UPLOAD_BYTES
+            )));
+        }
+
+        upload.last_chunk_start = upload.text.len();
+        upload.text.push_str(data);
+        upload.chunks += 1;
+        Ok(ToolResult::success(format!(
+            "Received chunk {}; {} bytes so far",
+            upload.chunks - 1, upload.text.len()
+        )))
+    }
+
+    fn handle_commit(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let session_id = required_session_id(args, "json-write-commit")?;
+
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        if args.get("abort").and_then(|v| v.as_bool()).unwrap_or(false) {
+            return Ok(match sessions.remove(session_id) {
+                Some(upload) => ToolResult::success(format!(
+                    "Aborted chunked write to '{}'; {} bytes discarded, nothing was written",
+                    upload.file_path.display(), upload.text.len()
+                )),
+                None => unknown_session(session_id),
+            });
+        }
+        let Some(upload) = sessions.get_mut(session_id) else {
+            return Ok(unknown_session(session_id));
+        };
+        upload.touched = Instant::now();
+
+        // Failed checks keep the session open so missing chunks can still be sent
+        if let Some(expected) = args.get("sha256").and_then(|v| v.as_str()) {
+            let actual = sha256_hex(upload.text.as_bytes());
+            if !hash_matches(expected, &actual) {
+                return Ok(ToolResult::error(format!(
+                    "SHA-256 mismatch: expected {}, assembled {} bytes in {} chunk(s) hash to {}. A chunk was lost or altered; abort and start again",
+                    expected, upload.text.len(), upload.chunks, actual
+                )));
+            }
+        }
+        let document: Value = match serde_json::from_str(&upload.text) {
+            Ok(document) => document,
+            Err(e) => {
+                let hint = if e.is_eof() {
+                    "The text ends early; send the remaining chunks, then commit again"
+                } else {
+                    "Abort this session and resend corrected chunks"
+                };
+                return Ok(ToolResult::error(format!(
+                    "Assembled text ({} bytes in {} chunk(s)) is not valid JSON: {}. {}",
+                    upload.text.len(), upload.chunks, e, hint
+                )));
+            }
+        };
+
+        let Some(upload) = sessions.remove(session_id) else {
+            return Ok(unknown_session(session_id));
+        };
+        drop(sessions);
+        let content = if upload.pretty {
+            serde_json::to_string_pretty(&document)?
+        } else {
+            upload.text
+        };
+        write_atomically(&upload.file_path, content.as_bytes(), upload.create_dirs)?;
+        let file_path = upload.file_path.to_string_lossy();
+        Ok(ToolResult::success(format!(
+            "Successfully wrote {} bytes from {} chunk(s) to '{}' (content_hash: {})",
+            content.len(), upload.chunks, file_path, file_content_hash(&file_path)?
+        )))
+    }
+}
+
+fn required_session_id<'a>(args: &'a HashMap<String, Value>, tool: &str) -> anyhow::Result<&'a str> {
+    args.get("session_id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!(
+            "session_id is required; start a session with json-write-begin. Usage example:\n{{\"name\": \"{}\", \"arguments\": {{\"session_id\": \"...\"}}}}",
+            tool
+        ))
+}
+
+fn unknown_session(session_id: &str) -> ToolResult {
+    ToolResult::error(format!(
+        "No chunked write session '{}'. It was committed, aborted or expired after {} idle minutes; start again with json-write-begin",
+        session_id, UPLOAD_IDLE_TIMEOUT.as_secs() / 60
+    ))
+}
+
+/// Write to a temporary sibling and rename it over the target so readers never see a
+/// partial file.
+fn write_atomically(path: &Path, content: &[u8], create_dirs: bool) -> anyhow::Result<()> {
+    if create_dirs {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| anyhow::anyhow!("Failed to create directories: {}", e))?;
+        }
+    }
+    storage::replace_file(path, content, "upload")
+}
+
+#[async_trait]
+impl ToolHandler for JsonUpload {
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        Ok(vec![Self::create_begin_tool(), Self::create_chunk_tool(), Self::create_commit_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-write-begin" => self.handle_begin(&tool_call.arguments),
+            "json-write-chunk" => self.handle_chunk(&tool_call.arguments),
+            "json-write-commit" => self.handle_commit(&tool_call.arguments),
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
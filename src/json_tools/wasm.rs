@@ -0,0 +1,229 @@
+use crate::json_tools::formats::{read_document, DocumentFormat};
+use crate::json_tools::storage;
+use crate::mcp::protocol::{Tool, ToolCall, ToolResult};
+use crate::mcp::server::ToolHandler;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use wasmi::core::TrapCode;
+use wasmi::{Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+pub const DEFAULT_FUEL: u64 = 100_000_000;
+pub const DEFAULT_MAX_MEMORY_MIB: usize = 64;
+
+/// What one transform call may use: `fuel` is roughly the number of WebAssembly
+/// instructions it may execute, `max_memory_bytes` the size its linear memory may grow to.
+#[derive(Debug, Clone, Copy)]
+pub struct WasmLimits {
+    pub fuel: u64,
+    pub max_memory_bytes: usize,
+}
+
+impl Default for WasmLimits {
+    fn default() -> Self {
+        Self { fuel: DEFAULT_FUEL, max_memory_bytes: DEFAULT_MAX_MEMORY_MIB * 1024 * 1024 }
+    }
+}
+
+/// WebAssembly modules found in the `--wasm-dir` directories, run by json-custom-transform.
+///
+/// A module is named after its file (`redact.wasm` is `redact`) and exports:
+/// - `memory`: its linear memory
+/// - `alloc(len: i32) -> i32`: room for `len` bytes of input, returning the offset
+/// - `transform(ptr: i32, len: i32) -> i64`: the JSON output for the UTF-8 JSON input at
+///   `ptr`, returned as `(out_ptr << 32) | out_len`
+///
+/// Modules get no imports, so they cannot reach files, the network or the clock. Every call
+/// runs in a fresh instance under [`WasmLimits`].
+///
+/// Modules run in wasmi rather than wasmtime: an interpreter in pure Rust with fuel metering
+/// built in, which keeps the `wasm` feature small to build.
+#[derive(Clone)]
+pub struct WasmTransforms {
+    engine: Engine,
+    modules: Arc<BTreeMap<String, Arc<Module>>>,
+    limits: WasmLimits,
+}
+
+impl Default for WasmTransforms {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WasmTransforms {
+    pub fn new() -> Self {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        Self { engine: Engine::new(&config), modules: Arc::default(), limits: WasmLimits::default() }
+    }
+
+    /// Compile every `.wasm` file in `dirs`. A module that does not validate, imports
+    /// anything, or shares its name with another is an error.
+    pub fn load(dirs: &[PathBuf], limits: WasmLimits) -> anyhow::Result<Self> {
+        let mut transforms = Self::new();
+        transforms.limits = limits;
+        let mut files = Vec::new();
+        for dir in dirs {
+            let entries = std::fs::read_dir(dir)
+                .map_err(|e| anyhow::anyhow!("Cannot read WebAssembly directory '{}': {}", dir.display(), e))?;
+            for entry in entries {
+                let path = entry?.path();
+                if path.extension().and_then(|e| e.to_str()) == Some("wasm") {
+                    files.push(path);
+                }
+            }
+        }
+        files.sort();
+
+        let mut modules = BTreeMap::new();
+        for path in files {
+            let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+            let module = Module::new(&transforms.engine, &std::fs::read(&path)?)
+                .map_err(|e| anyhow::anyhow!("Cannot compile WebAssembly module '{}': {}", path.display(), e))?;
+            if let Some(import) = module.imports().next() {
+                anyhow::bail!(
+                    "WebAssembly module '{}' imports '{}.{}', but transforms get no host functions",
+                    path.display(), import.module(), import.name()
+                );
+            }
+            if modules.insert(name.clone(), Arc::new(module)).is_some() {
+                anyhow::bail!("Two WebAssembly modules are named '{}'", name);
+            }
+        }
+        transforms.modules = Arc::new(modules);
+        Ok(transforms)
+    }
+
+    fn create_transform_tool(&self) -> Tool {
+        let names: Vec<&String> = self.modules.keys().collect();
+        Tool {
+            name: "json-custom-transform".to_string(),
+            description: "Transform JSON with a WebAssembly module loaded by the server (--wasm-dir), for logic no built-in tool offers. Modules run sandboxed, without file or network access and with instruction and memory limits.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "module": {
+                        "type": "string",
+                        "enum": names,
+                        "description": "Name of the module to run (its file name without .wasm)"
+                    },
+                    "file_path": {
+                        "type": "string",
+                        "description": "JSON (or TOML/MessagePack/CBOR) file to transform (use this or 'data')"
+                    },
+                    "data": {
+                        "description": "Inline input to transform, instead of file_path"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "File to write the result to as pretty JSON. The result is returned when omitted"
+                    }
+                },
+                "required": ["module"]
+            }),
+        }
+    }
+
+    async fn handle_transform(&self, args: &HashMap<String, Value>) -> anyhow::Result<ToolResult> {
+        let name = args.get("module").and_then(|v| v.as_str()).unwrap_or_default();
+        let Some(module) = self.modules.get(name).cloned() else {
+            let available = self.modules.keys().cloned().collect::<Vec<_>>();
+            return Ok(ToolResult::error(if available.is_empty() {
+                "No WebAssembly modules are loaded. Start the server with --wasm-dir <DIR> pointing at .wasm files".to_string()
+            } else {
+                format!("Unknown module '{}'. Available modules: {}", name, available.join(", "))
+            }));
+        };
+        let data = match (args.get("file_path").and_then(|v| v.as_str()), args.get("data")) {
+            (Some(path), None) => read_document(path, DocumentFormat::from_path(path))?,
+            (None, Some(data)) => data.clone(),
+            _ => return Ok(ToolResult::error("Provide exactly one of 'file_path' or 'data' as the module's input".to_string())),
+        };
+
+        let input = serde_json::to_vec(&data)?;
+        let engine = self.engine.clone();
+        let limits = self.limits;
+        let output = match tokio::task::spawn_blocking(move || run(&engine, &module, limits, &input)).await? {
+            Ok(output) => output,
+            Err(message) => return Ok(ToolResult::error(format!("Module '{}' failed: {}", name, message))),
+        };
+        let result: Value = match serde_json::from_slice(&output) {
+            Ok(result) => result,
+            Err(e) => return Ok(ToolResult::error(format!("Module '{}' returned invalid JSON: {}", name, e))),
+        };
+
+        let output = serde_json::to_string_pretty(&result)?;
+        match args.get("output_path").and_then(|v| v.as_str()) {
+            Some(output_path) => {
+                storage::create_parent_dirs(output_path)?;
+                storage::write(output_path, output.as_bytes())?;
+                Ok(ToolResult::success(format!("Wrote the result of module '{}' to '{}' ({} bytes)", name, output_path, output.len())))
+            }
+            None => Ok(ToolResult::success(format!("Result of module '{}':\n\n{}", name, output))),
+        }
+    }
+}
+
+/// Instantiate `module` under `limits`, pass it `input` and return the bytes it points at.
+fn run(engine: &Engine, module: &Module, limits: WasmLimits, input: &[u8]) -> Result<Vec<u8>, String> {
+    let store_limits = StoreLimitsBuilder::new()
+        .memory_size(limits.max_memory_bytes)
+        .memories(1)
+        .tables(1)
+        .instances(1)
+        .trap_on_grow_failure(true)
+        .build();
+    let mut store = Store::new(engine, store_limits);
+    store.limiter(|limits: &mut StoreLimits| limits);
+    store.set_fuel(limits.fuel).map_err(|e| e.to_string())?;
+
+    let explain = |e: wasmi::Error| match e.as_trap_code() {
+        Some(TrapCode::OutOfFuel) => format!("it ran out of fuel after {} units. Give it smaller input, or raise --wasm-fuel", limits.fuel),
+        Some(TrapCode::GrowthOperationLimited) => format!(
+            "it needed more than {} MiB of memory. Give it smaller input, or raise --wasm-max-memory", limits.max_memory_bytes / (1024 * 1024)
+        ),
+        _ => e.to_string(),
+    };
+    let instance = Linker::<StoreLimits>::new(engine)
+        .instantiate(&mut store, module)
+        .and_then(|pre| pre.start(&mut store))
+        .map_err(explain)?;
+    let memory = instance.get_memory(&store, "memory").ok_or("it does not export 'memory'")?;
+    let alloc = instance.get_typed_func::<i32, i32>(&store, "alloc")
+        .map_err(|e| format!("it does not export 'alloc(i32) -> i32' ({})", e))?;
+    let transform = instance.get_typed_func::<(i32, i32), i64>(&store, "transform")
+        .map_err(|e| format!("it does not export 'transform(i32, i32) -> i64' ({})", e))?;
+
+    let len = i32::try_from(input.len()).map_err(|_| "the input is larger than 2 GiB".to_string())?;
+    let ptr = alloc.call(&mut store, len).map_err(explain)?;
+    memory.write(&mut store, ptr as u32 as usize, input)
+        .map_err(|_| format!("alloc returned {}, which leaves no room for {} bytes of input", ptr, len))?;
+    let packed = transform.call(&mut store, (ptr, len)).map_err(explain)? as u64;
+
+    let (out_ptr, out_len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+    memory.data(&store)
+        .get(out_ptr..out_ptr + out_len)
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| format!("it returned {} bytes at offset {}, outside its memory", out_len, out_ptr))
+}
+
+#[async_trait]
+impl ToolHandler for WasmTransforms {
+    /// json-custom-transform is only offered once modules are loaded.
+    async fn get_tools(&self) -> anyhow::Result<Vec<Tool>> {
+        if self.modules.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(vec![self.create_transform_tool()])
+    }
+
+    async fn call_tool(&self, tool_call: ToolCall) -> anyhow::Result<ToolResult> {
+        match tool_call.name.as_str() {
+            "json-custom-transform" => self.handle_transform(&tool_call.arguments).await,
+            _ => Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name))),
+        }
+    }
+}
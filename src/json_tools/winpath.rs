@@ -0,0 +1,90 @@
+/// Where a Windows path is rooted, normalized so equivalent spellings compare equal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WindowsPrefix {
+    /// A drive, from `C:\`, `c:/`, `\\?\C:\` or `\\.\C:\`; the letter is upper-case.
+    Drive(char),
+    /// A network share, from `\\server\share`, `//server/share` or `\\?\UNC\server\share`;
+    /// both names are lower-case.
+    Unc(String, String),
+}
+
+/// A Windows path split into its prefix and components, with `.` and `..` resolved.
+/// Parsing is plain text, so these rules behave (and are tested) the same on every platform.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowsPath {
+    pub prefix: Option<WindowsPrefix>,
+    pub components: Vec<String>,
+}
+
+impl WindowsPath {
+    pub fn parse(path: &str) -> Self {
+        let text = path.replace('/', "\\");
+        let (prefix, rest) = match text.strip_prefix(r"\\?\").or_else(|| text.strip_prefix(r"\\.\")) {
+            Some(body) if has_unc_marker(body) => split_unc(&body[4..]),
+            Some(body) => split_drive(body),
+            None => match text.strip_prefix(r"\\") {
+                Some(body) => split_unc(body),
+                None => split_drive(&text),
+            },
+        };
+
+        let mut components: Vec<String> = Vec::new();
+        for component in rest.split('\\') {
+            match component {
+                "" | "." => {}
+                ".." => {
+                    components.pop();
+                }
+                name => components.push(name.to_string()),
+            }
+        }
+        Self { prefix, components }
+    }
+
+    /// Whether this path is `root` or lies below it. Windows file systems ignore case, so
+    /// components are compared case-insensitively.
+    pub fn starts_with(&self, root: &WindowsPath) -> bool {
+        self.prefix == root.prefix
+            && self.components.len() >= root.components.len()
+            && self.components.iter().zip(&root.components).all(|(a, b)| a.to_lowercase() == b.to_lowercase())
+    }
+}
+
+/// `path` without the `\\?\` long-path prefix Windows adds to canonical paths: `\\?\C:\x`
+/// becomes `C:\x` and `\\?\UNC\server\share\x` becomes `\\server\share\x`.
+pub fn strip_long_path_prefix(path: &str) -> String {
+    match path.strip_prefix(r"\\?\") {
+        Some(rest) if has_unc_marker(rest) => format!(r"\\{}", &rest[4..]),
+        Some(rest) => rest.to_string(),
+        None => path.to_string(),
+    }
+}
+
+/// Drop the slash some clients put before a drive letter when turning `file:///C:/x` URIs
+/// into paths (`/C:/x`), which Windows would read as a path on the current drive.
+pub fn strip_slash_before_drive(path: &str) -> &str {
+    match path.strip_prefix(['/', '\\']) {
+        Some(rest) if split_drive(rest).0.is_some() && rest[2..].starts_with(['/', '\\']) => rest,
+        _ => path,
+    }
+}
+
+fn has_unc_marker(text: &str) -> bool {
+    text.get(..4).is_some_and(|marker| marker.eq_ignore_ascii_case(r"UNC\"))
+}
+
+fn split_unc(text: &str) -> (Option<WindowsPrefix>, &str) {
+    let mut parts = text.splitn(3, '\\');
+    let server = parts.next().unwrap_or_default().to_lowercase();
+    let share = parts.next().unwrap_or_default().to_lowercase();
+    (Some(WindowsPrefix::Unc(server, share)), parts.next().unwrap_or_default())
+}
+
+fn split_drive(text: &str) -> (Option<WindowsPrefix>, &str) {
+    match text.as_bytes() {
+        [letter, b':', ..] if letter.is_ascii_alphabetic() => {
+            (Some(WindowsPrefix::Drive(letter.to_ascii_uppercase() as char)), &text[2..])
+        }
+        _ => (None, text),
+    }
+}
@@ -43,7 +43,7 @@ mod tests {
         
         if let Some(is_error) = tool_result.is_error {
             assert!(!is_error, "Expected success but got error: {}", 
-                   tool_result.content.get(0).map(|c| c.text.as_str()).unwrap_or("<no text>"));
+                   tool_result.content.first().map(|c| c.text.as_str()).unwrap_or("<no text>"));
         }
         
         let text = &tool_result.content[0].text;
@@ -71,7 +71,7 @@ mod tests {
         
         if let Some(is_error) = tool_result.is_error {
             assert!(!is_error, "Expected success but got error: {}", 
-                   tool_result.content.get(0).map(|c| c.text.as_str()).unwrap_or("<no text>"));
+                   tool_result.content.first().map(|c| c.text.as_str()).unwrap_or("<no text>"));
         }
         
         let text = &tool_result.content[0].text;
@@ -1,14 +1,20 @@
-use clap::Parser;
-use std::io::{self, BufRead, Write};
-use std::fs::OpenOptions;
+use clap::{Parser, Subcommand};
+use std::io::{self, Read, Write};
+use std::fs::{File, OpenOptions};
+use std::path::PathBuf;
+use std::time::Duration;
 
 mod json_tools;
 mod mcp;
 
-use json_tools::JsonToolsHandler;
+use json_tools::{
+    config::ServerConfig, encryption::Keyring, i18n::Language, journal::{Journal, Recovery}, outputs::TempOutputs, plugins::PluginRegistry, storage, telemetry::{Telemetry, TelemetrySink},
+    JsonPathDialect, JsonToolsHandler, PathPolicy, SymlinkPolicy,
+};
 use mcp::{
+    limits::{self, InboundLine},
     protocol::MCPResponse,
-    server::MCPServer,
+    server::{MCPServer, ToolHandler},
 };
 
 #[derive(Parser)]
@@ -20,24 +26,353 @@ use mcp::{
 struct Args {
     #[arg(short, long, default_value = "off")]
     log_level: String,
+
+    /// Trace every MCP message to json-mcp-server/debug.log in the per-user state directory
+    /// ($XDG_STATE_HOME or ~/.local/state; also enabled by JSON_MCP_DEBUG_LOG=1)
+    #[arg(long)]
+    debug_log: bool,
+
+    /// Directory that relative paths in tool arguments are resolved against
+    #[arg(long, value_name = "DIR")]
+    default_base_dir: Option<PathBuf>,
+
+    /// Restrict file access to this directory (repeatable); unrestricted when omitted
+    #[arg(long = "allowed-root", value_name = "DIR")]
+    allowed_roots: Vec<PathBuf>,
+
+    /// How path arguments through symlinks are treated: follow, refuse or within-roots
+    /// (follow only to targets inside the allowed roots)
+    #[arg(long, value_name = "POLICY", default_value = "within-roots")]
+    symlink_policy: String,
+
+    /// JSONPath implementation used by json-query and json-extract: jsonpath-rust or rfc9535
+    #[arg(long, value_name = "DIALECT", default_value = "jsonpath-rust")]
+    jsonpath_dialect: String,
+
+    /// Exercise every tool against temporary fixtures, print the results and exit
+    /// (nonzero if any tool fails)
+    #[arg(long)]
+    self_test: bool,
+
+    /// Read tool commands or raw JSON-RPC interactively instead of serving MCP on stdio
+    #[arg(long)]
+    repl: bool,
+
+    /// Append every request/response pair, with timestamps, to this JSON Lines file
+    #[arg(long, value_name = "FILE")]
+    record: Option<PathBuf>,
+
+    /// Send the requests recorded in this file to the server, report responses that differ
+    /// from the recording and exit (nonzero on any difference)
+    #[arg(long, value_name = "FILE", conflicts_with = "record")]
+    replay: Option<PathBuf>,
+
+    /// JSON or TOML file with further settings, such as disabled tools and tool aliases
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Language of json-help and common error messages: en, es, ja or zh (a call's
+    /// `language` argument overrides it)
+    #[arg(long, value_name = "LANG", default_value = "en")]
+    language: String,
+
+    /// Also offer every tool under its underscored name (json_read for json-read), for
+    /// clients that reject hyphens in tool names
+    #[arg(long)]
+    underscore_aliases: bool,
+
+    /// Journal json-write and json-format in this directory; writes interrupted by a crash
+    /// are completed or rolled back at the next start
+    #[arg(long, value_name = "DIR")]
+    journal_dir: Option<PathBuf>,
+
+    /// Offer the tools of the plugin executables in this directory (repeatable); each is
+    /// asked for its tools with `--describe` at startup
+    #[arg(long = "plugin-dir", value_name = "DIR")]
+    plugin_dirs: Vec<PathBuf>,
+
+    /// Offer the .wasm modules in this directory (repeatable) to json-custom-transform,
+    /// each under its file name
+    #[cfg(feature = "wasm")]
+    #[arg(long = "wasm-dir", value_name = "DIR")]
+    wasm_dirs: Vec<PathBuf>,
+
+    /// Instructions one json-custom-transform call may execute before it is stopped
+    #[cfg(feature = "wasm")]
+    #[arg(long, value_name = "UNITS", default_value_t = json_tools::wasm::DEFAULT_FUEL)]
+    wasm_fuel: u64,
+
+    /// Memory one json-custom-transform call may use, in MiB
+    #[cfg(feature = "wasm")]
+    #[arg(long, value_name = "MIB", default_value_t = json_tools::wasm::DEFAULT_MAX_MEMORY_MIB)]
+    wasm_max_memory: usize,
+
+    /// Opt in to anonymous usage telemetry: aggregate tool call counts, error categories and
+    /// latency buckets, appended to this file or POSTed to this https:// URL (`http`
+    /// feature). Off unless given; DO_NOT_TRACK=1 turns it off again
+    #[arg(long, value_name = "FILE|URL")]
+    telemetry: Option<String>,
+
+    /// Minutes between telemetry reports; a last report is sent when the client disconnects
+    #[arg(long, value_name = "MINUTES", default_value_t = 60)]
+    telemetry_interval: u64,
+
+    /// Directory for results of calls with `temp_output`, shared by every client of this
+    /// server (default: json-mcp-server-output in the system temp directory)
+    #[arg(long, value_name = "DIR")]
+    temp_dir: Option<PathBuf>,
+
+    /// Minutes a temp output is kept; older files in --temp-dir are deleted
+    #[arg(long, value_name = "MINUTES", default_value_t = 60)]
+    temp_ttl: u64,
+
+    /// Longest JSON-RPC message accepted from the client, in bytes; longer lines are dropped
+    /// without being buffered and answered with an error
+    #[arg(long, value_name = "BYTES", default_value_t = mcp::limits::DEFAULT_MAX_REQUEST_BYTES)]
+    max_request_bytes: usize,
+
+    /// Largest response sent to the client, in bytes; longer tool results are truncated with
+    /// a note on how to page through them
+    #[arg(long, value_name = "BYTES", default_value_t = mcp::limits::DEFAULT_MAX_RESPONSE_BYTES)]
+    max_response_bytes: usize,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run one tool and print its result, e.g. `run json-query --file data.json --query '$.a'`
+    Run {
+        /// Tool name, e.g. json-query
+        tool: String,
+        /// Tool arguments as `--name value` (`--file` for file_path, `--json '{...}'` for several)
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Run one tool over a JSON document read from stdin and print the result to stdout, e.g.
+    /// `cat data.json | pipe json-query --query '$.a'`
+    Pipe {
+        /// Tool name, e.g. json-query, json-script or json-format
+        tool: String,
+        /// Tool arguments as for `run`; file_path defaults to the document from stdin
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+}
+
+/// The tool handler configured by the command-line options and the `--config` file. An
+/// interrupted write left in the journal is completed or rolled back first, and reported on
+/// stderr, since stdout carries JSON-RPC.
+async fn build_handler(args: &Args) -> anyhow::Result<JsonToolsHandler> {
+    let dialect = JsonPathDialect::parse(&args.jsonpath_dialect).map_err(anyhow::Error::msg)?;
+    let mut config = match &args.config {
+        Some(path) => ServerConfig::load(path)?,
+        None => ServerConfig::default(),
+    };
+    config.tools.underscore_aliases |= args.underscore_aliases;
+    if let Some(keyring) = Keyring::from_config(&config.encryption)? {
+        keyring.install();
+    }
+    storage::install(config.storage);
+    config.environment.install();
+    let temp_dir = args.temp_dir.clone().unwrap_or_else(|| TempOutputs::default().dir().to_path_buf());
+    let temp_outputs = TempOutputs::new(temp_dir, Duration::from_secs(args.temp_ttl * 60))?;
+    let mut allowed_roots = args.allowed_roots.clone();
+    if !allowed_roots.is_empty() {
+        // Agents read temp outputs back with the other tools
+        allowed_roots.push(temp_outputs.dir().to_path_buf());
+    }
+    let path_policy = PathPolicy::new(args.default_base_dir.clone(), allowed_roots)?
+        .with_symlink_policy(SymlinkPolicy::parse(&args.symlink_policy).map_err(anyhow::Error::msg)?);
+    let mut handler = JsonToolsHandler::new()
+        .with_path_policy(path_policy)
+        .with_jsonpath_dialect(dialect)
+        .with_tool_config(config.tools)
+        .with_temp_outputs(temp_outputs)
+        .with_language(Language::parse(&args.language).map_err(anyhow::Error::msg)?);
+
+    if let Some(dir) = &args.journal_dir {
+        let journal = Journal::open(dir)?;
+        for action in journal.recover()? {
+            match action {
+                Recovery::Completed(path) => eprintln!("journal: completed interrupted write to '{}'", path.display()),
+                Recovery::RolledBack(path) => eprintln!("journal: rolled back interrupted write to '{}'", path.display()),
+            }
+        }
+        handler = handler.with_journal(journal);
+    }
+    if !args.plugin_dirs.is_empty() {
+        let built_in: Vec<String> = JsonToolsHandler::new().get_tools().await?.into_iter().map(|tool| tool.name).collect();
+        handler = handler.with_plugins(PluginRegistry::discover(&args.plugin_dirs, &built_in).await?);
+    }
+    #[cfg(feature = "wasm")]
+    if !args.wasm_dirs.is_empty() {
+        let limits = json_tools::wasm::WasmLimits { fuel: args.wasm_fuel, max_memory_bytes: args.wasm_max_memory * 1024 * 1024 };
+        handler = handler.with_wasm_transforms(json_tools::wasm::WasmTransforms::load(&args.wasm_dirs, limits)?);
+    }
+    Ok(handler)
+}
+
+/// Start opt-in telemetry for the server loop: a report every `--telemetry-interval`
+/// minutes, sent off the runtime's worker threads. `None` unless `--telemetry` is given.
+async fn start_telemetry(args: &Args, handler: &JsonToolsHandler) -> anyhow::Result<Option<Telemetry>> {
+    let Some(destination) = &args.telemetry else {
+        return Ok(None);
+    };
+    if Telemetry::do_not_track() {
+        eprintln!("telemetry: disabled by DO_NOT_TRACK");
+        return Ok(None);
+    }
+    let sink = TelemetrySink::parse(destination).map_err(anyhow::Error::msg)?;
+    // Built-in names only: aliases come from the user's configuration
+    let known_tools = JsonToolsHandler::new().get_tools().await?.into_iter().map(|tool| tool.name).collect();
+    let telemetry = Telemetry::new(sink, handler.shared_stats(), known_tools);
+
+    let periodic = telemetry.clone();
+    let interval = std::time::Duration::from_secs(args.telemetry_interval.max(1) * 60);
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(interval);
+        ticks.tick().await;
+        loop {
+            ticks.tick().await;
+            let telemetry = periodic.clone();
+            if let Ok(Err(e)) = tokio::task::spawn_blocking(move || telemetry.send()).await {
+                eprintln!("telemetry: {}", e);
+            }
+        }
+    });
+    Ok(Some(telemetry))
+}
+
+/// Delete expired temp outputs at startup and every minute while the server runs.
+fn start_temp_janitor(handler: &JsonToolsHandler) {
+    let temp_outputs = handler.temp_outputs().clone();
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            ticks.tick().await;
+            let temp_outputs = temp_outputs.clone();
+            if let Ok(Err(e)) = tokio::task::spawn_blocking(move || temp_outputs.sweep()).await {
+                eprintln!("temp outputs: {}", e);
+            }
+        }
+    });
+}
+
+/// Open the MCP message trace in the per-user state directory: `$XDG_STATE_HOME`, else
+/// `~/.local/state`, else `%LOCALAPPDATA%`. Only the user can read the directory and the
+/// file, and a symlink in place of the file is refused rather than followed.
+fn open_debug_log() -> anyhow::Result<(PathBuf, File)> {
+    let state = std::env::var_os("XDG_STATE_HOME").filter(|dir| !dir.is_empty()).map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("state")))
+        .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))
+        .ok_or_else(|| anyhow::anyhow!("none of XDG_STATE_HOME, HOME or LOCALAPPDATA is set"))?;
+    let dir = state.join("json-mcp-server");
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(&dir).map_err(|e| anyhow::anyhow!("cannot create '{}': {}", dir.display(), e))?;
+
+    let path = dir.join("debug.log");
+    if path.symlink_metadata().is_ok_and(|metadata| metadata.file_type().is_symlink()) {
+        anyhow::bail!("'{}' is a symlink", path.display());
+    }
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    #[cfg(target_os = "linux")]
+    std::os::unix::fs::OpenOptionsExt::custom_flags(&mut options, libc::O_NOFOLLOW);
+    let file = options.open(&path).map_err(|e| anyhow::anyhow!("cannot open '{}': {}", path.display(), e))?;
+    // A log left by an older version may have been created with the umask's permissions
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    Ok((path, file))
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let _args = Args::parse();
+    let args = Args::parse();
 
     // Tracing disabled for MCP compliance - stdout must be clean JSON-RPC only
     // Use --log-level debug if debugging is needed (will output to stderr)
 
-    // Create debug log file for MCP message tracing
-    let mut debug_log = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open("mcp_debug.log")
-        .ok();
+    if args.self_test {
+        let dialect = JsonPathDialect::parse(&args.jsonpath_dialect).map_err(anyhow::Error::msg)?;
+        let handler = JsonToolsHandler::new().with_jsonpath_dialect(dialect);
+        let outcomes = json_tools::selftest::run_self_test(&handler).await?;
+        let failed = outcomes.iter().filter(|o| !o.passed).count();
+        for outcome in &outcomes {
+            println!(
+                "{} {:<22} {:>8.1} ms  {}",
+                if outcome.passed { "ok  " } else { "FAIL" },
+                outcome.tool,
+                outcome.elapsed.as_secs_f64() * 1000.0,
+                outcome.detail
+            );
+        }
+        println!("Self-test: {} passed, {} failed", outcomes.len() - failed, failed);
+        std::process::exit(if failed == 0 { 0 } else { 1 });
+    }
+
+    if let Some(Command::Run { tool, args: tool_args }) = &args.command {
+        let handler = build_handler(&args).await?;
+        let result = json_tools::cli::run_tool(&handler, tool, tool_args).await?;
+        let text: Vec<&str> = result.content.iter().map(|c| c.text.as_str()).collect();
+        if result.is_error == Some(true) {
+            eprintln!("{}", text.join("\n"));
+            std::process::exit(1);
+        }
+        println!("{}", text.join("\n"));
+        return Ok(());
+    }
+
+    if let Some(Command::Pipe { tool, args: tool_args }) = &args.command {
+        let handler = build_handler(&args).await?;
+        let mut input = Vec::new();
+        io::stdin().read_to_end(&mut input)?;
+        match json_tools::cli::pipe_tool(&handler, tool, tool_args, &input).await {
+            Ok(piped) => {
+                if let Some(note) = piped.note {
+                    eprintln!("{}", note);
+                }
+                print!("{}", piped.output);
+                if !piped.output.ends_with('\n') {
+                    println!();
+                }
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    // Trace MCP messages only when asked, outside the working directory
+    let debug_log_enabled = args.debug_log || std::env::var("JSON_MCP_DEBUG_LOG").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"));
+    let mut debug_log = if debug_log_enabled {
+        match open_debug_log() {
+            Ok((path, file)) => {
+                eprintln!("Tracing MCP messages to {}", path.display());
+                Some(file)
+            }
+            Err(e) => {
+                eprintln!("Not tracing MCP messages: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
 
     // Create the JSON tools handler
-    let json_handler = JsonToolsHandler::new();
+    let json_handler = build_handler(&args).await?;
+
+    let telemetry = start_telemetry(&args, &json_handler).await?;
+    start_temp_janitor(&json_handler);
 
     // Create the MCP server
     let mut server = MCPServer::new(json_handler);
@@ -45,13 +380,47 @@ async fn main() -> anyhow::Result<()> {
     // Register JSON tools
     server.register_tools().await?;
 
+    if args.repl {
+        let options = mcp::repl::ReplOptions::for_terminal();
+        mcp::repl::run_repl(&server, io::stdin().lock(), io::stdout(), options).await?;
+        return Ok(());
+    }
+
+    if let Some(path) = &args.replay {
+        let report = mcp::session::replay_session(&server, path).await?;
+        for mismatch in &report.mismatches {
+            println!(
+                "line {} ({}): response differs at '{}'\n  recorded: {}\n  replayed: {}",
+                mismatch.line, mismatch.method, mismatch.pointer, mismatch.expected, mismatch.actual
+            );
+        }
+        println!("Replayed {} request(s): {} differed", report.replayed, report.mismatches.len());
+        std::process::exit(if report.mismatches.is_empty() { 0 } else { 1 });
+    }
+
+    let mut recorder = match &args.record {
+        Some(path) => Some(mcp::session::SessionRecorder::create(path)?),
+        None => None,
+    };
+
     // Start the server loop
-    let stdin = io::stdin();
+    let mut stdin = io::stdin().lock();
     let mut stdout = io::stdout();
 
-    for line in stdin.lock().lines() {
-        match line {
-            Ok(input) => {
+    loop {
+        match limits::read_message(&mut stdin, args.max_request_bytes) {
+            Ok(Some(InboundLine::Oversized { bytes, id })) => {
+                let response_str = serde_json::to_string(&limits::oversized_request(bytes, id, args.max_request_bytes))?;
+                if let Some(ref mut log) = debug_log {
+                    let timestamp = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S%.3f");
+                    let _ = writeln!(log, "[{}] ERROR_OUTGOING: {}", timestamp, response_str);
+                    let _ = log.flush();
+                }
+                if writeln!(stdout, "{}", response_str).and_then(|_| stdout.flush()).is_err() {
+                    break;
+                }
+            }
+            Ok(Some(InboundLine::Message(input))) => {
                 if input.trim().is_empty() {
                     continue;
                 }
@@ -63,8 +432,14 @@ async fn main() -> anyhow::Result<()> {
                     let _ = log.flush();
                 }
 
+                let started = std::time::Instant::now();
                 match server.handle_request(&input).await {
                     Ok(response) => {
+                        let response = limits::limit_response(response, args.max_response_bytes);
+                        if let Some(ref mut recorder) = recorder {
+                            let _ = recorder.record(&input, &response, started.elapsed());
+                        }
+
                         // Log outgoing response
                         if let Some(ref mut log) = debug_log {
                             let timestamp = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S%.3f");
@@ -72,6 +447,9 @@ async fn main() -> anyhow::Result<()> {
                             let _ = log.flush();
                         }
 
+                        if response.is_empty() {
+                            continue;
+                        }
                         if let Err(_e) = writeln!(stdout, "{}", response) {
                             break;
                         }
@@ -86,7 +464,10 @@ async fn main() -> anyhow::Result<()> {
                             &format!("Internal error: {}", e),
                         );
                         let response_str = serde_json::to_string(&error_response)?;
-                        
+                        if let Some(ref mut recorder) = recorder {
+                            let _ = recorder.record(&input, &response_str, started.elapsed());
+                        }
+
                         // Log outgoing error response
                         if let Some(ref mut log) = debug_log {
                             let timestamp = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S%.3f");
@@ -103,11 +484,17 @@ async fn main() -> anyhow::Result<()> {
                     }
                 }
             }
-            Err(_e) => {
+            Ok(None) | Err(_) => {
                 break;
             }
         }
     }
 
+    if let Some(telemetry) = telemetry {
+        if let Err(e) = telemetry.send() {
+            eprintln!("telemetry: {}", e);
+        }
+    }
+
     Ok(())
 }
@@ -0,0 +1,149 @@
+use crate::mcp::protocol::MCPResponse;
+use serde_json::{json, Value};
+use std::io::{self, BufRead};
+
+/// Default `--max-request-bytes` and `--max-response-bytes`.
+pub const DEFAULT_MAX_REQUEST_BYTES: usize = 16 * 1024 * 1024;
+pub const DEFAULT_MAX_RESPONSE_BYTES: usize = 16 * 1024 * 1024;
+/// How much of an oversized request is kept to find its `id`.
+const ID_SCAN_BYTES: usize = 4096;
+
+/// One line read from the client.
+#[derive(Debug, PartialEq)]
+pub enum InboundLine {
+    Message(String),
+    /// A line longer than the limit. Only its start was kept, to recover the request `id`.
+    Oversized { bytes: usize, id: Option<Value> },
+}
+
+/// Read the next newline-terminated message from `reader`, holding at most `max_bytes` of
+/// it in memory: the rest of a longer line is read and dropped. `None` at end of input.
+pub fn read_message<R: BufRead>(reader: &mut R, max_bytes: usize) -> io::Result<Option<InboundLine>> {
+    let mut line = Vec::new();
+    let mut bytes = 0;
+    let mut ended = false;
+    let mut last = None;
+    loop {
+        let available = reader.fill_buf()?;
+        if available.is_empty() {
+            break;
+        }
+        let (chunk, consumed) = match available.iter().position(|&b| b == b'\n') {
+            Some(end) => {
+                ended = true;
+                (&available[..end], end + 1)
+            }
+            None => (available, available.len()),
+        };
+        bytes += chunk.len();
+        last = chunk.last().copied().or(last);
+        // One byte over may be the '\r' of a CRLF line ending
+        if bytes <= max_bytes.saturating_add(1) {
+            line.extend_from_slice(chunk);
+        } else {
+            line.extend_from_slice(&chunk[..chunk.len().min(ID_SCAN_BYTES.saturating_sub(line.len()))]);
+            line.truncate(ID_SCAN_BYTES);
+        }
+        reader.consume(consumed);
+        if ended {
+            break;
+        }
+    }
+    if !ended && bytes == 0 {
+        return Ok(None);
+    }
+    if last == Some(b'\r') {
+        bytes -= 1;
+        if bytes < line.len() {
+            line.pop();
+        }
+    }
+    if bytes > max_bytes {
+        return Ok(Some(InboundLine::Oversized { bytes, id: leading_id(&line) }));
+    }
+    Ok(Some(InboundLine::Message(String::from_utf8_lossy(&line).into_owned())))
+}
+
+/// The `id` of a request from the start of its text, when it comes before the cut.
+fn leading_id(start: &[u8]) -> Option<Value> {
+    let text = String::from_utf8_lossy(start);
+    let after_key = &text[text.find("\"id\"")? + 4..];
+    let value = after_key.trim_start().strip_prefix(':')?;
+    match serde_json::Deserializer::from_str(value).into_iter::<Value>().next()? {
+        Ok(id @ (Value::Number(_) | Value::String(_))) => Some(id),
+        _ => None,
+    }
+}
+
+/// The error sent instead of handling a request longer than `max_bytes`.
+pub fn oversized_request(bytes: usize, id: Option<Value>, max_bytes: usize) -> MCPResponse {
+    let mut response = MCPResponse::error(id, -32600, &format!(
+        "Request of {} bytes exceeds the server's limit of {} bytes. Pass large documents by file_path instead of inline",
+        bytes, max_bytes
+    ));
+    if let Some(error) = &mut response.error {
+        error.data = Some(json!({"received_bytes": bytes, "max_request_bytes": max_bytes}));
+    }
+    response
+}
+
+/// `response` cut down to `max_bytes`: the longest text of a tool result or resource is
+/// truncated and ends with a note on how to get the rest. Responses that cannot be cut
+/// that way are replaced by an error.
+pub fn limit_response(response: String, max_bytes: usize) -> String {
+    if response.len() <= max_bytes {
+        return response;
+    }
+    let total = response.len();
+    let too_large = |id| {
+        let error = MCPResponse::error(id, -32603, &format!(
+            "Response of {} bytes exceeds the server's limit of {} bytes. Request less at a time (limit/offset, a narrower query) or pass temp_output: true to get a file path instead",
+            total, max_bytes
+        ));
+        serde_json::to_string(&error).unwrap_or_default()
+    };
+    let Ok(mut message) = serde_json::from_str::<MCPResponse>(&response) else {
+        return too_large(None);
+    };
+    let Some(pointer) = message.result.as_ref().and_then(longest_text) else {
+        return too_large(message.id);
+    };
+
+    let note = format!(
+        "\n\n[Truncated: the full response is {} bytes, over the server's limit of {}. Page with limit/offset, narrow the query or array_path, or pass temp_output: true to get a file path instead]",
+        total, max_bytes
+    );
+    let mut text = match message.result.as_mut().and_then(|result| result.pointer_mut(&pointer)) {
+        Some(Value::String(text)) => std::mem::take(text),
+        _ => return too_large(message.id),
+    };
+    loop {
+        if let Some(slot) = message.result.as_mut().and_then(|result| result.pointer_mut(&pointer)) {
+            *slot = Value::String(format!("{}{}", text, note));
+        }
+        let serialized = serde_json::to_string(&message).unwrap_or_default();
+        if serialized.len() <= max_bytes {
+            return serialized;
+        }
+        if text.is_empty() {
+            return too_large(message.id);
+        }
+        // Escaping makes the serialized text at least as long as the text itself
+        let mut keep = text.len().saturating_sub(serialized.len() - max_bytes);
+        while !text.is_char_boundary(keep) {
+            keep -= 1;
+        }
+        text.truncate(keep);
+    }
+}
+
+/// JSON Pointer (within `result`) to the longest `content[].text` or `contents[].text`.
+fn longest_text(result: &Value) -> Option<String> {
+    ["content", "contents"].iter()
+        .filter_map(|field| Some((field, result.get(field)?.as_array()?)))
+        .flat_map(|(field, items)| items.iter().enumerate().filter_map(move |(i, item)| {
+            Some((item.get("text")?.as_str()?.len(), format!("/{}/{}/text", field, i)))
+        }))
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, pointer)| pointer)
+}
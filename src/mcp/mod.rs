@@ -1,2 +1,5 @@
+pub mod limits;
 pub mod protocol;
+pub mod repl;
 pub mod server;
+pub mod session;
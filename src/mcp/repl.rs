@@ -0,0 +1,246 @@
+use crate::json_tools::cli::parse_tool_arguments;
+use crate::mcp::server::MCPServer;
+use serde_json::{json, Value};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+/// History entries kept in memory and in the history file.
+const MAX_HISTORY: usize = 1000;
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const CYAN: &str = "\x1b[36m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+const HELP: &str = "Commands:
+  <tool> --name value ...   call a tool, e.g. json-query --file data.json --query '$.a'
+  {\"jsonrpc\": ...}          send a raw JSON-RPC request
+  tools                     list tool names
+  describe <tool>           show a tool's description and input schema
+  history                   show previous commands; !N re-runs number N, !! the last one
+  help                      show this text
+  quit                      leave the REPL";
+
+pub struct ReplOptions {
+    /// Colorize output with ANSI escapes.
+    pub color: bool,
+    /// File commands are loaded from and appended to.
+    pub history_path: Option<PathBuf>,
+}
+
+impl ReplOptions {
+    /// Colors when stdout is a terminal, history in `~/.json_mcp_server_history`.
+    pub fn for_terminal() -> Self {
+        use std::io::IsTerminal;
+        let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"));
+        Self {
+            color: std::io::stdout().is_terminal(),
+            history_path: home.map(|home| PathBuf::from(home).join(".json_mcp_server_history")),
+        }
+    }
+}
+
+struct Repl<'a, W> {
+    server: &'a MCPServer,
+    out: W,
+    options: ReplOptions,
+    history: Vec<String>,
+    next_id: u64,
+}
+
+impl<W: Write> Repl<'_, W> {
+    fn paint(&self, color: &str, text: &str) -> String {
+        if self.options.color {
+            format!("{}{}{}", color, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn remember(&mut self, line: &str) {
+        if self.history.last().map(String::as_str) == Some(line) {
+            return;
+        }
+        self.history.push(line.to_string());
+        if self.history.len() > MAX_HISTORY {
+            self.history.remove(0);
+        }
+        if let Some(path) = &self.options.history_path {
+            // Append while the file is small, and rewrite it with the kept entries once it
+            // holds twice as many, so it stays bounded without a rewrite per command
+            let lines = fs::read_to_string(path).map(|text| text.lines().count()).unwrap_or(0);
+            if lines + 1 >= 2 * MAX_HISTORY {
+                let mut text = self.history.join("\n");
+                text.push('\n');
+                let _ = fs::write(path, text);
+            } else if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+                let _ = writeln!(file, "{}", line);
+            }
+        }
+    }
+
+    /// Expand `!!` and `!N` from the history.
+    fn recall(&self, line: &str) -> Result<String, String> {
+        let Some(reference) = line.strip_prefix('!') else {
+            return Ok(line.to_string());
+        };
+        let entry = if reference == "!" {
+            self.history.last()
+        } else {
+            let number: usize = reference.parse().map_err(|_| format!("Not a history reference: {}", line))?;
+            number.checked_sub(1).and_then(|i| self.history.get(i))
+        };
+        entry.cloned().ok_or_else(|| format!("No history entry {}", line))
+    }
+
+    async fn execute(&mut self, line: &str) -> std::io::Result<bool> {
+        let mut words = line.split_whitespace();
+        match words.next().unwrap_or_default() {
+            "quit" | "exit" => return Ok(false),
+            "help" => writeln!(self.out, "{}", HELP)?,
+            "history" => {
+                for (i, entry) in self.history.iter().enumerate() {
+                    writeln!(self.out, "{} {}", self.paint(DIM, &format!("{:>4}", i + 1)), entry)?;
+                }
+            }
+            "tools" => {
+                for tool in self.server.tools() {
+                    writeln!(self.out, "{}", self.paint(CYAN, &tool.name))?;
+                }
+            }
+            "describe" => match words.next().and_then(|name| self.server.tool(name)) {
+                Some(tool) => {
+                    writeln!(self.out, "{}\n{}", tool.description, serde_json::to_string_pretty(&tool.input_schema).unwrap_or_default())?;
+                }
+                None => writeln!(self.out, "{}", self.paint(RED, "Usage: describe <tool> (see `tools`)"))?,
+            },
+            _ if line.starts_with('{') => {
+                let response = self.server.handle_request(line).await;
+                self.print_response(response)?;
+            }
+            name => {
+                let Some(tool) = self.server.tool(name) else {
+                    writeln!(self.out, "{}", self.paint(RED, &format!("Unknown command or tool '{}'. Type `help` or `tools`", name)))?;
+                    return Ok(true);
+                };
+                let args = match split_args(&line[name.len()..]) {
+                    Ok(args) => args,
+                    Err(e) => {
+                        writeln!(self.out, "{}", self.paint(RED, &e))?;
+                        return Ok(true);
+                    }
+                };
+                match parse_tool_arguments(&tool.input_schema, &args) {
+                    Ok(arguments) => {
+                        self.next_id += 1;
+                        let request = json!({
+                            "jsonrpc": "2.0",
+                            "id": self.next_id,
+                            "method": "tools/call",
+                            "params": {"name": name, "arguments": arguments},
+                        });
+                        writeln!(self.out, "{}", self.paint(DIM, &request.to_string()))?;
+                        let response = self.server.handle_request(&request.to_string()).await;
+                        self.print_response(response)?;
+                    }
+                    Err(e) => writeln!(self.out, "{}", self.paint(RED, &e))?,
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Tool results are shown as their text, green or red; anything else as pretty JSON.
+    fn print_response(&mut self, response: anyhow::Result<String>) -> std::io::Result<()> {
+        if response.as_ref().is_ok_and(|text| text.is_empty()) {
+            return writeln!(self.out, "{}", self.paint(DIM, "(notification, no response)"));
+        }
+        let response: Value = match response.map(|text| serde_json::from_str(&text)) {
+            Ok(Ok(response)) => response,
+            Ok(Err(e)) => return writeln!(self.out, "{}", self.paint(RED, &format!("Unparseable response: {}", e))),
+            Err(e) => return writeln!(self.out, "{}", self.paint(RED, &format!("Request failed: {}", e))),
+        };
+        if let Some(error) = response.get("error").filter(|e| !e.is_null()) {
+            return writeln!(self.out, "{}", self.paint(RED, &serde_json::to_string_pretty(error).unwrap_or_default()));
+        }
+        let result = &response["result"];
+        match result.get("content").and_then(|c| c.as_array()) {
+            Some(content) => {
+                let failed = result["isError"] == json!(true);
+                for item in content {
+                    let text = item["text"].as_str().unwrap_or_default();
+                    writeln!(self.out, "{}", self.paint(if failed { RED } else { GREEN }, text))?;
+                }
+                Ok(())
+            }
+            None => writeln!(self.out, "{}", serde_json::to_string_pretty(result).unwrap_or_default()),
+        }
+    }
+}
+
+/// Split a command line into words, honoring single and double quotes and backslash
+/// escapes outside single quotes, as a shell would.
+fn split_args(line: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current: Option<String> = None;
+    let mut quote: Option<char> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('\''), c) => current.get_or_insert_with(String::new).push(c),
+            (_, '\\') => {
+                let escaped = chars.next().ok_or("Trailing backslash")?;
+                current.get_or_insert_with(String::new).push(escaped);
+            }
+            (Some(_), c) => current.get_or_insert_with(String::new).push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                current.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => args.extend(current.take()),
+            (None, c) => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err("Unterminated quote".to_string());
+    }
+    args.extend(current);
+    Ok(args)
+}
+
+/// Read commands from `input` until it ends or `quit`, writing responses to `out`.
+pub async fn run_repl<R: BufRead, W: Write>(server: &MCPServer, input: R, out: W, options: ReplOptions) -> std::io::Result<()> {
+    let history = options.history_path.as_ref()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|text| text.lines().map(str::to_string).collect::<Vec<_>>())
+        .unwrap_or_default();
+    let skip = history.len().saturating_sub(MAX_HISTORY);
+    let mut repl = Repl { server, out, options, history: history[skip..].to_vec(), next_id: 0 };
+
+    writeln!(repl.out, "json-mcp-server REPL. Type `help` for commands, `quit` to leave.")?;
+    let mut lines = input.lines();
+    loop {
+        write!(repl.out, "{}", repl.paint(CYAN, "json> "))?;
+        repl.out.flush()?;
+        let Some(line) = lines.next().transpose()? else { break };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let line = match repl.recall(line) {
+            Ok(line) => line,
+            Err(e) => {
+                writeln!(repl.out, "{}", repl.paint(RED, &e))?;
+                continue;
+            }
+        };
+        repl.remember(&line);
+        if !repl.execute(&line).await? {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -31,15 +31,33 @@ impl MCPServer {
         Ok(())
     }
 
+    /// Registered tools, sorted by name.
+    pub fn tools(&self) -> Vec<&Tool> {
+        let mut tools: Vec<&Tool> = self.tools.values().collect();
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+        tools
+    }
+
+    pub fn tool(&self, name: &str) -> Option<&Tool> {
+        self.tools.get(name)
+    }
+
+    /// Handle one JSON-RPC message. Notifications (`notifications/initialized`, ...) get no
+    /// response, so an empty string is returned for them.
     pub async fn handle_request(&self, input: &str) -> anyhow::Result<String> {
         debug!("Handling request: {}", input);
 
         let request: MCPRequest = serde_json::from_str(input)?;
+        self.handler.stats().record_request(&request.method);
+
+        if request.id.is_none() && (request.method.starts_with("notifications/") || request.method == "initialized") {
+            return Ok(String::new());
+        }
 
         let response = match request.method.as_str() {
             "tools/list" => {
-                let tools: Vec<&Tool> = self.tools.values().collect();
-                MCPResponse::success(request.id, json!({ "tools": tools }))
+                // Sorted so the listing is stable between runs (and replays)
+                MCPResponse::success(request.id, json!({ "tools": self.tools() }))
             }
             "tools/call" => {
                 if let Some(params) = request.params {
@@ -54,11 +72,24 @@ impl MCPServer {
                     MCPResponse::error(request.id, -32602, "Missing params for tool call")
                 }
             }
+            "resources/list" => match self.handler.resources().await {
+                Ok(resources) => MCPResponse::success(request.id, json!({ "resources": resources })),
+                Err(e) => MCPResponse::error(request.id, -32603, &format!("Listing resources failed: {}", e)),
+            },
+            "resources/read" => {
+                match request.params.as_ref().and_then(|params| params.get("uri")).and_then(Value::as_str) {
+                    Some(uri) => match self.handler.read_resource(uri).await {
+                        Ok(Some(contents)) => MCPResponse::success(request.id, json!({ "contents": [contents] })),
+                        Ok(None) => MCPResponse::error(request.id, -32002, &format!("Resource not found: {}", uri)),
+                        Err(e) => MCPResponse::error(request.id, -32603, &format!("Reading resource failed: {}", e)),
+                    },
+                    None => MCPResponse::error(request.id, -32602, "Missing uri for resources/read"),
+                }
+            }
             "initialize" => {
+                // Only capabilities whose methods are implemented are advertised
                 let capabilities = json!({
                     "tools": {},
-                    "logging": {},
-                    "prompts": {},
                     "resources": {}
                 });
                 MCPResponse::success(request.id, json!({
@@ -66,14 +97,11 @@ impl MCPServer {
                     "capabilities": capabilities,
                     "serverInfo": {
                         "name": "json-mcp-server",
-                        "version": "0.1.0"
+                        "version": env!("CARGO_PKG_VERSION")
                     }
                 }))
             }
-            "initialized" => {
-                // Notification - no response needed, but we'll send success
-                MCPResponse::success(request.id, json!({}))
-            }
+            "ping" => MCPResponse::success(request.id, json!({})),
             _ => MCPResponse::error(request.id, -32601, "Method not found"),
         };
 
@@ -84,7 +112,11 @@ impl MCPServer {
         let tool_call: ToolCall = serde_json::from_value(params)?;
 
         if !self.tools.contains_key(&tool_call.name) {
-            return Ok(ToolResult::error(format!("Unknown tool: {}", tool_call.name)));
+            let message = format!("Unknown tool: {}", tool_call.name);
+            return Ok(ToolResult::error(match self.handler.recovery_hint(&tool_call.name, &tool_call.arguments).await {
+                Some(hint) => hint.append_to(&message),
+                None => message,
+            }));
         }
 
         self.handler.call_tool(tool_call).await
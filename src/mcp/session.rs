@@ -0,0 +1,135 @@
+use crate::mcp::protocol::MCPResponse;
+use crate::mcp::server::MCPServer;
+use regex::Regex;
+use serde_json::{json, Value};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Longest value shown when describing a difference.
+const MAX_SHOWN: usize = 200;
+
+/// Appends each request/response pair to a JSON Lines file for later replay.
+pub struct SessionRecorder {
+    file: File,
+}
+
+impl SessionRecorder {
+    pub fn create(path: &Path) -> anyhow::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)
+            .map_err(|e| anyhow::anyhow!("Failed to open session file '{}': {}", path.display(), e))?;
+        Ok(Self { file })
+    }
+
+    /// Requests that are not valid JSON are kept as strings so they replay verbatim.
+    pub fn record(&mut self, request: &str, response: &str, elapsed: Duration) -> std::io::Result<()> {
+        let entry = json!({
+            "timestamp": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            "elapsed_ms": (elapsed.as_secs_f64() * 1_000_000.0).round() / 1000.0,
+            "request": serde_json::from_str::<Value>(request).unwrap_or_else(|_| json!(request)),
+            "response": serde_json::from_str::<Value>(response).unwrap_or_else(|_| json!(response)),
+        });
+        writeln!(self.file, "{}", entry)?;
+        self.file.flush()
+    }
+}
+
+/// A replayed request whose response differs from the recording.
+#[derive(Debug)]
+pub struct ReplayMismatch {
+    /// 1-based line of the session file.
+    pub line: usize,
+    pub method: String,
+    /// JSON Pointer into the response where the first difference is.
+    pub pointer: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+#[derive(Debug, Default)]
+pub struct ReplayReport {
+    pub replayed: usize,
+    pub mismatches: Vec<ReplayMismatch>,
+}
+
+/// Send every recorded request to `server` in order and compare each response with the
+/// recorded one. Timings (`elapsed_ms`) are ignored since they never repeat.
+pub async fn replay_session(server: &MCPServer, path: &Path) -> anyhow::Result<ReplayReport> {
+    let file = File::open(path)
+        .map_err(|e| anyhow::anyhow!("Failed to open session file '{}': {}", path.display(), e))?;
+    let mut report = ReplayReport::default();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: Value = serde_json::from_str(&line)
+            .map_err(|e| anyhow::anyhow!("Line {} of '{}' is not a session entry: {}", index + 1, path.display(), e))?;
+        let request = match &entry["request"] {
+            Value::String(raw) => raw.clone(),
+            request => request.to_string(),
+        };
+        // Failed requests get the same internal-error response the stdio loop sends
+        let actual = match server.handle_request(&request).await {
+            Ok(response) => serde_json::from_str(&response).unwrap_or(Value::String(response)),
+            Err(e) => serde_json::to_value(MCPResponse::error(None, -32603, &format!("Internal error: {}", e)))?,
+        };
+        report.replayed += 1;
+
+        let expected = normalize(&entry["response"]);
+        if let Some((pointer, expected, actual)) = first_difference(&expected, &normalize(&actual), String::new()) {
+            report.mismatches.push(ReplayMismatch {
+                line: index + 1,
+                method: entry["request"]["method"].as_str().unwrap_or("?").to_string(),
+                pointer,
+                expected: shorten(&expected),
+                actual: shorten(&actual),
+            });
+        }
+    }
+    Ok(report)
+}
+
+/// Blank out timings, including those inside tool output text.
+fn normalize(value: &Value) -> Value {
+    static ELAPSED: OnceLock<Regex> = OnceLock::new();
+    let elapsed = ELAPSED.get_or_init(|| Regex::new(r#""elapsed_ms":\s*[0-9.eE+-]+"#).expect("valid regex"));
+    match value {
+        Value::String(text) => Value::String(elapsed.replace_all(text, "\"elapsed_ms\": 0").into_owned()),
+        Value::Array(items) => Value::Array(items.iter().map(normalize).collect()),
+        Value::Object(map) => Value::Object(map.iter()
+            .map(|(key, value)| (key.clone(), if key == "elapsed_ms" { json!(0) } else { normalize(value) }))
+            .collect()),
+        other => other.clone(),
+    }
+}
+
+fn first_difference(expected: &Value, actual: &Value, pointer: String) -> Option<(String, Value, Value)> {
+    match (expected, actual) {
+        (Value::Object(a), Value::Object(b)) => {
+            for key in a.keys().chain(b.keys().filter(|k| !a.contains_key(*k))) {
+                let child = format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"));
+                let (x, y) = (a.get(key).unwrap_or(&Value::Null), b.get(key).unwrap_or(&Value::Null));
+                if let Some(found) = first_difference(x, y, child) {
+                    return Some(found);
+                }
+            }
+            None
+        }
+        (Value::Array(a), Value::Array(b)) if a.len() == b.len() => a.iter().zip(b)
+            .enumerate()
+            .find_map(|(i, (x, y))| first_difference(x, y, format!("{}/{}", pointer, i))),
+        _ if expected == actual => None,
+        _ => Some((pointer, expected.clone(), actual.clone())),
+    }
+}
+
+fn shorten(value: &Value) -> String {
+    let text = value.to_string();
+    match text.char_indices().nth(MAX_SHOWN) {
+        Some((cut, _)) => format!("{}...", &text[..cut]),
+        None => text,
+    }
+}
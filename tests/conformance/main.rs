@@ -0,0 +1,185 @@
+//! MCP conformance tests: every response the server sends is validated against the
+//! official MCP JSON schema definitions in `mcp-schema.json`.
+//!
+//! Run on their own with `cargo test --test conformance`.
+
+mod validator;
+
+use json_mcp_server::json_tools::JsonToolsHandler;
+use json_mcp_server::mcp::server::MCPServer;
+use serde_json::{json, Value};
+use validator::McpSchema;
+
+async fn server() -> MCPServer {
+    let mut server = MCPServer::new(JsonToolsHandler::new());
+    server.register_tools().await.unwrap();
+    server
+}
+
+/// Send one request and parse the response line.
+async fn exchange(server: &MCPServer, request: &Value) -> Value {
+    let response = server.handle_request(&request.to_string()).await.unwrap();
+    serde_json::from_str(&response).unwrap_or_else(|e| panic!("response to {} is not JSON ({}): {}", request, e, response))
+}
+
+/// Check a successful response's envelope and its result against `result_definition`.
+fn assert_result(schema: &McpSchema, request: &Value, response: &Value, result_definition: &str) {
+    let mut errors = schema.validate("JSONRPCResponse", response);
+    errors.extend(schema.validate(result_definition, &response["result"]).into_iter().map(|e| format!("/result{}", e)));
+    assert!(errors.is_empty(), "{} response to {} does not conform:\n{}\n{}", result_definition, request, errors.join("\n"), response);
+    assert_eq!(response["id"], request["id"], "response id must echo the request id");
+}
+
+fn assert_error(schema: &McpSchema, request: &Value, response: &Value, code: i64) {
+    let errors = schema.validate("JSONRPCError", response);
+    assert!(errors.is_empty(), "error response to {} does not conform:\n{}\n{}", request, errors.join("\n"), response);
+    assert_eq!(response["error"]["code"], json!(code), "{}", response);
+    assert_eq!(response["id"], request["id"]);
+}
+
+#[tokio::test]
+async fn test_lifecycle_methods_conform() {
+    let schema = McpSchema::load();
+    let server = server().await;
+
+    let initialize = json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {
+        "protocolVersion": "2024-11-05",
+        "capabilities": {},
+        "clientInfo": {"name": "conformance", "version": "1.0"}
+    }});
+    let response = exchange(&server, &initialize).await;
+    assert_result(&schema, &initialize, &response, "InitializeResult");
+    assert_eq!(response["result"]["protocolVersion"], "2024-11-05");
+
+    // Notifications must not be answered
+    let initialized = json!({"jsonrpc": "2.0", "method": "notifications/initialized"});
+    assert_eq!(server.handle_request(&initialized.to_string()).await.unwrap(), "");
+
+    let ping = json!({"jsonrpc": "2.0", "id": "ping-1", "method": "ping"});
+    let response = exchange(&server, &ping).await;
+    assert_result(&schema, &ping, &response, "EmptyResult");
+
+    let unknown = json!({"jsonrpc": "2.0", "id": 2, "method": "no/such/method"});
+    let response = exchange(&server, &unknown).await;
+    assert_error(&schema, &unknown, &response, -32601);
+}
+
+#[tokio::test]
+async fn test_tool_methods_conform() {
+    let schema = McpSchema::load();
+    let server = server().await;
+
+    let list = json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"});
+    let response = exchange(&server, &list).await;
+    assert_result(&schema, &list, &response, "ListToolsResult");
+    let tools = response["result"]["tools"].as_array().unwrap();
+    assert!(tools.len() > 10);
+
+    let calls = [
+        json!({"name": "json-help"}),
+        json!({"name": "json-help", "arguments": {"topic": "tools"}}),
+        json!({"name": "json-read", "arguments": {"file_path": "/nonexistent/conformance.json"}}),
+        json!({"name": "no-such-tool", "arguments": {}}),
+    ];
+    for (id, params) in calls.into_iter().enumerate() {
+        let call = json!({"jsonrpc": "2.0", "id": id + 10, "method": "tools/call", "params": params});
+        let response = exchange(&server, &call).await;
+        if response.get("error").is_some() {
+            assert_error(&schema, &call, &response, -32603);
+        } else {
+            assert_result(&schema, &call, &response, "CallToolResult");
+        }
+    }
+
+    let missing = json!({"jsonrpc": "2.0", "id": 20, "method": "tools/call"});
+    let response = exchange(&server, &missing).await;
+    assert_error(&schema, &missing, &response, -32602);
+}
+
+#[tokio::test]
+async fn test_tool_failures_set_is_error() {
+    let server = server().await;
+    let call = json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": {"name": "no-such-tool", "arguments": {}}});
+    let response = exchange(&server, &call).await;
+    assert_eq!(response["result"]["isError"], json!(true), "{}", response);
+}
+
+#[tokio::test]
+async fn test_tool_input_schemas_are_consistent() {
+    let server = server().await;
+    for tool in server.tools() {
+        let schema = &tool.input_schema;
+        let properties = schema["properties"].as_object();
+        for name in schema["required"].as_array().into_iter().flatten() {
+            let name = name.as_str().unwrap();
+            assert!(properties.is_some_and(|p| p.contains_key(name)), "{} requires undeclared property '{}'", tool.name, name);
+        }
+        for (name, property) in properties.into_iter().flatten() {
+            let types: Vec<&str> = match &property["type"] {
+                Value::String(kind) => vec![kind.as_str()],
+                Value::Array(kinds) => kinds.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            for kind in types {
+                assert!(
+                    ["object", "array", "string", "number", "integer", "boolean", "null"].contains(&kind),
+                    "{}.{} has unknown type '{}'", tool.name, name, kind
+                );
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_advertised_capabilities_are_served() {
+    let schema = McpSchema::load();
+    let server = server().await;
+    let initialize = json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}});
+    let capabilities = exchange(&server, &initialize).await["result"]["capabilities"].clone();
+
+    for (capability, definition) in [("tools", "ListToolsResult"), ("resources", "ListResourcesResult"), ("prompts", "ListPromptsResult")] {
+        if capabilities.get(capability).is_none() {
+            continue;
+        }
+        let list = json!({"jsonrpc": "2.0", "id": 2, "method": format!("{}/list", capability)});
+        let response = exchange(&server, &list).await;
+        assert!(response.get("error").is_none(), "capability '{}' is advertised but {}/list fails: {}", capability, capability, response);
+        assert_result(&schema, &list, &response, definition);
+    }
+}
+
+#[test]
+fn test_validator_catches_snake_case_drift() {
+    let schema = McpSchema::load();
+    let drifted = json!({"tools": [{"name": "json-read", "description": "Read", "input_schema": {"type": "object"}}]});
+    let errors = schema.validate("ListToolsResult", &drifted);
+    assert!(errors.iter().any(|e| e.contains("missing required property 'inputSchema'")), "{:?}", errors);
+    assert!(errors.iter().any(|e| e.contains("/tools/0/input_schema: snake_case spelling of 'inputSchema'")), "{:?}", errors);
+
+    let result = json!({"content": [{"type": "text", "text": "x"}], "is_error": true});
+    assert_eq!(schema.validate("CallToolResult", &result), vec!["/is_error: snake_case spelling of 'isError'".to_string()]);
+}
+
+#[tokio::test]
+async fn test_resource_methods_conform() {
+    let schema = McpSchema::load();
+    let server = server().await;
+
+    let list = json!({"jsonrpc": "2.0", "id": 1, "method": "resources/list"});
+    let response = exchange(&server, &list).await;
+    assert_result(&schema, &list, &response, "ListResourcesResult");
+
+    for (id, uri) in ["help://overview", "help://json-read", "example://merge-write"].into_iter().enumerate() {
+        let read = json!({"jsonrpc": "2.0", "id": id + 10, "method": "resources/read", "params": {"uri": uri}});
+        let response = exchange(&server, &read).await;
+        assert_result(&schema, &read, &response, "ReadResourceResult");
+    }
+
+    let unknown = json!({"jsonrpc": "2.0", "id": 20, "method": "resources/read", "params": {"uri": "help://no-such-topic"}});
+    let response = exchange(&server, &unknown).await;
+    assert_error(&schema, &unknown, &response, -32002);
+
+    let missing = json!({"jsonrpc": "2.0", "id": 21, "method": "resources/read"});
+    let response = exchange(&server, &missing).await;
+    assert_error(&schema, &missing, &response, -32602);
+}
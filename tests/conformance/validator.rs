@@ -0,0 +1,125 @@
+use serde_json::Value;
+
+/// The subset of JSON Schema draft-07 used by the MCP schema: `$ref` to definitions,
+/// `type`, `const`, `enum`, `required`, `properties`, `additionalProperties`, `items`,
+/// `anyOf`, `minimum` and `maximum`.
+pub struct McpSchema {
+    root: Value,
+}
+
+impl McpSchema {
+    pub fn load() -> Self {
+        let text = include_str!("mcp-schema.json");
+        Self { root: serde_json::from_str(text).expect("mcp-schema.json is valid JSON") }
+    }
+
+    /// Validate `value` against `#/definitions/<definition>`, returning one message per
+    /// violation, each prefixed with the JSON Pointer of the offending value.
+    pub fn validate(&self, definition: &str, value: &Value) -> Vec<String> {
+        let mut errors = Vec::new();
+        let schema = self.definition(definition);
+        self.check(schema, value, "", &mut errors);
+        errors
+    }
+
+    fn definition(&self, name: &str) -> &Value {
+        self.root["definitions"].get(name).unwrap_or_else(|| panic!("mcp-schema.json has no definition {}", name))
+    }
+
+    fn check(&self, schema: &Value, value: &Value, at: &str, errors: &mut Vec<String>) {
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            let name = reference.strip_prefix("#/definitions/").expect("local $ref");
+            return self.check(self.definition(name), value, at, errors);
+        }
+        if let Some(variants) = schema.get("anyOf").and_then(Value::as_array) {
+            let matched = variants.iter().any(|variant| {
+                let mut scratch = Vec::new();
+                self.check(variant, value, at, &mut scratch);
+                scratch.is_empty()
+            });
+            if !matched {
+                errors.push(format!("{}: matches none of the anyOf alternatives", at));
+            }
+        }
+        if let Some(types) = schema.get("type") {
+            let allowed: Vec<&str> = match types {
+                Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+                other => other.as_str().into_iter().collect(),
+            };
+            if !allowed.iter().any(|kind| has_type(value, kind)) {
+                errors.push(format!("{}: expected {}, found {}", at, allowed.join(" or "), value));
+                return;
+            }
+        }
+        if let Some(expected) = schema.get("const") {
+            if value != expected {
+                errors.push(format!("{}: expected {}, found {}", at, expected, value));
+            }
+        }
+        if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+            if !options.contains(value) {
+                errors.push(format!("{}: {} is not one of {}", at, value, Value::Array(options.clone())));
+            }
+        }
+        if let Some(number) = value.as_f64() {
+            if schema.get("minimum").and_then(Value::as_f64).is_some_and(|min| number < min)
+                || schema.get("maximum").and_then(Value::as_f64).is_some_and(|max| number > max)
+            {
+                errors.push(format!("{}: {} is out of range", at, number));
+            }
+        }
+        if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+            for (i, item) in array.iter().enumerate() {
+                self.check(items, item, &format!("{}/{}", at, i), errors);
+            }
+        }
+        if let Some(object) = value.as_object() {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for name in schema.get("required").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str) {
+                if !object.contains_key(name) {
+                    errors.push(format!("{}: missing required property '{}'", at, name));
+                }
+            }
+            for (key, child) in object {
+                let path = format!("{}/{}", at, key);
+                match (properties.and_then(|p| p.get(key)), schema.get("additionalProperties")) {
+                    (Some(property), _) => self.check(property, child, &path, errors),
+                    (None, Some(Value::Bool(false))) => errors.push(format!("{}: property not allowed", path)),
+                    (None, Some(additional)) if additional.is_object() => self.check(additional, child, &path, errors),
+                    _ => {}
+                }
+                // Unknown keys are allowed, but a snake_case spelling of a declared camelCase
+                // property is a serialization bug that clients silently ignore.
+                if let Some(camel) = properties.and_then(|p| p.keys().find(|name| *name != key && snake_case(name) == *key)) {
+                    errors.push(format!("{}: snake_case spelling of '{}'", path, camel));
+                }
+            }
+        }
+    }
+}
+
+fn has_type(value: &Value, kind: &str) -> bool {
+    match kind {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        _ => panic!("unsupported JSON Schema type {}", kind),
+    }
+}
+
+fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            out.push('_');
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}